@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BenchmarkPoint } from "./BenchmarkPoint";
+
+export interface BenchmarkHistory { benchmark: string, source: string | null, points: Array<BenchmarkPoint>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CommandError = { "code": "NOT_FOUND", resource: string, message: string, } | { "code": "STALE_DATA", message: string, } | { "code": "EXTERNAL_API", provider: string, status: number | null, message: string, } | { "code": "TOKEN_EXPIRED", provider: string, message: string, } | { "code": "DATABASE_BUSY", message: string, } | { "code": "DATABASE", message: string, } | { "code": "VALIDATION", field: string, message: string, } | { "code": "READ_ONLY", message: string, } | { "code": "INTERNAL", message: string, };
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CoverageGranularity } from "./CoverageGranularity";
+import type { PeriodCoverageTotals } from "./PeriodCoverageTotals";
+import type { StockCoverageRow } from "./StockCoverageRow";
+
+export interface CoverageHeatmap { target_date: string, granularity: CoverageGranularity, periods: Array<string>, rows: Array<StockCoverageRow>, totals: Array<PeriodCoverageTotals>, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScreenFilter } from "./ScreenFilter";
+import type { SortSpec } from "./SortSpec";
+
+export interface CustomScreen { name: string, filters: Array<ScreenFilter>, sort: SortSpec | null, limit: number | null, }
\ No newline at end of file
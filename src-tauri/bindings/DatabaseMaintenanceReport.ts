@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TableSize } from "./TableSize";
+
+export interface DatabaseMaintenanceReport { integrity_ok: boolean, integrity_messages: Array<string>, vacuumed: boolean, size_bytes_before: bigint, size_bytes_after: bigint, free_bytes: bigint, tables: Array<TableSize>, }
\ No newline at end of file
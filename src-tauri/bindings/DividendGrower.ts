@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DividendYear } from "./DividendYear";
+
+export interface DividendGrower { stock_id: bigint, symbol: string, company_name: string, sector: string | null, consecutive_increase_years: number, latest: DividendYear, dividend_yield: number | null, }
\ No newline at end of file
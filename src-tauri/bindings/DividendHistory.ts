@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DividendRecord } from "./DividendRecord";
+import type { DividendYear } from "./DividendYear";
+
+export interface DividendHistory { stock_id: bigint, records: Array<DividendRecord>, years: Array<DividendYear>, consecutive_increase_years: number, }
\ No newline at end of file
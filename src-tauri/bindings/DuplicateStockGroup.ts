@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DuplicateMatch } from "./DuplicateMatch";
+import type { DuplicateStock } from "./DuplicateStock";
+
+export interface DuplicateStockGroup { match_kind: DuplicateMatch, key: string, stocks: Array<DuplicateStock>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface DurationRange { min_minutes: number, expected_minutes: number, max_minutes: number, stale_stocks: bigint, total_stocks: bigint, based_on_runs: number, basis: string, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface FcfYieldStock { stock_id: bigint, symbol: string, sector: string | null, date: string, market_cap: number, free_cash_flow: number, fcf_yield: number, positive_fcf_years: number, }
\ No newline at end of file
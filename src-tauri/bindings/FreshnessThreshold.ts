@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface FreshnessThreshold { current_days: bigint, stale_days: bigint, }
\ No newline at end of file
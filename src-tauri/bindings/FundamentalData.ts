@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface FundamentalData { symbol: string, pe_ratio: number | null, pe_ratio_forward: number | null, market_cap: number | null, dividend_yield: number | null, dividend_per_share: number | null, eps: number | null, eps_forward: number | null, beta: number | null, week_52_high: number | null, week_52_low: number | null, pb_ratio: number | null, ps_ratio: number | null, shares_outstanding: number | null, float_shares: number | null, revenue_ttm: number | null, profit_margin: number | null, operating_margin: number | null, return_on_equity: number | null, return_on_assets: number | null, debt_to_equity: number | null, }
\ No newline at end of file
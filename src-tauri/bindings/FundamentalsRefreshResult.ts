@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RefreshRunError } from "./RefreshRunError";
+
+export interface FundamentalsRefreshResult { stocks_processed: bigint, records_stored: bigint, errors: Array<RefreshRunError>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface GrahamScreeningCriteria { max_pe_ratio: number | null, max_pb_ratio: number | null, min_current_ratio: number | null, min_earnings_stability_years: number | null, use_combined_pe_pb: boolean | null, max_pe_times_pb: number | null, min_market_cap: number | null, min_avg_daily_volume: number | null, sectors: Array<string> | null, include_negative_earnings: boolean | null, }
\ No newline at end of file
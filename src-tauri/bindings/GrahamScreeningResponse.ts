@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExclusionCount } from "./ExclusionCount";
+import type { GrahamScreeningResult } from "./GrahamScreeningResult";
+import type { ScreenExclusion } from "./ScreenExclusion";
+
+export interface GrahamScreeningResponse { results: Array<GrahamScreeningResult>, excluded: Array<ScreenExclusion>, exclusions: Array<ExclusionCount>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RatioNote } from "./RatioNote";
+
+export interface GrahamScreeningResult { stock_id: bigint, symbol: string, sector: string | null, current_price: number | null, market_cap: number | null, pe_ratio: number | null, pb_ratio: number | null, pe_times_pb: number | null, current_ratio: number | null, positive_earnings_years: number, latest_net_income: number | null, pe_ratio_note: RatioNote | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface GrossProfitabilityYear { fiscal_year: number, report_date: string, gross_profit: number | null, gross_profit_derived: boolean, total_assets: number | null, gross_profitability: number | null, }
\ No newline at end of file
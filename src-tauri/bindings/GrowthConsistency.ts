@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AnnualRevenueGrowth } from "./AnnualRevenueGrowth";
+import type { SkippedGrowthPeriod } from "./SkippedGrowthPeriod";
+
+export interface GrowthConsistency { stock_id: bigint, years: number, consecutive_growth_years: number, growth_rate_stddev: number | null, annual_growth_rates: Array<AnnualRevenueGrowth>, skipped_periods: Array<SkippedGrowthPeriod>, }
\ No newline at end of file
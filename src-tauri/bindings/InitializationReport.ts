@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InitializationAction } from "./InitializationAction";
+
+export interface InitializationReport { schema_ready: boolean, missing_tables: Array<string>, stock_count: bigint, sp500_count: bigint, has_daily_prices: boolean, has_sec_filings: boolean, sp500_last_updated: string | null, next_actions: Array<InitializationAction>, ready: boolean, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IntradayInterval } from "./IntradayInterval";
+
+export interface IntradayCollectionResult { trading_date: string, interval: IntradayInterval, stocks_collected: number, bars_stored: bigint, failed_symbols: Array<string>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface IntradayDownsampleResult { minute_bars_removed: bigint, bars_written: bigint, }
\ No newline at end of file
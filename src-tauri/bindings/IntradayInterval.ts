@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type IntradayInterval = "1min" | "5min" | "15min" | "30min";
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LeverageYear } from "./LeverageYear";
+
+export interface LeverageMetrics { stock_id: bigint, latest: LeverageYear | null, history: Array<LeverageYear>, debt_to_equity_change: number | null, net_debt_change: number | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type LeverageNote = "missing_data" | "non_positive_equity" | "negative_ebitda" | "no_interest_expense" | "near_zero_denominator";
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface LiquidityYear { fiscal_year: number, report_date: string, current_assets: number | null, current_liabilities: number | null, inventory: number | null, current_ratio: number | null, quick_ratio: number | null, inventory_missing: boolean, }
\ No newline at end of file
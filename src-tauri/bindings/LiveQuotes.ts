@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QuoteFailure } from "./QuoteFailure";
+import type { RealTimeQuote } from "./RealTimeQuote";
+
+export interface LiveQuotes { quotes: Array<RealTimeQuote>, failures: Array<QuoteFailure>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface LowLeverageCriteria { max_debt_to_equity: number | null, max_net_debt_to_ebitda: number | null, min_interest_coverage: number | null, limit: number | null, }
\ No newline at end of file
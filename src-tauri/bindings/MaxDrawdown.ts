@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface MaxDrawdown { stock_id: bigint, start_date: string, end_date: string, max_drawdown_pct: number, peak_date: string, peak_price: number, trough_date: string, trough_price: number, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ValuationMetric } from "./ValuationMetric";
+
+export interface MetricDistribution { metric: ValuationMetric, bin_edges: Array<number>, counts: Array<bigint>, median: number | null, sample_size: bigint, symbol: string | null, symbol_value: number | null, symbol_bin: bigint | null, }
\ No newline at end of file
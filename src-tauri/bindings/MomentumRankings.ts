@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MomentumRanking } from "./MomentumRanking";
+
+export interface MomentumRankings { as_of: string, rankings: Array<MomentumRanking>, excluded_insufficient_history: number, excluded_below_min_price: number, }
\ No newline at end of file
@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RatioNote } from "./RatioNote";
 
-export interface OShaughnessyValueResult { stock_id: bigint, symbol: string, sector: string | null, current_price: number | null, market_cap: number | null, enterprise_value: number | null, ps_ratio: number | null, evs_ratio: number | null, pe_ratio: number | null, pb_ratio: number | null, ev_ebitda_ratio: number | null, shareholder_yield: number | null, data_completeness_score: number, composite_score: number, composite_percentile: number, overall_rank: bigint, passes_screening: number, ps_rank: bigint | null, evs_rank: bigint | null, pe_rank: bigint | null, pb_rank: bigint | null, ebitda_rank: bigint | null, yield_rank: bigint | null, metrics_available: number, }
\ No newline at end of file
+export interface OShaughnessyValueResult { stock_id: bigint, symbol: string, sector: string | null, current_price: number | null, market_cap: number | null, enterprise_value: number | null, ps_ratio: number | null, evs_ratio: number | null, pe_ratio: number | null, pb_ratio: number | null, ev_ebitda_ratio: number | null, shareholder_yield: number | null, dividend_yield: number | null, buyback_yield: number | null, pe_ratio_note: RatioNote | null, pb_ratio_note: RatioNote | null, data_completeness_score: number, composite_score: number, composite_percentile: number, overall_rank: bigint, passes_screening: number, ps_rank: bigint | null, evs_rank: bigint | null, pe_rank: bigint | null, pb_rank: bigint | null, ebitda_rank: bigint | null, yield_rank: bigint | null, metrics_available: number, pe_percentile: number | null, pb_percentile: number | null, ps_percentile: number | null, ev_ebitda_percentile: number | null, yield_percentile: number | null, missing_metrics_note: string | null, }
\ No newline at end of file
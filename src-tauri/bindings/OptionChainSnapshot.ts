@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface OptionChainSnapshot { stock_id: bigint, symbol: string, snapshot_time: string, expirations: Array<string>, contracts_stored: bigint, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface PeriodCoverageTotals { period: string, full: number, partial: number, missing: number, not_listed: number, }
\ No newline at end of file
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export interface PiotoskiFScoreResult { stock_id: bigint, symbol: string, sector: string | null, current_net_income: number | null, f_score_complete: number, data_completeness_score: number, criterion_positive_net_income: number, criterion_positive_operating_cash_flow: number, criterion_improving_roa: number, criterion_cash_flow_quality: number, criterion_decreasing_debt_ratio: number, criterion_improving_current_ratio: number, criterion_no_dilution: number, criterion_improving_net_margin: number, criterion_improving_asset_turnover: number, current_roa: number | null, current_debt_ratio: number | null, current_current_ratio: number | null, current_net_margin: number | null, current_asset_turnover: number | null, current_operating_cash_flow: number | null, pb_ratio: number | null, criteria_met: number, passes_screening: number, }
\ No newline at end of file
+export interface PiotoskiFScoreResult { stock_id: bigint, symbol: string, sector: string | null, market_cap: number | null, current_net_income: number | null, f_score_complete: number, data_completeness_score: number, criterion_positive_net_income: number, criterion_positive_operating_cash_flow: number, criterion_improving_roa: number, criterion_cash_flow_quality: number, criterion_decreasing_debt_ratio: number, criterion_improving_current_ratio: number, criterion_no_dilution: number, criterion_improving_net_margin: number, criterion_improving_asset_turnover: number, current_roa: number | null, current_debt_ratio: number | null, current_current_ratio: number | null, current_net_margin: number | null, current_asset_turnover: number | null, current_operating_cash_flow: number | null, pb_ratio: number | null, criteria_met: number, passes_screening: number, }
\ No newline at end of file
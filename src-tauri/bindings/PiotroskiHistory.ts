@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PiotroskiYear } from "./PiotroskiYear";
+
+export interface PiotroskiHistory { stock_id: bigint, symbol: string, years: Array<PiotroskiYear>, }
\ No newline at end of file
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export interface PiotroskilScreeningCriteria { min_f_score: number | null, min_data_completeness: number | null, sectors: Array<string> | null, min_market_cap: number | null, passes_screening_only: boolean | null, }
\ No newline at end of file
+export interface PiotroskilScreeningCriteria { min_f_score: number | null, min_data_completeness: number | null, sectors: Array<string> | null, min_market_cap: number | null, min_avg_daily_volume: number | null, passes_screening_only: boolean | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PriceConflictKind } from "./PriceConflictKind";
+
+export interface PriceConflict { stock_id: bigint, symbol: string, date: string, kind: PriceConflictKind, stored_value: number | null, expected_value: number | null, detail: string, }
\ No newline at end of file
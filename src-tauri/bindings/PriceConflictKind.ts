@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type PriceConflictKind = "duplicate_day" | "inconsistent_range" | "market_cap_mismatch" | "valuation_price_mismatch";
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type PsSortKey = "ps_asc" | "ps_desc" | "market_cap_asc" | "market_cap_desc" | "revenue_growth_asc" | "revenue_growth_desc";
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface QualityCompounderCriteria { min_roic: number | null, years: number | null, max_margin_decline: number | null, limit: number | null, }
\ No newline at end of file
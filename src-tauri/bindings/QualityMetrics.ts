@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QualityYear } from "./QualityYear";
+
+export interface QualityMetrics { stock_id: bigint, latest: QualityYear | null, history: Array<QualityYear>, }
\ No newline at end of file
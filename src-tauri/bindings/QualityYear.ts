@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface QualityYear { fiscal_year: number, report_date: string, gross_margin: number | null, operating_margin: number | null, asset_turnover: number | null, effective_tax_rate: number | null, nopat: number | null, invested_capital: number | null, roic: number | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RawCompanyFacts { symbol: string, cik: string, total_bytes: bigint, truncated: boolean, json: string, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RefreshProgressEvent { session_id: string, stage: string, percent: number, current_symbol: string | null, eta_seconds: bigint | null, }
\ No newline at end of file
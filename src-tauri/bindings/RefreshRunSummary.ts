@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RefreshRunSummary { run_id: string, mode: string, requested_sources: Array<string>, initiated_by: string, started_at: string, ended_at: string | null, duration_seconds: bigint | null, status: string, stocks_processed: bigint, filings_stored: bigint, prices_inserted: bigint, errors_count: bigint, error_message: string | null, }
\ No newline at end of file
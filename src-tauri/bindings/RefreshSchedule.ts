@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RefreshSchedule { paused: boolean, market_refresh_time: string, financials_refresh_day: string, }
\ No newline at end of file
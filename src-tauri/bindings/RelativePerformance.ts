@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RelativeStrengthPoint } from "./RelativeStrengthPoint";
+
+export interface RelativePerformance { stock_id: bigint, benchmark: string, base_date: string | null, points: Array<RelativeStrengthPoint>, }
\ No newline at end of file
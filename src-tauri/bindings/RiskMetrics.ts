@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RiskMetrics { stock_id: bigint, period_days: bigint, risk_free_annual: number, observations: number, sharpe_ratio: number | null, sortino_ratio: number | null, benchmark: string | null, beta: number | null, }
\ No newline at end of file
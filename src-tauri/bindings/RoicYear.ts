@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RoicYear { fiscal_year: number, report_date: string, operating_income: number | null, reported_tax_rate: number | null, effective_tax_rate: number | null, nopat: number | null, total_debt: number | null, total_equity: number | null, cash: number | null, invested_capital: number | null, roic: number | null, }
\ No newline at end of file
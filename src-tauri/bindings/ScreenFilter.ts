@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FilterOp } from "./FilterOp";
+import type { FilterValue } from "./FilterValue";
+import type { ScreenMetric } from "./ScreenMetric";
+
+export interface ScreenFilter { metric: ScreenMetric, op: FilterOp, value: FilterValue, }
\ No newline at end of file
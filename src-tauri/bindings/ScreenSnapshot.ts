@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScreenType } from "./ScreenType";
+
+export interface ScreenSnapshot { id: bigint, screen_type: ScreenType, criteria: Record<string, unknown>, result_count: number, run_at: string, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ScreeningHistoryPruneReport { dry_run: boolean, snapshots_removed: bigint, items_removed: bigint, }
\ No newline at end of file
@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScreenReadiness } from "./ScreenReadiness";
 
-export interface ScreeningReadiness { valuation_analysis: boolean, blocking_issues: Array<string>, }
\ No newline at end of file
+export interface ScreeningReadiness { valuation_analysis: ScreenReadiness, piotroski_screening: ScreenReadiness, oshaughnessy_screening: ScreenReadiness, graham_screening: ScreenReadiness, ps_screening: ScreenReadiness, blocking_issues: Array<string>, }
\ No newline at end of file
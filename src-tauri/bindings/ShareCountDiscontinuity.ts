@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ShareCountDiscontinuity { stock_id: bigint, from_date: string, to_date: string, from_shares: number, to_shares: number, change_pct: number, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ShareholderYield { stock_id: bigint, symbol: string, fiscal_year: number | null, report_date: string | null, market_cap: number | null, dividends_paid: number | null, gross_buybacks: number | null, dividend_yield: number | null, buyback_yield: number | null, shareholder_yield: number | null, }
\ No newline at end of file
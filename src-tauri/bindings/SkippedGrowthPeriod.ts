@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface SkippedGrowthPeriod { fiscal_year: number, report_date: string, period_days: number | null, prior_period_days: number | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface StaleStock { stock_id: bigint, symbol: string, latest_filing_date: string | null, latest_price_date: string | null, filing_age_days: bigint | null, price_age_days: bigint | null, reasons: Array<string>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface StockArchiveResult { stock_id: bigint, symbol: string, status: string | null, archived_at: string | null, rows_moved: bigint, rows_deleted: bigint, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CoverageFlag } from "./CoverageFlag";
+
+export interface StockCoverageRow { stock_id: bigint, symbol: string, cells: Array<CoverageFlag>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type StockFilter = { "kind": "all" } | { "kind": "sp500" } | { "kind": "symbols", "value": Array<string> } | { "kind": "tag", "value": string };
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface StockSummary { stock_id: bigint, symbol: string, company_name: string, sector: string | null, latest_date: string | null, latest_close: number | null, previous_close: number | null, day_change: number | null, day_change_pct: number | null, market_cap: number | null, pe_ratio: number | null, ps_ratio: number | null, high_52_week: number | null, low_52_week: number | null, }
\ No newline at end of file
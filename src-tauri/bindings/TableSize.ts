@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface TableSize { name: string, row_count: bigint, size_bytes: bigint | null, }
\ No newline at end of file
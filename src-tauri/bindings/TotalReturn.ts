@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface TotalReturn { symbol: string, start_date: string, end_date: string, start_price: number, end_price: number, price_return_pct: number, total_return_pct: number, annualized_price_return_pct: number | null, annualized_total_return_pct: number | null, dividends_reinvested: number, }
\ No newline at end of file
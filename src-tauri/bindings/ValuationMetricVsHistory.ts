@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ValuationMetricVsHistory { metric: string, current: number | null, median_5y: number | null, premium_pct: number | null, data_points: number, note: string | null, }
\ No newline at end of file
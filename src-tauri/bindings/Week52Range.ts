@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface Week52Range { stock_id: bigint, as_of_date: string, window_start: string, trading_days: number, high_52_week: number | null, low_52_week: number | null, latest_close: number | null, pct_from_high: number | null, pct_from_low: number | null, is_complete: boolean, limited_history: boolean, note: string | null, }
\ No newline at end of file
@@ -0,0 +1,15 @@
+-- Revert inserted_at tracking on filings and statements
+
+DROP INDEX IF EXISTS idx_cash_flow_statements_inserted_at;
+DROP INDEX IF EXISTS idx_balance_sheets_inserted_at;
+DROP INDEX IF EXISTS idx_income_statements_inserted_at;
+DROP INDEX IF EXISTS idx_sec_filings_inserted_at;
+
+DROP TRIGGER IF EXISTS cash_flow_statements_set_inserted_at;
+DROP TRIGGER IF EXISTS balance_sheets_set_inserted_at;
+DROP TRIGGER IF EXISTS income_statements_set_inserted_at;
+DROP TRIGGER IF EXISTS sec_filings_set_inserted_at;
+
+-- The inserted_at columns are left in place: SQLite's DROP COLUMN re-validates every view,
+-- and piotroski_f_score_complete references columns that no longer exist. The columns are
+-- nullable and unused once the triggers are gone.
@@ -0,0 +1,41 @@
+-- Record when filings and statement rows were stored so we can report
+-- which companies got new fundamentals in the last refresh
+
+ALTER TABLE sec_filings ADD COLUMN inserted_at DATETIME;
+ALTER TABLE income_statements ADD COLUMN inserted_at DATETIME;
+ALTER TABLE balance_sheets ADD COLUMN inserted_at DATETIME;
+ALTER TABLE cash_flow_statements ADD COLUMN inserted_at DATETIME;
+
+-- Existing filings: created_at is the best timestamp we have
+UPDATE sec_filings SET inserted_at = created_at;
+
+-- SQLite can't ADD COLUMN with a CURRENT_TIMESTAMP default, so new rows are stamped
+-- by triggers (millisecond precision, UTC) regardless of which code path inserts them
+CREATE TRIGGER sec_filings_set_inserted_at AFTER INSERT ON sec_filings
+WHEN NEW.inserted_at IS NULL
+BEGIN
+    UPDATE sec_filings SET inserted_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER income_statements_set_inserted_at AFTER INSERT ON income_statements
+WHEN NEW.inserted_at IS NULL
+BEGIN
+    UPDATE income_statements SET inserted_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER balance_sheets_set_inserted_at AFTER INSERT ON balance_sheets
+WHEN NEW.inserted_at IS NULL
+BEGIN
+    UPDATE balance_sheets SET inserted_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER cash_flow_statements_set_inserted_at AFTER INSERT ON cash_flow_statements
+WHEN NEW.inserted_at IS NULL
+BEGIN
+    UPDATE cash_flow_statements SET inserted_at = strftime('%Y-%m-%d %H:%M:%f', 'now') WHERE id = NEW.id;
+END;
+
+CREATE INDEX idx_sec_filings_inserted_at ON sec_filings(inserted_at);
+CREATE INDEX idx_income_statements_inserted_at ON income_statements(inserted_at);
+CREATE INDEX idx_balance_sheets_inserted_at ON balance_sheets(inserted_at);
+CREATE INDEX idx_cash_flow_statements_inserted_at ON cash_flow_statements(inserted_at);
@@ -0,0 +1,5 @@
+-- Remove the stored daily valuation ratio series
+
+DROP INDEX IF EXISTS idx_daily_valuation_ratios_ps;
+DROP INDEX IF EXISTS idx_daily_valuation_ratios_stock_date;
+DROP TABLE IF EXISTS daily_valuation_ratios;
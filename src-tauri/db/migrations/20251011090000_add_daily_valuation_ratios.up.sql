@@ -0,0 +1,26 @@
+-- Stored daily P/S, P/E and EV/S series for fast charting and the P/S screener.
+-- Each trading day pairs that day's market cap with the annual statements
+-- whose report_date had already passed (written by backfill_daily_ratios)
+
+CREATE TABLE IF NOT EXISTS daily_valuation_ratios (
+    id INTEGER PRIMARY KEY,
+    stock_id INTEGER NOT NULL,
+    date DATE NOT NULL,
+    price REAL,
+    market_cap REAL,
+    enterprise_value REAL,
+    shares_outstanding REAL,
+    revenue_ttm REAL,
+    net_income_ttm REAL,
+    ps_ratio_ttm REAL,
+    pe_ratio_ttm REAL,
+    evs_ratio_ttm REAL,
+    data_completeness_score INTEGER NOT NULL DEFAULT 0,
+    last_financial_update DATE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id),
+    UNIQUE(stock_id, date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_daily_valuation_ratios_stock_date ON daily_valuation_ratios(stock_id, date);
+CREATE INDEX IF NOT EXISTS idx_daily_valuation_ratios_ps ON daily_valuation_ratios(ps_ratio_ttm);
@@ -0,0 +1,4 @@
+-- Remove refresh throughput history
+
+DROP INDEX IF EXISTS idx_refresh_tracking_source_completed;
+DROP TABLE IF EXISTS refresh_tracking;
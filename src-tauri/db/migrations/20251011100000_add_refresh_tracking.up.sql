@@ -0,0 +1,14 @@
+-- Per-run throughput of each refresh step, used to estimate the next run's duration.
+-- units are bars for daily_prices and CIKs for financial_statements
+
+CREATE TABLE IF NOT EXISTS refresh_tracking (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    data_source TEXT NOT NULL,
+    completed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    duration_seconds REAL NOT NULL,
+    units_processed INTEGER NOT NULL,
+    records_processed INTEGER NOT NULL DEFAULT 0,
+    units_per_second REAL
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tracking_source_completed ON refresh_tracking(data_source, completed_at);
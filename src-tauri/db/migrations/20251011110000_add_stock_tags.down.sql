@@ -0,0 +1,4 @@
+-- Remove stock tags
+
+DROP INDEX IF EXISTS idx_stock_tags_tag;
+DROP TABLE IF EXISTS stock_tags;
\ No newline at end of file
@@ -0,0 +1,12 @@
+-- User-defined tags on stocks ("watchlist", "avoid", ...), stored lowercase
+
+CREATE TABLE IF NOT EXISTS stock_tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stock_id INTEGER NOT NULL,
+    tag TEXT NOT NULL COLLATE NOCASE,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id) ON DELETE CASCADE,
+    UNIQUE(stock_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_tags_tag ON stock_tags(tag);
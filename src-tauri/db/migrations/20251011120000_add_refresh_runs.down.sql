@@ -0,0 +1,6 @@
+-- Remove refresh run history
+
+DROP INDEX IF EXISTS idx_refresh_run_errors_run_id;
+DROP TABLE IF EXISTS refresh_run_errors;
+DROP INDEX IF EXISTS idx_refresh_runs_started_at;
+DROP TABLE IF EXISTS refresh_runs;
\ No newline at end of file
@@ -0,0 +1,30 @@
+-- History of refresh runs with their totals, and the per-stock errors of each run
+
+CREATE TABLE IF NOT EXISTS refresh_runs (
+    run_id TEXT PRIMARY KEY,
+    mode TEXT NOT NULL,
+    requested_sources TEXT NOT NULL,            -- comma-separated data sources in the run's plan
+    initiated_by TEXT NOT NULL,
+    started_at TEXT NOT NULL,                   -- RFC 3339, UTC
+    ended_at TEXT,
+    status TEXT NOT NULL DEFAULT 'running',     -- running | completed | failed
+    stocks_processed INTEGER NOT NULL DEFAULT 0,
+    filings_stored INTEGER NOT NULL DEFAULT 0,
+    prices_inserted INTEGER NOT NULL DEFAULT 0,
+    errors_count INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_runs_started_at ON refresh_runs(started_at);
+
+CREATE TABLE IF NOT EXISTS refresh_run_errors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id TEXT NOT NULL,
+    data_source TEXT NOT NULL,
+    symbol TEXT,
+    cik TEXT,
+    message TEXT NOT NULL,
+    FOREIGN KEY (run_id) REFERENCES refresh_runs(run_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_run_errors_run_id ON refresh_run_errors(run_id);
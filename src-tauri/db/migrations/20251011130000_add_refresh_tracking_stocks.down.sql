@@ -0,0 +1,3 @@
+-- Remove per-step stock counts from refresh tracking
+
+ALTER TABLE refresh_tracking DROP COLUMN stocks_processed;
\ No newline at end of file
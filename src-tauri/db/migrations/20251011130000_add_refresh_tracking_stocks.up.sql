@@ -0,0 +1,4 @@
+-- Stocks covered by each refresh step, so estimates can scale per stock.
+-- NULL for steps recorded before this column existed; those are ignored by the estimator.
+
+ALTER TABLE refresh_tracking ADD COLUMN stocks_processed INTEGER;
\ No newline at end of file
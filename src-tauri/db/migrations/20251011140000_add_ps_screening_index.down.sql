@@ -0,0 +1,3 @@
+-- Remove the P/S screener covering index
+
+DROP INDEX IF EXISTS idx_daily_valuation_ratios_ps_screen;
\ No newline at end of file
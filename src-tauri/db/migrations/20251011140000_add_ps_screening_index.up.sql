@@ -0,0 +1,5 @@
+-- Covering index for the P/S screeners: the latest row per stock and each stock's
+-- P/S history are both read from the index without touching the table.
+
+CREATE INDEX IF NOT EXISTS idx_daily_valuation_ratios_ps_screen
+    ON daily_valuation_ratios(stock_id, date, ps_ratio_ttm, market_cap);
@@ -0,0 +1,4 @@
+-- Remove stored live quotes
+
+DROP INDEX IF EXISTS idx_real_time_quotes_stock_timestamp;
+DROP TABLE IF EXISTS real_time_quotes;
\ No newline at end of file
@@ -0,0 +1,22 @@
+-- Live quotes fetched on demand from Schwab (get_live_quotes with persist = true).
+-- Rows are append-only snapshots; daily bars stay in daily_prices.
+
+CREATE TABLE IF NOT EXISTS real_time_quotes (
+    id INTEGER PRIMARY KEY,
+    stock_id INTEGER NOT NULL,
+    timestamp DATETIME NOT NULL,
+    bid_price REAL,
+    bid_size INTEGER,
+    ask_price REAL,
+    ask_size INTEGER,
+    last_price REAL NOT NULL,
+    last_size INTEGER,
+    volume INTEGER,
+    change_amount REAL,
+    change_percent REAL,
+    day_high REAL,
+    day_low REAL,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_real_time_quotes_stock_timestamp ON real_time_quotes(stock_id, timestamp);
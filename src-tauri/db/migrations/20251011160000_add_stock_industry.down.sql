@@ -0,0 +1,3 @@
+-- Remove the industry classification
+
+ALTER TABLE stocks DROP COLUMN industry;
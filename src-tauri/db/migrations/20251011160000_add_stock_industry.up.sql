@@ -0,0 +1,4 @@
+-- Optional industry classification below sector, used by the sector overview drill-down.
+-- Stocks without one are grouped as Unclassified.
+
+ALTER TABLE stocks ADD COLUMN industry TEXT;
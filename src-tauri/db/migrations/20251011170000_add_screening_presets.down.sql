@@ -0,0 +1,3 @@
+-- Remove shared screening presets (saved presets are not copied back)
+
+DROP TABLE IF EXISTS screening_presets;
@@ -0,0 +1,28 @@
+-- Saved screener criteria shared by every screen type, replacing per-screen storage.
+-- criteria is the screen's criteria struct as JSON; schema_version records the struct
+-- version it was saved under.
+
+CREATE TABLE IF NOT EXISTS screening_presets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    screen_type TEXT NOT NULL,
+    name TEXT NOT NULL,
+    criteria TEXT NOT NULL,
+    schema_version INTEGER NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(screen_type, name)
+);
+
+-- Copy custom screens saved in the metadata table; the metadata key is left in place so
+-- this migration stays non-destructive
+INSERT OR IGNORE INTO screening_presets (screen_type, name, criteria, schema_version)
+SELECT 'custom',
+       json_extract(screen.value, '$.name'),
+       json_object(
+           'filters', json(json_extract(screen.value, '$.filters')),
+           'sort', json(json_extract(screen.value, '$.sort')),
+           'limit', json_extract(screen.value, '$.limit')
+       ),
+       1
+FROM metadata m, json_each(m.value) screen
+WHERE m.key = 'custom_screen_presets';
@@ -0,0 +1,3 @@
+-- Remove the first trading date
+
+ALTER TABLE stocks DROP COLUMN first_trading_date;
@@ -0,0 +1,4 @@
+-- Earliest date the stock traded. Set at import when known; otherwise backfilled
+-- from the earliest stored daily price.
+
+ALTER TABLE stocks ADD COLUMN first_trading_date DATE;
@@ -0,0 +1,5 @@
+-- Remove persisted screen runs
+
+DROP TABLE IF EXISTS snapshot_items;
+DROP INDEX IF EXISTS idx_screening_snapshots_type_run;
+DROP TABLE IF EXISTS screening_snapshots;
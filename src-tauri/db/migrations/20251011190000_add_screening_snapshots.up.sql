@@ -0,0 +1,21 @@
+-- Persisted screen runs: the criteria used and the ranked passing stocks, so a screen's
+-- output can be compared over time. Items are removed with their snapshot.
+
+CREATE TABLE IF NOT EXISTS screening_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    screen_type TEXT NOT NULL,
+    criteria TEXT NOT NULL,
+    result_count INTEGER NOT NULL,
+    run_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_screening_snapshots_type_run ON screening_snapshots(screen_type, run_at);
+
+CREATE TABLE IF NOT EXISTS snapshot_items (
+    snapshot_id INTEGER NOT NULL REFERENCES screening_snapshots(id) ON DELETE CASCADE,
+    stock_id INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    rank INTEGER NOT NULL,
+    score REAL,
+    PRIMARY KEY (snapshot_id, stock_id)
+);
@@ -0,0 +1,8 @@
+-- Remove the stock search index and description column
+
+DROP TRIGGER IF EXISTS stocks_fts_after_update;
+DROP TRIGGER IF EXISTS stocks_fts_after_delete;
+DROP TRIGGER IF EXISTS stocks_fts_after_insert;
+DROP TABLE IF EXISTS stocks_fts;
+
+ALTER TABLE stocks DROP COLUMN description;
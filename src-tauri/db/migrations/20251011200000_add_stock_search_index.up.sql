@@ -0,0 +1,38 @@
+-- Full-text index over stock names for search_stocks. Company descriptions get a column
+-- here so they are searchable once populated. stocks_fts is an external-content FTS5
+-- table: it stores only the index, and the triggers below keep it in step with stocks.
+
+ALTER TABLE stocks ADD COLUMN description TEXT;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS stocks_fts USING fts5(
+    symbol,
+    company_name,
+    description,
+    sector,
+    industry,
+    content = 'stocks',
+    content_rowid = 'id'
+);
+
+INSERT INTO stocks_fts(stocks_fts) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS stocks_fts_after_insert AFTER INSERT ON stocks
+BEGIN
+    INSERT INTO stocks_fts (rowid, symbol, company_name, description, sector, industry)
+    VALUES (new.id, new.symbol, new.company_name, new.description, new.sector, new.industry);
+END;
+
+CREATE TRIGGER IF NOT EXISTS stocks_fts_after_delete AFTER DELETE ON stocks
+BEGIN
+    INSERT INTO stocks_fts (stocks_fts, rowid, symbol, company_name, description, sector, industry)
+    VALUES ('delete', old.id, old.symbol, old.company_name, old.description, old.sector, old.industry);
+END;
+
+CREATE TRIGGER IF NOT EXISTS stocks_fts_after_update
+AFTER UPDATE OF symbol, company_name, description, sector, industry ON stocks
+BEGIN
+    INSERT INTO stocks_fts (stocks_fts, rowid, symbol, company_name, description, sector, industry)
+    VALUES ('delete', old.id, old.symbol, old.company_name, old.description, old.sector, old.industry);
+    INSERT INTO stocks_fts (rowid, symbol, company_name, description, sector, industry)
+    VALUES (new.id, new.symbol, new.company_name, new.description, new.sector, new.industry);
+END;
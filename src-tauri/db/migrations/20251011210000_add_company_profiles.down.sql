@@ -0,0 +1,4 @@
+-- Remove company profiles and their manual overrides
+
+DROP TABLE IF EXISTS company_profile_overrides;
+DROP TABLE IF EXISTS company_profiles;
@@ -0,0 +1,24 @@
+-- Company profile fields from the SEC Submissions API, refreshed with financials.
+-- Manual edits live in company_profile_overrides so a refresh never overwrites them.
+
+CREATE TABLE IF NOT EXISTS company_profiles (
+    stock_id INTEGER PRIMARY KEY,
+    sic_code TEXT,
+    sic_description TEXT,
+    state_of_incorporation TEXT,
+    headquarters TEXT,
+    phone TEXT,
+    website TEXT,
+    description TEXT,
+    sec_updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id)
+);
+
+CREATE TABLE IF NOT EXISTS company_profile_overrides (
+    stock_id INTEGER NOT NULL,
+    field TEXT NOT NULL,
+    value TEXT NOT NULL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (stock_id, field),
+    FOREIGN KEY (stock_id) REFERENCES stocks(id)
+);
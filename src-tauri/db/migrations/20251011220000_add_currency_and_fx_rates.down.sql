@@ -0,0 +1,11 @@
+-- Remove currencies and exchange rates
+
+DROP INDEX IF EXISTS idx_daily_valuation_ratios_ps_screen;
+CREATE INDEX IF NOT EXISTS idx_daily_valuation_ratios_ps_screen
+    ON daily_valuation_ratios(stock_id, date, ps_ratio_ttm, market_cap);
+
+DROP TABLE IF EXISTS fx_rates;
+
+ALTER TABLE cash_flow_statements DROP COLUMN currency;
+ALTER TABLE daily_valuation_ratios DROP COLUMN currency;
+ALTER TABLE daily_prices DROP COLUMN currency;
@@ -0,0 +1,24 @@
+-- Currency of every stored monetary value, and daily USD exchange rates to compare them.
+-- Existing rows are all US listings reporting in USD. income_statements and balance_sheets
+-- already have a nullable currency column; readers treat NULL as USD.
+
+ALTER TABLE daily_prices ADD COLUMN currency TEXT NOT NULL DEFAULT 'USD';
+ALTER TABLE daily_valuation_ratios ADD COLUMN currency TEXT NOT NULL DEFAULT 'USD';
+ALTER TABLE cash_flow_statements ADD COLUMN currency TEXT NOT NULL DEFAULT 'USD';
+UPDATE income_statements SET currency = 'USD' WHERE currency IS NULL;
+UPDATE balance_sheets SET currency = 'USD' WHERE currency IS NULL;
+
+-- Units of `currency` per 1 USD on `date`
+CREATE TABLE IF NOT EXISTS fx_rates (
+    currency TEXT NOT NULL,
+    date DATE NOT NULL,
+    rate REAL NOT NULL CHECK (rate > 0),
+    source TEXT NOT NULL DEFAULT 'frankfurter',
+    fetched_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (currency, date)
+);
+
+-- The P/S screeners now read the currency to compare market cap in USD; keep them on the index
+DROP INDEX IF EXISTS idx_daily_valuation_ratios_ps_screen;
+CREATE INDEX IF NOT EXISTS idx_daily_valuation_ratios_ps_screen
+    ON daily_valuation_ratios(stock_id, date, ps_ratio_ttm, market_cap, currency);
@@ -0,0 +1,4 @@
+-- Remove share count history and corporate actions
+
+DROP TABLE IF EXISTS corporate_actions;
+DROP TABLE IF EXISTS shares_outstanding_history;
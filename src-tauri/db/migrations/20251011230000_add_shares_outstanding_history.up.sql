@@ -0,0 +1,27 @@
+-- Share count as of each SEC fact end date, for point-in-time market caps.
+-- unexplained_change marks a move of more than 10% from the prior value with no split to account for it.
+
+CREATE TABLE IF NOT EXISTS shares_outstanding_history (
+    stock_id INTEGER NOT NULL,
+    as_of_date DATE NOT NULL,
+    shares REAL NOT NULL CHECK (shares > 0),
+    concept TEXT NOT NULL,
+    accession_number TEXT,
+    filed_date DATE,
+    unexplained_change INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (stock_id, as_of_date),
+    FOREIGN KEY (stock_id) REFERENCES stocks(id)
+);
+
+-- Stock splits; ratio is new shares per old share (0.1 for a 1-for-10 reverse split)
+CREATE TABLE IF NOT EXISTS corporate_actions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stock_id INTEGER NOT NULL,
+    action_type TEXT NOT NULL CHECK (action_type IN ('split')),
+    ex_date DATE NOT NULL,
+    ratio REAL NOT NULL CHECK (ratio > 0),
+    source TEXT NOT NULL DEFAULT 'manual',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id),
+    UNIQUE(stock_id, action_type, ex_date)
+);
@@ -0,0 +1,5 @@
+-- Remove free cash flow and FCF yield
+
+ALTER TABLE daily_valuation_ratios DROP COLUMN fcf_yield;
+ALTER TABLE daily_valuation_ratios DROP COLUMN free_cash_flow;
+ALTER TABLE cash_flow_statements DROP COLUMN free_cash_flow;
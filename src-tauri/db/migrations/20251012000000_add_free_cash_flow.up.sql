@@ -0,0 +1,11 @@
+-- Free cash flow (operating cash flow minus capital expenditures) per cash flow statement,
+-- and the daily FCF yield it gives. Capex is stored as reported, so its sign varies by source.
+
+ALTER TABLE cash_flow_statements ADD COLUMN free_cash_flow REAL;
+UPDATE cash_flow_statements
+SET free_cash_flow = operating_cash_flow - ABS(capital_expenditures)
+WHERE operating_cash_flow IS NOT NULL AND capital_expenditures IS NOT NULL;
+
+ALTER TABLE daily_valuation_ratios ADD COLUMN free_cash_flow REAL;
+-- Percent of market cap
+ALTER TABLE daily_valuation_ratios ADD COLUMN fcf_yield REAL;
@@ -0,0 +1,6 @@
+-- Remove quality metrics and the tax columns they use
+
+DROP TABLE IF EXISTS quality_metrics;
+
+ALTER TABLE income_statements DROP COLUMN pretax_income;
+ALTER TABLE income_statements DROP COLUMN tax_expense;
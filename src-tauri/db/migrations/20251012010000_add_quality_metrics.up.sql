@@ -0,0 +1,24 @@
+-- Tax and pre-tax income for effective tax rates, and per-fiscal-year quality metrics
+-- (ROIC, margins, asset turnover) recomputed with the daily ratios.
+
+ALTER TABLE income_statements ADD COLUMN tax_expense REAL;
+ALTER TABLE income_statements ADD COLUMN pretax_income REAL;
+
+CREATE TABLE IF NOT EXISTS quality_metrics (
+    stock_id INTEGER NOT NULL,
+    fiscal_year INTEGER NOT NULL,
+    report_date DATE NOT NULL,
+    -- Percent of revenue
+    gross_margin REAL,
+    operating_margin REAL,
+    asset_turnover REAL,
+    -- Clamped to [0, 0.5]
+    effective_tax_rate REAL,
+    nopat REAL,
+    invested_capital REAL,
+    -- Percent
+    roic REAL,
+    computed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (stock_id, fiscal_year),
+    FOREIGN KEY (stock_id) REFERENCES stocks(id)
+);
@@ -0,0 +1,4 @@
+-- Remove dividend history
+
+DROP INDEX IF EXISTS idx_dividends_stock_period;
+DROP TABLE IF EXISTS dividends;
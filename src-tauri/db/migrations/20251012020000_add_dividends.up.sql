@@ -0,0 +1,22 @@
+-- Dividends per share by period. SEC rows come from the per-share dividend facts; for fiscal
+-- years without them, cash_flow_derived rows approximate the year as dividends paid / shares.
+-- special_amount is the part of amount_per_share judged a one-off special dividend.
+
+CREATE TABLE IF NOT EXISTS dividends (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stock_id INTEGER NOT NULL,
+    period_type TEXT NOT NULL CHECK (period_type IN ('Quarterly', 'Annual')),
+    period_start DATE,
+    period_end DATE NOT NULL,
+    fiscal_year INTEGER,
+    amount_per_share REAL NOT NULL CHECK (amount_per_share >= 0),
+    special_amount REAL NOT NULL DEFAULT 0,
+    is_special INTEGER NOT NULL DEFAULT 0,
+    source TEXT NOT NULL CHECK (source IN ('sec_declared', 'sec_paid', 'cash_flow_derived')),
+    accession_number TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id),
+    UNIQUE(stock_id, period_type, period_end)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dividends_stock_period ON dividends(stock_id, period_type, period_end);
@@ -0,0 +1,4 @@
+-- Remove stored 52-week ranges
+
+DROP INDEX IF EXISTS idx_price_range_52w_pct_from_low;
+DROP TABLE IF EXISTS price_range_52w;
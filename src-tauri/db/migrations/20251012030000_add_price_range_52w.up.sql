@@ -0,0 +1,21 @@
+-- Trailing 52-week range per stock, as of its latest stored close. Rewritten for a stock
+-- whenever its prices are refreshed, so screens don't scan a year of bars per stock.
+-- limited_history marks stocks whose first bar falls inside the window (recent listings):
+-- their high and low cover only the bars available.
+
+CREATE TABLE IF NOT EXISTS price_range_52w (
+    stock_id INTEGER PRIMARY KEY,
+    as_of_date DATE NOT NULL,
+    window_start DATE NOT NULL,
+    trading_days INTEGER NOT NULL,
+    high_52_week REAL,
+    low_52_week REAL,
+    latest_close REAL,
+    pct_from_high REAL,
+    pct_from_low REAL,
+    limited_history INTEGER NOT NULL DEFAULT 0,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_range_52w_pct_from_low ON price_range_52w(pct_from_low);
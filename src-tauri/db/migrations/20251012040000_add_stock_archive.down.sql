@@ -0,0 +1,18 @@
+-- Remove stock archiving; archived rows are dropped with their tables
+
+DROP TABLE IF EXISTS cash_flow_statements_archive;
+DROP TABLE IF EXISTS balance_sheets_archive;
+DROP TABLE IF EXISTS income_statements_archive;
+DROP TABLE IF EXISTS daily_prices_archive;
+
+DROP VIEW IF EXISTS sp500_symbols;
+CREATE VIEW sp500_symbols AS
+SELECT
+    id,
+    symbol,
+    created_at
+FROM stocks
+WHERE is_sp500 = 1;
+
+ALTER TABLE stocks DROP COLUMN archived_at;
+ALTER TABLE stocks DROP COLUMN status;
@@ -0,0 +1,25 @@
+-- Archive delisted stocks instead of deleting them. An archived stock keeps its row with
+-- status 'delisted' and drops out of the S&P 500 refresh list; its prices and statements
+-- can be moved into the *_archive tables (same columns, no constraints) and back on restore.
+
+ALTER TABLE stocks ADD COLUMN status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'delisted'));
+ALTER TABLE stocks ADD COLUMN archived_at DATETIME;
+
+DROP VIEW IF EXISTS sp500_symbols;
+CREATE VIEW sp500_symbols AS
+SELECT
+    id,
+    symbol,
+    created_at
+FROM stocks
+WHERE is_sp500 = 1 AND status = 'active';
+
+CREATE TABLE IF NOT EXISTS daily_prices_archive AS SELECT * FROM daily_prices WHERE 0;
+CREATE TABLE IF NOT EXISTS income_statements_archive AS SELECT * FROM income_statements WHERE 0;
+CREATE TABLE IF NOT EXISTS balance_sheets_archive AS SELECT * FROM balance_sheets WHERE 0;
+CREATE TABLE IF NOT EXISTS cash_flow_statements_archive AS SELECT * FROM cash_flow_statements WHERE 0;
+
+CREATE INDEX IF NOT EXISTS idx_daily_prices_archive_stock ON daily_prices_archive(stock_id);
+CREATE INDEX IF NOT EXISTS idx_income_statements_archive_stock ON income_statements_archive(stock_id);
+CREATE INDEX IF NOT EXISTS idx_balance_sheets_archive_stock ON balance_sheets_archive(stock_id);
+CREATE INDEX IF NOT EXISTS idx_cash_flow_statements_archive_stock ON cash_flow_statements_archive(stock_id);
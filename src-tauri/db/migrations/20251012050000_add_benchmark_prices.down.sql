@@ -0,0 +1,3 @@
+-- Remove stored benchmark series
+
+DROP TABLE IF EXISTS benchmark_prices;
@@ -0,0 +1,15 @@
+-- Daily benchmark series, keyed by benchmark name. Composites (SP500_EW, SP500_CW) are built
+-- from S&P 500 constituents' closes and extended as prices refresh; real index series are
+-- imported from a provider under their own name. value is the index level: composites
+-- compound daily returns from 100, imports store the index close.
+
+CREATE TABLE IF NOT EXISTS benchmark_prices (
+    benchmark TEXT NOT NULL,
+    date DATE NOT NULL,
+    value REAL NOT NULL,
+    daily_return REAL,
+    constituents INTEGER,
+    source TEXT NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (benchmark, date)
+);
@@ -0,0 +1,13 @@
+-- Restore the rank-average ranking over stocks with all six metrics
+
+DROP VIEW IF EXISTS oshaughnessy_ranking;
+
+CREATE VIEW oshaughnessy_ranking AS
+WITH ranked AS (
+  SELECT *, RANK() OVER (ORDER BY pe_ratio ASC) as pe_rank, RANK() OVER (ORDER BY pb_ratio ASC) as pb_rank, RANK() OVER (ORDER BY ps_ratio ASC) as ps_rank, RANK() OVER (ORDER BY evs_ratio ASC) as evs_rank, RANK() OVER (ORDER BY ev_ebitda_ratio ASC) as ebitda_rank, RANK() OVER (ORDER BY shareholder_yield DESC) as yield_rank, COUNT(*) OVER () as total_stocks
+  FROM oshaughnessy_value_composite
+  WHERE pe_ratio IS NOT NULL AND pb_ratio IS NOT NULL AND ps_ratio IS NOT NULL AND evs_ratio IS NOT NULL AND ev_ebitda_ratio IS NOT NULL AND shareholder_yield IS NOT NULL
+)
+SELECT *, CAST((pe_rank + pb_rank + ps_rank + evs_rank + ebitda_rank + yield_rank) / 6.0 AS REAL) as composite_score, CAST(ROUND(((pe_rank + pb_rank + ps_rank + evs_rank + ebitda_rank + yield_rank) / 6.0 / total_stocks) * 100, 1) AS REAL) as composite_percentile, RANK() OVER (ORDER BY (pe_rank + pb_rank + ps_rank + evs_rank + ebitda_rank + yield_rank) / 6.0 ASC) as overall_rank, CASE WHEN RANK() OVER (ORDER BY (pe_rank + pb_rank + ps_rank + evs_rank + ebitda_rank + yield_rank) / 6.0 ASC) <= 10 THEN 1 ELSE 0 END as passes_screening, 6 as metrics_available
+FROM ranked
+ORDER BY composite_score ASC;
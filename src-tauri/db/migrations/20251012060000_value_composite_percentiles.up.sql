@@ -0,0 +1,36 @@
+-- Rank the O'Shaughnessy value composite by average percentile across P/E, P/B, P/S,
+-- EV/EBITDA and shareholder yield. Each percentile is taken among the stocks reporting that
+-- metric (0 = cheapest, highest yield counts as cheapest), so a stock missing components is
+-- ranked on the ones it has; stocks with none are left out. EV/S stays ranked for reference
+-- but is not part of the composite.
+
+DROP VIEW IF EXISTS oshaughnessy_ranking;
+
+CREATE VIEW oshaughnessy_ranking AS
+WITH ranked AS (
+  SELECT *,
+    CASE WHEN pe_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY pe_ratio IS NULL ORDER BY pe_ratio ASC) END as pe_rank,
+    CASE WHEN pb_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY pb_ratio IS NULL ORDER BY pb_ratio ASC) END as pb_rank,
+    CASE WHEN ps_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY ps_ratio IS NULL ORDER BY ps_ratio ASC) END as ps_rank,
+    CASE WHEN evs_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY evs_ratio IS NULL ORDER BY evs_ratio ASC) END as evs_rank,
+    CASE WHEN ev_ebitda_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY ev_ebitda_ratio IS NULL ORDER BY ev_ebitda_ratio ASC) END as ebitda_rank,
+    CASE WHEN shareholder_yield IS NOT NULL THEN RANK() OVER (PARTITION BY shareholder_yield IS NULL ORDER BY shareholder_yield DESC) END as yield_rank,
+    CASE WHEN pe_ratio IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY pe_ratio IS NULL ORDER BY pe_ratio ASC) END as pe_percentile,
+    CASE WHEN pb_ratio IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY pb_ratio IS NULL ORDER BY pb_ratio ASC) END as pb_percentile,
+    CASE WHEN ps_ratio IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY ps_ratio IS NULL ORDER BY ps_ratio ASC) END as ps_percentile,
+    CASE WHEN ev_ebitda_ratio IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY ev_ebitda_ratio IS NULL ORDER BY ev_ebitda_ratio ASC) END as ev_ebitda_percentile,
+    CASE WHEN shareholder_yield IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY shareholder_yield IS NULL ORDER BY shareholder_yield DESC) END as yield_percentile,
+    COUNT(*) OVER () as total_stocks
+  FROM oshaughnessy_value_composite
+  WHERE pe_ratio IS NOT NULL OR pb_ratio IS NOT NULL OR ps_ratio IS NOT NULL OR ev_ebitda_ratio IS NOT NULL OR shareholder_yield IS NOT NULL
+),
+scored AS (
+  SELECT *,
+    (pe_percentile IS NOT NULL) + (pb_percentile IS NOT NULL) + (ps_percentile IS NOT NULL) + (ev_ebitda_percentile IS NOT NULL) + (yield_percentile IS NOT NULL) as metrics_available,
+    CAST((COALESCE(pe_percentile, 0) + COALESCE(pb_percentile, 0) + COALESCE(ps_percentile, 0) + COALESCE(ev_ebitda_percentile, 0) + COALESCE(yield_percentile, 0))
+      / ((pe_percentile IS NOT NULL) + (pb_percentile IS NOT NULL) + (ps_percentile IS NOT NULL) + (ev_ebitda_percentile IS NOT NULL) + (yield_percentile IS NOT NULL)) AS REAL) as composite_score
+  FROM ranked
+)
+SELECT *, CAST(ROUND(100.0 * PERCENT_RANK() OVER (ORDER BY composite_score ASC), 1) AS REAL) as composite_percentile, RANK() OVER (ORDER BY composite_score ASC) as overall_rank, CASE WHEN RANK() OVER (ORDER BY composite_score ASC) <= 10 THEN 1 ELSE 0 END as passes_screening
+FROM scored
+ORDER BY composite_score ASC;
@@ -0,0 +1,70 @@
+-- Split shareholder yield into its dividend and buyback components, in percent of market cap
+-- like the other yields. Net issuance is stored as negative repurchases and lowers the yield;
+-- a stock reporting neither payout has no yield rather than a yield of zero.
+
+DROP VIEW IF EXISTS oshaughnessy_ranking;
+DROP VIEW IF EXISTS oshaughnessy_value_composite;
+
+CREATE VIEW oshaughnessy_value_composite AS
+SELECT
+  s.id as stock_id,
+  s.symbol,
+  s.sector,
+  (SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) as current_price,
+  (SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding as market_cap,
+  i.net_income,
+  i.revenue,
+  i.operating_income,
+  b.total_equity,
+  b.shares_outstanding,
+  b.total_debt,
+  b.cash_and_equivalents,
+  cf.dividends_paid,
+  cf.share_repurchases,
+  cf.depreciation_expense,
+  cf.amortization_expense,
+  (((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) + COALESCE(b.total_debt, 0) - COALESCE(b.cash_and_equivalents, 0)) as enterprise_value,
+  (COALESCE(i.operating_income, 0) + COALESCE(cf.depreciation_expense, 0) + COALESCE(cf.amortization_expense, 0)) as ebitda,
+  CASE WHEN i.net_income > 0 AND b.shares_outstanding > 0 THEN ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) / i.net_income ELSE NULL END as pe_ratio,
+  CASE WHEN b.total_equity > 0 AND b.shares_outstanding > 0 THEN ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) / b.total_equity ELSE NULL END as pb_ratio,
+  CASE WHEN i.revenue > 0 AND b.shares_outstanding > 0 THEN ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) / i.revenue ELSE NULL END as ps_ratio,
+  CASE WHEN i.revenue > 0 AND b.shares_outstanding > 0 THEN (((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) + COALESCE(b.total_debt, 0) - COALESCE(b.cash_and_equivalents, 0)) / i.revenue ELSE NULL END as evs_ratio,
+  CASE WHEN (COALESCE(i.operating_income, 0) + COALESCE(cf.depreciation_expense, 0) + COALESCE(cf.amortization_expense, 0)) > 0 AND b.shares_outstanding > 0 THEN (((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) + COALESCE(b.total_debt, 0) - COALESCE(b.cash_and_equivalents, 0)) / (COALESCE(i.operating_income, 0) + COALESCE(cf.depreciation_expense, 0) + COALESCE(cf.amortization_expense, 0)) ELSE NULL END as ev_ebitda_ratio,
+  CASE WHEN cf.dividends_paid IS NOT NULL AND b.shares_outstanding > 0 AND ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) > 0 THEN cf.dividends_paid * 100.0 / ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) ELSE NULL END as dividend_yield,
+  CASE WHEN cf.share_repurchases IS NOT NULL AND b.shares_outstanding > 0 AND ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) > 0 THEN cf.share_repurchases * 100.0 / ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) ELSE NULL END as buyback_yield,
+  CASE WHEN (cf.dividends_paid IS NOT NULL OR cf.share_repurchases IS NOT NULL) AND b.shares_outstanding > 0 AND ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) > 0 THEN (COALESCE(cf.dividends_paid, 0) + COALESCE(cf.share_repurchases, 0)) * 100.0 / ((SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) * b.shares_outstanding) ELSE NULL END as shareholder_yield,
+  ((CASE WHEN i.net_income > 0 AND b.shares_outstanding > 0 THEN 1 ELSE 0 END) + (CASE WHEN b.total_equity > 0 AND b.shares_outstanding > 0 THEN 1 ELSE 0 END) + (CASE WHEN i.revenue > 0 AND b.shares_outstanding > 0 THEN 1 ELSE 0 END) + (CASE WHEN i.revenue > 0 AND b.shares_outstanding > 0 THEN 1 ELSE 0 END) + (CASE WHEN (COALESCE(i.operating_income, 0) + COALESCE(cf.depreciation_expense, 0) + COALESCE(cf.amortization_expense, 0)) > 0 AND b.shares_outstanding > 0 THEN 1 ELSE 0 END) + (CASE WHEN b.shares_outstanding > 0 AND (SELECT close_price FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1) > 0 THEN 1 ELSE 0 END)) * 16.67 as data_completeness_score
+FROM stocks s
+LEFT JOIN (SELECT stock_id, net_income, revenue, operating_income, report_date, ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) as rn FROM income_statements WHERE period_type = 'FY' AND revenue IS NOT NULL) i ON s.id = i.stock_id AND i.rn = 1
+LEFT JOIN (SELECT stock_id, total_equity, shares_outstanding, total_debt, cash_and_equivalents, report_date, ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) as rn FROM balance_sheets WHERE period_type = 'Annual' AND total_equity IS NOT NULL) b ON s.id = b.stock_id AND b.rn = 1
+LEFT JOIN (SELECT stock_id, dividends_paid, share_repurchases, depreciation_expense, amortization_expense, report_date, ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) as rn FROM cash_flow_statements WHERE period_type = 'Annual' AND operating_cash_flow IS NOT NULL) cf ON s.id = cf.stock_id AND cf.rn = 1
+WHERE s.is_sp500 = 1;
+
+CREATE VIEW oshaughnessy_ranking AS
+WITH ranked AS (
+  SELECT *,
+    CASE WHEN pe_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY pe_ratio IS NULL ORDER BY pe_ratio ASC) END as pe_rank,
+    CASE WHEN pb_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY pb_ratio IS NULL ORDER BY pb_ratio ASC) END as pb_rank,
+    CASE WHEN ps_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY ps_ratio IS NULL ORDER BY ps_ratio ASC) END as ps_rank,
+    CASE WHEN evs_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY evs_ratio IS NULL ORDER BY evs_ratio ASC) END as evs_rank,
+    CASE WHEN ev_ebitda_ratio IS NOT NULL THEN RANK() OVER (PARTITION BY ev_ebitda_ratio IS NULL ORDER BY ev_ebitda_ratio ASC) END as ebitda_rank,
+    CASE WHEN shareholder_yield IS NOT NULL THEN RANK() OVER (PARTITION BY shareholder_yield IS NULL ORDER BY shareholder_yield DESC) END as yield_rank,
+    CASE WHEN pe_ratio IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY pe_ratio IS NULL ORDER BY pe_ratio ASC) END as pe_percentile,
+    CASE WHEN pb_ratio IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY pb_ratio IS NULL ORDER BY pb_ratio ASC) END as pb_percentile,
+    CASE WHEN ps_ratio IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY ps_ratio IS NULL ORDER BY ps_ratio ASC) END as ps_percentile,
+    CASE WHEN ev_ebitda_ratio IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY ev_ebitda_ratio IS NULL ORDER BY ev_ebitda_ratio ASC) END as ev_ebitda_percentile,
+    CASE WHEN shareholder_yield IS NOT NULL THEN 100.0 * PERCENT_RANK() OVER (PARTITION BY shareholder_yield IS NULL ORDER BY shareholder_yield DESC) END as yield_percentile,
+    COUNT(*) OVER () as total_stocks
+  FROM oshaughnessy_value_composite
+  WHERE pe_ratio IS NOT NULL OR pb_ratio IS NOT NULL OR ps_ratio IS NOT NULL OR ev_ebitda_ratio IS NOT NULL OR shareholder_yield IS NOT NULL
+),
+scored AS (
+  SELECT *,
+    (pe_percentile IS NOT NULL) + (pb_percentile IS NOT NULL) + (ps_percentile IS NOT NULL) + (ev_ebitda_percentile IS NOT NULL) + (yield_percentile IS NOT NULL) as metrics_available,
+    CAST((COALESCE(pe_percentile, 0) + COALESCE(pb_percentile, 0) + COALESCE(ps_percentile, 0) + COALESCE(ev_ebitda_percentile, 0) + COALESCE(yield_percentile, 0))
+      / ((pe_percentile IS NOT NULL) + (pb_percentile IS NOT NULL) + (ps_percentile IS NOT NULL) + (ev_ebitda_percentile IS NOT NULL) + (yield_percentile IS NOT NULL)) AS REAL) as composite_score
+  FROM ranked
+)
+SELECT *, CAST(ROUND(100.0 * PERCENT_RANK() OVER (ORDER BY composite_score ASC), 1) AS REAL) as composite_percentile, RANK() OVER (ORDER BY composite_score ASC) as overall_rank, CASE WHEN RANK() OVER (ORDER BY composite_score ASC) <= 10 THEN 1 ELSE 0 END as passes_screening
+FROM scored
+ORDER BY composite_score ASC;
@@ -0,0 +1,3 @@
+-- Remove the per-stock fiscal year end view
+
+DROP VIEW IF EXISTS stock_fiscal_year_ends;
@@ -0,0 +1,19 @@
+-- Fiscal year end month per stock, from the period ends of its annual income statements.
+-- A period end within the first half of a month counts toward the month before, so a
+-- 52/53-week year ending on October 1 is a September year end.
+
+CREATE VIEW stock_fiscal_year_ends AS
+WITH annual AS (
+  SELECT
+    stock_id,
+    CAST(strftime('%m', date(report_date, '-15 days')) AS INTEGER) as end_month,
+    ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) as rn
+  FROM income_statements
+  WHERE period_type = 'FY' AND report_date IS NOT NULL
+)
+SELECT
+  stock_id,
+  MAX(CASE WHEN rn = 1 THEN end_month END) as fiscal_year_end_month,
+  COUNT(DISTINCT end_month) > 1 as fiscal_year_end_changed
+FROM annual
+GROUP BY stock_id;
@@ -0,0 +1,3 @@
+-- Remove stored weekly and monthly bars
+
+DROP TABLE IF EXISTS interval_prices;
@@ -0,0 +1,18 @@
+-- Weekly and monthly OHLCV bars for long-range analyses that don't need every trading day.
+-- Daily bars stay in daily_prices; period_start is the first day of the week or month the
+-- provider dated the bar with.
+
+CREATE TABLE IF NOT EXISTS interval_prices (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stock_id INTEGER NOT NULL,
+    frequency TEXT NOT NULL CHECK (frequency IN ('weekly', 'monthly')),
+    period_start DATE NOT NULL,
+    open_price REAL NOT NULL,
+    high_price REAL NOT NULL,
+    low_price REAL NOT NULL,
+    close_price REAL NOT NULL,
+    volume INTEGER,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id),
+    UNIQUE(stock_id, frequency, period_start)
+);
@@ -0,0 +1,4 @@
+-- Forget merged stocks' former symbols
+
+DROP INDEX IF EXISTS idx_symbol_aliases_stock;
+DROP TABLE IF EXISTS symbol_aliases;
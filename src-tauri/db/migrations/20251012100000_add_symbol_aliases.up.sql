@@ -0,0 +1,11 @@
+-- Symbols a stock was previously listed under, kept when a duplicate stock row is merged
+-- into the one that survives a ticker change so searches for the old symbol still find it.
+
+CREATE TABLE IF NOT EXISTS symbol_aliases (
+    symbol TEXT PRIMARY KEY,
+    stock_id INTEGER NOT NULL,
+    merged_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_symbol_aliases_stock ON symbol_aliases(stock_id);
@@ -0,0 +1,4 @@
+-- Remove the estimated share count flags
+
+ALTER TABLE daily_valuation_ratios DROP COLUMN shares_estimated;
+ALTER TABLE balance_sheets DROP COLUMN shares_outstanding_estimated;
@@ -0,0 +1,5 @@
+-- Flag share counts estimated from a provider market cap / close because the filing reported
+-- none, and the valuation rows whose market cap rests on such an estimate.
+
+ALTER TABLE balance_sheets ADD COLUMN shares_outstanding_estimated INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE daily_valuation_ratios ADD COLUMN shares_estimated INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Remove the alias end dates
+
+ALTER TABLE symbol_aliases DROP COLUMN effective_until;
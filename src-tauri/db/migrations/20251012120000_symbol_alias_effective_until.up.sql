@@ -0,0 +1,4 @@
+-- When each former symbol stopped trading: the ticker change date the S&P 500 sync saw, or the
+-- merged stock's last price day. NULL when unknown.
+
+ALTER TABLE symbol_aliases ADD COLUMN effective_until DATE;
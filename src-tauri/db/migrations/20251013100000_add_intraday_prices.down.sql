@@ -0,0 +1,4 @@
+-- Remove intraday bars
+
+DROP INDEX IF EXISTS idx_intraday_prices_stock_date;
+DROP TABLE IF EXISTS intraday_prices;
@@ -0,0 +1,21 @@
+-- Minute bars for watchlist stocks. datetime is the bar's start in UTC, always written as
+-- RFC 3339 with a trailing 'Z' (e.g. 2024-06-03T13:30:00Z); trading_date is the New York
+-- session the bar belongs to. Old 1-minute bars are folded into 30-minute ones.
+
+CREATE TABLE IF NOT EXISTS intraday_prices (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stock_id INTEGER NOT NULL,
+    datetime TEXT NOT NULL,
+    trading_date DATE NOT NULL,
+    interval_type TEXT NOT NULL CHECK (interval_type IN ('1min', '5min', '15min', '30min')),
+    open_price REAL NOT NULL,
+    high_price REAL NOT NULL,
+    low_price REAL NOT NULL,
+    close_price REAL NOT NULL,
+    volume INTEGER,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id),
+    UNIQUE(stock_id, interval_type, datetime)
+);
+
+CREATE INDEX IF NOT EXISTS idx_intraday_prices_stock_date ON intraday_prices(stock_id, interval_type, trading_date);
@@ -0,0 +1,4 @@
+-- Remove option chain snapshots
+
+DROP INDEX IF EXISTS idx_options_chains_stock_snapshot;
+DROP TABLE IF EXISTS options_chains;
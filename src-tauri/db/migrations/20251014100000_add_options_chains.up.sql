@@ -0,0 +1,29 @@
+-- Option chain snapshots from Schwab. Each fetch stores every contract under the same
+-- snapshot_time (RFC 3339 in UTC with a trailing 'Z'), so the latest snapshot for a stock is
+-- the rows with its greatest snapshot_time. implied_volatility is in percent, as Schwab sends it.
+
+CREATE TABLE IF NOT EXISTS options_chains (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stock_id INTEGER NOT NULL,
+    snapshot_time TEXT NOT NULL,
+    contract_symbol TEXT NOT NULL,
+    expiration_date DATE NOT NULL,
+    strike_price REAL NOT NULL,
+    option_type TEXT NOT NULL CHECK (option_type IN ('CALL', 'PUT')),
+    bid REAL,
+    ask REAL,
+    last_price REAL,
+    volume INTEGER,
+    open_interest INTEGER,
+    implied_volatility REAL,
+    delta REAL,
+    gamma REAL,
+    theta REAL,
+    vega REAL,
+    rho REAL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id),
+    UNIQUE(stock_id, expiration_date, strike_price, option_type, snapshot_time)
+);
+
+CREATE INDEX IF NOT EXISTS idx_options_chains_stock_snapshot ON options_chains(stock_id, snapshot_time);
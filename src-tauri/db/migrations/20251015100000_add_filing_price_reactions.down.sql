@@ -0,0 +1,4 @@
+-- Remove filing price reactions
+
+DROP INDEX IF EXISTS idx_filing_price_reactions_stock;
+DROP TABLE IF EXISTS filing_price_reactions;
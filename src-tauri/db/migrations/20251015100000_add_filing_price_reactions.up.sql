@@ -0,0 +1,17 @@
+-- How the price moved around each SEC filing. reaction_date is the first trading day on or
+-- after the filed date; the returns run from that close to the close 5 and 30 trading days
+-- later and stay NULL until those prices exist.
+
+CREATE TABLE IF NOT EXISTS filing_price_reactions (
+    sec_filing_id INTEGER PRIMARY KEY,
+    stock_id INTEGER NOT NULL,
+    reaction_date DATE NOT NULL,
+    reaction_close REAL NOT NULL,
+    return_5d_pct REAL,
+    return_30d_pct REAL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (sec_filing_id) REFERENCES sec_filings(id) ON DELETE CASCADE,
+    FOREIGN KEY (stock_id) REFERENCES stocks(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_filing_price_reactions_stock ON filing_price_reactions(stock_id);
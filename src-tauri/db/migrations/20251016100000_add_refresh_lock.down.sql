@@ -0,0 +1,3 @@
+-- Remove the refresh lock
+
+DROP TABLE IF EXISTS refresh_lock;
@@ -0,0 +1,11 @@
+-- Single-row lock held for the length of a data refresh or database maintenance, so runs
+-- from the app, the scheduler, the HTTP API and the refresh_data CLI never overlap. The
+-- holder renews expires_at while it runs; a claim past expires_at was abandoned and may be
+-- taken over.
+
+CREATE TABLE IF NOT EXISTS refresh_lock (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    holder TEXT NOT NULL,
+    acquired_at DATETIME NOT NULL,
+    expires_at DATETIME NOT NULL
+);
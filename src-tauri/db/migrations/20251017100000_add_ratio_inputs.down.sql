@@ -0,0 +1,7 @@
+-- Remove the stored ratio inputs
+
+ALTER TABLE daily_valuation_ratios DROP COLUMN market_cap_source;
+ALTER TABLE daily_valuation_ratios DROP COLUMN shares_as_of_date;
+ALTER TABLE daily_valuation_ratios DROP COLUMN cash_flow_statement_id;
+ALTER TABLE daily_valuation_ratios DROP COLUMN balance_sheet_id;
+ALTER TABLE daily_valuation_ratios DROP COLUMN income_statement_id;
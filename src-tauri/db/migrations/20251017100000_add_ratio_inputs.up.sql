@@ -0,0 +1,10 @@
+-- The rows each stored daily ratio was computed from, so ratio provenance names the same
+-- statements and share count the stored value used. market_cap_source is 'shares_history',
+-- 'balance_sheet' (close × that source's share count) or 'provider' (the price row's own
+-- market cap). Rows written before this migration have none until ratios are recomputed.
+
+ALTER TABLE daily_valuation_ratios ADD COLUMN income_statement_id INTEGER;
+ALTER TABLE daily_valuation_ratios ADD COLUMN balance_sheet_id INTEGER;
+ALTER TABLE daily_valuation_ratios ADD COLUMN cash_flow_statement_id INTEGER;
+ALTER TABLE daily_valuation_ratios ADD COLUMN shares_as_of_date DATE;
+ALTER TABLE daily_valuation_ratios ADD COLUMN market_cap_source TEXT;
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+/// Net income, operating cash flow and year-end total assets per stock and fiscal year, latest
+/// restatement of each statement. `accrual_ratio` is NULL when any input is missing or assets
+/// aren't positive.
+const ANNUAL_ACCRUALS: &str = "
+    WITH income AS (
+        SELECT stock_id, fiscal_year, report_date, net_income,
+               ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+        FROM income_statements
+        WHERE period_type = 'FY' AND fiscal_year IS NOT NULL
+    ),
+    cash_flow AS (
+        SELECT stock_id, fiscal_year, operating_cash_flow,
+               ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+        FROM cash_flow_statements
+        WHERE period_type = 'Annual' AND fiscal_year IS NOT NULL
+    ),
+    balance AS (
+        SELECT stock_id, fiscal_year, total_assets,
+               ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+        FROM balance_sheets
+        WHERE period_type = 'Annual' AND fiscal_year IS NOT NULL
+    )
+    SELECT i.stock_id, i.fiscal_year, i.report_date, i.net_income, c.operating_cash_flow, b.total_assets,
+           CASE WHEN b.total_assets > 0 THEN (i.net_income - c.operating_cash_flow) / b.total_assets END AS accrual_ratio
+    FROM income i
+    LEFT JOIN cash_flow c ON c.stock_id = i.stock_id AND c.fiscal_year = i.fiscal_year AND c.rn = 1
+    LEFT JOIN balance b ON b.stock_id = i.stock_id AND b.fiscal_year = i.fiscal_year AND b.rn = 1
+    WHERE i.rn = 1";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AccrualYear {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    pub net_income: Option<f64>,
+    pub operating_cash_flow: Option<f64>,
+    /// Year-end total assets
+    pub total_assets: Option<f64>,
+    /// (net income - operating cash flow) / total assets; high values mean earnings the cash
+    /// flow doesn't back up
+    pub accrual_ratio: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AccrualHistory {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// Most recent fiscal year
+    pub latest: Option<AccrualYear>,
+    /// Oldest first
+    pub history: Vec<AccrualYear>,
+}
+
+/// A stock's accrual ratio for every fiscal year with an income statement
+pub async fn compute_accruals(pool: &SqlitePool, stock_id: i64, symbol: String) -> Result<AccrualHistory> {
+    let query = format!("SELECT * FROM ({}) WHERE stock_id = ? ORDER BY fiscal_year", ANNUAL_ACCRUALS);
+    let rows = sqlx::query(&query).bind(stock_id).fetch_all(pool).await?;
+
+    let history: Vec<AccrualYear> = rows
+        .iter()
+        .map(|row| AccrualYear {
+            fiscal_year: row.get("fiscal_year"),
+            report_date: row.get("report_date"),
+            net_income: row.get("net_income"),
+            operating_cash_flow: row.get("operating_cash_flow"),
+            total_assets: row.get("total_assets"),
+            accrual_ratio: row.get("accrual_ratio"),
+        })
+        .collect();
+
+    Ok(AccrualHistory { stock_id, symbol, latest: history.last().cloned(), history })
+}
+
+/// Restrict a screening query to stocks whose latest fiscal year's accrual ratio is at most
+/// `max_ratio`. Stocks without a ratio for that year are kept.
+pub fn push_max_accrual_filter(query: &mut String, params: &mut Vec<String>, stock_id_column: &str, max_ratio: f64) {
+    query.push_str(&format!(
+        " AND {} NOT IN (
+            SELECT stock_id FROM (
+                SELECT stock_id, accrual_ratio,
+                       ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY fiscal_year DESC) AS latest_rn
+                FROM ({})
+            )
+            WHERE latest_rn = 1 AND accrual_ratio > CAST(? AS REAL)
+        )",
+        stock_id_column, ANNUAL_ACCRUALS
+    ));
+    params.push(max_ratio.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn insert_year(pool: &SqlitePool, stock_id: i64, year: i32, net_income: f64, operating_cash_flow: f64, total_assets: f64) {
+        let report_date = format!("{}-12-31", year);
+        sqlx::query("INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, net_income) VALUES (?, 'FY', ?, ?, ?)")
+            .bind(stock_id).bind(&report_date).bind(year).bind(net_income)
+            .execute(pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, operating_cash_flow)
+             VALUES (?, 'Annual', ?, ?, ?)"
+        )
+        .bind(stock_id).bind(&report_date).bind(year).bind(operating_cash_flow)
+        .execute(pool).await.unwrap();
+        sqlx::query("INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_assets) VALUES (?, 'Annual', ?, ?, ?)")
+            .bind(stock_id).bind(&report_date).bind(year).bind(total_assets)
+            .execute(pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_earnings_far_above_cash_flow_are_flagged_and_screened_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'PAPER', 'Paper Profits'), (2, 'CASH', 'Cash Co'), (3, 'BARE', 'No Statements')")
+            .execute(&pool).await.unwrap();
+
+        // 2023's 300 of earnings come with only 20 of operating cash flow
+        insert_year(&pool, 1, 2022, 100.0, 90.0, 1000.0).await;
+        insert_year(&pool, 1, 2023, 300.0, 20.0, 1000.0).await;
+        // Cash flow ahead of earnings is a negative accrual
+        insert_year(&pool, 2, 2023, 100.0, 150.0, 1000.0).await;
+
+        let paper = compute_accruals(&pool, 1, "PAPER".to_string()).await.unwrap();
+        assert_eq!(paper.history.len(), 2);
+        assert!((paper.history[0].accrual_ratio.unwrap() - 0.01).abs() < 1e-9);
+        let latest = paper.latest.unwrap();
+        assert_eq!((latest.fiscal_year, latest.net_income, latest.operating_cash_flow), (2023, Some(300.0), Some(20.0)));
+        assert!((latest.accrual_ratio.unwrap() - 0.28).abs() < 1e-9);
+
+        let cash = compute_accruals(&pool, 2, "CASH".to_string()).await.unwrap();
+        assert!((cash.latest.unwrap().accrual_ratio.unwrap() + 0.05).abs() < 1e-9);
+
+        // Only the latest year counts; a stock without statements isn't excluded
+        for (max_ratio, expected) in [(0.1, vec!["BARE", "CASH"]), (0.3, vec!["BARE", "CASH", "PAPER"])] {
+            let mut query = String::from("SELECT symbol FROM stocks WHERE 1=1");
+            let mut params = Vec::new();
+            push_max_accrual_filter(&mut query, &mut params, "id", max_ratio);
+            query.push_str(" ORDER BY symbol");
+
+            let mut sqlx_query = sqlx::query_scalar::<_, String>(&query);
+            for param in params {
+                sqlx_query = sqlx_query.bind(param);
+            }
+            assert_eq!(sqlx_query.fetch_all(&pool).await.unwrap(), expected, "max ratio {}", max_ratio);
+        }
+    }
+}
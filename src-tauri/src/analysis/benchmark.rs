@@ -0,0 +1,488 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::api::StockDataProvider;
+use crate::error::CommandError;
+use crate::models::PriceFrequency;
+use crate::tools::fx_rates::usd_value_sql;
+use crate::tools::universe_filter::shares_on_date_sql;
+
+/// Equal-weight composite of the current S&P 500 constituents
+pub const SP500_EQUAL_WEIGHT: &str = "SP500_EW";
+/// Composite weighting each constituent by its previous day's market cap
+pub const SP500_CAP_WEIGHT: &str = "SP500_CW";
+/// Level composites compound their daily returns from
+pub const COMPOSITE_BASE_VALUE: f64 = 100.0;
+const COMPOSITE_SOURCE: &str = "composite";
+/// Calendar days of prices loaded before the resume date, so each stock's first new return has
+/// a previous close across weekends and holidays
+const RETURN_LOOKBACK_DAYS: i64 = 10;
+/// Resume date for a series with nothing stored yet, before any stored price
+const EARLIEST_DATE: &str = "1900-01-01";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompositeWeighting {
+    Equal,
+    MarketCap,
+}
+
+impl CompositeWeighting {
+    /// Name the composite is stored under
+    pub fn benchmark(self) -> &'static str {
+        match self {
+            CompositeWeighting::Equal => SP500_EQUAL_WEIGHT,
+            CompositeWeighting::MarketCap => SP500_CAP_WEIGHT,
+        }
+    }
+}
+
+/// Whether `benchmark` is built from constituent prices rather than imported
+pub fn is_composite(benchmark: &str) -> bool {
+    benchmark == SP500_EQUAL_WEIGHT || benchmark == SP500_CAP_WEIGHT
+}
+
+/// One day of a benchmark series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BenchmarkPoint {
+    pub date: String,
+    /// Index level: compounded from 100 for composites, the index close for imported series
+    pub value: f64,
+    /// None on an imported series' first day
+    pub daily_return: Option<f64>,
+    /// Stocks in the day's composite return; None for imported series
+    pub constituents: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BenchmarkHistory {
+    pub benchmark: String,
+    /// 'composite' or the provider the series was imported from; None when no days are stored
+    pub source: Option<String>,
+    pub points: Vec<BenchmarkPoint>,
+}
+
+/// Stock against benchmark on one trading day, both normalized to 1.0 on the first common date
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RelativeStrengthPoint {
+    pub date: String,
+    /// Close over the close on the first common date
+    pub stock_value: f64,
+    /// Benchmark level over its level on the first common date
+    pub benchmark_value: f64,
+    /// stock_value / benchmark_value: above 1.0 the stock has outperformed since the start
+    pub relative_strength: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RelativePerformance {
+    pub stock_id: i64,
+    pub benchmark: String,
+    /// First date in the range with both a close and a benchmark level; None when there is none
+    pub base_date: Option<String>,
+    pub points: Vec<RelativeStrengthPoint>,
+}
+
+/// Normalize date-aligned (date, close, benchmark level) rows to the first row
+pub fn relative_strength(aligned: &[(String, f64, f64)]) -> Vec<RelativeStrengthPoint> {
+    let Some((_, base_close, base_level)) = aligned.iter().find(|(_, close, level)| *close > 0.0 && *level > 0.0) else {
+        return Vec::new();
+    };
+    aligned
+        .iter()
+        .skip_while(|(_, close, level)| !(*close > 0.0 && *level > 0.0))
+        .map(|(date, close, level)| {
+            let stock_value = close / base_close;
+            let benchmark_value = level / base_level;
+            RelativeStrengthPoint {
+                date: date.clone(),
+                stock_value,
+                benchmark_value,
+                relative_strength: stock_value / benchmark_value,
+            }
+        })
+        .collect()
+}
+
+/// A stock's cumulative return relative to `benchmark` between `start` and `end`, on the trading
+/// days both have. A stock listed after `start` is normalized at its first common date.
+pub async fn compute_relative_performance(
+    pool: &SqlitePool,
+    stock_id: i64,
+    benchmark: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<RelativePerformance> {
+    let aligned: Vec<(String, f64, f64)> = sqlx::query_as(
+        "SELECT p.date, p.close_price, b.value
+         FROM daily_prices p
+         JOIN benchmark_prices b ON b.benchmark = ?2 AND b.date = p.date
+         WHERE p.stock_id = ?1 AND p.date BETWEEN ?3 AND ?4
+         ORDER BY p.date"
+    )
+    .bind(stock_id)
+    .bind(benchmark)
+    .bind(start.format("%Y-%m-%d").to_string())
+    .bind(end.format("%Y-%m-%d").to_string())
+    .fetch_all(pool)
+    .await?;
+
+    let points = relative_strength(&aligned);
+    Ok(RelativePerformance {
+        stock_id,
+        benchmark: benchmark.to_string(),
+        base_date: points.first().map(|point| point.date.clone()),
+        points,
+    })
+}
+
+/// The day before a series' last stored day, and its level: where an update resumes. The last
+/// day is rebuilt because a refresh may add closes for it after it was first stored.
+async fn resume_point(pool: &SqlitePool, benchmark: &str) -> Result<Option<(String, f64)>> {
+    let latest: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT date, value FROM benchmark_prices WHERE benchmark = ? ORDER BY date DESC LIMIT 2"
+    )
+    .bind(benchmark)
+    .fetch_all(pool)
+    .await?;
+    Ok(latest.into_iter().nth(1))
+}
+
+/// Extend a composite from its last stored day through the latest prices. Each day's return is
+/// the equal-weight or previous-day-market-cap-weighted mean of the constituents' close-to-close
+/// returns, market cap being that day's close × shares in USD; a stock without a previous close
+/// that day is left out, as are days with no weights for the cap-weighted series. Membership is today's S&P 500 list, so older history carries
+/// survivorship bias. Returns the number of days written.
+pub async fn update_composite_benchmark(pool: &SqlitePool, weighting: CompositeWeighting) -> Result<i64> {
+    let benchmark = weighting.benchmark();
+    let resume = resume_point(pool, benchmark).await?;
+    let (since, mut value) = match &resume {
+        Some((date, value)) => (date.as_str(), *value),
+        None => (EARLIEST_DATE, COMPOSITE_BASE_VALUE),
+    };
+
+    // Share lookups are only worth their cost for the cap-weighted series
+    let market_cap = match weighting {
+        CompositeWeighting::Equal => "NULL".to_string(),
+        CompositeWeighting::MarketCap => {
+            usd_value_sql(&format!("p.close_price * {}", shares_on_date_sql("p.stock_id", "p.date")), "p.currency", "p.date")
+        }
+    };
+    let query = format!(
+        "WITH bars AS (
+            SELECT p.date, p.close_price,
+                   LAG(p.close_price) OVER w AS prev_close,
+                   LAG({}) OVER w AS prev_market_cap
+            FROM daily_prices p
+            JOIN sp500_symbols sp ON sp.id = p.stock_id
+            WHERE p.date >= date(?1, '-' || ?2 || ' days')
+            WINDOW w AS (PARTITION BY p.stock_id ORDER BY p.date)
+        ),
+        returns AS (
+            SELECT date, close_price / prev_close - 1.0 AS stock_return,
+                   CASE WHEN prev_market_cap > 0 THEN prev_market_cap END AS weight
+            FROM bars
+            WHERE date > ?1 AND prev_close > 0 AND close_price > 0
+        )
+        SELECT date,
+               AVG(stock_return) AS equal_return,
+               SUM(weight * stock_return) / SUM(weight) AS cap_return,
+               COUNT(*) AS constituents,
+               COUNT(weight) AS weighted_constituents
+        FROM returns
+        GROUP BY date
+        ORDER BY date",
+        market_cap
+    );
+    let rows = sqlx::query(&query)
+        .bind(since)
+        .bind(RETURN_LOOKBACK_DAYS)
+        .fetch_all(pool)
+        .await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM benchmark_prices WHERE benchmark = ? AND date > ?")
+        .bind(benchmark)
+        .bind(since)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut written = 0;
+    for row in &rows {
+        let (daily_return, constituents): (Option<f64>, i32) = match weighting {
+            CompositeWeighting::Equal => (row.get("equal_return"), row.get("constituents")),
+            CompositeWeighting::MarketCap => (row.get("cap_return"), row.get("weighted_constituents")),
+        };
+        let Some(daily_return) = daily_return else { continue };
+        value *= 1.0 + daily_return;
+
+        sqlx::query(
+            "INSERT INTO benchmark_prices (benchmark, date, value, daily_return, constituents, source)
+             VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(benchmark)
+        .bind(row.get::<String, _>("date"))
+        .bind(value)
+        .bind(daily_return)
+        .bind(constituents)
+        .bind(COMPOSITE_SOURCE)
+        .execute(&mut *tx)
+        .await?;
+        written += 1;
+    }
+    tx.commit().await?;
+
+    Ok(written)
+}
+
+/// Extend both S&P 500 composites; returns the days written across them
+pub async fn update_composite_benchmarks(pool: &SqlitePool) -> Result<i64> {
+    let mut written = 0;
+    for weighting in [CompositeWeighting::Equal, CompositeWeighting::MarketCap] {
+        written += update_composite_benchmark(pool, weighting).await?;
+    }
+    Ok(written)
+}
+
+/// Import a real index series (Schwab's `$SPX`, say) from `provider` under `benchmark`, from
+/// `start` or the last stored day through `end`. The close is stored as the level, with
+/// close-to-close daily returns. Returns the number of days written.
+pub async fn import_index_benchmark<P: StockDataProvider + Sync + ?Sized>(
+    pool: &SqlitePool,
+    provider: &P,
+    source: &str,
+    symbol: &str,
+    benchmark: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<i64> {
+    if is_composite(benchmark) {
+        return Err(CommandError::validation("benchmark", format!("{} is built from constituent prices; import under another name", benchmark)).into());
+    }
+
+    let resume = resume_point(pool, benchmark).await?;
+    let (since, mut previous) = match &resume {
+        Some((date, value)) => (date.as_str(), Some(*value)),
+        None => (EARLIEST_DATE, None),
+    };
+    let start = match &resume {
+        Some((date, _)) => NaiveDate::parse_from_str(date, "%Y-%m-%d")?.succ_opt().unwrap_or(end),
+        None => start,
+    };
+
+    // One close per day, in date order
+    let closes: BTreeMap<String, f64> = provider
+        .get_price_history(symbol, start, end, PriceFrequency::Daily)
+        .await?
+        .into_iter()
+        .filter(|bar| bar.close > 0.0)
+        .filter_map(|bar| {
+            DateTime::from_timestamp(bar.datetime / 1000, 0).map(|datetime| (datetime.format("%Y-%m-%d").to_string(), bar.close))
+        })
+        .filter(|(date, _)| date.as_str() > since)
+        .collect();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM benchmark_prices WHERE benchmark = ? AND date > ?")
+        .bind(benchmark)
+        .bind(since)
+        .execute(&mut *tx)
+        .await?;
+
+    for (date, close) in &closes {
+        sqlx::query(
+            "INSERT INTO benchmark_prices (benchmark, date, value, daily_return, constituents, source)
+             VALUES (?, ?, ?, ?, NULL, ?)"
+        )
+        .bind(benchmark)
+        .bind(date)
+        .bind(close)
+        .bind(previous.map(|previous| close / previous - 1.0))
+        .bind(source)
+        .execute(&mut *tx)
+        .await?;
+        previous = Some(*close);
+    }
+    tx.commit().await?;
+
+    Ok(closes.len() as i64)
+}
+
+/// Stored days of `benchmark` between `start` and `end` (both optional), oldest first
+pub async fn load_benchmark_history(
+    pool: &SqlitePool,
+    benchmark: &str,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> Result<BenchmarkHistory> {
+    let rows = sqlx::query(
+        "SELECT date, value, daily_return, constituents, source
+         FROM benchmark_prices
+         WHERE benchmark = ?1 AND (?2 IS NULL OR date >= ?2) AND (?3 IS NULL OR date <= ?3)
+         ORDER BY date"
+    )
+    .bind(benchmark)
+    .bind(start.map(|date| date.format("%Y-%m-%d").to_string()))
+    .bind(end.map(|date| date.format("%Y-%m-%d").to_string()))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(BenchmarkHistory {
+        benchmark: benchmark.to_string(),
+        source: rows.first().map(|row| row.get("source")),
+        points: rows
+            .iter()
+            .map(|row| BenchmarkPoint {
+                date: row.get("date"),
+                value: row.get("value"),
+                daily_return: row.get("daily_return"),
+                constituents: row.get("constituents"),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SchwabPriceBar, SchwabQuote};
+    use crate::tests::fixtures::migrated_pool;
+
+    struct FixedHistory(Vec<(&'static str, f64)>);
+
+    #[async_trait::async_trait]
+    impl StockDataProvider for FixedHistory {
+        async fn get_quotes(&self, _symbols: &[String]) -> Result<Vec<SchwabQuote>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_price_history(
+            &self,
+            _symbol: &str,
+            from_date: NaiveDate,
+            to_date: NaiveDate,
+            frequency: PriceFrequency,
+        ) -> Result<Vec<SchwabPriceBar>> {
+            Ok(self
+                .0
+                .iter()
+                .map(|(day, close)| (NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap(), *close))
+                .filter(|(day, _)| (from_date..=to_date).contains(day))
+                .map(|(day, close)| SchwabPriceBar {
+                    datetime: day.and_hms_opt(5, 0, 0).unwrap().and_utc().timestamp_millis(),
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: 0,
+                    frequency,
+                })
+                .collect())
+        }
+    }
+
+    async fn insert_close(pool: &SqlitePool, stock_id: i64, day: &str, close: f64) {
+        sqlx::query(
+            "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+             VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id).bind(day).bind(close).bind(close).bind(close).bind(close)
+        .execute(pool).await.unwrap();
+    }
+
+    fn values(history: &BenchmarkHistory) -> Vec<(&str, f64)> {
+        history.points.iter().map(|point| (point.date.as_str(), (point.value * 1e6).round() / 1e6)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_composites_extend_incrementally_and_imports_keep_their_own_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (1, 'AAA', 'A Corp', 1), (2, 'BBB', 'B Corp', 1), (3, 'OUT', 'Not In Index', 0)")
+            .execute(&pool).await.unwrap();
+        // A: +10% then flat; B: flat then -10%. With 10 and 15 shares, B starts at three times
+        // A's market cap
+        for (id, shares) in [(1, 10.0), (2, 15.0), (3, 1000.0)] {
+            sqlx::query("INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, shares_outstanding) VALUES (?, 'Annual', '2023-12-31', 2023, ?)")
+                .bind(id).bind(shares)
+                .execute(&pool).await.unwrap();
+        }
+        for (day, a, b) in [("2024-01-02", 10.0, 20.0), ("2024-01-03", 11.0, 20.0), ("2024-01-04", 11.0, 18.0)] {
+            insert_close(&pool, 1, day, a).await;
+            insert_close(&pool, 2, day, b).await;
+            insert_close(&pool, 3, day, 1.0 + a).await;
+        }
+
+        assert_eq!(update_composite_benchmarks(&pool).await.unwrap(), 4);
+        let equal = load_benchmark_history(&pool, SP500_EQUAL_WEIGHT, None, None).await.unwrap();
+        assert_eq!(equal.source.as_deref(), Some("composite"));
+        assert_eq!(values(&equal), vec![("2024-01-03", 105.0), ("2024-01-04", 99.75)]);
+        assert_eq!(equal.points[0].constituents, Some(2));
+        // (100 × 10% + 300 × 0%) / 400, then on the previous day's caps (110 × 0% + 300 × -10%) / 410
+        let cap = load_benchmark_history(&pool, SP500_CAP_WEIGHT, None, None).await.unwrap();
+        assert_eq!(values(&cap), vec![("2024-01-03", 102.5), ("2024-01-04", 95.0)]);
+
+        // A new day extends the series, rebuilding only the last stored day
+        insert_close(&pool, 1, "2024-01-05", 12.1).await;
+        insert_close(&pool, 2, "2024-01-05", 18.0).await;
+        assert_eq!(update_composite_benchmark(&pool, CompositeWeighting::Equal).await.unwrap(), 2);
+        let equal = load_benchmark_history(&pool, SP500_EQUAL_WEIGHT, None, None).await.unwrap();
+        assert_eq!(values(&equal)[2], ("2024-01-05", 104.7375));
+        let from_jan_4 = NaiveDate::from_ymd_opt(2024, 1, 4);
+        let range = load_benchmark_history(&pool, SP500_EQUAL_WEIGHT, from_jan_4, from_jan_4).await.unwrap();
+        assert_eq!(values(&range), vec![("2024-01-04", 99.75)]);
+
+        let index = FixedHistory(vec![("2024-01-02", 4000.0), ("2024-01-03", 4100.0)]);
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+        assert!(import_index_benchmark(&pool, &index, "schwab", "$SPX", SP500_EQUAL_WEIGHT, start, end).await.is_err());
+        assert_eq!(import_index_benchmark(&pool, &index, "schwab", "$SPX", "SPX", start, end).await.unwrap(), 2);
+        let spx = load_benchmark_history(&pool, "SPX", None, None).await.unwrap();
+        assert_eq!(spx.source.as_deref(), Some("schwab"));
+        assert_eq!(spx.points[0].daily_return, None);
+        assert!((spx.points[1].daily_return.unwrap() - 0.025).abs() < 1e-12);
+        assert_eq!(values(&load_benchmark_history(&pool, SP500_EQUAL_WEIGHT, None, None).await.unwrap()).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_relative_performance_starts_at_first_common_date() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'NEW', 'Recent Listing')")
+            .execute(&pool).await.unwrap();
+        for (day, level) in [("2024-01-02", 4000.0), ("2024-01-03", 4040.0), ("2024-01-04", 4080.0), ("2024-01-05", 4200.0)] {
+            sqlx::query("INSERT INTO benchmark_prices (benchmark, date, value, source) VALUES ('SPX', ?, ?, 'schwab')")
+                .bind(day).bind(level)
+                .execute(&pool).await.unwrap();
+        }
+        // Listed on the 3rd; no benchmark level on the 8th
+        for (day, close) in [("2024-01-03", 20.0), ("2024-01-04", 22.0), ("2024-01-05", 21.0), ("2024-01-08", 25.0)] {
+            insert_close(&pool, 1, day, close).await;
+        }
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let performance = compute_relative_performance(&pool, 1, "SPX", start, end).await.unwrap();
+        assert_eq!(performance.base_date.as_deref(), Some("2024-01-03"));
+        let dates: Vec<&str> = performance.points.iter().map(|point| point.date.as_str()).collect();
+        assert_eq!(dates, vec!["2024-01-03", "2024-01-04", "2024-01-05"]);
+        assert_eq!(performance.points[0].relative_strength, 1.0);
+        // +10% against the benchmark's 4080 / 4040
+        let second = &performance.points[1];
+        assert!((second.stock_value - 1.1).abs() < 1e-12);
+        assert!((second.relative_strength - 1.1 / (4080.0 / 4040.0)).abs() < 1e-12);
+
+        let empty = compute_relative_performance(&pool, 1, "SP500_EW", start, end).await.unwrap();
+        assert_eq!((empty.base_date, empty.points.len()), (None, 0));
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::analysis::valuation_ratios::STATEMENT_FREE_CASH_FLOW;
+use crate::tools::dividends::{load_dividend_records, DividendRecord};
+
+/// Fiscal-year dividends with that year's dividends paid, net income and free cash flow
+/// (the latest report when restated)
+fn annual_dividend_inputs() -> String {
+    format!(
+        "WITH cash_flow AS (
+            SELECT c.stock_id, c.fiscal_year, ABS(c.dividends_paid) AS dividends_paid, {fcf} AS free_cash_flow,
+                   ROW_NUMBER() OVER (PARTITION BY c.stock_id, c.fiscal_year ORDER BY c.report_date DESC) AS rn
+            FROM cash_flow_statements c
+            WHERE c.period_type = 'Annual' AND c.fiscal_year IS NOT NULL
+        ),
+        income AS (
+            SELECT stock_id, fiscal_year, net_income,
+                   ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+            FROM income_statements
+            WHERE period_type = 'FY' AND fiscal_year IS NOT NULL
+        )
+        SELECT d.stock_id, d.fiscal_year, d.period_end, d.amount_per_share, d.special_amount, d.source,
+               cf.dividends_paid, cf.free_cash_flow, i.net_income
+        FROM dividends d
+        LEFT JOIN cash_flow cf ON cf.stock_id = d.stock_id AND cf.fiscal_year = d.fiscal_year AND cf.rn = 1
+        LEFT JOIN income i ON i.stock_id = d.stock_id AND i.fiscal_year = d.fiscal_year AND i.rn = 1
+        WHERE d.period_type = 'Annual' AND d.fiscal_year IS NOT NULL",
+        fcf = STATEMENT_FREE_CASH_FLOW
+    )
+}
+
+/// Dividends of one fiscal year, per share in today's shares (adjusted for later splits)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DividendYear {
+    pub fiscal_year: i32,
+    pub period_end: String,
+    pub regular_per_share: f64,
+    pub special_per_share: f64,
+    /// Percent change in the regular dividend from the previous fiscal year
+    pub growth_pct: Option<f64>,
+    /// Dividends paid as a percent of net income; None without positive earnings
+    pub payout_ratio_earnings: Option<f64>,
+    /// Dividends paid as a percent of free cash flow; None without positive free cash flow
+    pub payout_ratio_fcf: Option<f64>,
+    /// sec_declared, sec_paid or cash_flow_derived
+    pub source: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DividendHistory {
+    pub stock_id: i64,
+    /// Every stored quarter and fiscal year, oldest first, as reported
+    pub records: Vec<DividendRecord>,
+    /// Oldest first
+    pub years: Vec<DividendYear>,
+    /// Most recent fiscal years in a row whose regular dividend beat the year before
+    pub consecutive_increase_years: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DividendGrower {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub company_name: String,
+    pub sector: Option<String>,
+    pub consecutive_increase_years: i32,
+    pub latest: DividendYear,
+    /// Latest regular dividend as a percent of the latest close
+    pub dividend_yield: Option<f64>,
+}
+
+/// Fiscal-year dividend inputs as stored
+#[derive(Debug, Clone, Default)]
+pub struct DividendYearInputs {
+    pub fiscal_year: i32,
+    pub period_end: String,
+    pub amount_per_share: f64,
+    pub special_amount: f64,
+    pub source: String,
+    pub dividends_paid: Option<f64>,
+    pub net_income: Option<f64>,
+    pub free_cash_flow: Option<f64>,
+}
+
+/// Split ratios (new shares per old) by ex-date
+type Splits = Vec<(String, f64)>;
+
+/// Dividend years, oldest first, with growth against the previous year when it is the fiscal
+/// year before. Amounts are divided by the splits with ex-dates after the period end.
+pub fn compute_dividend_years(inputs: &[DividendYearInputs], splits: &[(String, f64)]) -> Vec<DividendYear> {
+    let mut years: Vec<DividendYear> = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let split_factor: f64 = splits
+            .iter()
+            .filter(|(ex_date, _)| ex_date.as_str() > input.period_end.as_str())
+            .map(|(_, ratio)| ratio)
+            .product();
+        let regular_per_share = (input.amount_per_share - input.special_amount) / split_factor;
+
+        let growth_pct = years
+            .last()
+            .filter(|prior| prior.fiscal_year == input.fiscal_year - 1 && prior.regular_per_share > 0.0)
+            .map(|prior| (regular_per_share / prior.regular_per_share - 1.0) * 100.0);
+        let payout = |base: Option<f64>| {
+            input
+                .dividends_paid
+                .zip(base.filter(|base| *base > 0.0))
+                .map(|(paid, base)| paid / base * 100.0)
+        };
+
+        years.push(DividendYear {
+            fiscal_year: input.fiscal_year,
+            period_end: input.period_end.clone(),
+            regular_per_share,
+            special_per_share: input.special_amount / split_factor,
+            growth_pct,
+            payout_ratio_earnings: payout(input.net_income),
+            payout_ratio_fcf: payout(input.free_cash_flow),
+            source: input.source.clone(),
+        });
+    }
+    years
+}
+
+/// Latest fiscal years in a row, counting back, whose regular dividend rose from the year before
+pub fn consecutive_increase_years(years: &[DividendYear]) -> i32 {
+    years
+        .iter()
+        .rev()
+        .take_while(|year| year.growth_pct.is_some_and(|growth| growth > 0.0))
+        .count() as i32
+}
+
+/// Dividend years per stock, oldest first; all stocks when `stock_id` is None
+async fn load_dividend_years(pool: &SqlitePool, stock_id: Option<i64>) -> Result<BTreeMap<i64, Vec<DividendYear>>> {
+    let query = format!(
+        "SELECT * FROM ({}) WHERE ?1 IS NULL OR stock_id = ?1 ORDER BY stock_id, period_end",
+        annual_dividend_inputs()
+    );
+    let rows = sqlx::query(&query).bind(stock_id).fetch_all(pool).await?;
+    let split_rows = sqlx::query(
+        "SELECT stock_id, ex_date, ratio FROM corporate_actions
+         WHERE action_type = 'split' AND (?1 IS NULL OR stock_id = ?1) ORDER BY ex_date"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut splits: BTreeMap<i64, Splits> = BTreeMap::new();
+    for row in split_rows {
+        splits.entry(row.get("stock_id")).or_default().push((row.get("ex_date"), row.get("ratio")));
+    }
+    let mut inputs: BTreeMap<i64, Vec<DividendYearInputs>> = BTreeMap::new();
+    for row in rows {
+        inputs.entry(row.get("stock_id")).or_default().push(DividendYearInputs {
+            fiscal_year: row.get("fiscal_year"),
+            period_end: row.get("period_end"),
+            amount_per_share: row.get("amount_per_share"),
+            special_amount: row.get("special_amount"),
+            source: row.get("source"),
+            dividends_paid: row.get("dividends_paid"),
+            net_income: row.get("net_income"),
+            free_cash_flow: row.get("free_cash_flow"),
+        });
+    }
+
+    Ok(inputs
+        .into_iter()
+        .map(|(id, inputs)| {
+            let stock_splits = splits.get(&id).map(Vec::as_slice).unwrap_or_default();
+            (id, compute_dividend_years(&inputs, stock_splits))
+        })
+        .collect())
+}
+
+/// Stored dividends of a stock with fiscal-year growth and payout ratios
+pub async fn load_dividend_history(pool: &SqlitePool, stock_id: i64) -> Result<DividendHistory> {
+    let records = load_dividend_records(pool, stock_id).await?;
+    let years = load_dividend_years(pool, Some(stock_id)).await?.remove(&stock_id).unwrap_or_default();
+    Ok(DividendHistory {
+        stock_id,
+        records,
+        consecutive_increase_years: consecutive_increase_years(&years),
+        years,
+    })
+}
+
+/// S&P 500 stocks that raised their regular dividend at least `min_years_of_growth` fiscal years
+/// running and yield at least `min_yield` percent, longest streak first
+pub async fn screen_dividend_growers(
+    pool: &SqlitePool,
+    min_years_of_growth: i32,
+    min_yield: Option<f64>,
+    limit: i32,
+) -> Result<Vec<DividendGrower>> {
+    let mut years = load_dividend_years(pool, None).await?;
+    let stocks = sqlx::query(
+        "SELECT s.id, s.symbol, s.company_name, s.sector,
+                (SELECT p.close_price FROM daily_prices p WHERE p.stock_id = s.id ORDER BY p.date DESC LIMIT 1) AS close_price
+         FROM stocks s
+         INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut growers: Vec<DividendGrower> = stocks
+        .iter()
+        .filter_map(|row| {
+            let stock_id: i64 = row.get("id");
+            let history = years.remove(&stock_id)?;
+            let streak = consecutive_increase_years(&history);
+            let latest = history.last()?.clone();
+            let close_price: Option<f64> = row.get("close_price");
+            let dividend_yield = close_price
+                .filter(|price| *price > 0.0)
+                .map(|price| latest.regular_per_share / price * 100.0);
+
+            let yield_ok = min_yield.map_or(true, |min| dividend_yield.is_some_and(|y| y >= min));
+            (streak >= min_years_of_growth && yield_ok).then(|| DividendGrower {
+                stock_id,
+                symbol: row.get("symbol"),
+                company_name: row.get("company_name"),
+                sector: row.get("sector"),
+                consecutive_increase_years: streak,
+                latest,
+                dividend_yield,
+            })
+        })
+        .collect();
+
+    growers.sort_by(|a, b| {
+        b.consecutive_increase_years
+            .cmp(&a.consecutive_increase_years)
+            .then_with(|| b.dividend_yield.unwrap_or(0.0).total_cmp(&a.dividend_yield.unwrap_or(0.0)))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    growers.truncate(limit.max(0) as usize);
+    Ok(growers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    fn year(fiscal_year: i32, amount: f64, special: f64) -> DividendYearInputs {
+        DividendYearInputs {
+            fiscal_year,
+            period_end: format!("{}-12-31", fiscal_year),
+            amount_per_share: amount,
+            special_amount: special,
+            source: "sec_declared".to_string(),
+            ..DividendYearInputs::default()
+        }
+    }
+
+    #[test]
+    fn test_special_dividends_and_splits_dont_break_the_streak() {
+        let inputs = [
+            year(2019, 2.0, 0.0),
+            year(2020, 2.2, 0.0),
+            // 2021 pays a 10.00 special on top of 2.40
+            year(2021, 12.4, 10.0),
+            year(2022, 2.6, 0.0),
+            // 2-for-1 split in mid-2023; 1.40 a share after it is 2.80 before
+            year(2023, 1.4, 0.0),
+        ];
+        let years = compute_dividend_years(&inputs, &[("2023-06-01".to_string(), 2.0)]);
+        assert!((years[2].regular_per_share - 1.2).abs() < 1e-9);
+        assert_eq!(years[2].special_per_share, 5.0);
+        assert_eq!(years.iter().map(|y| y.growth_pct.map(|g| g.round())).collect::<Vec<_>>(), vec![None, Some(10.0), Some(9.0), Some(8.0), Some(8.0)]);
+        assert_eq!(consecutive_increase_years(&years), 4);
+
+        // Without the special flag 2022 reads as a cut
+        let unflagged = compute_dividend_years(&[year(2021, 12.4, 0.0), year(2022, 2.6, 0.0)], &[]);
+        assert_eq!(consecutive_increase_years(&unflagged), 0);
+
+        // A missing fiscal year restarts the count
+        let gap = compute_dividend_years(&[year(2019, 1.0, 0.0), year(2020, 1.1, 0.0), year(2022, 1.2, 0.0)], &[]);
+        assert_eq!(consecutive_increase_years(&gap), 0);
+    }
+
+    #[test]
+    fn test_payout_ratios_need_positive_bases() {
+        let inputs = DividendYearInputs {
+            dividends_paid: Some(50.0),
+            net_income: Some(200.0),
+            free_cash_flow: Some(-10.0),
+            ..year(2023, 1.0, 0.0)
+        };
+        let years = compute_dividend_years(&[inputs], &[]);
+        assert_eq!((years[0].payout_ratio_earnings, years[0].payout_ratio_fcf), (Some(25.0), None));
+    }
+
+    #[tokio::test]
+    async fn test_growers_screen() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // (symbol, annual dividends 2019..=2023, close)
+        let stocks = [
+            ("RAISER", [1.0, 1.1, 1.2, 1.3, 1.4], 35.0),
+            ("LOWYIELD", [1.0, 1.1, 1.2, 1.3, 1.4], 700.0),
+            ("CUTTER", [1.0, 1.1, 0.5, 0.6, 0.7], 20.0),
+        ];
+        for (id, (symbol, amounts, close)) in stocks.into_iter().enumerate() {
+            let id = id as i64 + 1;
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (?, ?, ?, 1)")
+                .bind(id).bind(symbol).bind(symbol)
+                .execute(&pool).await.unwrap();
+            for (year, amount) in (2019..).zip(amounts) {
+                sqlx::query(
+                    "INSERT INTO dividends (stock_id, period_type, period_start, period_end, fiscal_year, amount_per_share, source)
+                     VALUES (?, 'Annual', ?, ?, ?, ?, 'sec_declared')"
+                )
+                .bind(id).bind(format!("{}-01-01", year)).bind(format!("{}-12-31", year)).bind(year).bind(amount)
+                .execute(&pool).await.unwrap();
+            }
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?, '2024-03-01', ?, ?, ?, ?)"
+            )
+            .bind(id).bind(close).bind(close).bind(close).bind(close)
+            .execute(&pool).await.unwrap();
+        }
+
+        let history = load_dividend_history(&pool, 1).await.unwrap();
+        assert_eq!((history.records.len(), history.consecutive_increase_years), (5, 4));
+
+        let growers = screen_dividend_growers(&pool, 3, Some(1.0), 10).await.unwrap();
+        assert_eq!(growers.iter().map(|g| g.symbol.as_str()).collect::<Vec<_>>(), vec!["RAISER"]);
+        assert!((growers[0].dividend_yield.unwrap() - 4.0).abs() < 1e-9);
+
+        let growers = screen_dividend_growers(&pool, 2, None, 10).await.unwrap();
+        assert_eq!(growers.iter().map(|g| g.symbol.as_str()).collect::<Vec<_>>(), vec!["RAISER", "LOWYIELD", "CUTTER"]);
+    }
+}
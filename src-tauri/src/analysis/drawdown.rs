@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::error::CommandError;
+
+/// Largest peak-to-trough decline in closing price over a date range
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MaxDrawdown {
+    pub stock_id: i64,
+    pub start_date: String,
+    pub end_date: String,
+    /// Decline from peak to trough as a positive percentage; 0 when prices never fell
+    pub max_drawdown_pct: f64,
+    pub peak_date: String,
+    pub peak_price: f64,
+    pub trough_date: String,
+    pub trough_price: f64,
+}
+
+/// (peak index, trough index, drawdown fraction) over closes in date order
+pub fn max_drawdown(closes: &[f64]) -> Option<(usize, usize, f64)> {
+    let first = *closes.first()?;
+    let mut peak_idx = 0;
+    let mut peak = first;
+    let mut worst = (0, 0, 0.0);
+
+    for (i, &close) in closes.iter().enumerate() {
+        if close > peak {
+            peak = close;
+            peak_idx = i;
+        } else if peak > 0.0 {
+            let drawdown = (peak - close) / peak;
+            if drawdown > worst.2 {
+                worst = (peak_idx, i, drawdown);
+            }
+        }
+    }
+
+    Some(worst)
+}
+
+pub async fn compute_max_drawdown(pool: &SqlitePool, stock_id: i64, start_date: &str, end_date: &str) -> Result<MaxDrawdown> {
+    let rows = sqlx::query(
+        "SELECT date, close_price FROM daily_prices
+         WHERE stock_id = ? AND date >= ? AND date <= ? AND close_price IS NOT NULL
+         ORDER BY date ASC"
+    )
+    .bind(stock_id)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    let dates: Vec<String> = rows.iter().map(|row| row.get::<String, _>("date")).collect();
+    let closes: Vec<f64> = rows.iter().map(|row| row.get::<f64, _>("close_price")).collect();
+
+    let Some((peak_idx, trough_idx, drawdown)) = max_drawdown(&closes) else {
+        return Err(CommandError::not_found(
+            "daily_prices",
+            format!("No prices for stock {} between {} and {}", stock_id, start_date, end_date),
+        )
+        .into());
+    };
+
+    Ok(MaxDrawdown {
+        stock_id,
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        max_drawdown_pct: drawdown * 100.0,
+        peak_date: dates[peak_idx].clone(),
+        peak_price: closes[peak_idx],
+        trough_date: dates[trough_idx].clone(),
+        trough_price: closes[trough_idx],
+    })
+}
+
+/// Episodes shallower than this, percent, are left out of the episode list and recovery statistics
+pub const MIN_EPISODE_DEPTH_PCT: f64 = 5.0;
+
+/// One decline from a peak, through its trough, to the first close back at the peak
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DrawdownEpisode {
+    pub peak_date: String,
+    pub peak_value: f64,
+    pub trough_date: String,
+    pub trough_value: f64,
+    /// Decline from peak to trough as a positive percentage
+    pub depth_pct: f64,
+    /// First date back at or above the peak; None if it hadn't recovered by the end of the range
+    pub recovery_date: Option<String>,
+    /// Calendar days from peak to trough
+    pub days_to_trough: i64,
+    /// Calendar days from trough to recovery
+    pub days_to_recover: Option<i64>,
+}
+
+/// Drawdown and recovery statistics of a close (or portfolio value) series over a date range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DrawdownAnalysis {
+    pub start_date: String,
+    pub end_date: String,
+    /// Deepest episode, recovered or not; None when the series never fell below a peak
+    pub max_drawdown: Option<DrawdownEpisode>,
+    /// Decline of the last value from the running peak as a positive percentage
+    pub current_drawdown_pct: f64,
+    pub current_peak_date: String,
+    pub current_peak_value: f64,
+    /// The running peak predates the range: the series started below an earlier high
+    pub started_in_drawdown: bool,
+    /// Episodes at least `MIN_EPISODE_DEPTH_PCT` deep, oldest first
+    pub episodes: Vec<DrawdownEpisode>,
+    pub recovered_episodes: i32,
+    pub avg_days_to_recover: Option<f64>,
+    pub max_days_to_recover: Option<i64>,
+}
+
+/// A stock and its share of a portfolio; weights needn't sum to one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PortfolioHolding {
+    pub stock_id: i64,
+    pub weight: f64,
+}
+
+fn episode(peak: (NaiveDate, f64), trough: (NaiveDate, f64), recovery: Option<NaiveDate>) -> DrawdownEpisode {
+    DrawdownEpisode {
+        peak_date: peak.0.format("%Y-%m-%d").to_string(),
+        peak_value: peak.1,
+        trough_date: trough.0.format("%Y-%m-%d").to_string(),
+        trough_value: trough.1,
+        depth_pct: if peak.1 > 0.0 { (peak.1 - trough.1) / peak.1 * 100.0 } else { 0.0 },
+        recovery_date: recovery.map(|date| date.format("%Y-%m-%d").to_string()),
+        days_to_trough: (trough.0 - peak.0).num_days(),
+        days_to_recover: recovery.map(|date| (date - trough.0).num_days()),
+    }
+}
+
+/// Drawdown episodes of `series` (date order) in one pass. `prior_peak` is the high before the
+/// series starts, if any; when it's above the first value, the series starts mid-drawdown and
+/// the first episode is measured from it.
+pub fn analyze_drawdowns(series: &[(NaiveDate, f64)], prior_peak: Option<(NaiveDate, f64)>) -> Option<DrawdownAnalysis> {
+    let (&(start, first_value), &(end, last_value)) = (series.first()?, series.last()?);
+    let mut peak = prior_peak.filter(|(_, value)| *value > first_value).unwrap_or((start, first_value));
+    let started_in_drawdown = peak.0 < start;
+
+    let mut trough: Option<(NaiveDate, f64)> = None;
+    let mut all_episodes = Vec::new();
+    for &(date, value) in series {
+        if value >= peak.1 {
+            if let Some(low) = trough.take() {
+                all_episodes.push(episode(peak, low, Some(date)));
+            }
+            peak = (date, value);
+        } else if trough.map_or(true, |(_, low)| value < low) {
+            trough = Some((date, value));
+        }
+    }
+    if let Some(low) = trough {
+        all_episodes.push(episode(peak, low, None));
+    }
+
+    let max_drawdown = all_episodes.iter().max_by(|a, b| a.depth_pct.total_cmp(&b.depth_pct)).cloned();
+    let episodes: Vec<DrawdownEpisode> =
+        all_episodes.into_iter().filter(|e| e.depth_pct >= MIN_EPISODE_DEPTH_PCT).collect();
+    let recoveries: Vec<i64> = episodes.iter().filter_map(|e| e.days_to_recover).collect();
+
+    Some(DrawdownAnalysis {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        max_drawdown,
+        current_drawdown_pct: if peak.1 > 0.0 { (peak.1 - last_value) / peak.1 * 100.0 } else { 0.0 },
+        current_peak_date: peak.0.format("%Y-%m-%d").to_string(),
+        current_peak_value: peak.1,
+        started_in_drawdown,
+        recovered_episodes: recoveries.len() as i32,
+        avg_days_to_recover: (!recoveries.is_empty()).then(|| recoveries.iter().sum::<i64>() as f64 / recoveries.len() as f64),
+        max_days_to_recover: recoveries.iter().copied().max(),
+        episodes,
+    })
+}
+
+/// Drawdowns of a stock's closes between two dates, measured from the highest earlier close
+/// when the range opens below it
+pub async fn compute_drawdown_analysis(pool: &SqlitePool, stock_id: i64, start: NaiveDate, end: NaiveDate) -> Result<DrawdownAnalysis> {
+    let series: Vec<(NaiveDate, f64)> = sqlx::query_as(
+        "SELECT date, close_price FROM daily_prices
+         WHERE stock_id = ? AND date >= ? AND date <= ? AND close_price IS NOT NULL
+         ORDER BY date ASC"
+    )
+    .bind(stock_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+    let prior_peak: Option<(NaiveDate, f64)> = sqlx::query_as(
+        "SELECT date, close_price FROM daily_prices
+         WHERE stock_id = ? AND date < ? AND close_price IS NOT NULL
+         ORDER BY close_price DESC, date DESC LIMIT 1"
+    )
+    .bind(stock_id)
+    .bind(start)
+    .fetch_optional(pool)
+    .await?;
+
+    analyze_drawdowns(&series, prior_peak).ok_or_else(|| {
+        CommandError::not_found("daily_prices", format!("No prices for stock {} between {} and {}", stock_id, start, end)).into()
+    })
+}
+
+/// Drawdowns of a buy-and-hold portfolio bought at the first date every holding has a close,
+/// each holding's weight of the starting value. Only dates with a close for every holding count.
+pub async fn compute_portfolio_drawdown_analysis(
+    pool: &SqlitePool,
+    holdings: &[PortfolioHolding],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<DrawdownAnalysis> {
+    let placeholders = vec!["?"; holdings.len()].join(", ");
+    let query = format!(
+        "SELECT stock_id, date, close_price FROM daily_prices
+         WHERE stock_id IN ({}) AND date >= ? AND date <= ? AND close_price > 0
+         ORDER BY date ASC",
+        placeholders
+    );
+    let mut sqlx_query = sqlx::query_as::<_, (i64, NaiveDate, f64)>(&query);
+    for holding in holdings {
+        sqlx_query = sqlx_query.bind(holding.stock_id);
+    }
+    let rows = sqlx_query.bind(start).bind(end).fetch_all(pool).await?;
+
+    let mut by_date: BTreeMap<NaiveDate, Vec<Option<f64>>> = BTreeMap::new();
+    for (stock_id, date, close) in rows {
+        let closes = by_date.entry(date).or_insert_with(|| vec![None; holdings.len()]);
+        for (i, holding) in holdings.iter().enumerate() {
+            if holding.stock_id == stock_id {
+                closes[i] = Some(close);
+            }
+        }
+    }
+
+    let total_weight: f64 = holdings.iter().map(|holding| holding.weight).sum();
+    let mut base: Option<Vec<f64>> = None;
+    let mut series = Vec::with_capacity(by_date.len());
+    for (date, closes) in by_date {
+        let Some(closes) = closes.into_iter().collect::<Option<Vec<f64>>>() else {
+            continue;
+        };
+        let base = base.get_or_insert_with(|| closes.clone());
+        let value = holdings
+            .iter()
+            .zip(closes.iter().zip(base.iter()))
+            .map(|(holding, (close, first))| holding.weight / total_weight * close / first)
+            .sum::<f64>();
+        series.push((date, value));
+    }
+
+    analyze_drawdowns(&series, None).ok_or_else(|| {
+        CommandError::not_found("daily_prices", format!("No date between {} and {} with prices for every holding", start, end)).into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_max_drawdown_of_known_decline() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE daily_prices (stock_id INTEGER, date DATE, close_price REAL)")
+            .execute(&pool).await.unwrap();
+
+        // Peak 100 on 01-04, trough 70 on 01-08 (30%); a later 20% dip from 110 is smaller
+        let series = [
+            ("2024-01-02", 90.0),
+            ("2024-01-03", 95.0),
+            ("2024-01-04", 100.0),
+            ("2024-01-05", 85.0),
+            ("2024-01-08", 70.0),
+            ("2024-01-09", 80.0),
+            ("2024-01-10", 110.0),
+            ("2024-01-11", 88.0),
+        ];
+        for (date, close) in series {
+            sqlx::query("INSERT INTO daily_prices (stock_id, date, close_price) VALUES (1, ?, ?)")
+                .bind(date)
+                .bind(close)
+                .execute(&pool).await.unwrap();
+        }
+
+        let drawdown = compute_max_drawdown(&pool, 1, "2024-01-01", "2024-01-31").await.unwrap();
+        assert!((drawdown.max_drawdown_pct - 30.0).abs() < 1e-9);
+        assert_eq!(drawdown.peak_date, "2024-01-04");
+        assert_eq!(drawdown.trough_date, "2024-01-08");
+        assert_eq!(drawdown.peak_price, 100.0);
+        assert_eq!(drawdown.trough_price, 70.0);
+
+        let empty = compute_max_drawdown(&pool, 1, "2023-01-01", "2023-12-31").await;
+        assert!(matches!(
+            empty.unwrap_err().downcast_ref::<CommandError>(),
+            Some(CommandError::NotFound { .. })
+        ));
+    }
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_episodes_from_series_starting_mid_drawdown() {
+        // The earlier high of 120 stands until 01-05; a shallow 2% dip after it doesn't count
+        let series = [
+            (day("2024-01-02"), 100.0),
+            (day("2024-01-03"), 90.0),
+            (day("2024-01-05"), 121.0),
+            (day("2024-01-08"), 118.58),
+            (day("2024-01-09"), 125.0),
+            (day("2024-01-10"), 100.0),
+            (day("2024-01-12"), 110.0),
+        ];
+        let analysis = analyze_drawdowns(&series, Some((day("2023-12-01"), 120.0))).unwrap();
+        assert!(analysis.started_in_drawdown);
+        assert_eq!(analysis.episodes.len(), 2);
+
+        let first = &analysis.episodes[0];
+        assert_eq!((first.peak_date.as_str(), first.trough_date.as_str()), ("2023-12-01", "2024-01-03"));
+        assert!((first.depth_pct - 25.0).abs() < 1e-9);
+        assert_eq!((first.recovery_date.as_deref(), first.days_to_recover), (Some("2024-01-05"), Some(2)));
+
+        // Down 20% from 125 and still 12% under it at the end
+        let max = analysis.max_drawdown.unwrap();
+        assert_eq!(max, first.clone());
+        let open = &analysis.episodes[1];
+        assert!((open.depth_pct - 20.0).abs() < 1e-9);
+        assert_eq!(open.recovery_date, None);
+        assert!((analysis.current_drawdown_pct - 12.0).abs() < 1e-9);
+        assert_eq!(analysis.current_peak_date, "2024-01-09");
+        assert_eq!((analysis.recovered_episodes, analysis.max_days_to_recover), (1, Some(2)));
+
+        // A prior peak below the first close doesn't matter
+        let fresh = analyze_drawdowns(&series, Some((day("2023-12-01"), 50.0))).unwrap();
+        assert!(!fresh.started_in_drawdown);
+        assert!((fresh.episodes[0].depth_pct - 10.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_drawdown_uses_weighted_buy_and_hold_value() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE daily_prices (stock_id INTEGER, date DATE, close_price REAL)")
+            .execute(&pool).await.unwrap();
+
+        // Stock 2 has no close on 01-03, so that day is skipped
+        let closes = [
+            (1, "2024-01-02", 100.0), (2, "2024-01-02", 50.0),
+            (1, "2024-01-03", 10.0),
+            (1, "2024-01-04", 50.0), (2, "2024-01-04", 50.0),
+            (1, "2024-01-05", 100.0), (2, "2024-01-05", 55.0),
+        ];
+        for (stock_id, date, close) in closes {
+            sqlx::query("INSERT INTO daily_prices (stock_id, date, close_price) VALUES (?, ?, ?)")
+                .bind(stock_id).bind(date).bind(close)
+                .execute(&pool).await.unwrap();
+        }
+
+        // 25% / 75%: stock 1 halving takes the portfolio down 12.5%; stock 2's 10% gain then lifts it past 1
+        let holdings = [PortfolioHolding { stock_id: 1, weight: 1.0 }, PortfolioHolding { stock_id: 2, weight: 3.0 }];
+        let analysis = compute_portfolio_drawdown_analysis(&pool, &holdings, day("2024-01-01"), day("2024-01-31")).await.unwrap();
+        let max = analysis.max_drawdown.unwrap();
+        assert_eq!((max.peak_date.as_str(), max.trough_date.as_str()), ("2024-01-02", "2024-01-04"));
+        assert!((max.depth_pct - 12.5).abs() < 1e-9);
+        assert_eq!(max.recovery_date.as_deref(), Some("2024-01-05"));
+        assert!((analysis.current_peak_value - 1.075).abs() < 1e-9);
+        assert_eq!(analysis.current_drawdown_pct, 0.0);
+    }
+}
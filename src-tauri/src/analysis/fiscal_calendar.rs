@@ -0,0 +1,59 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// Period ends this many days into a month count toward the month before, so 52/53-week
+/// years ending in the first days of a month land on the month they mostly cover
+const MONTH_END_GRACE_DAYS: i64 = 15;
+
+/// Month (1-12) whose end is nearest the period end; the same rule the stock_fiscal_year_ends
+/// view applies to each stock's annual filings
+pub fn fiscal_year_end_month(period_end: NaiveDate) -> u32 {
+    (period_end - Duration::days(MONTH_END_GRACE_DAYS)).month()
+}
+
+/// Calendar year whose December 31 is nearest the period end. July through December year ends
+/// map to their own year, January through June ones to the year before, so AAPL's
+/// September 2023 year and a retailer's year ending January 2024 both compare as 2023.
+pub fn calendar_year_bucket(period_end: NaiveDate) -> i32 {
+    let shifted = period_end - Duration::days(MONTH_END_GRACE_DAYS);
+    if shifted.month() >= 7 { shifted.year() } else { shifted.year() - 1 }
+}
+
+/// SQL expression for `calendar_year_bucket` over a report date column
+pub fn calendar_year_sql(report_date_column: &str) -> String {
+    let shifted = format!("date({}, '-{} days')", report_date_column, MONTH_END_GRACE_DAYS);
+    format!(
+        "(CAST(strftime('%Y', {0}) AS INTEGER) - (CAST(strftime('%m', {0}) AS INTEGER) < 7))",
+        shifted
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::SqlitePool;
+
+    #[tokio::test]
+    async fn test_period_ends_bucket_to_nearest_calendar_year() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let query = format!("SELECT {} FROM (SELECT ? AS report_date)", calendar_year_sql("report_date"));
+
+        for (period_end, month, bucket) in [
+            ("2023-12-31", 12, 2023),
+            ("2023-09-30", 9, 2023),
+            // 53-week years ending just after the month
+            ("2023-10-01", 9, 2023),
+            ("2024-01-02", 12, 2023),
+            // Retail and mid-year ends fall to the year before
+            ("2024-01-31", 1, 2023),
+            ("2023-06-30", 6, 2022),
+            ("2023-07-31", 7, 2023),
+        ] {
+            let date = NaiveDate::parse_from_str(period_end, "%Y-%m-%d").unwrap();
+            assert_eq!(fiscal_year_end_month(date), month, "{}", period_end);
+            assert_eq!(calendar_year_bucket(date), bucket, "{}", period_end);
+
+            let sql_bucket: i32 = sqlx::query_scalar(&query).bind(period_end).fetch_one(&pool).await.unwrap();
+            assert_eq!(sql_bucket, bucket, "{}", period_end);
+        }
+    }
+}
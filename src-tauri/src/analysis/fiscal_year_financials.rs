@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use sqlx::{SqlitePool, Row};
+
+/// Annual statement figures of one fiscal year as stored, the latest report of each statement
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FiscalYearFinancials {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    pub net_income: Option<f64>,
+    pub revenue: Option<f64>,
+    pub gross_profit: Option<f64>,
+    pub total_assets: Option<f64>,
+    pub total_debt: Option<f64>,
+    pub total_equity: Option<f64>,
+    pub current_assets: Option<f64>,
+    pub current_liabilities: Option<f64>,
+    pub shares_outstanding: Option<f64>,
+    pub operating_cash_flow: Option<f64>,
+}
+
+/// A stock's latest fiscal year with an income statement and the one stored before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatestFiscalYears {
+    pub current: FiscalYearFinancials,
+    pub prior: Option<FiscalYearFinancials>,
+}
+
+/// Latest two fiscal years of one statement type for every stock (or the given ones), latest
+/// report per year, newest first within a stock
+fn latest_two_years_sql(table: &str, period_type: &str, columns: &str, stock_count: Option<usize>) -> String {
+    let stock_filter = stock_count
+        .map(|count| format!(" AND stock_id IN ({})", vec!["?"; count].join(",")))
+        .unwrap_or_default();
+    format!(
+        "SELECT * FROM (
+            SELECT *, ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY fiscal_year DESC) AS year_rank
+            FROM (
+                SELECT stock_id, fiscal_year, report_date, {columns},
+                       ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+                FROM {table}
+                WHERE period_type = '{period_type}' AND fiscal_year IS NOT NULL{stock_filter}
+            )
+            WHERE rn = 1
+        )
+        WHERE year_rank <= 2
+        ORDER BY stock_id, fiscal_year DESC"
+    )
+}
+
+async fn fetch_latest_two_years(
+    pool: &SqlitePool,
+    table: &str,
+    period_type: &str,
+    columns: &str,
+    stock_ids: Option<&[i64]>,
+) -> Result<Vec<sqlx::sqlite::SqliteRow>> {
+    let query = latest_two_years_sql(table, period_type, columns, stock_ids.map(<[i64]>::len));
+    let mut sqlx_query = sqlx::query(&query);
+    for stock_id in stock_ids.unwrap_or_default() {
+        sqlx_query = sqlx_query.bind(stock_id);
+    }
+    Ok(sqlx_query.fetch_all(pool).await?)
+}
+
+/// Latest two fiscal years for every stock with an annual income statement, or only for
+/// `stock_ids`, from one query per statement type. Years follow the income statements; balance
+/// sheet and cash flow figures are None for a year without that statement.
+pub async fn load_latest_fiscal_years(pool: &SqlitePool, stock_ids: Option<&[i64]>) -> Result<HashMap<i64, LatestFiscalYears>> {
+    if stock_ids.is_some_and(|ids| ids.is_empty()) {
+        return Ok(HashMap::new());
+    }
+
+    let mut years: HashMap<i64, Vec<FiscalYearFinancials>> = HashMap::new();
+    for row in fetch_latest_two_years(pool, "income_statements", "FY", "net_income, revenue, gross_profit", stock_ids).await? {
+        years.entry(row.get("stock_id")).or_default().push(FiscalYearFinancials {
+            fiscal_year: row.get("fiscal_year"),
+            report_date: row.get("report_date"),
+            net_income: row.get("net_income"),
+            revenue: row.get("revenue"),
+            gross_profit: row.get("gross_profit"),
+            ..Default::default()
+        });
+    }
+
+    let balance_columns = "total_assets, total_debt, total_equity, current_assets, current_liabilities, shares_outstanding";
+    for row in fetch_latest_two_years(pool, "balance_sheets", "Annual", balance_columns, stock_ids).await? {
+        let fiscal_year: i32 = row.get("fiscal_year");
+        if let Some(year) = years
+            .get_mut(&row.get::<i64, _>("stock_id"))
+            .and_then(|stock_years| stock_years.iter_mut().find(|year| year.fiscal_year == fiscal_year))
+        {
+            year.total_assets = row.get("total_assets");
+            year.total_debt = row.get("total_debt");
+            year.total_equity = row.get("total_equity");
+            year.current_assets = row.get("current_assets");
+            year.current_liabilities = row.get("current_liabilities");
+            year.shares_outstanding = row.get("shares_outstanding");
+        }
+    }
+
+    for row in fetch_latest_two_years(pool, "cash_flow_statements", "Annual", "operating_cash_flow", stock_ids).await? {
+        let fiscal_year: i32 = row.get("fiscal_year");
+        if let Some(year) = years
+            .get_mut(&row.get::<i64, _>("stock_id"))
+            .and_then(|stock_years| stock_years.iter_mut().find(|year| year.fiscal_year == fiscal_year))
+        {
+            year.operating_cash_flow = row.get("operating_cash_flow");
+        }
+    }
+
+    Ok(years
+        .into_iter()
+        .map(|(stock_id, mut stock_years)| {
+            let prior = (stock_years.len() > 1).then(|| stock_years.remove(1));
+            (stock_id, LatestFiscalYears { current: stock_years.remove(0), prior })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_latest_two_years_from_the_latest_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'OLD', 'Long History'), (2, 'NEW', 'Recent Listing');
+             INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, net_income, revenue) VALUES
+                (1, 'FY', '2021-12-31', 2021, 10, 100),
+                (1, 'FY', '2022-12-31', 2022, 20, 200),
+                (1, 'FY', '2023-12-31', 2023, 30, 300),
+                (1, 'FY', '2024-03-15', 2023, 35, 300),
+                (1, 'TTM', '2024-06-30', 2024, 40, 400),
+                (2, 'FY', '2023-12-31', 2023, 5, 50);
+             INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_assets, shares_outstanding) VALUES
+                (1, 'Annual', '2022-12-31', 2022, 1000, 10),
+                (1, 'Annual', '2023-12-31', 2023, 1100, 9),
+                (2, 'Annual', '2022-12-31', 2022, 500, 5);
+             INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, operating_cash_flow) VALUES
+                (1, 'Annual', '2023-12-31', 2023, 45)"
+        )
+        .execute(&pool).await.unwrap();
+
+        let years = load_latest_fiscal_years(&pool, None).await.unwrap();
+        assert_eq!(years.len(), 2);
+
+        // The restated 2023 wins; the TTM row and 2021 are ignored
+        let old = &years[&1];
+        assert_eq!((old.current.fiscal_year, old.current.report_date.as_str()), (2023, "2024-03-15"));
+        assert_eq!((old.current.net_income, old.current.total_assets, old.current.operating_cash_flow), (Some(35.0), Some(1100.0), Some(45.0)));
+        let prior = old.prior.as_ref().unwrap();
+        assert_eq!((prior.fiscal_year, prior.net_income, prior.shares_outstanding, prior.operating_cash_flow), (2022, Some(20.0), Some(10.0), None));
+
+        // A balance sheet for a year without an income statement isn't attached
+        let new = &years[&2];
+        assert_eq!((new.current.fiscal_year, new.current.total_assets, new.prior.is_none()), (2023, None, true));
+
+        let only_new = load_latest_fiscal_years(&pool, Some(&[2])).await.unwrap();
+        assert_eq!(only_new.keys().copied().collect::<Vec<_>>(), vec![2]);
+        assert!(load_latest_fiscal_years(&pool, Some(&[])).await.unwrap().is_empty());
+    }
+}
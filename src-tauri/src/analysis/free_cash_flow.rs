@@ -0,0 +1,167 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::analysis::valuation_ratios::STATEMENT_FREE_CASH_FLOW;
+use crate::tools::fx_rates::usd_value_sql;
+use crate::tools::universe_filter::{apply_universe_filter, market_cap_sql, UniverseFilter};
+
+/// Fiscal years of positive free cash flow required when the caller doesn't say
+pub const DEFAULT_MIN_POSITIVE_FCF_YEARS: i32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FcfYieldStock {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub sector: Option<String>,
+    /// Day of the close the market cap comes from
+    pub date: String,
+    /// Close × shares outstanding in USD
+    pub market_cap: f64,
+    /// Latest fiscal year's, in USD
+    pub free_cash_flow: f64,
+    /// Free cash flow as a percent of market cap
+    pub fcf_yield: f64,
+    /// Most recent fiscal years in a row with positive free cash flow
+    pub positive_fcf_years: i32,
+}
+
+/// Stocks with the highest latest FCF yield whose free cash flow was positive in each of
+/// their last `min_positive_years` fiscal years, subject to the shared size and liquidity floor.
+/// The yield is the latest fiscal year's free cash flow over `market_cap_sql`'s close × shares,
+/// both in USD; stocks whose market cap can't be computed are left out.
+pub async fn screen_top_fcf_yield(
+    pool: &SqlitePool,
+    min_positive_years: i32,
+    universe: &UniverseFilter,
+    limit: i32,
+) -> Result<Vec<FcfYieldStock>> {
+    let mut query = format!(
+        "WITH annual_fcf AS (
+            SELECT stock_id, free_cash_flow, usd_free_cash_flow,
+                   ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY fiscal_year DESC) AS rn
+            FROM (
+                SELECT c.stock_id, c.fiscal_year, {fcf} AS free_cash_flow, {usd_fcf} AS usd_free_cash_flow,
+                       ROW_NUMBER() OVER (PARTITION BY c.stock_id, c.fiscal_year ORDER BY c.report_date DESC) AS restated_rn
+                FROM cash_flow_statements c
+                WHERE c.period_type = 'Annual' AND c.fiscal_year IS NOT NULL
+            )
+            WHERE restated_rn = 1
+        ),
+        streak AS (
+            SELECT stock_id,
+                   COALESCE(MIN(CASE WHEN free_cash_flow IS NULL OR free_cash_flow <= 0 THEN rn END) - 1, MAX(rn)) AS positive_fcf_years
+            FROM annual_fcf
+            GROUP BY stock_id
+        )
+        SELECT * FROM (
+            SELECT stock_id, symbol, sector, date, market_cap, free_cash_flow,
+                   free_cash_flow / market_cap * 100.0 AS fcf_yield, positive_fcf_years
+            FROM (
+                SELECT s.id AS stock_id, s.symbol, s.sector,
+                       (SELECT MAX(p.date) FROM daily_prices p WHERE p.stock_id = s.id AND p.close_price > 0) AS date,
+                       {market_cap} AS market_cap,
+                       f.usd_free_cash_flow AS free_cash_flow,
+                       COALESCE(st.positive_fcf_years, 0) AS positive_fcf_years
+                FROM stocks s
+                JOIN annual_fcf f ON f.stock_id = s.id AND f.rn = 1
+                LEFT JOIN streak st ON st.stock_id = s.id
+            )
+            WHERE market_cap > 0 AND free_cash_flow IS NOT NULL
+        ) fcf
+        WHERE positive_fcf_years >= CAST(? AS INTEGER)",
+        fcf = STATEMENT_FREE_CASH_FLOW,
+        usd_fcf = usd_value_sql(STATEMENT_FREE_CASH_FLOW, "c.currency", "c.report_date"),
+        market_cap = market_cap_sql("s.id")
+    );
+    let mut params = vec![min_positive_years.to_string()];
+
+    apply_universe_filter(&mut query, &mut params, "fcf.stock_id", universe);
+    query.push_str(" ORDER BY fcf_yield DESC, symbol LIMIT ?");
+    params.push(limit.to_string());
+
+    let mut sqlx_query = sqlx::query(&query);
+    for param in &params {
+        sqlx_query = sqlx_query.bind(param);
+    }
+
+    Ok(sqlx_query
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| FcfYieldStock {
+            stock_id: row.get("stock_id"),
+            symbol: row.get("symbol"),
+            sector: row.get("sector"),
+            date: row.get("date"),
+            market_cap: row.get("market_cap"),
+            free_cash_flow: row.get("free_cash_flow"),
+            fcf_yield: row.get("fcf_yield"),
+            positive_fcf_years: row.get("positive_fcf_years"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::valuation_ratios::{backfill_daily_ratios, load_daily_valuation_ratios};
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_fcf_yield_ranks_stocks_with_a_positive_streak() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // (symbol, [(operating cash flow, capex)] for 2021..=2023); filers disagree on the sign of capex
+        let stocks = [
+            ("STEADY", [(100.0, -40.0), (110.0, -40.0), (120.0, -40.0)]),
+            ("RICH", [(300.0, 50.0), (300.0, 50.0), (300.0, 50.0)]),
+            ("DIPPED", [(400.0, 50.0), (10.0, 50.0), (400.0, 50.0)]),
+        ];
+        for (id, (symbol, years)) in stocks.into_iter().enumerate() {
+            let id = id as i64 + 1;
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (?, ?, ?)")
+                .bind(id).bind(symbol).bind(symbol)
+                .execute(&pool).await.unwrap();
+            for (year, (ocf, capex)) in (2021..).zip(years) {
+                // Rows written before free_cash_flow existed; readers compute it from the parts
+                sqlx::query(
+                    "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, operating_cash_flow, capital_expenditures)
+                     VALUES (?, 'Annual', ?, ?, ?, ?)"
+                )
+                .bind(id).bind(format!("{}-12-31", year)).bind(year).bind(ocf).bind(capex)
+                .execute(&pool).await.unwrap();
+            }
+            // A 1000 market cap from a close of 10 on 100 shares
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?, '2024-03-01', 10.0, 10.0, 10.0, 10.0)"
+            )
+            .bind(id)
+            .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, shares_outstanding)
+                 VALUES (?, 'Annual', '2023-12-31', 2023, 100.0)"
+            )
+            .bind(id)
+            .execute(&pool).await.unwrap();
+            backfill_daily_ratios(&pool, id).await.unwrap();
+        }
+
+        // STEADY: 120 - 40 on a 1000 market cap
+        let steady = load_daily_valuation_ratios(&pool, "STEADY", "2024-01-01", "2024-12-31").await.unwrap();
+        assert_eq!((steady[0].free_cash_flow, steady[0].fcf_yield), (Some(80.0), Some(8.0)));
+
+        let no_floor = UniverseFilter::new(Some(0.0), Some(0.0));
+        let screened = screen_top_fcf_yield(&pool, DEFAULT_MIN_POSITIVE_FCF_YEARS, &no_floor, 10).await.unwrap();
+        let ranked: Vec<(&str, i32)> = screened.iter().map(|s| (s.symbol.as_str(), s.positive_fcf_years)).collect();
+        // DIPPED has the highest yield but burned cash in 2022
+        assert_eq!(ranked, vec![("RICH", 3), ("STEADY", 3)]);
+
+        let screened = screen_top_fcf_yield(&pool, 1, &no_floor, 1).await.unwrap();
+        assert_eq!(screened[0].symbol, "DIPPED");
+    }
+}
@@ -0,0 +1,362 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use tracing::warn;
+use ts_rs::TS;
+
+/// Annual periods up to this many days off a 365-day year, 52/53-week years among them, are
+/// scaled to 365 days before comparing; further off is a stub or transition period
+pub const MAX_ANNUALIZE_DAYS: i64 = 31;
+
+/// SQL scaling `revenue` to a 365-day year from the days since the prior annual period end.
+/// Calendar-length and unknown periods are left as is; stub periods give NULL.
+pub fn annualized_revenue_sql(revenue: &str, period_days: &str) -> String {
+    format!(
+        "CASE WHEN {days} IS NULL OR {days} IN (365, 366) THEN {revenue}
+              WHEN ABS({days} - 365) <= {max} THEN {revenue} * 365.0 / {days} END",
+        days = period_days,
+        revenue = revenue,
+        max = MAX_ANNUALIZE_DAYS
+    )
+}
+
+/// Fiscal-year revenue, one row per stock and year (the latest report, or the one stored last,
+/// when restated).
+/// Each row carries the year and annualized revenue before it for the YoY comparison, with
+/// NULL revenue on either side of a stub period.
+pub fn fy_revenue_with_prior() -> String {
+    format!(
+        "SELECT stock_id, fiscal_year, report_date, revenue, annualized_revenue,
+               LEAD(fiscal_year) OVER w AS prior_year,
+               LEAD(annualized_revenue) OVER w AS prior_revenue,
+               ROW_NUMBER() OVER w AS rn
+        FROM (
+            SELECT stock_id, fiscal_year, report_date, revenue, {} AS annualized_revenue
+            FROM (
+                SELECT stock_id, fiscal_year, report_date, revenue,
+                       julianday(report_date) - julianday(LAG(report_date) OVER (PARTITION BY stock_id ORDER BY fiscal_year)) AS period_days
+                FROM (
+                    SELECT stock_id, fiscal_year, report_date, revenue,
+                           ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC, rowid DESC) AS restated_rn
+                    FROM income_statements
+                    WHERE period_type = 'FY' AND revenue IS NOT NULL AND fiscal_year IS NOT NULL
+                )
+                WHERE restated_rn = 1
+            )
+        )
+        WINDOW w AS (PARTITION BY stock_id ORDER BY fiscal_year DESC)",
+        annualized_revenue_sql("revenue", "period_days")
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AnnualRevenueGrowth {
+    pub fiscal_year: i32,
+    /// Percent change from the prior fiscal year
+    pub growth_rate: f64,
+    /// Either year's revenue was scaled to 365 days, as for a 52/53-week year
+    pub annualized: bool,
+}
+
+/// A year left without a growth rate because it or the year before is a stub period, as
+/// around a fiscal year end change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SkippedGrowthPeriod {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    /// Days since the prior annual period end
+    pub period_days: Option<i32>,
+    pub prior_period_days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct GrowthConsistency {
+    pub stock_id: i64,
+    pub years: i32,
+    /// Most recent fiscal years in a row with higher revenue than the year before
+    pub consecutive_growth_years: i32,
+    /// Population standard deviation of the annual growth rates; lower is more consistent
+    pub growth_rate_stddev: Option<f64>,
+    /// Oldest first, at most `years` entries
+    pub annual_growth_rates: Vec<AnnualRevenueGrowth>,
+    /// Years in the window skipped for a stub period; each ends any streak running through it
+    pub skipped_periods: Vec<SkippedGrowthPeriod>,
+}
+
+/// One fiscal year's revenue and the date its period ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnualRevenue {
+    pub fiscal_year: i32,
+    pub report_date: NaiveDate,
+    pub revenue: f64,
+}
+
+enum YearOverYear {
+    Growth(AnnualRevenueGrowth),
+    Skipped(SkippedGrowthPeriod),
+    Missing,
+}
+
+/// Scale factor to a 365-day year, or None for a stub period. Unknown lengths count as a full year.
+fn annualization_factor(period_days: Option<i64>) -> Option<f64> {
+    match period_days {
+        None | Some(365) | Some(366) => Some(1.0),
+        Some(days) if (days - 365).abs() <= MAX_ANNUALIZE_DAYS => Some(365.0 / days as f64),
+        Some(_) => None,
+    }
+}
+
+/// YoY growth over a revenue series sorted oldest first, on revenue annualized by period length.
+/// A missing year or a non-positive prior revenue leaves no rate, and a stub period on either
+/// side skips the pair; both end any streak running through them.
+fn year_over_year(revenues: &[AnnualRevenue]) -> Vec<YearOverYear> {
+    let period_days: Vec<Option<i64>> = (0..revenues.len())
+        .map(|i| i.checked_sub(1).map(|prior| (revenues[i].report_date - revenues[prior].report_date).num_days()))
+        .collect();
+
+    (1..revenues.len())
+        .map(|i| {
+            let (prior, current) = (&revenues[i - 1], &revenues[i]);
+            if current.fiscal_year != prior.fiscal_year + 1 || prior.revenue <= 0.0 {
+                return YearOverYear::Missing;
+            }
+            match (annualization_factor(period_days[i - 1]), annualization_factor(period_days[i])) {
+                (Some(prior_factor), Some(current_factor)) => {
+                    let (prior_revenue, current_revenue) = (prior.revenue * prior_factor, current.revenue * current_factor);
+                    YearOverYear::Growth(AnnualRevenueGrowth {
+                        fiscal_year: current.fiscal_year,
+                        growth_rate: (current_revenue - prior_revenue) / prior_revenue * 100.0,
+                        annualized: prior_factor != 1.0 || current_factor != 1.0,
+                    })
+                }
+                _ => {
+                    warn!(
+                        fiscal_year = current.fiscal_year,
+                        "Skipping revenue growth across a stub period ({:?} days after {:?})",
+                        period_days[i],
+                        period_days[i - 1]
+                    );
+                    YearOverYear::Skipped(SkippedGrowthPeriod {
+                        fiscal_year: current.fiscal_year,
+                        report_date: current.report_date.format("%Y-%m-%d").to_string(),
+                        period_days: period_days[i].map(|days| days as i32),
+                        prior_period_days: period_days[i - 1].map(|days| days as i32),
+                    })
+                }
+            }
+        })
+        .collect()
+}
+
+/// Streak and dispersion over the last `years` growth rates of a revenue series sorted oldest first
+pub fn summarize_growth_consistency(stock_id: i64, revenues: &[AnnualRevenue], years: i32) -> GrowthConsistency {
+    let growth = year_over_year(revenues);
+    let window = &growth[growth.len().saturating_sub(years.max(0) as usize)..];
+
+    let consecutive_growth_years = window
+        .iter()
+        .rev()
+        .take_while(|g| matches!(g, YearOverYear::Growth(g) if g.growth_rate > 0.0))
+        .count() as i32;
+
+    let mut annual_growth_rates = Vec::new();
+    let mut skipped_periods = Vec::new();
+    for year in window {
+        match year {
+            YearOverYear::Growth(growth) => annual_growth_rates.push(growth.clone()),
+            YearOverYear::Skipped(skipped) => skipped_periods.push(skipped.clone()),
+            YearOverYear::Missing => {}
+        }
+    }
+    let growth_rate_stddev = (!annual_growth_rates.is_empty()).then(|| {
+        let n = annual_growth_rates.len() as f64;
+        let mean = annual_growth_rates.iter().map(|g| g.growth_rate).sum::<f64>() / n;
+        (annual_growth_rates.iter().map(|g| (g.growth_rate - mean).powi(2)).sum::<f64>() / n).sqrt()
+    });
+
+    GrowthConsistency { stock_id, years, consecutive_growth_years, growth_rate_stddev, annual_growth_rates, skipped_periods }
+}
+
+/// Growth consistency over the stock's last `years` fiscal years of revenue. One extra year is
+/// read so the oldest compared year's period length is known.
+pub async fn compute_growth_consistency(pool: &SqlitePool, stock_id: i64, years: i32) -> Result<GrowthConsistency> {
+    let query = format!(
+        "SELECT fiscal_year, date(report_date) AS report_date, revenue FROM ({}) WHERE stock_id = ? AND rn <= ? ORDER BY fiscal_year",
+        fy_revenue_with_prior()
+    );
+    let revenues: Vec<AnnualRevenue> = sqlx::query(&query)
+        .bind(stock_id)
+        .bind(years + 2)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| AnnualRevenue {
+            fiscal_year: row.get("fiscal_year"),
+            report_date: row.get("report_date"),
+            revenue: row.get("revenue"),
+        })
+        .collect();
+
+    Ok(summarize_growth_consistency(stock_id, &revenues, years))
+}
+
+/// Restrict a screening query to stocks whose latest `min_years` fiscal years all grew revenue,
+/// using the same streak rule as `summarize_growth_consistency`
+pub fn push_min_growth_streak_filter(query: &mut String, params: &mut Vec<String>, stock_id_column: &str, min_years: i32) {
+    if min_years <= 0 {
+        return;
+    }
+
+    // The oldest year has no prior, so every stock has a row that ends its streak
+    query.push_str(&format!(
+        " AND {} IN (
+            SELECT stock_id FROM ({})
+            GROUP BY stock_id
+            HAVING MIN(CASE WHEN prior_year IS NULL OR prior_year != fiscal_year - 1
+                                 OR prior_revenue IS NULL OR annualized_revenue IS NULL
+                                 OR prior_revenue <= 0 OR annualized_revenue <= prior_revenue
+                            THEN rn END) - 1 >= CAST(? AS INTEGER)
+        )",
+        stock_id_column,
+        fy_revenue_with_prior()
+    ));
+    params.push(min_years.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_pool() -> SqlitePool {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE stocks (id INTEGER PRIMARY KEY, symbol TEXT NOT NULL)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE income_statements (stock_id INTEGER, period_type TEXT, report_date TEXT, fiscal_year INTEGER, revenue REAL)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO stocks (id, symbol) VALUES (1, 'DIP'), (2, 'STEADY')")
+            .execute(&pool).await.unwrap();
+
+        // DIP grows for four years, dips in 2020, then grows three more
+        let dip = [(2016, 100.0), (2017, 110.0), (2018, 120.0), (2019, 130.0), (2020, 125.0), (2021, 135.0), (2022, 145.0), (2023, 160.0)];
+        let steady = [(2019, 100.0), (2020, 110.0), (2021, 121.0), (2022, 133.1), (2023, 146.41)];
+        for (stock_id, series) in [(1, &dip[..]), (2, &steady[..])] {
+            for (year, revenue) in series {
+                sqlx::query(
+                    "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue) VALUES (?, 'FY', ?, ?, ?)"
+                )
+                .bind(stock_id)
+                .bind(format!("{}-12-31", year))
+                .bind(year)
+                .bind(revenue)
+                .execute(&pool).await.unwrap();
+            }
+        }
+        // A restated 2023 filing replaces the original
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue) VALUES (1, 'FY', '2023-12-31', 2023, 150.0)"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_streak_resets_after_a_single_dip() {
+        let pool = seeded_pool().await;
+
+        let dip = compute_growth_consistency(&pool, 1, 10).await.unwrap();
+        assert_eq!(dip.consecutive_growth_years, 3);
+        assert_eq!(dip.annual_growth_rates.len(), 7);
+        assert_eq!(dip.annual_growth_rates.last().unwrap().fiscal_year, 2023);
+        assert!(dip.annual_growth_rates[3].growth_rate < 0.0);
+
+        // A window that ends before the dip sees only growth
+        let recent = compute_growth_consistency(&pool, 1, 2).await.unwrap();
+        assert_eq!((recent.consecutive_growth_years, recent.annual_growth_rates.len()), (2, 2));
+
+        // 10% every year: full streak, no dispersion
+        let steady = compute_growth_consistency(&pool, 2, 10).await.unwrap();
+        assert_eq!(steady.consecutive_growth_years, 4);
+        assert!(steady.growth_rate_stddev.unwrap() < 1e-9);
+        assert!(dip.growth_rate_stddev.unwrap() > steady.growth_rate_stddev.unwrap());
+
+        for (min_years, expected) in [(3, vec!["DIP", "STEADY"]), (4, vec!["STEADY"]), (5, vec![])] {
+            let mut query = String::from("SELECT symbol FROM stocks WHERE 1=1");
+            let mut params = Vec::new();
+            push_min_growth_streak_filter(&mut query, &mut params, "id", min_years);
+            query.push_str(" ORDER BY symbol");
+
+            let mut sqlx_query = sqlx::query_scalar::<_, String>(&query);
+            for param in params {
+                sqlx_query = sqlx_query.bind(param);
+            }
+            assert_eq!(sqlx_query.fetch_all(&pool).await.unwrap(), expected, "min {} years", min_years);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_53_week_and_transition_years_do_not_show_as_growth_swings() {
+        let pool = seeded_pool().await;
+        sqlx::query("INSERT INTO stocks (id, symbol) VALUES (3, 'RETAIL'), (4, 'SHIFT')")
+            .execute(&pool).await.unwrap();
+
+        // RETAIL sells 10 a week; its FY2023 is a 53-week year ending 2024-01-06
+        let retail = [
+            (2020, "2021-01-02", 520.0),
+            (2021, "2022-01-01", 520.0),
+            (2022, "2022-12-31", 520.0),
+            (2023, "2024-01-06", 530.0),
+            (2024, "2025-01-04", 520.0),
+        ];
+        // SHIFT moves its year end from May to December: FY2021 is a seven-month stub
+        let shift = [
+            (2018, "2018-05-31", 90.0),
+            (2019, "2019-05-31", 100.0),
+            (2020, "2020-05-31", 110.0),
+            (2021, "2020-12-31", 70.0),
+            (2022, "2021-12-31", 125.0),
+            (2023, "2022-12-31", 135.0),
+        ];
+        for (stock_id, series) in [(3, &retail[..]), (4, &shift[..])] {
+            for (year, report_date, revenue) in series {
+                sqlx::query(
+                    "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue) VALUES (?, 'FY', ?, ?, ?)"
+                )
+                .bind(stock_id).bind(report_date).bind(year).bind(revenue)
+                .execute(&pool).await.unwrap();
+            }
+        }
+
+        // Unadjusted, FY2023 would be +1.9% and FY2024 -1.9%
+        let retail = compute_growth_consistency(&pool, 3, 2).await.unwrap();
+        let rates: Vec<(i32, bool)> = retail.annual_growth_rates.iter().map(|g| (g.fiscal_year, g.annualized)).collect();
+        assert_eq!(rates, vec![(2023, true), (2024, true)]);
+        assert!(retail.annual_growth_rates.iter().all(|g| g.growth_rate.abs() < 1e-9));
+        assert!(retail.skipped_periods.is_empty());
+
+        // Unadjusted, the stub would be a 36% drop followed by 79% growth
+        let shift = compute_growth_consistency(&pool, 4, 4).await.unwrap();
+        let years: Vec<i32> = shift.annual_growth_rates.iter().map(|g| g.fiscal_year).collect();
+        assert_eq!(years, vec![2020, 2023]);
+        assert!((shift.annual_growth_rates[1].growth_rate - 8.0).abs() < 1e-9);
+        let skipped: Vec<(i32, Option<i32>, Option<i32>)> =
+            shift.skipped_periods.iter().map(|p| (p.fiscal_year, p.period_days, p.prior_period_days)).collect();
+        assert_eq!(skipped, vec![(2021, Some(214), Some(366)), (2022, Some(365), Some(214))]);
+        assert_eq!(shift.consecutive_growth_years, 1);
+
+        // The screening filter skips the same pairs
+        let mut query = String::from("SELECT symbol FROM stocks WHERE id IN (3, 4)");
+        let mut params = Vec::new();
+        push_min_growth_streak_filter(&mut query, &mut params, "id", 2);
+        let mut sqlx_query = sqlx::query_scalar::<_, String>(&query);
+        for param in params {
+            sqlx_query = sqlx_query.bind(param);
+        }
+        assert!(sqlx_query.fetch_all(&pool).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use super::fiscal_calendar::calendar_year_sql;
+use super::growth_consistency::annualized_revenue_sql;
+use crate::tools::universe_filter::market_cap_sql;
+
+/// Industries with fewer members that have a growth rate and market cap are left out
+pub const MIN_INDUSTRY_STOCKS: usize = 3;
+
+/// Revenue growth of one industry, weighting each member's CAGR by its market cap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct IndustryGrowth {
+    pub industry: String,
+    /// Sector most members are classified under
+    pub sector: Option<String>,
+    /// 1 is the fastest-growing industry
+    pub rank: i32,
+    pub member_count: i32,
+    /// Members with a revenue CAGR and market cap, the ones the growth figures cover
+    pub stocks_with_growth: i32,
+    pub total_market_cap: f64,
+    /// Percent per year
+    pub weighted_revenue_cagr: f64,
+    pub equal_weight_revenue_cagr: f64,
+}
+
+/// One member stock's inputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndustryMember {
+    pub industry: String,
+    pub sector: Option<String>,
+    pub revenue_cagr: Option<f64>,
+    pub market_cap: Option<f64>,
+}
+
+/// Compound annual growth, in percent, from `start` to `end` over `years`; None unless both are positive
+pub fn revenue_cagr(start: f64, end: f64, years: i32) -> Option<f64> {
+    (start > 0.0 && end > 0.0 && years > 0).then(|| ((end / start).powf(1.0 / years as f64) - 1.0) * 100.0)
+}
+
+#[derive(Default)]
+struct IndustryValues {
+    member_count: i32,
+    sectors: BTreeMap<String, i32>,
+    growth: Vec<(f64, f64)>,
+}
+
+/// Group members by industry, drop industries with under `MIN_INDUSTRY_STOCKS` usable members,
+/// and rank the rest by market-cap-weighted CAGR, fastest first
+pub fn rank_industry_growth(members: &[IndustryMember]) -> Vec<IndustryGrowth> {
+    let mut industries: BTreeMap<&str, IndustryValues> = BTreeMap::new();
+    for member in members {
+        let values = industries.entry(member.industry.as_str()).or_default();
+        values.member_count += 1;
+        if let Some(sector) = &member.sector {
+            *values.sectors.entry(sector.clone()).or_default() += 1;
+        }
+        if let (Some(cagr), Some(cap)) = (member.revenue_cagr, member.market_cap.filter(|cap| *cap > 0.0)) {
+            values.growth.push((cagr, cap));
+        }
+    }
+
+    let mut ranked: Vec<IndustryGrowth> = industries
+        .into_iter()
+        .filter(|(_, values)| values.growth.len() >= MIN_INDUSTRY_STOCKS)
+        .map(|(industry, values)| {
+            let total_market_cap: f64 = values.growth.iter().map(|(_, cap)| cap).sum();
+            let weighted = values.growth.iter().map(|(cagr, cap)| cagr * cap).sum::<f64>() / total_market_cap;
+            let equal = values.growth.iter().map(|(cagr, _)| cagr).sum::<f64>() / values.growth.len() as f64;
+            // Most common sector, ties to the first alphabetically
+            let sector = values
+                .sectors
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(sector, _)| sector.clone());
+            IndustryGrowth {
+                industry: industry.to_string(),
+                sector,
+                rank: 0,
+                member_count: values.member_count,
+                stocks_with_growth: values.growth.len() as i32,
+                total_market_cap,
+                weighted_revenue_cagr: weighted,
+                equal_weight_revenue_cagr: equal,
+            }
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.weighted_revenue_cagr.total_cmp(&a.weighted_revenue_cagr).then_with(|| a.industry.cmp(&b.industry))
+    });
+    for (i, industry) in ranked.iter_mut().enumerate() {
+        industry.rank = i as i32 + 1;
+    }
+    ranked
+}
+
+/// Industries ranked by their members' market-cap-weighted revenue CAGR over the last `years`
+/// years. Annual statements are placed in calendar-year buckets by period end, so members with
+/// different fiscal year ends compare on roughly the same periods. Each active stock's CAGR runs
+/// from year N - `years` to its latest year N, on revenue annualized to 365 days, and is left
+/// out when either end is a stub period; market cap is the latest close × shares in USD.
+pub async fn compute_industry_growth_ranking(pool: &SqlitePool, years: i32) -> Result<Vec<IndustryGrowth>> {
+    let query = format!(
+        "WITH fy AS (
+            SELECT stock_id, calendar_year, {1} AS revenue FROM (
+                SELECT stock_id, calendar_year, revenue,
+                       julianday(report_date) - julianday(LAG(report_date) OVER (PARTITION BY stock_id ORDER BY report_date)) AS period_days
+                FROM (
+                    SELECT stock_id, {0} AS calendar_year, revenue, report_date,
+                           ROW_NUMBER() OVER (PARTITION BY stock_id, {0} ORDER BY report_date DESC) AS restated_rn
+                    FROM income_statements
+                    WHERE period_type = 'FY' AND revenue IS NOT NULL AND report_date IS NOT NULL
+                )
+                WHERE restated_rn = 1
+            )
+        ),
+        latest_fy AS (
+            SELECT stock_id, MAX(calendar_year) AS calendar_year FROM fy GROUP BY stock_id
+        )
+        SELECT s.industry, s.sector, base.revenue AS start_revenue, cur.revenue AS end_revenue,
+               {2} AS market_cap
+        FROM stocks s
+        LEFT JOIN latest_fy l ON l.stock_id = s.id
+        LEFT JOIN fy cur ON cur.stock_id = l.stock_id AND cur.calendar_year = l.calendar_year
+        LEFT JOIN fy base ON base.stock_id = l.stock_id AND base.calendar_year = l.calendar_year - ?
+        WHERE s.industry IS NOT NULL AND s.status = 'active'",
+        calendar_year_sql("report_date"),
+        annualized_revenue_sql("revenue", "period_days"),
+        market_cap_sql("s.id")
+    );
+
+    let rows = sqlx::query(&query).bind(years).fetch_all(pool).await?;
+
+    let members: Vec<IndustryMember> = rows
+        .iter()
+        .map(|row| {
+            let start: Option<f64> = row.get("start_revenue");
+            let end: Option<f64> = row.get("end_revenue");
+            IndustryMember {
+                industry: row.get("industry"),
+                sector: row.get("sector"),
+                revenue_cagr: start.zip(end).and_then(|(start, end)| revenue_cagr(start, end, years)),
+                market_cap: row.get("market_cap"),
+            }
+        })
+        .collect();
+
+    Ok(rank_industry_growth(&members))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_industries_ranked_by_cap_weighted_cagr() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // Revenue 100 in FY2021; FY2023 revenue of 121, 144 and 169 is 10%, 20% and 30% a year
+        let stocks = [
+            ("F1", "Fast", 169.0, 100.0),
+            ("F2", "Fast", 121.0, 300.0),
+            ("F3", "Fast", 144.0, 100.0),
+            ("S1", "Slow", 100.0, 100.0),
+            ("S2", "Slow", 121.0, 100.0),
+            ("S3", "Slow", 144.0, 200.0),
+            // Only two members: too few to rank
+            ("T1", "Tiny", 400.0, 100.0),
+            ("T2", "Tiny", 400.0, 100.0),
+        ];
+        for (id, (symbol, industry, revenue_2023, market_cap)) in stocks.into_iter().enumerate() {
+            let id = id as i64 + 1;
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, sector, industry) VALUES (?, ?, ?, 'Industrials', ?)")
+                .bind(id).bind(symbol).bind(symbol).bind(industry)
+                .execute(&pool).await.unwrap();
+            for (year, revenue) in [(2021, 100.0), (2022, 110.0), (2023, revenue_2023)] {
+                sqlx::query(
+                    "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue)
+                     VALUES (?, 'FY', ?, ?, ?)"
+                )
+                .bind(id).bind(format!("{}-12-31", year)).bind(year).bind(revenue)
+                .execute(&pool).await.unwrap();
+            }
+            // Market cap is close × shares, so a close of 10 and a tenth as many shares
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?, '2024-03-01', 10.0, 10.0, 10.0, 10.0)"
+            )
+            .bind(id)
+            .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, shares_outstanding)
+                 VALUES (?, 'Annual', '2023-12-31', 2023, ?)"
+            )
+            .bind(id).bind(market_cap / 10.0)
+            .execute(&pool).await.unwrap();
+        }
+
+        let ranking = compute_industry_growth_ranking(&pool, 2).await.unwrap();
+        let names: Vec<&str> = ranking.iter().map(|industry| industry.industry.as_str()).collect();
+        assert_eq!(names, vec!["Fast", "Slow"]);
+
+        // Fast: (30×100 + 10×300 + 20×100) / 500; its big slow grower pulls it below the equal-weight 20
+        let fast = &ranking[0];
+        assert_eq!((fast.rank, fast.member_count, fast.stocks_with_growth), (1, 3, 3));
+        assert!((fast.weighted_revenue_cagr - 16.0).abs() < 1e-9);
+        assert!((fast.equal_weight_revenue_cagr - 20.0).abs() < 1e-9);
+        assert_eq!(fast.total_market_cap, 500.0);
+
+        // Slow: (0×100 + 10×100 + 20×200) / 400
+        let slow = &ranking[1];
+        assert!((slow.weighted_revenue_cagr - 12.5).abs() < 1e-9);
+        assert!((slow.equal_weight_revenue_cagr - 10.0).abs() < 1e-9);
+        assert_eq!(slow.sector.as_deref(), Some("Industrials"));
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::safe_f64::{finite, NonFiniteReason, SafeF64};
+
+/// Fiscal years of history returned with the latest leverage figures
+pub const LEVERAGE_HISTORY_YEARS: usize = 5;
+
+/// Annual leverage inputs, one row per stock and fiscal year (the latest report when restated).
+/// Total debt falls back to short plus long-term debt; EBITDA is operating income plus D&A from
+/// the cash flow statement, else the income statement's depreciation and amortization, else none.
+const ANNUAL_LEVERAGE_INPUTS: &str = "
+    WITH balance AS (
+        SELECT stock_id, fiscal_year, report_date, COALESCE(currency, 'USD') AS currency,
+               COALESCE(total_debt,
+                        CASE WHEN short_term_debt IS NOT NULL OR long_term_debt IS NOT NULL
+                             THEN COALESCE(short_term_debt, 0) + COALESCE(long_term_debt, 0) END) AS total_debt,
+               total_equity, cash_and_equivalents,
+               ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+        FROM balance_sheets
+        WHERE period_type = 'Annual' AND fiscal_year IS NOT NULL
+    ),
+    income AS (
+        SELECT stock_id, fiscal_year, operating_income, interest_expense,
+               CASE WHEN depreciation_expense IS NOT NULL OR amortization_expense IS NOT NULL
+                    THEN COALESCE(depreciation_expense, 0) + COALESCE(amortization_expense, 0) END AS d_and_a,
+               ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+        FROM income_statements
+        WHERE period_type = 'FY' AND fiscal_year IS NOT NULL
+    ),
+    cash_flow AS (
+        SELECT stock_id, fiscal_year, depreciation_amortization,
+               ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+        FROM cash_flow_statements
+        WHERE period_type = 'Annual' AND fiscal_year IS NOT NULL
+    )
+    SELECT b.stock_id, b.fiscal_year, b.report_date, b.currency, b.total_debt, b.total_equity,
+           b.cash_and_equivalents, i.operating_income, i.interest_expense,
+           i.operating_income + COALESCE(c.depreciation_amortization, i.d_and_a, 0) AS ebitda
+    FROM balance b
+    LEFT JOIN income i ON i.stock_id = b.stock_id AND i.fiscal_year = b.fiscal_year AND i.rn = 1
+    LEFT JOIN cash_flow c ON c.stock_id = b.stock_id AND c.fiscal_year = b.fiscal_year AND c.rn = 1
+    WHERE b.rn = 1";
+
+/// Why a ratio has no value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum LeverageNote {
+    /// An input is not in the stored statements
+    MissingData,
+    /// Zero or negative equity makes debt/equity meaningless
+    NonPositiveEquity,
+    /// Zero or negative EBITDA; the company isn't earning its way out of debt
+    NegativeEbitda,
+    /// No interest expense reported, so there is nothing to cover
+    NoInterestExpense,
+    /// The denominator is so close to zero that the ratio overflows
+    NearZeroDenominator,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LeverageYear {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    /// Statement currency of the amounts below
+    pub currency: String,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub total_debt: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub total_equity: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub cash: Option<f64>,
+    /// Total debt minus cash; negative is a net cash position
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub net_debt: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub ebitda: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub debt_to_equity: Option<f64>,
+    pub debt_to_equity_note: Option<LeverageNote>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub net_debt_to_ebitda: Option<f64>,
+    pub net_debt_to_ebitda_note: Option<LeverageNote>,
+    /// Operating income over interest expense
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub interest_coverage: Option<f64>,
+    pub interest_coverage_note: Option<LeverageNote>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LeverageMetrics {
+    pub stock_id: i64,
+    pub latest: Option<LeverageYear>,
+    /// Oldest first, at most `LEVERAGE_HISTORY_YEARS` entries ending with `latest`
+    pub history: Vec<LeverageYear>,
+    /// Latest minus oldest debt/equity in `history`; positive means leverage went up
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub debt_to_equity_change: Option<f64>,
+    /// Latest minus oldest net debt in `history`
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub net_debt_change: Option<f64>,
+}
+
+/// Ceilings for the low-leverage screen; a None bound is not checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LowLeverageCriteria {
+    pub max_debt_to_equity: Option<f64>,
+    /// Net cash positions pass regardless of EBITDA
+    pub max_net_debt_to_ebitda: Option<f64>,
+    /// Stocks without interest expense pass
+    pub min_interest_coverage: Option<f64>,
+    pub limit: Option<i32>,
+}
+
+impl Default for LowLeverageCriteria {
+    fn default() -> Self {
+        Self {
+            max_debt_to_equity: Some(1.0),
+            max_net_debt_to_ebitda: Some(3.0),
+            min_interest_coverage: Some(3.0),
+            limit: Some(50),
+        }
+    }
+}
+
+impl LowLeverageCriteria {
+    fn passes(&self, year: &LeverageYear) -> bool {
+        let debt_to_equity_ok = self
+            .max_debt_to_equity
+            .map_or(true, |max| year.debt_to_equity.is_some_and(|de| de <= max));
+        let net_debt_ok = self.max_net_debt_to_ebitda.map_or(true, |max| {
+            year.net_debt.is_some_and(|net| net <= 0.0) || year.net_debt_to_ebitda.is_some_and(|ratio| ratio <= max)
+        });
+        let coverage_ok = self.min_interest_coverage.map_or(true, |min| {
+            year.interest_coverage_note == Some(LeverageNote::NoInterestExpense)
+                || year.interest_coverage.is_some_and(|coverage| coverage >= min)
+        });
+        debt_to_equity_ok && net_debt_ok && coverage_ok
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LowLeverageStock {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub company_name: String,
+    pub sector: Option<String>,
+    pub leverage: LeverageYear,
+}
+
+/// Leverage inputs of one fiscal year as stored
+#[derive(Debug, Clone, Default)]
+pub struct LeverageInputs {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    pub currency: String,
+    pub total_debt: Option<f64>,
+    pub total_equity: Option<f64>,
+    pub cash: Option<f64>,
+    pub operating_income: Option<f64>,
+    pub interest_expense: Option<f64>,
+    pub ebitda: Option<f64>,
+}
+
+/// `numerator / denominator`, or why it has no value
+fn leverage_ratio(numerator: f64, denominator: f64) -> (Option<f64>, Option<LeverageNote>) {
+    let ratio = SafeF64::ratio(numerator, denominator);
+    match ratio.reason() {
+        None => (ratio.value(), None),
+        Some(NonFiniteReason::NonFiniteInput) => (None, Some(LeverageNote::MissingData)),
+        Some(_) => (None, Some(LeverageNote::NearZeroDenominator)),
+    }
+}
+
+/// Ratios of one fiscal year. Each ratio without a value gets a note saying why.
+pub fn compute_leverage_year(inputs: &LeverageInputs) -> LeverageYear {
+    let net_debt = inputs.total_debt.zip(inputs.cash).and_then(|(debt, cash)| finite(debt - cash));
+
+    let (debt_to_equity, debt_to_equity_note) = match (inputs.total_debt, inputs.total_equity) {
+        (_, Some(equity)) if equity <= 0.0 => (None, Some(LeverageNote::NonPositiveEquity)),
+        (Some(debt), Some(equity)) => leverage_ratio(debt, equity),
+        _ => (None, Some(LeverageNote::MissingData)),
+    };
+
+    let (net_debt_to_ebitda, net_debt_to_ebitda_note) = match (net_debt, inputs.ebitda) {
+        (_, Some(ebitda)) if ebitda <= 0.0 => (None, Some(LeverageNote::NegativeEbitda)),
+        (Some(net), Some(ebitda)) => leverage_ratio(net, ebitda),
+        _ => (None, Some(LeverageNote::MissingData)),
+    };
+
+    // Filers disagree on the sign of interest expense, so only its size counts
+    let (interest_coverage, interest_coverage_note) = match (inputs.operating_income, inputs.interest_expense.map(f64::abs)) {
+        _ if inputs.ebitda.is_some_and(|ebitda| ebitda <= 0.0) => (None, Some(LeverageNote::NegativeEbitda)),
+        (Some(_), None) => (None, Some(LeverageNote::NoInterestExpense)),
+        (Some(_), Some(interest)) if interest == 0.0 => (None, Some(LeverageNote::NoInterestExpense)),
+        (Some(operating), Some(interest)) => leverage_ratio(operating, interest),
+        (None, _) => (None, Some(LeverageNote::MissingData)),
+    };
+
+    LeverageYear {
+        fiscal_year: inputs.fiscal_year,
+        report_date: inputs.report_date.clone(),
+        currency: inputs.currency.clone(),
+        total_debt: inputs.total_debt,
+        total_equity: inputs.total_equity,
+        cash: inputs.cash,
+        net_debt,
+        ebitda: inputs.ebitda,
+        debt_to_equity,
+        debt_to_equity_note,
+        net_debt_to_ebitda,
+        net_debt_to_ebitda_note,
+        interest_coverage,
+        interest_coverage_note,
+    }
+}
+
+/// Leverage by fiscal year, oldest first, per stock; all stocks when `stock_id` is None
+async fn load_leverage_years(pool: &SqlitePool, stock_id: Option<i64>) -> Result<BTreeMap<i64, Vec<LeverageYear>>> {
+    let query = format!(
+        "SELECT * FROM ({}) WHERE ?1 IS NULL OR stock_id = ?1 ORDER BY stock_id, fiscal_year",
+        ANNUAL_LEVERAGE_INPUTS
+    );
+    let rows = sqlx::query(&query).bind(stock_id).fetch_all(pool).await?;
+
+    let mut years: BTreeMap<i64, Vec<LeverageYear>> = BTreeMap::new();
+    for row in rows {
+        let inputs = LeverageInputs {
+            fiscal_year: row.get("fiscal_year"),
+            report_date: row.get("report_date"),
+            currency: row.get("currency"),
+            total_debt: row.get("total_debt"),
+            total_equity: row.get("total_equity"),
+            cash: row.get("cash_and_equivalents"),
+            operating_income: row.get("operating_income"),
+            interest_expense: row.get("interest_expense"),
+            ebitda: row.get("ebitda"),
+        };
+        years.entry(row.get("stock_id")).or_default().push(compute_leverage_year(&inputs));
+    }
+    Ok(years)
+}
+
+/// Latest leverage ratios and the last `LEVERAGE_HISTORY_YEARS` fiscal years of them
+pub async fn compute_leverage_metrics(pool: &SqlitePool, stock_id: i64) -> Result<LeverageMetrics> {
+    let mut years = load_leverage_years(pool, Some(stock_id)).await?.remove(&stock_id).unwrap_or_default();
+    let history = years.split_off(years.len().saturating_sub(LEVERAGE_HISTORY_YEARS));
+
+    let change = |value: fn(&LeverageYear) -> Option<f64>| match (history.first(), history.last()) {
+        (Some(oldest), Some(latest)) if history.len() > 1 => value(latest).zip(value(oldest)).map(|(l, o)| l - o),
+        _ => None,
+    };
+    let debt_to_equity_change = change(|year| year.debt_to_equity);
+    let net_debt_change = change(|year| year.net_debt);
+
+    Ok(LeverageMetrics {
+        stock_id,
+        latest: history.last().cloned(),
+        history,
+        debt_to_equity_change,
+        net_debt_change,
+    })
+}
+
+/// S&P 500 stocks whose latest fiscal year meets every bound in `criteria`, lowest debt/equity first
+pub async fn screen_low_leverage_stocks(pool: &SqlitePool, criteria: &LowLeverageCriteria) -> Result<Vec<LowLeverageStock>> {
+    let mut years = load_leverage_years(pool, None).await?;
+    let stocks = sqlx::query(
+        "SELECT s.id, s.symbol, s.company_name, s.sector
+         FROM stocks s
+         INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut matches: Vec<LowLeverageStock> = stocks
+        .iter()
+        .filter_map(|row| {
+            let stock_id: i64 = row.get("id");
+            let latest = years.remove(&stock_id)?.pop()?;
+            criteria.passes(&latest).then(|| LowLeverageStock {
+                stock_id,
+                symbol: row.get("symbol"),
+                company_name: row.get("company_name"),
+                sector: row.get("sector"),
+                leverage: latest,
+            })
+        })
+        .collect();
+
+    // Stocks without a debt/equity (only possible with no ceiling on it) sort last
+    matches.sort_by(|a, b| {
+        let key = |stock: &LowLeverageStock| stock.leverage.debt_to_equity.unwrap_or(f64::INFINITY);
+        key(a).total_cmp(&key(b)).then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    if let Some(limit) = criteria.limit {
+        matches.truncate(limit.max(0) as usize);
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn insert_year(pool: &SqlitePool, stock_id: i64, year: i32, debt: f64, equity: f64, cash: f64, operating: f64, interest: f64) {
+        let report_date = format!("{}-12-31", year);
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_debt, total_equity, cash_and_equivalents)
+             VALUES (?, 'Annual', ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id).bind(&report_date).bind(year).bind(debt).bind(equity).bind(cash)
+        .execute(pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, operating_income, interest_expense)
+             VALUES (?, 'FY', ?, ?, ?, ?)"
+        )
+        .bind(stock_id).bind(&report_date).bind(year).bind(operating).bind(interest)
+        .execute(pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, depreciation_amortization)
+             VALUES (?, 'Annual', ?, ?, 10.0)"
+        )
+        .bind(stock_id).bind(&report_date).bind(year)
+        .execute(pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ratios_trend_and_screen() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        for (id, symbol) in [(1, "STEADY"), (2, "NODEBT"), (3, "BURNER")] {
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (?, ?, ?, 1)")
+                .bind(id).bind(symbol).bind(symbol)
+                .execute(&pool).await.unwrap();
+        }
+        // STEADY deleverages over six years; only the last five are history
+        for (i, year) in (2018..=2023).enumerate() {
+            insert_year(&pool, 1, year, 200.0 - 20.0 * i as f64, 200.0, 50.0, 90.0, 10.0).await;
+        }
+        insert_year(&pool, 2, 2023, 0.0, 500.0, 80.0, 40.0, 0.0).await;
+        insert_year(&pool, 3, 2023, 300.0, 100.0, 20.0, -60.0, 15.0).await;
+
+        let steady = compute_leverage_metrics(&pool, 1).await.unwrap();
+        assert_eq!(steady.history.iter().map(|y| y.fiscal_year).collect::<Vec<_>>(), vec![2019, 2020, 2021, 2022, 2023]);
+        let latest = steady.latest.clone().unwrap();
+        // Debt 100, cash 50, EBITDA 90 + 10
+        assert_eq!((latest.debt_to_equity, latest.net_debt, latest.ebitda), (Some(0.5), Some(50.0), Some(100.0)));
+        assert_eq!((latest.net_debt_to_ebitda, latest.interest_coverage), (Some(0.5), Some(9.0)));
+        assert!((steady.debt_to_equity_change.unwrap() - (0.5 - 0.9)).abs() < 1e-9);
+        assert_eq!(steady.net_debt_change, Some(-80.0));
+
+        // No interest expense and negative EBITDA leave coverage empty with a reason, serialized as null
+        let no_debt = compute_leverage_metrics(&pool, 2).await.unwrap().latest.unwrap();
+        assert_eq!((no_debt.interest_coverage, no_debt.interest_coverage_note), (None, Some(LeverageNote::NoInterestExpense)));
+        let burner = compute_leverage_metrics(&pool, 3).await.unwrap().latest.unwrap();
+        assert_eq!(burner.interest_coverage_note, Some(LeverageNote::NegativeEbitda));
+        assert_eq!(burner.net_debt_to_ebitda_note, Some(LeverageNote::NegativeEbitda));
+        let json = serde_json::to_value(&burner).unwrap();
+        assert!(json["interest_coverage"].is_null());
+        assert_eq!(json["interest_coverage_note"], "negative_ebitda");
+
+        let screened = screen_low_leverage_stocks(&pool, &LowLeverageCriteria::default()).await.unwrap();
+        assert_eq!(screened.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), vec!["NODEBT", "STEADY"]);
+
+        let strict = LowLeverageCriteria { max_debt_to_equity: Some(0.25), ..LowLeverageCriteria::default() };
+        let screened = screen_low_leverage_stocks(&pool, &strict).await.unwrap();
+        assert_eq!(screened.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), vec!["NODEBT"]);
+
+        let missing = compute_leverage_metrics(&pool, 99).await.unwrap();
+        assert!(missing.latest.is_none() && missing.history.is_empty());
+    }
+}
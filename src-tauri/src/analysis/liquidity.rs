@@ -0,0 +1,138 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::safe_f64::safe_ratio;
+
+/// Current and quick ratio for one fiscal year
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LiquidityYear {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub current_assets: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub current_liabilities: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub inventory: Option<f64>,
+    /// Current assets over current liabilities
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub current_ratio: Option<f64>,
+    /// Current assets less inventory over current liabilities; None when inventory is missing
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub quick_ratio: Option<f64>,
+    /// No inventory was reported, so only the current ratio is given
+    pub inventory_missing: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LiquidityRatios {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// Oldest first
+    pub years: Vec<LiquidityYear>,
+}
+
+/// Both ratios from one balance sheet; None without positive current liabilities
+pub fn liquidity_year(
+    fiscal_year: i32,
+    report_date: String,
+    current_assets: Option<f64>,
+    current_liabilities: Option<f64>,
+    inventory: Option<f64>,
+) -> LiquidityYear {
+    let liabilities = current_liabilities.filter(|liabilities| *liabilities > 0.0);
+    let current_ratio = current_assets.zip(liabilities).and_then(|(assets, liabilities)| safe_ratio(assets, liabilities));
+    let quick_ratio = current_assets
+        .zip(inventory)
+        .zip(liabilities)
+        .and_then(|((assets, inventory), liabilities)| safe_ratio(assets - inventory, liabilities));
+
+    LiquidityYear {
+        fiscal_year,
+        report_date,
+        current_assets,
+        current_liabilities,
+        inventory,
+        current_ratio,
+        quick_ratio,
+        inventory_missing: inventory.is_none(),
+    }
+}
+
+/// Current and quick ratio per fiscal year from annual balance sheets, oldest first; the latest
+/// report wins for restated years
+pub async fn compute_liquidity_ratios(pool: &SqlitePool, stock_id: i64) -> Result<Vec<LiquidityYear>> {
+    let rows = sqlx::query(
+        "SELECT fiscal_year, report_date, current_assets, current_liabilities, inventory FROM (
+            SELECT fiscal_year, report_date, current_assets, current_liabilities, inventory,
+                   ROW_NUMBER() OVER (PARTITION BY fiscal_year ORDER BY report_date DESC) AS rn
+            FROM balance_sheets
+            WHERE stock_id = ? AND period_type = 'Annual' AND fiscal_year IS NOT NULL
+        )
+        WHERE rn = 1
+        ORDER BY fiscal_year"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            liquidity_year(
+                row.get("fiscal_year"),
+                row.get("report_date"),
+                row.get("current_assets"),
+                row.get("current_liabilities"),
+                row.get("inventory"),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_quick_ratio_falls_back_when_inventory_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'SHOP', 'Retailer')")
+            .execute(&pool).await.unwrap();
+        for (year, current_assets, current_liabilities, inventory) in [
+            (2022, 300.0, 200.0, None),
+            (2023, 500.0, 250.0, Some(150.0)),
+        ] {
+            sqlx::query(
+                "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, current_assets, current_liabilities, inventory)
+                 VALUES (1, 'Annual', ?, ?, ?, ?, ?)"
+            )
+            .bind(format!("{}-12-31", year)).bind(year).bind(current_assets).bind(current_liabilities).bind(inventory)
+            .execute(&pool).await.unwrap();
+        }
+
+        let years = compute_liquidity_ratios(&pool, 1).await.unwrap();
+        assert_eq!(years.iter().map(|year| year.fiscal_year).collect::<Vec<_>>(), vec![2022, 2023]);
+
+        let fallback = &years[0];
+        assert_eq!(fallback.current_ratio, Some(1.5));
+        assert_eq!(fallback.quick_ratio, None);
+        assert!(fallback.inventory_missing);
+
+        // 500 / 250 and (500 - 150) / 250
+        let latest = &years[1];
+        assert_eq!(latest.current_ratio, Some(2.0));
+        assert_eq!(latest.quick_ratio, Some(1.4));
+        assert!(!latest.inventory_missing);
+
+        let no_liabilities = liquidity_year(2024, "2024-12-31".to_string(), Some(100.0), Some(0.0), Some(10.0));
+        assert_eq!((no_liabilities.current_ratio, no_liabilities.quick_ratio), (None, None));
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use super::sector_relative::{ValuationMetric, LATEST_VALUATION_INPUTS};
+
+/// Values below this percentile or above `100 - CLAMP_PERCENTILE` are counted in the edge bins
+/// instead of widening the axis
+pub const CLAMP_PERCENTILE: f64 = 1.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MetricDistribution {
+    pub metric: ValuationMetric,
+    /// `counts.len() + 1` ascending edges from the 1st to the 99th percentile; empty with no values
+    pub bin_edges: Vec<f64>,
+    /// Stocks per bin; the first and last bins include the clamped outliers
+    pub counts: Vec<i64>,
+    /// Of all values, outliers included
+    pub median: Option<f64>,
+    /// S&P 500 stocks with a positive latest value
+    pub sample_size: i64,
+    pub symbol: Option<String>,
+    pub symbol_value: Option<f64>,
+    /// Bin the symbol's value falls in, clamped like the outliers
+    pub symbol_bin: Option<i64>,
+}
+
+/// Linearly interpolated percentile (0-100) of ascending `sorted`
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = percent * (sorted.len() - 1) as f64 / 100.0;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+}
+
+fn median(sorted: &[f64]) -> Option<f64> {
+    let n = sorted.len();
+    match n {
+        0 => None,
+        _ if n % 2 == 1 => Some(sorted[n / 2]),
+        _ => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0),
+    }
+}
+
+/// `count` equal-width bins starting at `low`
+struct Bins {
+    low: f64,
+    width: f64,
+    count: usize,
+}
+
+impl Bins {
+    /// Index of the bin holding `value`, with values outside the range in the edge bins
+    fn index(&self, value: f64) -> usize {
+        if self.width == 0.0 {
+            return 0;
+        }
+        ((value - self.low) / self.width).floor().clamp(0.0, (self.count - 1) as f64) as usize
+    }
+}
+
+/// Histogram of the latest positive `metric` across the S&P 500 in `buckets` bins spanning the
+/// 1st to 99th percentile. `symbol`, which need not be in the S&P 500, is placed in its bin when
+/// it has a positive value.
+pub async fn compute_metric_distribution(
+    pool: &SqlitePool,
+    metric: ValuationMetric,
+    buckets: usize,
+    symbol: Option<&str>,
+) -> Result<MetricDistribution> {
+    let query = format!(
+        "{}
+        SELECT symbol, in_sp500, value FROM (
+            SELECT s.symbol, sp.id IS NOT NULL AS in_sp500, {} AS value
+            FROM stocks s
+            JOIN ratios r ON r.stock_id = s.id
+            LEFT JOIN balance b ON b.stock_id = s.id AND b.rn = 1
+            LEFT JOIN sp500_symbols sp ON sp.id = s.id
+        )
+        WHERE value > 0 AND (in_sp500 OR symbol = ?)",
+        LATEST_VALUATION_INPUTS,
+        metric.expression()
+    );
+
+    let mut values = Vec::new();
+    let mut symbol_value = None;
+    for row in sqlx::query(&query).bind(symbol).fetch_all(pool).await? {
+        let value: f64 = row.get("value");
+        if row.get::<i64, _>("in_sp500") == 1 {
+            values.push(value);
+        }
+        if symbol == Some(row.get::<String, _>("symbol").as_str()) {
+            symbol_value = Some(value);
+        }
+    }
+    values.sort_by(f64::total_cmp);
+
+    let (bin_edges, counts, symbol_bin) = if values.is_empty() {
+        (Vec::new(), vec![0; buckets], None)
+    } else {
+        let low = percentile(&values, CLAMP_PERCENTILE);
+        let high = percentile(&values, 100.0 - CLAMP_PERCENTILE);
+        let bins = Bins { low, width: (high - low) / buckets as f64, count: buckets };
+
+        let mut counts = vec![0i64; buckets];
+        for value in &values {
+            counts[bins.index(*value)] += 1;
+        }
+        let bin_edges = (0..=buckets).map(|i| low + bins.width * i as f64).collect();
+        (bin_edges, counts, symbol_value.map(|value| bins.index(value) as i64))
+    };
+
+    Ok(MetricDistribution {
+        metric,
+        bin_edges,
+        counts,
+        median: median(&values),
+        sample_size: values.len() as i64,
+        symbol: symbol.map(str::to_string),
+        symbol_value,
+        symbol_bin,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{day, Fixture, FixtureBuilder, ValuationDay};
+
+    fn with_pe(builder: FixtureBuilder, symbol: &str, pe: f64) -> FixtureBuilder {
+        builder.with_stock(symbol).with_valuation_ratios(day(2024, 6, 28)..=day(2024, 6, 28), |_, _| ValuationDay {
+            pe_ratio_ttm: Some(pe),
+            ..Default::default()
+        })
+    }
+
+    /// 101 S&P 500 stocks: P/E 10 through 108, one at 0.1 and one at 5000, so the 1st and 99th
+    /// percentiles are 10 and 108. OTHER at 30 is outside the index and LOSS has negative earnings.
+    async fn pe_fixture() -> Fixture {
+        let mut builder = FixtureBuilder::new();
+        for (i, pe) in (10..=108).map(f64::from).chain([0.1, 5000.0]).enumerate() {
+            builder = with_pe(builder, &format!("S{}", i), pe).with_sp500_flag();
+        }
+        builder = with_pe(builder, "LOSS", -4.0).with_sp500_flag();
+        builder = with_pe(builder, "OTHER", 30.0);
+        builder.build().await
+    }
+
+    #[tokio::test]
+    async fn test_outliers_are_clamped_into_edge_bins() {
+        let fixture = pe_fixture().await;
+
+        let distribution = compute_metric_distribution(&fixture.pool, ValuationMetric::Pe, 7, Some("OTHER")).await.unwrap();
+        assert_eq!(distribution.sample_size, 101);
+        assert_eq!(distribution.bin_edges, vec![10.0, 24.0, 38.0, 52.0, 66.0, 80.0, 94.0, 108.0]);
+        // Fourteen whole P/Es per bin, 108 closes the last one, and 0.1 and 5000 join the edges
+        assert_eq!(distribution.counts, vec![15, 14, 14, 14, 14, 14, 16]);
+        assert_eq!(distribution.median, Some(59.0));
+        assert_eq!((distribution.symbol_value, distribution.symbol_bin), (Some(30.0), Some(1)));
+
+        let outlier = compute_metric_distribution(&fixture.pool, ValuationMetric::Pe, 7, Some("S100")).await.unwrap();
+        assert_eq!((outlier.symbol_value, outlier.symbol_bin), (Some(5000.0), Some(6)));
+        assert_eq!(outlier.counts, distribution.counts);
+
+        let loss = compute_metric_distribution(&fixture.pool, ValuationMetric::Pe, 7, Some("LOSS")).await.unwrap();
+        assert_eq!((loss.symbol_value, loss.symbol_bin), (None, None));
+    }
+}
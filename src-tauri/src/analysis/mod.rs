@@ -1,8 +1,62 @@
 pub mod pe_statistics;
 pub mod recommendation_engine;
+pub mod risk_metrics;
+pub mod price_range;
+pub mod drawdown;
+pub mod stock_summary;
+pub mod ratio_provenance;
+pub mod ps_history;
+pub mod valuation_ratios;
+pub mod sector_overview;
+pub mod growth_consistency;
+pub mod sector_relative;
+pub mod metric_distribution;
+pub mod leverage;
+pub mod liquidity;
+pub mod free_cash_flow;
+pub mod quality;
+pub mod piotroski_history;
+pub mod dividend_growth;
+pub mod total_return;
+pub mod momentum;
+pub mod industry_growth;
+pub mod benchmark;
+pub mod shareholder_yield;
+pub mod fiscal_calendar;
+pub mod valuation_history;
+pub mod accruals;
+pub mod watchlist_snapshot;
+pub mod fiscal_year_financials;
 
 pub use pe_statistics::*;
 pub use recommendation_engine::*;
+pub use risk_metrics::*;
+pub use price_range::*;
+pub use drawdown::*;
+pub use stock_summary::*;
+pub use ratio_provenance::*;
+pub use ps_history::*;
+pub use valuation_ratios::*;
+pub use sector_overview::*;
+pub use growth_consistency::*;
+pub use sector_relative::*;
+pub use metric_distribution::*;
+pub use leverage::*;
+pub use liquidity::*;
+pub use free_cash_flow::*;
+pub use quality::*;
+pub use piotroski_history::*;
+pub use dividend_growth::*;
+pub use total_return::*;
+pub use momentum::*;
+pub use industry_growth::*;
+pub use benchmark::*;
+pub use shareholder_yield::*;
+pub use fiscal_calendar::*;
+pub use valuation_history::*;
+pub use accruals::*;
+pub use watchlist_snapshot::*;
+pub use fiscal_year_financials::*;
 
 // Re-export Tauri commands from commands::analysis
 pub use crate::commands::analysis::{
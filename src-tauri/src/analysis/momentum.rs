@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{Duration, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+/// How far before an offset date the nearest bar may be; a stock without a bar in that window
+/// doesn't have the history for the return. Covers weekends and holiday runs.
+pub const NEAREST_BAR_TOLERANCE_DAYS: i64 = 7;
+
+/// Closes at the as-of date and each offset, with the number of bars in the 12-month window.
+/// Each offset takes the latest bar in (offset - tolerance, offset]; every pick is one range scan
+/// of the date index, so the query stays a single pass however many stocks there are.
+const MOMENTUM_CLOSES: &str = "
+    WITH picks AS (
+        SELECT stock_id, 'latest' AS label, MAX(date) AS date FROM daily_prices
+        WHERE date > ?1 AND date <= ?2 AND close_price IS NOT NULL GROUP BY stock_id
+        UNION ALL
+        SELECT stock_id, '1m', MAX(date) FROM daily_prices
+        WHERE date > ?3 AND date <= ?4 AND close_price IS NOT NULL GROUP BY stock_id
+        UNION ALL
+        SELECT stock_id, '6m', MAX(date) FROM daily_prices
+        WHERE date > ?5 AND date <= ?6 AND close_price IS NOT NULL GROUP BY stock_id
+        UNION ALL
+        SELECT stock_id, '12m', MAX(date) FROM daily_prices
+        WHERE date > ?7 AND date <= ?8 AND close_price IS NOT NULL GROUP BY stock_id
+    ),
+    history AS (
+        SELECT stock_id, COUNT(*) AS trading_days FROM daily_prices
+        WHERE date > ?8 AND date <= ?2 AND close_price IS NOT NULL GROUP BY stock_id
+    )
+    SELECT s.id AS stock_id, s.symbol, s.sector,
+           MAX(CASE WHEN k.label = 'latest' THEN k.date END) AS latest_date,
+           MAX(CASE WHEN k.label = 'latest' THEN p.close_price END) AS latest_close,
+           MAX(CASE WHEN k.label = '1m' THEN p.close_price END) AS close_1m,
+           MAX(CASE WHEN k.label = '6m' THEN p.close_price END) AS close_6m,
+           MAX(CASE WHEN k.label = '12m' THEN p.close_price END) AS close_12m,
+           COALESCE(h.trading_days, 0) AS trading_days
+    FROM picks k
+    JOIN stocks s ON s.id = k.stock_id
+    JOIN daily_prices p ON p.stock_id = k.stock_id AND p.date = k.date
+    LEFT JOIN history h ON h.stock_id = k.stock_id
+    GROUP BY s.id
+    HAVING latest_date IS NOT NULL";
+
+/// Offset dates behind an as-of date. Month arithmetic clamps to the month's last day,
+/// so March 31 looks back to February 28 or 29.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MomentumDates {
+    pub as_of: NaiveDate,
+    pub one_month: NaiveDate,
+    pub six_months: NaiveDate,
+    pub twelve_months: NaiveDate,
+}
+
+impl MomentumDates {
+    pub fn new(as_of: NaiveDate) -> Self {
+        let back = |months: u32| as_of.checked_sub_months(Months::new(months)).unwrap_or(as_of);
+        Self {
+            as_of,
+            one_month: back(1),
+            six_months: back(6),
+            twelve_months: back(12),
+        }
+    }
+}
+
+/// Filters and options for the momentum rankings; a None bound is not checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MomentumCriteria {
+    /// YYYY-MM-DD; defaults to the latest stored price date
+    pub as_of: Option<String>,
+    /// Latest close at or above this
+    pub min_price: Option<f64>,
+    /// Bars in the last 12 months at or above this
+    pub min_trading_days: Option<i32>,
+    /// Trending Value: only stocks in the cheapest `max_value_percentile` of the value
+    /// composite, ranked by 6-month return
+    pub trending_value: Option<bool>,
+    pub max_value_percentile: Option<f64>,
+    pub limit: Option<i32>,
+}
+
+impl Default for MomentumCriteria {
+    fn default() -> Self {
+        Self {
+            as_of: None,
+            min_price: Some(5.0),
+            min_trading_days: Some(200),
+            trending_value: Some(false),
+            max_value_percentile: Some(10.0),
+            limit: Some(100),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MomentumRanking {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub sector: Option<String>,
+    pub latest_date: String,
+    pub latest_close: f64,
+    /// Percent change in close over six months
+    pub return_6m_pct: f64,
+    /// Percent change in close from twelve months back to one month back, skipping the latest month
+    pub return_12_1m_pct: f64,
+    /// 100 is the strongest of the ranked stocks, 0 the weakest
+    pub percentile_6m: f64,
+    pub percentile_12_1m: f64,
+    /// Mean of the two percentiles
+    pub momentum_percentile: f64,
+    /// Value composite percentile (lower is cheaper), when combined with value
+    pub value_percentile: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MomentumRankings {
+    pub as_of: String,
+    pub rankings: Vec<MomentumRanking>,
+    /// Stocks with a recent close but no bar near an offset date, or too few bars
+    pub excluded_insufficient_history: i32,
+    pub excluded_below_min_price: i32,
+}
+
+/// Percentile of each value among `values`: the share of the others it beats, ties counting
+/// half, so 100 is the highest and 0 the lowest
+pub fn percentile_ranks(values: &[f64]) -> Vec<f64> {
+    if values.len() < 2 {
+        return vec![100.0; values.len()];
+    }
+    let others = (values.len() - 1) as f64;
+    values
+        .iter()
+        .map(|value| {
+            let below = values.iter().filter(|other| *other < value).count() as f64;
+            let tied = values.iter().filter(|other| *other == value).count() as f64 - 1.0;
+            (below + tied / 2.0) / others * 100.0
+        })
+        .collect()
+}
+
+/// 6-month and 12-1 month returns of every stock with a close within the tolerance of the as-of
+/// date, percentile-ranked, strongest combined momentum first (strongest 6-month return first
+/// for Trending Value)
+pub async fn compute_momentum_rankings(pool: &SqlitePool, criteria: &MomentumCriteria) -> Result<MomentumRankings> {
+    let as_of = match &criteria.as_of {
+        Some(date) => Some(NaiveDate::parse_from_str(date, "%Y-%m-%d")?),
+        None => sqlx::query_scalar::<_, Option<NaiveDate>>("SELECT MAX(date) FROM daily_prices")
+            .fetch_one(pool)
+            .await?,
+    };
+    let Some(as_of) = as_of else {
+        return Ok(MomentumRankings {
+            as_of: String::new(),
+            rankings: Vec::new(),
+            excluded_insufficient_history: 0,
+            excluded_below_min_price: 0,
+        });
+    };
+
+    let dates = MomentumDates::new(as_of);
+    let mut query = sqlx::query(MOMENTUM_CLOSES);
+    for date in [dates.as_of, dates.one_month, dates.six_months, dates.twelve_months] {
+        query = query.bind(date - Duration::days(NEAREST_BAR_TOLERANCE_DAYS)).bind(date);
+    }
+    let rows = query.fetch_all(pool).await?;
+
+    let mut insufficient_history = 0;
+    let mut below_min_price = 0;
+    let mut candidates = Vec::new();
+    for row in &rows {
+        let latest_close: f64 = row.get("latest_close");
+        let closes: (Option<f64>, Option<f64>, Option<f64>) = (row.get("close_1m"), row.get("close_6m"), row.get("close_12m"));
+        let trading_days: i32 = row.get("trading_days");
+
+        let (Some(close_1m), Some(close_6m), Some(close_12m)) = closes else {
+            insufficient_history += 1;
+            continue;
+        };
+        if criteria.min_trading_days.is_some_and(|min| trading_days < min) || close_6m <= 0.0 || close_12m <= 0.0 {
+            insufficient_history += 1;
+            continue;
+        }
+        if criteria.min_price.is_some_and(|min| latest_close < min) {
+            below_min_price += 1;
+            continue;
+        }
+
+        candidates.push(MomentumRanking {
+            stock_id: row.get("stock_id"),
+            symbol: row.get("symbol"),
+            sector: row.get("sector"),
+            latest_date: row.get::<NaiveDate, _>("latest_date").format("%Y-%m-%d").to_string(),
+            latest_close,
+            return_6m_pct: (latest_close / close_6m - 1.0) * 100.0,
+            return_12_1m_pct: (close_1m / close_12m - 1.0) * 100.0,
+            percentile_6m: 0.0,
+            percentile_12_1m: 0.0,
+            momentum_percentile: 0.0,
+            value_percentile: None,
+        });
+    }
+
+    let six = percentile_ranks(&candidates.iter().map(|c| c.return_6m_pct).collect::<Vec<_>>());
+    let twelve = percentile_ranks(&candidates.iter().map(|c| c.return_12_1m_pct).collect::<Vec<_>>());
+    for (candidate, (six, twelve)) in candidates.iter_mut().zip(six.into_iter().zip(twelve)) {
+        candidate.percentile_6m = six;
+        candidate.percentile_12_1m = twelve;
+        candidate.momentum_percentile = (six + twelve) / 2.0;
+    }
+
+    if criteria.trending_value.unwrap_or(false) {
+        let value: HashMap<i64, f64> = sqlx::query_as::<_, (i64, f64)>(
+            "SELECT stock_id, composite_percentile FROM oshaughnessy_ranking WHERE composite_percentile IS NOT NULL"
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+        let max_percentile = criteria.max_value_percentile.unwrap_or(100.0);
+
+        candidates.retain_mut(|candidate| {
+            candidate.value_percentile = value.get(&candidate.stock_id).copied();
+            candidate.value_percentile.is_some_and(|percentile| percentile <= max_percentile)
+        });
+        candidates.sort_by(|a, b| b.return_6m_pct.total_cmp(&a.return_6m_pct).then_with(|| a.symbol.cmp(&b.symbol)));
+    } else {
+        candidates.sort_by(|a, b| {
+            b.momentum_percentile
+                .total_cmp(&a.momentum_percentile)
+                .then_with(|| b.return_6m_pct.total_cmp(&a.return_6m_pct))
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+    }
+    if let Some(limit) = criteria.limit {
+        candidates.truncate(limit.max(0) as usize);
+    }
+
+    Ok(MomentumRankings {
+        as_of: as_of.format("%Y-%m-%d").to_string(),
+        rankings: candidates,
+        excluded_insufficient_history: insufficient_history,
+        excluded_below_min_price: below_min_price,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_offsets_clamp_to_month_end() {
+        let leap = MomentumDates::new(date("2024-03-31"));
+        assert_eq!((leap.one_month, leap.six_months, leap.twelve_months), (date("2024-02-29"), date("2023-09-30"), date("2023-03-31")));
+
+        let plain = MomentumDates::new(date("2023-03-31"));
+        assert_eq!(plain.one_month, date("2023-02-28"));
+
+        let mid = MomentumDates::new(date("2024-08-15"));
+        assert_eq!((mid.one_month, mid.six_months, mid.twelve_months), (date("2024-07-15"), date("2024-02-15"), date("2023-08-15")));
+    }
+
+    #[test]
+    fn test_percentile_ranks() {
+        assert_eq!(percentile_ranks(&[10.0, 30.0, 20.0]), vec![0.0, 100.0, 50.0]);
+        assert_eq!(percentile_ranks(&[5.0, 5.0]), vec![50.0, 50.0]);
+        assert_eq!(percentile_ranks(&[7.0]), vec![100.0]);
+    }
+
+    #[tokio::test]
+    async fn test_rankings_use_nearest_earlier_bar_and_report_exclusions() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // As of Friday 2024-05-31 the offsets are 2024-04-30, 2023-11-30 and 2023-05-31
+        let stocks: [(&str, &[(&str, f64)]); 4] = [
+            ("UP", &[("2023-05-31", 50.0), ("2023-11-30", 80.0), ("2024-04-30", 100.0), ("2024-05-31", 90.0)]),
+            // No bar on 2023-11-30: the Monday before is used, never the Friday after
+            ("GAP", &[("2023-05-31", 100.0), ("2023-11-27", 100.0), ("2023-12-01", 999.0), ("2024-04-30", 120.0), ("2024-05-31", 110.0)]),
+            // Listed in September; its nearest bar to the 12-month offset is months off
+            ("YOUNG", &[("2023-09-01", 10.0), ("2023-11-30", 10.0), ("2024-04-30", 12.0), ("2024-05-31", 13.0)]),
+            ("PENNY", &[("2023-05-31", 1.0), ("2023-11-30", 2.0), ("2024-04-30", 3.0), ("2024-05-31", 4.0)]),
+        ];
+        for (id, (symbol, bars)) in stocks.into_iter().enumerate() {
+            let id = id as i64 + 1;
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (?, ?, ?)")
+                .bind(id).bind(symbol).bind(symbol)
+                .execute(&pool).await.unwrap();
+            for (day, close) in bars {
+                sqlx::query(
+                    "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                     VALUES (?, ?, ?, ?, ?, ?)"
+                )
+                .bind(id).bind(day).bind(close).bind(close).bind(close).bind(close)
+                .execute(&pool).await.unwrap();
+            }
+        }
+
+        let criteria = MomentumCriteria { min_trading_days: None, ..MomentumCriteria::default() };
+        let result = compute_momentum_rankings(&pool, &criteria).await.unwrap();
+        assert_eq!(result.as_of, "2024-05-31");
+        assert_eq!((result.excluded_insufficient_history, result.excluded_below_min_price), (1, 1));
+
+        let ranked: Vec<(&str, f64, f64)> = result
+            .rankings
+            .iter()
+            .map(|r| (r.symbol.as_str(), r.return_6m_pct.round(), r.return_12_1m_pct.round()))
+            .collect();
+        // UP: 90 / 80 and 100 / 50; GAP: 110 / 100 and 120 / 100
+        assert_eq!(ranked, vec![("UP", 13.0, 100.0), ("GAP", 10.0, 20.0)]);
+        assert_eq!((result.rankings[0].momentum_percentile, result.rankings[1].momentum_percentile), (100.0, 0.0));
+
+        // Four bars in the window is below any realistic floor
+        let strict = compute_momentum_rankings(&pool, &MomentumCriteria::default()).await.unwrap();
+        assert!(strict.rankings.is_empty());
+        assert_eq!(strict.excluded_insufficient_history, 3);
+    }
+}
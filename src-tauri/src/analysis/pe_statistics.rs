@@ -1,13 +1,22 @@
 use serde::{Deserialize, Serialize};
 
+use crate::safe_f64::mean_and_std_dev;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PEStatistics {
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub min: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub max: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub mean: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub median: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub percentile_25: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub percentile_75: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub volatility: f64,
     pub data_points: usize,
 }
@@ -16,13 +25,20 @@ pub struct PEStatistics {
 pub struct PEAnalysis {
     pub symbol: String,
     pub company_name: String,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
     pub current_pe: Option<f64>,
     pub current_pe_date: Option<String>, // Date of the current P/E ratio
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub historical_min: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub historical_max: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub historical_avg: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub historical_median: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub value_score: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
     pub risk_score: f64,
     pub value_threshold: f64, // 20% above historical min
     pub is_value_stock: bool,
@@ -51,8 +67,8 @@ pub fn calculate_pe_statistics(pe_data: &[f64]) -> PEStatistics {
         return PEStatistics::new();
     }
 
-    // Filter out negative P/E ratios for statistical analysis
-    let positive_pe: Vec<f64> = pe_data.iter().copied().filter(|&pe| pe > 0.0).collect();
+    // Filter out negative (and non-finite) P/E ratios for statistical analysis
+    let positive_pe: Vec<f64> = pe_data.iter().copied().filter(|&pe| pe > 0.0 && pe.is_finite()).collect();
     
     if positive_pe.is_empty() {
         return PEStatistics::new();
@@ -64,11 +80,12 @@ pub fn calculate_pe_statistics(pe_data: &[f64]) -> PEStatistics {
     let len = sorted_pe.len();
     let min = sorted_pe[0];
     let max = sorted_pe[len - 1];
-    let mean = sorted_pe.iter().sum::<f64>() / len as f64;
+    let (mean, volatility) = mean_and_std_dev(&sorted_pe).unwrap_or_default();
     
     // Calculate median
     let median = if len % 2 == 0 {
-        (sorted_pe[len / 2 - 1] + sorted_pe[len / 2]) / 2.0
+        // Halved first so the largest P/Es can't overflow
+        sorted_pe[len / 2 - 1] / 2.0 + sorted_pe[len / 2] / 2.0
     } else {
         sorted_pe[len / 2]
     };
@@ -79,12 +96,6 @@ pub fn calculate_pe_statistics(pe_data: &[f64]) -> PEStatistics {
     let percentile_25 = sorted_pe[percentile_25_idx.min(len - 1)];
     let percentile_75 = sorted_pe[percentile_75_idx.min(len - 1)];
 
-    // Calculate volatility (standard deviation)
-    let variance = sorted_pe.iter()
-        .map(|&pe| (pe - mean).powi(2))
-        .sum::<f64>() / len as f64;
-    let volatility = variance.sqrt();
-
     PEStatistics {
         min,
         max,
@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::analysis::fiscal_year_financials::{load_latest_fiscal_years, FiscalYearFinancials, LatestFiscalYears};
+use crate::safe_f64::safe_ratio;
+
+/// Latest annual statements per fiscal year, one row per year with an income statement
+const ANNUAL_PIOTROSKI_INPUTS: &str = "
+    WITH income AS (
+        SELECT fiscal_year, report_date, net_income, revenue,
+               ROW_NUMBER() OVER (PARTITION BY fiscal_year ORDER BY report_date DESC) AS rn
+        FROM income_statements
+        WHERE stock_id = ?1 AND period_type = 'FY' AND fiscal_year IS NOT NULL
+    ),
+    balance AS (
+        SELECT fiscal_year, total_assets, total_debt, current_assets, current_liabilities, shares_outstanding,
+               ROW_NUMBER() OVER (PARTITION BY fiscal_year ORDER BY report_date DESC) AS rn
+        FROM balance_sheets
+        WHERE stock_id = ?1 AND period_type = 'Annual' AND fiscal_year IS NOT NULL
+    ),
+    cash_flow AS (
+        SELECT fiscal_year, operating_cash_flow,
+               ROW_NUMBER() OVER (PARTITION BY fiscal_year ORDER BY report_date DESC) AS rn
+        FROM cash_flow_statements
+        WHERE stock_id = ?1 AND period_type = 'Annual' AND fiscal_year IS NOT NULL
+    )
+    SELECT i.fiscal_year, i.report_date, i.net_income, i.revenue,
+           b.total_assets, b.total_debt, b.current_assets, b.current_liabilities, b.shares_outstanding,
+           c.operating_cash_flow
+    FROM income i
+    LEFT JOIN balance b ON b.fiscal_year = i.fiscal_year AND b.rn = 1
+    LEFT JOIN cash_flow c ON c.fiscal_year = i.fiscal_year AND c.rn = 1
+    WHERE i.rn = 1
+    ORDER BY i.fiscal_year";
+
+/// F-Score breakdown for one fiscal year against the year before; each criterion is 1 when met
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PiotroskiYear {
+    pub fiscal_year: i32,
+    pub report_date: String,
+
+    // Profitability
+    pub criterion_positive_net_income: i32,
+    pub criterion_positive_operating_cash_flow: i32,
+    pub criterion_improving_roa: i32,
+    pub criterion_cash_flow_quality: i32,
+
+    // Leverage and liquidity
+    pub criterion_decreasing_debt_ratio: i32,
+    pub criterion_improving_current_ratio: i32,
+    pub criterion_no_dilution: i32,
+
+    // Operating efficiency
+    pub criterion_improving_net_margin: i32,
+    pub criterion_improving_asset_turnover: i32,
+
+    /// Sum of the nine criteria, 0-9
+    pub f_score: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PiotroskiHistory {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// Oldest first; only years whose previous fiscal year is stored
+    pub years: Vec<PiotroskiYear>,
+}
+
+/// `numerator / denominator` when both are known and the quotient is finite
+fn ratio(numerator: Option<f64>, denominator: Option<f64>) -> Option<f64> {
+    numerator.zip(denominator).and_then(|(n, d)| safe_ratio(n, d))
+}
+
+/// 1 when `current` beats `prior` by `better`, 0 when either is unknown
+fn improved(current: Option<f64>, prior: Option<f64>, better: fn(f64, f64) -> bool) -> i32 {
+    current.zip(prior).map_or(0, |(current, prior)| better(current, prior) as i32)
+}
+
+/// The nine criteria for `current` against `prior`, as the Piotroski screen scores the latest
+/// year: a criterion with missing inputs is not met
+pub fn piotroski_year(current: &FiscalYearFinancials, prior: &FiscalYearFinancials) -> PiotroskiYear {
+    let higher = |current: f64, prior: f64| current > prior;
+
+    let criterion_positive_net_income = current.net_income.map_or(0, |income| (income > 0.0) as i32);
+    let criterion_positive_operating_cash_flow = current.operating_cash_flow.map_or(0, |cash| (cash > 0.0) as i32);
+    let criterion_improving_roa = improved(
+        ratio(current.net_income, current.total_assets),
+        ratio(prior.net_income, prior.total_assets),
+        higher,
+    );
+    let criterion_cash_flow_quality = improved(current.operating_cash_flow, current.net_income, higher);
+    let criterion_decreasing_debt_ratio = improved(
+        ratio(current.total_debt, current.total_assets),
+        ratio(prior.total_debt, prior.total_assets),
+        |current, prior| current < prior,
+    );
+    let criterion_improving_current_ratio = improved(
+        ratio(current.current_assets, current.current_liabilities),
+        ratio(prior.current_assets, prior.current_liabilities),
+        higher,
+    );
+    let criterion_no_dilution = improved(current.shares_outstanding, prior.shares_outstanding, |current, prior| current <= prior);
+    let criterion_improving_net_margin = improved(
+        ratio(current.net_income, current.revenue),
+        ratio(prior.net_income, prior.revenue),
+        higher,
+    );
+    let criterion_improving_asset_turnover = improved(
+        ratio(current.revenue, current.total_assets),
+        ratio(prior.revenue, prior.total_assets),
+        higher,
+    );
+
+    PiotroskiYear {
+        fiscal_year: current.fiscal_year,
+        report_date: current.report_date.clone(),
+        criterion_positive_net_income,
+        criterion_positive_operating_cash_flow,
+        criterion_improving_roa,
+        criterion_cash_flow_quality,
+        criterion_decreasing_debt_ratio,
+        criterion_improving_current_ratio,
+        criterion_no_dilution,
+        criterion_improving_net_margin,
+        criterion_improving_asset_turnover,
+        f_score: criterion_positive_net_income
+            + criterion_positive_operating_cash_flow
+            + criterion_improving_roa
+            + criterion_cash_flow_quality
+            + criterion_decreasing_debt_ratio
+            + criterion_improving_current_ratio
+            + criterion_no_dilution
+            + criterion_improving_net_margin
+            + criterion_improving_asset_turnover,
+    }
+}
+
+/// Percent of the screen's 17 inputs that are stored (net income counts twice per year, as it
+/// feeds two criteria), rounded down; a missing prior year counts as all missing
+pub fn piotroski_data_completeness(current: &FiscalYearFinancials, prior: Option<&FiscalYearFinancials>) -> i32 {
+    let current_inputs = [
+        current.net_income,
+        current.operating_cash_flow,
+        current.total_assets,
+        current.total_debt,
+        current.current_assets,
+        current.current_liabilities,
+        current.shares_outstanding,
+        current.net_income,
+        current.revenue,
+    ];
+    let prior_inputs = prior.map_or([None; 8], |prior| {
+        [
+            prior.net_income,
+            prior.total_assets,
+            prior.total_debt,
+            prior.current_assets,
+            prior.current_liabilities,
+            prior.shares_outstanding,
+            prior.net_income,
+            prior.revenue,
+        ]
+    });
+    let stored = current_inputs.iter().chain(prior_inputs.iter()).filter(|input| input.is_some()).count();
+    (stored * 100 / 17) as i32
+}
+
+/// A stock's F-Score for its latest fiscal year, as the screen scores it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatestPiotroskiScore {
+    pub year: PiotroskiYear,
+    pub data_completeness_score: i32,
+}
+
+/// Score the latest fiscal year against the prior one; None without a latest-year net income
+/// and operating cash flow. A missing prior year scores as all inputs missing.
+pub fn latest_piotroski_score(years: &LatestFiscalYears) -> Option<LatestPiotroskiScore> {
+    let current = &years.current;
+    if current.net_income.is_none() || current.operating_cash_flow.is_none() {
+        return None;
+    }
+    Some(LatestPiotroskiScore {
+        year: piotroski_year(current, years.prior.as_ref().unwrap_or(&FiscalYearFinancials::default())),
+        data_completeness_score: piotroski_data_completeness(current, years.prior.as_ref()),
+    })
+}
+
+/// Latest F-Scores of every stock the screen can score, or of `stock_ids`, from one query per
+/// statement type
+pub async fn load_latest_piotroski_scores(pool: &SqlitePool, stock_ids: Option<&[i64]>) -> Result<BTreeMap<i64, LatestPiotroskiScore>> {
+    Ok(load_latest_fiscal_years(pool, stock_ids)
+        .await?
+        .iter()
+        .filter_map(|(stock_id, years)| latest_piotroski_score(years).map(|score| (*stock_id, score)))
+        .collect())
+}
+
+/// `scores` as a derived table of (stock_id, f_score_complete, data_completeness_score), for
+/// queries that join or filter on the F-Score
+pub fn piotroski_scores_sql(scores: &BTreeMap<i64, LatestPiotroskiScore>) -> String {
+    if scores.is_empty() {
+        return "(SELECT NULL AS stock_id, NULL AS f_score_complete, NULL AS data_completeness_score WHERE 0)".to_string();
+    }
+    let rows: Vec<String> = scores
+        .iter()
+        .map(|(stock_id, score)| format!("({}, {}, {})", stock_id, score.year.f_score, score.data_completeness_score))
+        .collect();
+    format!(
+        "(SELECT column1 AS stock_id, column2 AS f_score_complete, column3 AS data_completeness_score FROM (VALUES {}))",
+        rows.join(", ")
+    )
+}
+
+/// F-Score of every fiscal year with an annual income statement for it and the year before,
+/// oldest first; the latest report wins for restated years
+pub async fn compute_piotroski_history(pool: &SqlitePool, stock_id: i64) -> Result<Vec<PiotroskiYear>> {
+    let inputs: Vec<FiscalYearFinancials> = sqlx::query(ANNUAL_PIOTROSKI_INPUTS)
+        .bind(stock_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| FiscalYearFinancials {
+            fiscal_year: row.get("fiscal_year"),
+            report_date: row.get("report_date"),
+            net_income: row.get("net_income"),
+            revenue: row.get("revenue"),
+            total_assets: row.get("total_assets"),
+            total_debt: row.get("total_debt"),
+            current_assets: row.get("current_assets"),
+            current_liabilities: row.get("current_liabilities"),
+            shares_outstanding: row.get("shares_outstanding"),
+            operating_cash_flow: row.get("operating_cash_flow"),
+            ..Default::default()
+        })
+        .collect();
+
+    Ok(inputs
+        .windows(2)
+        .filter(|pair| pair[1].fiscal_year == pair[0].fiscal_year + 1)
+        .map(|pair| piotroski_year(&pair[1], &pair[0]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_scores_each_year_with_a_prior_year() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'TURN', 'Turnaround Co')")
+            .execute(&pool).await.unwrap();
+        // 2022 improves on 2021 everywhere; 2023 loses money, borrows and issues shares
+        for (year, net_income, revenue, assets, debt, current_assets, current_liabilities, shares, operating_cash_flow) in [
+            (2021, 50.0, 1000.0, 1000.0, 400.0, 300.0, 200.0, 100.0, 40.0),
+            (2022, 100.0, 1200.0, 1000.0, 300.0, 400.0, 200.0, 95.0, 150.0),
+            (2023, -20.0, 1100.0, 1100.0, 500.0, 300.0, 250.0, 110.0, 30.0),
+        ] {
+            let report_date = format!("{}-12-31", year);
+            sqlx::query(
+                "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, net_income, revenue)
+                 VALUES (1, 'FY', ?, ?, ?, ?)"
+            )
+            .bind(&report_date).bind(year).bind(net_income).bind(revenue)
+            .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_assets, total_debt,
+                    current_assets, current_liabilities, shares_outstanding)
+                 VALUES (1, 'Annual', ?, ?, ?, ?, ?, ?, ?)"
+            )
+            .bind(&report_date).bind(year).bind(assets).bind(debt).bind(current_assets).bind(current_liabilities).bind(shares)
+            .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, operating_cash_flow)
+                 VALUES (1, 'Annual', ?, ?, ?)"
+            )
+            .bind(&report_date).bind(year).bind(operating_cash_flow)
+            .execute(&pool).await.unwrap();
+        }
+
+        let history = compute_piotroski_history(&pool, 1).await.unwrap();
+        assert_eq!(history.iter().map(|year| year.fiscal_year).collect::<Vec<_>>(), vec![2022, 2023]);
+
+        let strong = &history[0];
+        assert_eq!(strong.f_score, 9);
+
+        // Only positive operating cash flow, above the net loss
+        let weak = &history[1];
+        assert_eq!(weak.f_score, 2);
+        assert_eq!((weak.criterion_positive_operating_cash_flow, weak.criterion_cash_flow_quality), (1, 1));
+        assert_eq!((weak.criterion_positive_net_income, weak.criterion_no_dilution, weak.criterion_improving_asset_turnover), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_missing_prior_year_scores_current_year_criteria_only() {
+        let current = FiscalYearFinancials {
+            fiscal_year: 2023,
+            net_income: Some(100.0),
+            revenue: Some(1000.0),
+            total_assets: Some(2000.0),
+            total_debt: Some(500.0),
+            current_assets: Some(300.0),
+            current_liabilities: Some(200.0),
+            shares_outstanding: Some(50.0),
+            operating_cash_flow: Some(150.0),
+            ..Default::default()
+        };
+
+        // Positive income and cash flow, with cash flow above income; nothing to improve on
+        let alone = piotroski_year(&current, &FiscalYearFinancials::default());
+        assert_eq!(alone.f_score, 3);
+        assert_eq!(piotroski_data_completeness(&current, None), 52);
+
+        let prior = FiscalYearFinancials { fiscal_year: 2022, revenue: None, total_debt: None, ..current.clone() };
+        assert_eq!(piotroski_data_completeness(&current, Some(&prior)), 88);
+        assert_eq!(piotroski_data_completeness(&current, Some(&current)), 100);
+    }
+}
@@ -0,0 +1,351 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::analysis::piotroski_history::{load_latest_piotroski_scores, piotroski_scores_sql};
+use crate::analysis::valuation_ratios::STATEMENT_FREE_CASH_FLOW;
+
+/// Calendar days in the trailing 52-week window
+const WINDOW_DAYS: i64 = 52 * 7;
+
+/// Fewer trading days than this in the window means the range may miss the true high or low
+const MIN_TRADING_DAYS: i32 = 200;
+
+/// Trailing 52-week high/low from stored daily prices
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Week52Range {
+    pub stock_id: i64,
+    pub as_of_date: String,
+    pub window_start: String,
+    pub trading_days: i32,
+    pub high_52_week: Option<f64>,
+    pub low_52_week: Option<f64>,
+    pub latest_close: Option<f64>,
+    /// Percent of the latest close below the 52-week high (zero or negative)
+    pub pct_from_high: Option<f64>,
+    /// Percent of the latest close above the 52-week low (zero or positive)
+    pub pct_from_low: Option<f64>,
+    pub is_complete: bool,
+    /// The stock's first bar is inside the window, so the range covers only the bars since
+    pub limited_history: bool,
+    pub note: Option<String>,
+}
+
+fn pct_distance(from: f64, to: Option<f64>) -> Option<f64> {
+    to.filter(|t| *t > 0.0).map(|t| (from - t) / t * 100.0)
+}
+
+pub async fn compute_52_week_range(pool: &SqlitePool, stock_id: i64, as_of: NaiveDate) -> Result<Week52Range, sqlx::Error> {
+    let window_start = as_of - Duration::days(WINDOW_DAYS - 1);
+    let as_of_str = as_of.format("%Y-%m-%d").to_string();
+    let window_start_str = window_start.format("%Y-%m-%d").to_string();
+
+    let row = sqlx::query(
+        "SELECT
+            COUNT(*) AS trading_days,
+            MAX(COALESCE(high_price, close_price)) AS high_52_week,
+            MIN(COALESCE(low_price, close_price)) AS low_52_week,
+            (SELECT close_price FROM daily_prices
+             WHERE stock_id = ?1 AND date >= ?2 AND date <= ?3
+             ORDER BY date DESC LIMIT 1) AS latest_close,
+            (SELECT MIN(date) FROM daily_prices WHERE stock_id = ?1) AS first_date
+         FROM daily_prices
+         WHERE stock_id = ?1 AND date >= ?2 AND date <= ?3"
+    )
+    .bind(stock_id)
+    .bind(&window_start_str)
+    .bind(&as_of_str)
+    .fetch_one(pool)
+    .await?;
+
+    let trading_days = row.get::<i64, _>("trading_days") as i32;
+    let high_52_week: Option<f64> = row.get("high_52_week");
+    let low_52_week: Option<f64> = row.get("low_52_week");
+    let latest_close: Option<f64> = row.get("latest_close");
+    let first_date: Option<String> = row.get("first_date");
+
+    let is_complete = trading_days >= MIN_TRADING_DAYS;
+    let limited_history = first_date.as_deref().is_some_and(|first| first > window_start_str.as_str());
+    let note = if limited_history {
+        Some(format!(
+            "Prices start {}; the high and low cover only the {} trading days since",
+            first_date.unwrap_or_default(), trading_days
+        ))
+    } else {
+        (!is_complete).then(|| {
+            format!(
+                "Only {} trading days in the 52-week window (need {}); the range may be incomplete",
+                trading_days, MIN_TRADING_DAYS
+            )
+        })
+    };
+
+    Ok(Week52Range {
+        stock_id,
+        as_of_date: as_of_str,
+        window_start: window_start_str,
+        trading_days,
+        high_52_week,
+        low_52_week,
+        latest_close,
+        pct_from_high: latest_close.and_then(|close| pct_distance(close, high_52_week)),
+        pct_from_low: latest_close.and_then(|close| pct_distance(close, low_52_week)),
+        is_complete,
+        limited_history,
+        note,
+    })
+}
+
+/// Recompute and store the 52-week range of a stock as of its latest close. None when it has no prices.
+pub async fn refresh_52_week_range(pool: &SqlitePool, stock_id: i64) -> Result<Option<Week52Range>, sqlx::Error> {
+    let latest: Option<NaiveDate> = sqlx::query_scalar("SELECT MAX(date) FROM daily_prices WHERE stock_id = ?")
+        .bind(stock_id)
+        .fetch_one(pool)
+        .await?;
+    let Some(as_of) = latest else {
+        return Ok(None);
+    };
+
+    let range = compute_52_week_range(pool, stock_id, as_of).await?;
+    sqlx::query(
+        "INSERT OR REPLACE INTO price_range_52w
+            (stock_id, as_of_date, window_start, trading_days, high_52_week, low_52_week, latest_close,
+             pct_from_high, pct_from_low, limited_history, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)"
+    )
+    .bind(stock_id)
+    .bind(&range.as_of_date)
+    .bind(&range.window_start)
+    .bind(range.trading_days)
+    .bind(range.high_52_week)
+    .bind(range.low_52_week)
+    .bind(range.latest_close)
+    .bind(range.pct_from_high)
+    .bind(range.pct_from_low)
+    .bind(range.limited_history)
+    .execute(pool)
+    .await?;
+
+    Ok(Some(range))
+}
+
+/// Rebuild the stored 52-week range of every stock with prices; returns how many were written
+pub async fn refresh_all_52_week_ranges(pool: &SqlitePool) -> Result<usize, sqlx::Error> {
+    let stock_ids: Vec<i64> = sqlx::query_scalar("SELECT DISTINCT stock_id FROM daily_prices")
+        .fetch_all(pool)
+        .await?;
+    let mut refreshed = 0;
+    for stock_id in stock_ids {
+        if refresh_52_week_range(pool, stock_id).await?.is_some() {
+            refreshed += 1;
+        }
+    }
+    Ok(refreshed)
+}
+
+/// A stock trading close to its stored 52-week low
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct NearLowStock {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub sector: Option<String>,
+    pub as_of_date: String,
+    pub latest_close: f64,
+    pub low_52_week: f64,
+    pub high_52_week: Option<f64>,
+    /// Percent of the latest close above the 52-week low
+    pub pct_from_low: f64,
+    pub pct_from_high: Option<f64>,
+    pub limited_history: bool,
+    /// Piotroski F-Score, when the screen filtered on it
+    pub f_score: Option<i32>,
+    /// Latest annual free cash flow
+    pub free_cash_flow: Option<f64>,
+}
+
+/// Stocks whose latest close is within `max_distance_pct` percent of their stored 52-week low,
+/// nearest first. `min_f_score` and `require_positive_fcf` narrow it to quality names; a stock
+/// without the statements for either doesn't pass that filter.
+pub async fn screen_near_52_week_low(
+    pool: &SqlitePool,
+    max_distance_pct: f64,
+    min_f_score: Option<i32>,
+    require_positive_fcf: bool,
+    limit: i32,
+) -> anyhow::Result<Vec<NearLowStock>> {
+    let (f_score_column, piotroski_join) = if min_f_score.is_some() {
+        let scores = load_latest_piotroski_scores(pool, None).await?;
+        ("p.f_score_complete", format!("JOIN {} p ON p.stock_id = r.stock_id", piotroski_scores_sql(&scores)))
+    } else {
+        ("NULL", String::new())
+    };
+    let mut query = format!(
+        "WITH latest_fcf AS (
+            SELECT stock_id, free_cash_flow FROM (
+                SELECT c.stock_id, {fcf} AS free_cash_flow,
+                       ROW_NUMBER() OVER (PARTITION BY c.stock_id ORDER BY c.fiscal_year DESC, c.report_date DESC) AS rn
+                FROM cash_flow_statements c
+                WHERE c.period_type = 'Annual' AND c.fiscal_year IS NOT NULL
+            )
+            WHERE rn = 1
+        )
+        SELECT s.id AS stock_id, s.symbol, s.sector, r.as_of_date, r.latest_close, r.low_52_week, r.high_52_week,
+               r.pct_from_low, r.pct_from_high, r.limited_history, {f_score_column} AS f_score, f.free_cash_flow
+        FROM price_range_52w r
+        JOIN stocks s ON s.id = r.stock_id
+        {piotroski_join}
+        LEFT JOIN latest_fcf f ON f.stock_id = r.stock_id
+        WHERE r.pct_from_low IS NOT NULL AND r.low_52_week > 0 AND r.pct_from_low <= ?",
+        fcf = STATEMENT_FREE_CASH_FLOW
+    );
+    if min_f_score.is_some() {
+        query.push_str(" AND p.f_score_complete >= ?");
+    }
+    if require_positive_fcf {
+        query.push_str(" AND f.free_cash_flow > 0");
+    }
+    query.push_str(" ORDER BY r.pct_from_low, s.symbol LIMIT ?");
+
+    let mut sqlx_query = sqlx::query(&query).bind(max_distance_pct);
+    if let Some(min_f_score) = min_f_score {
+        sqlx_query = sqlx_query.bind(min_f_score);
+    }
+    let rows = sqlx_query.bind(limit).fetch_all(pool).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| NearLowStock {
+            stock_id: row.get("stock_id"),
+            symbol: row.get("symbol"),
+            sector: row.get("sector"),
+            as_of_date: row.get("as_of_date"),
+            latest_close: row.get("latest_close"),
+            low_52_week: row.get("low_52_week"),
+            high_52_week: row.get("high_52_week"),
+            pct_from_low: row.get("pct_from_low"),
+            pct_from_high: row.get("pct_from_high"),
+            limited_history: row.get("limited_history"),
+            f_score: row.get("f_score"),
+            free_cash_flow: row.get("free_cash_flow"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Weekday};
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_52_week_range_over_synthetic_series() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE daily_prices (stock_id INTEGER, date DATE, high_price REAL, low_price REAL, close_price REAL)")
+            .execute(&pool).await.unwrap();
+
+        // 300 weekday rows rising from 100; a spike early on falls outside the window
+        let mut dates = Vec::new();
+        let mut day = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        while dates.len() < 300 {
+            if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                dates.push(day);
+            }
+            day = day.succ_opt().unwrap();
+        }
+        for (i, date) in dates.iter().enumerate() {
+            let close = 100.0 + i as f64 * 0.1;
+            let high = match i {
+                20 => 500.0,
+                150 => 200.0,
+                _ => close + 1.0,
+            };
+            let low = if i == 200 { 50.0 } else { close - 1.0 };
+            sqlx::query("INSERT INTO daily_prices (stock_id, date, high_price, low_price, close_price) VALUES (1, ?, ?, ?, ?)")
+                .bind(date.format("%Y-%m-%d").to_string())
+                .bind(high)
+                .bind(low)
+                .bind(close)
+                .execute(&pool).await.unwrap();
+        }
+
+        let as_of = *dates.last().unwrap();
+        let range = compute_52_week_range(&pool, 1, as_of).await.unwrap();
+
+        let window_start = as_of - Duration::days(WINDOW_DAYS - 1);
+        let expected_days = dates.iter().filter(|d| **d >= window_start).count() as i32;
+        assert_eq!(range.trading_days, expected_days);
+        assert!(range.is_complete);
+        assert_eq!(range.high_52_week, Some(200.0));
+        assert_eq!(range.low_52_week, Some(50.0));
+
+        let close = 100.0 + 299.0 * 0.1;
+        assert!((range.latest_close.unwrap() - close).abs() < 1e-9);
+        assert!((range.pct_from_high.unwrap() - (close - 200.0) / 200.0 * 100.0).abs() < 1e-9);
+        assert!((range.pct_from_low.unwrap() - (close - 50.0) / 50.0 * 100.0).abs() < 1e-9);
+
+        // Early in the series the window is short
+        let early = compute_52_week_range(&pool, 1, dates[99]).await.unwrap();
+        assert_eq!(early.trading_days, 100);
+        assert!(!early.is_complete);
+        assert!(early.note.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stored_ranges_feed_near_low_screen() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // As of 2024-05-31 the window starts 2023-06-04
+        let stocks: [(&str, &[(&str, f64)], f64); 3] = [
+            ("LOW", &[("2023-06-01", 100.0), ("2023-09-01", 80.0), ("2024-01-02", 50.0), ("2024-05-31", 52.0)], 1_000.0),
+            // Listed in March: the range is the three months available
+            ("NEW", &[("2024-03-01", 20.0), ("2024-05-31", 20.5)], -500.0),
+            ("FAR", &[("2023-06-01", 10.0), ("2023-09-01", 10.0), ("2024-05-31", 20.0)], 1_000.0),
+        ];
+        for (id, (symbol, bars, fcf)) in stocks.into_iter().enumerate() {
+            let id = id as i64 + 1;
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (?, ?, ?)")
+                .bind(id).bind(symbol).bind(symbol)
+                .execute(&pool).await.unwrap();
+            for (day, close) in bars {
+                sqlx::query(
+                    "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                     VALUES (?, ?, ?, ?, ?, ?)"
+                )
+                .bind(id).bind(day).bind(close).bind(close).bind(close).bind(close)
+                .execute(&pool).await.unwrap();
+            }
+            sqlx::query(
+                "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, free_cash_flow)
+                 VALUES (?, 'Annual', '2023-12-31', 2023, ?)"
+            )
+            .bind(id).bind(fcf)
+            .execute(&pool).await.unwrap();
+        }
+
+        assert_eq!(refresh_all_52_week_ranges(&pool).await.unwrap(), 3);
+        let listed = refresh_52_week_range(&pool, 2).await.unwrap().unwrap();
+        assert!(listed.limited_history);
+        assert_eq!((listed.low_52_week, listed.high_52_week), (Some(20.0), Some(20.5)));
+        assert!(listed.note.unwrap().starts_with("Prices start 2024-03-01"));
+
+        let near: Vec<(String, bool)> = screen_near_52_week_low(&pool, 5.0, None, false, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|stock| (stock.symbol, stock.limited_history))
+            .collect();
+        assert_eq!(near, vec![("NEW".to_string(), true), ("LOW".to_string(), false)]);
+
+        let quality = screen_near_52_week_low(&pool, 5.0, None, true, 10).await.unwrap();
+        assert_eq!(quality.len(), 1);
+        assert_eq!(quality[0].symbol, "LOW");
+        assert!((quality[0].pct_from_low - 4.0).abs() < 1e-9);
+
+        // No balance sheets or income statements, so no F-Score to pass
+        assert!(screen_near_52_week_low(&pool, 5.0, Some(6), false, 10).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+use sqlx::{SqlitePool, Row};
+
+use crate::analysis::growth_consistency::fy_revenue_with_prior;
+use crate::tools::fx_rates::usd_value_sql;
+use crate::tools::universe_filter::{apply_universe_filter, UniverseFilter};
+
+/// Per-stock history queries in flight at once when screening the whole universe
+const HISTORY_CONCURRENCY: usize = 8;
+
+/// Latest screener-eligible valuation row for a stock
+#[derive(Debug, Clone)]
+pub struct PsCurrentPoint {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub date: String,
+    pub ps_ratio_ttm: f64,
+    pub price: f64,
+    pub market_cap: f64,
+    pub data_completeness_score: i32,
+}
+
+/// P/S distribution before the current point
+#[derive(Debug, Clone, PartialEq)]
+pub struct PsHistoryStats {
+    pub mean: f64,
+    pub median: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Population variance (the screeners compare it directly against P/S, not its square root)
+    pub variance: f64,
+    pub data_points: i32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RevenueGrowth {
+    pub current_ttm_revenue: Option<f64>,
+    pub ttm_growth_rate: Option<f64>,
+    pub current_annual_revenue: Option<f64>,
+    pub annual_growth_rate: Option<f64>,
+}
+
+/// Stats over P/S values sorted ascending; None when there are none
+pub fn summarize_ps_history(sorted: &[f64]) -> Option<PsHistoryStats> {
+    let n = sorted.len();
+    if n == 0 {
+        return None;
+    }
+
+    let mean = sorted.iter().sum::<f64>() / n as f64;
+    let variance = sorted.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n as f64;
+    let median = if n % 2 == 1 {
+        sorted[n / 2]
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    };
+
+    Some(PsHistoryStats {
+        mean,
+        median,
+        min: sorted[0],
+        max: sorted[n - 1],
+        variance,
+        data_points: n as i32,
+    })
+}
+
+/// One set-based pass: the latest row per ticker with P/S > 0.01 and market cap above the floor,
+/// limited to stocks inside the shared universe filter
+pub async fn load_current_ps_points(
+    pool: &SqlitePool,
+    tickers: &[String],
+    universe: &UniverseFilter,
+) -> Result<Vec<PsCurrentPoint>, sqlx::Error> {
+    if tickers.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let placeholders = tickers.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let mut universe_clause = String::new();
+    let mut universe_params = Vec::new();
+    apply_universe_filter(&mut universe_clause, &mut universe_params, "s.id", universe);
+
+    let query = format!(
+        "WITH latest AS (
+            SELECT dvr.stock_id, MAX(dvr.date) AS date
+            FROM stocks s
+            JOIN daily_valuation_ratios dvr ON dvr.stock_id = s.id
+            WHERE s.symbol IN ({})
+              AND dvr.ps_ratio_ttm > 0.01
+              AND {} > ?
+            GROUP BY dvr.stock_id
+        )
+        SELECT s.id AS stock_id, s.symbol, d.date, d.ps_ratio_ttm, d.price, d.market_cap, d.data_completeness_score
+        FROM latest l
+        JOIN daily_valuation_ratios d ON d.stock_id = l.stock_id AND d.date = l.date
+        JOIN stocks s ON s.id = l.stock_id
+        WHERE 1=1{}",
+        placeholders,
+        usd_value_sql("dvr.market_cap", "dvr.currency", "dvr.date"),
+        universe_clause
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for ticker in tickers {
+        query_builder = query_builder.bind(ticker);
+    }
+    query_builder = query_builder.bind(universe.min_market_cap);
+    for param in universe_params {
+        query_builder = query_builder.bind(param);
+    }
+    let rows = query_builder.fetch_all(pool).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| PsCurrentPoint {
+            stock_id: row.get("stock_id"),
+            symbol: row.get("symbol"),
+            date: row.get("date"),
+            ps_ratio_ttm: row.get("ps_ratio_ttm"),
+            price: row.get::<Option<f64>, _>("price").unwrap_or(0.0),
+            market_cap: row.get("market_cap"),
+            data_completeness_score: row.get("data_completeness_score"),
+        })
+        .collect())
+}
+
+/// History stats for each point, keyed by stock_id. Each stock's values come from the
+/// covering (stock_id, date, ps_ratio_ttm, market_cap, currency) index, a bounded number at
+/// a time; `min_market_cap` is in USD.
+pub async fn load_ps_history_stats(
+    pool: &SqlitePool,
+    points: &[PsCurrentPoint],
+    min_market_cap: f64,
+) -> Result<HashMap<i64, PsHistoryStats>, sqlx::Error> {
+    stream::iter(points)
+        .map(|point| async move {
+            let query = format!(
+                "SELECT d.ps_ratio_ttm FROM daily_valuation_ratios d
+                 WHERE d.stock_id = ? AND d.date < ? AND d.ps_ratio_ttm > 0.01 AND {} > ?",
+                usd_value_sql("d.market_cap", "d.currency", "d.date")
+            );
+            let mut values: Vec<f64> = sqlx::query_scalar(&query)
+            .bind(point.stock_id)
+            .bind(&point.date)
+            .bind(min_market_cap)
+            .fetch_all(pool)
+            .await?;
+
+            values.sort_by(f64::total_cmp);
+            Ok::<_, sqlx::Error>((point.stock_id, summarize_ps_history(&values)))
+        })
+        .buffer_unordered(HISTORY_CONCURRENCY)
+        .try_filter_map(|(stock_id, stats)| async move { Ok(stats.map(|s| (stock_id, s))) })
+        .try_collect()
+        .await
+}
+
+/// Latest two TTM revenues and latest fiscal-year revenue per stock, with growth as a percentage.
+/// Annual growth compares revenue annualized to 365 days and is left out for non-consecutive
+/// fiscal years or when either is a stub period.
+pub async fn load_revenue_growth(
+    pool: &SqlitePool,
+    stock_ids: &[i64],
+) -> Result<HashMap<i64, RevenueGrowth>, sqlx::Error> {
+    if stock_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // Numbered so both halves of the union share the binds
+    let placeholders = (1..=stock_ids.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(",");
+    let query = format!(
+        "WITH ttm AS (
+            SELECT stock_id, revenue,
+                   ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) AS rn
+            FROM income_statements
+            WHERE stock_id IN ({0}) AND period_type = 'TTM'
+        ),
+        revenues AS (
+            SELECT stock_id,
+                   CASE WHEN rn = 1 THEN revenue END AS current_ttm,
+                   CASE WHEN rn = 2 THEN revenue END AS previous_ttm,
+                   NULL AS current_annual, NULL AS current_annualized, NULL AS previous_annualized
+            FROM ttm
+            WHERE rn <= 2
+            UNION ALL
+            SELECT stock_id, NULL, NULL, revenue, annualized_revenue,
+                   CASE WHEN prior_year = fiscal_year - 1 THEN prior_revenue END
+            FROM ({1})
+            WHERE rn = 1 AND stock_id IN ({0})
+        )
+        SELECT stock_id,
+               MAX(current_ttm) AS current_ttm,
+               MAX(previous_ttm) AS previous_ttm,
+               MAX(current_annual) AS current_annual,
+               MAX(current_annualized) AS current_annualized,
+               MAX(previous_annualized) AS previous_annualized
+        FROM revenues
+        GROUP BY stock_id",
+        placeholders,
+        fy_revenue_with_prior()
+    );
+
+    let mut query_builder = sqlx::query(&query);
+    for stock_id in stock_ids {
+        query_builder = query_builder.bind(stock_id);
+    }
+    let rows = query_builder.fetch_all(pool).await?;
+
+    fn growth(current: Option<f64>, previous: Option<f64>) -> Option<f64> {
+        match (current, previous) {
+            (Some(current), Some(previous)) if previous > 0.0 => Some((current - previous) / previous * 100.0),
+            _ => None,
+        }
+    }
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let current_ttm: Option<f64> = row.get("current_ttm");
+            let current_annual: Option<f64> = row.get("current_annual");
+            (
+                row.get("stock_id"),
+                RevenueGrowth {
+                    current_ttm_revenue: current_ttm,
+                    ttm_growth_rate: growth(current_ttm, row.get("previous_ttm")),
+                    current_annual_revenue: current_annual,
+                    annual_growth_rate: growth(row.get("current_annualized"), row.get("previous_annualized")),
+                },
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_matches_population_stats() {
+        assert_eq!(summarize_ps_history(&[]), None);
+
+        let stats = summarize_ps_history(&[1.0, 2.0, 4.0, 5.0]).unwrap();
+        assert_eq!(stats.mean, 3.0);
+        assert_eq!(stats.median, 3.0);
+        assert_eq!((stats.min, stats.max), (1.0, 5.0));
+        assert_eq!(stats.variance, 2.5);
+        assert_eq!(stats.data_points, 4);
+
+        assert_eq!(summarize_ps_history(&[1.0, 2.0, 9.0]).unwrap().median, 2.0);
+    }
+}
@@ -0,0 +1,729 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::database::helpers::is_read_only;
+use crate::safe_f64::{finite, safe_ratio, SafeF64};
+
+/// Tax rate applied to operating income when the filing gives no usable effective rate
+pub const DEFAULT_TAX_RATE: f64 = 0.21;
+
+/// Effective tax rates are clamped to this range so one-off charges and credits don't swing NOPAT
+pub const MAX_EFFECTIVE_TAX_RATE: f64 = 0.5;
+
+/// Annual quality inputs, one row per stock and fiscal year of income statement (the latest
+/// report when restated), with that year's balance sheet when there is one. Total debt falls
+/// back to short plus long-term debt as in the leverage metrics.
+const ANNUAL_QUALITY_INPUTS: &str = "
+    WITH income AS (
+        SELECT stock_id, fiscal_year, report_date, revenue, gross_profit, cost_of_revenue,
+               operating_income, tax_expense, pretax_income,
+               ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+        FROM income_statements
+        WHERE period_type = 'FY' AND fiscal_year IS NOT NULL
+    ),
+    balance AS (
+        SELECT stock_id, fiscal_year, total_assets, total_equity, cash_and_equivalents,
+               COALESCE(total_debt,
+                        CASE WHEN short_term_debt IS NOT NULL OR long_term_debt IS NOT NULL
+                             THEN COALESCE(short_term_debt, 0) + COALESCE(long_term_debt, 0) END) AS total_debt,
+               ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+        FROM balance_sheets
+        WHERE period_type = 'Annual' AND fiscal_year IS NOT NULL
+    )
+    SELECT i.stock_id, i.fiscal_year, i.report_date, i.revenue, i.gross_profit, i.cost_of_revenue,
+           i.operating_income, i.tax_expense, i.pretax_income,
+           b.total_assets, b.total_debt, b.total_equity, b.cash_and_equivalents
+    FROM income i
+    LEFT JOIN balance b ON b.stock_id = i.stock_id AND b.fiscal_year = i.fiscal_year AND b.rn = 1
+    WHERE i.rn = 1";
+
+/// Quality metrics of one fiscal year, as stored in quality_metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QualityYear {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    /// Percent of revenue
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub gross_margin: Option<f64>,
+    /// Percent of revenue
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub operating_margin: Option<f64>,
+    /// Revenue over year-end total assets
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub asset_turnover: Option<f64>,
+    /// Tax expense over pre-tax income, clamped to [0, 0.5]
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub effective_tax_rate: Option<f64>,
+    /// Operating income after tax at the effective rate
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub nopat: Option<f64>,
+    /// Total debt plus equity minus cash
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub invested_capital: Option<f64>,
+    /// NOPAT as a percent of invested capital
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub roic: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QualityMetrics {
+    pub stock_id: i64,
+    pub latest: Option<QualityYear>,
+    /// Every stored fiscal year, oldest first
+    pub history: Vec<QualityYear>,
+}
+
+/// ROIC and the inputs behind it for one fiscal year
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RoicYear {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub operating_income: Option<f64>,
+    /// Tax expense over pre-tax income as filed, before clamping
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub reported_tax_rate: Option<f64>,
+    /// Rate NOPAT is taxed at: the reported rate clamped to [0, 0.5], or 21% without a usable one
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub effective_tax_rate: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub nopat: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub total_debt: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub total_equity: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub cash: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub invested_capital: Option<f64>,
+    /// NOPAT as a percent of invested capital
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub roic: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RoicHistory {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub latest: Option<RoicYear>,
+    /// Every fiscal year with an income statement, oldest first
+    pub history: Vec<RoicYear>,
+}
+
+/// Bounds for the quality compounders screen; a None bound is not checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QualityCompounderCriteria {
+    /// ROIC in percent that every one of the last `years` fiscal years must exceed
+    pub min_roic: Option<f64>,
+    /// Latest fiscal years looked at; stocks with fewer stored years don't pass
+    pub years: Option<i32>,
+    /// Percentage points gross and operating margin may fall from one year to the next
+    pub max_margin_decline: Option<f64>,
+    pub limit: Option<i32>,
+}
+
+impl Default for QualityCompounderCriteria {
+    fn default() -> Self {
+        Self {
+            min_roic: Some(15.0),
+            years: Some(5),
+            max_margin_decline: Some(1.0),
+            limit: Some(50),
+        }
+    }
+}
+
+impl QualityCompounderCriteria {
+    /// Whether `years`, oldest first and already cut to the window, meet every bound
+    fn passes(&self, years: &[QualityYear]) -> bool {
+        let roic_ok = self
+            .min_roic
+            .map_or(true, |min| years.iter().all(|year| year.roic.is_some_and(|roic| roic > min)));
+        let margins_ok = self.max_margin_decline.map_or(true, |max_decline| {
+            let holds = |margin: fn(&QualityYear) -> Option<f64>| {
+                years.windows(2).all(|pair| match (margin(&pair[0]), margin(&pair[1])) {
+                    (Some(before), Some(after)) => after >= before - max_decline,
+                    _ => false,
+                })
+            };
+            holds(|year| year.gross_margin) && holds(|year| year.operating_margin)
+        });
+        roic_ok && margins_ok
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QualityCompounder {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub company_name: String,
+    pub sector: Option<String>,
+    pub latest: QualityYear,
+    /// Lowest ROIC over the screened years
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
+    pub min_roic: f64,
+}
+
+/// Novy-Marx gross profitability of one fiscal year
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct GrossProfitabilityYear {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub gross_profit: Option<f64>,
+    /// Gross profit wasn't filed and is revenue minus cost of revenue
+    pub gross_profit_derived: bool,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub total_assets: Option<f64>,
+    /// Gross profit over year-end total assets
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub gross_profitability: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct GrossProfitabilityHistory {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub latest: Option<GrossProfitabilityYear>,
+    /// Every fiscal year with an income statement, oldest first
+    pub history: Vec<GrossProfitabilityYear>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct GrossProfitabilityRanking {
+    /// 1 for the most profitable
+    pub rank: i32,
+    pub stock_id: i64,
+    pub symbol: String,
+    pub company_name: String,
+    pub sector: Option<String>,
+    pub latest: GrossProfitabilityYear,
+}
+
+/// Quality inputs of one fiscal year as stored
+#[derive(Debug, Clone, Default)]
+pub struct QualityInputs {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    pub revenue: Option<f64>,
+    pub gross_profit: Option<f64>,
+    pub cost_of_revenue: Option<f64>,
+    pub operating_income: Option<f64>,
+    pub tax_expense: Option<f64>,
+    pub pretax_income: Option<f64>,
+    pub total_assets: Option<f64>,
+    pub total_debt: Option<f64>,
+    pub total_equity: Option<f64>,
+    pub cash: Option<f64>,
+}
+
+/// Tax expense over pre-tax income clamped to [0, `MAX_EFFECTIVE_TAX_RATE`];
+/// `DEFAULT_TAX_RATE` when either is missing or pre-tax income is zero
+pub fn effective_tax_rate(tax_expense: Option<f64>, pretax_income: Option<f64>) -> f64 {
+    match (tax_expense, pretax_income) {
+        (Some(tax), Some(pretax)) if pretax != 0.0 => {
+            safe_ratio(tax, pretax).map_or(DEFAULT_TAX_RATE, |rate| rate.clamp(0.0, MAX_EFFECTIVE_TAX_RATE))
+        }
+        _ => DEFAULT_TAX_RATE,
+    }
+}
+
+/// Metrics of one fiscal year. Margins need positive revenue; ROIC needs positive invested capital.
+pub fn compute_quality_year(inputs: &QualityInputs) -> QualityYear {
+    let revenue = inputs.revenue.filter(|revenue| *revenue > 0.0);
+    let gross_profit = inputs
+        .gross_profit
+        .or_else(|| inputs.revenue.zip(inputs.cost_of_revenue).map(|(revenue, cost)| revenue - cost));
+    let percent_of_revenue =
+        |value: Option<f64>| value.zip(revenue).and_then(|(value, revenue)| SafeF64::ratio(value, revenue).map(|ratio| ratio * 100.0).value());
+
+    let asset_turnover = revenue
+        .zip(inputs.total_assets.filter(|assets| *assets > 0.0))
+        .and_then(|(revenue, assets)| safe_ratio(revenue, assets));
+
+    let effective_tax_rate = inputs.operating_income.map(|_| effective_tax_rate(inputs.tax_expense, inputs.pretax_income));
+    let nopat = inputs.operating_income.zip(effective_tax_rate).map(|(operating, rate)| operating * (1.0 - rate));
+
+    let invested_capital = match (inputs.total_debt, inputs.total_equity) {
+        (Some(debt), Some(equity)) => finite(debt + equity - inputs.cash.unwrap_or(0.0)),
+        _ => None,
+    };
+    let roic = nopat
+        .zip(invested_capital.filter(|capital| *capital > 0.0))
+        .and_then(|(nopat, capital)| SafeF64::ratio(nopat, capital).map(|ratio| ratio * 100.0).value());
+
+    QualityYear {
+        fiscal_year: inputs.fiscal_year,
+        report_date: inputs.report_date.clone(),
+        gross_margin: percent_of_revenue(gross_profit),
+        operating_margin: percent_of_revenue(inputs.operating_income),
+        asset_turnover,
+        effective_tax_rate,
+        nopat,
+        invested_capital,
+        roic,
+    }
+}
+
+/// Gross profit over total assets; gross profit falls back to revenue minus cost of revenue, and
+/// the ratio needs positive assets
+pub fn gross_profitability_year(inputs: &QualityInputs) -> GrossProfitabilityYear {
+    let derived = inputs.revenue.zip(inputs.cost_of_revenue).map(|(revenue, cost)| revenue - cost);
+    let gross_profit = inputs.gross_profit.or(derived);
+    let gross_profitability = gross_profit
+        .zip(inputs.total_assets.filter(|assets| *assets > 0.0))
+        .and_then(|(gross_profit, assets)| safe_ratio(gross_profit, assets));
+
+    GrossProfitabilityYear {
+        fiscal_year: inputs.fiscal_year,
+        report_date: inputs.report_date.clone(),
+        gross_profit,
+        gross_profit_derived: inputs.gross_profit.is_none() && derived.is_some(),
+        total_assets: inputs.total_assets,
+        gross_profitability,
+    }
+}
+
+/// Quality inputs by fiscal year, oldest first, per stock; all stocks when `stock_id` is None
+async fn load_quality_inputs_by_stock(pool: &SqlitePool, stock_id: Option<i64>) -> Result<BTreeMap<i64, Vec<QualityInputs>>> {
+    let query = format!(
+        "SELECT * FROM ({}) WHERE ?1 IS NULL OR stock_id = ?1 ORDER BY stock_id, fiscal_year",
+        ANNUAL_QUALITY_INPUTS
+    );
+    let rows = sqlx::query(&query).bind(stock_id).fetch_all(pool).await?;
+
+    let mut inputs: BTreeMap<i64, Vec<QualityInputs>> = BTreeMap::new();
+    for row in rows {
+        inputs.entry(row.get("stock_id")).or_default().push(QualityInputs {
+            fiscal_year: row.get("fiscal_year"),
+            report_date: row.get("report_date"),
+            revenue: row.get("revenue"),
+            gross_profit: row.get("gross_profit"),
+            cost_of_revenue: row.get("cost_of_revenue"),
+            operating_income: row.get("operating_income"),
+            tax_expense: row.get("tax_expense"),
+            pretax_income: row.get("pretax_income"),
+            total_assets: row.get("total_assets"),
+            total_debt: row.get("total_debt"),
+            total_equity: row.get("total_equity"),
+            cash: row.get("cash_and_equivalents"),
+        });
+    }
+    Ok(inputs)
+}
+
+/// A stock's quality inputs by fiscal year, oldest first
+async fn load_quality_inputs(pool: &SqlitePool, stock_id: i64) -> Result<Vec<QualityInputs>> {
+    Ok(load_quality_inputs_by_stock(pool, Some(stock_id)).await?.remove(&stock_id).unwrap_or_default())
+}
+
+/// Recompute every fiscal year of a stock's quality metrics from its statements and replace
+/// what quality_metrics holds for it. Returns the years stored, oldest first.
+pub async fn refresh_quality_metrics(pool: &SqlitePool, stock_id: i64) -> Result<Vec<QualityYear>> {
+    let years: Vec<QualityYear> = load_quality_inputs(pool, stock_id).await?.iter().map(compute_quality_year).collect();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM quality_metrics WHERE stock_id = ?")
+        .bind(stock_id)
+        .execute(&mut *tx)
+        .await?;
+    for year in &years {
+        sqlx::query(
+            "INSERT INTO quality_metrics (
+                stock_id, fiscal_year, report_date, gross_margin, operating_margin, asset_turnover,
+                effective_tax_rate, nopat, invested_capital, roic
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id)
+        .bind(year.fiscal_year)
+        .bind(&year.report_date)
+        .bind(year.gross_margin)
+        .bind(year.operating_margin)
+        .bind(year.asset_turnover)
+        .bind(year.effective_tax_rate)
+        .bind(year.nopat)
+        .bind(year.invested_capital)
+        .bind(year.roic)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(years)
+}
+
+/// Stored quality metrics by fiscal year, oldest first, per stock; all stocks when `stock_id` is None
+async fn load_quality_years(pool: &SqlitePool, stock_id: Option<i64>) -> Result<BTreeMap<i64, Vec<QualityYear>>> {
+    let rows = sqlx::query(
+        "SELECT * FROM quality_metrics WHERE ?1 IS NULL OR stock_id = ?1 ORDER BY stock_id, fiscal_year"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut years: BTreeMap<i64, Vec<QualityYear>> = BTreeMap::new();
+    for row in rows {
+        years.entry(row.get("stock_id")).or_default().push(QualityYear {
+            fiscal_year: row.get("fiscal_year"),
+            report_date: row.get("report_date"),
+            gross_margin: row.get("gross_margin"),
+            operating_margin: row.get("operating_margin"),
+            asset_turnover: row.get("asset_turnover"),
+            effective_tax_rate: row.get("effective_tax_rate"),
+            nopat: row.get("nopat"),
+            invested_capital: row.get("invested_capital"),
+            roic: row.get("roic"),
+        });
+    }
+    Ok(years)
+}
+
+/// A stock's quality metrics, refreshed from its statements first so they match what is stored.
+/// A read-only database gets them computed without being stored.
+pub async fn compute_quality_metrics(pool: &SqlitePool, stock_id: i64) -> Result<QualityMetrics> {
+    let history = if is_read_only(pool).await? {
+        load_quality_inputs(pool, stock_id).await?.iter().map(compute_quality_year).collect()
+    } else {
+        refresh_quality_metrics(pool, stock_id).await?
+    };
+    Ok(QualityMetrics {
+        stock_id,
+        latest: history.last().cloned(),
+        history,
+    })
+}
+
+/// ROIC by fiscal year from the stock's statements, with the inputs and the tax rate before
+/// and after clamping. Reads only; quality_metrics is left as it is.
+pub async fn compute_roic(pool: &SqlitePool, stock_id: i64, symbol: String) -> Result<RoicHistory> {
+    let history: Vec<RoicYear> = load_quality_inputs(pool, stock_id)
+        .await?
+        .iter()
+        .map(|inputs| {
+            let year = compute_quality_year(inputs);
+            RoicYear {
+                fiscal_year: year.fiscal_year,
+                report_date: year.report_date,
+                operating_income: inputs.operating_income,
+                reported_tax_rate: inputs.tax_expense.zip(inputs.pretax_income).and_then(|(tax, pretax)| safe_ratio(tax, pretax)),
+                effective_tax_rate: year.effective_tax_rate,
+                nopat: year.nopat,
+                total_debt: inputs.total_debt,
+                total_equity: inputs.total_equity,
+                cash: inputs.cash,
+                invested_capital: year.invested_capital,
+                roic: year.roic,
+            }
+        })
+        .collect();
+
+    Ok(RoicHistory {
+        stock_id,
+        symbol,
+        latest: history.last().cloned(),
+        history,
+    })
+}
+
+/// Gross profitability by fiscal year from the stock's statements
+pub async fn compute_gross_profitability(pool: &SqlitePool, stock_id: i64, symbol: String) -> Result<GrossProfitabilityHistory> {
+    let history: Vec<GrossProfitabilityYear> =
+        load_quality_inputs(pool, stock_id).await?.iter().map(gross_profitability_year).collect();
+
+    Ok(GrossProfitabilityHistory {
+        stock_id,
+        symbol,
+        latest: history.last().cloned(),
+        history,
+    })
+}
+
+/// S&P 500 stocks ranked by gross profitability in their latest fiscal year, highest first.
+/// Stocks whose latest year has no ratio are left out.
+pub async fn rank_by_gross_profitability(pool: &SqlitePool, limit: usize) -> Result<Vec<GrossProfitabilityRanking>> {
+    let mut inputs = load_quality_inputs_by_stock(pool, None).await?;
+    let stocks = sqlx::query(
+        "SELECT s.id, s.symbol, s.company_name, s.sector
+         FROM stocks s
+         INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut ranked: Vec<GrossProfitabilityRanking> = stocks
+        .iter()
+        .filter_map(|row| {
+            let stock_id: i64 = row.get("id");
+            let latest = gross_profitability_year(inputs.remove(&stock_id)?.last()?);
+            latest.gross_profitability?;
+            Some(GrossProfitabilityRanking {
+                rank: 0,
+                stock_id,
+                symbol: row.get("symbol"),
+                company_name: row.get("company_name"),
+                sector: row.get("sector"),
+                latest,
+            })
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        let key = |stock: &GrossProfitabilityRanking| stock.latest.gross_profitability.unwrap_or(f64::NEG_INFINITY);
+        key(b).total_cmp(&key(a)).then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    ranked.truncate(limit);
+    for (index, stock) in ranked.iter_mut().enumerate() {
+        stock.rank = index as i32 + 1;
+    }
+    Ok(ranked)
+}
+
+/// S&P 500 stocks whose stored quality metrics meet every bound in `criteria` over their last
+/// `years` fiscal years, highest latest ROIC first. Reads quality_metrics as the last ratio
+/// recompute left it.
+pub async fn screen_quality_compounders(pool: &SqlitePool, criteria: &QualityCompounderCriteria) -> Result<Vec<QualityCompounder>> {
+    let window = criteria.years.unwrap_or(1).max(1) as usize;
+    let mut years = load_quality_years(pool, None).await?;
+    let stocks = sqlx::query(
+        "SELECT s.id, s.symbol, s.company_name, s.sector
+         FROM stocks s
+         INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut matches: Vec<QualityCompounder> = stocks
+        .iter()
+        .filter_map(|row| {
+            let stock_id: i64 = row.get("id");
+            let mut history = years.remove(&stock_id)?;
+            if history.len() < window {
+                return None;
+            }
+            let screened = history.split_off(history.len() - window);
+            if !criteria.passes(&screened) {
+                return None;
+            }
+            Some(QualityCompounder {
+                stock_id,
+                symbol: row.get("symbol"),
+                company_name: row.get("company_name"),
+                sector: row.get("sector"),
+                min_roic: screened.iter().filter_map(|year| year.roic).fold(f64::INFINITY, f64::min),
+                latest: screened.last()?.clone(),
+            })
+        })
+        .filter(|stock| stock.min_roic.is_finite())
+        .collect();
+
+    matches.sort_by(|a, b| {
+        let key = |stock: &QualityCompounder| stock.latest.roic.unwrap_or(f64::NEG_INFINITY);
+        key(b).total_cmp(&key(a)).then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    if let Some(limit) = criteria.limit {
+        matches.truncate(limit.max(0) as usize);
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    /// Revenue 1000, gross profit 400, operating income 200, pre-tax income 180 and tax 36;
+    /// debt 300, equity 700, cash 200 and total assets 1250
+    fn worked_example() -> QualityInputs {
+        QualityInputs {
+            fiscal_year: 2023,
+            report_date: "2023-12-31".to_string(),
+            revenue: Some(1000.0),
+            gross_profit: Some(400.0),
+            cost_of_revenue: None,
+            operating_income: Some(200.0),
+            tax_expense: Some(36.0),
+            pretax_income: Some(180.0),
+            total_assets: Some(1250.0),
+            total_debt: Some(300.0),
+            total_equity: Some(700.0),
+            cash: Some(200.0),
+        }
+    }
+
+    #[test]
+    fn test_worked_example() {
+        let year = compute_quality_year(&worked_example());
+        assert_eq!((year.gross_margin, year.operating_margin), (Some(40.0), Some(20.0)));
+        assert_eq!(year.asset_turnover, Some(0.8));
+        // 36 / 180 = 0.2, so NOPAT is 200 × 0.8 = 160 on 300 + 700 - 200 = 800 invested
+        assert_eq!(year.effective_tax_rate, Some(0.2));
+        assert!((year.nopat.unwrap() - 160.0).abs() < 1e-9);
+        assert_eq!(year.invested_capital, Some(800.0));
+        assert!((year.roic.unwrap() - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tax_rate_is_clamped() {
+        // 120 / 180 is a 67% rate, capped at 50%: NOPAT 100, ROIC 12.5%
+        let year = compute_quality_year(&QualityInputs { tax_expense: Some(120.0), ..worked_example() });
+        assert_eq!(year.effective_tax_rate, Some(0.5));
+        assert_eq!((year.nopat, year.roic), (Some(100.0), Some(12.5)));
+
+        // Tax owed on a pre-tax loss gives a negative rate, floored at zero
+        assert_eq!(effective_tax_rate(Some(10.0), Some(-50.0)), 0.0);
+        assert_eq!(effective_tax_rate(None, Some(180.0)), DEFAULT_TAX_RATE);
+        assert_eq!(effective_tax_rate(Some(36.0), Some(0.0)), DEFAULT_TAX_RATE);
+    }
+
+    #[test]
+    fn test_missing_and_degenerate_inputs() {
+        // Gross profit from cost of revenue; more cash than debt and equity leaves ROIC empty
+        let year = compute_quality_year(&QualityInputs {
+            gross_profit: None,
+            cost_of_revenue: Some(700.0),
+            cash: Some(1200.0),
+            ..worked_example()
+        });
+        assert_eq!(year.gross_margin, Some(30.0));
+        assert_eq!((year.invested_capital, year.roic), (Some(-200.0), None));
+
+        let no_revenue = compute_quality_year(&QualityInputs { revenue: Some(0.0), ..worked_example() });
+        assert_eq!((no_revenue.gross_margin, no_revenue.asset_turnover), (None, None));
+    }
+
+    async fn insert_year(pool: &SqlitePool, stock_id: i64, year: i32, revenue: f64, gross_profit: f64, operating: f64) {
+        let report_date = format!("{}-12-31", year);
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, gross_profit,
+                                            operating_income, pretax_income, tax_expense)
+             VALUES (?, 'FY', ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id).bind(&report_date).bind(year).bind(revenue).bind(gross_profit)
+        .bind(operating).bind(operating).bind(operating * 0.2)
+        .execute(pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_assets, total_debt,
+                                         total_equity, cash_and_equivalents)
+             VALUES (?, 'Annual', ?, ?, 1000.0, 200.0, 600.0, 0.0)"
+        )
+        .bind(stock_id).bind(&report_date).bind(year)
+        .execute(pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_metrics_are_persisted_and_screened() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        for (id, symbol) in [(1, "COMPOUND"), (2, "SQUEEZED"), (3, "YOUNG")] {
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (?, ?, ?, 1)")
+                .bind(id).bind(symbol).bind(symbol)
+                .execute(&pool).await.unwrap();
+        }
+        // 800 invested and a 20% tax rate: operating income 200 is a 20% ROIC
+        for (i, year) in (2019..=2023).enumerate() {
+            insert_year(&pool, 1, year, 1000.0, 400.0 + 10.0 * i as f64, 200.0).await;
+            // Same returns, but gross margin drops two points a year
+            insert_year(&pool, 2, year, 1000.0, 400.0 - 20.0 * i as f64, 200.0).await;
+        }
+        insert_year(&pool, 3, 2023, 1000.0, 400.0, 250.0).await;
+
+        for stock_id in 1..=3 {
+            refresh_quality_metrics(&pool, stock_id).await.unwrap();
+        }
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM quality_metrics").fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 11);
+
+        let compound = compute_quality_metrics(&pool, 1).await.unwrap();
+        assert_eq!(compound.history.len(), 5);
+        let latest = compound.latest.unwrap();
+        assert_eq!((latest.fiscal_year, latest.gross_margin), (2023, Some(44.0)));
+        assert!((latest.roic.unwrap() - 20.0).abs() < 1e-9);
+
+        let screened = screen_quality_compounders(&pool, &QualityCompounderCriteria::default()).await.unwrap();
+        assert_eq!(screened.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), vec!["COMPOUND"]);
+
+        let lenient = QualityCompounderCriteria { years: Some(1), max_margin_decline: None, ..QualityCompounderCriteria::default() };
+        let screened = screen_quality_compounders(&pool, &lenient).await.unwrap();
+        assert_eq!(screened.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), vec!["YOUNG", "COMPOUND", "SQUEEZED"]);
+    }
+
+    #[tokio::test]
+    async fn test_gross_profitability_falls_back_to_revenue_less_cogs() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        for (id, symbol) in [(1, "FILED"), (2, "DERIVED"), (3, "NOASSETS")] {
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (?, ?, ?, 1)")
+                .bind(id).bind(symbol).bind(symbol)
+                .execute(&pool).await.unwrap();
+        }
+        // 400 of gross profit on the 1,000 of assets insert_year files
+        insert_year(&pool, 1, 2023, 1000.0, 400.0, 200.0).await;
+        // No gross profit filed: 1,000 of revenue less 450 of cost of revenue
+        insert_year(&pool, 2, 2023, 1000.0, 0.0, 200.0).await;
+        sqlx::query("UPDATE income_statements SET gross_profit = NULL, cost_of_revenue = 450.0 WHERE stock_id = 2")
+            .execute(&pool).await.unwrap();
+        insert_year(&pool, 3, 2023, 1000.0, 900.0, 200.0).await;
+        sqlx::query("UPDATE balance_sheets SET total_assets = NULL WHERE stock_id = 3")
+            .execute(&pool).await.unwrap();
+
+        let filed = compute_gross_profitability(&pool, 1, "FILED".to_string()).await.unwrap().latest.unwrap();
+        assert_eq!((filed.gross_profit, filed.gross_profit_derived), (Some(400.0), false));
+        assert!((filed.gross_profitability.unwrap() - 0.4).abs() < 1e-9);
+
+        let derived = compute_gross_profitability(&pool, 2, "DERIVED".to_string()).await.unwrap().latest.unwrap();
+        assert_eq!((derived.gross_profit, derived.gross_profit_derived), (Some(550.0), true));
+        assert!((derived.gross_profitability.unwrap() - 0.55).abs() < 1e-9);
+
+        let ranked = rank_by_gross_profitability(&pool, 10).await.unwrap();
+        assert_eq!(
+            ranked.iter().map(|s| (s.rank, s.symbol.as_str())).collect::<Vec<_>>(),
+            vec![(1, "DERIVED"), (2, "FILED")]
+        );
+        assert_eq!(rank_by_gross_profitability(&pool, 1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_roic_by_fiscal_year_reports_clamped_tax_rate() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'ROIC', 'Roic Co')")
+            .execute(&pool).await.unwrap();
+        insert_year(&pool, 1, 2022, 1000.0, 400.0, 200.0).await;
+        insert_year(&pool, 1, 2023, 1000.0, 400.0, 200.0).await;
+        // A one-off charge takes 2023's tax to 150 on 200 of pre-tax income
+        sqlx::query("UPDATE income_statements SET tax_expense = 150.0 WHERE fiscal_year = 2023")
+            .execute(&pool).await.unwrap();
+
+        let roic = compute_roic(&pool, 1, "ROIC".to_string()).await.unwrap();
+        assert_eq!(roic.history.len(), 2);
+
+        // 200 × (1 - 0.2) = 160 on 200 + 600 - 0 = 800 invested
+        let normal = &roic.history[0];
+        assert_eq!((normal.reported_tax_rate, normal.effective_tax_rate), (Some(0.2), Some(0.2)));
+        assert!((normal.nopat.unwrap() - 160.0).abs() < 1e-9);
+        assert_eq!(normal.invested_capital, Some(800.0));
+        assert!((normal.roic.unwrap() - 20.0).abs() < 1e-9);
+
+        // 75% as filed, taxed at the 50% cap: NOPAT 100, ROIC 12.5%
+        let clamped = roic.latest.unwrap();
+        assert_eq!((clamped.reported_tax_rate, clamped.effective_tax_rate), (Some(0.75), Some(MAX_EFFECTIVE_TAX_RATE)));
+        assert_eq!((clamped.nopat, clamped.roic), (Some(100.0), Some(12.5)));
+
+        // Computing ROIC doesn't write quality_metrics
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM quality_metrics").fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 0);
+    }
+}
@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::safe_f64::safe_ratio;
+
+/// Ratios whose inputs can be traced back to the rows a stored daily ratio used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum ProvenanceRatio {
+    #[serde(rename = "pe")]
+    Pe,
+    #[serde(rename = "ps")]
+    Ps,
+    #[serde(rename = "pb")]
+    Pb,
+}
+
+impl ProvenanceRatio {
+    /// (statement table, denominator column)
+    fn denominator_source(&self) -> (&'static str, &'static str) {
+        match self {
+            ProvenanceRatio::Pe => ("income_statements", "net_income"),
+            ProvenanceRatio::Ps => ("income_statements", "revenue"),
+            ProvenanceRatio::Pb => ("balance_sheets", "total_equity"),
+        }
+    }
+}
+
+/// One statement value used in a ratio, with the filing it came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProvenanceInput {
+    pub name: String,
+    pub value: Option<f64>,
+    pub source_table: String,
+    pub report_date: String,
+    pub accession_number: Option<String>,
+    pub filed_date: Option<String>,
+    /// A share count estimated from a provider market cap because the filing reported none
+    pub is_estimated: bool,
+}
+
+/// Full derivation of a ratio as of a date, from the stored daily ratio row on or before it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RatioProvenance {
+    pub stock_id: i64,
+    pub ratio: ProvenanceRatio,
+    pub as_of_date: String,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub value: Option<f64>,
+    pub price_date: Option<String>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub close_price: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub market_cap: Option<f64>,
+    /// shares_history, balance_sheet or provider; None without a stored ratio or for rows
+    /// computed before their inputs were recorded
+    pub market_cap_source: Option<String>,
+    /// None when the market cap came from the provider
+    pub shares_outstanding: Option<ProvenanceInput>,
+    pub denominator: Option<ProvenanceInput>,
+    /// The market cap rests on an estimated share count
+    pub is_estimated: bool,
+    pub formula: String,
+}
+
+/// `column` of the statement row `id`, with its filing
+async fn load_statement_input(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    id: Option<i64>,
+) -> Result<Option<ProvenanceInput>, sqlx::Error> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+    let estimated = if column == "shares_outstanding" { "t.shares_outstanding_estimated" } else { "0" };
+    let query = format!(
+        "SELECT t.{column} AS value, t.report_date, f.accession_number, f.filed_date, {estimated} AS is_estimated
+         FROM {table} t
+         LEFT JOIN sec_filings f ON f.id = t.sec_filing_id
+         WHERE t.id = ?"
+    );
+
+    let row = sqlx::query(&query)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| ProvenanceInput {
+        name: column.to_string(),
+        value: row.try_get::<Option<f64>, _>("value").ok().flatten(),
+        source_table: table.to_string(),
+        report_date: row.get("report_date"),
+        accession_number: row.try_get::<Option<String>, _>("accession_number").ok().flatten(),
+        filed_date: row.try_get::<Option<String>, _>("filed_date").ok().flatten(),
+        is_estimated: row.get::<bool, _>("is_estimated"),
+    }))
+}
+
+/// The shares_outstanding_history entry as of `as_of_date`
+async fn load_history_input(pool: &SqlitePool, stock_id: i64, as_of_date: Option<String>) -> Result<Option<ProvenanceInput>, sqlx::Error> {
+    let Some(as_of_date) = as_of_date else {
+        return Ok(None);
+    };
+    let row = sqlx::query(
+        "SELECT shares, as_of_date, accession_number, filed_date FROM shares_outstanding_history
+         WHERE stock_id = ? AND as_of_date = ?"
+    )
+    .bind(stock_id)
+    .bind(&as_of_date)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| ProvenanceInput {
+        name: "shares_outstanding".to_string(),
+        value: Some(row.get("shares")),
+        source_table: "shares_outstanding_history".to_string(),
+        report_date: row.get("as_of_date"),
+        accession_number: row.get("accession_number"),
+        filed_date: row.get("filed_date"),
+        is_estimated: false,
+    }))
+}
+
+pub async fn calculate_ratio_provenance(
+    pool: &SqlitePool,
+    stock_id: i64,
+    ratio: ProvenanceRatio,
+    as_of_date: &str,
+) -> Result<RatioProvenance, sqlx::Error> {
+    let (table, column) = ratio.denominator_source();
+    let mut provenance = RatioProvenance {
+        stock_id,
+        ratio,
+        as_of_date: as_of_date.to_string(),
+        value: None,
+        price_date: None,
+        close_price: None,
+        market_cap: None,
+        market_cap_source: None,
+        shares_outstanding: None,
+        denominator: None,
+        is_estimated: false,
+        formula: format!("close_price * shares_outstanding / {}", column),
+    };
+
+    let stored = sqlx::query(
+        "SELECT date, price, market_cap, shares_estimated, ps_ratio_ttm, pe_ratio_ttm,
+                income_statement_id, balance_sheet_id, shares_as_of_date, market_cap_source
+         FROM daily_valuation_ratios
+         WHERE stock_id = ? AND date <= ?
+         ORDER BY date DESC
+         LIMIT 1"
+    )
+    .bind(stock_id)
+    .bind(as_of_date)
+    .fetch_optional(pool)
+    .await?;
+    let Some(stored) = stored else {
+        return Ok(provenance);
+    };
+
+    let income_id: Option<i64> = stored.get("income_statement_id");
+    let balance_id: Option<i64> = stored.get("balance_sheet_id");
+    let market_cap_source: Option<String> = stored.get("market_cap_source");
+
+    provenance.shares_outstanding = match market_cap_source.as_deref() {
+        Some("shares_history") => load_history_input(pool, stock_id, stored.get("shares_as_of_date")).await?,
+        Some("balance_sheet") => load_statement_input(pool, "balance_sheets", "shares_outstanding", balance_id).await?,
+        _ => None,
+    };
+    let statement_id = if table == "balance_sheets" { balance_id } else { income_id };
+    provenance.denominator = load_statement_input(pool, table, column, statement_id).await?;
+
+    provenance.price_date = Some(stored.get("date"));
+    provenance.close_price = stored.get("price");
+    provenance.market_cap = stored.get("market_cap");
+    provenance.is_estimated = stored.get::<bool, _>("shares_estimated");
+    // P/S and P/E are the stored values; P/B isn't stored, so it is taken over the same market cap
+    provenance.value = match ratio {
+        ProvenanceRatio::Ps => stored.get("ps_ratio_ttm"),
+        ProvenanceRatio::Pe => stored.get("pe_ratio_ttm"),
+        ProvenanceRatio::Pb => {
+            let equity = provenance.denominator.as_ref().and_then(|d| d.value).filter(|&v| v > 0.0);
+            provenance.market_cap.zip(equity).and_then(|(market_cap, equity)| safe_ratio(market_cap, equity))
+        }
+    };
+    if market_cap_source.as_deref() == Some("provider") {
+        provenance.formula = format!("market_cap / {}", column);
+    }
+    provenance.market_cap_source = market_cap_source;
+
+    Ok(provenance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::valuation_ratios::backfill_daily_ratios;
+    use crate::tests::fixtures::{Fixture, FixtureBuilder};
+
+    /// AAPL's FY2022 and FY2023 statements. The FY2023 balance sheet carries equity but no
+    /// share count, so the stored ratios take their shares from FY2022's.
+    async fn seeded_fixture() -> Fixture {
+        let fixture = FixtureBuilder::new().with_stock("AAPL").build().await;
+        let pool = &fixture.pool;
+        for insert in [
+            "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price) VALUES
+                (1, '2023-12-29', 190.0, 190.0, 190.0, 190.0), (1, '2024-06-28', 210.0, 210.0, 210.0, 210.0)",
+            "INSERT INTO sec_filings (id, stock_id, accession_number, form_type, filed_date, fiscal_year, report_date) VALUES
+                (1, 1, '0000320193-22-000108', '10-K', '2022-10-28', 2022, '2022-09-24'),
+                (2, 1, '0000320193-23-000106', '10-K', '2023-11-03', 2023, '2023-09-30')",
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income, sec_filing_id) VALUES
+                (1, 'FY', '2022-09-24', 2022, 394.3e9, 99.8e9, 1), (1, 'FY', '2023-09-30', 2023, 383.3e9, 97.0e9, 2)",
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_equity, shares_outstanding, sec_filing_id) VALUES
+                (1, 'Annual', '2022-09-24', 2022, 50.7e9, 15.9e9, 1), (1, 'Annual', '2023-09-30', 2023, 62.1e9, NULL, 2)",
+        ] {
+            sqlx::query(insert).execute(pool).await.unwrap();
+        }
+        backfill_daily_ratios(pool, 1).await.unwrap();
+        fixture
+    }
+
+    #[tokio::test]
+    async fn test_ps_provenance_names_the_stored_ratios_inputs() {
+        let fixture = seeded_fixture().await;
+        let pool = &fixture.pool;
+
+        let provenance = calculate_ratio_provenance(pool, 1, ProvenanceRatio::Ps, "2024-01-15").await.unwrap();
+
+        assert_eq!(provenance.price_date.as_deref(), Some("2023-12-29"));
+        assert_eq!(provenance.close_price, Some(190.0));
+        assert_eq!(provenance.market_cap_source.as_deref(), Some("balance_sheet"));
+        let revenue = provenance.denominator.as_ref().unwrap();
+        assert_eq!(revenue.value, Some(383.3e9));
+        assert_eq!(revenue.accession_number.as_deref(), Some("0000320193-23-000106"));
+        // The latest balance sheet has equity but no share count; the stored ratio used FY2022's
+        let shares = provenance.shares_outstanding.as_ref().unwrap();
+        assert_eq!(shares.value, Some(15.9e9));
+        assert_eq!(shares.report_date, "2022-09-24");
+
+        let stored: f64 = sqlx::query_scalar("SELECT ps_ratio_ttm FROM daily_valuation_ratios WHERE stock_id = 1 AND date = '2023-12-29'")
+            .fetch_one(pool).await.unwrap();
+        assert_eq!(provenance.value, Some(stored));
+        assert!((stored - 190.0 * 15.9e9 / 383.3e9).abs() < 1e-9);
+
+        // P/B divides the same market cap by the balance sheet the shares came from
+        let pb = calculate_ratio_provenance(pool, 1, ProvenanceRatio::Pb, "2024-01-15").await.unwrap();
+        assert_eq!(pb.denominator.as_ref().unwrap().value, Some(50.7e9));
+        assert!((pb.value.unwrap() - 190.0 * 15.9e9 / 50.7e9).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_provenance_follows_share_history_and_provider_market_caps() {
+        let fixture = seeded_fixture().await;
+        let pool = &fixture.pool;
+        sqlx::query(
+            "INSERT INTO shares_outstanding_history (stock_id, as_of_date, shares, concept, accession_number, filed_date)
+             VALUES (1, '2024-04-19', 15.4e9, 'dei:EntityCommonStockSharesOutstanding', '0000320193-24-000069', '2024-05-03')"
+        )
+        .execute(pool).await.unwrap();
+        backfill_daily_ratios(pool, 1).await.unwrap();
+
+        let provenance = calculate_ratio_provenance(pool, 1, ProvenanceRatio::Pe, "2024-06-30").await.unwrap();
+        assert_eq!(provenance.market_cap_source.as_deref(), Some("shares_history"));
+        let shares = provenance.shares_outstanding.as_ref().unwrap();
+        assert_eq!((shares.source_table.as_str(), shares.report_date.as_str()), ("shares_outstanding_history", "2024-04-19"));
+        assert!((provenance.value.unwrap() - 210.0 * 15.4e9 / 97.0e9).abs() < 1e-9);
+
+        // Without any share count the provider's market cap is used, and no shares are named
+        sqlx::query("DELETE FROM shares_outstanding_history").execute(pool).await.unwrap();
+        sqlx::query("UPDATE balance_sheets SET shares_outstanding = NULL").execute(pool).await.unwrap();
+        sqlx::query("UPDATE daily_prices SET market_cap = 3.0e12").execute(pool).await.unwrap();
+        backfill_daily_ratios(pool, 1).await.unwrap();
+
+        let provenance = calculate_ratio_provenance(pool, 1, ProvenanceRatio::Ps, "2024-06-30").await.unwrap();
+        assert_eq!(provenance.market_cap_source.as_deref(), Some("provider"));
+        assert_eq!(provenance.shares_outstanding, None);
+        assert_eq!(provenance.formula, "market_cap / revenue");
+        assert!((provenance.value.unwrap() - 3.0e12 / 383.3e9).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_provenance_without_a_stored_ratio_is_empty() {
+        let fixture = seeded_fixture().await;
+
+        let provenance = calculate_ratio_provenance(&fixture.pool, 1, ProvenanceRatio::Pb, "2023-06-30").await.unwrap();
+
+        assert_eq!(provenance.price_date, None);
+        assert_eq!(provenance.value, None);
+        assert_eq!(provenance.denominator, None);
+    }
+}
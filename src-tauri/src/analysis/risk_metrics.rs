@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::safe_f64::{finite, safe_ratio};
+
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// Risk-adjusted return metrics for one stock over a trailing window
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RiskMetrics {
+    pub stock_id: i64,
+    pub period_days: i64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
+    pub risk_free_annual: f64,
+    pub observations: i32,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub sharpe_ratio: Option<f64>,
+    /// None when there are no returns below the risk-free rate in the window
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub sortino_ratio: Option<f64>,
+    /// Benchmark series beta is measured against
+    pub benchmark: Option<String>,
+    /// None without a benchmark or fewer than two days shared with it
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub beta: Option<f64>,
+}
+
+/// Simple daily returns from consecutive closing prices
+pub fn daily_returns(closes: &[f64]) -> Vec<f64> {
+    closes
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect()
+}
+
+fn daily_risk_free(risk_free_annual: f64) -> f64 {
+    risk_free_annual / TRADING_DAYS_PER_YEAR
+}
+
+/// Annualized Sharpe ratio; None when there is no return variation or it overflows
+pub fn sharpe_ratio(returns: &[f64], risk_free_annual: f64) -> Option<f64> {
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let rf = daily_risk_free(risk_free_annual);
+    let n = returns.len() as f64;
+    let mean_excess = returns.iter().map(|r| r - rf).sum::<f64>() / n;
+    let mean = returns.iter().sum::<f64>() / n;
+    let std_dev = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+
+    if std_dev == 0.0 {
+        return None;
+    }
+    finite(mean_excess / std_dev * TRADING_DAYS_PER_YEAR.sqrt())
+}
+
+/// Downside deviation below the minimum acceptable return (MAR), over all observations.
+/// None when no return falls below the MAR.
+pub fn downside_deviation(returns: &[f64], mar: f64) -> Option<f64> {
+    if returns.is_empty() || !returns.iter().any(|&r| r < mar) {
+        return None;
+    }
+
+    let downside_sum = returns.iter().map(|&r| (r - mar).min(0.0).powi(2)).sum::<f64>();
+    finite((downside_sum / returns.len() as f64).sqrt())
+}
+
+/// Annualized Sortino ratio using the daily risk-free rate as the MAR
+pub fn sortino_ratio(returns: &[f64], risk_free_annual: f64) -> Option<f64> {
+    let mar = daily_risk_free(risk_free_annual);
+    let downside = downside_deviation(returns, mar)?;
+
+    let mean_excess = returns.iter().map(|r| r - mar).sum::<f64>() / returns.len() as f64;
+    finite(mean_excess / downside * TRADING_DAYS_PER_YEAR.sqrt())
+}
+
+/// Beta of stock returns against same-day benchmark returns, given as (stock, benchmark) pairs;
+/// None when the benchmark doesn't vary
+pub fn beta(paired_returns: &[(f64, f64)]) -> Option<f64> {
+    if paired_returns.len() < 2 {
+        return None;
+    }
+
+    let n = paired_returns.len() as f64;
+    let mean_stock = paired_returns.iter().map(|(stock, _)| stock).sum::<f64>() / n;
+    let mean_benchmark = paired_returns.iter().map(|(_, benchmark)| benchmark).sum::<f64>() / n;
+    let covariance = paired_returns
+        .iter()
+        .map(|(stock, benchmark)| (stock - mean_stock) * (benchmark - mean_benchmark))
+        .sum::<f64>();
+    let variance = paired_returns.iter().map(|(_, benchmark)| (benchmark - mean_benchmark).powi(2)).sum::<f64>();
+
+    if variance == 0.0 {
+        return None;
+    }
+    safe_ratio(covariance, variance)
+}
+
+/// Closing prices for the trailing `period_days` calendar days ending at the latest price date
+async fn load_closes(pool: &SqlitePool, stock_id: i64, period_days: i64) -> Result<Vec<f64>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT close_price FROM daily_prices
+         WHERE stock_id = ?1
+           AND date >= (SELECT date(MAX(date), '-' || ?2 || ' days') FROM daily_prices WHERE stock_id = ?1)
+         ORDER BY date ASC"
+    )
+    .bind(stock_id)
+    .bind(period_days)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|row| row.get::<f64, _>("close_price")).collect())
+}
+
+/// Daily stock returns over the same trailing window, paired with the `benchmark` series' return
+/// on each day both have one
+async fn load_benchmark_pairs(pool: &SqlitePool, stock_id: i64, period_days: i64, benchmark: &str) -> Result<Vec<(f64, f64)>, sqlx::Error> {
+    let rows = sqlx::query(
+        "WITH closes AS (
+            SELECT date, close_price, LAG(close_price) OVER (ORDER BY date) AS prev_close
+            FROM daily_prices
+            WHERE stock_id = ?1
+              AND date >= (SELECT date(MAX(date), '-' || ?2 || ' days') FROM daily_prices WHERE stock_id = ?1)
+        )
+        SELECT c.close_price / c.prev_close - 1.0 AS stock_return, b.daily_return AS benchmark_return
+        FROM closes c
+        JOIN benchmark_prices b ON b.benchmark = ?3 AND b.date = c.date
+        WHERE c.prev_close > 0 AND b.daily_return IS NOT NULL
+        ORDER BY c.date ASC"
+    )
+    .bind(stock_id)
+    .bind(period_days)
+    .bind(benchmark)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|row| (row.get("stock_return"), row.get("benchmark_return"))).collect())
+}
+
+pub async fn compute_sharpe(pool: &SqlitePool, stock_id: i64, period_days: i64, risk_free_annual: f64) -> Result<Option<f64>, sqlx::Error> {
+    let closes = load_closes(pool, stock_id, period_days).await?;
+    Ok(sharpe_ratio(&daily_returns(&closes), risk_free_annual))
+}
+
+pub async fn compute_sortino(pool: &SqlitePool, stock_id: i64, period_days: i64, risk_free_annual: f64) -> Result<Option<f64>, sqlx::Error> {
+    let closes = load_closes(pool, stock_id, period_days).await?;
+    Ok(sortino_ratio(&daily_returns(&closes), risk_free_annual))
+}
+
+pub async fn calculate_risk_metrics(
+    pool: &SqlitePool,
+    stock_id: i64,
+    period_days: i64,
+    risk_free_annual: f64,
+    benchmark: Option<&str>,
+) -> Result<RiskMetrics, sqlx::Error> {
+    let closes = load_closes(pool, stock_id, period_days).await?;
+    let returns = daily_returns(&closes);
+    let beta = match benchmark {
+        Some(benchmark) => beta(&load_benchmark_pairs(pool, stock_id, period_days, benchmark).await?),
+        None => None,
+    };
+
+    Ok(RiskMetrics {
+        stock_id,
+        period_days,
+        risk_free_annual,
+        observations: returns.len() as i32,
+        sharpe_ratio: sharpe_ratio(&returns, risk_free_annual),
+        sortino_ratio: sortino_ratio(&returns, risk_free_annual),
+        benchmark: benchmark.map(str::to_string),
+        beta,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sortino_all_positive_returns_is_none() {
+        let returns = vec![0.01, 0.02, 0.005, 0.015];
+        assert_eq!(downside_deviation(&returns, 0.0), None);
+        assert_eq!(sortino_ratio(&returns, 0.0), None);
+        assert!(sharpe_ratio(&returns, 0.0).is_some());
+    }
+
+    #[test]
+    fn test_sortino_mixed_returns() {
+        let returns = vec![0.02, -0.01, 0.03, -0.02];
+
+        // Downside deviation: sqrt((0.01² + 0.02²) / 4)
+        let expected_downside = (0.0005_f64 / 4.0).sqrt();
+        let downside = downside_deviation(&returns, 0.0).unwrap();
+        assert!((downside - expected_downside).abs() < 1e-12);
+
+        // Mean return 0.005 over downside deviation, annualized
+        let expected = 0.005 / expected_downside * 252_f64.sqrt();
+        let sortino = sortino_ratio(&returns, 0.0).unwrap();
+        assert!((sortino - expected).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_compute_sortino_from_prices() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE daily_prices (stock_id INTEGER, date DATE, close_price REAL)")
+            .execute(&pool).await.unwrap();
+
+        // Strictly rising prices: no downside returns
+        for (day, close) in [100.0, 101.0, 103.0, 104.0].iter().enumerate() {
+            sqlx::query("INSERT INTO daily_prices (stock_id, date, close_price) VALUES (1, date('2024-01-01', ?), ?)")
+                .bind(format!("+{} days", day))
+                .bind(close)
+                .execute(&pool).await.unwrap();
+        }
+
+        assert_eq!(compute_sortino(&pool, 1, 30, 0.0).await.unwrap(), None);
+
+        let metrics = calculate_risk_metrics(&pool, 1, 30, 0.0, None).await.unwrap();
+        assert_eq!(metrics.observations, 3);
+        assert!(metrics.sharpe_ratio.is_some());
+        assert_eq!(metrics.sortino_ratio, None);
+        assert_eq!((metrics.benchmark, metrics.beta), (None, None));
+    }
+
+    #[tokio::test]
+    async fn test_beta_against_benchmark_series() {
+        // Twice the benchmark's move each day, offset by a constant: beta 2
+        let paired = vec![(0.021, 0.01), (-0.039, -0.02), (0.061, 0.03), (0.001, 0.0)];
+        assert!((beta(&paired).unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!(beta(&[(0.01, 0.0), (0.02, 0.0)]), None);
+
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE daily_prices (stock_id INTEGER, date DATE, close_price REAL)")
+            .execute(&pool).await.unwrap();
+        sqlx::query("CREATE TABLE benchmark_prices (benchmark TEXT, date DATE, value REAL, daily_return REAL)")
+            .execute(&pool).await.unwrap();
+        for (day, close, benchmark_return) in [("2024-01-02", 100.0, None), ("2024-01-03", 102.0, Some(0.01)), ("2024-01-04", 96.9, Some(-0.025))] {
+            sqlx::query("INSERT INTO daily_prices (stock_id, date, close_price) VALUES (1, ?, ?)")
+                .bind(day).bind(close)
+                .execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO benchmark_prices (benchmark, date, value, daily_return) VALUES ('SP500_EW', ?, 100.0, ?)")
+                .bind(day).bind(benchmark_return)
+                .execute(&pool).await.unwrap();
+        }
+
+        let metrics = calculate_risk_metrics(&pool, 1, 30, 0.0, Some("SP500_EW")).await.unwrap();
+        assert_eq!(metrics.benchmark.as_deref(), Some("SP500_EW"));
+        assert!((metrics.beta.unwrap() - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use super::growth_consistency::fy_revenue_with_prior;
+use super::piotroski_history::{load_latest_piotroski_scores, piotroski_scores_sql};
+
+/// Aggregate over the stocks in a group that have the underlying data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SectorMetric {
+    pub value: Option<f64>,
+    /// Stocks the value was computed from
+    pub sample_size: i64,
+}
+
+/// One heatmap tile: a sector, or an industry when drilling into a sector
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SectorOverview {
+    pub name: String,
+    pub stock_count: i64,
+    pub total_market_cap: Option<f64>,
+    pub median_market_cap: SectorMetric,
+    pub median_pe: SectorMetric,
+    pub median_ps: SectorMetric,
+    pub median_ev_ebitda: SectorMetric,
+    /// Latest fiscal year's revenue over the year before, percent, both annualized to 365 days;
+    /// only for consecutive fiscal years, neither a stub period
+    pub median_revenue_growth: SectorMetric,
+    pub avg_piotroski_score: SectorMetric,
+    /// Equal-weight mean of per-stock price returns, percent
+    pub return_1m: SectorMetric,
+    pub return_1y: SectorMetric,
+}
+
+fn median_metric(mut values: Vec<f64>) -> SectorMetric {
+    values.sort_by(f64::total_cmp);
+    let n = values.len();
+    let value = match n {
+        0 => None,
+        _ if n % 2 == 1 => Some(values[n / 2]),
+        _ => Some((values[n / 2 - 1] + values[n / 2]) / 2.0),
+    };
+    SectorMetric { value, sample_size: n as i64 }
+}
+
+fn mean_metric(values: Vec<f64>) -> SectorMetric {
+    let n = values.len();
+    let value = (n > 0).then(|| values.iter().sum::<f64>() / n as f64);
+    SectorMetric { value, sample_size: n as i64 }
+}
+
+#[derive(Default)]
+struct GroupValues {
+    stock_count: i64,
+    market_caps: Vec<f64>,
+    pe: Vec<f64>,
+    ps: Vec<f64>,
+    ev_ebitda: Vec<f64>,
+    revenue_growth: Vec<f64>,
+    piotroski: Vec<f64>,
+    return_1m: Vec<f64>,
+    return_1y: Vec<f64>,
+}
+
+fn percent_change(current: Option<f64>, previous: Option<f64>) -> Option<f64> {
+    match (current, previous) {
+        (Some(current), Some(previous)) if previous > 0.0 => Some((current - previous) / previous * 100.0),
+        _ => None,
+    }
+}
+
+/// Per-sector aggregates, or per-industry within `sector` when given. One set-based query
+/// reads each stock's latest figures; stocks missing a figure only shrink that metric's sample.
+pub async fn compute_sector_overview(pool: &SqlitePool, sector: Option<&str>) -> Result<Vec<SectorOverview>> {
+    let group_column = if sector.is_some() { "s.industry" } else { "s.sector" };
+    let query = format!(
+        "WITH grouped AS (
+            SELECT s.id AS stock_id, COALESCE({0}, 'Unclassified') AS group_name
+            FROM stocks s
+            WHERE ?1 IS NULL OR s.sector = ?1
+        ),
+        latest_ratio AS (
+            SELECT d.stock_id, MAX(d.date) AS date
+            FROM daily_valuation_ratios d JOIN grouped g ON g.stock_id = d.stock_id
+            GROUP BY d.stock_id
+        ),
+        ratios AS (
+            SELECT d.stock_id, d.market_cap, d.pe_ratio_ttm, d.ps_ratio_ttm
+            FROM latest_ratio l JOIN daily_valuation_ratios d ON d.stock_id = l.stock_id AND d.date = l.date
+        ),
+        latest_price AS (
+            SELECT p.stock_id, MAX(p.date) AS date
+            FROM daily_prices p JOIN grouped g ON g.stock_id = p.stock_id
+            WHERE p.close_price > 0
+            GROUP BY p.stock_id
+        ),
+        prices AS (
+            SELECT p.stock_id, p.close_price, p.market_cap,
+                   (SELECT close_price FROM daily_prices
+                    WHERE stock_id = p.stock_id AND date <= date(p.date, '-1 month') AND close_price > 0
+                    ORDER BY date DESC LIMIT 1) AS close_1m,
+                   (SELECT close_price FROM daily_prices
+                    WHERE stock_id = p.stock_id AND date <= date(p.date, '-1 year') AND close_price > 0
+                    ORDER BY date DESC LIMIT 1) AS close_1y
+            FROM latest_price l JOIN daily_prices p ON p.stock_id = l.stock_id AND p.date = l.date
+        ),
+        revenue AS (
+            SELECT stock_id, annualized_revenue,
+                   CASE WHEN prior_year = fiscal_year - 1 THEN prior_revenue END AS prior_revenue
+            FROM ({1})
+            WHERE rn = 1 AND stock_id IN (SELECT stock_id FROM grouped)
+        )
+        SELECT g.group_name,
+               COALESCE(r.market_cap, p.market_cap) AS market_cap,
+               r.pe_ratio_ttm, r.ps_ratio_ttm, o.ev_ebitda_ratio,
+               rev.annualized_revenue AS current_revenue, rev.prior_revenue AS previous_revenue,
+               pio.f_score_complete,
+               p.close_price, p.close_1m, p.close_1y
+        FROM grouped g
+        LEFT JOIN ratios r ON r.stock_id = g.stock_id
+        LEFT JOIN prices p ON p.stock_id = g.stock_id
+        LEFT JOIN revenue rev ON rev.stock_id = g.stock_id
+        LEFT JOIN oshaughnessy_value_composite o ON o.stock_id = g.stock_id
+        LEFT JOIN {2} pio ON pio.stock_id = g.stock_id",
+        group_column,
+        fy_revenue_with_prior(),
+        piotroski_scores_sql(&load_latest_piotroski_scores(pool, None).await?)
+    );
+
+    let rows = sqlx::query(&query).bind(sector).fetch_all(pool).await?;
+
+    let mut groups: BTreeMap<String, GroupValues> = BTreeMap::new();
+    for row in &rows {
+        let group = groups.entry(row.get("group_name")).or_default();
+        group.stock_count += 1;
+
+        // Non-positive multiples (losses, missing revenue) carry no valuation signal
+        let positive = |column: &str| row.get::<Option<f64>, _>(column).filter(|v| *v > 0.0);
+        group.market_caps.extend(positive("market_cap"));
+        group.pe.extend(positive("pe_ratio_ttm"));
+        group.ps.extend(positive("ps_ratio_ttm"));
+        group.ev_ebitda.extend(positive("ev_ebitda_ratio"));
+        group.revenue_growth.extend(percent_change(row.get("current_revenue"), row.get("previous_revenue")));
+        group.piotroski.extend(row.get::<Option<i64>, _>("f_score_complete").map(|score| score as f64));
+
+        let close: Option<f64> = row.get("close_price");
+        group.return_1m.extend(percent_change(close, row.get("close_1m")));
+        group.return_1y.extend(percent_change(close, row.get("close_1y")));
+    }
+
+    let mut overview: Vec<SectorOverview> = groups
+        .into_iter()
+        .map(|(name, values)| SectorOverview {
+            name,
+            stock_count: values.stock_count,
+            total_market_cap: (!values.market_caps.is_empty()).then(|| values.market_caps.iter().sum()),
+            median_market_cap: median_metric(values.market_caps),
+            median_pe: median_metric(values.pe),
+            median_ps: median_metric(values.ps),
+            median_ev_ebitda: median_metric(values.ev_ebitda),
+            median_revenue_growth: median_metric(values.revenue_growth),
+            avg_piotroski_score: mean_metric(values.piotroski),
+            return_1m: mean_metric(values.return_1m),
+            return_1y: mean_metric(values.return_1y),
+        })
+        .collect();
+
+    // Largest tiles first
+    overview.sort_by(|a, b| {
+        b.total_market_cap.unwrap_or(0.0).total_cmp(&a.total_market_cap.unwrap_or(0.0)).then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(overview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, sector, industry) VALUES
+                (1, 'SOFT', 'Software Co', 'Technology', 'Software'),
+                (2, 'CHIP', 'Chip Co', 'Technology', NULL),
+                (3, 'PHRM', 'Pharma Co', 'Health Care', NULL)"
+        )
+        .execute(&pool).await.unwrap();
+
+        // Both Technology stocks have prices and ratios; only SOFT has a year of history and financials
+        for (id, date, close) in [
+            (1, "2023-06-01", 80.0), (1, "2024-05-01", 100.0), (1, "2024-06-01", 110.0),
+            (2, "2024-05-01", 50.0), (2, "2024-06-01", 40.0),
+        ] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?1, ?2, ?3, ?3, ?3, ?3)"
+            )
+            .bind(id).bind(date).bind(close)
+            .execute(&pool).await.unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO daily_valuation_ratios (stock_id, date, market_cap, pe_ratio_ttm, ps_ratio_ttm, data_completeness_score) VALUES
+                (1, '2024-06-01', 3e9, 20.0, 5.0, 100),
+                (2, '2024-06-01', 1e9, NULL, 2.0, 60)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue) VALUES
+                (1, 'FY', '2023-12-31', 2023, 120.0), (1, 'FY', '2022-12-31', 2022, 100.0)"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_sector_overview_reports_sample_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+
+        let overview = compute_sector_overview(&pool, None).await.unwrap();
+        assert_eq!(overview.iter().map(|o| o.name.as_str()).collect::<Vec<_>>(), vec!["Technology", "Health Care"]);
+
+        let tech = &overview[0];
+        assert_eq!(tech.stock_count, 2);
+        assert_eq!(tech.total_market_cap, Some(4e9));
+        assert_eq!(tech.median_market_cap, SectorMetric { value: Some(2e9), sample_size: 2 });
+        assert_eq!(tech.median_pe, SectorMetric { value: Some(20.0), sample_size: 1 });
+        assert_eq!(tech.median_ps, SectorMetric { value: Some(3.5), sample_size: 2 });
+        let growth = &tech.median_revenue_growth;
+        assert_eq!(growth.sample_size, 1);
+        assert!((growth.value.unwrap() - 20.0).abs() < 1e-9);
+        // SOFT +10%, CHIP -20%
+        assert_eq!(tech.return_1m, SectorMetric { value: Some(-5.0), sample_size: 2 });
+        assert_eq!(tech.return_1y.sample_size, 1);
+        assert!((tech.return_1y.value.unwrap() - 37.5).abs() < 1e-9);
+
+        let health = &overview[1];
+        assert_eq!(health.stock_count, 1);
+        assert_eq!(health.total_market_cap, None);
+        assert_eq!(health.median_pe, SectorMetric { value: None, sample_size: 0 });
+
+        let industries = compute_sector_overview(&pool, Some("Technology")).await.unwrap();
+        assert_eq!(industries.iter().map(|o| (o.name.as_str(), o.stock_count)).collect::<Vec<_>>(), vec![("Software", 1), ("Unclassified", 1)]);
+    }
+}
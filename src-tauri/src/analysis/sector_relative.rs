@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::safe_f64::mean_and_std_dev;
+
+/// Sectors with fewer valid values than this are too small for a meaningful z-score
+pub const MIN_SECTOR_STOCKS: usize = 5;
+
+/// Each stock's latest valuation ratios (`ratios`) and latest annual balance sheet (`balance`,
+/// current where `rn = 1`), the inputs of `ValuationMetric::expression`
+pub(crate) const LATEST_VALUATION_INPUTS: &str = "
+    WITH latest_ratio AS (
+        SELECT stock_id, MAX(date) AS date FROM daily_valuation_ratios GROUP BY stock_id
+    ),
+    ratios AS (
+        SELECT d.stock_id, d.market_cap, d.pe_ratio_ttm, d.ps_ratio_ttm
+        FROM latest_ratio l JOIN daily_valuation_ratios d ON d.stock_id = l.stock_id AND d.date = l.date
+    ),
+    balance AS (
+        SELECT stock_id, total_equity,
+               ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) AS rn
+        FROM balance_sheets
+        WHERE period_type = 'Annual'
+    )";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ValuationMetric {
+    Pe,
+    Ps,
+    Pb,
+}
+
+impl ValuationMetric {
+    /// Expression over the latest ratios (`r`) and latest annual balance sheet (`b`)
+    pub(crate) fn expression(self) -> &'static str {
+        match self {
+            ValuationMetric::Pe => "r.pe_ratio_ttm",
+            ValuationMetric::Ps => "r.ps_ratio_ttm",
+            ValuationMetric::Pb => "CASE WHEN r.market_cap > 0 AND b.total_equity > 0 THEN r.market_cap / b.total_equity END",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SectorRelativeValuation {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub sector: String,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
+    pub value: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
+    pub sector_mean: f64,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
+    pub sector_stddev: f64,
+    /// Standard deviations from the sector mean; negative is cheaper than the sector
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite_f64")]
+    pub z_score: f64,
+    /// Stocks in the sector with a valid value
+    pub sector_sample_size: i64,
+}
+
+/// Z-score of each stock's latest `metric` within its sector, most undervalued first.
+/// Only positive values count; sectors with fewer than `MIN_SECTOR_STOCKS` of them, or
+/// with no spread, are skipped.
+pub async fn compute_sector_relative_valuation(pool: &SqlitePool, metric: ValuationMetric) -> Result<Vec<SectorRelativeValuation>> {
+    let query = format!(
+        "{}
+        SELECT stock_id, symbol, sector, value FROM (
+            SELECT s.id AS stock_id, s.symbol, s.sector, {} AS value
+            FROM stocks s
+            JOIN ratios r ON r.stock_id = s.id
+            LEFT JOIN balance b ON b.stock_id = s.id AND b.rn = 1
+            WHERE s.sector IS NOT NULL
+        )
+        WHERE value > 0",
+        LATEST_VALUATION_INPUTS,
+        metric.expression()
+    );
+
+    let mut sectors: HashMap<String, Vec<(i64, String, f64)>> = HashMap::new();
+    for row in sqlx::query(&query).fetch_all(pool).await? {
+        sectors
+            .entry(row.get("sector"))
+            .or_default()
+            .push((row.get("stock_id"), row.get("symbol"), row.get("value")));
+    }
+
+    let mut valuations = Vec::new();
+    for (sector, stocks) in sectors {
+        if stocks.len() < MIN_SECTOR_STOCKS {
+            continue;
+        }
+        let values: Vec<f64> = stocks.iter().map(|(_, _, value)| *value).collect();
+        let Some((mean, stddev)) = mean_and_std_dev(&values).filter(|(_, stddev)| *stddev > 0.0) else {
+            continue;
+        };
+
+        valuations.extend(stocks.iter().map(|(stock_id, symbol, value)| SectorRelativeValuation {
+            stock_id: *stock_id,
+            symbol: symbol.clone(),
+            sector: sector.clone(),
+            value: *value,
+            sector_mean: mean,
+            sector_stddev: stddev,
+            z_score: (value - mean) / stddev,
+            sector_sample_size: stocks.len() as i64,
+        }));
+    }
+
+    valuations.sort_by(|a, b| a.z_score.total_cmp(&b.z_score).then_with(|| a.symbol.cmp(&b.symbol)));
+    Ok(valuations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+
+        // Technology trades at much higher multiples than Utilities; Energy is too small to score
+        let stocks = [
+            ("Technology", [("T1", 10.0), ("T2", 20.0), ("T3", 30.0), ("T4", 40.0), ("T5", 50.0)].to_vec()),
+            ("Utilities", [("U1", 8.0), ("U2", 10.0), ("U3", 10.0), ("U4", 10.0), ("U5", 12.0)].to_vec()),
+            ("Energy", [("E1", 2.0), ("E2", 4.0), ("E3", 6.0), ("E4", 8.0)].to_vec()),
+        ];
+        let mut id = 0;
+        for (sector, members) in stocks {
+            for (symbol, pe) in members {
+                id += 1;
+                sqlx::query("INSERT INTO stocks (id, symbol, company_name, sector) VALUES (?, ?, ?, ?)")
+                    .bind(id).bind(symbol).bind(symbol).bind(sector)
+                    .execute(&pool).await.unwrap();
+                sqlx::query("INSERT INTO daily_valuation_ratios (stock_id, date, pe_ratio_ttm) VALUES (?, '2024-06-01', ?)")
+                    .bind(id).bind(pe)
+                    .execute(&pool).await.unwrap();
+            }
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_stocks_are_ranked_by_sector_z_score() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+
+        let valuations = compute_sector_relative_valuation(&pool, ValuationMetric::Pe).await.unwrap();
+        assert_eq!(valuations.len(), 10);
+        assert!(valuations.iter().all(|v| v.sector != "Energy"));
+
+        let t1 = valuations.iter().find(|v| v.symbol == "T1").unwrap();
+        assert_eq!((t1.sector_mean, t1.sector_sample_size), (30.0, 5));
+        assert!((t1.sector_stddev - 200f64.sqrt()).abs() < 1e-9);
+        assert!((t1.z_score - (-20.0 / 200f64.sqrt())).abs() < 1e-9);
+        let u1 = valuations.iter().find(|v| v.symbol == "U1").unwrap();
+        assert!((u1.z_score - (-2.0 / 1.6f64.sqrt())).abs() < 1e-9);
+
+        // T2 at 20x sorts ahead of U2 at 10x: it is cheap for its sector, U2 is at its sector mean
+        let order: Vec<&str> = valuations.iter().map(|v| v.symbol.as_str()).collect();
+        assert_eq!(order, vec!["U1", "T1", "T2", "T3", "U2", "U3", "U4", "T4", "T5", "U5"]);
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::safe_f64::safe_ratio;
+
+/// Trailing shareholder yield and its components, on the inputs the O'Shaughnessy value
+/// composite ranks: the latest annual cash flow statement over the latest close times the
+/// latest annual share count
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ShareholderYield {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// Cash flow statement the payouts come from
+    pub fiscal_year: Option<i32>,
+    pub report_date: Option<String>,
+    pub market_cap: Option<f64>,
+    pub dividends_paid: Option<f64>,
+    /// Cash paid for repurchases (PaymentsForRepurchaseOfCommonStock); stock issued isn't
+    /// netted against it
+    pub gross_buybacks: Option<f64>,
+    /// Percent of market cap
+    pub dividend_yield: Option<f64>,
+    /// Gross buybacks as a percent of market cap
+    pub buyback_yield: Option<f64>,
+    /// Dividend plus buyback yield; None without a market cap or either payout
+    pub shareholder_yield: Option<f64>,
+}
+
+pub async fn compute_shareholder_yield(pool: &SqlitePool, stock_id: i64, symbol: String) -> Result<ShareholderYield> {
+    let cash_flow = sqlx::query(
+        "SELECT fiscal_year, report_date, dividends_paid, share_repurchases
+         FROM cash_flow_statements
+         WHERE stock_id = ? AND period_type = 'Annual' AND operating_cash_flow IS NOT NULL
+         ORDER BY report_date DESC
+         LIMIT 1"
+    )
+    .bind(stock_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let market_cap: Option<f64> = sqlx::query_scalar(
+        "SELECT (SELECT close_price FROM daily_prices WHERE stock_id = ?1 ORDER BY date DESC LIMIT 1)
+              * (SELECT shares_outstanding FROM balance_sheets
+                 WHERE stock_id = ?1 AND period_type = 'Annual' AND total_equity IS NOT NULL
+                 ORDER BY report_date DESC LIMIT 1)"
+    )
+    .bind(stock_id)
+    .fetch_one(pool)
+    .await?;
+
+    let dividends_paid: Option<f64> = cash_flow.as_ref().and_then(|row| row.get("dividends_paid"));
+    let gross_buybacks: Option<f64> = cash_flow.as_ref().and_then(|row| row.get("share_repurchases"));
+    let total = (dividends_paid.is_some() || gross_buybacks.is_some())
+        .then(|| dividends_paid.unwrap_or(0.0) + gross_buybacks.unwrap_or(0.0));
+
+    let positive_cap = market_cap.filter(|cap| *cap > 0.0);
+    let percent_of_cap = |amount: Option<f64>| amount.zip(positive_cap).and_then(|(amount, cap)| safe_ratio(amount * 100.0, cap));
+
+    Ok(ShareholderYield {
+        stock_id,
+        symbol,
+        fiscal_year: cash_flow.as_ref().and_then(|row| row.get("fiscal_year")),
+        report_date: cash_flow.as_ref().map(|row| row.get("report_date")),
+        market_cap,
+        dividends_paid,
+        gross_buybacks,
+        dividend_yield: percent_of_cap(dividends_paid),
+        buyback_yield: percent_of_cap(gross_buybacks),
+        shareholder_yield: percent_of_cap(total),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{day, AnnualFinancials, FixtureBuilder, PriceBar};
+
+    fn payouts(dividends_paid: Option<f64>, share_repurchases: Option<f64>) -> impl Fn(i32) -> AnnualFinancials {
+        move |_| AnnualFinancials {
+            total_equity: Some(5e7),
+            shares_outstanding: Some(1e6),
+            operating_cash_flow: Some(1e7),
+            dividends_paid,
+            share_repurchases,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_yield_splits_into_dividends_and_buybacks() {
+        // $100 a share on a million shares: a $100M market cap
+        let fixture = FixtureBuilder::new()
+            .with_stock("RTRN").with_sp500_flag()
+            .with_prices(day(2024, 6, 28)..=day(2024, 6, 28), |_, _| PriceBar::flat(100.0))
+            .with_annual_financials(2023..=2023, payouts(Some(2e6), Some(3e6)))
+            .with_stock("BUYB").with_sp500_flag()
+            .with_prices(day(2024, 6, 28)..=day(2024, 6, 28), |_, _| PriceBar::flat(100.0))
+            .with_annual_financials(2023..=2023, payouts(None, Some(4e6)))
+            .with_stock("NONE").with_sp500_flag()
+            .with_prices(day(2024, 6, 28)..=day(2024, 6, 28), |_, _| PriceBar::flat(100.0))
+            .with_annual_financials(2023..=2023, payouts(None, None))
+            .build()
+            .await;
+        let pool = &fixture.pool;
+
+        let returner = compute_shareholder_yield(pool, 1, "RTRN".to_string()).await.unwrap();
+        assert_eq!(returner.market_cap, Some(1e8));
+        assert_eq!((returner.fiscal_year, returner.report_date.as_deref()), (Some(2023), Some("2023-12-31")));
+        assert_eq!(
+            (returner.dividend_yield, returner.buyback_yield, returner.shareholder_yield),
+            (Some(2.0), Some(3.0), Some(5.0))
+        );
+
+        // Buybacks alone are a yield; the missing dividend has no yield of its own
+        let buyer = compute_shareholder_yield(pool, 2, "BUYB".to_string()).await.unwrap();
+        assert_eq!(buyer.gross_buybacks, Some(4e6));
+        assert_eq!(
+            (buyer.dividend_yield, buyer.buyback_yield, buyer.shareholder_yield),
+            (None, Some(4.0), Some(4.0))
+        );
+
+        let no_payouts = compute_shareholder_yield(pool, 3, "NONE".to_string()).await.unwrap();
+        assert_eq!(no_payouts.shareholder_yield, None);
+
+        // The value composite ranks on the same figures
+        let composite: Vec<(String, Option<f64>, Option<f64>, Option<f64>)> = sqlx::query_as(
+            "SELECT symbol, dividend_yield, buyback_yield, shareholder_yield FROM oshaughnessy_value_composite ORDER BY symbol"
+        )
+        .fetch_all(pool).await.unwrap();
+        assert_eq!(composite, vec![
+            ("BUYB".to_string(), None, Some(4.0), Some(4.0)),
+            ("NONE".to_string(), None, None, None),
+            ("RTRN".to_string(), Some(2.0), Some(3.0), Some(5.0)),
+        ]);
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::analysis::price_range::compute_52_week_range;
+use crate::error::CommandError;
+
+/// Header figures for the stock detail page
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StockSummary {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub company_name: String,
+    /// Industry is optional on stocks and only used by the sector overview drill-down
+    pub sector: Option<String>,
+    pub latest_date: Option<String>,
+    pub latest_close: Option<f64>,
+    pub previous_close: Option<f64>,
+    pub day_change: Option<f64>,
+    pub day_change_pct: Option<f64>,
+    pub market_cap: Option<f64>,
+    pub pe_ratio: Option<f64>,
+    pub ps_ratio: Option<f64>,
+    pub high_52_week: Option<f64>,
+    pub low_52_week: Option<f64>,
+}
+
+/// Summary from two queries: stock, last two closes and latest ratios, then the 52-week range
+pub async fn compute_stock_summary(pool: &SqlitePool, symbol: &str, as_of: NaiveDate) -> Result<StockSummary> {
+    let row = sqlx::query(
+        "WITH stock AS (
+            SELECT id, symbol, company_name, sector FROM stocks WHERE symbol = ?1
+        ),
+        recent AS (
+            SELECT date, close_price, market_cap, ROW_NUMBER() OVER (ORDER BY date DESC) AS rn
+            FROM daily_prices
+            WHERE stock_id = (SELECT id FROM stock) AND close_price IS NOT NULL
+            ORDER BY date DESC
+            LIMIT 2
+        ),
+        ratios AS (
+            SELECT market_cap, pe_ratio_ttm, ps_ratio_ttm
+            FROM daily_valuation_ratios
+            WHERE stock_id = (SELECT id FROM stock)
+            ORDER BY date DESC
+            LIMIT 1
+        )
+        SELECT
+            s.id, s.symbol, s.company_name, s.sector,
+            (SELECT date FROM recent WHERE rn = 1) AS latest_date,
+            (SELECT close_price FROM recent WHERE rn = 1) AS latest_close,
+            (SELECT close_price FROM recent WHERE rn = 2) AS previous_close,
+            COALESCE((SELECT market_cap FROM ratios), (SELECT market_cap FROM recent WHERE rn = 1)) AS market_cap,
+            (SELECT pe_ratio_ttm FROM ratios) AS pe_ratio,
+            (SELECT ps_ratio_ttm FROM ratios) AS ps_ratio
+         FROM stock s"
+    )
+    .bind(symbol)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+
+    let stock_id: i64 = row.get("id");
+    let latest_close: Option<f64> = row.get("latest_close");
+    let previous_close: Option<f64> = row.get("previous_close");
+
+    let day_change = latest_close.zip(previous_close).map(|(latest, previous)| latest - previous);
+    let day_change_pct = day_change
+        .zip(previous_close)
+        .filter(|(_, previous)| *previous > 0.0)
+        .map(|(change, previous)| change / previous * 100.0);
+
+    let range = compute_52_week_range(pool, stock_id, as_of).await?;
+
+    Ok(StockSummary {
+        stock_id,
+        symbol: row.get("symbol"),
+        company_name: row.get("company_name"),
+        sector: row.get("sector"),
+        latest_date: row.get("latest_date"),
+        latest_close,
+        previous_close,
+        day_change,
+        day_change_pct,
+        market_cap: row.get("market_cap"),
+        pe_ratio: row.get("pe_ratio"),
+        ps_ratio: row.get("ps_ratio"),
+        high_52_week: range.high_52_week,
+        low_52_week: range.low_52_week,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_pool() -> SqlitePool {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE stocks (id INTEGER PRIMARY KEY, symbol TEXT NOT NULL, company_name TEXT NOT NULL, sector TEXT)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE daily_prices (stock_id INTEGER, date TEXT, high_price REAL, low_price REAL, close_price REAL, market_cap REAL)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE daily_valuation_ratios (stock_id INTEGER, date TEXT, market_cap REAL, pe_ratio_ttm REAL, ps_ratio_ttm REAL)"
+        )
+        .execute(&pool).await.unwrap();
+
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, sector) VALUES
+                (1, 'AAPL', 'Apple Inc.', 'Technology'),
+                (2, 'NEW', 'Newly Listed Co', NULL)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO daily_prices (stock_id, date, high_price, low_price, close_price, market_cap) VALUES
+                (1, '2024-06-03', 196.0, 190.0, 194.0, NULL),
+                (1, '2024-06-04', 195.0, 150.0, 190.0, NULL),
+                (1, '2024-06-05', 210.0, 191.0, 209.0, 3.2e12)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO daily_valuation_ratios (stock_id, date, market_cap, pe_ratio_ttm, ps_ratio_ttm) VALUES
+                (1, '2024-06-04', 2.9e12, 29.5, 7.6),
+                (1, '2024-06-05', 3.1e12, 32.4, 8.3)"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_summary_assembles_prices_ratios_and_range() {
+        let pool = seeded_pool().await;
+        let as_of = NaiveDate::from_ymd_opt(2024, 6, 5).unwrap();
+
+        let summary = compute_stock_summary(&pool, "AAPL", as_of).await.unwrap();
+        assert_eq!(summary.sector.as_deref(), Some("Technology"));
+        assert_eq!(summary.latest_date.as_deref(), Some("2024-06-05"));
+        assert_eq!(summary.latest_close, Some(209.0));
+        assert_eq!(summary.previous_close, Some(190.0));
+        assert_eq!(summary.day_change, Some(19.0));
+        assert!((summary.day_change_pct.unwrap() - 19.0 / 190.0 * 100.0).abs() < 1e-9);
+        // Ratios table wins over the price row's market cap
+        assert_eq!(summary.market_cap, Some(3.1e12));
+        assert_eq!(summary.pe_ratio, Some(32.4));
+        assert_eq!(summary.ps_ratio, Some(8.3));
+        assert_eq!(summary.high_52_week, Some(210.0));
+        assert_eq!(summary.low_52_week, Some(150.0));
+
+        // No prices or ratios: everything but the stock itself is None
+        let empty = compute_stock_summary(&pool, "NEW", as_of).await.unwrap();
+        assert_eq!(empty.company_name, "Newly Listed Co");
+        assert!(empty.latest_close.is_none() && empty.day_change.is_none() && empty.market_cap.is_none());
+        assert!(empty.pe_ratio.is_none() && empty.high_52_week.is_none());
+
+        let unknown = compute_stock_summary(&pool, "ZZZZ", as_of).await;
+        assert!(matches!(
+            unknown.unwrap_err().downcast_ref::<CommandError>(),
+            Some(CommandError::NotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+
+use crate::database::helpers::get_price_on_or_before;
+use crate::error::CommandError;
+use crate::tools::dividends::load_dividend_records;
+
+/// Price-only and dividend-reinvested return between two dates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TotalReturn {
+    pub symbol: String,
+    /// Trading days the endpoint closes come from, on or before the requested dates
+    pub start_date: String,
+    pub end_date: String,
+    pub start_price: f64,
+    pub end_price: f64,
+    pub price_return_pct: f64,
+    /// With each dividend reinvested at the close on or before its date
+    pub total_return_pct: f64,
+    /// None when both endpoints fall on the same trading day
+    pub annualized_price_return_pct: Option<f64>,
+    pub annualized_total_return_pct: Option<f64>,
+    pub dividends_reinvested: i32,
+}
+
+/// A dividend per share and the close it was reinvested at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReinvestedDividend {
+    pub amount_per_share: f64,
+    pub price: f64,
+}
+
+/// Shares held at the end per share bought at the start, reinvesting each dividend
+pub fn reinvested_shares(dividends: &[ReinvestedDividend]) -> f64 {
+    dividends
+        .iter()
+        .filter(|dividend| dividend.price > 0.0)
+        .fold(1.0, |shares, dividend| shares * (1.0 + dividend.amount_per_share / dividend.price))
+}
+
+/// Compound annual rate, in percent, of a `growth` multiple over `days`
+pub fn annualize(growth: f64, days: i64) -> Option<f64> {
+    (days > 0 && growth > 0.0).then(|| (growth.powf(365.25 / days as f64) - 1.0) * 100.0)
+}
+
+/// Price and total return of `symbol` from the close on or before `start` to the close on or before
+/// `end`. Dividends come from the stored quarterly rows with a period end after the start close
+/// and up to the end close, and from fiscal years with no quarters stored; each is reinvested on
+/// its period end. Closes aren't adjusted for splits, so a split inside the range distorts both returns.
+pub async fn compute_total_return(pool: &SqlitePool, symbol: &str, start: NaiveDate, end: NaiveDate) -> Result<TotalReturn> {
+    let stock_id: i64 = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(symbol)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+
+    let no_price = |date: NaiveDate| CommandError::not_found("daily_prices", format!("No close for {} on or before {}", symbol, date));
+    let (start_date, start_price) = get_price_on_or_before(pool, stock_id, start)
+        .await
+        .map_err(|e| anyhow!(e))?
+        .ok_or_else(|| no_price(start))?;
+    let (end_date, end_price) = get_price_on_or_before(pool, stock_id, end)
+        .await
+        .map_err(|e| anyhow!(e))?
+        .ok_or_else(|| no_price(end))?;
+    if start_price <= 0.0 {
+        return Err(CommandError::validation("start_date", format!("Close on {} is not positive", start_date)).into());
+    }
+
+    let records = load_dividend_records(pool, stock_id).await?;
+    let (start_key, end_key) = (start_date.format("%Y-%m-%d").to_string(), end_date.format("%Y-%m-%d").to_string());
+    let mut dividends = Vec::new();
+    for record in &records {
+        let in_range = record.period_end > start_key && record.period_end <= end_key;
+        let covered_by_quarters = record.period_type == "Annual"
+            && records.iter().any(|q| {
+                q.period_type == "Quarterly"
+                    && record.period_start.as_deref().is_some_and(|s| q.period_end.as_str() > s)
+                    && q.period_end <= record.period_end
+            });
+        if !in_range || covered_by_quarters || record.amount_per_share <= 0.0 {
+            continue;
+        }
+        let Ok(paid_on) = NaiveDate::parse_from_str(&record.period_end, "%Y-%m-%d") else {
+            continue;
+        };
+        if let Some((_, price)) = get_price_on_or_before(pool, stock_id, paid_on).await.map_err(|e| anyhow!(e))? {
+            dividends.push(ReinvestedDividend { amount_per_share: record.amount_per_share, price });
+        }
+    }
+
+    let price_growth = end_price / start_price;
+    let total_growth = price_growth * reinvested_shares(&dividends);
+    let days = (end_date - start_date).num_days();
+
+    Ok(TotalReturn {
+        symbol: symbol.to_string(),
+        start_date: start_key,
+        end_date: end_key,
+        start_price,
+        end_price,
+        price_return_pct: (price_growth - 1.0) * 100.0,
+        total_return_pct: (total_growth - 1.0) * 100.0,
+        annualized_price_return_pct: annualize(price_growth, days),
+        annualized_total_return_pct: annualize(total_growth, days),
+        dividends_reinvested: dividends.len() as i32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_total_return_exceeds_price_return_by_reinvested_dividends() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'DIV', 'Dividend Payer')")
+            .execute(&pool).await.unwrap();
+        for (day, close) in [("2022-12-30", 100.0), ("2023-03-31", 110.0), ("2023-06-30", 100.0), ("2023-12-29", 120.0)] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (1, ?, ?, ?, ?, ?)"
+            )
+            .bind(day).bind(close).bind(close).bind(close).bind(close)
+            .execute(&pool).await.unwrap();
+        }
+        // 2.20 on a 110 close and 1.00 on a 100 close; the 2022 quarter is before the start
+        for (start, end, amount) in [("2022-10-01", "2022-12-30", 5.0), ("2023-01-01", "2023-03-31", 2.2), ("2023-04-01", "2023-06-30", 1.0)] {
+            sqlx::query(
+                "INSERT INTO dividends (stock_id, period_type, period_start, period_end, amount_per_share, source)
+                 VALUES (1, 'Quarterly', ?, ?, ?, 'sec_declared')"
+            )
+            .bind(start).bind(end).bind(amount)
+            .execute(&pool).await.unwrap();
+        }
+
+        // Both requested dates are Sundays
+        let result = compute_total_return(&pool, "DIV", date("2023-01-01"), date("2023-12-31")).await.unwrap();
+        assert_eq!((result.start_date.as_str(), result.end_date.as_str()), ("2022-12-30", "2023-12-29"));
+        assert_eq!(result.dividends_reinvested, 2);
+        assert!((result.price_return_pct - 20.0).abs() < 1e-9);
+
+        // 1.02 × 1.01 shares worth 120 each
+        let reinvested_pct = (1.02 * 1.01 - 1.0) * 120.0;
+        assert!((result.total_return_pct - result.price_return_pct - reinvested_pct).abs() < 1e-9);
+        assert!((result.total_return_pct - 23.624).abs() < 1e-9);
+
+        // 364 days, so the annualized rate is a touch above the period's
+        let annualized = result.annualized_total_return_pct.unwrap();
+        assert!(annualized > result.total_return_pct && annualized < 23.8);
+
+        let err = compute_total_return(&pool, "DIV", date("2022-01-01"), date("2023-12-31")).await.unwrap_err();
+        assert!(err.to_string().contains("No close for DIV on or before 2022-01-01"));
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::tools::fx_rates::usd_value_sql;
+
+/// Years of the daily valuation series the current ratios are compared against
+pub const HISTORY_YEARS: i32 = 5;
+
+/// Positive daily values a metric needs in the window before its median means anything: about
+/// two years of trading days
+pub const MIN_HISTORY_POINTS: usize = 500;
+
+/// One ratio today against its own median over the history window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ValuationMetricVsHistory {
+    /// "P/E", "P/S" or "P/B"
+    pub metric: String,
+    /// Value on the latest valuation date; None when it isn't positive there
+    pub current: Option<f64>,
+    pub median_5y: Option<f64>,
+    /// Percent above the median, negative for a discount
+    pub premium_pct: Option<f64>,
+    /// Positive values in the window, the latest day included
+    pub data_points: i32,
+    /// Why there is no comparison, when there isn't
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ValuationVsHistory {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// Latest date in the daily valuation series; None when the stock has none
+    pub as_of_date: Option<String>,
+    pub pe: ValuationMetricVsHistory,
+    pub ps: ValuationMetricVsHistory,
+    pub pb: ValuationMetricVsHistory,
+}
+
+/// Median of values sorted ascending; None when there are none
+fn median(sorted: &[f64]) -> Option<f64> {
+    let n = sorted.len();
+    match n {
+        0 => None,
+        _ if n % 2 == 1 => Some(sorted[n / 2]),
+        _ => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0),
+    }
+}
+
+/// Compare the latest day's value with the median of every positive value in the window.
+/// `series` is oldest first and ends on the latest valuation date.
+fn compare_with_history(metric: &str, series: &[Option<f64>]) -> ValuationMetricVsHistory {
+    let current = series.last().copied().flatten().filter(|v| *v > 0.0);
+    let mut values: Vec<f64> = series.iter().flatten().copied().filter(|v| *v > 0.0).collect();
+    values.sort_by(f64::total_cmp);
+    let data_points = values.len() as i32;
+
+    let note = if values.len() < MIN_HISTORY_POINTS {
+        Some(format!(
+            "Only {} days with a positive {} in the last {} years; at least {} are needed",
+            values.len(), metric, HISTORY_YEARS, MIN_HISTORY_POINTS
+        ))
+    } else if current.is_none() {
+        Some(format!("No positive {} on the latest valuation date", metric))
+    } else {
+        None
+    };
+    let median_5y = if values.len() >= MIN_HISTORY_POINTS { median(&values) } else { None };
+    let premium_pct = current.zip(median_5y).map(|(current, median)| (current / median - 1.0) * 100.0);
+
+    ValuationMetricVsHistory { metric: metric.to_string(), current, median_5y, premium_pct, data_points, note }
+}
+
+/// Current P/E, P/S and P/B against their medians over the `HISTORY_YEARS` up to the latest
+/// valuation date. P/E and P/S come from the daily series; P/B divides each day's market cap by
+/// the equity on the latest annual balance sheet filed by then, both in USD.
+pub async fn compute_valuation_vs_history(pool: &SqlitePool, stock_id: i64, symbol: String) -> Result<ValuationVsHistory> {
+    let as_of_date: Option<String> = sqlx::query_scalar("SELECT MAX(date) FROM daily_valuation_ratios WHERE stock_id = ?")
+        .bind(stock_id)
+        .fetch_one(pool)
+        .await?;
+
+    let rows = match &as_of_date {
+        Some(as_of_date) => {
+            let query = format!(
+                "SELECT d.pe_ratio_ttm, d.ps_ratio_ttm,
+                        CASE WHEN b.total_equity > 0 THEN {} / {} END AS pb_ratio
+                 FROM daily_valuation_ratios d
+                 LEFT JOIN balance_sheets b ON b.id = (
+                     SELECT id FROM balance_sheets
+                     WHERE stock_id = d.stock_id AND period_type = 'Annual' AND report_date <= d.date
+                       AND total_equity IS NOT NULL
+                     ORDER BY report_date DESC LIMIT 1
+                 )
+                 WHERE d.stock_id = ? AND d.date > date(?, ?) AND d.date <= ?
+                 ORDER BY d.date",
+                usd_value_sql("d.market_cap", "d.currency", "d.date"),
+                usd_value_sql("b.total_equity", "b.currency", "d.date")
+            );
+            sqlx::query(&query)
+                .bind(stock_id)
+                .bind(as_of_date)
+                .bind(format!("-{} years", HISTORY_YEARS))
+                .bind(as_of_date)
+                .fetch_all(pool)
+                .await?
+        }
+        None => Vec::new(),
+    };
+
+    let column = |name: &str| rows.iter().map(|row| row.get::<Option<f64>, _>(name)).collect::<Vec<_>>();
+    Ok(ValuationVsHistory {
+        stock_id,
+        symbol,
+        as_of_date,
+        pe: compare_with_history("P/E", &column("pe_ratio_ttm")),
+        ps: compare_with_history("P/S", &column("ps_ratio_ttm")),
+        pb: compare_with_history("P/B", &column("pb_ratio")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{day, AnnualFinancials, FixtureBuilder, ValuationDay};
+
+    #[tokio::test]
+    async fn test_current_ratios_against_five_year_median() {
+        // P/E 20 and P/S 4 every day until the last, which closes at 25 and 3. 2019 is outside
+        // the window, so its P/E of 100 doesn't count. A $1,000 market cap on $200 of equity is
+        // a P/B of 5 until the 2024 balance sheet raises equity to $250 on the last day.
+        let last = day(2024, 12, 31);
+        let fixture = FixtureBuilder::new()
+            .with_stock("HIST")
+            .with_valuation_ratios(day(2019, 1, 1)..=last, |_, date| ValuationDay {
+                market_cap: Some(1000.0),
+                pe_ratio_ttm: Some(if date == last { 25.0 } else if date < day(2020, 1, 1) { 100.0 } else { 20.0 }),
+                ps_ratio_ttm: Some(if date == last { 3.0 } else { 4.0 }),
+                ..Default::default()
+            })
+            .with_annual_financials(2018..=2024, |year| AnnualFinancials {
+                total_equity: Some(if year == 2024 { 250.0 } else { 200.0 }),
+                ..Default::default()
+            })
+            .with_stock("NEW")
+            .with_valuation_ratios(day(2024, 1, 1)..=last, |_, _| ValuationDay {
+                market_cap: Some(1000.0),
+                pe_ratio_ttm: Some(15.0),
+                ..Default::default()
+            })
+            .build()
+            .await;
+
+        let history = compute_valuation_vs_history(&fixture.pool, 1, "HIST".to_string()).await.unwrap();
+        assert_eq!(history.as_of_date.as_deref(), Some("2024-12-31"));
+        // 2020-01-01 through 2024-12-31
+        assert_eq!(history.pe.data_points, 1827);
+        assert_eq!((history.pe.current, history.pe.median_5y), (Some(25.0), Some(20.0)));
+        assert!((history.pe.premium_pct.unwrap() - 25.0).abs() < 1e-9);
+        assert_eq!((history.ps.current, history.ps.median_5y), (Some(3.0), Some(4.0)));
+        assert!((history.ps.premium_pct.unwrap() + 25.0).abs() < 1e-9);
+        assert_eq!((history.pb.current, history.pb.median_5y), (Some(4.0), Some(5.0)));
+        assert!((history.pb.premium_pct.unwrap() + 20.0).abs() < 1e-9);
+        assert_eq!(history.pe.note, None);
+
+        // A year of history isn't enough for a median; the current value is still reported
+        let short = compute_valuation_vs_history(&fixture.pool, 2, "NEW".to_string()).await.unwrap();
+        assert_eq!((short.pe.current, short.pe.median_5y, short.pe.premium_pct), (Some(15.0), None, None));
+        assert_eq!(short.pe.data_points, 366);
+        assert!(short.pe.note.unwrap().contains("at least 500"));
+        assert_eq!((short.ps.current, short.ps.data_points), (None, 0));
+        assert!(short.ps.note.is_some());
+    }
+}
@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use tracing::{info, warn};
+use ts_rs::TS;
+
+use crate::tools::fx_rates::{usd_rate, usd_rate_sql};
+
+/// One stored row of the daily valuation series
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DailyValuationRatio {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub date: String,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub price: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub market_cap: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub enterprise_value: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub revenue_ttm: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub net_income_ttm: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub ps_ratio_ttm: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub pe_ratio_ttm: Option<f64>,
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub evs_ratio_ttm: Option<f64>,
+    /// From the latest annual cash flow statement
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub free_cash_flow: Option<f64>,
+    /// Free cash flow as a percent of market cap
+    #[serde(serialize_with = "crate::safe_f64::serialize_finite")]
+    pub fcf_yield: Option<f64>,
+    /// Market cap and the ratios on it use a share count estimated from a provider market cap
+    /// because the filing reported none
+    pub is_estimated: bool,
+    pub data_completeness_score: i32,
+    /// Report date of the most recent annual statement used for the row
+    pub last_financial_update: Option<String>,
+}
+
+/// Free cash flow of a cash flow statement `c`; computed from its parts for rows stored without it
+pub const STATEMENT_FREE_CASH_FLOW: &str = "COALESCE(c.free_cash_flow, c.operating_cash_flow - ABS(c.capital_expenditures))";
+
+/// Write daily P/S, P/E, EV/S and FCF yield for every trading day of a stock.
+///
+/// Each day uses the latest annual income statement, balance sheet and cash flow
+/// statement whose report_date is on or before that day, so the ratio steps when a newer
+/// fiscal year is crossed. Market cap is close × the share count as of that
+/// day from shares_outstanding_history (the annual balance sheet's when the
+/// history has none yet, flagging rows where that count was estimated), falling back to
+/// the provider market cap. Statements reported
+/// in another currency than the price are converted to the price's currency at
+/// that day's USD crosses; without a rate their figures and ratios are left
+/// empty rather than mixing currencies. Each row records the statement rows and share
+/// count source it used, for ratio provenance. Re-running replaces existing rows.
+/// Returns the number of rows written.
+pub async fn backfill_daily_ratios(pool: &SqlitePool, stock_id: i64) -> Result<i64, sqlx::Error> {
+    log_currency_conversions(pool, stock_id).await?;
+
+    let query = format!(
+        "WITH inputs AS (
+            SELECT
+                dp.stock_id,
+                dp.date,
+                dp.currency,
+                dp.close_price AS price,
+                dp.market_cap AS reported_market_cap,
+                (SELECT i.id FROM income_statements i
+                 WHERE i.stock_id = dp.stock_id AND i.period_type = 'FY'
+                   AND i.revenue IS NOT NULL AND i.report_date <= dp.date
+                 ORDER BY i.report_date DESC LIMIT 1) AS income_id,
+                (SELECT b.id FROM balance_sheets b
+                 WHERE b.stock_id = dp.stock_id AND b.period_type = 'Annual'
+                   AND b.shares_outstanding IS NOT NULL AND b.report_date <= dp.date
+                 ORDER BY b.report_date DESC LIMIT 1) AS balance_id,
+                (SELECT c.id FROM cash_flow_statements c
+                 WHERE c.stock_id = dp.stock_id AND c.period_type = 'Annual'
+                   AND {free_cash_flow} IS NOT NULL AND c.report_date <= dp.date
+                 ORDER BY c.report_date DESC LIMIT 1) AS cash_flow_id,
+                (SELECT h.as_of_date FROM shares_outstanding_history h
+                 WHERE h.stock_id = dp.stock_id AND h.as_of_date <= dp.date
+                 ORDER BY h.as_of_date DESC LIMIT 1) AS history_date
+            FROM daily_prices dp
+            WHERE dp.stock_id = ?
+        ),
+        joined AS (
+            SELECT
+                inputs.stock_id,
+                inputs.date,
+                inputs.currency,
+                inputs.price,
+                COALESCE(inputs.price * COALESCE(inputs.history_shares, b.shares_outstanding), inputs.reported_market_cap) AS market_cap,
+                COALESCE(inputs.history_shares, b.shares_outstanding) AS shares_outstanding,
+                CASE WHEN inputs.history_shares IS NULL AND b.shares_outstanding_estimated = 1 THEN 1 ELSE 0 END AS shares_estimated,
+                b.total_debt * {balance_fx} AS total_debt,
+                b.cash_and_equivalents * {balance_fx} AS cash_and_equivalents,
+                {balance_fx} AS balance_fx,
+                i.revenue * {income_fx} AS revenue,
+                i.net_income * {income_fx} AS net_income,
+                {free_cash_flow} * {cash_flow_fx} AS free_cash_flow,
+                i.report_date AS income_report_date,
+                b.report_date AS balance_report_date,
+                inputs.income_id,
+                inputs.balance_id,
+                inputs.cash_flow_id,
+                inputs.history_date,
+                CASE
+                    WHEN inputs.history_shares IS NOT NULL THEN 'shares_history'
+                    WHEN b.shares_outstanding IS NOT NULL THEN 'balance_sheet'
+                    WHEN inputs.reported_market_cap IS NOT NULL THEN 'provider'
+                END AS market_cap_source
+            FROM (
+                SELECT inputs.*, h.shares AS history_shares FROM inputs
+                LEFT JOIN shares_outstanding_history h ON h.stock_id = inputs.stock_id AND h.as_of_date = inputs.history_date
+            ) inputs
+            LEFT JOIN income_statements i ON i.id = inputs.income_id
+            LEFT JOIN balance_sheets b ON b.id = inputs.balance_id
+            LEFT JOIN cash_flow_statements c ON c.id = inputs.cash_flow_id
+        )
+        INSERT OR REPLACE INTO daily_valuation_ratios (
+            stock_id, date, price, market_cap, enterprise_value, shares_outstanding, shares_estimated,
+            revenue_ttm, net_income_ttm, ps_ratio_ttm, pe_ratio_ttm, evs_ratio_ttm,
+            free_cash_flow, fcf_yield, data_completeness_score, last_financial_update, currency,
+            income_statement_id, balance_sheet_id, cash_flow_statement_id, shares_as_of_date, market_cap_source
+        )
+        SELECT
+            stock_id,
+            date,
+            price,
+            market_cap,
+            CASE WHEN balance_fx IS NOT NULL THEN market_cap + COALESCE(total_debt, 0) - COALESCE(cash_and_equivalents, 0) END,
+            shares_outstanding,
+            shares_estimated,
+            revenue,
+            net_income,
+            CASE WHEN revenue > 0 THEN market_cap / revenue END,
+            CASE WHEN net_income > 0 THEN market_cap / net_income END,
+            CASE WHEN revenue > 0 AND balance_fx IS NOT NULL
+                THEN (market_cap + COALESCE(total_debt, 0) - COALESCE(cash_and_equivalents, 0)) / revenue END,
+            free_cash_flow,
+            CASE WHEN market_cap > 0 THEN free_cash_flow / market_cap * 100.0 END,
+            (CASE WHEN price IS NOT NULL THEN 25 ELSE 0 END)
+              + (CASE WHEN market_cap IS NOT NULL THEN 25 ELSE 0 END)
+              + (CASE WHEN revenue IS NOT NULL THEN 25 ELSE 0 END)
+              + (CASE WHEN net_income IS NOT NULL THEN 25 ELSE 0 END),
+            CASE
+                WHEN income_report_date IS NULL THEN balance_report_date
+                WHEN balance_report_date IS NULL OR income_report_date >= balance_report_date THEN income_report_date
+                ELSE balance_report_date
+            END,
+            currency,
+            income_id,
+            balance_id,
+            cash_flow_id,
+            CASE WHEN market_cap_source = 'shares_history' THEN history_date END,
+            market_cap_source
+        FROM joined",
+        income_fx = statement_fx_sql("i.currency"),
+        balance_fx = statement_fx_sql("b.currency"),
+        cash_flow_fx = statement_fx_sql("c.currency"),
+        free_cash_flow = STATEMENT_FREE_CASH_FLOW
+    );
+
+    let result = sqlx::query(&query)
+        .bind(stock_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() as i64)
+}
+
+/// SQL for price-currency units per unit of a statement's currency on the inputs row's day;
+/// NULL when either side has no known USD rate
+fn statement_fx_sql(statement_currency: &str) -> String {
+    format!(
+        "(CASE WHEN COALESCE({statement}, 'USD') = inputs.currency THEN 1.0
+            ELSE {price_rate} / {statement_rate} END)",
+        statement = statement_currency,
+        price_rate = usd_rate_sql("inputs.currency", "inputs.date"),
+        statement_rate = usd_rate_sql(statement_currency, "inputs.date")
+    )
+}
+
+/// Log each statement currency that is converted into the stock's price currency, and warn
+/// when a currency involved has no rate as of the latest price
+async fn log_currency_conversions(pool: &SqlitePool, stock_id: i64) -> Result<(), sqlx::Error> {
+    let pairs = sqlx::query(
+        "SELECT dp.currency AS price_currency, st.currency AS statement_currency, MAX(dp.date) AS last_date
+         FROM daily_prices dp
+         JOIN (
+             SELECT COALESCE(currency, 'USD') AS currency FROM income_statements WHERE stock_id = ?1 AND period_type = 'FY'
+             UNION
+             SELECT COALESCE(currency, 'USD') FROM balance_sheets WHERE stock_id = ?1 AND period_type = 'Annual'
+             UNION
+             SELECT currency FROM cash_flow_statements WHERE stock_id = ?1 AND period_type = 'Annual'
+         ) st ON st.currency <> dp.currency
+         WHERE dp.stock_id = ?1
+         GROUP BY dp.currency, st.currency"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    for row in pairs {
+        let price_currency: String = row.get("price_currency");
+        let statement_currency: String = row.get("statement_currency");
+        let last_date: String = row.get("last_date");
+
+        let mut missing = Vec::new();
+        for currency in [&price_currency, &statement_currency] {
+            if usd_rate(pool, currency, &last_date).await?.is_none() {
+                missing.push(currency.as_str());
+            }
+        }
+        if missing.is_empty() {
+            info!(stock_id, "Converting {} statements to {} for ratios", statement_currency, price_currency);
+        } else {
+            warn!(
+                stock_id,
+                "No exchange rate for {}; ratios mixing {} statements with {} prices are left empty",
+                missing.join(", "),
+                statement_currency,
+                price_currency
+            );
+        }
+    }
+    Ok(())
+}
+
+pub async fn load_daily_valuation_ratios(
+    pool: &SqlitePool,
+    symbol: &str,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<DailyValuationRatio>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT dvr.stock_id, s.symbol, dvr.date, dvr.price, dvr.market_cap, dvr.enterprise_value,
+                dvr.revenue_ttm, dvr.net_income_ttm, dvr.ps_ratio_ttm, dvr.pe_ratio_ttm, dvr.evs_ratio_ttm,
+                dvr.free_cash_flow, dvr.fcf_yield, dvr.shares_estimated, dvr.data_completeness_score, dvr.last_financial_update
+         FROM daily_valuation_ratios dvr
+         JOIN stocks s ON s.id = dvr.stock_id
+         WHERE s.symbol = ?1 AND dvr.date BETWEEN ?2 AND ?3
+         ORDER BY dvr.date ASC"
+    )
+    .bind(symbol)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| DailyValuationRatio {
+            stock_id: row.get("stock_id"),
+            symbol: row.get("symbol"),
+            date: row.get("date"),
+            price: row.try_get::<Option<f64>, _>("price").ok().flatten(),
+            market_cap: row.try_get::<Option<f64>, _>("market_cap").ok().flatten(),
+            enterprise_value: row.try_get::<Option<f64>, _>("enterprise_value").ok().flatten(),
+            revenue_ttm: row.try_get::<Option<f64>, _>("revenue_ttm").ok().flatten(),
+            net_income_ttm: row.try_get::<Option<f64>, _>("net_income_ttm").ok().flatten(),
+            ps_ratio_ttm: row.try_get::<Option<f64>, _>("ps_ratio_ttm").ok().flatten(),
+            pe_ratio_ttm: row.try_get::<Option<f64>, _>("pe_ratio_ttm").ok().flatten(),
+            evs_ratio_ttm: row.try_get::<Option<f64>, _>("evs_ratio_ttm").ok().flatten(),
+            free_cash_flow: row.try_get::<Option<f64>, _>("free_cash_flow").ok().flatten(),
+            fcf_yield: row.try_get::<Option<f64>, _>("fcf_yield").ok().flatten(),
+            is_estimated: row.get::<bool, _>("shares_estimated"),
+            data_completeness_score: row.get::<i64, _>("data_completeness_score") as i32,
+            last_financial_update: row.try_get::<Option<String>, _>("last_financial_update").ok().flatten(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_ratio_steps_when_newer_annual_report_date_is_crossed() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'AAPL', 'Apple Inc.')")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income) VALUES
+                (1, 'FY', '2022-09-24', 2022, 400.0, 100.0),
+                (1, 'FY', '2023-09-30', 2023, 500.0, 80.0)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, shares_outstanding, total_debt, cash_and_equivalents) VALUES
+                (1, 'Annual', '2022-09-24', 2022, 10.0, 50.0, 20.0),
+                (1, 'Annual', '2023-09-30', 2023, 10.0, 50.0, 20.0)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price) VALUES
+                (1, '2023-09-29', 100.0, 100.0, 100.0, 100.0),
+                (1, '2023-10-02', 100.0, 100.0, 100.0, 100.0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        assert_eq!(backfill_daily_ratios(&pool, 1).await.unwrap(), 2);
+
+        let ratios = load_daily_valuation_ratios(&pool, "AAPL", "2023-01-01", "2023-12-31").await.unwrap();
+        assert_eq!(ratios.len(), 2);
+
+        // Same price, but FY2023 revenue applies once its report date has passed
+        assert_eq!(ratios[0].ps_ratio_ttm, Some(1000.0 / 400.0));
+        assert_eq!(ratios[0].pe_ratio_ttm, Some(1000.0 / 100.0));
+        assert_eq!(ratios[0].last_financial_update.as_deref(), Some("2022-09-24"));
+        assert_eq!(ratios[1].ps_ratio_ttm, Some(1000.0 / 500.0));
+        assert_eq!(ratios[1].pe_ratio_ttm, Some(1000.0 / 80.0));
+        assert_eq!(ratios[1].evs_ratio_ttm, Some(1030.0 / 500.0));
+        assert_eq!(ratios[1].last_financial_update.as_deref(), Some("2023-09-30"));
+        assert_eq!(ratios[1].data_completeness_score, 100);
+
+        // Re-running replaces rows instead of duplicating them
+        backfill_daily_ratios(&pool, 1).await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM daily_valuation_ratios WHERE stock_id = 1")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_market_cap_uses_share_count_as_of_each_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'AAPL', 'Apple Inc.')")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income)
+             VALUES (1, 'FY', '2023-09-30', 2023, 500.0, 50.0)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, shares_outstanding)
+             VALUES (1, 'Annual', '2023-09-30', 2023, 10.0)"
+        )
+        .execute(&pool).await.unwrap();
+        // A quarterly count after the annual report: buybacks took shares to 8
+        sqlx::query(
+            "INSERT INTO shares_outstanding_history (stock_id, as_of_date, shares, concept) VALUES
+                (1, '2023-09-30', 10.0, 'CommonStockSharesOutstanding'),
+                (1, '2023-12-30', 8.0, 'EntityCommonStockSharesOutstanding')"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price) VALUES
+                (1, '2023-12-29', 100.0, 100.0, 100.0, 100.0),
+                (1, '2024-01-02', 100.0, 100.0, 100.0, 100.0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        backfill_daily_ratios(&pool, 1).await.unwrap();
+        let ratios = load_daily_valuation_ratios(&pool, "AAPL", "2023-01-01", "2024-12-31").await.unwrap();
+        assert_eq!((ratios[0].market_cap, ratios[0].ps_ratio_ttm), (Some(1000.0), Some(2.0)));
+        assert_eq!((ratios[1].market_cap, ratios[1].ps_ratio_ttm), (Some(800.0), Some(1.6)));
+    }
+
+    #[tokio::test]
+    async fn test_eur_statements_are_converted_to_the_price_currency() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // A EUR-reporting company whose shares trade in USD
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'SAP', 'SAP SE')")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income, currency)
+             VALUES (1, 'FY', '2023-12-31', 2023, 800.0, 80.0, 'EUR')"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, shares_outstanding, total_debt, cash_and_equivalents, currency)
+             VALUES (1, 'Annual', '2023-12-31', 2023, 10.0, 40.0, 8.0, 'EUR')"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price) VALUES
+                (1, '2024-01-02', 100.0, 100.0, 100.0, 100.0),
+                (1, '2024-01-03', 100.0, 100.0, 100.0, 100.0)"
+        )
+        .execute(&pool).await.unwrap();
+        // The first rate is published on the second day
+        sqlx::query("INSERT INTO fx_rates (currency, date, rate) VALUES ('EUR', '2024-01-03', 0.8)")
+            .execute(&pool).await.unwrap();
+
+        backfill_daily_ratios(&pool, 1).await.unwrap();
+        let ratios = load_daily_valuation_ratios(&pool, "SAP", "2024-01-01", "2024-12-31").await.unwrap();
+
+        // No rate yet: EUR figures aren't divided into a USD market cap
+        assert_eq!((ratios[0].revenue_ttm, ratios[0].ps_ratio_ttm, ratios[0].evs_ratio_ttm), (None, None, None));
+        assert_eq!(ratios[0].market_cap, Some(1000.0));
+
+        // EUR 800 revenue at 0.8 EUR per USD is $1000; debt and cash likewise
+        assert_eq!(ratios[1].revenue_ttm, Some(1000.0));
+        assert_eq!(ratios[1].ps_ratio_ttm, Some(1.0));
+        assert_eq!(ratios[1].pe_ratio_ttm, Some(10.0));
+        assert_eq!(ratios[1].enterprise_value, Some(1040.0));
+
+        let currency: String = sqlx::query_scalar("SELECT currency FROM daily_valuation_ratios WHERE stock_id = 1 AND date = '2024-01-03'")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(currency, "USD");
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::commands::graham_screening::run_graham_screening_for;
+use crate::tools::fx_rates::usd_value_sql;
+use crate::tools::universe_filter::market_cap_sql;
+
+/// One watchlist row: price and valuation as of the latest stored day. Every field but
+/// `symbol` is None for a symbol that isn't stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WatchlistSnapshotRow {
+    pub symbol: String,
+    pub stock_id: Option<i64>,
+    pub company_name: Option<String>,
+    pub price_date: Option<String>,
+    pub close_price: Option<f64>,
+    /// Change from the previous stored close
+    pub day_change: Option<f64>,
+    pub day_change_pct: Option<f64>,
+    /// Latest close × shares outstanding in USD, as the screens compute it
+    pub market_cap: Option<f64>,
+    /// Market cap over the latest fiscal year's net income; None for a loss year
+    pub pe_ratio: Option<f64>,
+    /// Market cap over the latest fiscal year's revenue
+    pub ps_ratio: Option<f64>,
+    /// Passes the Graham screen under its default criteria; None without a market cap
+    pub passes_graham: Option<bool>,
+}
+
+struct LatestCloses {
+    date: String,
+    close: f64,
+    previous_close: Option<f64>,
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(",")
+}
+
+/// Snapshot rows for `symbols`, in the order given, from one query per data set rather than
+/// one per symbol
+pub async fn load_watchlist_snapshot(pool: &SqlitePool, symbols: &[String]) -> Result<Vec<WatchlistSnapshotRow>> {
+    if symbols.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query = format!("SELECT id, symbol, company_name FROM stocks WHERE symbol IN ({})", placeholders(symbols.len()));
+    let mut sqlx_query = sqlx::query_as::<_, (i64, String, String)>(&query);
+    for symbol in symbols {
+        sqlx_query = sqlx_query.bind(symbol);
+    }
+    let stocks: HashMap<String, (i64, String)> = sqlx_query
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|(id, symbol, company_name)| (symbol, (id, company_name)))
+        .collect();
+    let stock_ids: Vec<i64> = stocks.values().map(|(id, _)| *id).collect();
+    if stock_ids.is_empty() {
+        return Ok(symbols.iter().map(|symbol| missing_row(symbol)).collect());
+    }
+
+    let query = format!(
+        "SELECT stock_id, date, close_price FROM (
+            SELECT stock_id, date, close_price,
+                   ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY date DESC) AS rn
+            FROM daily_prices
+            WHERE stock_id IN ({})
+        )
+        WHERE rn <= 2
+        ORDER BY stock_id, date DESC",
+        placeholders(stock_ids.len())
+    );
+    let mut sqlx_query = sqlx::query(&query);
+    for stock_id in &stock_ids {
+        sqlx_query = sqlx_query.bind(stock_id);
+    }
+    let mut closes: HashMap<i64, LatestCloses> = HashMap::new();
+    for row in sqlx_query.fetch_all(pool).await? {
+        let close: f64 = row.get("close_price");
+        closes
+            .entry(row.get("stock_id"))
+            .and_modify(|latest| latest.previous_close = Some(close))
+            .or_insert_with(|| LatestCloses { date: row.get("date"), close, previous_close: None });
+    }
+
+    let latest_fy = |value: &str| {
+        format!(
+            "(SELECT {} FROM income_statements i
+              WHERE i.stock_id = s.id AND i.period_type = 'FY' AND i.fiscal_year IS NOT NULL
+              ORDER BY i.fiscal_year DESC, i.report_date DESC LIMIT 1)",
+            usd_value_sql(value, "i.currency", "i.report_date")
+        )
+    };
+    let query = format!(
+        "SELECT stock_id, market_cap,
+                CASE WHEN net_income > 0 THEN market_cap / net_income END AS pe_ratio,
+                CASE WHEN revenue > 0 THEN market_cap / revenue END AS ps_ratio
+         FROM (
+             SELECT s.id AS stock_id, {} AS market_cap, {} AS net_income, {} AS revenue
+             FROM stocks s
+             WHERE s.id IN ({})
+         )",
+        market_cap_sql("s.id"),
+        latest_fy("i.net_income"),
+        latest_fy("i.revenue"),
+        placeholders(stock_ids.len())
+    );
+    let mut sqlx_query = sqlx::query_as::<_, (i64, Option<f64>, Option<f64>, Option<f64>)>(&query);
+    for stock_id in &stock_ids {
+        sqlx_query = sqlx_query.bind(stock_id);
+    }
+    let ratios: HashMap<i64, (Option<f64>, Option<f64>, Option<f64>)> = sqlx_query
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|(stock_id, market_cap, pe, ps)| (stock_id, (market_cap, pe, ps)))
+        .collect();
+
+    // One screen over the requested stocks; Graham can only judge stocks with a market cap
+    let graham_passes: HashSet<i64> = run_graham_screening_for(pool, None, None, Some(&stock_ids))
+        .await?
+        .results
+        .iter()
+        .map(|result| result.stock_id)
+        .collect();
+
+    Ok(symbols
+        .iter()
+        .map(|symbol| {
+            let Some((stock_id, company_name)) = stocks.get(symbol) else {
+                return missing_row(symbol);
+            };
+            let latest = closes.get(stock_id);
+            let day_change = latest.and_then(|l| l.previous_close.map(|previous| l.close - previous));
+            let day_change_pct = latest.and_then(|l| {
+                l.previous_close.filter(|previous| *previous != 0.0).map(|previous| (l.close / previous - 1.0) * 100.0)
+            });
+            let (market_cap, pe_ratio, ps_ratio) = ratios.get(stock_id).copied().unwrap_or_default();
+
+            WatchlistSnapshotRow {
+                symbol: symbol.clone(),
+                stock_id: Some(*stock_id),
+                company_name: Some(company_name.clone()),
+                price_date: latest.map(|l| l.date.clone()),
+                close_price: latest.map(|l| l.close),
+                day_change,
+                day_change_pct,
+                market_cap,
+                pe_ratio,
+                ps_ratio,
+                passes_graham: market_cap.is_some().then(|| graham_passes.contains(stock_id)),
+            }
+        })
+        .collect())
+}
+
+fn missing_row(symbol: &str) -> WatchlistSnapshotRow {
+    WatchlistSnapshotRow {
+        symbol: symbol.to_string(),
+        stock_id: None,
+        company_name: None,
+        price_date: None,
+        close_price: None,
+        day_change: None,
+        day_change_pct: None,
+        market_cap: None,
+        pe_ratio: None,
+        ps_ratio: None,
+        passes_graham: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{day, AnnualFinancials, FixtureBuilder, PriceBar};
+
+    #[tokio::test]
+    async fn test_snapshot_assembles_rows_in_request_order() {
+        // VALUE passes Graham: 2e7 shares at 51 is a P/E of 10.2, P/B of 1.02, current ratio 3
+        // and five profitable years. PRICEY has the same books at a P/E of 30. No ratio rows are
+        // stored; everything is derived from prices and statements.
+        let books = |shares: f64, revenue: Option<f64>| {
+            move |_: i32| AnnualFinancials {
+                revenue,
+                net_income: Some(1e8),
+                total_equity: Some(1e9),
+                current_assets: Some(3e8),
+                current_liabilities: Some(1e8),
+                shares_outstanding: Some(shares),
+                ..Default::default()
+            }
+        };
+        let fixture = FixtureBuilder::new()
+            .with_stock("VALUE")
+            .with_prices(day(2024, 3, 4)..=day(2024, 3, 5), |i, _| PriceBar::flat(if i == 0 { 50.0 } else { 51.0 }))
+            .with_annual_financials(2019..=2023, books(2e7, Some(6.8e8)))
+            .with_stock("PRICEY")
+            .with_prices(day(2024, 3, 5)..=day(2024, 3, 5), |_, _| PriceBar::flat(300.0))
+            .with_annual_financials(2019..=2023, books(1e7, None))
+            .build()
+            .await;
+
+        let symbols: Vec<String> = ["PRICEY", "MISSING", "VALUE"].into_iter().map(String::from).collect();
+        let rows = load_watchlist_snapshot(&fixture.pool, &symbols).await.unwrap();
+        assert_eq!(rows.iter().map(|r| r.symbol.as_str()).collect::<Vec<_>>(), vec!["PRICEY", "MISSING", "VALUE"]);
+
+        let value = &rows[2];
+        assert_eq!((value.stock_id, value.price_date.as_deref(), value.close_price), (Some(1), Some("2024-03-05"), Some(51.0)));
+        assert_eq!(value.day_change, Some(1.0));
+        assert!((value.day_change_pct.unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!((value.market_cap, value.pe_ratio, value.ps_ratio), (Some(1.02e9), Some(10.2), Some(1.5)));
+        assert_eq!(value.passes_graham, Some(true));
+
+        // A single stored close has no day change
+        let pricey = &rows[0];
+        assert_eq!((pricey.close_price, pricey.day_change, pricey.day_change_pct), (Some(300.0), None, None));
+        assert_eq!((pricey.pe_ratio, pricey.ps_ratio, pricey.passes_graham), (Some(30.0), None, Some(false)));
+
+        assert_eq!(rows[1], missing_row("MISSING"));
+    }
+}
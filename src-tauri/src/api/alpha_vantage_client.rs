@@ -0,0 +1,194 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::error::CommandError;
+use crate::models::{FundamentalData, PriceFrequency, SchwabPriceBar, SchwabQuote};
+use super::StockDataProvider;
+
+pub const ALPHA_VANTAGE_BASE_URL: &str = "https://www.alphavantage.co";
+
+/// Environment variable holding the Alpha Vantage API key
+pub const ALPHA_VANTAGE_API_KEY_ENV: &str = "ALPHA_VANTAGE_API_KEY";
+
+/// Alpha Vantage client, used to cross-check SEC fundamentals against a second source
+pub struct AlphaVantageClient {
+    client: Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl AlphaVantageClient {
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .user_agent("rust-stocks/1.0")
+            .build()?;
+        Ok(Self { client, api_key: api_key.into(), base_url: ALPHA_VANTAGE_BASE_URL.to_string() })
+    }
+
+    /// Client with the key from `ALPHA_VANTAGE_API_KEY`
+    pub fn from_env() -> Result<Self> {
+        dotenvy::dotenv().ok();
+        let api_key = std::env::var(ALPHA_VANTAGE_API_KEY_ENV)
+            .ok()
+            .filter(|key| !key.trim().is_empty())
+            .ok_or_else(|| CommandError::validation(ALPHA_VANTAGE_API_KEY_ENV, "Set an Alpha Vantage API key to use it as a provider"))?;
+        Self::new(api_key)
+    }
+
+    /// Send requests to a different host (used by tests with a mock server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    async fn query(&self, function: &str, symbol: &str) -> Result<Value> {
+        let url = format!("{}/query", self.base_url.trim_end_matches('/'));
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("function", function), ("symbol", symbol), ("apikey", self.api_key.as_str())])
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(CommandError::external_api("alpha_vantage", Some(status.as_u16()), body).into());
+        }
+
+        // Errors and rate limiting come back as 200s with a single message field
+        let json: Value = response.json().await?;
+        for key in ["Error Message", "Note", "Information"] {
+            if let Some(message) = json.get(key).and_then(Value::as_str) {
+                return Err(CommandError::external_api("alpha_vantage", None, message).into());
+            }
+        }
+        Ok(json)
+    }
+}
+
+/// Alpha Vantage sends numbers as strings, with "None" or "-" when it has no value
+fn number(overview: &Value, key: &str) -> Option<f64> {
+    match overview.get(key)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+/// Map an OVERVIEW response into `FundamentalData`. Yields and margins come as fractions and
+/// are converted to percent, as Schwab reports them.
+pub fn parse_overview(symbol: &str, overview: &Value) -> Result<FundamentalData> {
+    if overview.get("Symbol").is_none() {
+        return Err(CommandError::not_found("fundamentals", format!("Alpha Vantage has no overview for {}", symbol)).into());
+    }
+    let percent = |key: &str| number(overview, key).map(|fraction| fraction * 100.0);
+
+    Ok(FundamentalData {
+        symbol: symbol.to_string(),
+        pe_ratio: number(overview, "PERatio"),
+        pe_ratio_forward: number(overview, "ForwardPE"),
+        market_cap: number(overview, "MarketCapitalization"),
+        dividend_yield: percent("DividendYield"),
+        dividend_per_share: number(overview, "DividendPerShare"),
+        eps: number(overview, "EPS"),
+        eps_forward: None,
+        beta: number(overview, "Beta"),
+        week_52_high: number(overview, "52WeekHigh"),
+        week_52_low: number(overview, "52WeekLow"),
+        pb_ratio: number(overview, "PriceToBookRatio"),
+        ps_ratio: number(overview, "PriceToSalesRatioTTM"),
+        shares_outstanding: number(overview, "SharesOutstanding"),
+        float_shares: number(overview, "SharesFloat"),
+        revenue_ttm: number(overview, "RevenueTTM"),
+        profit_margin: percent("ProfitMargin"),
+        operating_margin: percent("OperatingMarginTTM"),
+        return_on_equity: percent("ReturnOnEquityTTM"),
+        return_on_assets: percent("ReturnOnAssetsTTM"),
+        debt_to_equity: None,
+    })
+}
+
+#[async_trait::async_trait]
+impl StockDataProvider for AlphaVantageClient {
+    /// Quotes come from Schwab; this client only supplies fundamentals
+    async fn get_quotes(&self, _symbols: &[String]) -> Result<Vec<SchwabQuote>> {
+        Err(anyhow!("Alpha Vantage quotes are not supported; use Schwab"))
+    }
+
+    async fn get_price_history(
+        &self,
+        symbol: &str,
+        _from_date: NaiveDate,
+        _to_date: NaiveDate,
+        _frequency: PriceFrequency,
+    ) -> Result<Vec<SchwabPriceBar>> {
+        Err(anyhow!("Alpha Vantage price history is not supported; use Schwab for {}", symbol))
+    }
+
+    async fn get_fundamentals(&self, symbol: &str) -> Result<FundamentalData> {
+        let overview = self.query("OVERVIEW", symbol).await?;
+        parse_overview(symbol, &overview)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_overview_maps_into_fundamental_data() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/query"))
+            .and(query_param("function", "OVERVIEW"))
+            .and(query_param("symbol", "IBM"))
+            .and(query_param("apikey", "demo"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "Symbol": "IBM",
+                "MarketCapitalization": "170000000000",
+                "PERatio": "22.5",
+                "ForwardPE": "17.1",
+                "PriceToBookRatio": "7.4",
+                "PriceToSalesRatioTTM": "2.7",
+                "EPS": "8.14",
+                "Beta": "0.71",
+                "DividendPerShare": "6.66",
+                "DividendYield": "0.0363",
+                "ProfitMargin": "0.12",
+                "OperatingMarginTTM": "0.15",
+                "ReturnOnEquityTTM": "0.36",
+                "ReturnOnAssetsTTM": "None",
+                "RevenueTTM": "62000000000",
+                "SharesOutstanding": "920000000",
+                "52WeekHigh": "199.18",
+                "52WeekLow": "135.87"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/query"))
+            .and(query_param("symbol", "NOPE"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&server)
+            .await;
+
+        let client = AlphaVantageClient::new("demo").unwrap().with_base_url(server.uri());
+        let data = client.get_fundamentals("IBM").await.unwrap();
+        assert_eq!(data.symbol, "IBM");
+        assert_eq!(data.market_cap, Some(170_000_000_000.0));
+        assert_eq!((data.pe_ratio, data.pe_ratio_forward), (Some(22.5), Some(17.1)));
+        assert_eq!((data.week_52_high, data.week_52_low), (Some(199.18), Some(135.87)));
+        assert_eq!(data.shares_outstanding, Some(920_000_000.0));
+        assert!((data.dividend_yield.unwrap() - 3.63).abs() < 1e-9);
+        assert!((data.profit_margin.unwrap() - 12.0).abs() < 1e-9);
+        assert_eq!(data.return_on_assets, None);
+
+        let err = client.get_fundamentals("NOPE").await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<CommandError>(), Some(CommandError::NotFound { .. })));
+    }
+}
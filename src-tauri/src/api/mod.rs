@@ -1,11 +1,13 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::NaiveDate;
 use std::time::Duration;
 
-use crate::models::{SchwabQuote, SchwabPriceBar};
+use crate::models::{FundamentalData, IntradayInterval, PriceFrequency, SchwabOptionContract, SchwabQuote, SchwabPriceBar};
 
 pub mod schwab_client;
+pub mod alpha_vantage_client;
 pub use schwab_client::SchwabClient;
+pub use alpha_vantage_client::AlphaVantageClient;
 
 /// Simple rate limiter for API requests
 pub struct ApiRateLimiter {
@@ -33,11 +35,40 @@ impl ApiRateLimiter {
 pub trait StockDataProvider {
     #[allow(dead_code)]
     async fn get_quotes(&self, symbols: &[String]) -> Result<Vec<SchwabQuote>>;
+    /// Bars of the given frequency between the dates; callers that only need closes pass
+    /// `PriceFrequency::Daily`, the default
     async fn get_price_history(
         &self,
         symbol: &str,
         from_date: NaiveDate,
         to_date: NaiveDate,
+        frequency: PriceFrequency,
     ) -> Result<Vec<SchwabPriceBar>>;
+
+    /// Regular-session bars of `interval` for one trading day. Their `frequency` is left at the
+    /// default; the interval is the one asked for.
+    async fn get_intraday_history(
+        &self,
+        symbol: &str,
+        date: NaiveDate,
+        interval: IntradayInterval,
+    ) -> Result<Vec<SchwabPriceBar>> {
+        Err(anyhow!("This provider doesn't supply {} bars (requested {} on {})", interval.as_str(), symbol, date))
+    }
+
+    /// Calls and puts expiring between the dates, inclusive
+    async fn get_option_chain(
+        &self,
+        symbol: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+    ) -> Result<Vec<SchwabOptionContract>> {
+        Err(anyhow!("This provider doesn't supply option chains (requested {} from {} to {})", symbol, from_date, to_date))
+    }
+
+    /// Company fundamentals, for cross-checking the SEC-derived figures; not every provider has them
+    async fn get_fundamentals(&self, symbol: &str) -> Result<FundamentalData> {
+        Err(anyhow!("This provider doesn't supply fundamentals (requested {})", symbol))
+    }
 }
 
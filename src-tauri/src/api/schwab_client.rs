@@ -9,7 +9,8 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::{info, warn, debug};
 
-use crate::models::{Config, SchwabQuote, SchwabPriceBar, FundamentalData};
+use crate::error::CommandError;
+use crate::models::{Config, SchwabQuote, SchwabPriceBar, SchwabOptionContract, FundamentalData, IntradayInterval, PriceFrequency};
 use super::{ApiRateLimiter, StockDataProvider};
 
 /// Schwab OAuth token response
@@ -68,6 +69,7 @@ pub struct SchwabClient {
     #[allow(dead_code)]
     callback_url: String,
     token_path: String,
+    base_url: String,
     rate_limiter: ApiRateLimiter,
     current_tokens: Arc<Mutex<Option<StoredTokens>>>,
 }
@@ -88,6 +90,7 @@ impl SchwabClient {
             app_secret: config.schwab_app_secret.clone(),
             callback_url: config.schwab_callback_url.clone(),
             token_path: config.schwab_token_path.clone(),
+            base_url: "https://api.schwabapi.com".to_string(),
             rate_limiter,
             current_tokens: Arc::new(Mutex::new(None)),
         };
@@ -95,6 +98,12 @@ impl SchwabClient {
         Ok(schwab_client)
     }
 
+    /// Point market data and OAuth requests at a different host (used by tests with a mock server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
     /// Load tokens from file
     async fn load_tokens(&self) -> Result<()> {
         #[cfg(feature = "debug-logging")]
@@ -268,7 +277,27 @@ impl SchwabClient {
 
         #[cfg(feature = "debug-logging")]
         debug!("DEBUG: Returning error - no valid access token");
-        Err(anyhow!("No valid access token available. Please run initial authentication."))
+        Err(CommandError::token_expired("schwab", "No valid access token available. Please run initial authentication.").into())
+    }
+
+    /// Refresh with the stored refresh token after the API rejected the access token
+    async fn force_refresh(&self) -> Result<String> {
+        let refresh_token = match &*self.current_tokens.lock().await {
+            Some(tokens) => tokens.refresh_token.clone(),
+            None => return Err(CommandError::token_expired("schwab", "No refresh token available. Please re-authenticate with Schwab.").into()),
+        };
+
+        match self.refresh_access_token(&refresh_token).await {
+            Ok(new_tokens) => {
+                *self.current_tokens.lock().await = Some(new_tokens.clone());
+                self.save_tokens(&new_tokens)?;
+                Ok(new_tokens.access_token)
+            }
+            Err(e) => {
+                warn!("Failed to refresh rejected token: {}", e);
+                Err(CommandError::token_expired("schwab", "Schwab session expired and could not be refreshed. Please re-authenticate.").into())
+            }
+        }
     }
 
     /// Refresh access token using refresh token
@@ -287,7 +316,7 @@ impl SchwabClient {
         self.rate_limiter.wait().await;
         
         let response = self.client
-            .post("https://api.schwabapi.com/v1/oauth/token")
+            .post(format!("{}/v1/oauth/token", self.base_url))
             .headers(headers)
             .form(&params)
             .send()
@@ -309,10 +338,8 @@ impl SchwabClient {
         })
     }
 
-    /// Make authenticated request to Schwab API
-    async fn make_request(&self, url: &str) -> Result<Value> {
-        let access_token = self.get_access_token().await?;
-        
+    /// Send a GET with the given bearer token
+    async fn send_authenticated(&self, url: &str, access_token: &str) -> Result<reqwest::Response> {
         let mut headers = HeaderMap::new();
         headers.insert("Authorization", HeaderValue::from_str(&format!("Bearer {}", access_token))?);
         headers.insert("Accept", HeaderValue::from_str("application/json")?);
@@ -321,11 +348,28 @@ impl SchwabClient {
 
         debug!("Making request to: {}", url);
         
-        let response = self.client
+        Ok(self.client
             .get(url)
             .headers(headers)
             .send()
-            .await?;
+            .await?)
+    }
+
+    /// Make authenticated request to Schwab API
+    async fn make_request(&self, url: &str) -> Result<Value> {
+        let access_token = self.get_access_token().await?;
+        let mut response = self.send_authenticated(url, &access_token).await?;
+
+        // Token revoked or expired server-side: refresh once and retry
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            warn!("Schwab rejected the access token, refreshing and retrying once");
+            let access_token = self.force_refresh().await?;
+            response = self.send_authenticated(url, &access_token).await?;
+
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                return Err(CommandError::token_expired("schwab", "Schwab rejected the refreshed token. Please re-authenticate.").into());
+            }
+        }
 
         if !response.status().is_success() {
             let status = response.status();
@@ -342,7 +386,7 @@ impl SchwabClient {
 
     /// Get comprehensive fundamental data for a symbol
     pub async fn get_fundamentals(&self, symbol: &str) -> Result<FundamentalData> {
-        let url = format!("https://api.schwabapi.com/marketdata/v1/instruments?symbol={}&projection=fundamental", symbol);
+        let url = format!("{}/marketdata/v1/instruments?symbol={}&projection=fundamental", self.base_url, symbol);
         let data = self.make_request(&url).await?;
         
         let mut fundamental_data = FundamentalData {
@@ -424,21 +468,21 @@ impl SchwabClient {
     /// Get instrument data by symbol
     #[allow(dead_code)]
     pub async fn get_instrument(&self, symbol: &str) -> Result<Value> {
-        let url = format!("https://api.schwabapi.com/marketdata/v1/instruments?symbol={}&projection=symbol-search", symbol);
+        let url = format!("{}/marketdata/v1/instruments?symbol={}&projection=symbol-search", self.base_url, symbol);
         self.make_request(&url).await
     }
 
     /// Get current market hours
     #[allow(dead_code)]
     pub async fn get_market_hours(&self, market: &str) -> Result<Value> {
-        let url = format!("https://api.schwabapi.com/marketdata/v1/markets/{}", market);
+        let url = format!("{}/marketdata/v1/markets/{}", self.base_url, market);
         self.make_request(&url).await
     }
     
     /// Get market hours for a specific date
     #[allow(dead_code)]
     pub async fn get_market_hours_for_date(&self, market: &str, date: &str) -> Result<Value> {
-        let url = format!("https://api.schwabapi.com/marketdata/v1/markets?markets={}&date={}", market, date);
+        let url = format!("{}/marketdata/v1/markets?markets={}&date={}", self.base_url, market, date);
         self.make_request(&url).await
     }
     
@@ -450,8 +494,8 @@ impl SchwabClient {
 
         let symbols_str = symbols.join(",");
         let url = format!(
-            "https://api.schwabapi.com/marketdata/v1/quotes?symbols={}&fields=quote,fundamental", 
-            symbols_str
+            "{}/marketdata/v1/quotes?symbols={}&fields=quote,fundamental", 
+            self.base_url, symbols_str
         );
         
         let data = self.make_request(&url).await?;
@@ -510,6 +554,94 @@ impl SchwabClient {
     }
 }
 
+/// Bars from a pricehistory response's candles, tagged with `frequency`
+fn parse_candles(data: &Value, frequency: PriceFrequency) -> Vec<SchwabPriceBar> {
+    let mut price_bars = Vec::new();
+
+    if let Some(candles) = data.get("candles").and_then(|v| v.as_array()) {
+        for candle in candles {
+            if let Some(candle_obj) = candle.as_object() {
+                let price_bar = SchwabPriceBar {
+                    datetime: candle_obj.get("datetime")
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(0),
+                    open: candle_obj.get("open")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0),
+                    high: candle_obj.get("high")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0),
+                    low: candle_obj.get("low")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0),
+                    close: candle_obj.get("close")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0),
+                    volume: candle_obj.get("volume")
+                        .and_then(|v| v.as_i64())
+                        .unwrap_or(0),
+                    frequency,
+                };
+                price_bars.push(price_bar);
+            }
+        }
+    }
+
+    price_bars
+}
+
+/// Contracts from a chains response's callExpDateMap and putExpDateMap, which map
+/// "yyyy-MM-dd:days" to strike to contracts. Schwab sends -999 for Greeks and volatility it
+/// couldn't compute; those become None.
+fn parse_option_chain(data: &Value) -> Vec<SchwabOptionContract> {
+    let number = |contract: &Value, field: &str| contract.get(field).and_then(|v| v.as_f64()).filter(|v| *v != -999.0);
+    let mut contracts = Vec::new();
+
+    for map in ["callExpDateMap", "putExpDateMap"] {
+        let Some(expirations) = data.get(map).and_then(|v| v.as_object()) else {
+            continue;
+        };
+        for (expiration_key, strikes) in expirations {
+            let date = expiration_key.split(':').next().unwrap_or_default();
+            let Ok(expiration_date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                warn!("Skipping option expiration with unexpected key {}", expiration_key);
+                continue;
+            };
+            let Some(strikes) = strikes.as_object() else {
+                continue;
+            };
+            for contract in strikes.values().filter_map(|v| v.as_array()).flatten() {
+                let (Some(symbol), Some(put_call), Some(strike_price)) = (
+                    contract.get("symbol").and_then(|v| v.as_str()),
+                    contract.get("putCall").and_then(|v| v.as_str()),
+                    number(contract, "strikePrice"),
+                ) else {
+                    continue;
+                };
+                contracts.push(SchwabOptionContract {
+                    symbol: symbol.to_string(),
+                    put_call: put_call.to_string(),
+                    expiration_date,
+                    strike_price,
+                    bid: number(contract, "bid"),
+                    ask: number(contract, "ask"),
+                    last: number(contract, "last"),
+                    total_volume: contract.get("totalVolume").and_then(|v| v.as_i64()),
+                    open_interest: contract.get("openInterest").and_then(|v| v.as_i64()),
+                    volatility: number(contract, "volatility"),
+                    delta: number(contract, "delta"),
+                    gamma: number(contract, "gamma"),
+                    theta: number(contract, "theta"),
+                    vega: number(contract, "vega"),
+                    rho: number(contract, "rho"),
+                });
+            }
+        }
+    }
+
+    contracts
+}
+
 #[async_trait::async_trait]
 impl StockDataProvider for SchwabClient {
 
@@ -520,7 +652,7 @@ impl StockDataProvider for SchwabClient {
         }
 
         let symbols_str = symbols.join(",");
-        let url = format!("https://api.schwabapi.com/marketdata/v1/quotes?symbols={}", symbols_str);
+        let url = format!("{}/marketdata/v1/quotes?symbols={}", self.base_url, symbols_str);
         
         let data = self.make_request(&url).await?;
         let mut quotes = Vec::new();
@@ -560,12 +692,13 @@ impl StockDataProvider for SchwabClient {
         Ok(quotes)
     }
 
-    /// Get price history for a symbol
+    /// Get price history for a symbol, one bar per day, week or month
     async fn get_price_history(
         &self,
         symbol: &str,
         from_date: NaiveDate,
         to_date: NaiveDate,
+        frequency: PriceFrequency,
     ) -> Result<Vec<SchwabPriceBar>> {
         // Convert dates to timestamps (milliseconds since epoch)
         let from_timestamp = from_date
@@ -580,45 +713,64 @@ impl StockDataProvider for SchwabClient {
             .timestamp_millis();
 
         let url = format!(
-            "https://api.schwabapi.com/marketdata/v1/pricehistory?symbol={}&periodType=year&frequencyType=daily&frequency=1&startDate={}&endDate={}",
-            symbol, from_timestamp, to_timestamp
+            "{}/marketdata/v1/pricehistory?symbol={}&periodType=year&frequencyType={}&frequency=1&startDate={}&endDate={}",
+            self.base_url, symbol, frequency.as_str(), from_timestamp, to_timestamp
         );
 
         let data = self.make_request(&url).await?;
-        let mut price_bars = Vec::new();
-
-        if let Some(candles) = data.get("candles").and_then(|v| v.as_array()) {
-            for candle in candles {
-                if let Some(candle_obj) = candle.as_object() {
-                    let price_bar = SchwabPriceBar {
-                        datetime: candle_obj.get("datetime")
-                            .and_then(|v| v.as_i64())
-                            .unwrap_or(0),
-                        open: candle_obj.get("open")
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0),
-                        high: candle_obj.get("high")
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0),
-                        low: candle_obj.get("low")
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0),
-                        close: candle_obj.get("close")
-                            .and_then(|v| v.as_f64())
-                            .unwrap_or(0.0),
-                        volume: candle_obj.get("volume")
-                            .and_then(|v| v.as_i64())
-                            .unwrap_or(0),
-                    };
-                    price_bars.push(price_bar);
-                }
-            }
-        }
+        let price_bars = parse_candles(&data, frequency);
 
-        debug!("Retrieved {} price bars for {} from {} to {}", 
-               price_bars.len(), symbol, from_date, to_date);
+        debug!("Retrieved {} {} price bars for {} from {} to {}", 
+               price_bars.len(), frequency.as_str(), symbol, from_date, to_date);
         Ok(price_bars)
     }
+
+    /// Minute bars for one day without extended hours. Schwab keeps roughly the last 48 days of
+    /// 1-minute bars and longer for the wider intervals.
+    async fn get_intraday_history(
+        &self,
+        symbol: &str,
+        date: NaiveDate,
+        interval: IntradayInterval,
+    ) -> Result<Vec<SchwabPriceBar>> {
+        // The regular session falls within the UTC day either side of daylight saving time
+        let from_timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis();
+        let to_timestamp = date.and_hms_opt(23, 59, 59).unwrap().and_utc().timestamp_millis();
+
+        let url = format!(
+            "{}/marketdata/v1/pricehistory?symbol={}&periodType=day&frequencyType=minute&frequency={}&startDate={}&endDate={}&needExtendedHoursData=false",
+            self.base_url, symbol, interval.minutes(), from_timestamp, to_timestamp
+        );
+
+        let data = self.make_request(&url).await?;
+        let price_bars = parse_candles(&data, PriceFrequency::default());
+
+        debug!("Retrieved {} {} bars for {} on {}", price_bars.len(), interval.as_str(), symbol, date);
+        Ok(price_bars)
+    }
+
+    /// Single-leg chain of every strike expiring between the dates
+    async fn get_option_chain(
+        &self,
+        symbol: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+    ) -> Result<Vec<SchwabOptionContract>> {
+        let url = format!(
+            "{}/marketdata/v1/chains?symbol={}&contractType=ALL&strategy=SINGLE&includeUnderlyingQuote=false&fromDate={}&toDate={}",
+            self.base_url, symbol, from_date, to_date
+        );
+
+        let data = self.make_request(&url).await?;
+        let contracts = parse_option_chain(&data);
+
+        debug!("Retrieved {} option contracts for {} expiring {} to {}", contracts.len(), symbol, from_date, to_date);
+        Ok(contracts)
+    }
+
+    async fn get_fundamentals(&self, symbol: &str) -> Result<FundamentalData> {
+        SchwabClient::get_fundamentals(self, symbol).await
+    }
 }
 
 #[cfg(test)]
@@ -639,4 +791,200 @@ mod tests {
         assert_eq!(tokens.access_token, deserialized.access_token);
         assert_eq!(tokens.refresh_token, deserialized.refresh_token);
     }
+
+    fn client_with_tokens(dir: &tempfile::TempDir, server_uri: &str) -> SchwabClient {
+        let token_path = dir.path().join("schwab_tokens.json");
+        let tokens = StoredTokens {
+            access_token: "stale_access".to_string(),
+            refresh_token: "stored_refresh".to_string(),
+            expires_at: Utc::now() + chrono::Duration::hours(1),
+        };
+        fs::write(&token_path, serde_json::to_string(&tokens).unwrap()).unwrap();
+
+        let config = Config {
+            schwab_api_key: "key".to_string(),
+            schwab_app_secret: "secret".to_string(),
+            schwab_callback_url: "https://localhost:8080".to_string(),
+            schwab_token_path: token_path.to_string_lossy().to_string(),
+            database_path: "unused.db".to_string(),
+            rate_limit_per_minute: 60_000,
+            batch_size: 1,
+            debug_log_retention_days: 14,
+            debug_log_max_files: 50,
+        };
+        SchwabClient::new(&config).unwrap().with_base_url(server_uri)
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_refreshes_token_and_retries_once() {
+        use wiremock::matchers::{body_string_contains, header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/marketdata/v1/quotes"))
+            .and(header("Authorization", "Bearer stale_access"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("invalid_token"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/oauth/token"))
+            .and(body_string_contains("refresh_token=stored_refresh"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "fresh_access",
+                "refresh_token": "fresh_refresh",
+                "expires_in": 1800,
+                "token_type": "Bearer"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/marketdata/v1/quotes"))
+            .and(header("Authorization", "Bearer fresh_access"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "AAPL": { "lastPrice": 190.5, "closePrice": 189.0 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_tokens(&dir, &server.uri());
+
+        let quotes = client.get_quotes(&["AAPL".to_string()]).await.unwrap();
+        assert_eq!(quotes.len(), 1);
+        assert_eq!(quotes[0].last_price, 190.5);
+
+        // Refreshed tokens are persisted for the next run
+        let saved: StoredTokens = serde_json::from_str(&fs::read_to_string(dir.path().join("schwab_tokens.json")).unwrap()).unwrap();
+        assert_eq!(saved.access_token, "fresh_access");
+        assert_eq!(saved.refresh_token, "fresh_refresh");
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_surfaces_token_expired() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/marketdata/v1/quotes"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/oauth/token"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("invalid_grant"))
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_tokens(&dir, &server.uri());
+
+        let err = client.get_quotes(&["AAPL".to_string()]).await.unwrap_err();
+        let err: CommandError = err.into();
+        assert_eq!(err.code(), "TOKEN_EXPIRED");
+    }
+
+    #[tokio::test]
+    async fn test_weekly_history_requests_weekly_bars_and_tags_them() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/marketdata/v1/pricehistory"))
+            .and(query_param("symbol", "AAPL"))
+            .and(query_param("frequencyType", "weekly"))
+            .and(query_param("frequency", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "symbol": "AAPL",
+                "empty": false,
+                "candles": [
+                    // Weeks of Jan 1 and Jan 8, 2024
+                    { "open": 187.15, "high": 188.44, "low": 180.17, "close": 181.18, "volume": 263_000_000i64, "datetime": 1_704_088_800_000i64 },
+                    { "open": 182.09, "high": 187.05, "low": 181.50, "close": 185.92, "volume": 240_000_000i64, "datetime": 1_704_693_600_000i64 }
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_tokens(&dir, &server.uri());
+
+        let bars = client
+            .get_price_history(
+                "AAPL",
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(),
+                PriceFrequency::Weekly,
+            )
+            .await
+            .unwrap();
+        assert_eq!(bars.len(), 2);
+        assert!(bars.iter().all(|bar| bar.frequency == PriceFrequency::Weekly));
+        assert_eq!((bars[0].open, bars[0].close, bars[0].volume), (187.15, 181.18, 263_000_000));
+        assert_eq!(bars[1].datetime, 1_704_693_600_000);
+    }
+
+    #[tokio::test]
+    async fn test_option_chain_flattens_calls_and_puts() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/marketdata/v1/chains"))
+            .and(query_param("symbol", "AAPL"))
+            .and(query_param("fromDate", "2024-01-01"))
+            .and(query_param("toDate", "2024-02-29"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "symbol": "AAPL",
+                "status": "SUCCESS",
+                "callExpDateMap": {
+                    "2024-01-19:10": {
+                        "190.0": [{
+                            "putCall": "CALL", "symbol": "AAPL  240119C00190000", "strikePrice": 190.0,
+                            "bid": 1.25, "ask": 1.3, "last": 1.27, "totalVolume": 5400, "openInterest": 21000,
+                            "volatility": 18.4, "delta": 0.32, "gamma": 0.05, "theta": -0.12, "vega": 0.14, "rho": 0.02
+                        }]
+                    }
+                },
+                "putExpDateMap": {
+                    "2024-01-19:10": {
+                        "180.0": [{
+                            "putCall": "PUT", "symbol": "AAPL  240119P00180000", "strikePrice": 180.0,
+                            "bid": 0.8, "ask": 0.85, "totalVolume": 0, "openInterest": 9000,
+                            "volatility": -999.0, "delta": -999.0
+                        }]
+                    }
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_tokens(&dir, &server.uri());
+
+        let contracts = client
+            .get_option_chain(
+                "AAPL",
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(contracts.len(), 2);
+        let call = &contracts[0];
+        assert_eq!((call.put_call.as_str(), call.strike_price, call.bid), ("CALL", 190.0, Some(1.25)));
+        assert_eq!(call.expiration_date, NaiveDate::from_ymd_opt(2024, 1, 19).unwrap());
+        assert_eq!((call.open_interest, call.volatility), (Some(21000), Some(18.4)));
+        // Greeks Schwab couldn't compute are missing rather than -999
+        let put = &contracts[1];
+        assert_eq!((put.put_call.as_str(), put.last, put.volatility, put.delta), ("PUT", None, None, None));
+    }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use clap::{Arg, Command};
-use rust_stocks_tauri_lib::database::{DatabaseManager, run_manual_migration};
+use rust_stocks_tauri_lib::database::{DatabaseManager, run_manual_migration, reset_database, reset_confirmation_token};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -32,6 +32,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Command::new("verify")
                 .about("Verify database integrity")
         )
+        .subcommand(
+            Command::new("reset")
+                .about("Wipe ALL data and recreate the schema (requires the database file name as confirmation)")
+                .arg(Arg::new("confirm")
+                    .long("confirm")
+                    .value_name("DB_FILENAME")
+                    .help("Database file name, e.g. stocks.db"))
+                .arg(Arg::new("no-backup")
+                    .long("no-backup")
+                    .help("Skip the automatic backup before wiping")
+                    .action(clap::ArgAction::SetTrue))
+        )
         .get_matches();
 
     let db_path = matches.get_one::<String>("database").unwrap();
@@ -82,12 +94,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("   Total records: {} stocks, {} prices", stats.total_stocks, stats.total_prices);
         }
         
+        Some(("reset", sub_matches)) => {
+            let expected = reset_confirmation_token(db_path);
+            let Some(confirmation) = sub_matches.get_one::<String>("confirm") else {
+                println!("⚠️  Reset deletes ALL collected data. Confirm with the database file name:");
+                println!("   cargo run --bin db_admin -- --db {} reset --confirm {}", db_path, expected);
+                return Ok(());
+            };
+            
+            let backup_path = reset_database(db_path, confirmation, !sub_matches.get_flag("no-backup")).await?;
+            if let Some(path) = backup_path {
+                println!("📦 Backup: {}", path);
+            }
+            println!("✅ Database reset completed");
+        }
+        
         _ => {
             println!("📋 Available commands:");
             println!("   backup   - Create database backup");
             println!("   status   - Show database statistics");
             println!("   migrate  - Run migrations (with --confirm)");
             println!("   verify   - Verify database integrity");
+            println!("   reset    - Wipe all data (with --confirm <db filename>)");
             println!("\nExamples:");
             println!("   cargo run --bin db_admin -- backup");
             println!("   cargo run --bin db_admin -- status");
@@ -9,7 +9,8 @@ use chrono::{NaiveDate, DateTime, Utc, Local};
 use clap::{Parser, Subcommand};
 use rust_stocks_tauri_lib::api::schwab_client::SchwabClient;
 use rust_stocks_tauri_lib::api::StockDataProvider;
-use rust_stocks_tauri_lib::models::Config;
+use rust_stocks_tauri_lib::database::helpers::resolve_symbol;
+use rust_stocks_tauri_lib::models::{validate_ohlc, Config, PriceFrequency};
 // DataStatusReader removed - using SEC filing-based freshness checking
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
@@ -58,6 +59,18 @@ struct Cli {
     /// Maximum number of retries per symbol
     #[arg(long, default_value = "3")]
     max_retries: usize,
+
+    /// Fail a symbol on any impossible OHLC bar instead of skipping and logging it
+    #[arg(long)]
+    strict: bool,
+
+    /// Bar interval: daily bars go to daily_prices, weekly and monthly ones to interval_prices
+    #[arg(long, default_value = "daily", value_parser = parse_frequency)]
+    frequency: PriceFrequency,
+}
+
+fn parse_frequency(value: &str) -> Result<PriceFrequency, String> {
+    PriceFrequency::parse(value).ok_or_else(|| format!("unknown frequency '{}' (expected daily, weekly or monthly)", value))
 }
 
 #[derive(Subcommand)]
@@ -90,6 +103,11 @@ struct DownloadSettings {
     end_date: String,
     batch_size: usize,
     max_retries: usize,
+    /// Progress files from before this setting load as non-strict
+    #[serde(default)]
+    strict: bool,
+    #[serde(default)]
+    frequency: PriceFrequency,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -207,6 +225,7 @@ impl BulkDownloader {
         println!("=====================================");
         println!("Total symbols: {}", symbols.len());
         println!("Date range: {} to {}", self.progress.settings.start_date, self.progress.settings.end_date);
+        println!("Frequency: {}", self.progress.settings.frequency.as_str());
         println!("Progress file: {}", self.progress_file.display());
         println!();
         
@@ -278,8 +297,12 @@ impl BulkDownloader {
         let start_date = NaiveDate::parse_from_str(&self.progress.settings.start_date, "%Y-%m-%d")?;
         let end_date = NaiveDate::parse_from_str(&self.progress.settings.end_date, "%Y-%m-%d")?;
 
-        // Get stock_id from database
-        let stock_id = self.get_stock_id(symbol).await?;
+        // Get stock_id from database; a former ticker downloads under the current one
+        let (stock_id, current_symbol) = self.get_stock_id(symbol).await?;
+        if current_symbol != symbol {
+            info!("🔁 {} now trades as {}", symbol, current_symbol);
+        }
+        let symbol = current_symbol.as_str();
 
         // If incremental mode, calculate what data is actually needed
         if self.incremental_mode {
@@ -306,7 +329,7 @@ impl BulkDownloader {
 
         // Fetch price data from Schwab API
         let price_bars = self.schwab_client
-            .get_price_history(symbol, start_date, end_date)
+            .get_price_history(symbol, start_date, end_date, self.progress.settings.frequency)
             .await?;
 
         if price_bars.is_empty() {
@@ -321,16 +344,11 @@ impl BulkDownloader {
 
     
     
-    async fn get_stock_id(&self, symbol: &str) -> Result<i64> {
-        let stock_id = sqlx::query_scalar::<_, i64>(
-            "SELECT id FROM stocks WHERE symbol = ?"
-        )
-        .bind(symbol)
-        .fetch_optional(&self.db_pool)
-        .await?;
+    async fn get_stock_id(&self, symbol: &str) -> Result<(i64, String)> {
+        let stock = resolve_symbol(&self.db_pool, symbol).await.map_err(|e| anyhow!(e))?;
         
-        match stock_id {
-            Some(id) => Ok(id),
+        match stock {
+            Some(stock) => Ok(stock),
             None => Err(anyhow!("Stock symbol {} not found in database", symbol)),
         }
     }
@@ -347,15 +365,35 @@ impl BulkDownloader {
             let date = chrono::DateTime::from_timestamp_millis(bar.datetime)
                 .ok_or_else(|| anyhow!("Invalid timestamp: {}", bar.datetime))?
                 .date_naive();
+
+            // Impossible bars fail the symbol in strict mode and are skipped otherwise
+            if let Err(e) = validate_ohlc(bar.open, bar.high, bar.low, bar.close) {
+                if self.progress.settings.strict {
+                    return Err(anyhow!("Invalid price bar for stock {} on {}: {}", stock_id, date, e));
+                }
+                warn!(stock_id, %date, "Skipping invalid price bar: {}", e);
+                continue;
+            }
             
-            // Insert with UPSERT to handle duplicates
-            sqlx::query(
-                r#"
-                INSERT OR REPLACE INTO daily_prices 
-                (stock_id, date, open_price, high_price, low_price, close_price, volume)
-                VALUES (?, ?, ?, ?, ?, ?, ?)
-                "#
-            )
+            // Insert with UPSERT to handle duplicates; weekly and monthly bars are tagged with their interval
+            let query = match bar.frequency {
+                PriceFrequency::Daily => sqlx::query(
+                    r#"
+                    INSERT OR REPLACE INTO daily_prices 
+                    (stock_id, date, open_price, high_price, low_price, close_price, volume)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)
+                    "#
+                ),
+                frequency => sqlx::query(
+                    r#"
+                    INSERT OR REPLACE INTO interval_prices
+                    (frequency, stock_id, period_start, open_price, high_price, low_price, close_price, volume)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                    "#
+                )
+                .bind(frequency.as_str()),
+            };
+            query
             .bind(stock_id)
             .bind(date)
             .bind(bar.open)
@@ -457,6 +495,8 @@ async fn main() -> Result<()> {
         end_date,
         batch_size: cli.batch_size,
         max_retries: cli.max_retries,
+        strict: cli.strict,
+        frequency: cli.frequency,
     };
 
     // Determine if we should use incremental mode (default to true for better performance)
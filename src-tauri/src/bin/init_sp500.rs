@@ -3,18 +3,8 @@
 /// Downloads the S&P 500 company list from GitHub and populates the stocks table
 
 use anyhow::Result;
+use rust_stocks_tauri_lib::tools::sp500_constituents::{fetch_sp500_constituents, sync_sp500_constituents};
 use sqlx::sqlite::SqlitePoolOptions;
-use serde::Deserialize;
-
-#[derive(Debug, Deserialize)]
-struct StockRecord {
-    #[serde(rename = "Symbol")]
-    symbol: String,
-    #[serde(rename = "Security")]
-    company_name: String,
-    #[serde(rename = "GICS Sector")]
-    sector: String,
-}
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -31,22 +21,9 @@ async fn main() -> Result<()> {
         .connect(&database_url)
         .await?;
 
-    // Step 1: Fetch S&P 500 list from GitHub
+    // Step 1: Fetch and parse the S&P 500 list from GitHub
     println!("📥 Downloading S&P 500 list from GitHub...");
-    let url = "https://raw.githubusercontent.com/datasets/s-and-p-500-companies/main/data/constituents.csv";
-
-    let response = reqwest::get(url).await?;
-    let csv_text = response.text().await?;
-
-    // Step 2: Parse CSV
-    println!("📄 Parsing CSV data...");
-    let mut reader = csv::Reader::from_reader(csv_text.as_bytes());
-    let mut companies = Vec::new();
-
-    for result in reader.deserialize() {
-        let record: StockRecord = result?;
-        companies.push(record);
-    }
+    let companies = fetch_sp500_constituents().await?;
 
     println!("   ✅ Found {} companies", companies.len());
 
@@ -54,57 +31,28 @@ async fn main() -> Result<()> {
         anyhow::bail!("No companies found in S&P 500 data");
     }
 
-    // Step 3: Insert into stocks table
+    // Step 2: Sync the stocks table, keeping history through ticker changes
     println!("💾 Inserting stocks into database...");
-    let mut inserted = 0;
-    let mut updated = 0;
-
-    for company in &companies {
-        let sector = if company.sector.is_empty() {
-            None
-        } else {
-            Some(&company.sector)
-        };
-
-        // Use INSERT OR REPLACE to handle existing stocks
-        let result = sqlx::query(
-            "INSERT INTO stocks (symbol, company_name, sector, is_sp500)
-             VALUES (?1, ?2, ?3, 1)
-             ON CONFLICT(symbol) DO UPDATE SET
-                company_name = ?2,
-                sector = ?3,
-                is_sp500 = 1"
-        )
-        .bind(&company.symbol)
-        .bind(&company.company_name)
-        .bind(sector)
-        .execute(&pool)
-        .await;
+    let current_date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let summary = sync_sp500_constituents(&pool, &companies, &current_date).await?;
 
-        match result {
-            Ok(query_result) => {
-                if query_result.rows_affected() > 0 {
-                    inserted += 1;
-                } else {
-                    updated += 1;
-                }
-            }
-            Err(e) => eprintln!("   ⚠️  Failed to insert {}: {}", company.symbol, e),
-        }
+    for (old_symbol, new_symbol) in &summary.renamed {
+        println!("   🔁 {} is now {}", old_symbol, new_symbol);
     }
-
-    println!("   ✅ Inserted: {}, Updated: {}", inserted, updated);
-
-    // Step 4: Update metadata
-    let current_date = chrono::Utc::now().format("%Y-%m-%d").to_string();
-    sqlx::query("INSERT OR REPLACE INTO metadata (key, value) VALUES ('sp500_last_updated', ?1)")
-        .bind(&current_date)
-        .execute(&pool)
-        .await?;
+    for (symbol, error) in &summary.failed {
+        eprintln!("   ⚠️  Failed to store {}: {}", symbol, error);
+    }
+    println!(
+        "   ✅ Inserted: {}, Updated: {}, Ticker changes: {}, No longer listed: {}",
+        summary.inserted,
+        summary.updated,
+        summary.renamed.len(),
+        summary.removed
+    );
 
     println!();
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("✅ Successfully initialized {} S&P 500 companies!", inserted + updated);
+    println!("✅ Successfully initialized {} S&P 500 companies!", summary.inserted + summary.updated);
     println!("📅 Last updated: {}", current_date);
     println!();
     println!("💡 Next steps:");
@@ -59,10 +59,9 @@ struct Cli {
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    // Initialize logging if verbose
-    if cli.verbose {
-        tracing_subscriber::fmt::init();
-    }
+    // Refresh progress is reported through tracing; verbose adds per-filing detail
+    let max_level = if cli.verbose { tracing::Level::DEBUG } else { tracing::Level::INFO };
+    tracing_subscriber::fmt().with_max_level(max_level).with_target(false).init();
 
     // Auto-detect database path with WAL mode optimization
     let database_path = "db/stocks.db";
@@ -339,6 +338,9 @@ async fn execute_data_refresh(pool: &sqlx::SqlitePool, cli: &Cli, mode: RefreshM
     println!("📝 Session ID: {}", result.session_id);
     println!("⏱️  Duration: {} seconds", result.duration_seconds.unwrap_or(0));
     println!("📊 Records processed: {}", result.total_records_processed);
+    if !result.updated_stock_ids.is_empty() {
+        println!("📐 Ratios recomputed for {} stocks with new statements", result.updated_stock_ids.len());
+    }
 
     if !result.sources_refreshed.is_empty() {
         println!("✅ Refreshed: {}", result.sources_refreshed.join(", "));
@@ -1,15 +1,59 @@
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
-use crate::database::helpers::get_database_connection;
+use sqlx::{Row, SqlitePool};
+use crate::analysis::ps_history::{load_current_ps_points, load_ps_history_stats, load_revenue_growth};
+use crate::database::helpers::{ensure_writable, get_database_connection, resolve_stock};
+use crate::error::CommandError;
+use crate::tools::screen_exclusions::ScreenResults;
+use crate::tools::screening_readiness::{require_screen_ready, Screen};
+use crate::tools::universe_filter::UniverseFilter;
+use ts_rs::TS;
+
+/// Columns returned by `get_price_history`; fields outside the set are left out of the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceFieldSet {
+    /// Date and close, enough for a line chart
+    CloseOnly,
+    Ohlc,
+    OhlcV,
+    /// OHLC, volume and P/E
+    #[default]
+    All,
+}
+
+impl PriceFieldSet {
+    fn columns(self) -> &'static str {
+        match self {
+            PriceFieldSet::CloseOnly => "dp.date, dp.close_price",
+            PriceFieldSet::Ohlc => "dp.date, dp.open_price, dp.high_price, dp.low_price, dp.close_price",
+            PriceFieldSet::OhlcV => "dp.date, dp.open_price, dp.high_price, dp.low_price, dp.close_price, dp.volume",
+            PriceFieldSet::All => "dp.date, dp.open_price, dp.high_price, dp.low_price, dp.close_price, dp.volume, dp.pe_ratio",
+        }
+    }
+
+    fn has_ohlc(self) -> bool {
+        self != PriceFieldSet::CloseOnly
+    }
+
+    fn has_volume(self) -> bool {
+        matches!(self, PriceFieldSet::OhlcV | PriceFieldSet::All)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceData {
     pub date: String,
-    pub open_price: f64,
-    pub high_price: f64,
-    pub low_price: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low_price: Option<f64>,
     pub close_price: f64,
-    pub volume: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub pe_ratio: Option<f64>,
 }
 
@@ -49,45 +93,71 @@ pub struct ValuationExtremes {
 }
 
 
+/// `fields` defaults to `All`
 #[tauri::command]
-pub async fn get_price_history(symbol: String, start_date: String, end_date: String) -> Result<Vec<PriceData>, String> {
+pub async fn get_price_history(
+    symbol: String,
+    start_date: String,
+    end_date: String,
+    fields: Option<PriceFieldSet>,
+) -> Result<Vec<PriceData>, CommandError> {
     let pool = get_database_connection().await?;
+    validate_price_history_dates(&start_date, &end_date)?;
     
-    // Validate date format but use as strings since database stores DATE format
-    chrono::NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid start date format: {}", e))?;
-    
-    chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid end date format: {}", e))?;
+    get_price_history_internal(&pool, &symbol, &start_date, &end_date, fields.unwrap_or_default()).await
+}
+
+/// Validate date format but use as strings since database stores DATE format
+pub fn validate_price_history_dates(start_date: &str, end_date: &str) -> Result<(), CommandError> {
+    chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|e| CommandError::validation("start_date", format!("Invalid start date format: {}", e)))?;
     
-    let query = "
-        SELECT dp.date, dp.open_price, dp.high_price, dp.low_price, dp.close_price, dp.volume, dp.pe_ratio 
+    chrono::NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|e| CommandError::validation("end_date", format!("Invalid end date format: {}", e)))?;
+
+    Ok(())
+}
+
+pub async fn get_price_history_internal(
+    pool: &SqlitePool,
+    symbol: &str,
+    start_date: &str,
+    end_date: &str,
+    fields: PriceFieldSet,
+) -> Result<Vec<PriceData>, CommandError> {
+    let query = format!("
+        SELECT {}
         FROM daily_prices dp
         JOIN stocks s ON dp.stock_id = s.id
         WHERE s.symbol = ?1 AND dp.date BETWEEN ?2 AND ?3 
         ORDER BY dp.date ASC
         LIMIT 1000
-    ";
+    ", fields.columns());
     
-    match sqlx::query(query)
-        .bind(&symbol)
-        .bind(&start_date)
-        .bind(&end_date)
-        .fetch_all(&pool).await 
+    match sqlx::query(&query)
+        .bind(symbol)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(pool).await 
     {
         Ok(rows) => {
             let price_data: Vec<PriceData> = rows.into_iter().map(|row| {
                 // Date is stored as DATE string in database, not timestamp
                 let date_string: String = row.get("date");
+                let ohlc = |column: &str| fields.has_ohlc().then(|| row.get::<f64, _>(column));
                 
                 PriceData {
                     date: date_string,
-                    open_price: row.get::<f64, _>("open_price"),
-                    high_price: row.get::<f64, _>("high_price"),
-                    low_price: row.get::<f64, _>("low_price"),
+                    open_price: ohlc("open_price"),
+                    high_price: ohlc("high_price"),
+                    low_price: ohlc("low_price"),
                     close_price: row.get::<f64, _>("close_price"),
-                    volume: row.try_get::<Option<i64>, _>("volume").unwrap_or(None).unwrap_or(0),
-                    pe_ratio: row.try_get::<Option<f64>, _>("pe_ratio").unwrap_or(None),
+                    volume: fields.has_volume().then(|| row.try_get::<Option<i64>, _>("volume").unwrap_or(None).unwrap_or(0)),
+                    pe_ratio: if fields == PriceFieldSet::All {
+                        row.try_get::<Option<f64>, _>("pe_ratio").unwrap_or(None)
+                    } else {
+                        None
+                    },
                 }
             }).collect();
             
@@ -95,13 +165,13 @@ pub async fn get_price_history(symbol: String, start_date: String, end_date: Str
         }
         Err(e) => {
             eprintln!("Price history query error: {}", e);
-            Err(format!("Database query failed: {}", e))
+            Err(e.into())
         }
     }
 }
 
 #[tauri::command]
-pub async fn get_stock_date_range(symbol: String) -> Result<DateRangeInfo, String> {
+pub async fn get_stock_date_range(symbol: String) -> Result<DateRangeInfo, CommandError> {
     let pool = get_database_connection().await?;
     
     let result = sqlx::query("
@@ -129,18 +199,23 @@ pub async fn get_stock_date_range(symbol: String) -> Result<DateRangeInfo, Strin
             })
         }
         Ok(None) => {
-            Err(format!("No data found for symbol: {}", symbol))
+            Err(CommandError::not_found("symbol", format!("No data found for symbol: {}", symbol)))
         }
         Err(e) => {
-            Err(format!("Database error: {}", e))
+            Err(e.into())
         }
     }
 }
 
 #[tauri::command]
-pub async fn get_valuation_ratios(symbol: String) -> Result<Option<ValuationRatios>, String> {
+pub async fn get_valuation_ratios(symbol: String) -> Result<Option<ValuationRatios>, CommandError> {
     let pool = get_database_connection().await?;
-    
+
+    get_valuation_ratios_internal(&pool, &symbol).await
+}
+
+/// Latest row of daily_valuation_ratios for `symbol`
+pub async fn get_valuation_ratios_internal(pool: &SqlitePool, symbol: &str) -> Result<Option<ValuationRatios>, CommandError> {
     let query = "
         SELECT 
             dvr.stock_id,
@@ -162,8 +237,8 @@ pub async fn get_valuation_ratios(symbol: String) -> Result<Option<ValuationRati
     ";
     
     match sqlx::query(query)
-        .bind(&symbol)
-        .fetch_optional(&pool).await 
+        .bind(symbol)
+        .fetch_optional(pool).await 
     {
         Ok(Some(row)) => {
             let ratios = ValuationRatios {
@@ -184,21 +259,21 @@ pub async fn get_valuation_ratios(symbol: String) -> Result<Option<ValuationRati
         Ok(None) => Ok(None),
         Err(e) => {
             eprintln!("Valuation ratios query error: {}", e);
-            Err(format!("Database query failed: {}", e))
+            Err(e.into())
         }
     }
 }
 
 #[tauri::command]
-pub async fn get_ps_evs_history(symbol: String, start_date: String, end_date: String) -> Result<Vec<ValuationRatios>, String> {
+pub async fn get_ps_evs_history(symbol: String, start_date: String, end_date: String) -> Result<Vec<ValuationRatios>, CommandError> {
     let pool = get_database_connection().await?;
     
     // Validate date format
     chrono::NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid start date format: {}", e))?;
+        .map_err(|e| CommandError::validation("start_date", format!("Invalid start date format: {}", e)))?;
     
     chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
-        .map_err(|e| format!("Invalid end date format: {}", e))?;
+        .map_err(|e| CommandError::validation("end_date", format!("Invalid end date format: {}", e)))?;
     
     let query = "
         SELECT 
@@ -247,11 +322,49 @@ pub async fn get_ps_evs_history(symbol: String, start_date: String, end_date: St
         }
         Err(e) => {
             eprintln!("P/S EV/S history query error: {}", e);
-            Err(format!("Database query failed: {}", e))
+            Err(e.into())
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_daily_valuation_ratios(
+    symbol: String,
+    start_date: String,
+    end_date: String
+) -> Result<Vec<crate::analysis::valuation_ratios::DailyValuationRatio>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    chrono::NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| CommandError::validation("start_date", format!("Invalid start date format: {}", e)))?;
+
+    chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+        .map_err(|e| CommandError::validation("end_date", format!("Invalid end date format: {}", e)))?;
+
+    match crate::analysis::valuation_ratios::load_daily_valuation_ratios(&pool, &symbol, &start_date, &end_date).await {
+        Ok(ratios) => Ok(ratios),
+        Err(e) => {
+            eprintln!("Daily valuation ratios query error: {}", e);
+            Err(e.into())
         }
     }
 }
 
+/// Rebuild the stored daily ratio series for one stock; returns rows written
+#[tauri::command]
+pub async fn backfill_daily_ratios(stock_id: i64) -> Result<i64, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    Ok(crate::analysis::valuation_ratios::backfill_daily_ratios(&pool, stock_id).await?)
+}
+
+/// Market cap floor the P/S screener commands apply when none is given
+pub const PS_SCREEN_DEFAULT_MIN_MARKET_CAP: f64 = 500_000_000.0;
+
+/// Prior P/S points the smart P/S screen needs before it will judge a stock
+pub const PS_SCREEN_MIN_HISTORY: i32 = 20;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct SmartUndervaluedStock {
     pub stock_id: i32,
@@ -267,6 +380,57 @@ pub struct SmartUndervaluedStock {
     pub market_cap: f64,
     pub price: f64,
     pub data_completeness_score: i32,
+    pub ttm_growth_rate: Option<f64>,
+}
+
+/// Output ordering for the P/S screener
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum PsSortKey {
+    /// Lowest P/S first (original screener ordering)
+    #[default]
+    #[serde(rename = "ps_asc")]
+    PsAscending,
+    #[serde(rename = "ps_desc")]
+    PsDescending,
+    #[serde(rename = "market_cap_asc")]
+    MarketCapAscending,
+    #[serde(rename = "market_cap_desc")]
+    MarketCapDescending,
+    /// Stocks without TTM revenue growth data always sort last
+    #[serde(rename = "revenue_growth_asc")]
+    RevenueGrowthAscending,
+    #[serde(rename = "revenue_growth_desc")]
+    RevenueGrowthDescending,
+}
+
+/// Stable in-place sort of screener results; ties keep their incoming order.
+pub fn sort_undervalued_stocks(stocks: &mut [SmartUndervaluedStock], sort_by: PsSortKey) {
+    use std::cmp::Ordering;
+
+    // Missing growth data goes last in either direction
+    fn growth_order(a: Option<f64>, b: Option<f64>, descending: bool) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) if descending => b.total_cmp(&a),
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    match sort_by {
+        PsSortKey::PsAscending => stocks.sort_by(|a, b| a.current_ps.total_cmp(&b.current_ps)),
+        PsSortKey::PsDescending => stocks.sort_by(|a, b| b.current_ps.total_cmp(&a.current_ps)),
+        PsSortKey::MarketCapAscending => stocks.sort_by(|a, b| a.market_cap.total_cmp(&b.market_cap)),
+        PsSortKey::MarketCapDescending => stocks.sort_by(|a, b| b.market_cap.total_cmp(&a.market_cap)),
+        PsSortKey::RevenueGrowthAscending => {
+            stocks.sort_by(|a, b| growth_order(a.ttm_growth_rate, b.ttm_growth_rate, false))
+        }
+        PsSortKey::RevenueGrowthDescending => {
+            stocks.sort_by(|a, b| growth_order(a.ttm_growth_rate, b.ttm_growth_rate, true))
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -299,378 +463,171 @@ pub struct PsRevenueGrowthStock {
 #[tauri::command]
 pub async fn get_undervalued_stocks_by_ps(
     stock_tickers: Vec<String>, 
-    limit: Option<i32>, 
-    min_market_cap: Option<f64>
-) -> Result<Vec<SmartUndervaluedStock>, String> {
+    limit: Option<usize>, 
+    min_market_cap: Option<f64>,
+    sort_by: Option<PsSortKey>,
+    min_avg_daily_volume: Option<f64>,
+    override_readiness: Option<bool>,
+) -> Result<ScreenResults<SmartUndervaluedStock>, CommandError> {
     let pool = get_database_connection().await?;
+    require_screen_ready(&pool, Screen::Ps, override_readiness).await?;
     let limit_value = limit.unwrap_or(50);
-    let min_market_cap_value = min_market_cap.unwrap_or(500_000_000.0); // Default $500M
-    
-    if stock_tickers.is_empty() {
-        return Ok(vec![]);
-    }
-    
-    // Create placeholders for the IN clause
-    let placeholders = stock_tickers.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-    
-    // Smart P/S screening algorithm - calculate everything on-the-fly
-    let query = format!("
-        WITH sp500_stocks AS (
-            SELECT s.id, s.symbol
-            FROM stocks s
-            WHERE s.symbol IN ({})
-        ),
-        historical_ps_data AS (
-            SELECT 
-                s.id as stock_id,
-                s.symbol,
-                dvr.ps_ratio_ttm,
-                dvr.date,
-                dvr.price,
-                dvr.market_cap,
-                dvr.data_completeness_score,
-                ROW_NUMBER() OVER (PARTITION BY s.id ORDER BY dvr.date DESC) as rn
-            FROM sp500_stocks s
-            JOIN daily_valuation_ratios dvr ON s.id = dvr.stock_id
-            WHERE dvr.ps_ratio_ttm IS NOT NULL 
-              AND dvr.ps_ratio_ttm > 0.01
-              AND dvr.market_cap > ?
-        ),
-        current_data AS (
-            SELECT * FROM historical_ps_data WHERE rn = 1
-        ),
-        historical_stats AS (
-            SELECT 
-                stock_id,
-                AVG(ps_ratio_ttm) as hist_mean,
-                MIN(ps_ratio_ttm) as hist_min,
-                MAX(ps_ratio_ttm) as hist_max,
-                COUNT(*) as data_points
-            FROM historical_ps_data 
-            WHERE rn > 1  -- Exclude current data point for historical analysis
-            GROUP BY stock_id
-            HAVING COUNT(*) >= 20  -- Require at least 20 historical data points (roughly 1 month)
-        ),
-        variance_calc AS (
-            SELECT 
-                h.stock_id,
-                AVG((s.ps_ratio_ttm - h.hist_mean) * (s.ps_ratio_ttm - h.hist_mean)) as hist_variance
-            FROM historical_ps_data s
-            JOIN historical_stats h ON s.stock_id = h.stock_id
-            WHERE s.rn > 1  -- Exclude current data point
-            GROUP BY h.stock_id
-        ),
-        median_calc AS (
-            SELECT 
-                stock_id,
-                ps_ratio_ttm,
-                ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY ps_ratio_ttm) as rn,
-                COUNT(*) OVER (PARTITION BY stock_id) as total_count
-            FROM historical_ps_data 
-            WHERE rn > 1  -- Exclude current data point
-        ),
-        median_data AS (
-            SELECT 
-                stock_id,
-                AVG(ps_ratio_ttm) as hist_median
-            FROM median_calc
-            WHERE rn IN ((total_count + 1) / 2, (total_count + 2) / 2)
-            GROUP BY stock_id
-        ),
-        market_mean AS (
-            SELECT AVG(ps_ratio_ttm) as market_mean FROM current_data
-        ),
-        market_variance AS (
-            SELECT 
-                AVG((c.ps_ratio_ttm - m.market_mean) * (c.ps_ratio_ttm - m.market_mean)) as market_variance
-            FROM current_data c
-            CROSS JOIN market_mean m
-        )
-        SELECT 
-            c.stock_id,
-            c.symbol,
-            c.ps_ratio_ttm as current_ps,
-            COALESCE(h.hist_mean, 0.0) as historical_mean,
-            COALESCE(m.hist_median, 0.0) as historical_median,
-            COALESCE(h.hist_min, 0.0) as historical_min,
-            COALESCE(h.hist_max, 0.0) as historical_max,
-            COALESCE(v.hist_variance, 0.0) as historical_variance,
-            CASE 
-                WHEN v.hist_variance > 0 THEN (c.ps_ratio_ttm - h.hist_mean) / v.hist_variance
-                ELSE 0.0
-            END as z_score,
-            CASE 
-                WHEN h.hist_mean > 0 AND v.hist_variance > 0 AND h.data_points >= 20 THEN
-                    -- Stock is undervalued if current P/S is significantly below historical mean
-                    -- Using a simple threshold: current P/S < mean - 0.5 * variance
-                    c.ps_ratio_ttm < (h.hist_mean - 0.5 * v.hist_variance) AND
-                    -- And also below historical median
-                    c.ps_ratio_ttm < m.hist_median
-                ELSE false
-            END as is_undervalued,
-            c.market_cap,
-            c.price,
-            c.data_completeness_score
-        FROM current_data c
-        LEFT JOIN historical_stats h ON c.stock_id = h.stock_id
-        LEFT JOIN variance_calc v ON c.stock_id = v.stock_id
-        LEFT JOIN median_data m ON c.stock_id = m.stock_id
-        CROSS JOIN market_mean mm
-        CROSS JOIN market_variance mv
-        WHERE c.market_cap > ?
-        ORDER BY 
-            CASE 
-                WHEN h.hist_mean > 0 AND v.hist_variance > 0 AND h.data_points >= 20 THEN
-                    c.ps_ratio_ttm < (h.hist_mean - 0.5 * v.hist_variance) AND
-                    c.ps_ratio_ttm < m.hist_median
-                ELSE false
-            END DESC,
-            c.ps_ratio_ttm ASC
-        LIMIT ?
-    ", placeholders);
-    
-    let mut query_builder = sqlx::query_as::<_, SmartUndervaluedStock>(&query);
-    
-    // Bind stock tickers
-    for ticker in &stock_tickers {
-        query_builder = query_builder.bind(ticker);
-    }
-    
-    // Bind min market cap (used twice in the query)
-    query_builder = query_builder.bind(min_market_cap_value);
-    query_builder = query_builder.bind(min_market_cap_value);
-    query_builder = query_builder.bind(limit_value);
-    
-    match query_builder.fetch_all(&pool).await {
-        Ok(stocks) => {
-            // Filter to only return truly undervalued stocks
-            let undervalued_stocks: Vec<SmartUndervaluedStock> = stocks
-                .into_iter()
-                .filter(|stock| stock.is_undervalued)
-                .take(limit_value as usize)
-                .collect();
-            
-            Ok(undervalued_stocks)
-        }
+    let sort_by = sort_by.unwrap_or_default();
+    let min_market_cap_value = min_market_cap.unwrap_or(PS_SCREEN_DEFAULT_MIN_MARKET_CAP);
+    let universe = UniverseFilter::new(Some(min_market_cap_value), min_avg_daily_volume);
+
+    match get_undervalued_stocks_by_ps_internal(&pool, &stock_tickers, limit_value, &universe, sort_by).await {
+        Ok(stocks) => Ok(ScreenResults::with_exclusions(&pool, stocks, |stock| stock.stock_id as i64, &stock_tickers).await?),
         Err(e) => {
             eprintln!("Smart undervalued stocks query error: {}", e);
-            Err(format!("Database query failed: {}", e))
+            Err(e.into())
         }
     }
 }
 
+/// Smart P/S screen: current P/S below both the historical median and mean - 0.5 × variance,
+/// over at least 20 prior data points. Historical rows are held to the same market cap floor.
+pub async fn get_undervalued_stocks_by_ps_internal(
+    pool: &SqlitePool,
+    stock_tickers: &[String],
+    limit: usize,
+    universe: &UniverseFilter,
+    sort_by: PsSortKey,
+) -> Result<Vec<SmartUndervaluedStock>, sqlx::Error> {
+    let mut current = load_current_ps_points(pool, stock_tickers, universe).await?;
+    let history = load_ps_history_stats(pool, &current, universe.min_market_cap).await?;
+    let stock_ids: Vec<i64> = current.iter().map(|c| c.stock_id).collect();
+    let growth = load_revenue_growth(pool, &stock_ids).await?;
+
+    // Original screener ordering before the requested sort is applied
+    current.sort_by(|a, b| a.ps_ratio_ttm.total_cmp(&b.ps_ratio_ttm).then_with(|| a.symbol.cmp(&b.symbol)));
+
+    let mut undervalued_stocks: Vec<SmartUndervaluedStock> = current
+        .into_iter()
+        .map(|c| {
+            // Require at least 20 historical data points (roughly 1 month)
+            let stats = history.get(&c.stock_id).filter(|s| s.data_points >= PS_SCREEN_MIN_HISTORY);
+            let (mean, median, min, max, variance) = stats
+                .map(|s| (s.mean, s.median, s.min, s.max, s.variance))
+                .unwrap_or_default();
+
+            SmartUndervaluedStock {
+                stock_id: c.stock_id as i32,
+                symbol: c.symbol,
+                current_ps: c.ps_ratio_ttm,
+                historical_mean: mean,
+                historical_median: median,
+                historical_min: min,
+                historical_max: max,
+                historical_variance: variance,
+                z_score: if variance > 0.0 { (c.ps_ratio_ttm - mean) / variance } else { 0.0 },
+                // Significantly below the historical mean, and also below the historical median
+                is_undervalued: mean > 0.0
+                    && variance > 0.0
+                    && c.ps_ratio_ttm < mean - 0.5 * variance
+                    && c.ps_ratio_ttm < median,
+                market_cap: c.market_cap,
+                price: c.price,
+                data_completeness_score: c.data_completeness_score,
+                ttm_growth_rate: growth.get(&c.stock_id).and_then(|g| g.ttm_growth_rate),
+            }
+        })
+        .filter(|stock| stock.is_undervalued)
+        .collect();
+
+    // Sort before truncating so the limit keeps the top of the requested order
+    sort_undervalued_stocks(&mut undervalued_stocks, sort_by);
+    undervalued_stocks.truncate(limit);
+
+    Ok(undervalued_stocks)
+}
+
 #[tauri::command]
 pub async fn get_ps_screening_with_revenue_growth(
     stock_tickers: Vec<String>, 
     limit: Option<i32>, 
-    min_market_cap: Option<f64>
-) -> Result<Vec<PsRevenueGrowthStock>, String> {
+    min_market_cap: Option<f64>,
+    min_avg_daily_volume: Option<f64>,
+    override_readiness: Option<bool>,
+) -> Result<ScreenResults<PsRevenueGrowthStock>, CommandError> {
     let pool = get_database_connection().await?;
-    let limit_value = limit.unwrap_or(50);
-    let min_market_cap_value = min_market_cap.unwrap_or(500_000_000.0); // Default $500M
-    
-    if stock_tickers.is_empty() {
-        return Ok(vec![]);
-    }
-    
-    // Create placeholders for the IN clause
-    let placeholders = stock_tickers.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-    
-    // P/S screening with revenue growth algorithm
-    let query = format!("
-        WITH sp500_stocks AS (
-            SELECT s.id, s.symbol
-            FROM stocks s
-            WHERE s.symbol IN ({})
-        ),
-        historical_ps_data AS (
-            SELECT 
-                s.id as stock_id,
-                s.symbol,
-                dvr.ps_ratio_ttm,
-                dvr.date,
-                dvr.price,
-                dvr.market_cap,
-                dvr.data_completeness_score,
-                ROW_NUMBER() OVER (PARTITION BY s.id ORDER BY dvr.date DESC) as rn
-            FROM sp500_stocks s
-            JOIN daily_valuation_ratios dvr ON s.id = dvr.stock_id
-            WHERE dvr.ps_ratio_ttm IS NOT NULL 
-              AND dvr.ps_ratio_ttm > 0.01
-              AND dvr.market_cap > ?
-        ),
-        current_data AS (
-            SELECT * FROM historical_ps_data WHERE rn = 1
-        ),
-        historical_stats AS (
-            SELECT 
-                stock_id,
-                AVG(ps_ratio_ttm) as hist_mean,
-                MIN(ps_ratio_ttm) as hist_min,
-                MAX(ps_ratio_ttm) as hist_max,
-                COUNT(*) as data_points
-            FROM historical_ps_data 
-            WHERE rn > 1  -- Exclude current data point for historical analysis
-            GROUP BY stock_id
-            HAVING COUNT(*) >= 10  -- Require at least 10 historical data points
-        ),
-        variance_calc AS (
-            SELECT 
-                h.stock_id,
-                AVG((s.ps_ratio_ttm - h.hist_mean) * (s.ps_ratio_ttm - h.hist_mean)) as hist_variance
-            FROM historical_ps_data s
-            JOIN historical_stats h ON s.stock_id = h.stock_id
-            WHERE s.rn > 1  -- Exclude current data point
-            GROUP BY h.stock_id
-        ),
-        median_calc AS (
-            SELECT 
-                stock_id,
-                ps_ratio_ttm,
-                ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY ps_ratio_ttm) as rn,
-                COUNT(*) OVER (PARTITION BY stock_id) as total_count
-            FROM historical_ps_data 
-            WHERE rn > 1  -- Exclude current data point
-        ),
-        median_data AS (
-            SELECT 
-                stock_id,
-                AVG(ps_ratio_ttm) as hist_median
-            FROM median_calc
-            WHERE rn IN ((total_count + 1) / 2, (total_count + 2) / 2)
-            GROUP BY stock_id
-        ),
-        stddev_calc AS (
-            SELECT 
-                h.stock_id,
-                v.hist_variance as hist_stddev
-            FROM historical_stats h
-            JOIN variance_calc v ON h.stock_id = v.stock_id
-        ),
-        -- Revenue data for TTM growth (simplified)
-        ttm_growth AS (
-            SELECT 
-                c.stock_id,
-                current_ttm.revenue as current_ttm_revenue,
-                CASE 
-                    WHEN prev_ttm.revenue > 0 THEN 
-                        ((current_ttm.revenue - prev_ttm.revenue) / prev_ttm.revenue) * 100
-                    ELSE NULL
-                END as ttm_growth_rate
-            FROM current_data c
-            LEFT JOIN (
-                SELECT stock_id, revenue, ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) as rn
-                FROM income_statements 
-                WHERE period_type = 'TTM'
-            ) current_ttm ON c.stock_id = current_ttm.stock_id AND current_ttm.rn = 1
-            LEFT JOIN (
-                SELECT stock_id, revenue, ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) as rn
-                FROM income_statements 
-                WHERE period_type = 'TTM'
-            ) prev_ttm ON c.stock_id = prev_ttm.stock_id AND prev_ttm.rn = 2
-        ),
-        -- Revenue data for Annual growth (simplified)
-        annual_growth AS (
-            SELECT 
-                c.stock_id,
-                current_annual.revenue as current_annual_revenue,
-                CASE 
-                    WHEN prev_annual.revenue > 0 THEN 
-                        ((current_annual.revenue - prev_annual.revenue) / prev_annual.revenue) * 100
-                    ELSE NULL
-                END as annual_growth_rate
-            FROM current_data c
-            LEFT JOIN (
-                SELECT stock_id, revenue, ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY fiscal_year DESC) as rn
-                FROM income_statements 
-                WHERE period_type = 'Annual'
-            ) current_annual ON c.stock_id = current_annual.stock_id AND current_annual.rn = 1
-            LEFT JOIN (
-                SELECT stock_id, revenue, ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY fiscal_year DESC) as rn
-                FROM income_statements 
-                WHERE period_type = 'Annual'
-            ) prev_annual ON c.stock_id = prev_annual.stock_id AND prev_annual.rn = 2
-        )
-        SELECT 
-            c.stock_id,
-            c.symbol,
-            c.ps_ratio_ttm as current_ps,
-            COALESCE(h.hist_mean, 0.0) as historical_mean,
-            COALESCE(m.hist_median, 0.0) as historical_median,
-            COALESCE(s.hist_stddev, 0.0) as historical_stddev,
-            COALESCE(h.hist_min, 0.0) as historical_min,
-            COALESCE(h.hist_max, 0.0) as historical_max,
-            COALESCE(h.data_points, 0) as data_points,
-            tg.current_ttm_revenue,
-            tg.ttm_growth_rate,
-            ag.current_annual_revenue,
-            ag.annual_growth_rate,
-            CASE 
-                WHEN s.hist_stddev > 0 THEN (c.ps_ratio_ttm - h.hist_mean) / s.hist_stddev
-                ELSE 0.0
-            END as z_score,
-            c.data_completeness_score as quality_score,
-            CASE 
-                WHEN h.hist_mean > 0 AND s.hist_stddev > 0 AND h.data_points >= 10 THEN
-                    -- Stock is undervalued if ALL THREE conditions are met:
-                    -- 1. Current P/S < (Historical Median - 1.0 × Std Dev)  -- Statistical undervaluation
-                    -- 2. Revenue Growth > 0% (TTM OR Annual)               -- Growth requirement
-                    -- 3. Quality Score >= 50                               -- Data quality filter
-                    c.ps_ratio_ttm < (m.hist_median - 1.0 * s.hist_stddev) AND
-                    (tg.ttm_growth_rate > 0 OR ag.annual_growth_rate > 0) AND
-                    c.data_completeness_score >= 50
-                ELSE false
-            END as undervalued_flag,
-            c.market_cap,
-            c.price,
-            c.data_completeness_score
-        FROM current_data c
-        LEFT JOIN historical_stats h ON c.stock_id = h.stock_id
-        LEFT JOIN variance_calc v ON c.stock_id = v.stock_id
-        LEFT JOIN median_data m ON c.stock_id = m.stock_id
-        LEFT JOIN stddev_calc s ON c.stock_id = s.stock_id
-        LEFT JOIN ttm_growth tg ON c.stock_id = tg.stock_id
-        LEFT JOIN annual_growth ag ON c.stock_id = ag.stock_id
-        WHERE c.market_cap > ?
-        ORDER BY 
-            undervalued_flag DESC,
-            c.ps_ratio_ttm ASC
-        LIMIT ?
-    ", placeholders);
-    
-    let mut query_builder = sqlx::query_as::<_, PsRevenueGrowthStock>(&query);
-    
-    // Bind stock tickers
-    for ticker in &stock_tickers {
-        query_builder = query_builder.bind(ticker);
-    }
-    
-    // Bind min market cap (used twice in the query)
-    query_builder = query_builder.bind(min_market_cap_value);
-    query_builder = query_builder.bind(min_market_cap_value);
-    query_builder = query_builder.bind(limit_value);
-    
-    match query_builder.fetch_all(&pool).await {
-        Ok(stocks) => {
-            // Filter to only return truly undervalued stocks
-            // Filter to only undervalued stocks
-            let undervalued_stocks: Vec<PsRevenueGrowthStock> = stocks
-                .into_iter()
-                .filter(|stock| stock.undervalued_flag)
-                .take(limit_value as usize)
-                .collect();
-            
-            Ok(undervalued_stocks)
-        }
+    require_screen_ready(&pool, Screen::Ps, override_readiness).await?;
+    let limit_value = limit.unwrap_or(50).max(0) as usize;
+    let min_market_cap_value = min_market_cap.unwrap_or(PS_SCREEN_DEFAULT_MIN_MARKET_CAP);
+    let universe = UniverseFilter::new(Some(min_market_cap_value), min_avg_daily_volume);
+
+    match get_ps_screening_with_revenue_growth_internal(&pool, &stock_tickers, limit_value, &universe).await {
+        Ok(stocks) => Ok(ScreenResults::with_exclusions(&pool, stocks, |stock| stock.stock_id as i64, &stock_tickers).await?),
         Err(e) => {
             eprintln!("P/S screening with revenue growth query error: {}", e);
-            Err(format!("Database query failed: {}", e))
+            Err(e.into())
         }
     }
 }
 
+/// P/S screen with revenue growth: undervalued only if ALL THREE conditions are met:
+/// 1. Current P/S < (Historical Median - 1.0 × Std Dev), over at least 10 prior data points
+/// 2. Revenue Growth > 0% (TTM OR Annual)
+/// 3. Quality Score >= 50
+pub async fn get_ps_screening_with_revenue_growth_internal(
+    pool: &SqlitePool,
+    stock_tickers: &[String],
+    limit: usize,
+    universe: &UniverseFilter,
+) -> Result<Vec<PsRevenueGrowthStock>, sqlx::Error> {
+    let mut current = load_current_ps_points(pool, stock_tickers, universe).await?;
+    let history = load_ps_history_stats(pool, &current, universe.min_market_cap).await?;
+    let stock_ids: Vec<i64> = current.iter().map(|c| c.stock_id).collect();
+    let growth = load_revenue_growth(pool, &stock_ids).await?;
+
+    current.sort_by(|a, b| a.ps_ratio_ttm.total_cmp(&b.ps_ratio_ttm));
+
+    Ok(current
+        .into_iter()
+        .map(|c| {
+            let stats = history.get(&c.stock_id).filter(|s| s.data_points >= 10);
+            // The screen has always used the variance as its "std dev"
+            let (mean, median, stddev, min, max, data_points) = stats
+                .map(|s| (s.mean, s.median, s.variance, s.min, s.max, s.data_points))
+                .unwrap_or_default();
+            let growth = growth.get(&c.stock_id).cloned().unwrap_or_default();
+
+            let growing = growth.ttm_growth_rate.is_some_and(|g| g > 0.0)
+                || growth.annual_growth_rate.is_some_and(|g| g > 0.0);
+
+            PsRevenueGrowthStock {
+                stock_id: c.stock_id as i32,
+                symbol: c.symbol,
+                current_ps: c.ps_ratio_ttm,
+                historical_mean: mean,
+                historical_median: median,
+                historical_stddev: stddev,
+                historical_min: min,
+                historical_max: max,
+                data_points,
+                current_ttm_revenue: growth.current_ttm_revenue,
+                ttm_growth_rate: growth.ttm_growth_rate,
+                current_annual_revenue: growth.current_annual_revenue,
+                annual_growth_rate: growth.annual_growth_rate,
+                z_score: if stddev > 0.0 { (c.ps_ratio_ttm - mean) / stddev } else { 0.0 },
+                quality_score: c.data_completeness_score,
+                undervalued_flag: mean > 0.0
+                    && stddev > 0.0
+                    && c.ps_ratio_ttm < median - 1.0 * stddev
+                    && growing
+                    && c.data_completeness_score >= 50,
+                market_cap: c.market_cap,
+                price: c.price,
+                data_completeness_score: c.data_completeness_score,
+            }
+        })
+        .filter(|stock| stock.undervalued_flag)
+        .take(limit)
+        .collect())
+}
+
 #[tauri::command]
-pub async fn get_valuation_extremes(symbol: String) -> Result<ValuationExtremes, String> {
+pub async fn get_valuation_extremes(symbol: String) -> Result<ValuationExtremes, CommandError> {
     let pool = get_database_connection().await?;
     
     // Get P/E ratio extremes
@@ -686,8 +643,7 @@ pub async fn get_valuation_extremes(symbol: String) -> Result<ValuationExtremes,
     )
     .bind(&symbol)
     .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("Failed to fetch P/E extremes: {}", e))?;
+    .await?;
     
     // Get P/S ratio extremes
     let ps_extremes = sqlx::query_as::<_, (Option<f64>, Option<f64>)>(
@@ -702,8 +658,7 @@ pub async fn get_valuation_extremes(symbol: String) -> Result<ValuationExtremes,
     )
     .bind(&symbol)
     .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("Failed to fetch P/S extremes: {}", e))?;
+    .await?;
     
     // Get EV/S ratio extremes
     let evs_extremes = sqlx::query_as::<_, (Option<f64>, Option<f64>)>(
@@ -718,8 +673,7 @@ pub async fn get_valuation_extremes(symbol: String) -> Result<ValuationExtremes,
     )
     .bind(&symbol)
     .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("Failed to fetch EV/S extremes: {}", e))?;
+    .await?;
     
     Ok(ValuationExtremes {
         symbol,
@@ -732,6 +686,769 @@ pub async fn get_valuation_extremes(symbol: String) -> Result<ValuationExtremes,
     })
 }
 
+/// Sharpe, Sortino and beta over a trailing window; beta is against the equal-weight S&P 500
+/// composite unless another stored benchmark is named
+#[tauri::command]
+pub async fn get_risk_metrics(
+    stock_id: i64,
+    period_days: Option<i64>,
+    risk_free_annual: Option<f64>,
+    benchmark: Option<String>
+) -> Result<crate::analysis::risk_metrics::RiskMetrics, CommandError> {
+    let pool = get_database_connection().await?;
+    let period_days = period_days.unwrap_or(365);
+    let risk_free_annual = risk_free_annual.unwrap_or(0.04);
+
+    if period_days <= 0 {
+        return Err(CommandError::validation("period_days", "Period must be at least one day"));
+    }
+    let benchmark = match benchmark {
+        Some(benchmark) => {
+            let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM benchmark_prices WHERE benchmark = ?")
+                .bind(&benchmark)
+                .fetch_one(&pool)
+                .await?;
+            if stored == 0 {
+                return Err(CommandError::not_found("benchmark", format!("No stored series named {}", benchmark)));
+            }
+            benchmark
+        }
+        None => crate::analysis::benchmark::SP500_EQUAL_WEIGHT.to_string(),
+    };
+
+    match crate::analysis::risk_metrics::calculate_risk_metrics(&pool, stock_id, period_days, risk_free_annual, Some(&benchmark)).await {
+        Ok(metrics) => Ok(metrics),
+        Err(e) => {
+            eprintln!("Risk metrics query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_max_drawdown(
+    stock_id: i64,
+    start_date: String,
+    end_date: String
+) -> Result<crate::analysis::drawdown::MaxDrawdown, CommandError> {
+    let pool = get_database_connection().await?;
+
+    for (field, date) in [("start_date", &start_date), ("end_date", &end_date)] {
+        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+            return Err(CommandError::validation(field, "Date must be in YYYY-MM-DD format"));
+        }
+    }
+    if start_date > end_date {
+        return Err(CommandError::validation("start_date", "Start date must not be after end date"));
+    }
+
+    match crate::analysis::drawdown::compute_max_drawdown(&pool, stock_id, &start_date, &end_date).await {
+        Ok(drawdown) => Ok(drawdown),
+        Err(e) => {
+            eprintln!("Max drawdown query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Max and current drawdown of a stock's closes with each episode's recovery, for the risk discussion
+#[tauri::command]
+pub async fn get_drawdown_analysis(
+    stock_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<crate::analysis::drawdown::DrawdownAnalysis, CommandError> {
+    let pool = get_database_connection().await?;
+    let (start, end) = parse_date_range(&start_date, &end_date)?;
+
+    match crate::analysis::drawdown::compute_drawdown_analysis(&pool, stock_id, start, end).await {
+        Ok(analysis) => Ok(analysis),
+        Err(e) => {
+            eprintln!("Drawdown analysis query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Drawdown analysis of a weighted buy-and-hold portfolio of stocks
+#[tauri::command]
+pub async fn get_portfolio_drawdown_analysis(
+    holdings: Vec<crate::analysis::drawdown::PortfolioHolding>,
+    start_date: String,
+    end_date: String,
+) -> Result<crate::analysis::drawdown::DrawdownAnalysis, CommandError> {
+    let pool = get_database_connection().await?;
+    let (start, end) = parse_date_range(&start_date, &end_date)?;
+
+    if holdings.is_empty() {
+        return Err(CommandError::validation("holdings", "At least one holding is required"));
+    }
+    if holdings.iter().any(|holding| !(holding.weight.is_finite() && holding.weight > 0.0)) {
+        return Err(CommandError::validation("holdings", "Weights must be positive"));
+    }
+
+    match crate::analysis::drawdown::compute_portfolio_drawdown_analysis(&pool, &holdings, start, end).await {
+        Ok(analysis) => Ok(analysis),
+        Err(e) => {
+            eprintln!("Portfolio drawdown analysis query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Daily levels and returns of a stored benchmark (the equal-weight S&P 500 composite by
+/// default), optionally limited to a date range. Composites are built on first use.
+#[tauri::command]
+pub async fn get_benchmark_history(
+    benchmark: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<crate::analysis::benchmark::BenchmarkHistory, CommandError> {
+    let pool = get_database_connection().await?;
+    let benchmark = benchmark.unwrap_or_else(|| crate::analysis::benchmark::SP500_EQUAL_WEIGHT.to_string());
+
+    let parse = |field: &str, date: Option<String>| {
+        date.map(|date| {
+            chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|_| CommandError::validation(field, "Date must be in YYYY-MM-DD format"))
+        })
+        .transpose()
+    };
+    let start = parse("start_date", start_date)?;
+    let end = parse("end_date", end_date)?;
+
+    ensure_benchmark_stored(&pool, &benchmark).await?;
+
+    match crate::analysis::benchmark::load_benchmark_history(&pool, &benchmark, start, end).await {
+        Ok(history) => Ok(history),
+        Err(e) => {
+            eprintln!("Benchmark history query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// A stock's cumulative return over a benchmark's (the equal-weight S&P 500 composite by
+/// default), both normalized to 1.0 at the first date in the range they share
+#[tauri::command]
+pub async fn get_relative_performance(
+    symbol: String,
+    start_date: String,
+    end_date: String,
+    benchmark: Option<String>,
+) -> Result<crate::analysis::benchmark::RelativePerformance, CommandError> {
+    let pool = get_database_connection().await?;
+    let (start, end) = parse_date_range(&start_date, &end_date)?;
+    let benchmark = benchmark.unwrap_or_else(|| crate::analysis::benchmark::SP500_EQUAL_WEIGHT.to_string());
+
+    let stock_id: i64 = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(&symbol)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+    ensure_benchmark_stored(&pool, &benchmark).await?;
+
+    match crate::analysis::benchmark::compute_relative_performance(&pool, stock_id, &benchmark, start, end).await {
+        Ok(performance) => Ok(performance),
+        Err(e) => {
+            eprintln!("Relative performance query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Composites extend as prices refresh; build them once for databases filled before that.
+/// Imported series must already exist.
+async fn ensure_benchmark_stored(pool: &SqlitePool, benchmark: &str) -> Result<(), CommandError> {
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM benchmark_prices WHERE benchmark = ?")
+        .bind(benchmark)
+        .fetch_one(pool)
+        .await?;
+    if stored == 0 {
+        if !crate::analysis::benchmark::is_composite(benchmark) {
+            return Err(CommandError::not_found("benchmark", format!("No stored series named {}", benchmark)));
+        }
+        crate::analysis::benchmark::update_composite_benchmarks(pool).await?;
+    }
+    Ok(())
+}
+
+fn parse_date_range(start_date: &str, end_date: &str) -> Result<(chrono::NaiveDate, chrono::NaiveDate), CommandError> {
+    let parse = |field: &str, date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| CommandError::validation(field, "Date must be in YYYY-MM-DD format"))
+    };
+    let start = parse("start_date", start_date)?;
+    let end = parse("end_date", end_date)?;
+    if start > end {
+        return Err(CommandError::validation("start_date", "Start date must not be after end date"));
+    }
+    Ok((start, end))
+}
+
+/// Price-only and dividend-reinvested return between two dates, annualized. Dates that aren't
+/// trading days use the close before them.
+#[tauri::command]
+pub async fn compute_total_return(
+    symbol: String,
+    start_date: String,
+    end_date: String
+) -> Result<crate::analysis::total_return::TotalReturn, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let parse = |field: &str, date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| CommandError::validation(field, "Date must be in YYYY-MM-DD format"))
+    };
+    let start = parse("start_date", &start_date)?;
+    let end = parse("end_date", &end_date)?;
+    if start > end {
+        return Err(CommandError::validation("start_date", "Start date must not be after end date"));
+    }
+
+    match crate::analysis::total_return::compute_total_return(&pool, &symbol, start, end).await {
+        Ok(total_return) => Ok(total_return),
+        Err(e) => {
+            eprintln!("Total return query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Trailing 52-week high/low and the latest close's distance from each
+#[tauri::command]
+pub async fn get_52_week_stats(symbol: String) -> Result<crate::analysis::price_range::Week52Range, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let stock_id: Option<i64> = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(&symbol)
+        .fetch_optional(&pool)
+        .await?;
+    let stock_id = stock_id.ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+
+    let as_of = chrono::Local::now().date_naive();
+    match crate::analysis::price_range::compute_52_week_range(&pool, stock_id, as_of).await {
+        Ok(range) => Ok(range),
+        Err(e) => {
+            eprintln!("52-week range query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Stocks within `max_distance_pct` percent of their 52-week low, optionally only those with an
+/// F-Score of at least `min_f_score` or positive free cash flow
+#[tauri::command]
+pub async fn get_stocks_near_52_week_low(
+    max_distance_pct: f64,
+    min_f_score: Option<i32>,
+    require_positive_fcf: Option<bool>,
+    limit: Option<i32>,
+) -> Result<Vec<crate::analysis::price_range::NearLowStock>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    if !(0.0..=1000.0).contains(&max_distance_pct) {
+        return Err(CommandError::validation("max_distance_pct", "Must be between 0 and 1000"));
+    }
+    if min_f_score.is_some_and(|score| !(0..=9).contains(&score)) {
+        return Err(CommandError::validation("min_f_score", "Must be between 0 and 9"));
+    }
+
+    // Ranges are stored as prices refresh; build them once for databases filled before that
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM price_range_52w").fetch_one(&pool).await?;
+    if stored == 0 {
+        crate::analysis::price_range::refresh_all_52_week_ranges(&pool).await?;
+    }
+
+    match crate::analysis::price_range::screen_near_52_week_low(
+        &pool,
+        max_distance_pct,
+        min_f_score,
+        require_positive_fcf.unwrap_or(false),
+        limit.unwrap_or(50),
+    )
+    .await
+    {
+        Ok(stocks) => Ok(stocks),
+        Err(e) => {
+            eprintln!("Near 52-week low screen query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Latest price, day change, valuation and 52-week range for the stock detail header
+#[tauri::command]
+pub async fn get_stock_summary(symbol: String) -> Result<crate::analysis::stock_summary::StockSummary, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let as_of = chrono::Local::now().date_naive();
+    match crate::analysis::stock_summary::compute_stock_summary(&pool, &symbol, as_of).await {
+        Ok(summary) => Ok(summary),
+        Err(e) => {
+            eprintln!("Stock summary query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Sector aggregates for the landing heatmap; pass a sector to break it down by industry
+#[tauri::command]
+pub async fn get_sector_overview(sector: Option<String>) -> Result<Vec<crate::analysis::sector_overview::SectorOverview>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    match crate::analysis::sector_overview::compute_sector_overview(&pool, sector.as_deref()).await {
+        Ok(overview) => Ok(overview),
+        Err(e) => {
+            eprintln!("Sector overview query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Industries ranked by market-cap-weighted revenue CAGR over the last `years` fiscal years,
+/// fastest first; industries with fewer than three members with growth data are left out
+#[tauri::command]
+pub async fn get_industry_growth_ranking(years: i32) -> Result<Vec<crate::analysis::industry_growth::IndustryGrowth>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    if !(1..=20).contains(&years) {
+        return Err(CommandError::validation("years", "Must be between 1 and 20"));
+    }
+
+    match crate::analysis::industry_growth::compute_industry_growth_ranking(&pool, years).await {
+        Ok(ranking) => Ok(ranking),
+        Err(e) => {
+            eprintln!("Industry growth ranking query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Consecutive years of revenue growth and the spread of annual growth rates over the last `years`
+#[tauri::command]
+pub async fn get_growth_consistency(symbol: String, years: i32) -> Result<crate::analysis::growth_consistency::GrowthConsistency, CommandError> {
+    let pool = get_database_connection().await?;
+
+    if !(1..=30).contains(&years) {
+        return Err(CommandError::validation("years", "Must be between 1 and 30"));
+    }
+    let stock_id: i64 = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(&symbol)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+
+    match crate::analysis::growth_consistency::compute_growth_consistency(&pool, stock_id, years).await {
+        Ok(consistency) => Ok(consistency),
+        Err(e) => {
+            eprintln!("Growth consistency query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Each stock's z-score of `metric` within its sector, most undervalued first
+#[tauri::command]
+pub async fn get_sector_relative_valuation(
+    metric: crate::analysis::sector_relative::ValuationMetric,
+) -> Result<Vec<crate::analysis::sector_relative::SectorRelativeValuation>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    match crate::analysis::sector_relative::compute_sector_relative_valuation(&pool, metric).await {
+        Ok(valuations) => Ok(valuations),
+        Err(e) => {
+            eprintln!("Sector relative valuation query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Histogram of `metric` across the S&P 500, with `symbol`'s bin when given
+#[tauri::command]
+pub async fn get_metric_distribution(
+    metric: crate::analysis::sector_relative::ValuationMetric,
+    buckets: usize,
+    symbol: Option<String>,
+) -> Result<crate::analysis::metric_distribution::MetricDistribution, CommandError> {
+    let pool = get_database_connection().await?;
+
+    if !(1..=200).contains(&buckets) {
+        return Err(CommandError::validation("buckets", "Must be between 1 and 200"));
+    }
+    if let Some(symbol) = &symbol {
+        let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+            .bind(symbol)
+            .fetch_optional(&pool)
+            .await?;
+        if exists.is_none() {
+            return Err(CommandError::not_found("stock", format!("Unknown symbol {}", symbol)));
+        }
+    }
+
+    match crate::analysis::metric_distribution::compute_metric_distribution(&pool, metric, buckets, symbol.as_deref()).await {
+        Ok(distribution) => Ok(distribution),
+        Err(e) => {
+            eprintln!("Metric distribution query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Debt/equity, net debt, net debt/EBITDA and interest coverage, latest and over the last five fiscal years
+#[tauri::command]
+pub async fn get_leverage_metrics(stock_id: i64) -> Result<crate::analysis::leverage::LeverageMetrics, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM stocks WHERE id = ?")
+        .bind(stock_id)
+        .fetch_optional(&pool)
+        .await?;
+    if exists.is_none() {
+        return Err(CommandError::not_found("stock", format!("No stock with id {}", stock_id)));
+    }
+
+    match crate::analysis::leverage::compute_leverage_metrics(&pool, stock_id).await {
+        Ok(metrics) => Ok(metrics),
+        Err(e) => {
+            eprintln!("Leverage metrics query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Current and quick ratio per fiscal year; years without inventory give the current ratio only
+#[tauri::command]
+pub async fn get_liquidity_ratios(symbol: String) -> Result<crate::analysis::liquidity::LiquidityRatios, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let stock_id: i64 = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(&symbol)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+
+    match crate::analysis::liquidity::compute_liquidity_ratios(&pool, stock_id).await {
+        Ok(years) => Ok(crate::analysis::liquidity::LiquidityRatios { stock_id, symbol, years }),
+        Err(e) => {
+            eprintln!("Liquidity ratios query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// S&P 500 stocks within the leverage ceilings, lowest debt/equity first; default ceilings when none are given
+#[tauri::command]
+pub async fn get_low_leverage_stocks(
+    criteria: Option<crate::analysis::leverage::LowLeverageCriteria>,
+) -> Result<ScreenResults<crate::analysis::leverage::LowLeverageStock>, CommandError> {
+    let pool = get_database_connection().await?;
+    let criteria = criteria.unwrap_or_default();
+
+    if criteria.limit.is_some_and(|limit| limit < 1) {
+        return Err(CommandError::validation("limit", "Must be at least 1"));
+    }
+
+    match crate::analysis::leverage::screen_low_leverage_stocks(&pool, &criteria).await {
+        Ok(stocks) => Ok(ScreenResults::with_exclusions(&pool, stocks, |stock| stock.stock_id, &[]).await?),
+        Err(e) => {
+            eprintln!("Low leverage screen query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Highest latest FCF yields among stocks with positive free cash flow in each of their last
+/// `min_positive_years` fiscal years (three by default)
+#[tauri::command]
+pub async fn get_top_fcf_yield(
+    min_positive_years: Option<i32>,
+    min_market_cap: Option<f64>,
+    limit: Option<i32>,
+) -> Result<ScreenResults<crate::analysis::free_cash_flow::FcfYieldStock>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let min_positive_years = min_positive_years.unwrap_or(crate::analysis::free_cash_flow::DEFAULT_MIN_POSITIVE_FCF_YEARS);
+    if !(0..=30).contains(&min_positive_years) {
+        return Err(CommandError::validation("min_positive_years", "Must be between 0 and 30"));
+    }
+    let universe = UniverseFilter::new(min_market_cap, None);
+
+    match crate::analysis::free_cash_flow::screen_top_fcf_yield(&pool, min_positive_years, &universe, limit.unwrap_or(50)).await {
+        Ok(stocks) => Ok(ScreenResults::with_exclusions(&pool, stocks, |stock| stock.stock_id, &[]).await?),
+        Err(e) => {
+            eprintln!("FCF yield screen query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// ROIC, margins and asset turnover for every stored fiscal year, recomputed from the statements
+#[tauri::command]
+pub async fn get_quality_metrics(stock_id: i64) -> Result<crate::analysis::quality::QualityMetrics, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM stocks WHERE id = ?")
+        .bind(stock_id)
+        .fetch_optional(&pool)
+        .await?;
+    if exists.is_none() {
+        return Err(CommandError::not_found("stock", format!("No stock with id {}", stock_id)));
+    }
+
+    match crate::analysis::quality::compute_quality_metrics(&pool, stock_id).await {
+        Ok(metrics) => Ok(metrics),
+        Err(e) => {
+            eprintln!("Quality metrics query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Return on invested capital for every fiscal year: NOPAT at the clamped effective tax rate over
+/// debt plus equity minus cash
+#[tauri::command]
+pub async fn get_roic(symbol: String) -> Result<crate::analysis::quality::RoicHistory, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let (stock_id, symbol) = resolve_stock(&pool, &symbol).await?;
+
+    match crate::analysis::quality::compute_roic(&pool, stock_id, symbol).await {
+        Ok(roic) => Ok(roic),
+        Err(e) => {
+            eprintln!("ROIC query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Current P/E, P/S and P/B next to each one's 5-year median and the premium or discount to it
+#[tauri::command]
+pub async fn get_valuation_vs_history(symbol: String) -> Result<crate::analysis::valuation_history::ValuationVsHistory, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let (stock_id, symbol) = resolve_stock(&pool, &symbol).await?;
+
+    match crate::analysis::valuation_history::compute_valuation_vs_history(&pool, stock_id, symbol).await {
+        Ok(history) => Ok(history),
+        Err(e) => {
+            eprintln!("Valuation history query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Novy-Marx gross profitability (gross profit over total assets) for every fiscal year
+#[tauri::command]
+pub async fn get_gross_profitability(symbol: String) -> Result<crate::analysis::quality::GrossProfitabilityHistory, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let (stock_id, symbol) = resolve_stock(&pool, &symbol).await?;
+
+    match crate::analysis::quality::compute_gross_profitability(&pool, stock_id, symbol).await {
+        Ok(history) => Ok(history),
+        Err(e) => {
+            eprintln!("Gross profitability query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// S&P 500 stocks ranked by their latest fiscal year's gross profitability, highest first
+#[tauri::command]
+pub async fn get_stocks_by_gross_profitability(
+    limit: Option<i32>,
+) -> Result<Vec<crate::analysis::quality::GrossProfitabilityRanking>, CommandError> {
+    let pool = get_database_connection().await?;
+    let limit = limit.unwrap_or(50);
+    if limit < 1 {
+        return Err(CommandError::validation("limit", "Must be at least 1"));
+    }
+
+    match crate::analysis::quality::rank_by_gross_profitability(&pool, limit as usize).await {
+        Ok(stocks) => Ok(stocks),
+        Err(e) => {
+            eprintln!("Gross profitability ranking query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Accrual ratio, (net income - operating cash flow) / total assets, for every fiscal year
+#[tauri::command]
+pub async fn get_accruals(symbol: String) -> Result<crate::analysis::accruals::AccrualHistory, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let (stock_id, symbol) = resolve_stock(&pool, &symbol).await?;
+
+    match crate::analysis::accruals::compute_accruals(&pool, stock_id, symbol).await {
+        Ok(accruals) => Ok(accruals),
+        Err(e) => {
+            eprintln!("Accruals query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Latest close and day change, market cap, P/E, P/S and Graham pass/fail for each symbol, in
+/// the order given, for rendering a watchlist in one call. Unknown symbols come back with nulls.
+#[tauri::command]
+pub async fn get_watchlist_snapshot(symbols: Vec<String>) -> Result<Vec<crate::analysis::watchlist_snapshot::WatchlistSnapshotRow>, CommandError> {
+    let pool = get_database_connection().await?;
+    let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.trim().to_uppercase()).collect();
+
+    match crate::analysis::watchlist_snapshot::load_watchlist_snapshot(&pool, &symbols).await {
+        Ok(rows) => Ok(rows),
+        Err(e) => {
+            eprintln!("Watchlist snapshot query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// S&P 500 stocks with ROIC above the threshold in each of their last N fiscal years and no
+/// margin slipping by more than the allowed decline, highest ROIC first
+#[tauri::command]
+pub async fn get_quality_compounders(
+    criteria: Option<crate::analysis::quality::QualityCompounderCriteria>,
+) -> Result<Vec<crate::analysis::quality::QualityCompounder>, CommandError> {
+    let pool = get_database_connection().await?;
+    let criteria = criteria.unwrap_or_default();
+
+    if criteria.years.is_some_and(|years| !(1..=30).contains(&years)) {
+        return Err(CommandError::validation("years", "Must be between 1 and 30"));
+    }
+    if criteria.limit.is_some_and(|limit| limit < 1) {
+        return Err(CommandError::validation("limit", "Must be at least 1"));
+    }
+
+    match crate::analysis::quality::screen_quality_compounders(&pool, &criteria).await {
+        Ok(stocks) => Ok(stocks),
+        Err(e) => {
+            eprintln!("Quality compounders screen query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Stored dividends by quarter and fiscal year, with growth, payout ratios and special dividends flagged
+#[tauri::command]
+pub async fn get_dividend_history(stock_id: i64) -> Result<crate::analysis::dividend_growth::DividendHistory, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM stocks WHERE id = ?")
+        .bind(stock_id)
+        .fetch_optional(&pool)
+        .await?;
+    if exists.is_none() {
+        return Err(CommandError::not_found("stock", format!("No stock with id {}", stock_id)));
+    }
+
+    match crate::analysis::dividend_growth::load_dividend_history(&pool, stock_id).await {
+        Ok(history) => Ok(history),
+        Err(e) => {
+            eprintln!("Dividend history query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// S&P 500 stocks that raised their regular dividend at least `min_years_of_growth` fiscal years
+/// running, optionally yielding at least `min_yield` percent
+#[tauri::command]
+pub async fn get_dividend_growers(
+    min_years_of_growth: i32,
+    min_yield: Option<f64>,
+    limit: Option<i32>,
+) -> Result<Vec<crate::analysis::dividend_growth::DividendGrower>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    if !(1..=50).contains(&min_years_of_growth) {
+        return Err(CommandError::validation("min_years_of_growth", "Must be between 1 and 50"));
+    }
+    if min_yield.is_some_and(|min| min < 0.0) {
+        return Err(CommandError::validation("min_yield", "Must not be negative"));
+    }
+
+    match crate::analysis::dividend_growth::screen_dividend_growers(&pool, min_years_of_growth, min_yield, limit.unwrap_or(50)).await {
+        Ok(growers) => Ok(growers),
+        Err(e) => {
+            eprintln!("Dividend growers screen query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Trailing shareholder yield split into dividend and net buyback yield, in percent of market cap
+#[tauri::command]
+pub async fn get_shareholder_yield(symbol: String) -> Result<crate::analysis::shareholder_yield::ShareholderYield, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let stock_id: i64 = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(&symbol)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+
+    match crate::analysis::shareholder_yield::compute_shareholder_yield(&pool, stock_id, symbol).await {
+        Ok(shareholder_yield) => Ok(shareholder_yield),
+        Err(e) => {
+            eprintln!("Shareholder yield query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Stocks ranked by 6-month and 12-1 month price momentum, optionally restricted to the cheapest
+/// value composite decile (Trending Value)
+#[tauri::command]
+pub async fn get_momentum_rankings(
+    criteria: Option<crate::analysis::momentum::MomentumCriteria>,
+) -> Result<crate::analysis::momentum::MomentumRankings, CommandError> {
+    let pool = get_database_connection().await?;
+    let criteria = criteria.unwrap_or_default();
+
+    if criteria.as_of.as_deref().is_some_and(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err()) {
+        return Err(CommandError::validation("as_of", "Date must be in YYYY-MM-DD format"));
+    }
+    if criteria.max_value_percentile.is_some_and(|max| !(0.0..=100.0).contains(&max)) {
+        return Err(CommandError::validation("max_value_percentile", "Must be between 0 and 100"));
+    }
+    if criteria.limit.is_some_and(|limit| limit < 1) {
+        return Err(CommandError::validation("limit", "Must be at least 1"));
+    }
+
+    match crate::analysis::momentum::compute_momentum_rankings(&pool, &criteria).await {
+        Ok(rankings) => Ok(rankings),
+        Err(e) => {
+            eprintln!("Momentum rankings query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_ratio_provenance(
+    stock_id: i64,
+    ratio: crate::analysis::ratio_provenance::ProvenanceRatio,
+    date: Option<String>
+) -> Result<crate::analysis::ratio_provenance::RatioProvenance, CommandError> {
+    let pool = get_database_connection().await?;
+    let date = date.unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
+
+    if chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() {
+        return Err(CommandError::validation("date", "Date must be in YYYY-MM-DD format"));
+    }
+
+    match crate::analysis::ratio_provenance::calculate_ratio_provenance(&pool, stock_id, ratio, &date).await {
+        Ok(provenance) => Ok(provenance),
+        Err(e) => {
+            eprintln!("Ratio provenance query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use sqlx::{SqlitePool, pool::PoolOptions};
@@ -769,6 +1486,7 @@ mod tests {
             "AAPL".to_string(),
             "2024-01-01".to_string(),
             "2024-01-31".to_string(),
+            None,
         ).await;
 
         assert!(result.is_ok(), "get_price_history should succeed");
@@ -776,12 +1494,40 @@ mod tests {
 
         if !prices.is_empty() {
             assert!(prices[0].close_price > 0.0, "Price should be positive");
-            assert!(prices[0].volume >= 0, "Volume should be non-negative");
+            assert!(prices[0].volume.is_some_and(|v| v >= 0), "Volume should be non-negative");
         }
 
         println!("✅ get_price_history test passed with {} records", prices.len());
     }
 
+    #[tokio::test]
+    async fn test_close_only_price_history_omits_other_fields() {
+        use super::{get_price_history_internal, PriceFieldSet};
+
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE stocks (id INTEGER PRIMARY KEY, symbol TEXT NOT NULL)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE daily_prices (stock_id INTEGER, date DATE, open_price REAL, high_price REAL, low_price REAL,
+                close_price REAL, volume INTEGER, pe_ratio REAL)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO stocks (id, symbol) VALUES (1, 'AAPL')").execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO daily_prices VALUES (1, '2024-01-02', 185.0, 188.0, 183.0, 186.0, 5000000, 30.5)")
+            .execute(&pool).await.unwrap();
+
+        let close_only = get_price_history_internal(&pool, "AAPL", "2024-01-01", "2024-01-31", PriceFieldSet::CloseOnly).await.unwrap();
+        let payload = serde_json::to_value(&close_only[0]).unwrap();
+        assert_eq!(payload, serde_json::json!({ "date": "2024-01-02", "close_price": 186.0 }));
+
+        let ohlcv = get_price_history_internal(&pool, "AAPL", "2024-01-01", "2024-01-31", PriceFieldSet::OhlcV).await.unwrap();
+        let payload = serde_json::to_value(&ohlcv[0]).unwrap();
+        assert_eq!((payload["volume"].as_i64(), payload.get("pe_ratio")), (Some(5000000), None));
+
+        let all = get_price_history_internal(&pool, "AAPL", "2024-01-01", "2024-01-31", PriceFieldSet::default()).await.unwrap();
+        assert_eq!((all[0].open_price, all[0].pe_ratio), (Some(185.0), Some(30.5)));
+    }
+
     #[tokio::test]
     async fn test_get_valuation_ratios() {
         let _test_db = TestDatabase::new().await.unwrap();
@@ -855,4 +1601,49 @@ mod tests {
 
         println!("✅ get_valuation_extremes test passed");
     }
-}
\ No newline at end of file
+
+    fn ps_stock(symbol: &str, current_ps: f64, market_cap: f64, ttm_growth_rate: Option<f64>) -> super::SmartUndervaluedStock {
+        super::SmartUndervaluedStock {
+            stock_id: 0,
+            symbol: symbol.to_string(),
+            current_ps,
+            historical_mean: 0.0,
+            historical_median: 0.0,
+            historical_min: 0.0,
+            historical_max: 0.0,
+            historical_variance: 0.0,
+            z_score: 0.0,
+            is_undervalued: true,
+            market_cap,
+            price: 0.0,
+            data_completeness_score: 100,
+            ttm_growth_rate,
+        }
+    }
+
+    fn sorted_symbols(sort_by: super::PsSortKey) -> Vec<String> {
+        let mut stocks = vec![
+            ps_stock("AAA", 2.0, 3e9, Some(5.0)),
+            ps_stock("BBB", 1.0, 9e9, None),
+            ps_stock("CCC", 3.0, 1e9, Some(-2.0)),
+            ps_stock("DDD", 2.0, 5e9, Some(12.0)),
+        ];
+        super::sort_undervalued_stocks(&mut stocks, sort_by);
+        stocks.into_iter().map(|s| s.symbol).collect()
+    }
+
+    #[test]
+    fn test_ps_sort_keys() {
+        use super::PsSortKey;
+
+        // Ties on P/S keep incoming order (AAA before DDD)
+        assert_eq!(sorted_symbols(PsSortKey::PsAscending), ["BBB", "AAA", "DDD", "CCC"]);
+        assert_eq!(sorted_symbols(PsSortKey::PsDescending), ["CCC", "AAA", "DDD", "BBB"]);
+        assert_eq!(sorted_symbols(PsSortKey::MarketCapAscending), ["CCC", "AAA", "DDD", "BBB"]);
+        assert_eq!(sorted_symbols(PsSortKey::MarketCapDescending), ["BBB", "DDD", "AAA", "CCC"]);
+        // Missing growth data sorts last in both directions
+        assert_eq!(sorted_symbols(PsSortKey::RevenueGrowthAscending), ["CCC", "AAA", "DDD", "BBB"]);
+        assert_eq!(sorted_symbols(PsSortKey::RevenueGrowthDescending), ["DDD", "AAA", "CCC", "BBB"]);
+        assert_eq!(PsSortKey::default(), PsSortKey::PsAscending);
+    }
+}
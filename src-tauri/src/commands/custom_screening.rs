@@ -0,0 +1,744 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use crate::analysis::growth_consistency::annualized_revenue_sql;
+use crate::analysis::piotroski_history::{load_latest_piotroski_scores, piotroski_scores_sql};
+use crate::commands::screening_presets::{
+    delete_screening_preset_internal, load_typed_presets, save_screening_preset_internal, ScreenType,
+};
+use crate::commands::screening_snapshots::{save_screen_snapshot_internal, SnapshotItem};
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+use crate::tools::screen_exclusions::{count_exclusions, ExclusionCount, ExclusionReason, RatioNote, ScreenExclusion};
+use crate::tools::screening_readiness::{require_screen_ready, Screen};
+use ts_rs::TS;
+
+const DEFAULT_LIMIT: i32 = 100;
+const MAX_LIMIT: i32 = 1000;
+
+/// Metrics a custom screen may filter or sort on. Each maps to a fixed column of the
+/// metrics query, so user input never reaches the SQL text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenMetric {
+    MarketCap,
+    Pe,
+    Ps,
+    Pb,
+    EvEbitda,
+    /// Mean of the last three fiscal-year revenue growth rates, percent
+    #[serde(rename = "revenue_growth_3y")]
+    RevenueGrowth3y,
+    /// Latest annual operating cash flow less capex over market cap, percent
+    FcfYield,
+    PiotroskiScore,
+    DebtToEquity,
+    /// Latest annual dividends paid over market cap, percent
+    DividendYield,
+    /// Latest fiscal year's return on invested capital as of the last ratio recompute, percent
+    Roic,
+    Sector,
+}
+
+impl ScreenMetric {
+    fn column(self) -> &'static str {
+        match self {
+            ScreenMetric::MarketCap => "market_cap",
+            ScreenMetric::Pe => "pe",
+            ScreenMetric::Ps => "ps",
+            ScreenMetric::Pb => "pb",
+            ScreenMetric::EvEbitda => "ev_ebitda",
+            ScreenMetric::RevenueGrowth3y => "revenue_growth_3y",
+            ScreenMetric::FcfYield => "fcf_yield",
+            ScreenMetric::PiotroskiScore => "piotroski_score",
+            ScreenMetric::DebtToEquity => "debt_to_equity",
+            ScreenMetric::DividendYield => "dividend_yield",
+            ScreenMetric::Roic => "roic",
+            ScreenMetric::Sector => "sector",
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        self != ScreenMetric::Sector
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOp {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+    Neq,
+    /// Sector only, against a list of values
+    In,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(untagged)]
+pub enum FilterValue {
+    Number(f64),
+    Text(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreenFilter {
+    pub metric: ScreenMetric,
+    pub op: FilterOp,
+    pub value: FilterValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SortSpec {
+    pub metric: ScreenMetric,
+    pub descending: bool,
+}
+
+/// A named screen saved for reuse
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CustomScreen {
+    pub name: String,
+    pub filters: Vec<ScreenFilter>,
+    pub sort: Option<SortSpec>,
+    pub limit: Option<i32>,
+}
+
+/// Stored form of a saved screen; the name is the preset key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomScreenCriteria {
+    pub filters: Vec<ScreenFilter>,
+    pub sort: Option<SortSpec>,
+    pub limit: Option<i32>,
+}
+
+impl CustomScreenCriteria {
+    /// Reject filters that could never run
+    pub fn validate(&self) -> Result<(), CommandError> {
+        let mut scratch = String::new();
+        for filter in &self.filters {
+            push_filter(&mut scratch, &mut Vec::new(), filter)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreenMetricValue {
+    pub metric: ScreenMetric,
+    pub value: Option<f64>,
+    /// Why a P/E or P/B is empty when the sign of earnings or book value rules it out
+    pub note: Option<RatioNote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CustomScreenResult {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub company_name: String,
+    pub sector: Option<String>,
+    /// Every numeric metric referenced by the filters or sort, in first-referenced order
+    pub values: Vec<ScreenMetricValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CustomScreenResponse {
+    pub results: Vec<CustomScreenResult>,
+    /// Stocks passing every other filter that a P/E or P/B filter left out because earnings
+    /// or book value are negative
+    pub excluded: Vec<ScreenExclusion>,
+    pub exclusions: Vec<ExclusionCount>,
+}
+
+/// Latest value of every whitelisted metric per stock. F-Scores, as a derived table from
+/// `piotroski_scores_sql`, are only joined when a screen references them. P/E is empty when the latest fiscal year lost money, and P/B
+/// when book value is negative. Revenue growth compares revenue annualized by period length
+/// and is empty when a stub period falls in the last three years.
+fn metrics_query(piotroski_scores: Option<&str>) -> String {
+    let (piotroski_column, piotroski_join) = match piotroski_scores {
+        Some(scores) => ("CAST(pio.f_score_complete AS REAL)", format!("LEFT JOIN {} pio ON pio.stock_id = s.id", scores)),
+        None => ("NULL", String::new()),
+    };
+
+    format!(
+        "WITH latest_ratio AS (
+            SELECT stock_id, MAX(date) AS date FROM daily_valuation_ratios GROUP BY stock_id
+        ),
+        ratios AS (
+            SELECT d.stock_id, d.market_cap, d.pe_ratio_ttm, d.ps_ratio_ttm
+            FROM latest_ratio l JOIN daily_valuation_ratios d ON d.stock_id = l.stock_id AND d.date = l.date
+        ),
+        latest_price AS (
+            SELECT stock_id, MAX(date) AS date FROM daily_prices GROUP BY stock_id
+        ),
+        prices AS (
+            SELECT p.stock_id, p.market_cap
+            FROM latest_price l JOIN daily_prices p ON p.stock_id = l.stock_id AND p.date = l.date
+        ),
+        income AS (
+            SELECT stock_id, revenue, operating_income, net_income,
+                   LEAD(revenue, 1) OVER w AS revenue_1,
+                   LEAD(revenue, 2) OVER w AS revenue_2,
+                   LEAD(revenue, 3) OVER w AS revenue_3,
+                   julianday(report_date) - julianday(LEAD(report_date, 1) OVER w) AS period_days,
+                   julianday(LEAD(report_date, 1) OVER w) - julianday(LEAD(report_date, 2) OVER w) AS period_days_1,
+                   julianday(LEAD(report_date, 2) OVER w) - julianday(LEAD(report_date, 3) OVER w) AS period_days_2,
+                   julianday(LEAD(report_date, 3) OVER w) - julianday(LEAD(report_date, 4) OVER w) AS period_days_3,
+                   ROW_NUMBER() OVER w AS rn
+            FROM income_statements
+            WHERE period_type = 'FY' AND revenue IS NOT NULL
+            WINDOW w AS (PARTITION BY stock_id ORDER BY report_date DESC)
+        ),
+        balance AS (
+            SELECT stock_id, total_debt, total_equity, cash_and_equivalents,
+                   ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) AS rn
+            FROM balance_sheets
+            WHERE period_type = 'Annual'
+        ),
+        cash_flow AS (
+            SELECT stock_id, operating_cash_flow, capital_expenditures, dividends_paid,
+                   depreciation_expense, amortization_expense,
+                   ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY report_date DESC) AS rn
+            FROM cash_flow_statements
+            WHERE period_type = 'Annual' AND operating_cash_flow IS NOT NULL
+        ),
+        quality AS (
+            SELECT stock_id, roic,
+                   ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY fiscal_year DESC) AS rn
+            FROM quality_metrics
+        ),
+        base AS (
+            SELECT s.id AS stock_id, s.symbol, s.company_name, s.sector,
+                   COALESCE(r.market_cap, p.market_cap) AS market_cap,
+                   r.pe_ratio_ttm, r.ps_ratio_ttm,
+                   {revenue} AS revenue, {revenue_1} AS revenue_1, {revenue_2} AS revenue_2, {revenue_3} AS revenue_3,
+                   i.operating_income, i.net_income,
+                   b.total_debt, b.total_equity, b.cash_and_equivalents,
+                   cf.operating_cash_flow, cf.capital_expenditures, cf.dividends_paid,
+                   COALESCE(i.operating_income, 0) + COALESCE(cf.depreciation_expense, 0)
+                       + COALESCE(cf.amortization_expense, 0) AS ebitda,
+                   q.roic,
+                   {} AS piotroski_score
+            FROM stocks s
+            LEFT JOIN ratios r ON r.stock_id = s.id
+            LEFT JOIN prices p ON p.stock_id = s.id
+            LEFT JOIN income i ON i.stock_id = s.id AND i.rn = 1
+            LEFT JOIN balance b ON b.stock_id = s.id AND b.rn = 1
+            LEFT JOIN cash_flow cf ON cf.stock_id = s.id AND cf.rn = 1
+            LEFT JOIN quality q ON q.stock_id = s.id AND q.rn = 1
+            {}
+        ),
+        metrics AS (
+            SELECT stock_id, symbol, company_name, sector, market_cap, net_income, total_equity,
+                   CASE WHEN net_income IS NULL OR net_income > 0 THEN pe_ratio_ttm END AS pe,
+                   ps_ratio_ttm AS ps,
+                   CASE WHEN market_cap > 0 AND total_equity > 0 THEN market_cap / total_equity END AS pb,
+                   CASE WHEN market_cap > 0 AND operating_income IS NOT NULL AND ebitda > 0
+                        THEN (market_cap + COALESCE(total_debt, 0) - COALESCE(cash_and_equivalents, 0)) / ebitda END AS ev_ebitda,
+                   CASE WHEN revenue_1 > 0 AND revenue_2 > 0 AND revenue_3 > 0
+                        THEN ((revenue - revenue_1) / revenue_1 + (revenue_1 - revenue_2) / revenue_2
+                              + (revenue_2 - revenue_3) / revenue_3) / 3.0 * 100.0 END AS revenue_growth_3y,
+                   CASE WHEN market_cap > 0
+                        THEN (operating_cash_flow - ABS(COALESCE(capital_expenditures, 0))) / market_cap * 100.0 END AS fcf_yield,
+                   piotroski_score,
+                   CASE WHEN total_equity > 0 THEN total_debt / total_equity END AS debt_to_equity,
+                   CASE WHEN market_cap > 0 AND operating_cash_flow IS NOT NULL
+                        THEN ABS(COALESCE(dividends_paid, 0)) / market_cap * 100.0 END AS dividend_yield,
+                   roic
+            FROM base
+        )
+        SELECT * FROM metrics WHERE 1=1",
+        piotroski_column,
+        piotroski_join,
+        revenue = annualized_revenue_sql("i.revenue", "i.period_days"),
+        revenue_1 = annualized_revenue_sql("i.revenue_1", "i.period_days_1"),
+        revenue_2 = annualized_revenue_sql("i.revenue_2", "i.period_days_2"),
+        revenue_3 = annualized_revenue_sql("i.revenue_3", "i.period_days_3"),
+    )
+}
+
+/// Append one filter as a parameterized predicate. Filters are ANDed; an any_of group would
+/// compile its members the same way and join them with OR inside parentheses.
+fn push_filter(query: &mut String, params: &mut Vec<String>, filter: &ScreenFilter) -> Result<(), CommandError> {
+    let column = filter.metric.column();
+    let comparison = match filter.op {
+        FilterOp::Gt => ">",
+        FilterOp::Gte => ">=",
+        FilterOp::Lt => "<",
+        FilterOp::Lte => "<=",
+        FilterOp::Eq => "=",
+        FilterOp::Neq => "!=",
+        FilterOp::In => "IN",
+    };
+
+    match (&filter.value, filter.metric.is_numeric(), filter.op) {
+        (FilterValue::Number(value), true, op) if op != FilterOp::In => {
+            if !value.is_finite() {
+                return Err(CommandError::validation(column, "Value must be a finite number"));
+            }
+            query.push_str(&format!(" AND {} {} CAST(? AS REAL)", column, comparison));
+            params.push(value.to_string());
+        }
+        (FilterValue::Text(value), false, FilterOp::Eq | FilterOp::Neq) => {
+            query.push_str(&format!(" AND {} {} ?", column, comparison));
+            params.push(value.clone());
+        }
+        (FilterValue::List(values), false, FilterOp::In) if !values.is_empty() => {
+            let placeholders = values.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            query.push_str(&format!(" AND {} IN ({})", column, placeholders));
+            params.extend(values.iter().cloned());
+        }
+        _ => {
+            return Err(CommandError::validation(
+                column,
+                format!("{:?} {:?} is not a supported filter", filter.op, filter.value),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn run_custom_screen(
+    filters: Vec<ScreenFilter>,
+    sort: Option<SortSpec>,
+    limit: Option<i32>,
+    persist: Option<bool>,
+    include_negative_earnings: Option<bool>,
+    override_readiness: Option<bool>,
+) -> Result<CustomScreenResponse, CommandError> {
+    let pool = get_database_connection().await?;
+    require_screen_ready(&pool, Screen::Custom, override_readiness).await?;
+    if persist.unwrap_or(false) {
+        ensure_writable(&pool).await?;
+    }
+
+    let response =
+        run_custom_screen_internal(&pool, &filters, sort.as_ref(), limit, include_negative_earnings.unwrap_or(false)).await?;
+
+    if persist.unwrap_or(false) {
+        let sort_metric = sort.as_ref().map(|s| s.metric);
+        let criteria = CustomScreenCriteria { filters, sort, limit };
+        let snapshot_criteria = serde_json::to_value(&criteria).map_err(|e| CommandError::internal(e.to_string()))?;
+        let items: Vec<SnapshotItem> = response
+            .results
+            .iter()
+            .enumerate()
+            .map(|(i, row)| SnapshotItem {
+                stock_id: row.stock_id,
+                symbol: row.symbol.clone(),
+                rank: i as i32 + 1,
+                score: row.values.iter().find(|v| Some(v.metric) == sort_metric).and_then(|v| v.value),
+            })
+            .collect();
+        save_screen_snapshot_internal(&pool, ScreenType::Custom, &snapshot_criteria, &items).await?;
+    }
+    Ok(response)
+}
+
+/// P/E filters leave out companies whose latest fiscal year lost money unless
+/// `include_negative_earnings` is set, in which case they pass those filters with an empty
+/// P/E. P/B filters always leave out negative book value. Either way the stocks left out for
+/// the sign alone are listed in the response.
+pub async fn run_custom_screen_internal(
+    pool: &SqlitePool,
+    filters: &[ScreenFilter],
+    sort: Option<&SortSpec>,
+    limit: Option<i32>,
+    include_negative_earnings: bool,
+) -> Result<CustomScreenResponse, CommandError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(CommandError::validation("limit", format!("Must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    let mut referenced: Vec<ScreenMetric> = Vec::new();
+    for metric in filters.iter().map(|f| f.metric).chain(sort.map(|s| s.metric)) {
+        if metric.is_numeric() && !referenced.contains(&metric) {
+            referenced.push(metric);
+        }
+    }
+
+    let piotroski_scores = if referenced.contains(&ScreenMetric::PiotroskiScore) {
+        Some(piotroski_scores_sql(&load_latest_piotroski_scores(pool, None).await?))
+    } else {
+        None
+    };
+    let mut query = metrics_query(piotroski_scores.as_deref());
+    let mut params = Vec::new();
+    for filter in filters {
+        push_screen_filter(&mut query, &mut params, filter, include_negative_earnings)?;
+    }
+
+    // Stocks without the sort metric go last in either direction
+    match sort {
+        Some(sort) => query.push_str(&format!(
+            " ORDER BY {0} IS NULL, {0} {1}, symbol",
+            sort.metric.column(),
+            if sort.descending { "DESC" } else { "ASC" }
+        )),
+        None => query.push_str(" ORDER BY symbol"),
+    }
+    query.push_str(" LIMIT ?");
+    params.push(limit.to_string());
+
+    let mut sqlx_query = sqlx::query(&query);
+    for param in params {
+        sqlx_query = sqlx_query.bind(param);
+    }
+    let rows = sqlx_query.fetch_all(pool).await?;
+
+    let results = rows
+        .iter()
+        .map(|row| CustomScreenResult {
+            stock_id: row.get("stock_id"),
+            symbol: row.get("symbol"),
+            company_name: row.get("company_name"),
+            sector: row.get("sector"),
+            values: referenced
+                .iter()
+                .map(|metric| ScreenMetricValue {
+                    metric: *metric,
+                    value: row.get(metric.column()),
+                    note: match metric {
+                        ScreenMetric::Pe => RatioNote::for_pe(row.get("net_income")),
+                        ScreenMetric::Pb => RatioNote::for_pb(row.get("total_equity")),
+                        _ => None,
+                    },
+                })
+                .collect(),
+        })
+        .collect();
+
+    let excluded = load_sign_exclusions(pool, filters, piotroski_scores.as_deref(), include_negative_earnings).await?;
+    Ok(CustomScreenResponse {
+        results,
+        exclusions: count_exclusions(&excluded),
+        excluded,
+    })
+}
+
+/// `push_filter`, letting loss-making companies through P/E filters when they are included
+fn push_screen_filter(
+    query: &mut String,
+    params: &mut Vec<String>,
+    filter: &ScreenFilter,
+    include_negative_earnings: bool,
+) -> Result<(), CommandError> {
+    if filter.metric == ScreenMetric::Pe && include_negative_earnings {
+        let mut predicate = String::new();
+        push_filter(&mut predicate, params, filter)?;
+        query.push_str(&format!(" AND ({} OR net_income <= 0)", predicate.trim_start_matches(" AND ")));
+        Ok(())
+    } else {
+        push_filter(query, params, filter)
+    }
+}
+
+/// Stocks passing every filter other than P/E and P/B that those filters drop for negative
+/// earnings or book value
+async fn load_sign_exclusions(
+    pool: &SqlitePool,
+    filters: &[ScreenFilter],
+    piotroski_scores: Option<&str>,
+    include_negative_earnings: bool,
+) -> Result<Vec<ScreenExclusion>, CommandError> {
+    let filters_pe = !include_negative_earnings && filters.iter().any(|f| f.metric == ScreenMetric::Pe);
+    let filters_pb = filters.iter().any(|f| f.metric == ScreenMetric::Pb);
+    if !filters_pe && !filters_pb {
+        return Ok(Vec::new());
+    }
+
+    let mut query = metrics_query(piotroski_scores);
+    let mut params = Vec::new();
+    for filter in filters.iter().filter(|f| !matches!(f.metric, ScreenMetric::Pe | ScreenMetric::Pb)) {
+        push_filter(&mut query, &mut params, filter)?;
+    }
+    let sign_checks: Vec<&str> = [(filters_pe, "net_income <= 0"), (filters_pb, "total_equity <= 0")]
+        .into_iter()
+        .filter_map(|(applies, check)| applies.then_some(check))
+        .collect();
+    query.push_str(&format!(" AND ({}) ORDER BY symbol", sign_checks.join(" OR ")));
+
+    let mut sqlx_query = sqlx::query(&query);
+    for param in params {
+        sqlx_query = sqlx_query.bind(param);
+    }
+    let rows = sqlx_query.fetch_all(pool).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let total_equity: Option<f64> = row.get("total_equity");
+            let reason = if filters_pb && total_equity.is_some_and(|equity| equity <= 0.0) {
+                ExclusionReason::NegativeEquity
+            } else {
+                ExclusionReason::NegativeEarnings
+            };
+            ScreenExclusion { stock_id: row.get("stock_id"), symbol: row.get("symbol"), reason }
+        })
+        .collect())
+}
+
+// Saved screens live in the shared screening presets; these commands wrap them
+
+#[tauri::command]
+pub async fn list_custom_screens() -> Result<Vec<CustomScreen>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    load_custom_screens(&pool).await
+}
+
+/// Save a screen, replacing any with the same name; returns all saved screens
+#[tauri::command]
+pub async fn save_custom_screen(screen: CustomScreen) -> Result<Vec<CustomScreen>, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    save_custom_screen_internal(&pool, screen).await
+}
+
+#[tauri::command]
+pub async fn delete_custom_screen(name: String) -> Result<Vec<CustomScreen>, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    delete_screening_preset_internal(&pool, ScreenType::Custom, &name).await?;
+    load_custom_screens(&pool).await
+}
+
+pub async fn save_custom_screen_internal(pool: &SqlitePool, screen: CustomScreen) -> Result<Vec<CustomScreen>, CommandError> {
+    let criteria = CustomScreenCriteria { filters: screen.filters, sort: screen.sort, limit: screen.limit };
+    let criteria = serde_json::to_value(criteria).map_err(|e| CommandError::internal(e.to_string()))?;
+
+    save_screening_preset_internal(pool, ScreenType::Custom, &screen.name, &criteria).await?;
+    load_custom_screens(pool).await
+}
+
+pub async fn load_custom_screens(pool: &SqlitePool) -> Result<Vec<CustomScreen>, CommandError> {
+    Ok(load_typed_presets::<CustomScreenCriteria>(pool, ScreenType::Custom)
+        .await?
+        .into_iter()
+        .map(|(name, criteria)| CustomScreen { name, filters: criteria.filters, sort: criteria.sort, limit: criteria.limit })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, sector) VALUES
+                (1, 'VAL', 'Value Co', 'Industrials'),
+                (2, 'GRO', 'Growth Co', 'Technology'),
+                (3, 'PRI', 'Pricey Co', 'Industrials')"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO daily_valuation_ratios (stock_id, date, market_cap, pe_ratio_ttm, ps_ratio_ttm, data_completeness_score) VALUES
+                (1, '2024-06-01', 1000.0, 9.0, 0.8, 100),
+                (2, '2024-06-01', 5000.0, 15.0, 6.0, 100),
+                (3, '2024-05-01', 2000.0, 12.0, 1.0, 100),
+                (3, '2024-06-01', 2000.0, 40.0, 3.0, 100)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_debt, total_equity) VALUES
+                (1, 'Annual', '2023-12-31', 2023, 250.0, 500.0)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, operating_cash_flow,
+                capital_expenditures, dividends_paid) VALUES
+                (1, 'Annual', '2023-12-31', 2023, 150.0, -50.0, -30.0)"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_metric_names_match_columns() {
+        for metric in [ScreenMetric::MarketCap, ScreenMetric::RevenueGrowth3y, ScreenMetric::PiotroskiScore] {
+            assert_eq!(serde_json::to_value(metric).unwrap(), metric.column());
+        }
+    }
+
+    fn number(metric: ScreenMetric, op: FilterOp, value: f64) -> ScreenFilter {
+        ScreenFilter { metric, op, value: FilterValue::Number(value) }
+    }
+
+    #[tokio::test]
+    async fn test_filters_compile_to_parameterized_and() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+
+        // Uses each stock's latest ratio row: PRI's P/E is 40, not 12
+        let filters = vec![
+            number(ScreenMetric::Pe, FilterOp::Lt, 20.0),
+            ScreenFilter {
+                metric: ScreenMetric::Sector,
+                op: FilterOp::In,
+                value: FilterValue::List(vec!["Industrials".to_string(), "Technology".to_string()]),
+            },
+        ];
+        let sort = SortSpec { metric: ScreenMetric::Ps, descending: true };
+        let results = run_custom_screen_internal(&pool, &filters, Some(&sort), None, false).await.unwrap().results;
+        assert_eq!(results.iter().map(|r| r.symbol.as_str()).collect::<Vec<_>>(), vec!["GRO", "VAL"]);
+        assert_eq!(
+            results[1].values,
+            vec![
+                ScreenMetricValue { metric: ScreenMetric::Pe, value: Some(9.0), note: None },
+                ScreenMetricValue { metric: ScreenMetric::Ps, value: Some(0.8), note: None },
+            ]
+        );
+
+        // Derived statement metrics: FCF 100 and dividends 30 on a 1000 market cap
+        let filters = vec![
+            number(ScreenMetric::FcfYield, FilterOp::Gte, 10.0),
+            number(ScreenMetric::DebtToEquity, FilterOp::Lte, 0.5),
+        ];
+        let results = run_custom_screen_internal(&pool, &filters, None, None, false).await.unwrap().results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol, "VAL");
+        assert_eq!(results[0].values[0].value, Some(10.0));
+        assert_eq!(results[0].values[1].value, Some(0.5));
+
+        // ROIC from the latest stored quality year: GRO's improved to 18%, VAL's fell to 9%
+        sqlx::query(
+            "INSERT INTO quality_metrics (stock_id, fiscal_year, report_date, roic) VALUES
+                (1, 2022, '2022-12-31', 20.0), (1, 2023, '2023-12-31', 9.0),
+                (2, 2022, '2022-12-31', 11.0), (2, 2023, '2023-12-31', 18.0)"
+        )
+        .execute(&pool).await.unwrap();
+        let results = run_custom_screen_internal(&pool, &[number(ScreenMetric::Roic, FilterOp::Gt, 15.0)], None, None, false)
+            .await.unwrap().results;
+        assert_eq!(results.iter().map(|r| (r.symbol.as_str(), r.values[0].value)).collect::<Vec<_>>(), vec![("GRO", Some(18.0))]);
+
+        // User values are bound, never spliced into the SQL
+        let injection = vec![ScreenFilter {
+            metric: ScreenMetric::Sector,
+            op: FilterOp::Eq,
+            value: FilterValue::Text("x' OR '1'='1".to_string()),
+        }];
+        assert!(run_custom_screen_internal(&pool, &injection, None, None, false).await.unwrap().results.is_empty());
+
+        let mismatched = vec![ScreenFilter {
+            metric: ScreenMetric::Pe,
+            op: FilterOp::Lt,
+            value: FilterValue::Text("20".to_string()),
+        }];
+        assert!(matches!(
+            run_custom_screen_internal(&pool, &mismatched, None, None, false).await,
+            Err(CommandError::Validation { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_negative_earnings_and_book_value_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name, sector) VALUES (4, 'LOSS', 'Loss Co', 'Industrials'), (5, 'UNDR', 'Underwater Co', 'Industrials')")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO daily_valuation_ratios (stock_id, date, market_cap, pe_ratio_ttm, ps_ratio_ttm, data_completeness_score) VALUES
+                (4, '2024-06-01', 800.0, NULL, 0.5, 100),
+                (5, '2024-06-01', 900.0, 11.0, 0.7, 100)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income) VALUES
+                (4, 'FY', '2023-12-31', 2023, 1000.0, -40.0)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_debt, total_equity) VALUES
+                (5, 'Annual', '2023-12-31', 2023, 300.0, -100.0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        // GRO has no balance sheet, so no P/B; that is missing data, not a sign exclusion
+        let filters = vec![number(ScreenMetric::Pe, FilterOp::Lt, 20.0), number(ScreenMetric::Pb, FilterOp::Lt, 5.0)];
+        let screen = run_custom_screen_internal(&pool, &filters, None, None, false).await.unwrap();
+        assert_eq!(screen.results.iter().map(|r| r.symbol.as_str()).collect::<Vec<_>>(), vec!["VAL"]);
+        assert_eq!(
+            screen.excluded,
+            vec![
+                ScreenExclusion { stock_id: 4, symbol: "LOSS".to_string(), reason: ExclusionReason::NegativeEarnings },
+                ScreenExclusion { stock_id: 5, symbol: "UNDR".to_string(), reason: ExclusionReason::NegativeEquity },
+            ]
+        );
+        assert_eq!(
+            screen.exclusions,
+            vec![
+                ExclusionCount { reason: ExclusionReason::NegativeEarnings, count: 1 },
+                ExclusionCount { reason: ExclusionReason::NegativeEquity, count: 1 },
+            ]
+        );
+
+        // Included loss-makers pass the P/E filter without a P/E; negative book is marked, not negative
+        let filters = vec![number(ScreenMetric::Pe, FilterOp::Lt, 20.0)];
+        let sort = SortSpec { metric: ScreenMetric::Pb, descending: false };
+        let screen = run_custom_screen_internal(&pool, &filters, Some(&sort), None, true).await.unwrap();
+        assert_eq!(screen.results.iter().map(|r| r.symbol.as_str()).collect::<Vec<_>>(), vec!["VAL", "GRO", "LOSS", "UNDR"]);
+        assert_eq!(
+            screen.results[2].values,
+            vec![
+                ScreenMetricValue { metric: ScreenMetric::Pe, value: None, note: Some(RatioNote::NegativeEarnings) },
+                ScreenMetricValue { metric: ScreenMetric::Pb, value: None, note: None },
+            ]
+        );
+        assert_eq!(
+            screen.results[3].values,
+            vec![
+                ScreenMetricValue { metric: ScreenMetric::Pe, value: Some(11.0), note: None },
+                ScreenMetricValue { metric: ScreenMetric::Pb, value: None, note: Some(RatioNote::NegativeBookValue) },
+            ]
+        );
+        assert!(screen.excluded.is_empty() && screen.exclusions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_saved_screens_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+        assert!(load_custom_screens(&pool).await.unwrap().is_empty());
+
+        let screen = CustomScreen {
+            name: " Cheap industrials ".to_string(),
+            filters: vec![number(ScreenMetric::Pe, FilterOp::Lt, 15.0)],
+            sort: Some(SortSpec { metric: ScreenMetric::Pe, descending: false }),
+            limit: Some(25),
+        };
+        save_custom_screen_internal(&pool, screen.clone()).await.unwrap();
+        // Saving under the same name replaces it
+        let replaced = CustomScreen { limit: Some(50), ..screen };
+        let saved = save_custom_screen_internal(&pool, replaced).await.unwrap();
+
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].name, "Cheap industrials");
+        assert_eq!(saved[0].limit, Some(50));
+        assert_eq!(load_custom_screens(&pool).await.unwrap(), saved);
+
+        let invalid = CustomScreen {
+            name: "Broken".to_string(),
+            filters: vec![number(ScreenMetric::Sector, FilterOp::Gt, 1.0)],
+            sort: None,
+            limit: None,
+        };
+        assert!(save_custom_screen_internal(&pool, invalid).await.is_err());
+    }
+}
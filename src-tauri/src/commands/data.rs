@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, Row};
+use crate::error::CommandError;
+use crate::database::helpers::ensure_writable;
+use crate::tools::data_refresh_orchestrator::RefreshRunningGuard;
+use crate::tools::query_cache::{global_query_cache, CacheStats, CachedQuery};
+use crate::tools::ratio_recompute::{self, RatioRecomputeProgress};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseStats {
@@ -14,13 +19,20 @@ async fn get_database_connection() -> Result<SqlitePool, String> {
     crate::database::helpers::get_database_connection().await
 }
 
+/// Served from the query cache for up to its TTL, or until the next refresh or price insert
 #[tauri::command]
-pub async fn get_database_stats() -> Result<DatabaseStats, String> {
+pub async fn get_database_stats() -> Result<DatabaseStats, CommandError> {
     let pool = get_database_connection().await?;
-    
+
+    global_query_cache()
+        .get_or_compute(CachedQuery::DatabaseStats, || get_database_stats_internal(&pool))
+        .await
+}
+
+pub async fn get_database_stats_internal(pool: &SqlitePool) -> Result<DatabaseStats, CommandError> {
     // Get total stocks count
     let stocks_count = match sqlx::query("SELECT COUNT(*) as count FROM stocks")
-        .fetch_one(&pool).await 
+        .fetch_one(pool).await 
     {
         Ok(row) => row.get::<i64, _>("count") as usize,
         Err(_) => 0,
@@ -28,7 +40,7 @@ pub async fn get_database_stats() -> Result<DatabaseStats, String> {
     
     // Get total price records count
     let price_records_count = match sqlx::query("SELECT COUNT(*) as count FROM daily_prices")
-        .fetch_one(&pool).await 
+        .fetch_one(pool).await 
     {
         Ok(row) => row.get::<i64, _>("count") as usize,
         Err(_) => 0,
@@ -36,7 +48,7 @@ pub async fn get_database_stats() -> Result<DatabaseStats, String> {
     
     // Get latest update date
     let last_update = match sqlx::query("SELECT MAX(date) as latest_date FROM daily_prices")
-        .fetch_one(&pool).await 
+        .fetch_one(pool).await 
     {
         Ok(row) => {
             match row.try_get::<Option<String>, _>("latest_date") {
@@ -52,7 +64,7 @@ pub async fn get_database_stats() -> Result<DatabaseStats, String> {
         // Get stocks with data
         let stocks_with_data = match sqlx::query(
             "SELECT COUNT(DISTINCT stock_id) as count FROM daily_prices"
-        ).fetch_one(&pool).await {
+        ).fetch_one(pool).await {
             Ok(row) => row.get::<i64, _>("count") as f64,
             Err(_) => 0.0,
         };
@@ -70,11 +82,243 @@ pub async fn get_database_stats() -> Result<DatabaseStats, String> {
     })
 }
 
+/// Read-only freshness report built from stored data; never downloads or writes statements.
+/// Cached like get_database_stats.
+#[tauri::command]
+pub async fn get_data_freshness_status() -> Result<crate::tools::freshness_types::SystemFreshnessReport, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_cached_data_freshness_status(&pool).await
+}
+
+pub async fn get_cached_data_freshness_status(pool: &SqlitePool) -> Result<crate::tools::freshness_types::SystemFreshnessReport, CommandError> {
+    global_query_cache()
+        .get_or_compute(CachedQuery::DataFreshnessStatus, || get_data_freshness_status_internal(pool))
+        .await
+}
+
+/// Hit, miss and invalidation counts of the query cache, for debugging
+#[tauri::command]
+pub async fn get_cache_stats() -> Result<CacheStats, CommandError> {
+    Ok(global_query_cache().stats())
+}
+
+pub async fn get_data_freshness_status_internal(pool: &SqlitePool) -> Result<crate::tools::freshness_types::SystemFreshnessReport, CommandError> {
+    let reader = crate::tools::freshness_checker::DataStatusReader::new(pool.clone());
+
+    Ok(reader.check_system_freshness().await?)
+}
+
+/// Data coverage checks for valuation analysis and each screen, with the issues blocking them
+#[tauri::command]
+pub async fn check_screening_readiness() -> Result<crate::tools::freshness_types::ScreeningReadiness, CommandError> {
+    let pool = get_database_connection().await?;
+    let policy = crate::tools::freshness_policy::load_freshness_policy(&pool).await?;
+    let today = chrono::Local::now().date_naive();
+
+    Ok(crate::tools::screening_readiness::evaluate_screening_readiness(&pool, &policy, today).await?)
+}
+
+/// S&P 500 stocks behind on SEC filings and with no close in the last `staleness_days` days
+/// (the market data policy's current window by default), each with its reasons
+#[tauri::command]
+pub async fn get_stale_stocks(staleness_days: Option<i64>) -> Result<Vec<crate::tools::stale_stocks::StaleStock>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let staleness_days = match staleness_days {
+        Some(days) => days,
+        None => crate::tools::freshness_policy::load_freshness_policy(&pool).await?.market_data.current_days,
+    };
+    if staleness_days < 0 {
+        return Err(CommandError::validation("staleness_days", "Must not be negative"));
+    }
+    let today = chrono::Local::now().date_naive();
+
+    Ok(crate::tools::stale_stocks::find_stale_stocks(&pool, staleness_days, today).await?)
+}
+
+/// daily_prices rows whose values conflict: duplicate days the UNIQUE constraint missed,
+/// impossible ranges, and market caps or valuation prices that disagree with the close
+#[tauri::command]
+pub async fn find_conflicting_prices() -> Result<Vec<crate::tools::price_conflicts::PriceConflict>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    match crate::tools::price_conflicts::find_price_conflicts(&pool).await {
+        Ok(conflicts) => Ok(conflicts),
+        Err(e) => {
+            eprintln!("Price conflict check error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Estimated duration of the next market and financial refresh, from recent runs and the stocks now stale
+#[tauri::command]
+pub async fn get_refresh_duration_estimates() -> Result<crate::tools::refresh_estimates::RefreshDurationEstimates, CommandError> {
+    let pool = get_database_connection().await?;
+    let policy = crate::tools::freshness_policy::load_freshness_policy(&pool).await?;
+    let today = chrono::Local::now().date_naive();
+
+    Ok(crate::tools::refresh_estimates::estimate_refresh_durations(&pool, &policy, today).await?)
+}
+
+/// Most recent refresh runs, newest first
+#[tauri::command]
+pub async fn get_refresh_history(limit: Option<i64>) -> Result<Vec<crate::tools::refresh_runs::RefreshRunSummary>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    Ok(crate::tools::refresh_runs::load_refresh_history(&pool, limit.unwrap_or(20).max(1)).await?)
+}
+
+/// One refresh run with the stocks that failed during it
+#[tauri::command]
+pub async fn get_refresh_run_details(run_id: String) -> Result<crate::tools::refresh_runs::RefreshRunDetails, CommandError> {
+    let pool = get_database_connection().await?;
+
+    Ok(crate::tools::refresh_runs::load_refresh_run_details(&pool, &run_id).await?)
+}
+
+/// Fill missing first trading dates from stored prices; returns the number of stocks updated
+#[tauri::command]
+pub async fn backfill_first_trading_dates() -> Result<u64, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    let updated = crate::database::helpers::backfill_first_trading_dates(&pool).await?;
+    global_query_cache().invalidate_all();
+    Ok(updated)
+}
+
+/// Rebuild stored ratios for the whole S&P 500 universe. Pass a session id to poll
+/// get_ratio_recompute_progress or cancel while it runs; one is generated otherwise.
+/// Refused while a data refresh runs.
+#[tauri::command]
+pub async fn recompute_all_ratios(session_id: Option<String>) -> Result<RatioRecomputeProgress, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+    let running = RefreshRunningGuard::acquire(&pool, "ratio recompute").await?;
+    let session_id = session_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let progress = ratio_recompute::recompute_all_ratios(&pool, &session_id).await;
+    running.release().await;
+    let progress = progress?;
+    global_query_cache().invalidate_all();
+    Ok(progress)
+}
+
+#[tauri::command]
+pub async fn get_ratio_recompute_progress(session_id: String) -> Result<Option<RatioRecomputeProgress>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    Ok(ratio_recompute::load_ratio_recompute_progress(&pool, &session_id).await?)
+}
+
+/// Stop a running recompute after its current chunk; false if it isn't running
+#[tauri::command]
+pub async fn cancel_ratio_recompute(session_id: String) -> Result<bool, CommandError> {
+    let pool = get_database_connection().await?;
+
+    Ok(ratio_recompute::cancel_ratio_recompute(&pool, &session_id).await?)
+}
+
+/// Fetch the USD exchange rates stored prices and statements are missing, so screens can
+/// compare non-USD market caps; returns the number of rates stored
+#[tauri::command]
+pub async fn update_fx_rates() -> Result<u64, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    let stored = crate::tools::fx_rates::update_fx_rates(&pool, crate::tools::fx_rates::FRANKFURTER_BASE_URL)
+        .await
+        .map_err(|e| CommandError::external_api("frankfurter", None, e.to_string()))?;
+    global_query_cache().invalidate_all();
+    Ok(stored)
+}
+
+/// Integrity check and ANALYZE, plus VACUUM when `vacuum` is set, with rows and size per table.
+/// VACUUM can take minutes on a large database and needs about its size in free disk space.
+/// Refused while a data refresh runs.
+#[tauri::command]
+pub async fn run_database_maintenance(
+    vacuum: Option<bool>,
+) -> Result<crate::tools::database_maintenance::DatabaseMaintenanceReport, CommandError> {
+    let pool = get_database_connection().await?;
+
+    run_database_maintenance_internal(&pool, vacuum.unwrap_or(false)).await
+}
+
+/// Maintenance under the refresh lock, held until it finishes so no refresh starts mid-VACUUM
+pub async fn run_database_maintenance_internal(
+    pool: &SqlitePool,
+    vacuum: bool,
+) -> Result<crate::tools::database_maintenance::DatabaseMaintenanceReport, CommandError> {
+    ensure_writable(pool).await?;
+    let running = RefreshRunningGuard::acquire(pool, "database maintenance")
+        .await
+        .map_err(|e| match e {
+            CommandError::DatabaseBusy { .. } => CommandError::DatabaseBusy {
+                message: "A data refresh is running; run maintenance after it finishes".to_string(),
+            },
+            e => e,
+        })?;
+
+    let report = crate::tools::database_maintenance::run_database_maintenance(pool, vacuum).await;
+    running.release().await;
+    Ok(report?)
+}
+
+/// Extract SEC fundamentals for the stocks matching `filter`, not only the S&P 500. Stocks
+/// without a CIK are skipped; failures are listed per stock rather than ending the run.
+#[tauri::command]
+pub async fn refresh_fundamentals_for(
+    filter: crate::tools::freshness_types::StockFilter,
+) -> Result<crate::tools::freshness_types::FundamentalsRefreshResult, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+    let running = RefreshRunningGuard::acquire(&pool, "fundamentals refresh").await?;
+
+    let result = refresh_fundamentals_for_internal(crate::tools::freshness_checker::DataStatusReader::new(pool), &filter).await;
+    running.release().await;
+    global_query_cache().invalidate_all();
+    Ok(result?)
+}
+
+pub async fn refresh_fundamentals_for_internal(
+    reader: crate::tools::freshness_checker::DataStatusReader,
+    filter: &crate::tools::freshness_types::StockFilter,
+) -> Result<crate::tools::freshness_types::FundamentalsRefreshResult, CommandError> {
+    let stocks = reader.get_stocks_with_ciks(filter).await?;
+    if stocks.is_empty() {
+        return Err(CommandError::validation("filter", "No stocks with a CIK match the filter"));
+    }
+
+    let (records_stored, errors) = reader.run_unified_financials_for_stocks(&stocks).await?;
+    Ok(crate::tools::freshness_types::FundamentalsRefreshResult {
+        stocks_processed: stocks.len() as i64,
+        records_stored,
+        errors,
+    })
+}
+
+/// Per-stock price coverage flags by year (or quarter) up to `target_date`, with per-period totals
+#[tauri::command]
+pub async fn get_coverage_heatmap(
+    target_date: String,
+    granularity: Option<crate::tools::data_coverage::CoverageGranularity>,
+) -> Result<crate::tools::data_coverage::CoverageHeatmap, CommandError> {
+    let pool = get_database_connection().await?;
+    let target_date = chrono::NaiveDate::parse_from_str(&target_date, "%Y-%m-%d")
+        .map_err(|e| CommandError::validation("target_date", format!("Invalid target date format: {}", e)))?;
+
+    Ok(crate::tools::data_coverage::compute_coverage_heatmap(&pool, target_date, granularity.unwrap_or_default()).await?)
+}
+
 #[cfg(test)]
 mod tests {
     use sqlx::{SqlitePool, pool::PoolOptions};
     use std::time::Duration;
     use anyhow::Result;
+    use crate::tests::fixtures::FixtureBuilder;
 
     /// Simple test database setup for data module tests
     struct TestDatabase {
@@ -115,4 +359,39 @@ mod tests {
         println!("✅ Database stats test passed: {} stocks, {} price records, {:.1}% coverage",
                  stats.total_stocks, stats.total_price_records, stats.data_coverage_percentage);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_freshness_status_does_not_write() {
+        let fixture = FixtureBuilder::new()
+            .with_stock("AAPL").with_cik("0000320193").with_sp500_flag()
+            .with_stock("MSFT").with_cik("0000789019").with_sp500_flag()
+            .build()
+            .await;
+        let pool = &fixture.pool;
+
+        let report = super::get_data_freshness_status_internal(pool).await.unwrap();
+        assert_eq!(report.financial_data.data_summary.stock_count, Some(2));
+
+        for table in ["sec_filings", "income_statements", "balance_sheets", "cash_flow_statements"] {
+            let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+                .fetch_one(pool).await.unwrap();
+            assert_eq!(count, 0, "{} should not be written by a status check", table);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_is_refused_while_a_refresh_holds_the_lock() {
+        let fixture = FixtureBuilder::new().with_stock("AAPL").build().await;
+        let pool = &fixture.pool;
+
+        let refresh = crate::tools::data_refresh_orchestrator::RefreshRunningGuard::acquire(pool, "data refresh").await.unwrap();
+        let busy = super::run_database_maintenance_internal(pool, false).await;
+        assert!(matches!(busy, Err(crate::error::CommandError::DatabaseBusy { .. })));
+        refresh.release().await;
+
+        let report = super::run_database_maintenance_internal(pool, false).await.unwrap();
+        assert!(report.integrity_ok);
+        // Maintenance gave the lock back
+        assert!(!crate::tools::data_refresh_orchestrator::is_refresh_running(pool).await.unwrap());
+    }
+}
@@ -0,0 +1,575 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use std::collections::BTreeSet;
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+use crate::tools::filing_reactions::{self, FilingReaction};
+use crate::tools::sec_edgar_client::{SecEdgarClient, SubmissionsResponse};
+use crate::tools::shares_history::{self, ShareCountDiscontinuity};
+use ts_rs::TS;
+
+/// Annual report forms we store in sec_filings
+const TRACKED_FORMS: [&str; 2] = ["10-K", "10-K/A"];
+
+/// Imbalance, as a percent of total assets, above which a balance sheet is flagged
+const DEFAULT_IDENTITY_TOLERANCE_PCT: f64 = 1.0;
+
+/// Filing-date comparison between our sec_filings rows and the SEC Submissions API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FilingDiff {
+    pub symbol: String,
+    pub cik: String,
+    /// Filed at SEC but not in our database
+    pub missing_locally: Vec<String>,
+    /// In our database but not listed by SEC (possibly superseded)
+    pub missing_at_sec: Vec<String>,
+    pub matched: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn diff_filings_vs_sec(symbol: String) -> Result<FilingDiff, CommandError> {
+    let pool = get_database_connection().await?;
+    let mut client = SecEdgarClient::new(pool.clone());
+
+    diff_filings_vs_sec_internal(&pool, &mut client, &symbol).await
+}
+
+pub async fn diff_filings_vs_sec_internal(
+    pool: &SqlitePool,
+    client: &mut SecEdgarClient,
+    symbol: &str,
+) -> Result<FilingDiff, CommandError> {
+    let (stock_id, cik) = resolve_cik(pool, symbol).await?;
+
+    let sql = format!(
+        "SELECT DISTINCT filed_date FROM sec_filings WHERE stock_id = ? AND form_type IN ({})",
+        vec!["?"; TRACKED_FORMS.len()].join(", ")
+    );
+    let mut query = sqlx::query(&sql).bind(stock_id);
+    for form in TRACKED_FORMS {
+        query = query.bind(form);
+    }
+    let our_rows = query.fetch_all(pool).await?;
+    let our_dates: Vec<String> = our_rows.iter().map(|row| row.get::<String, _>("filed_date")).collect();
+
+    // fetch_company_submissions waits on the client's rate limiter
+    let submissions = client
+        .fetch_company_submissions(&cik)
+        .await
+        .map_err(|e| CommandError::external_api("sec_edgar", None, e.to_string()))?;
+
+    Ok(compute_filing_diff(symbol, &cik, &sec_filing_dates(&submissions), &our_dates))
+}
+
+/// Stock id and CIK of `symbol`
+async fn resolve_cik(pool: &SqlitePool, symbol: &str) -> Result<(i64, String), CommandError> {
+    let stock = sqlx::query("SELECT id, cik FROM stocks WHERE symbol = ?")
+        .bind(symbol)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("symbol", format!("Unknown symbol: {}", symbol)))?;
+
+    let cik = stock
+        .get::<Option<String>, _>("cik")
+        .ok_or_else(|| CommandError::not_found("cik", format!("No CIK mapped for {}", symbol)))?;
+    Ok((stock.get("id"), cik))
+}
+
+/// Filing dates of the annual report forms listed in a submissions response
+fn sec_filing_dates(submissions: &SubmissionsResponse) -> Vec<String> {
+    let recent = &submissions.filings.recent;
+    recent
+        .form
+        .iter()
+        .zip(&recent.filing_date)
+        .filter(|(form, _)| TRACKED_FORMS.contains(&form.as_str()))
+        .map(|(_, date)| date.clone())
+        .collect()
+}
+
+fn compute_filing_diff(symbol: &str, cik: &str, sec_dates: &[String], our_dates: &[String]) -> FilingDiff {
+    let sec: BTreeSet<&String> = sec_dates.iter().collect();
+    let ours: BTreeSet<&String> = our_dates.iter().collect();
+
+    FilingDiff {
+        symbol: symbol.to_string(),
+        cik: cik.to_string(),
+        missing_locally: sec.difference(&ours).map(|d| d.to_string()).collect(),
+        missing_at_sec: ours.difference(&sec).map(|d| d.to_string()).collect(),
+        matched: sec.intersection(&ours).map(|d| d.to_string()).collect(),
+    }
+}
+
+/// Company Facts payloads run from a few MB to tens of MB for large filers; this is
+/// what `fetch_raw_company_facts` returns unless asked for more
+pub const DEFAULT_RAW_FACTS_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+/// Source Company Facts JSON of a stock, for checking an extraction by hand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RawCompanyFacts {
+    pub symbol: String,
+    pub cik: String,
+    /// Size of the full (possibly pretty-printed) JSON
+    pub total_bytes: i64,
+    /// True when `json` was cut at the size cap and no longer parses
+    pub truncated: bool,
+    pub json: String,
+}
+
+/// Diagnostic: fetch a stock's companyfacts JSON from SEC as-is. The payload is large, so it
+/// is cut at `max_bytes` (default `DEFAULT_RAW_FACTS_MAX_BYTES`).
+#[tauri::command]
+pub async fn fetch_raw_company_facts(
+    symbol: String,
+    pretty: Option<bool>,
+    max_bytes: Option<usize>,
+) -> Result<RawCompanyFacts, CommandError> {
+    let pool = get_database_connection().await?;
+    let mut client = SecEdgarClient::new(pool.clone());
+
+    fetch_raw_company_facts_internal(&pool, &mut client, &symbol, pretty.unwrap_or(false), max_bytes.unwrap_or(DEFAULT_RAW_FACTS_MAX_BYTES)).await
+}
+
+pub async fn fetch_raw_company_facts_internal(
+    pool: &SqlitePool,
+    client: &mut SecEdgarClient,
+    symbol: &str,
+    pretty: bool,
+    max_bytes: usize,
+) -> Result<RawCompanyFacts, CommandError> {
+    if max_bytes == 0 {
+        return Err(CommandError::validation("max_bytes", "Must be positive"));
+    }
+    let (_, cik) = resolve_cik(pool, symbol).await?;
+
+    // fetch_company_facts_raw waits on the client's rate limiter
+    let mut json = client
+        .fetch_company_facts_raw(&cik)
+        .await
+        .map_err(|e| CommandError::external_api("sec_edgar", None, e.to_string()))?;
+
+    if pretty {
+        let value: serde_json::Value = serde_json::from_str(&json)
+            .map_err(|e| CommandError::external_api("sec_edgar", None, format!("Company facts for CIK {} are not valid JSON: {}", cik, e)))?;
+        json = serde_json::to_string_pretty(&value).map_err(|e| CommandError::internal(e.to_string()))?;
+    }
+
+    let total_bytes = json.len() as i64;
+    let truncated = json.len() > max_bytes;
+    if truncated {
+        let mut end = max_bytes;
+        while !json.is_char_boundary(end) {
+            end -= 1;
+        }
+        json.truncate(end);
+    }
+
+    Ok(RawCompanyFacts { symbol: symbol.to_string(), cik, total_bytes, truncated, json })
+}
+
+/// A stock that received new filings or statement rows since a point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdatedFundamentals {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// Statement tables that got new rows: "income", "balance", "cash_flow"
+    pub statement_types: Vec<String>,
+    pub latest_filed_date: Option<String>,
+    pub last_stored_at: Option<String>,
+}
+
+#[tauri::command]
+pub async fn get_recently_updated_fundamentals(since: DateTime<Utc>) -> Result<Vec<UpdatedFundamentals>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_recently_updated_fundamentals_internal(&pool, since).await
+}
+
+pub async fn get_recently_updated_fundamentals_internal(
+    pool: &SqlitePool,
+    since: DateTime<Utc>,
+) -> Result<Vec<UpdatedFundamentals>, CommandError> {
+    // inserted_at is stamped by triggers as UTC 'YYYY-MM-DD HH:MM:SS.SSS'
+    let since_timestamp = since.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+    let since_date = since.format("%Y-%m-%d").to_string();
+
+    let rows = sqlx::query(
+        "WITH changes AS (
+            SELECT stock_id, 'income' AS statement_type, inserted_at FROM income_statements WHERE inserted_at > ?1
+            UNION ALL
+            SELECT stock_id, 'balance', inserted_at FROM balance_sheets WHERE inserted_at > ?1
+            UNION ALL
+            SELECT stock_id, 'cash_flow', inserted_at FROM cash_flow_statements WHERE inserted_at > ?1
+            UNION ALL
+            SELECT stock_id, NULL, inserted_at FROM sec_filings WHERE inserted_at > ?1 OR filed_date > ?2
+        )
+        SELECT
+            s.id AS stock_id,
+            s.symbol,
+            GROUP_CONCAT(DISTINCT c.statement_type) AS statement_types,
+            MAX(c.inserted_at) AS last_stored_at,
+            (SELECT MAX(f.filed_date) FROM sec_filings f WHERE f.stock_id = s.id) AS latest_filed_date
+        FROM changes c
+        JOIN stocks s ON s.id = c.stock_id
+        GROUP BY s.id, s.symbol
+        ORDER BY s.symbol"
+    )
+    .bind(&since_timestamp)
+    .bind(&since_date)
+    .fetch_all(pool)
+    .await?;
+
+    let results = rows
+        .iter()
+        .map(|row| {
+            let found = row.try_get::<Option<String>, _>("statement_types").ok().flatten().unwrap_or_default();
+            // Fixed order so the frontend can render consistent badges
+            let statement_types = ["income", "balance", "cash_flow"]
+                .iter()
+                .filter(|t| found.split(',').any(|f| f == **t))
+                .map(|t| t.to_string())
+                .collect();
+
+            UpdatedFundamentals {
+                stock_id: row.get("stock_id"),
+                symbol: row.get("symbol"),
+                statement_types,
+                latest_filed_date: row.try_get::<Option<String>, _>("latest_filed_date").ok().flatten(),
+                last_stored_at: row.try_get::<Option<String>, _>("last_stored_at").ok().flatten(),
+            }
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Assets against liabilities plus equity for one stored annual balance sheet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BalanceSheetIdentityCheck {
+    pub fiscal_year: i32,
+    pub report_date: String,
+    pub total_assets: Option<f64>,
+    pub total_liabilities: Option<f64>,
+    pub total_equity: Option<f64>,
+    /// (assets - (liabilities + equity)) / assets, percent; None when a value is missing
+    pub discrepancy_pct: Option<f64>,
+    pub imbalanced: bool,
+}
+
+/// Check Assets = Liabilities + Equity for each fiscal year's stored balance sheet
+#[tauri::command]
+pub async fn check_balance_sheet_identity(
+    symbol: String,
+    tolerance_pct: Option<f64>,
+) -> Result<Vec<BalanceSheetIdentityCheck>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    check_balance_sheet_identity_internal(&pool, &symbol, tolerance_pct.unwrap_or(DEFAULT_IDENTITY_TOLERANCE_PCT)).await
+}
+
+/// Oldest year first; a restated year is checked on its latest report only
+pub async fn check_balance_sheet_identity_internal(
+    pool: &SqlitePool,
+    symbol: &str,
+    tolerance_pct: f64,
+) -> Result<Vec<BalanceSheetIdentityCheck>, CommandError> {
+    if !tolerance_pct.is_finite() || tolerance_pct < 0.0 {
+        return Err(CommandError::validation("tolerance_pct", "Must be a non-negative number"));
+    }
+    let stock_id: i64 = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(symbol)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("symbol", format!("Unknown symbol: {}", symbol)))?;
+
+    let rows = sqlx::query(
+        "SELECT fiscal_year, report_date, total_assets, total_liabilities, total_equity FROM (
+            SELECT fiscal_year, report_date, total_assets, total_liabilities, total_equity,
+                   ROW_NUMBER() OVER (PARTITION BY fiscal_year ORDER BY report_date DESC) AS rn
+            FROM balance_sheets
+            WHERE stock_id = ? AND period_type = 'Annual' AND fiscal_year IS NOT NULL
+        )
+        WHERE rn = 1
+        ORDER BY fiscal_year"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let total_assets: Option<f64> = row.get("total_assets");
+            let total_liabilities: Option<f64> = row.get("total_liabilities");
+            let total_equity: Option<f64> = row.get("total_equity");
+            let discrepancy_pct = match (total_assets, total_liabilities, total_equity) {
+                (Some(assets), Some(liabilities), Some(equity)) if assets != 0.0 => {
+                    Some((assets - (liabilities + equity)) / assets * 100.0)
+                }
+                _ => None,
+            };
+
+            BalanceSheetIdentityCheck {
+                fiscal_year: row.get("fiscal_year"),
+                report_date: row.get("report_date"),
+                total_assets,
+                total_liabilities,
+                total_equity,
+                discrepancy_pct,
+                imbalanced: discrepancy_pct.is_some_and(|pct| pct.abs() > tolerance_pct),
+            }
+        })
+        .collect())
+}
+
+/// Share count moves of more than 10% that no recorded split explains
+#[tauri::command]
+pub async fn get_share_count_discontinuities(stock_id: i64) -> Result<Vec<ShareCountDiscontinuity>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    Ok(shares_history::load_share_count_discontinuities(&pool, stock_id).await?)
+}
+
+/// Record a stock split (`ratio` new shares per old share) and return the moves still unexplained
+#[tauri::command]
+pub async fn record_stock_split(stock_id: i64, ex_date: String, ratio: f64) -> Result<Vec<ShareCountDiscontinuity>, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    chrono::NaiveDate::parse_from_str(&ex_date, "%Y-%m-%d")
+        .map_err(|e| CommandError::validation("ex_date", format!("Invalid ex-date format: {}", e)))?;
+    if !(ratio.is_finite() && ratio > 0.0) {
+        return Err(CommandError::validation("ratio", "Split ratio must be a positive number"));
+    }
+
+    Ok(shares_history::record_stock_split(&pool, stock_id, &ex_date, ratio).await?)
+}
+
+/// A stock's filings, most recent first, with the close on the first trading day on or after
+/// each filing and the 5- and 30-trading-day returns from there
+#[tauri::command]
+pub async fn get_filing_reactions(stock_id: i64) -> Result<Vec<FilingReaction>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    Ok(filing_reactions::load_filing_reactions(&pool, stock_id).await?)
+}
+
+/// Price filings without a reaction and fill in returns that were waiting on later prices, for
+/// one stock or every stock. Returns the reactions written.
+#[tauri::command]
+pub async fn backfill_filing_reactions(stock_id: Option<i64>) -> Result<i64, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    Ok(filing_reactions::backfill_filing_reactions(&pool, stock_id).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool() -> SqlitePool {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE stocks (id INTEGER PRIMARY KEY, symbol TEXT NOT NULL, cik TEXT)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE sec_filings (id INTEGER PRIMARY KEY, stock_id INTEGER NOT NULL, accession_number TEXT NOT NULL, form_type TEXT NOT NULL, filed_date DATE NOT NULL)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO stocks (id, symbol, cik) VALUES (1, 'AAPL', '320193'), (2, 'NOCIK', NULL)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO sec_filings (stock_id, accession_number, form_type, filed_date) VALUES
+                (1, '0000320193-22-000108', '10-K', '2022-10-28'),
+                (1, '0000320193-23-000106', '10-K', '2023-11-03'),
+                (1, '0000320193-21-000105', '10-K', '2021-10-29'),
+                (1, '0000320193-24-000001', '10-Q', '2024-02-02')"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    fn submissions_body() -> serde_json::Value {
+        serde_json::json!({
+            "cik": "320193",
+            "name": "Apple Inc.",
+            "tickers": ["AAPL"],
+            "filings": {
+                "recent": {
+                    "accessionNumber": ["0000320193-24-000123", "0000320193-24-000081", "0000320193-23-000106", "0000320193-22-000108"],
+                    "filingDate": ["2024-11-01", "2024-08-02", "2023-11-03", "2022-10-28"],
+                    "reportDate": ["2024-09-28", "2024-06-29", "2023-09-30", "2022-09-24"],
+                    "form": ["10-K", "10-Q", "10-K", "10-K"]
+                },
+                "files": []
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_diff_filings_vs_sec_with_mocked_submissions() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/submissions/CIK0000320193.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(submissions_body()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let pool = seeded_pool().await;
+        let mut client = SecEdgarClient::new(pool.clone()).with_base_url(server.uri());
+
+        let diff = diff_filings_vs_sec_internal(&pool, &mut client, "AAPL").await.unwrap();
+
+        assert_eq!(diff.cik, "320193");
+        assert_eq!(diff.missing_locally, ["2024-11-01"]);
+        assert_eq!(diff.missing_at_sec, ["2021-10-29"]);
+        assert_eq!(diff.matched, ["2022-10-28", "2023-11-03"]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_raw_company_facts_with_mocked_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/xbrl/companyfacts/CIK0000320193.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "cik": 320193,
+                "entityName": "Apple Inc.",
+                "facts": { "us-gaap": { "Assets": { "units": { "USD": [
+                    { "end": "2023-09-30", "val": 352583000000.0, "accn": "0000320193-23-000106" }
+                ]}}}}
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let pool = seeded_pool().await;
+        let mut client = SecEdgarClient::new(pool.clone()).with_base_url(server.uri());
+
+        let raw = fetch_raw_company_facts_internal(&pool, &mut client, "AAPL", true, DEFAULT_RAW_FACTS_MAX_BYTES).await.unwrap();
+        assert!(!raw.truncated);
+        assert_eq!(raw.total_bytes, raw.json.len() as i64);
+        let parsed: serde_json::Value = serde_json::from_str(&raw.json).unwrap();
+        assert_eq!(parsed["cik"], 320193);
+        assert!(raw.json.contains('\n'));
+
+        let capped = fetch_raw_company_facts_internal(&pool, &mut client, "AAPL", false, 40).await.unwrap();
+        assert!(capped.truncated);
+        assert_eq!(capped.json.len(), 40);
+        assert!(capped.total_bytes > 40);
+    }
+
+    #[tokio::test]
+    async fn test_diff_filings_vs_sec_unknown_symbol_and_missing_cik() {
+        let pool = seeded_pool().await;
+        let mut client = SecEdgarClient::new(pool.clone()).with_base_url("http://127.0.0.1:9");
+
+        let err = diff_filings_vs_sec_internal(&pool, &mut client, "ZZZZ").await.unwrap_err();
+        assert_eq!(err.code(), "NOT_FOUND");
+
+        let err = diff_filings_vs_sec_internal(&pool, &mut client, "NOCIK").await.unwrap_err();
+        assert_eq!(err, CommandError::not_found("cik", "No CIK mapped for NOCIK"));
+    }
+
+    #[tokio::test]
+    async fn test_recently_updated_fundamentals_before_and_after_store() {
+        use crate::tools::sec_edgar_client::{BalanceSheetData, CashFlowData, FilingMetadata, IncomeStatementData};
+        use chrono::NaiveDate;
+
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name, cik) VALUES (1, 'AAPL', 'Apple Inc.', '320193')")
+            .execute(&pool).await.unwrap();
+
+        let report_date = NaiveDate::from_ymd_opt(2023, 9, 30).unwrap();
+        let metadata = FilingMetadata {
+            accession_number: "0000320193-23-000106".to_string(),
+            form_type: "10-K".to_string(),
+            filing_date: "2023-11-03".to_string(),
+            fiscal_period: "FY".to_string(),
+            report_date: "2023-09-30".to_string(),
+        };
+        let balance = BalanceSheetData {
+            stock_id: 1, symbol: "AAPL".to_string(), report_date, fiscal_year: 2023,
+            total_assets: Some(352.6e9), total_liabilities: Some(290.4e9), total_equity: Some(62.1e9),
+            cash_and_equivalents: None, short_term_debt: None, long_term_debt: None, total_debt: None,
+            current_assets: None, current_liabilities: None, inventory: None, share_repurchases: None, shares_outstanding: None,
+            shares_outstanding_estimated: false,
+        };
+        let income = IncomeStatementData {
+            stock_id: 1, symbol: "AAPL".to_string(), report_date, fiscal_year: 2023, period_type: "FY".to_string(),
+            revenue: Some(383.3e9), net_income: Some(97.0e9), operating_income: None, gross_profit: None,
+            cost_of_revenue: None, interest_expense: None, tax_expense: None, pretax_income: None, shares_basic: None, shares_diluted: None,
+        };
+        let cash_flow = CashFlowData {
+            stock_id: 1, symbol: "AAPL".to_string(), report_date, fiscal_year: 2023,
+            depreciation_expense: None, amortization_expense: None, dividends_paid: None, share_repurchases: None,
+            operating_cash_flow: Some(110.5e9), investing_cash_flow: None, financing_cash_flow: None,
+            capital_expenditures: None,
+        };
+
+        let before_store = Utc::now() - chrono::Duration::seconds(1);
+        let client = SecEdgarClient::new(pool.clone());
+        client
+            .store_filing_atomic(1, "AAPL", &metadata, 2023, "2023-09-30", &balance, &income, &cash_flow)
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        let after_store = Utc::now();
+
+        let updated = get_recently_updated_fundamentals_internal(&pool, before_store).await.unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].symbol, "AAPL");
+        assert_eq!(updated[0].statement_types, ["income", "balance", "cash_flow"]);
+        assert_eq!(updated[0].latest_filed_date.as_deref(), Some("2023-11-03"));
+
+        let updated = get_recently_updated_fundamentals_internal(&pool, after_store).await.unwrap();
+        assert!(updated.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_balance_sheet_identity_flags_imbalanced_year() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'AAPL', 'Apple Inc.')")
+            .execute(&pool).await.unwrap();
+        // 2022 balances; 2023 picked a liabilities concept that misses 10% of assets
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_assets, total_liabilities, total_equity) VALUES
+                (1, 'Annual', '2022-09-24', 2022, 1000.0, 700.0, 299.5),
+                (1, 'Annual', '2023-09-30', 2023, 1000.0, 600.0, 300.0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        let checks = check_balance_sheet_identity_internal(&pool, "AAPL", DEFAULT_IDENTITY_TOLERANCE_PCT).await.unwrap();
+        assert_eq!(checks.iter().map(|c| (c.fiscal_year, c.imbalanced)).collect::<Vec<_>>(), vec![(2022, false), (2023, true)]);
+        assert!((checks[0].discrepancy_pct.unwrap() - 0.05).abs() < 1e-9);
+        assert!((checks[1].discrepancy_pct.unwrap() - 10.0).abs() < 1e-9);
+
+        let err = check_balance_sheet_identity_internal(&pool, "ZZZZ", 1.0).await.unwrap_err();
+        assert_eq!(err.code(), "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_diff_filings_vs_sec_surfaces_sec_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&server)
+            .await;
+
+        let pool = seeded_pool().await;
+        let mut client = SecEdgarClient::new(pool.clone()).with_base_url(server.uri());
+
+        let err = diff_filings_vs_sec_internal(&pool, &mut client, "AAPL").await.unwrap_err();
+        assert_eq!(err.code(), "EXTERNAL_API");
+    }
+}
@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use crate::database::helpers::get_database_connection;
+use crate::error::CommandError;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum FinancialStatementType {
+    #[serde(rename = "income")]
+    Income,
+    #[serde(rename = "balance")]
+    Balance,
+    #[serde(rename = "cash_flow")]
+    CashFlow,
+}
+
+impl FinancialStatementType {
+    fn table(&self) -> &'static str {
+        match self {
+            FinancialStatementType::Income => "income_statements",
+            FinancialStatementType::Balance => "balance_sheets",
+            FinancialStatementType::CashFlow => "cash_flow_statements",
+        }
+    }
+
+    fn line_items(&self) -> &'static [&'static str] {
+        match self {
+            FinancialStatementType::Income => &[
+                "revenue", "cost_of_revenue", "gross_profit", "research_development",
+                "selling_general_admin", "operating_income", "interest_expense",
+                "depreciation_expense", "amortization_expense", "net_income",
+                "shares_basic", "shares_diluted",
+            ],
+            FinancialStatementType::Balance => &[
+                "cash_and_equivalents", "accounts_receivable", "inventory", "current_assets",
+                "total_assets", "accounts_payable", "current_liabilities", "short_term_debt",
+                "long_term_debt", "total_debt", "total_liabilities", "total_equity",
+                "working_capital", "shares_outstanding",
+            ],
+            FinancialStatementType::CashFlow => &[
+                "operating_cash_flow", "depreciation_amortization", "depreciation_expense",
+                "amortization_expense", "investing_cash_flow", "capital_expenditures",
+                "financing_cash_flow", "dividends_paid", "share_repurchases", "net_cash_flow",
+            ],
+        }
+    }
+}
+
+/// One statement line pivoted across fiscal years (aligned with `FinancialStatementView::fiscal_years`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FinancialLineItem {
+    pub name: String,
+    pub values: Vec<Option<f64>>,
+    pub yoy_change: Vec<Option<f64>>,
+    pub yoy_percent_change: Vec<Option<f64>>,
+}
+
+/// Which filing each fiscal year's numbers came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FilingProvenance {
+    pub fiscal_year: i32,
+    pub report_date: Option<String>,
+    pub accession_number: Option<String>,
+    pub filed_date: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FinancialStatementView {
+    pub stock_id: i64,
+    pub statement_type: FinancialStatementType,
+    /// Oldest first; gaps inside the range are kept as null columns
+    pub fiscal_years: Vec<i32>,
+    pub line_items: Vec<FinancialLineItem>,
+    pub provenance: Vec<FilingProvenance>,
+}
+
+/// Annual statement row as loaded from the database
+struct AnnualRow {
+    fiscal_year: i32,
+    report_date: Option<String>,
+    accession_number: Option<String>,
+    filed_date: Option<String>,
+    values: Vec<Option<f64>>,
+}
+
+#[tauri::command]
+pub async fn get_financial_statements(
+    stock_id: i64,
+    statement_type: FinancialStatementType,
+    years: Option<i32>,
+) -> Result<FinancialStatementView, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_financial_statements_internal(&pool, stock_id, statement_type, years).await
+}
+
+pub async fn get_financial_statements_internal(
+    pool: &SqlitePool,
+    stock_id: i64,
+    statement_type: FinancialStatementType,
+    years: Option<i32>,
+) -> Result<FinancialStatementView, CommandError> {
+    let years = years.unwrap_or(5);
+    if years <= 0 {
+        return Err(CommandError::validation("years", "Years must be at least 1"));
+    }
+
+    let columns = statement_type.line_items();
+    let select_columns = columns.iter().map(|c| format!("t.{}", c)).collect::<Vec<_>>().join(", ");
+
+    // Income statements use 'FY', balance sheets and cash flows use 'Annual'
+    let query = format!(
+        "SELECT t.fiscal_year, t.report_date, {}, f.accession_number, f.filed_date
+         FROM {} t
+         LEFT JOIN sec_filings f ON f.id = t.sec_filing_id
+         WHERE t.stock_id = ? AND t.period_type IN ('FY', 'Annual') AND t.fiscal_year IS NOT NULL
+         ORDER BY t.fiscal_year DESC, t.report_date DESC",
+        select_columns,
+        statement_type.table()
+    );
+
+    let rows = sqlx::query(&query).bind(stock_id).fetch_all(pool).await?;
+
+    let mut annual_rows: Vec<AnnualRow> = Vec::new();
+    for row in rows {
+        let fiscal_year = row.get::<i64, _>("fiscal_year") as i32;
+        // Latest report for a fiscal year wins (rows are ordered by report_date DESC)
+        if annual_rows.iter().any(|r| r.fiscal_year == fiscal_year) {
+            continue;
+        }
+        annual_rows.push(AnnualRow {
+            fiscal_year,
+            report_date: row.try_get::<Option<String>, _>("report_date").ok().flatten(),
+            accession_number: row.try_get::<Option<String>, _>("accession_number").ok().flatten(),
+            filed_date: row.try_get::<Option<String>, _>("filed_date").ok().flatten(),
+            values: columns.iter().map(|c| row.try_get::<Option<f64>, _>(*c).ok().flatten()).collect(),
+        });
+    }
+
+    Ok(pivot_statement(stock_id, statement_type, columns, &annual_rows, years))
+}
+
+fn pivot_statement(
+    stock_id: i64,
+    statement_type: FinancialStatementType,
+    columns: &[&str],
+    rows: &[AnnualRow],
+    years: i32,
+) -> FinancialStatementView {
+    let fiscal_years: Vec<i32> = match (rows.iter().map(|r| r.fiscal_year).min(), rows.iter().map(|r| r.fiscal_year).max()) {
+        // Cap at the available history instead of erroring
+        (Some(earliest), Some(latest)) => ((latest - years + 1).max(earliest)..=latest).collect(),
+        _ => Vec::new(),
+    };
+
+    let row_for = |year: i32| rows.iter().find(|r| r.fiscal_year == year);
+
+    let line_items = columns
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let values: Vec<Option<f64>> = fiscal_years
+                .iter()
+                .map(|&year| row_for(year).and_then(|r| r.values[idx]))
+                .collect();
+
+            let mut yoy_change = vec![None];
+            let mut yoy_percent_change = vec![None];
+            for pair in values.windows(2) {
+                match (pair[0], pair[1]) {
+                    (Some(prev), Some(curr)) => {
+                        yoy_change.push(Some(curr - prev));
+                        yoy_percent_change.push(if prev != 0.0 { Some((curr - prev) / prev.abs() * 100.0) } else { None });
+                    }
+                    _ => {
+                        yoy_change.push(None);
+                        yoy_percent_change.push(None);
+                    }
+                }
+            }
+            yoy_change.truncate(values.len());
+            yoy_percent_change.truncate(values.len());
+
+            FinancialLineItem { name: name.to_string(), values, yoy_change, yoy_percent_change }
+        })
+        .collect();
+
+    let provenance = fiscal_years
+        .iter()
+        .map(|&year| match row_for(year) {
+            Some(r) => FilingProvenance {
+                fiscal_year: year,
+                report_date: r.report_date.clone(),
+                accession_number: r.accession_number.clone(),
+                filed_date: r.filed_date.clone(),
+            },
+            None => FilingProvenance { fiscal_year: year, report_date: None, accession_number: None, filed_date: None },
+        })
+        .collect();
+
+    FinancialStatementView { stock_id, statement_type, fiscal_years, line_items, provenance }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_pool() -> SqlitePool {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE sec_filings (id INTEGER PRIMARY KEY, stock_id INTEGER, accession_number TEXT, filed_date DATE)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE income_statements (id INTEGER PRIMARY KEY, stock_id INTEGER, period_type TEXT, report_date DATE, fiscal_year INTEGER,
+                revenue REAL, gross_profit REAL, operating_income REAL, net_income REAL, shares_basic REAL, shares_diluted REAL,
+                cost_of_revenue REAL, research_development REAL, selling_general_admin REAL, depreciation_expense REAL,
+                amortization_expense REAL, interest_expense REAL, sec_filing_id INTEGER)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO sec_filings (id, stock_id, accession_number, filed_date) VALUES
+                (1, 1, '0000320193-20-000096', '2020-10-30'),
+                (2, 1, '0000320193-21-000105', '2021-10-29'),
+                (3, 1, '0000320193-23-000106', '2023-11-03')"
+        )
+        .execute(&pool).await.unwrap();
+        // FY2022 is missing; the quarterly row must be ignored
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income, sec_filing_id) VALUES
+                (1, 'FY', '2020-09-26', 2020, 200.0, 50.0, 1),
+                (1, 'FY', '2021-09-25', 2021, 250.0, 0.0, 2),
+                (1, 'FY', '2023-09-30', 2023, 300.0, 60.0, 3),
+                (1, 'Quarterly', '2023-12-30', 2024, 120.0, 30.0, NULL)"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    fn line<'a>(view: &'a FinancialStatementView, name: &str) -> &'a FinancialLineItem {
+        view.line_items.iter().find(|l| l.name == name).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_income_statement_pivot_with_missing_year() {
+        let pool = seeded_pool().await;
+
+        let view = get_financial_statements_internal(&pool, 1, FinancialStatementType::Income, Some(10)).await.unwrap();
+
+        // Capped at available history, gap kept as an explicit null column
+        assert_eq!(view.fiscal_years, vec![2020, 2021, 2022, 2023]);
+
+        let revenue = line(&view, "revenue");
+        assert_eq!(revenue.values, vec![Some(200.0), Some(250.0), None, Some(300.0)]);
+        assert_eq!(revenue.yoy_change, vec![None, Some(50.0), None, None]);
+        assert_eq!(revenue.yoy_percent_change, vec![None, Some(25.0), None, None]);
+
+        // Falling to zero is a full -100% drop
+        let net_income = line(&view, "net_income");
+        assert_eq!(net_income.yoy_change[1], Some(-50.0));
+        assert_eq!(net_income.yoy_percent_change[1], Some(-100.0));
+
+        assert_eq!(view.provenance[1].accession_number.as_deref(), Some("0000320193-21-000105"));
+        assert_eq!(view.provenance[2].accession_number, None);
+        assert_eq!(view.provenance[3].filed_date.as_deref(), Some("2023-11-03"));
+    }
+
+    #[tokio::test]
+    async fn test_years_limits_to_most_recent() {
+        let pool = seeded_pool().await;
+
+        let view = get_financial_statements_internal(&pool, 1, FinancialStatementType::Income, Some(2)).await.unwrap();
+        assert_eq!(view.fiscal_years, vec![2022, 2023]);
+        assert_eq!(line(&view, "revenue").values, vec![None, Some(300.0)]);
+
+        let empty = get_financial_statements_internal(&pool, 99, FinancialStatementType::Income, None).await.unwrap();
+        assert!(empty.fiscal_years.is_empty());
+        assert!(empty.line_items.iter().all(|l| l.values.is_empty()));
+    }
+}
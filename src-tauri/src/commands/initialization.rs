@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
-use crate::database::helpers::get_database_connection;
+use sqlx::{Row, SqlitePool};
+use crate::database::helpers::{ensure_writable, get_database_connection, get_database_path, read_only_requested};
+use crate::database::protected_init::{reset_database as reset_database_file, reset_confirmation_token};
+use crate::error::CommandError;
+use crate::tools::sp500_constituents::{fetch_sp500_constituents, sync_sp500_constituents};
+use ts_rs::TS;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InitProgress {
@@ -10,87 +14,67 @@ pub struct InitProgress {
     pub status: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StockData {
-    pub symbol: String,
-    pub company_name: String,
-    pub sector: Option<String>,
+/// Onboarding checklist: what data exists and what still needs to be run
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct InitializationReport {
+    pub schema_ready: bool,
+    pub missing_tables: Vec<String>,
+    pub stock_count: i64,
+    pub sp500_count: i64,
+    pub has_daily_prices: bool,
+    pub has_sec_filings: bool,
+    pub sp500_last_updated: Option<String>,
+    pub next_actions: Vec<InitializationAction>,
+    pub ready: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct InitializationAction {
+    pub description: String,
+    pub command: String,
 }
 
+const REQUIRED_TABLES: [&str; 5] = ["stocks", "daily_prices", "metadata", "sec_filings", "income_statements"];
 
+
+/// Sync the stocks table with the current S&P 500 list. Stocks are upserted rather than
+/// replaced, so prices and statements stay attached and ticker changes become aliases.
 #[tauri::command]
-pub async fn initialize_sp500_stocks() -> Result<String, String> {
+pub async fn initialize_sp500_stocks() -> Result<String, CommandError> {
     let pool = get_database_connection().await?;
-    
-    // Step 1: Fetch S&P 500 list from GitHub
-    let url = "https://raw.githubusercontent.com/datasets/s-and-p-500-companies/main/data/constituents.csv";
-    
-    let response = reqwest::get(url).await
-        .map_err(|e| format!("Failed to fetch S&P 500 data: {}", e))?;
-    
-    let csv_text = response.text().await
-        .map_err(|e| format!("Failed to read CSV data: {}", e))?;
-    
-    // Step 2: Parse CSV data
-    let mut reader = csv::Reader::from_reader(csv_text.as_bytes());
-    let mut companies = Vec::new();
-    
-    for result in reader.records() {
-        let record = result.map_err(|e| format!("CSV parsing error: {}", e))?;
-        if record.len() >= 2 {
-            let symbol = record[0].trim().to_string();
-            let name = record[1].trim().to_string(); // "Security" column
-            let sector = record.get(2).unwrap_or("").trim().to_string(); // "GICS Sector" column
-            
-            companies.push(StockData {
-                symbol,
-                company_name: name,
-                sector: if sector.is_empty() { None } else { Some(sector) },
-            });
-        }
-    }
-    
+    ensure_writable(&pool).await?;
+
+    let companies = fetch_sp500_constituents()
+        .await
+        .map_err(|e| CommandError::external_api("github", None, format!("Failed to load S&P 500 list: {}", e)))?;
     if companies.is_empty() {
-        return Err("No companies found in S&P 500 data".to_string());
+        return Err(CommandError::external_api("github", None, "No companies found in S&P 500 data"));
     }
-    
-    // Step 3: Clear existing stocks and insert new ones
-    sqlx::query("DELETE FROM stocks")
-        .execute(&pool).await
-        .map_err(|e| format!("Failed to clear existing stocks: {}", e))?;
-    
-    let mut inserted = 0;
-    for company in &companies {
-        match sqlx::query(
-            "INSERT INTO stocks (symbol, company_name, sector) VALUES (?1, ?2, ?3)"
-        )
-        .bind(&company.symbol)
-        .bind(&company.company_name)
-        .bind(&company.sector)
-        .execute(&pool).await
-        {
-            Ok(_) => inserted += 1,
-            Err(e) => eprintln!("Failed to insert {}: {}", company.symbol, e),
-        }
-    }
-    
-    // Step 4: Update metadata
+
     let current_date = chrono::Utc::now().format("%Y-%m-%d").to_string();
-    sqlx::query("INSERT OR REPLACE INTO metadata (key, value) VALUES ('sp500_last_updated', ?1)")
-        .bind(&current_date)
-        .execute(&pool).await
-        .map_err(|e| format!("Failed to update metadata: {}", e))?;
-    
+    let summary = sync_sp500_constituents(&pool, &companies, &current_date).await?;
+    for (symbol, error) in &summary.failed {
+        eprintln!("Failed to store {}: {}", symbol, error);
+    }
+    crate::tools::query_cache::global_query_cache().invalidate_all();
+
     let message = format!(
-        "Successfully initialized {} S&P 500 companies out of {} found in dataset. Last updated: {}",
-        inserted, companies.len(), current_date
+        "Successfully initialized {} S&P 500 companies out of {} found in dataset ({} new, {} ticker changes, {} no longer listed). Last updated: {}",
+        summary.inserted + summary.updated,
+        companies.len(),
+        summary.inserted,
+        summary.renamed.len(),
+        summary.removed,
+        current_date
     );
-    
+
     Ok(message)
 }
 
 #[tauri::command]
-pub async fn get_initialization_status() -> Result<InitProgress, String> {
+pub async fn get_initialization_status() -> Result<InitProgress, CommandError> {
     let pool = get_database_connection().await?;
     
     // Check if stocks are initialized
@@ -125,7 +109,7 @@ pub async fn get_initialization_status() -> Result<InitProgress, String> {
 }
 
 #[tauri::command]
-pub async fn check_database_schema() -> Result<String, String> {
+pub async fn check_database_schema() -> Result<String, CommandError> {
     let pool = get_database_connection().await?;
     
     // Check if required tables exist
@@ -134,8 +118,7 @@ pub async fn check_database_schema() -> Result<String, String> {
     
     for table in tables {
         let exists = sqlx::query(&format!("SELECT name FROM sqlite_master WHERE type='table' AND name='{}'", table))
-            .fetch_optional(&pool).await
-            .map_err(|e| format!("Database query error: {}", e))?;
+            .fetch_optional(&pool).await?;
             
         if exists.is_none() {
             missing_tables.push(table);
@@ -145,10 +128,125 @@ pub async fn check_database_schema() -> Result<String, String> {
     if missing_tables.is_empty() {
         Ok("Database schema is ready".to_string())
     } else {
-        Err(format!("Missing required tables: {}", missing_tables.join(", ")))
+        Err(CommandError::not_found("table", format!("Missing required tables: {}", missing_tables.join(", "))))
     }
 }
 
+#[tauri::command]
+pub async fn get_initialization_report() -> Result<InitializationReport, CommandError> {
+    let pool = get_database_connection().await?;
+
+    build_initialization_report(&pool).await
+}
+
+pub async fn build_initialization_report(pool: &SqlitePool) -> Result<InitializationReport, CommandError> {
+    let mut missing_tables = Vec::new();
+    for table in REQUIRED_TABLES {
+        let exists = sqlx::query("SELECT name FROM sqlite_master WHERE type='table' AND name = ?")
+            .bind(table)
+            .fetch_optional(pool).await?;
+
+        if exists.is_none() {
+            missing_tables.push(table.to_string());
+        }
+    }
+
+    // Counts are only meaningful once the schema exists
+    let table_exists = |table: &str| !missing_tables.iter().any(|t| t == table);
+
+    let (stock_count, sp500_count) = if table_exists("stocks") {
+        let row = sqlx::query(
+            "SELECT COUNT(*) as total, COALESCE(SUM(CASE WHEN is_sp500 = 1 THEN 1 ELSE 0 END), 0) as sp500 FROM stocks"
+        )
+        .fetch_one(pool).await?;
+        (row.get::<i64, _>("total"), row.get::<i64, _>("sp500"))
+    } else {
+        (0, 0)
+    };
+
+    let has_daily_prices = table_exists("daily_prices")
+        && sqlx::query("SELECT 1 FROM daily_prices LIMIT 1").fetch_optional(pool).await?.is_some();
+
+    let has_sec_filings = table_exists("sec_filings")
+        && sqlx::query("SELECT 1 FROM sec_filings LIMIT 1").fetch_optional(pool).await?.is_some();
+
+    let sp500_last_updated = if table_exists("metadata") {
+        sqlx::query("SELECT value FROM metadata WHERE key = 'sp500_last_updated'")
+            .fetch_optional(pool).await?
+            .map(|row| row.get::<String, _>("value"))
+    } else {
+        None
+    };
+
+    let schema_ready = missing_tables.is_empty();
+    let mut next_actions = Vec::new();
+
+    if !schema_ready {
+        next_actions.push(InitializationAction {
+            description: "Run database migrations".to_string(),
+            command: "cargo run --bin migrate".to_string(),
+        });
+    }
+    if sp500_count == 0 {
+        next_actions.push(InitializationAction {
+            description: "Import S&P 500 list".to_string(),
+            command: "initialize_sp500_stocks".to_string(),
+        });
+    }
+    if !has_daily_prices {
+        next_actions.push(InitializationAction {
+            description: "Run market data refresh".to_string(),
+            command: "cargo run --bin refresh_data market".to_string(),
+        });
+    }
+    if !has_sec_filings {
+        next_actions.push(InitializationAction {
+            description: "Run financial refresh".to_string(),
+            command: "cargo run --bin refresh_data financials".to_string(),
+        });
+    }
+
+    Ok(InitializationReport {
+        schema_ready,
+        missing_tables,
+        stock_count,
+        sp500_count,
+        has_daily_prices,
+        has_sec_filings,
+        sp500_last_updated,
+        ready: next_actions.is_empty(),
+        next_actions,
+    })
+}
+
+/// Wipe all collected data and recreate the schema.
+/// `confirmation` must be the database file name (e.g. "stocks.db").
+#[tauri::command]
+pub async fn reset_database(confirmation: String, auto_backup: Option<bool>) -> Result<String, CommandError> {
+    if read_only_requested() {
+        return Err(CommandError::read_only("The database is open read-only; unset DATABASE_READ_ONLY to reset it"));
+    }
+    let db_path = get_database_path()?;
+
+    let expected = reset_confirmation_token(&db_path);
+    if confirmation != expected {
+        return Err(CommandError::validation(
+            "confirmation",
+            format!("Type the database file name '{}' to confirm the reset", expected),
+        ));
+    }
+
+    let backup_path = reset_database_file(&db_path, &confirmation, auto_backup.unwrap_or(true))
+        .await
+        .map_err(|e| CommandError::internal(format!("Database reset failed: {}", e)))?;
+    crate::tools::query_cache::global_query_cache().invalidate_all();
+
+    Ok(match backup_path {
+        Some(path) => format!("Database reset complete. Backup saved to {}", path),
+        None => "Database reset complete (no backup taken)".to_string(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use sqlx::{SqlitePool, pool::PoolOptions};
@@ -208,4 +306,73 @@ mod tests {
 
         println!("✅ Database schema check test passed: {}", message);
     }
-}
\ No newline at end of file
+
+    async fn create_schema(pool: &SqlitePool) {
+        for ddl in [
+            "CREATE TABLE stocks (id INTEGER PRIMARY KEY, symbol TEXT NOT NULL, company_name TEXT NOT NULL, is_sp500 BOOLEAN DEFAULT 0)",
+            "CREATE TABLE daily_prices (id INTEGER PRIMARY KEY, stock_id INTEGER, date DATE, close_price REAL)",
+            "CREATE TABLE metadata (key TEXT PRIMARY KEY, value TEXT)",
+            "CREATE TABLE sec_filings (id INTEGER PRIMARY KEY, stock_id INTEGER, accession_number TEXT)",
+            "CREATE TABLE income_statements (id INTEGER PRIMARY KEY, stock_id INTEGER)",
+        ] {
+            sqlx::query(ddl).execute(pool).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_initialization_report_on_empty_database() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        create_schema(&pool).await;
+
+        let report = super::build_initialization_report(&pool).await.unwrap();
+
+        assert!(report.schema_ready);
+        assert_eq!(report.stock_count, 0);
+        assert_eq!(report.sp500_count, 0);
+        assert!(!report.has_daily_prices);
+        assert!(!report.has_sec_filings);
+        assert!(!report.ready);
+
+        let commands: Vec<&str> = report.next_actions.iter().map(|a| a.command.as_str()).collect();
+        assert_eq!(commands, [
+            "initialize_sp500_stocks",
+            "cargo run --bin refresh_data market",
+            "cargo run --bin refresh_data financials",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_initialization_report_on_missing_schema() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+
+        let report = super::build_initialization_report(&pool).await.unwrap();
+
+        assert!(!report.schema_ready);
+        assert_eq!(report.missing_tables.len(), 5);
+        assert_eq!(report.next_actions[0].command, "cargo run --bin migrate");
+    }
+
+    #[tokio::test]
+    async fn test_initialization_report_on_seeded_database() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        create_schema(&pool).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (1, 'AAPL', 'Apple Inc.', 1), (2, 'XYZ', 'Other', 0)")
+            .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO daily_prices (stock_id, date, close_price) VALUES (1, '2024-01-02', 185.6)")
+            .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO sec_filings (stock_id, accession_number) VALUES (1, '0000320193-23-000106')")
+            .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO metadata (key, value) VALUES ('sp500_last_updated', '2024-01-01')")
+            .execute(&pool).await.unwrap();
+
+        let report = super::build_initialization_report(&pool).await.unwrap();
+
+        assert_eq!(report.stock_count, 2);
+        assert_eq!(report.sp500_count, 1);
+        assert!(report.has_daily_prices);
+        assert!(report.has_sec_filings);
+        assert_eq!(report.sp500_last_updated.as_deref(), Some("2024-01-01"));
+        assert!(report.next_actions.is_empty());
+        assert!(report.ready);
+    }
+}
@@ -0,0 +1,18 @@
+use tracing::Level;
+use crate::error::CommandError;
+use crate::logging::{global_log_buffer, LogEntry};
+
+const DEFAULT_LOG_LIMIT: usize = 200;
+
+/// Most recent backend log entries, newest first, at `level` ("info" by default) or more severe
+#[tauri::command]
+pub async fn get_recent_logs(level: Option<String>, limit: Option<usize>) -> Result<Vec<LogEntry>, CommandError> {
+    let min_level = match level.as_deref() {
+        None => Level::INFO,
+        Some(level) => level
+            .parse::<Level>()
+            .map_err(|_| CommandError::validation("level", "Level must be one of trace, debug, info, warn, error"))?,
+    };
+
+    Ok(global_log_buffer().recent(min_level, limit.unwrap_or(DEFAULT_LOG_LIMIT)))
+}
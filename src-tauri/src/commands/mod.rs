@@ -4,4 +4,15 @@ pub mod analysis;
 pub mod initialization;
 pub mod recommendations;
 pub mod piotroski_screening;
-pub mod oshaughnessy_screening;
\ No newline at end of file
+pub mod oshaughnessy_screening;
+pub mod graham_screening;
+pub mod filings;
+pub mod financials;
+pub mod settings;
+pub mod tags;
+pub mod logs;
+pub mod quotes;
+pub mod custom_screening;
+pub mod screen_membership;
+pub mod screening_presets;
+pub mod screening_snapshots;
@@ -1,6 +1,15 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, Row};
-use crate::database::helpers::get_database_connection;
+use crate::commands::screening_presets::ScreenType;
+use crate::commands::screening_snapshots::{save_screen_snapshot_internal, SnapshotItem};
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::commands::tags::push_tag_filter;
+use crate::error::CommandError;
+use crate::tools::screen_exclusions::{RatioNote, ScreenResults};
+use crate::tools::screening_readiness::{require_screen_ready, Screen};
+use crate::tools::universe_filter::{apply_universe_filter, UniverseFilter};
+use crate::analysis::accruals::push_max_accrual_filter;
+use crate::analysis::growth_consistency::push_min_growth_streak_filter;
 use ts_rs::TS;
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -20,6 +29,13 @@ pub struct OShaughnessyValueResult {
     pub pb_ratio: Option<f64>,
     pub ev_ebitda_ratio: Option<f64>,
     pub shareholder_yield: Option<f64>,
+    /// Shareholder yield components, in percent of market cap; buyback yield is on gross repurchases
+    pub dividend_yield: Option<f64>,
+    pub buyback_yield: Option<f64>,
+    /// Set when P/E or P/B is empty because earnings or book value are negative; the composite
+    /// is then averaged without it
+    pub pe_ratio_note: Option<RatioNote>,
+    pub pb_ratio_note: Option<RatioNote>,
 
     // Ranking and scoring
     pub data_completeness_score: f64,
@@ -35,7 +51,17 @@ pub struct OShaughnessyValueResult {
     pub pb_rank: Option<i64>,
     pub ebitda_rank: Option<i64>,
     pub yield_rank: Option<i64>,
+    /// Composite components present, out of five
     pub metrics_available: i32,
+
+    // Percentile of each composite component among stocks reporting it, 0 = cheapest
+    pub pe_percentile: Option<f64>,
+    pub pb_percentile: Option<f64>,
+    pub ps_percentile: Option<f64>,
+    pub ev_ebitda_percentile: Option<f64>,
+    pub yield_percentile: Option<f64>,
+    /// Set when the composite was averaged over fewer than all five components
+    pub missing_metrics_note: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -45,7 +71,16 @@ pub struct OShaughnessyScreeningCriteria {
     pub max_ps_ratio: Option<f64>,
     pub max_evs_ratio: Option<f64>,
     pub min_market_cap: Option<f64>,
+    /// Average shares per day over the last 30 trading days; defaults to the shared floor
+    pub min_avg_daily_volume: Option<f64>,
     pub sectors: Option<Vec<String>>,
+    /// Only stocks carrying at least one of these tags
+    pub tags: Option<Vec<String>>,
+    /// Latest fiscal years in a row with revenue growth
+    pub min_consecutive_growth_years: Option<i32>,
+    /// Exclude stocks whose latest fiscal year's accrual ratio, (net income - operating cash
+    /// flow) / total assets, is above this
+    pub max_accrual_ratio: Option<f64>,
     pub passes_screening_only: Option<bool>,
 }
 
@@ -56,7 +91,11 @@ impl Default for OShaughnessyScreeningCriteria {
             max_ps_ratio: Some(2.0),
             max_evs_ratio: Some(2.0),
             min_market_cap: Some(200_000_000.0), // $200M
+            min_avg_daily_volume: None,
             sectors: None,
+            tags: None,
+            min_consecutive_growth_years: None,
+            max_accrual_ratio: None,
             passes_screening_only: Some(true),
         }
     }
@@ -67,18 +106,41 @@ pub async fn get_oshaughnessy_screening_results(
     stock_tickers: Vec<String>,
     criteria: Option<OShaughnessyScreeningCriteria>,
     limit: Option<i32>,
-) -> Result<Vec<OShaughnessyValueResult>, String> {
+    override_readiness: Option<bool>,
+    persist: Option<bool>,
+) -> Result<ScreenResults<OShaughnessyValueResult>, CommandError> {
     let pool = get_database_connection().await?;
+    require_screen_ready(&pool, Screen::OShaughnessy, override_readiness).await?;
+    if persist.unwrap_or(false) {
+        ensure_writable(&pool).await?;
+    }
 
-    get_oshaughnessy_screening_results_internal(&pool, stock_tickers, criteria, limit).await
+    let criteria = criteria.unwrap_or_default();
+    let snapshot_criteria = serde_json::to_value(&criteria).map_err(|e| CommandError::internal(e.to_string()))?;
+    let results = get_oshaughnessy_screening_results_internal(&pool, stock_tickers.clone(), Some(criteria), limit).await?;
+
+    if persist.unwrap_or(false) {
+        let items: Vec<SnapshotItem> = results
+            .iter()
+            .enumerate()
+            .map(|(i, row)| SnapshotItem {
+                stock_id: row.stock_id,
+                symbol: row.symbol.clone(),
+                rank: i as i32 + 1,
+                score: Some(row.composite_percentile),
+            })
+            .collect();
+        save_screen_snapshot_internal(&pool, ScreenType::OShaughnessy, &snapshot_criteria, &items).await?;
+    }
+    Ok(ScreenResults::with_exclusions(&pool, results, |row| row.stock_id, &stock_tickers).await?)
 }
 
-async fn get_oshaughnessy_screening_results_internal(
+pub async fn get_oshaughnessy_screening_results_internal(
     pool: &SqlitePool,
     stock_tickers: Vec<String>,
     criteria: Option<OShaughnessyScreeningCriteria>,
     limit: Option<i32>,
-) -> Result<Vec<OShaughnessyValueResult>, String> {
+) -> Result<Vec<OShaughnessyValueResult>, CommandError> {
     let criteria = criteria.unwrap_or_default();
     println!("🔍 Starting O'Shaughnessy screening with criteria: {:?}", criteria);
 
@@ -96,6 +158,10 @@ async fn get_oshaughnessy_screening_results_internal(
             pb_ratio,
             ev_ebitda_ratio,
             shareholder_yield,
+            dividend_yield,
+            buyback_yield,
+            net_income,
+            total_equity,
             data_completeness_score,
             composite_score,
             composite_percentile,
@@ -107,7 +173,12 @@ async fn get_oshaughnessy_screening_results_internal(
             pb_rank,
             ebitda_rank,
             yield_rank,
-            metrics_available
+            metrics_available,
+            pe_percentile,
+            pb_percentile,
+            ps_percentile,
+            ev_ebitda_percentile,
+            yield_percentile
         FROM oshaughnessy_ranking
         WHERE 1=1"
     );
@@ -131,11 +202,6 @@ async fn get_oshaughnessy_screening_results_internal(
         params.push(max_evs.to_string());
     }
 
-    if let Some(min_market_cap) = criteria.min_market_cap {
-        query.push_str(" AND market_cap >= ?");
-        params.push(min_market_cap.to_string());
-    }
-
     if criteria.passes_screening_only.unwrap_or(false) {
         query.push_str(" AND passes_screening = 1");
     }
@@ -150,6 +216,18 @@ async fn get_oshaughnessy_screening_results_internal(
         }
     }
 
+    if let Some(tags) = &criteria.tags {
+        push_tag_filter(&mut query, &mut params, "stock_id", tags);
+    }
+
+    if let Some(min_years) = criteria.min_consecutive_growth_years {
+        push_min_growth_streak_filter(&mut query, &mut params, "stock_id", min_years);
+    }
+
+    if let Some(max_ratio) = criteria.max_accrual_ratio {
+        push_max_accrual_filter(&mut query, &mut params, "stock_id", max_ratio);
+    }
+
     if !stock_tickers.is_empty() {
         let placeholders = stock_tickers.iter().map(|_| "?").collect::<Vec<_>>().join(",");
         query.push_str(&format!(" AND symbol IN ({})", placeholders));
@@ -158,6 +236,9 @@ async fn get_oshaughnessy_screening_results_internal(
         }
     }
 
+    let universe = UniverseFilter::new(criteria.min_market_cap, criteria.min_avg_daily_volume);
+    apply_universe_filter(&mut query, &mut params, "oshaughnessy_ranking.stock_id", &universe);
+
     query.push_str(" ORDER BY composite_score ASC, overall_rank ASC");
 
     // Add LIMIT as parameter to prevent SQL injection
@@ -176,8 +257,7 @@ async fn get_oshaughnessy_screening_results_internal(
 
     let results = sqlx_query
         .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Database query failed: {}", e))?;
+        .await?;
 
     println!("🔍 Query executed successfully, got {} results", results.len());
     Ok(results)
@@ -187,7 +267,7 @@ async fn get_oshaughnessy_screening_results_internal(
 impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for OShaughnessyValueResult {
     fn from_row(row: &sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
         use sqlx::Row;
-        Ok(OShaughnessyValueResult {
+        let mut result = OShaughnessyValueResult {
             stock_id: row.try_get("stock_id")?,
             symbol: row.try_get("symbol")?,
             sector: row.try_get("sector")?,
@@ -202,6 +282,10 @@ impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for OShaughnessyValueResult {
             pb_ratio: row.try_get("pb_ratio")?,
             ev_ebitda_ratio: row.try_get("ev_ebitda_ratio")?,
             shareholder_yield: row.try_get("shareholder_yield")?,
+            dividend_yield: row.try_get("dividend_yield")?,
+            buyback_yield: row.try_get("buyback_yield")?,
+            pe_ratio_note: RatioNote::for_pe(row.try_get("net_income")?),
+            pb_ratio_note: RatioNote::for_pb(row.try_get("total_equity")?),
 
             // Ranking and scoring
             data_completeness_score: row.try_get("data_completeness_score")?,
@@ -218,12 +302,74 @@ impl sqlx::FromRow<'_, sqlx::sqlite::SqliteRow> for OShaughnessyValueResult {
             ebitda_rank: row.try_get("ebitda_rank").ok(),
             yield_rank: row.try_get("yield_rank").ok(),
             metrics_available: row.try_get("metrics_available")?,
-        })
+
+            // Component percentiles
+            pe_percentile: row.try_get("pe_percentile").ok(),
+            pb_percentile: row.try_get("pb_percentile").ok(),
+            ps_percentile: row.try_get("ps_percentile").ok(),
+            ev_ebitda_percentile: row.try_get("ev_ebitda_percentile").ok(),
+            yield_percentile: row.try_get("yield_percentile").ok(),
+            missing_metrics_note: None,
+        };
+        result.missing_metrics_note = missing_metrics_note(&[
+            ("P/E", result.pe_ratio),
+            ("P/B", result.pb_ratio),
+            ("P/S", result.ps_ratio),
+            ("EV/EBITDA", result.ev_ebitda_ratio),
+            ("shareholder yield", result.shareholder_yield),
+        ]);
+        Ok(result)
     }
 }
 
+/// Which value composite components a stock was ranked without, if any
+fn missing_metrics_note(components: &[(&str, Option<f64>)]) -> Option<String> {
+    let missing: Vec<&str> = components
+        .iter()
+        .filter(|(_, value)| value.is_none())
+        .map(|(name, _)| *name)
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Ranked on {} of {} metrics; missing {}",
+        components.len() - missing.len(),
+        components.len(),
+        missing.join(", ")
+    ))
+}
+
+/// S&P 500 ranked by the value composite: the average percentile across P/E, P/B, P/S,
+/// EV/EBITDA and shareholder yield, cheapest first. Stocks missing components are ranked on the
+/// ones they have and carry a note saying which are missing.
+#[tauri::command]
+pub async fn get_value_composite_ranking(limit: Option<i32>) -> Result<Vec<OShaughnessyValueResult>, CommandError> {
+    let pool = get_database_connection().await?;
+    get_value_composite_ranking_internal(&pool, limit).await
+}
+
+pub async fn get_value_composite_ranking_internal(
+    pool: &SqlitePool,
+    limit: Option<i32>,
+) -> Result<Vec<OShaughnessyValueResult>, CommandError> {
+    let criteria = OShaughnessyScreeningCriteria {
+        max_composite_percentile: None,
+        max_ps_ratio: None,
+        max_evs_ratio: None,
+        min_market_cap: Some(0.0),
+        min_avg_daily_volume: Some(0.0),
+        sectors: None,
+        tags: None,
+        min_consecutive_growth_years: None,
+        max_accrual_ratio: None,
+        passes_screening_only: Some(false),
+    };
+    get_oshaughnessy_screening_results_internal(pool, Vec::new(), Some(criteria), limit).await
+}
+
 #[tauri::command]
-pub async fn get_oshaughnessy_statistics() -> Result<serde_json::Value, String> {
+pub async fn get_oshaughnessy_statistics() -> Result<serde_json::Value, CommandError> {
     let pool = get_database_connection().await?;
 
     let stats = sqlx::query(
@@ -238,8 +384,7 @@ pub async fn get_oshaughnessy_statistics() -> Result<serde_json::Value, String>
         FROM oshaughnessy_ranking"
     )
     .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("Failed to get O'Shaughnessy statistics: {}", e))?;
+    .await?;
 
     let result = serde_json::json!({
         "total_stocks": stats.get::<i64, _>("total_stocks"),
@@ -252,4 +397,70 @@ pub async fn get_oshaughnessy_statistics() -> Result<serde_json::Value, String>
     });
 
     Ok(result)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_value_composite_ranks_on_available_components() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // Same fundamentals at rising prices, so each metric gets steadily more expensive;
+        // LATE has filed no income statement, leaving only P/B and shareholder yield
+        for (id, symbol, price) in [(1, "AAA", 10.0), (2, "BBB", 20.0), (3, "CCC", 30.0), (4, "LATE", 40.0)] {
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (?, ?, ?, 1)")
+                .bind(id).bind(symbol).bind(format!("{} Corp", symbol))
+                .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?1, '2024-01-02', ?2, ?2, ?2, ?2)"
+            )
+            .bind(id).bind(price)
+            .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_equity, shares_outstanding)
+                 VALUES (?, 'Annual', '2023-12-31', 2023, 100.0, 10.0)"
+            )
+            .bind(id)
+            .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, operating_cash_flow, dividends_paid)
+                 VALUES (?, 'Annual', '2023-12-31', 2023, 10.0, 5.0)"
+            )
+            .bind(id)
+            .execute(&pool).await.unwrap();
+            if symbol != "LATE" {
+                sqlx::query(
+                    "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income, operating_income)
+                     VALUES (?, 'FY', '2023-12-31', 2023, 1000.0, 50.0, 80.0)"
+                )
+                .bind(id)
+                .execute(&pool).await.unwrap();
+            }
+        }
+
+        let ranking = get_value_composite_ranking_internal(&pool, None).await.unwrap();
+        let order: Vec<(&str, f64)> = ranking.iter().map(|row| (row.symbol.as_str(), row.composite_percentile)).collect();
+        assert_eq!(order, vec![("AAA", 0.0), ("BBB", 33.3), ("CCC", 66.7), ("LATE", 100.0)]);
+
+        // P/E, P/S and EV/EBITDA rank among the three reporting them; P/B and yield among all four
+        let middle = &ranking[1];
+        assert_eq!((middle.pe_percentile, middle.ps_percentile), (Some(50.0), Some(50.0)));
+        assert!((middle.composite_score - (3.0 * 50.0 + 2.0 * 100.0 / 3.0) / 5.0).abs() < 1e-9);
+        assert_eq!((middle.metrics_available, middle.missing_metrics_note.as_deref()), (5, None));
+
+        let partial = &ranking[3];
+        assert_eq!(partial.metrics_available, 2);
+        assert_eq!((partial.pb_percentile, partial.yield_percentile, partial.pe_percentile), (Some(100.0), Some(100.0), None));
+        assert_eq!(
+            partial.missing_metrics_note.as_deref(),
+            Some("Ranked on 2 of 5 metrics; missing P/E, P/S, EV/EBITDA")
+        );
+
+        assert_eq!(get_value_composite_ranking_internal(&pool, Some(2)).await.unwrap().len(), 2);
+    }
+}
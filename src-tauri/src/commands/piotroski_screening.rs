@@ -1,6 +1,16 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{SqlitePool, Row};
-use crate::database::helpers::get_database_connection;
+use sqlx::SqlitePool;
+use crate::analysis::fiscal_year_financials::load_latest_fiscal_years;
+use crate::analysis::piotroski_history::{latest_piotroski_score, load_latest_piotroski_scores};
+use crate::commands::screening_presets::ScreenType;
+use crate::commands::screening_snapshots::{save_screen_snapshot_internal, SnapshotItem};
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+use crate::safe_f64::safe_ratio;
+use crate::tools::fx_rates::usd_value_sql;
+use crate::tools::screen_exclusions::ScreenResults;
+use crate::tools::screening_readiness::{require_screen_ready, Screen};
+use crate::tools::universe_filter::{apply_universe_filter, market_cap_sql, UniverseFilter};
 use ts_rs::TS;
 
 #[derive(Debug, Serialize, Deserialize, TS)]
@@ -9,6 +19,9 @@ pub struct PiotoskiFScoreResult {
     pub stock_id: i64,
     pub symbol: String,
     pub sector: Option<String>,
+    /// Latest close × shares outstanding in USD; None when it can't be computed, in which
+    /// case the market cap floor wasn't applied
+    pub market_cap: Option<f64>,
     pub current_net_income: Option<f64>,
     pub f_score_complete: i32,
     pub data_completeness_score: i32,
@@ -31,6 +44,7 @@ pub struct PiotoskiFScoreResult {
     pub current_net_margin: Option<f64>,
     pub current_asset_turnover: Option<f64>,
     pub current_operating_cash_flow: Option<f64>,
+    /// Market cap over the latest annual total equity in USD; None when equity is at or below zero
     pub pb_ratio: Option<f64>,
 
     // Data availability transparency
@@ -46,7 +60,10 @@ pub struct PiotroskilScreeningCriteria {
     pub min_f_score: Option<i32>,
     pub min_data_completeness: Option<i32>,
     pub sectors: Option<Vec<String>>,
+    /// Defaults to the shared universe floor ($50M)
     pub min_market_cap: Option<f64>,
+    /// Average shares per day over the last 30 trading days; defaults to the shared floor
+    pub min_avg_daily_volume: Option<f64>,
     pub passes_screening_only: Option<bool>,
 }
 
@@ -57,6 +74,7 @@ impl Default for PiotroskilScreeningCriteria {
             min_data_completeness: Some(80), // Require high data completeness
             sectors: None,
             min_market_cap: None,
+            min_avg_daily_volume: None,
             passes_screening_only: Some(true), // Only show stocks that pass screening
         }
     }
@@ -67,74 +85,58 @@ pub async fn get_piotroski_screening_results(
     stock_tickers: Vec<String>,
     criteria: Option<PiotroskilScreeningCriteria>,
     limit: Option<i32>,
-) -> Result<Vec<PiotoskiFScoreResult>, String> {
+    override_readiness: Option<bool>,
+    persist: Option<bool>,
+) -> Result<ScreenResults<PiotoskiFScoreResult>, CommandError> {
     let pool = get_database_connection().await?;
+    require_screen_ready(&pool, Screen::Piotroski, override_readiness).await?;
+    if persist.unwrap_or(false) {
+        ensure_writable(&pool).await?;
+    }
 
-    get_piotroski_screening_results_internal(&pool, stock_tickers, criteria, limit).await
+    let criteria = criteria.unwrap_or_default();
+    let snapshot_criteria = serde_json::to_value(&criteria).map_err(|e| CommandError::internal(e.to_string()))?;
+    let results = get_piotroski_screening_results_internal(&pool, stock_tickers.clone(), Some(criteria), limit).await?;
+
+    if persist.unwrap_or(false) {
+        let items: Vec<SnapshotItem> = results
+            .iter()
+            .enumerate()
+            .map(|(i, row)| SnapshotItem {
+                stock_id: row.stock_id,
+                symbol: row.symbol.clone(),
+                rank: i as i32 + 1,
+                score: Some(row.f_score_complete as f64),
+            })
+            .collect();
+        save_screen_snapshot_internal(&pool, ScreenType::Piotroski, &snapshot_criteria, &items).await?;
+    }
+    Ok(ScreenResults::with_exclusions(&pool, results, |row| row.stock_id, &stock_tickers).await?)
 }
 
+/// Scores the S&P 500 from one query per statement type, then filters, sorts and limits in
+/// memory. Only stocks with a latest-year net income and operating cash flow are scored.
 pub async fn get_piotroski_screening_results_internal(
     pool: &SqlitePool,
     stock_tickers: Vec<String>,
     criteria: Option<PiotroskilScreeningCriteria>,
     limit: Option<i32>,
-) -> Result<Vec<PiotoskiFScoreResult>, String> {
+) -> Result<Vec<PiotoskiFScoreResult>, CommandError> {
     let criteria = criteria.unwrap_or_default();
 
-    let mut query = String::from(
-        "SELECT 
-            stock_id,
-            symbol,
-            sector,
-            current_net_income,
-            f_score_complete,
-            data_completeness_score,
-            criterion_positive_net_income,
-            criterion_positive_operating_cash_flow,
-            criterion_improving_roa,
-            criterion_cash_flow_quality,
-            criterion_decreasing_debt_ratio,
-            criterion_improving_current_ratio,
-            criterion_no_dilution,
-            criterion_improving_net_margin,
-            criterion_improving_asset_turnover,
-            current_roa,
-            current_debt_ratio,
-            current_current_ratio,
-            current_net_margin,
-            current_asset_turnover,
-            current_operating_cash_flow,
-            pb_ratio,
-            CASE 
-                WHEN f_score_complete >= ? AND data_completeness_score >= ? THEN 1 
-                ELSE 0 
-            END as passes_screening
-        FROM piotroski_screening_results
-        WHERE 1=1"
+    let book_value = format!(
+        "(SELECT {} FROM balance_sheets b
+          WHERE b.stock_id = stocks.id AND b.period_type = 'Annual' AND b.total_equity IS NOT NULL
+          ORDER BY b.report_date DESC LIMIT 1)",
+        usd_value_sql("b.total_equity", "b.currency", "b.report_date")
+    );
+    let mut query = format!(
+        "SELECT id, symbol, sector, {} AS market_cap, {} AS book_value FROM stocks WHERE is_sp500 = 1",
+        market_cap_sql("stocks.id"),
+        book_value
     );
-
     let mut params = Vec::new();
 
-    // Add parameters for dynamic passes_screening calculation
-    let min_f_score = criteria.min_f_score.unwrap_or(6);
-    let min_completeness = criteria.min_data_completeness.unwrap_or(80);
-    params.push(min_f_score.to_string());
-    params.push(min_completeness.to_string());
-
-    // Apply filters
-    if let Some(min_f_score) = criteria.min_f_score {
-        query.push_str(" AND f_score_complete >= ?");
-        params.push(min_f_score.to_string());
-    }
-
-    if let Some(min_completeness) = criteria.min_data_completeness {
-        query.push_str(" AND data_completeness_score >= ?");
-        params.push(min_completeness.to_string());
-    }
-
-    // Dynamic screening based on min_f_score instead of hardcoded passes_screening
-    // The passes_screening column is now calculated dynamically in the query
-
     if let Some(sectors) = &criteria.sectors {
         if !sectors.is_empty() {
             let placeholders = sectors.iter().map(|_| "?").collect::<Vec<_>>().join(",");
@@ -153,75 +155,76 @@ pub async fn get_piotroski_screening_results_internal(
         }
     }
 
-    query.push_str(" ORDER BY f_score_complete DESC, data_completeness_score DESC");
-
-    // Add LIMIT as parameter to prevent SQL injection
-    let limit_val = limit.unwrap_or(10);
-    query.push_str(" LIMIT ?");
-    params.push(limit_val.to_string());
+    let universe = UniverseFilter::new(criteria.min_market_cap, criteria.min_avg_daily_volume);
+    apply_universe_filter(&mut query, &mut params, "stocks.id", &universe);
+    query.push_str(" ORDER BY symbol");
 
-    // Build the query with parameters
-    let mut sqlx_query = sqlx::query(&query);
+    let mut sqlx_query = sqlx::query_as::<_, (i64, String, Option<String>, Option<f64>, Option<f64>)>(&query);
     for param in params {
         sqlx_query = sqlx_query.bind(param);
     }
-
-    let rows = sqlx_query
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Database query failed: {}", e))?;
-
-    // Manual row parsing to avoid FromRow issues
-    let mut results = Vec::new();
-    for row in rows {
-        use sqlx::Row;
-
-
-        // Calculate simple Piotroski metrics
-        let criteria_scores = [
-            row.try_get::<i64, _>("criterion_positive_net_income").unwrap_or(0) as i32,
-            row.try_get::<i64, _>("criterion_positive_operating_cash_flow").unwrap_or(0) as i32,
-            row.try_get::<i64, _>("criterion_improving_roa").unwrap_or(0) as i32,
-            row.try_get::<i64, _>("criterion_cash_flow_quality").unwrap_or(0) as i32,
-            row.try_get::<i64, _>("criterion_decreasing_debt_ratio").unwrap_or(0) as i32,
-            row.try_get::<i64, _>("criterion_improving_current_ratio").unwrap_or(0) as i32,
-            row.try_get::<i64, _>("criterion_no_dilution").unwrap_or(0) as i32,
-            row.try_get::<i64, _>("criterion_improving_net_margin").unwrap_or(0) as i32,
-            row.try_get::<i64, _>("criterion_improving_asset_turnover").unwrap_or(0) as i32,
-        ];
-
-        // Simple logic: count how many criteria are met
-        let criteria_met: i32 = criteria_scores.iter().sum();
-
-        let result = PiotoskiFScoreResult {
-            stock_id: row.try_get::<i64, _>("stock_id").unwrap_or(0),
-            symbol: row.try_get::<String, _>("symbol").unwrap_or_default(),
-            sector: row.try_get::<String, _>("sector").ok(),
-            current_net_income: row.try_get::<Option<f64>, _>("current_net_income").ok().flatten(),
-            f_score_complete: row.try_get::<i64, _>("f_score_complete").unwrap_or(0) as i32,
-            data_completeness_score: row.try_get::<i64, _>("data_completeness_score").unwrap_or(0) as i32,
-            criterion_positive_net_income: criteria_scores[0],
-            criterion_positive_operating_cash_flow: criteria_scores[1],
-            criterion_improving_roa: criteria_scores[2],
-            criterion_cash_flow_quality: criteria_scores[3],
-            criterion_decreasing_debt_ratio: criteria_scores[4],
-            criterion_improving_current_ratio: criteria_scores[5],
-            criterion_no_dilution: criteria_scores[6],
-            criterion_improving_net_margin: criteria_scores[7],
-            criterion_improving_asset_turnover: criteria_scores[8],
-            current_roa: row.try_get::<Option<f64>, _>("current_roa").ok().flatten(),
-            current_debt_ratio: row.try_get::<Option<f64>, _>("current_debt_ratio").ok().flatten(),
-            current_current_ratio: row.try_get::<Option<f64>, _>("current_current_ratio").ok().flatten(),
-            current_net_margin: row.try_get::<Option<f64>, _>("current_net_margin").ok().flatten(),
-            current_asset_turnover: row.try_get::<Option<f64>, _>("current_asset_turnover").ok().flatten(),
-            current_operating_cash_flow: row.try_get::<Option<f64>, _>("current_operating_cash_flow").ok().flatten(),
-            pb_ratio: row.try_get::<Option<f64>, _>("pb_ratio").ok().flatten(),
-            criteria_met,
-            passes_screening: row.try_get::<i64, _>("passes_screening").unwrap_or(0) as i32,
-        };
-
-        results.push(result);
-    }
+    let stocks = sqlx_query.fetch_all(pool).await?;
+
+    // Statements for the requested tickers only; otherwise for everything in one pass
+    let stock_ids: Vec<i64> = stocks.iter().map(|(id, ..)| *id).collect();
+    let financials = load_latest_fiscal_years(pool, (!stock_tickers.is_empty()).then_some(stock_ids.as_slice())).await?;
+
+    // Thresholds for passes_screening when the criteria leave them open
+    let passing_f_score = criteria.min_f_score.unwrap_or(6);
+    let passing_completeness = criteria.min_data_completeness.unwrap_or(80);
+
+    let mut results: Vec<PiotoskiFScoreResult> = stocks
+        .into_iter()
+        .filter_map(|(stock_id, symbol, sector, market_cap, book_value)| {
+            let years = financials.get(&stock_id)?;
+            let current = &years.current;
+            let score = latest_piotroski_score(years)?;
+            let (scores, data_completeness_score) = (score.year, score.data_completeness_score);
+            let positive = |value: Option<f64>| value.filter(|value| *value > 0.0);
+
+            Some(PiotoskiFScoreResult {
+                stock_id,
+                symbol,
+                sector,
+                market_cap,
+                current_net_income: current.net_income,
+                f_score_complete: scores.f_score,
+                data_completeness_score,
+                criterion_positive_net_income: scores.criterion_positive_net_income,
+                criterion_positive_operating_cash_flow: scores.criterion_positive_operating_cash_flow,
+                criterion_improving_roa: scores.criterion_improving_roa,
+                criterion_cash_flow_quality: scores.criterion_cash_flow_quality,
+                criterion_decreasing_debt_ratio: scores.criterion_decreasing_debt_ratio,
+                criterion_improving_current_ratio: scores.criterion_improving_current_ratio,
+                criterion_no_dilution: scores.criterion_no_dilution,
+                criterion_improving_net_margin: scores.criterion_improving_net_margin,
+                criterion_improving_asset_turnover: scores.criterion_improving_asset_turnover,
+                current_roa: current.net_income.zip(positive(current.total_assets)).map(|(income, assets)| income / assets),
+                current_debt_ratio: current.total_debt.zip(positive(current.total_assets)).map(|(debt, assets)| debt / assets),
+                current_current_ratio: current
+                    .current_assets
+                    .zip(positive(current.current_liabilities))
+                    .map(|(assets, liabilities)| assets / liabilities),
+                current_net_margin: current.net_income.zip(positive(current.revenue)).map(|(income, revenue)| income / revenue),
+                current_asset_turnover: current.revenue.zip(positive(current.total_assets)).map(|(revenue, assets)| revenue / assets),
+                current_operating_cash_flow: current.operating_cash_flow,
+                pb_ratio: market_cap.zip(positive(book_value)).and_then(|(market_cap, book)| safe_ratio(market_cap, book)),
+                criteria_met: scores.f_score,
+                passes_screening: (scores.f_score >= passing_f_score && data_completeness_score >= passing_completeness) as i32,
+            })
+        })
+        .filter(|result| criteria.min_f_score.map_or(true, |min| result.f_score_complete >= min))
+        .filter(|result| criteria.min_data_completeness.map_or(true, |min| result.data_completeness_score >= min))
+        .collect();
+
+    // Stable sort keeps symbol order among ties
+    results.sort_by(|a, b| {
+        b.f_score_complete
+            .cmp(&a.f_score_complete)
+            .then(b.data_completeness_score.cmp(&a.data_completeness_score))
+    });
+    // A negative limit means no limit, as in SQL
+    results.truncate(usize::try_from(limit.unwrap_or(10)).unwrap_or(usize::MAX));
 
     Ok(results)
 }
@@ -231,32 +234,52 @@ pub async fn get_piotroski_screening_results_internal(
 // Removed fake confidence criteria summary - Piotroski is just simple 0-9 scoring
 
 #[tauri::command]
-pub async fn get_piotroski_statistics() -> Result<serde_json::Value, String> {
+pub async fn get_piotroski_statistics() -> Result<serde_json::Value, CommandError> {
     let pool = get_database_connection().await?;
+    Ok(piotroski_statistics(&pool).await?)
+}
 
-    let stats = sqlx::query(
-        "SELECT
-            COUNT(*) as total_stocks,
-            AVG(f_score_complete) as avg_f_score,
-            AVG(data_completeness_score) as avg_completeness,
-            COUNT(CASE WHEN f_score_complete >= 6 THEN 1 END) as high_quality_stocks,
-            COUNT(CASE WHEN f_score_complete >= 7 THEN 1 END) as excellent_stocks,
-            COUNT(CASE WHEN f_score_complete >= 6 AND data_completeness_score >= 80 THEN 1 END) as passing_stocks
-        FROM piotroski_screening_results
-        WHERE stock_id IN (SELECT id FROM stocks WHERE is_sp500 = 1)"
-    )
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("Failed to get Piotroski statistics: {}", e))?;
-
-    let result = serde_json::json!({
-        "total_stocks": stats.get::<i64, _>("total_stocks"),
-        "avg_f_score": stats.get::<f64, _>("avg_f_score"),
-        "avg_completeness": stats.get::<f64, _>("avg_completeness"),
-        "high_quality_stocks": stats.get::<i64, _>("high_quality_stocks"),
-        "excellent_stocks": stats.get::<i64, _>("excellent_stocks"),
-        "passing_stocks": stats.get::<i64, _>("passing_stocks"),
-    });
+/// Score counts and averages over the S&P 500 stocks the screen can score
+pub async fn piotroski_statistics(pool: &SqlitePool) -> anyhow::Result<serde_json::Value> {
+    let sp500: Vec<i64> = sqlx::query_scalar("SELECT id FROM stocks WHERE is_sp500 = 1").fetch_all(pool).await?;
+    let scores = load_latest_piotroski_scores(pool, Some(&sp500)).await?;
+
+    let count = |passes: fn(i32, i32) -> bool| {
+        scores.values().filter(|score| passes(score.year.f_score, score.data_completeness_score)).count() as i64
+    };
+    // Averages are null with nothing scored, as SQL's AVG was
+    let average = |value: fn(i32, i32) -> i32| {
+        (!scores.is_empty()).then(|| {
+            scores.values().map(|score| value(score.year.f_score, score.data_completeness_score) as f64).sum::<f64>() / scores.len() as f64
+        })
+    };
+
+    Ok(serde_json::json!({
+        "total_stocks": scores.len() as i64,
+        "avg_f_score": average(|f_score, _| f_score),
+        "avg_completeness": average(|_, completeness| completeness),
+        "high_quality_stocks": count(|f_score, _| f_score >= 6),
+        "excellent_stocks": count(|f_score, _| f_score >= 7),
+        "passing_stocks": count(|f_score, completeness| f_score >= 6 && completeness >= 80),
+    }))
+}
 
-    Ok(result)
-}
\ No newline at end of file
+/// F-Score breakdown per fiscal year, for each year whose previous year is also on file
+#[tauri::command]
+pub async fn get_piotroski_history(symbol: String) -> Result<crate::analysis::piotroski_history::PiotroskiHistory, CommandError> {
+    let pool = get_database_connection().await?;
+
+    let stock_id: i64 = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(&symbol)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+
+    match crate::analysis::piotroski_history::compute_piotroski_history(&pool, stock_id).await {
+        Ok(years) => Ok(crate::analysis::piotroski_history::PiotroskiHistory { stock_id, symbol, years }),
+        Err(e) => {
+            eprintln!("Piotroski history query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,466 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use std::collections::{HashMap, HashSet};
+use ts_rs::TS;
+
+use crate::api::{AlphaVantageClient, SchwabClient, StockDataProvider};
+use crate::database::helpers::{ensure_writable, get_database_connection, resolve_stock};
+use crate::error::CommandError;
+use crate::models::{Config, FundamentalData, IntradayInterval, IntradayPrice, RealTimeQuote, SchwabQuote};
+use crate::tools::intraday_prices::{
+    self, IntradayCollectionResult, IntradayDownsampleResult, DEFAULT_INTRADAY_RETENTION_DAYS,
+};
+use crate::tools::option_chains::{self, CoveredCallCandidate, OptionChainSnapshot};
+use crate::tools::quote_polling;
+
+/// A requested symbol that produced no quote
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QuoteFailure {
+    pub symbol: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LiveQuotes {
+    pub quotes: Vec<RealTimeQuote>,
+    pub failures: Vec<QuoteFailure>,
+}
+
+/// Live quotes from Schwab for the given symbols, optionally saved to real_time_quotes.
+/// Symbols that are unknown or missing from the response are listed in `failures`.
+#[tauri::command]
+pub async fn get_live_quotes(symbols: Vec<String>, persist: Option<bool>) -> Result<LiveQuotes, CommandError> {
+    let pool = get_database_connection().await?;
+    let config = Config::from_env()?;
+    let client = SchwabClient::new(&config)?;
+
+    get_live_quotes_internal(&pool, &client, &symbols, persist.unwrap_or(false), Utc::now()).await
+}
+
+/// Fundamentals for a symbol from Alpha Vantage, to cross-check the SEC-derived figures
+#[tauri::command]
+pub async fn get_provider_fundamentals(symbol: String) -> Result<FundamentalData, CommandError> {
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() {
+        return Err(CommandError::validation("symbol", "Symbol is required"));
+    }
+
+    let client = AlphaVantageClient::from_env()?;
+    Ok(client.get_fundamentals(&symbol).await?)
+}
+
+/// Import a real index's daily closes from Schwab (e.g. `$SPX`) as a benchmark series named
+/// `benchmark` (the symbol by default), resuming after the last stored day. Returns days written.
+#[tauri::command]
+pub async fn import_benchmark_index(
+    symbol: String,
+    benchmark: Option<String>,
+    start_date: Option<String>,
+) -> Result<i64, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() {
+        return Err(CommandError::validation("symbol", "Symbol is required"));
+    }
+    let benchmark = benchmark
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| symbol.trim_start_matches(['$', '^']).to_string());
+    let start = match start_date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| CommandError::validation("start_date", "Date must be in YYYY-MM-DD format"))?,
+        None => chrono::NaiveDate::from_ymd_opt(2015, 1, 1).expect("Valid date"),
+    };
+    let end = chrono::Local::now().date_naive();
+
+    let config = Config::from_env()?;
+    let client = SchwabClient::new(&config)?;
+    Ok(crate::analysis::benchmark::import_index_benchmark(&pool, &client, "schwab", &symbol, &benchmark, start, end).await?)
+}
+
+/// Poll Schwab quotes for the stocks tagged `watchlist_id` every `interval_secs`, keeping the
+/// latest per symbol in memory and pushing each poll as a "quote-update" event. Polls slow down
+/// while the market is closed. Replaces any poller already running; returns the symbol count.
+#[tauri::command]
+pub async fn start_quote_polling(watchlist_id: String, interval_secs: u64) -> Result<i64, CommandError> {
+    let pool = get_database_connection().await?;
+    let stocks = crate::commands::tags::get_stocks_by_tag_internal(&pool, &watchlist_id).await?;
+    if stocks.is_empty() {
+        return Err(CommandError::validation("watchlist_id", format!("No stocks are tagged '{}'", watchlist_id)));
+    }
+
+    let config = Config::from_env()?;
+    let minimum = quote_polling::minimum_poll_interval_secs(stocks.len(), config.rate_limit_per_minute);
+    if interval_secs < minimum {
+        return Err(CommandError::validation(
+            "interval_secs",
+            format!(
+                "{} symbols at {} requests a minute need an interval of at least {} seconds",
+                stocks.len(), config.rate_limit_per_minute, minimum
+            ),
+        ));
+    }
+
+    let client = SchwabClient::new(&config)?;
+    quote_polling::start_quote_polling(pool, client, watchlist_id, std::time::Duration::from_secs(interval_secs));
+    Ok(stocks.len() as i64)
+}
+
+/// Stop quote polling; false if it wasn't running
+#[tauri::command]
+pub async fn stop_quote_polling() -> Result<bool, CommandError> {
+    Ok(quote_polling::stop_quote_polling())
+}
+
+/// The latest polled quote for each symbol
+#[tauri::command]
+pub async fn get_polled_quotes() -> Result<Vec<RealTimeQuote>, CommandError> {
+    Ok(quote_polling::polled_quotes())
+}
+
+/// Fetch a stock's option chain from Schwab and store its monthly expirations between
+/// `from_date` (today by default) and `to_date` (60 days on by default) as a new snapshot
+#[tauri::command]
+pub async fn fetch_option_chain(
+    symbol: String,
+    from_date: Option<String>,
+    to_date: Option<String>,
+) -> Result<OptionChainSnapshot, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+    // A former ticker resolves to the stock's current one, which is what Schwab quotes
+    let (stock_id, symbol) = resolve_stock(&pool, symbol.trim().to_uppercase().as_str()).await?;
+
+    let parse_date = |field: &str, date: String| {
+        chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| CommandError::validation(field, "Date must be in YYYY-MM-DD format"))
+    };
+    let from = match from_date {
+        Some(date) => parse_date("from_date", date)?,
+        None => intraday_prices::trading_date(Utc::now()),
+    };
+    let to = match to_date {
+        Some(date) => parse_date("to_date", date)?,
+        None => from + chrono::Duration::days(60),
+    };
+    if to < from {
+        return Err(CommandError::validation("to_date", "Must not be before from_date"));
+    }
+
+    let config = Config::from_env()?;
+    let client = SchwabClient::new(&config)?;
+    Ok(option_chains::fetch_option_chain(&pool, &client, stock_id, &symbol, from, to, Utc::now()).await?)
+}
+
+/// Out-of-the-money calls on watchlist stocks whose bid yields at least
+/// `min_annualized_premium_yield` percent a year (10 by default), expiring within
+/// `max_days_to_expiry` days (45) with at least `min_open_interest` contracts open (100).
+/// Uses each stock's latest chain snapshot and flags snapshots over a day old.
+#[tauri::command]
+pub async fn get_covered_call_candidates(
+    min_annualized_premium_yield: Option<f64>,
+    max_days_to_expiry: Option<i64>,
+    min_open_interest: Option<i64>,
+) -> Result<Vec<CoveredCallCandidate>, CommandError> {
+    let pool = get_database_connection().await?;
+    let max_days_to_expiry = max_days_to_expiry.unwrap_or(45);
+    if max_days_to_expiry < 1 {
+        return Err(CommandError::validation("max_days_to_expiry", "Must be at least 1"));
+    }
+    let min_open_interest = min_open_interest.unwrap_or(100);
+    if min_open_interest < 0 {
+        return Err(CommandError::validation("min_open_interest", "Must not be negative"));
+    }
+
+    match option_chains::covered_call_candidates(
+        &pool,
+        min_annualized_premium_yield.unwrap_or(10.0),
+        max_days_to_expiry,
+        min_open_interest,
+        Utc::now(),
+    )
+    .await
+    {
+        Ok(candidates) => Ok(candidates),
+        Err(e) => {
+            eprintln!("Covered call screen query error: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+fn parse_session(date: Option<String>) -> Result<chrono::NaiveDate, CommandError> {
+    match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| CommandError::validation("date", "Date must be in YYYY-MM-DD format")),
+        None => Ok(intraday_prices::trading_date(Utc::now())),
+    }
+}
+
+/// Fetch one session's bars (1-minute by default) from Schwab for every stock tagged
+/// "watchlist", then fold 1-minute bars older than the retention window into 30-minute ones.
+/// `date` is the New York session, today's by default.
+#[tauri::command]
+pub async fn collect_intraday_prices(
+    date: Option<String>,
+    interval: Option<IntradayInterval>,
+) -> Result<IntradayCollectionResult, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+    let session = parse_session(date)?;
+
+    let config = Config::from_env()?;
+    let client = SchwabClient::new(&config)?;
+    let interval = interval.unwrap_or(IntradayInterval::OneMinute);
+    let collected = intraday_prices::collect_watchlist_intraday(&pool, &client, session, interval).await?;
+
+    let today = intraday_prices::trading_date(Utc::now());
+    intraday_prices::downsample_intraday_prices(&pool, DEFAULT_INTRADAY_RETENTION_DAYS, today).await?;
+    Ok(collected)
+}
+
+/// A stock's stored bars of one interval for one New York session, earliest first
+#[tauri::command]
+pub async fn get_intraday_prices(
+    stock_id: i64,
+    date: String,
+    interval: IntradayInterval,
+) -> Result<Vec<IntradayPrice>, CommandError> {
+    let pool = get_database_connection().await?;
+    let session = parse_session(Some(date))?;
+
+    Ok(intraday_prices::load_intraday_prices(&pool, stock_id, session, interval).await?)
+}
+
+/// Fold 1-minute bars from sessions older than `retention_days` (30 by default) into 30-minute
+/// bars and delete them
+#[tauri::command]
+pub async fn downsample_intraday_prices(retention_days: Option<i64>) -> Result<IntradayDownsampleResult, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+    let retention_days = retention_days.unwrap_or(DEFAULT_INTRADAY_RETENTION_DAYS);
+    if retention_days < 0 {
+        return Err(CommandError::validation("retention_days", "Must not be negative"));
+    }
+
+    let today = intraday_prices::trading_date(Utc::now());
+    Ok(intraday_prices::downsample_intraday_prices(&pool, retention_days, today).await?)
+}
+
+/// Quote with its change measured against `previous_close`
+pub(crate) fn to_real_time_quote(
+    stock_id: i64,
+    quote: &SchwabQuote,
+    previous_close: Option<f64>,
+    timestamp: DateTime<Utc>,
+) -> RealTimeQuote {
+    let change_amount = previous_close.map(|close| quote.last_price - close);
+    let change_percent = change_amount
+        .zip(previous_close)
+        .filter(|(_, close)| *close > 0.0)
+        .map(|(change, close)| change / close * 100.0);
+
+    RealTimeQuote {
+        id: None,
+        stock_id,
+        symbol: quote.symbol.clone(),
+        timestamp,
+        bid_price: None,
+        bid_size: None,
+        ask_price: None,
+        ask_size: None,
+        last_price: quote.last_price,
+        last_size: None,
+        volume: quote.volume,
+        change_amount,
+        change_percent,
+        day_high: quote.high_price,
+        day_low: quote.low_price,
+    }
+}
+
+pub async fn get_live_quotes_internal(
+    pool: &SqlitePool,
+    provider: &(impl StockDataProvider + Sync),
+    symbols: &[String],
+    persist: bool,
+    timestamp: DateTime<Utc>,
+) -> Result<LiveQuotes, CommandError> {
+    let mut seen = HashSet::new();
+    let requested: Vec<String> = symbols
+        .iter()
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty() && seen.insert(s.clone()))
+        .collect();
+    if requested.is_empty() {
+        return Err(CommandError::validation("symbols", "At least one symbol is required"));
+    }
+
+    let placeholders = requested.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!("SELECT id, symbol FROM stocks WHERE symbol IN ({})", placeholders);
+    let mut stock_query = sqlx::query(&query);
+    for symbol in &requested {
+        stock_query = stock_query.bind(symbol);
+    }
+    let stock_ids: HashMap<String, i64> = stock_query
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| (row.get("symbol"), row.get("id")))
+        .collect();
+
+    let mut failures = Vec::new();
+    let known: Vec<String> = requested
+        .into_iter()
+        .filter(|symbol| {
+            let is_known = stock_ids.contains_key(symbol);
+            if !is_known {
+                failures.push(QuoteFailure { symbol: symbol.clone(), reason: "Unknown symbol".to_string() });
+            }
+            is_known
+        })
+        .collect();
+
+    let mut returned: HashMap<String, SchwabQuote> = if known.is_empty() {
+        HashMap::new()
+    } else {
+        provider
+            .get_quotes(&known)
+            .await?
+            .into_iter()
+            .map(|quote| (quote.symbol.to_uppercase(), quote))
+            .collect()
+    };
+
+    let mut quotes = Vec::new();
+    for symbol in known {
+        match returned.remove(&symbol) {
+            Some(quote) if quote.last_price > 0.0 => {
+                quotes.push(to_real_time_quote(stock_ids[&symbol], &quote, quote.close_price, timestamp))
+            }
+            Some(_) => failures.push(QuoteFailure { symbol, reason: "Quote has no last price".to_string() }),
+            None => failures.push(QuoteFailure { symbol, reason: "No quote returned".to_string() }),
+        }
+    }
+
+    if persist && !quotes.is_empty() {
+        let mut tx = pool.begin().await?;
+        for quote in &mut quotes {
+            let id = sqlx::query(
+                "INSERT INTO real_time_quotes
+                    (stock_id, timestamp, bid_price, bid_size, ask_price, ask_size, last_price, last_size,
+                     volume, change_amount, change_percent, day_high, day_low)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            )
+            .bind(quote.stock_id)
+            .bind(quote.timestamp.to_rfc3339())
+            .bind(quote.bid_price)
+            .bind(quote.bid_size)
+            .bind(quote.ask_price)
+            .bind(quote.ask_size)
+            .bind(quote.last_price)
+            .bind(quote.last_size)
+            .bind(quote.volume)
+            .bind(quote.change_amount)
+            .bind(quote.change_percent)
+            .bind(quote.day_high)
+            .bind(quote.day_low)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+            quote.id = Some(id);
+        }
+        tx.commit().await?;
+    }
+
+    Ok(LiveQuotes { quotes, failures })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name) VALUES
+                (1, 'AAPL', 'Apple Inc.'), (2, 'MSFT', 'Microsoft Corp'), (3, 'DELIST', 'Delisted Co')"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    fn mock_client(dir: &tempfile::TempDir, server_uri: &str) -> SchwabClient {
+        let token_path = dir.path().join("schwab_tokens.json");
+        let tokens = serde_json::json!({
+            "access_token": "access",
+            "refresh_token": "refresh",
+            "expires_at": (Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+        });
+        std::fs::write(&token_path, tokens.to_string()).unwrap();
+
+        let config = Config {
+            schwab_api_key: "key".to_string(),
+            schwab_app_secret: "secret".to_string(),
+            schwab_callback_url: "https://localhost:8080".to_string(),
+            schwab_token_path: token_path.to_string_lossy().to_string(),
+            database_path: "unused.db".to_string(),
+            rate_limit_per_minute: 60_000,
+            batch_size: 1,
+            debug_log_retention_days: 14,
+            debug_log_max_files: 50,
+        };
+        SchwabClient::new(&config).unwrap().with_base_url(server_uri)
+    }
+
+    #[tokio::test]
+    async fn test_multi_symbol_quotes_with_partial_failures() {
+        let server = MockServer::start().await;
+        // DELIST is requested but absent from the response; ZZZZ is never sent
+        Mock::given(method("GET"))
+            .and(path("/marketdata/v1/quotes"))
+            .and(query_param("symbols", "AAPL,MSFT,DELIST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "AAPL": { "lastPrice": 190.0, "closePrice": 200.0, "highPrice": 201.0, "lowPrice": 189.5, "totalVolume": 51000000 },
+                "MSFT": { "lastPrice": 420.0, "closePrice": 400.0 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+        let client = mock_client(&dir, &server.uri());
+        let fetched_at = Utc.with_ymd_and_hms(2025, 3, 3, 15, 30, 0).unwrap();
+        let symbols: Vec<String> = ["aapl", "MSFT", "DELIST", "ZZZZ"].iter().map(|s| s.to_string()).collect();
+
+        let result = get_live_quotes_internal(&pool, &client, &symbols, true, fetched_at).await.unwrap();
+
+        assert_eq!(result.quotes.iter().map(|q| q.symbol.as_str()).collect::<Vec<_>>(), vec!["AAPL", "MSFT"]);
+        let aapl = &result.quotes[0];
+        assert_eq!(aapl.stock_id, 1);
+        assert_eq!(aapl.change_amount, Some(-10.0));
+        assert_eq!(aapl.change_percent, Some(-5.0));
+        assert_eq!((aapl.day_high, aapl.day_low, aapl.volume), (Some(201.0), Some(189.5), Some(51_000_000)));
+        assert!(aapl.id.is_some());
+
+        assert_eq!(
+            result.failures,
+            vec![
+                QuoteFailure { symbol: "ZZZZ".to_string(), reason: "Unknown symbol".to_string() },
+                QuoteFailure { symbol: "DELIST".to_string(), reason: "No quote returned".to_string() },
+            ]
+        );
+
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM real_time_quotes").fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 2);
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use crate::analysis::ps_history::{load_current_ps_points, load_ps_history_stats};
+use crate::commands::analysis::{
+    get_undervalued_stocks_by_ps_internal, PsSortKey, PS_SCREEN_DEFAULT_MIN_MARKET_CAP, PS_SCREEN_MIN_HISTORY,
+};
+use crate::commands::graham_screening::{run_graham_screening_for, GrahamScreeningCriteria};
+use crate::commands::oshaughnessy_screening::{get_oshaughnessy_screening_results_internal, OShaughnessyScreeningCriteria};
+use crate::commands::piotroski_screening::{get_piotroski_screening_results_internal, PiotroskilScreeningCriteria};
+use crate::database::helpers::get_database_connection;
+use crate::error::CommandError;
+use crate::tools::universe_filter::UniverseFilter;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum ScreenStatus {
+    Pass,
+    Fail,
+    /// The stock lacks the data this screener needs
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreenMembership {
+    pub status: ScreenStatus,
+    /// F-Score for Piotroski, composite percentile for O'Shaughnessy, P/E × P/B for Graham,
+    /// current P/S for the P/S screen
+    pub score: Option<f64>,
+}
+
+impl ScreenMembership {
+    fn unknown() -> Self {
+        Self { status: ScreenStatus::Unknown, score: None }
+    }
+}
+
+/// Criteria that return the stock's row whenever the screener has data for it
+fn open_piotroski_criteria() -> PiotroskilScreeningCriteria {
+    PiotroskilScreeningCriteria {
+        min_f_score: None,
+        min_data_completeness: None,
+        sectors: None,
+        min_market_cap: Some(0.0),
+        min_avg_daily_volume: Some(0.0),
+        passes_screening_only: Some(false),
+    }
+}
+
+fn open_oshaughnessy_criteria() -> OShaughnessyScreeningCriteria {
+    OShaughnessyScreeningCriteria {
+        max_composite_percentile: None,
+        max_ps_ratio: None,
+        max_evs_ratio: None,
+        min_market_cap: Some(0.0),
+        min_avg_daily_volume: Some(0.0),
+        sectors: None,
+        tags: None,
+        min_consecutive_growth_years: None,
+        max_accrual_ratio: None,
+        passes_screening_only: Some(false),
+    }
+}
+
+fn open_graham_criteria() -> GrahamScreeningCriteria {
+    GrahamScreeningCriteria {
+        max_pe_ratio: None,
+        max_pb_ratio: None,
+        min_current_ratio: None,
+        min_earnings_stability_years: None,
+        use_combined_pe_pb: Some(false),
+        max_pe_times_pb: None,
+        min_market_cap: Some(0.0),
+        min_avg_daily_volume: Some(0.0),
+        sectors: None,
+        include_negative_earnings: Some(true),
+    }
+}
+
+/// Which screeners the stock passes under their default criteria, keyed by screener name
+#[tauri::command]
+pub async fn get_screen_membership(symbol: String) -> Result<BTreeMap<String, ScreenMembership>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_screen_membership_internal(&pool, &symbol).await
+}
+
+pub async fn get_screen_membership_internal(
+    pool: &SqlitePool,
+    symbol: &str,
+) -> Result<BTreeMap<String, ScreenMembership>, CommandError> {
+    let stock_id: i64 = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(symbol)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?;
+
+    let tickers = vec![symbol.to_string()];
+    let mut membership = BTreeMap::new();
+
+    // Each screener runs twice for the one ticker: with its defaults to decide Pass, then
+    // with every threshold open to tell Fail (has data) from Unknown (no data)
+    let piotroski = if let Some(row) = get_piotroski_screening_results_internal(pool, tickers.clone(), None, None).await?.first() {
+        ScreenMembership { status: ScreenStatus::Pass, score: Some(row.f_score_complete as f64) }
+    } else if let Some(row) = get_piotroski_screening_results_internal(pool, tickers.clone(), Some(open_piotroski_criteria()), None)
+        .await?
+        .first()
+    {
+        ScreenMembership { status: ScreenStatus::Fail, score: Some(row.f_score_complete as f64) }
+    } else {
+        ScreenMembership::unknown()
+    };
+    membership.insert("piotroski".to_string(), piotroski);
+
+    let oshaughnessy = if let Some(row) = get_oshaughnessy_screening_results_internal(pool, tickers.clone(), None, None).await?.first() {
+        ScreenMembership { status: ScreenStatus::Pass, score: Some(row.composite_percentile) }
+    } else if let Some(row) = get_oshaughnessy_screening_results_internal(pool, tickers.clone(), Some(open_oshaughnessy_criteria()), None)
+        .await?
+        .first()
+    {
+        ScreenMembership { status: ScreenStatus::Fail, score: Some(row.composite_percentile) }
+    } else {
+        ScreenMembership::unknown()
+    };
+    membership.insert("oshaughnessy".to_string(), oshaughnessy);
+
+    // Graham reports stocks it excludes for negative equity separately; those fail too
+    let stock_ids = [stock_id];
+    let graham = if let Some(row) = run_graham_screening_for(pool, None, None, Some(&stock_ids)).await?.results.first() {
+        ScreenMembership { status: ScreenStatus::Pass, score: row.pe_times_pb }
+    } else {
+        let open = run_graham_screening_for(pool, Some(open_graham_criteria()), None, Some(&stock_ids)).await?;
+        match open.results.first() {
+            Some(row) => ScreenMembership { status: ScreenStatus::Fail, score: row.pe_times_pb },
+            None if !open.excluded.is_empty() => ScreenMembership { status: ScreenStatus::Fail, score: None },
+            None => ScreenMembership::unknown(),
+        }
+    };
+    membership.insert("graham".to_string(), graham);
+
+    // The P/S screen only returns passing stocks, so data coverage is checked directly
+    let open = UniverseFilter { min_market_cap: 0.0, min_avg_daily_volume: 0.0 };
+    let current = load_current_ps_points(pool, &tickers, &open).await?;
+    let history = load_ps_history_stats(pool, &current, 0.0).await?;
+    let ps_screen = match current.first() {
+        Some(point) if history.get(&point.stock_id).is_some_and(|h| h.data_points >= PS_SCREEN_MIN_HISTORY) => {
+            let universe = UniverseFilter::new(Some(PS_SCREEN_DEFAULT_MIN_MARKET_CAP), None);
+            let passing = get_undervalued_stocks_by_ps_internal(pool, &tickers, 1, &universe, PsSortKey::default()).await?;
+            let status = if passing.is_empty() { ScreenStatus::Fail } else { ScreenStatus::Pass };
+            ScreenMembership { status, score: Some(point.ps_ratio_ttm) }
+        }
+        _ => ScreenMembership::unknown(),
+    };
+    membership.insert("ps_undervalued".to_string(), ps_screen);
+
+    Ok(membership)
+}
@@ -0,0 +1,280 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use crate::commands::custom_screening::CustomScreenCriteria;
+use crate::commands::oshaughnessy_screening::OShaughnessyScreeningCriteria;
+use crate::commands::piotroski_screening::PiotroskilScreeningCriteria;
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+use ts_rs::TS;
+
+/// Version of the criteria structs presets are saved under; bump when a criteria struct
+/// renames or drops a field
+pub const CRITERIA_SCHEMA_VERSION: i32 = 1;
+
+/// Screens with saved presets; each owns one criteria struct
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenType {
+    Piotroski,
+    #[serde(rename = "oshaughnessy")]
+    OShaughnessy,
+    Custom,
+}
+
+impl ScreenType {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ScreenType::Piotroski => "piotroski",
+            ScreenType::OShaughnessy => "oshaughnessy",
+            ScreenType::Custom => "custom",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreeningPreset {
+    pub screen_type: ScreenType,
+    pub name: String,
+    /// The screen's criteria struct as JSON
+    #[ts(type = "Record<string, unknown>")]
+    pub criteria: serde_json::Value,
+    pub schema_version: i32,
+    /// Stored fields the current criteria struct no longer has; they are ignored when run
+    pub unknown_fields: Vec<String>,
+    /// Why the stored criteria no longer load, if they don't
+    pub error: Option<String>,
+}
+
+/// Round-trip `criteria` through the criteria struct: returns the struct and the input fields it dropped
+fn check_criteria<T: DeserializeOwned + Serialize>(criteria: &serde_json::Value) -> Result<(T, Vec<String>), String> {
+    let input = criteria.as_object().ok_or("Criteria must be a JSON object")?;
+    let typed: T = serde_json::from_value(criteria.clone()).map_err(|e| e.to_string())?;
+    let known = serde_json::to_value(&typed).map_err(|e| e.to_string())?;
+
+    let unknown_fields = input
+        .keys()
+        .filter(|key| known.get(key.as_str()).is_none())
+        .cloned()
+        .collect();
+    Ok((typed, unknown_fields))
+}
+
+/// Criteria normalized to the current struct's fields, plus the fields that were dropped
+fn normalize_criteria(screen_type: ScreenType, criteria: &serde_json::Value) -> Result<(serde_json::Value, Vec<String>), String> {
+    fn normalized<T: DeserializeOwned + Serialize>(criteria: &serde_json::Value) -> Result<(serde_json::Value, Vec<String>), String> {
+        let (typed, unknown_fields) = check_criteria::<T>(criteria)?;
+        Ok((serde_json::to_value(typed).map_err(|e| e.to_string())?, unknown_fields))
+    }
+
+    match screen_type {
+        ScreenType::Piotroski => normalized::<PiotroskilScreeningCriteria>(criteria),
+        ScreenType::OShaughnessy => normalized::<OShaughnessyScreeningCriteria>(criteria),
+        ScreenType::Custom => {
+            let (typed, unknown_fields) = check_criteria::<CustomScreenCriteria>(criteria)?;
+            typed.validate().map_err(|e| e.to_string())?;
+            Ok((serde_json::to_value(typed).map_err(|e| e.to_string())?, unknown_fields))
+        }
+    }
+}
+
+/// Save a preset, replacing any of the same type and name; returns the type's presets
+#[tauri::command]
+pub async fn save_screening_preset(
+    screen_type: ScreenType,
+    name: String,
+    criteria: serde_json::Value,
+) -> Result<Vec<ScreeningPreset>, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    save_screening_preset_internal(&pool, screen_type, &name, &criteria).await?;
+    get_screening_presets_internal(&pool, screen_type).await
+}
+
+#[tauri::command]
+pub async fn get_screening_presets(screen_type: ScreenType) -> Result<Vec<ScreeningPreset>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_screening_presets_internal(&pool, screen_type).await
+}
+
+/// Returns the type's remaining presets
+#[tauri::command]
+pub async fn delete_screening_preset(screen_type: ScreenType, name: String) -> Result<Vec<ScreeningPreset>, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    delete_screening_preset_internal(&pool, screen_type, &name).await?;
+    get_screening_presets_internal(&pool, screen_type).await
+}
+
+/// Criteria are rejected if they don't match the screen's criteria struct, including any
+/// field the struct doesn't have
+pub async fn save_screening_preset_internal(
+    pool: &SqlitePool,
+    screen_type: ScreenType,
+    name: &str,
+    criteria: &serde_json::Value,
+) -> Result<(), CommandError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CommandError::validation("name", "Preset name is required"));
+    }
+
+    let (normalized, unknown_fields) =
+        normalize_criteria(screen_type, criteria).map_err(|e| CommandError::validation("criteria", e))?;
+    if !unknown_fields.is_empty() {
+        return Err(CommandError::validation(
+            "criteria",
+            format!("Unknown {} criteria fields: {}", screen_type.as_str(), unknown_fields.join(", ")),
+        ));
+    }
+
+    sqlx::query(
+        "INSERT INTO screening_presets (screen_type, name, criteria, schema_version) VALUES (?, ?, ?, ?)
+         ON CONFLICT(screen_type, name) DO UPDATE SET
+            criteria = excluded.criteria,
+            schema_version = excluded.schema_version,
+            updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(screen_type.as_str())
+    .bind(name)
+    .bind(normalized.to_string())
+    .bind(CRITERIA_SCHEMA_VERSION)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Presets by name. Criteria saved under an older struct are reported, not dropped:
+/// fields it no longer has go to `unknown_fields`, and criteria that fail to load set `error`.
+pub async fn get_screening_presets_internal(pool: &SqlitePool, screen_type: ScreenType) -> Result<Vec<ScreeningPreset>, CommandError> {
+    let rows = sqlx::query(
+        "SELECT name, criteria, schema_version FROM screening_presets WHERE screen_type = ? ORDER BY name"
+    )
+    .bind(screen_type.as_str())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let stored: String = row.get("criteria");
+            let (criteria, unknown_fields, error) = match serde_json::from_str::<serde_json::Value>(&stored) {
+                Ok(raw) => match normalize_criteria(screen_type, &raw) {
+                    Ok((_, unknown_fields)) => (raw, unknown_fields, None),
+                    Err(e) => (raw, Vec::new(), Some(e)),
+                },
+                Err(e) => (serde_json::Value::Null, Vec::new(), Some(e.to_string())),
+            };
+            ScreeningPreset {
+                screen_type,
+                name: row.get("name"),
+                criteria,
+                schema_version: row.get("schema_version"),
+                unknown_fields,
+                error,
+            }
+        })
+        .collect())
+}
+
+/// Presets that load into the screen's current criteria struct, with their names
+pub async fn load_typed_presets<T: DeserializeOwned + Serialize>(
+    pool: &SqlitePool,
+    screen_type: ScreenType,
+) -> Result<Vec<(String, T)>, CommandError> {
+    Ok(get_screening_presets_internal(pool, screen_type)
+        .await?
+        .into_iter()
+        .filter(|preset| preset.error.is_none())
+        .filter_map(|preset| check_criteria::<T>(&preset.criteria).ok().map(|(typed, _)| (preset.name, typed)))
+        .collect())
+}
+
+pub async fn delete_screening_preset_internal(pool: &SqlitePool, screen_type: ScreenType, name: &str) -> Result<(), CommandError> {
+    sqlx::query("DELETE FROM screening_presets WHERE screen_type = ? AND name = ?")
+        .bind(screen_type.as_str())
+        .bind(name.trim())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn presets_pool() -> SqlitePool {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query(
+            "CREATE TABLE screening_presets (id INTEGER PRIMARY KEY AUTOINCREMENT, screen_type TEXT NOT NULL, name TEXT NOT NULL,
+                criteria TEXT NOT NULL, schema_version INTEGER NOT NULL, created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP, UNIQUE(screen_type, name))"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_presets_are_scoped_by_screen_type() {
+        let pool = presets_pool().await;
+
+        save_screening_preset_internal(&pool, ScreenType::Piotroski, "Strict", &json!({ "min_f_score": 8 })).await.unwrap();
+        save_screening_preset_internal(&pool, ScreenType::OShaughnessy, "Strict", &json!({ "max_ps_ratio": 1.0 })).await.unwrap();
+        // Same type and name replaces
+        save_screening_preset_internal(&pool, ScreenType::Piotroski, "Strict", &json!({ "min_f_score": 9 })).await.unwrap();
+
+        let piotroski = get_screening_presets_internal(&pool, ScreenType::Piotroski).await.unwrap();
+        assert_eq!(piotroski.len(), 1);
+        assert_eq!(piotroski[0].criteria["min_f_score"], 9);
+        // Missing optional fields are stored explicitly
+        assert!(piotroski[0].criteria["sectors"].is_null());
+        assert_eq!((piotroski[0].schema_version, piotroski[0].error.as_deref()), (CRITERIA_SCHEMA_VERSION, None));
+
+        let typed = load_typed_presets::<PiotroskilScreeningCriteria>(&pool, ScreenType::Piotroski).await.unwrap();
+        assert_eq!(typed[0].1.min_f_score, Some(9));
+
+        delete_screening_preset_internal(&pool, ScreenType::Piotroski, "Strict").await.unwrap();
+        assert!(get_screening_presets_internal(&pool, ScreenType::Piotroski).await.unwrap().is_empty());
+        assert_eq!(get_screening_presets_internal(&pool, ScreenType::OShaughnessy).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_and_invalid_criteria_are_reported() {
+        let pool = presets_pool().await;
+
+        let err = save_screening_preset_internal(&pool, ScreenType::Piotroski, "Typo", &json!({ "min_fscore": 8 }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("min_fscore"), "{}", err);
+        let err = save_screening_preset_internal(&pool, ScreenType::Piotroski, "Wrong type", &json!({ "min_f_score": "high" }))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CommandError::Validation { .. }));
+
+        // Rows saved before a criteria field was removed, and before a type change
+        sqlx::query(
+            "INSERT INTO screening_presets (screen_type, name, criteria, schema_version) VALUES
+                ('piotroski', 'Old', '{\"min_f_score\": 7, \"min_confidence\": 0.8}', 0),
+                ('piotroski', 'Broken', '{\"min_f_score\": [7]}', 0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        let presets = get_screening_presets_internal(&pool, ScreenType::Piotroski).await.unwrap();
+        let broken = presets.iter().find(|p| p.name == "Broken").unwrap();
+        assert!(broken.error.is_some());
+        let old = presets.iter().find(|p| p.name == "Old").unwrap();
+        assert_eq!((old.unknown_fields.clone(), old.error.clone()), (vec!["min_confidence".to_string()], None));
+
+        // Only the loadable preset is offered to the screen
+        let typed = load_typed_presets::<PiotroskilScreeningCriteria>(&pool, ScreenType::Piotroski).await.unwrap();
+        assert_eq!(typed.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["Old"]);
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use crate::commands::screening_presets::ScreenType;
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+use ts_rs::TS;
+
+/// Snapshots older than this are pruned when no retention is given
+pub const DEFAULT_SNAPSHOT_RETENTION_DAYS: i64 = 365;
+
+/// metadata table key holding the JSON-encoded history retention
+const HISTORY_RETENTION_KEY: &str = "screening_history_retention";
+
+/// How screen run history is compacted: every run from the last `keep_all_days` days, then the
+/// latest run per screen and calendar week
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreeningHistoryRetention {
+    /// Compact after every persisted screen run
+    pub prune_after_screening: bool,
+    pub keep_all_days: i64,
+}
+
+impl Default for ScreeningHistoryRetention {
+    fn default() -> Self {
+        Self {
+            prune_after_screening: false,
+            keep_all_days: 30,
+        }
+    }
+}
+
+/// Runs and their rows removed by a compaction, or that would be on a dry run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreeningHistoryPruneReport {
+    pub dry_run: bool,
+    pub snapshots_removed: i64,
+    pub items_removed: i64,
+}
+
+/// Snapshots older than the cutoff that aren't the latest run of their screen in their week.
+/// Weeks are keyed by their Monday, so a week spanning New Year stays one week.
+const COMPACTABLE_SNAPSHOTS: &str = "
+    SELECT id FROM (
+        SELECT id,
+               ROW_NUMBER() OVER (
+                   PARTITION BY screen_type, date(run_at, '-' || ((strftime('%w', run_at) + 6) % 7) || ' days')
+                   ORDER BY run_at DESC, id DESC
+               ) AS week_rn
+        FROM screening_snapshots
+        WHERE run_at < datetime('now', ?)
+    )
+    WHERE week_rn > 1";
+
+/// One passing stock in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SnapshotItem {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// 1-based position in the screen's output
+    pub rank: i32,
+    /// F-Score for Piotroski, composite percentile for O'Shaughnessy, the sort metric for custom screens
+    pub score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreenSnapshot {
+    pub id: i64,
+    pub screen_type: ScreenType,
+    /// The criteria the screen ran with, as JSON
+    #[ts(type = "Record<string, unknown>")]
+    pub criteria: serde_json::Value,
+    pub result_count: i32,
+    pub run_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SnapshotRankChange {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub previous_rank: i32,
+    pub current_rank: i32,
+    pub previous_score: Option<f64>,
+    pub current_score: Option<f64>,
+}
+
+/// How a screen's output changed from one snapshot to a later one
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SnapshotDiff {
+    pub from_snapshot_id: i64,
+    pub to_snapshot_id: i64,
+    /// In the later snapshot only, with their new rank
+    pub entered: Vec<SnapshotItem>,
+    /// In the earlier snapshot only, with their old rank
+    pub left: Vec<SnapshotItem>,
+    pub rank_changes: Vec<SnapshotRankChange>,
+}
+
+/// Snapshots of one screen, newest first
+#[tauri::command]
+pub async fn get_screen_snapshots(screen_type: ScreenType) -> Result<Vec<ScreenSnapshot>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_screen_snapshots_internal(&pool, screen_type).await
+}
+
+/// Stocks that entered, left, or changed rank between snapshot `a` and snapshot `b`
+#[tauri::command]
+pub async fn diff_screen_snapshots(a: i64, b: i64) -> Result<SnapshotDiff, CommandError> {
+    let pool = get_database_connection().await?;
+
+    diff_screen_snapshots_internal(&pool, a, b).await
+}
+
+/// Delete snapshots older than `retention_days`; returns the number removed
+#[tauri::command]
+pub async fn prune_screen_snapshots(retention_days: Option<i64>) -> Result<u64, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    prune_screen_snapshots_internal(&pool, retention_days.unwrap_or(DEFAULT_SNAPSHOT_RETENTION_DAYS)).await
+}
+
+/// Keep every run from the last `keep_all_days` days (default from settings) and one per screen
+/// and week before that. With `dry_run` nothing is deleted and the report says what would be.
+#[tauri::command]
+pub async fn prune_screening_history(keep_all_days: Option<i64>, dry_run: Option<bool>) -> Result<ScreeningHistoryPruneReport, CommandError> {
+    let pool = get_database_connection().await?;
+    let dry_run = dry_run.unwrap_or(false);
+    if !dry_run {
+        ensure_writable(&pool).await?;
+    }
+
+    let keep_all_days = match keep_all_days {
+        Some(days) => days,
+        None => load_screening_history_retention(&pool).await?.keep_all_days,
+    };
+    prune_screening_history_internal(&pool, keep_all_days, dry_run).await
+}
+
+/// Stored retention, or the defaults when none has been saved (or it can't be parsed)
+pub async fn load_screening_history_retention(pool: &SqlitePool) -> Result<ScreeningHistoryRetention, CommandError> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM metadata WHERE key = ?")
+        .bind(HISTORY_RETENTION_KEY)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(value.and_then(|value| serde_json::from_str(&value).ok()).unwrap_or_default())
+}
+
+pub async fn save_screening_history_retention(pool: &SqlitePool, retention: &ScreeningHistoryRetention) -> Result<(), CommandError> {
+    if retention.keep_all_days < 0 {
+        return Err(CommandError::validation("keep_all_days", "Must not be negative"));
+    }
+    let value = serde_json::to_string(retention).map_err(|e| CommandError::internal(e.to_string()))?;
+    sqlx::query(
+        "INSERT INTO metadata (key, value, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(HISTORY_RETENTION_KEY)
+    .bind(value)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a screen run; `items` must already be in rank order. Returns the snapshot id.
+/// History is compacted afterwards when settings ask for it.
+pub async fn save_screen_snapshot_internal(
+    pool: &SqlitePool,
+    screen_type: ScreenType,
+    criteria: &serde_json::Value,
+    items: &[SnapshotItem],
+) -> Result<i64, CommandError> {
+    let mut tx = pool.begin().await?;
+
+    let snapshot_id = sqlx::query("INSERT INTO screening_snapshots (screen_type, criteria, result_count) VALUES (?, ?, ?)")
+        .bind(screen_type.as_str())
+        .bind(criteria.to_string())
+        .bind(items.len() as i32)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+    for item in items {
+        sqlx::query("INSERT INTO snapshot_items (snapshot_id, stock_id, symbol, rank, score) VALUES (?, ?, ?, ?, ?)")
+            .bind(snapshot_id)
+            .bind(item.stock_id)
+            .bind(&item.symbol)
+            .bind(item.rank)
+            .bind(item.score)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    // The run is saved either way; a failed compaction is retried after the next one
+    match load_screening_history_retention(pool).await {
+        Ok(retention) if retention.prune_after_screening => {
+            if let Err(e) = prune_screening_history_internal(pool, retention.keep_all_days, false).await {
+                eprintln!("Screening history compaction error: {}", e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Screening history retention error: {}", e),
+    }
+    Ok(snapshot_id)
+}
+
+pub async fn get_screen_snapshots_internal(pool: &SqlitePool, screen_type: ScreenType) -> Result<Vec<ScreenSnapshot>, CommandError> {
+    let rows = sqlx::query(
+        "SELECT id, criteria, result_count, run_at FROM screening_snapshots
+         WHERE screen_type = ? ORDER BY run_at DESC, id DESC"
+    )
+    .bind(screen_type.as_str())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| ScreenSnapshot {
+            id: row.get("id"),
+            screen_type,
+            criteria: serde_json::from_str(row.get::<&str, _>("criteria")).unwrap_or(serde_json::Value::Null),
+            result_count: row.get("result_count"),
+            run_at: row.get("run_at"),
+        })
+        .collect())
+}
+
+async fn load_snapshot_items(pool: &SqlitePool, snapshot_id: i64) -> Result<(String, Vec<SnapshotItem>), CommandError> {
+    let screen_type: Option<String> = sqlx::query_scalar("SELECT screen_type FROM screening_snapshots WHERE id = ?")
+        .bind(snapshot_id)
+        .fetch_optional(pool)
+        .await?;
+    let screen_type = screen_type
+        .ok_or_else(|| CommandError::not_found("screening_snapshot", format!("No snapshot with id {}", snapshot_id)))?;
+
+    let items = sqlx::query("SELECT stock_id, symbol, rank, score FROM snapshot_items WHERE snapshot_id = ? ORDER BY rank")
+        .bind(snapshot_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| SnapshotItem {
+            stock_id: row.get("stock_id"),
+            symbol: row.get("symbol"),
+            rank: row.get("rank"),
+            score: row.get("score"),
+        })
+        .collect();
+    Ok((screen_type, items))
+}
+
+pub async fn diff_screen_snapshots_internal(pool: &SqlitePool, a: i64, b: i64) -> Result<SnapshotDiff, CommandError> {
+    let (from_type, from_items) = load_snapshot_items(pool, a).await?;
+    let (to_type, to_items) = load_snapshot_items(pool, b).await?;
+    if from_type != to_type {
+        return Err(CommandError::validation(
+            "b",
+            format!("Snapshot {} is a {} screen but {} is a {} screen", a, from_type, b, to_type),
+        ));
+    }
+
+    let from_by_stock: HashMap<i64, &SnapshotItem> = from_items.iter().map(|item| (item.stock_id, item)).collect();
+    let to_by_stock: HashMap<i64, &SnapshotItem> = to_items.iter().map(|item| (item.stock_id, item)).collect();
+
+    let entered = to_items.iter().filter(|item| !from_by_stock.contains_key(&item.stock_id)).cloned().collect();
+    let left = from_items.iter().filter(|item| !to_by_stock.contains_key(&item.stock_id)).cloned().collect();
+    let rank_changes = to_items
+        .iter()
+        .filter_map(|current| {
+            let previous = from_by_stock.get(&current.stock_id)?;
+            (previous.rank != current.rank).then(|| SnapshotRankChange {
+                stock_id: current.stock_id,
+                symbol: current.symbol.clone(),
+                previous_rank: previous.rank,
+                current_rank: current.rank,
+                previous_score: previous.score,
+                current_score: current.score,
+            })
+        })
+        .collect();
+
+    Ok(SnapshotDiff { from_snapshot_id: a, to_snapshot_id: b, entered, left, rank_changes })
+}
+
+pub async fn prune_screen_snapshots_internal(pool: &SqlitePool, retention_days: i64) -> Result<u64, CommandError> {
+    if retention_days < 0 {
+        return Err(CommandError::validation("retention_days", "Must not be negative"));
+    }
+    let cutoff = format!("-{} days", retention_days);
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "DELETE FROM snapshot_items WHERE snapshot_id IN
+            (SELECT id FROM screening_snapshots WHERE run_at < datetime('now', ?))"
+    )
+    .bind(&cutoff)
+    .execute(&mut *tx)
+    .await?;
+    let removed = sqlx::query("DELETE FROM screening_snapshots WHERE run_at < datetime('now', ?)")
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+
+    Ok(removed)
+}
+
+pub async fn prune_screening_history_internal(
+    pool: &SqlitePool,
+    keep_all_days: i64,
+    dry_run: bool,
+) -> Result<ScreeningHistoryPruneReport, CommandError> {
+    if keep_all_days < 0 {
+        return Err(CommandError::validation("keep_all_days", "Must not be negative"));
+    }
+    let cutoff = format!("-{} days", keep_all_days);
+
+    let mut tx = pool.begin().await?;
+    let items_query = format!("SELECT COUNT(*) FROM snapshot_items WHERE snapshot_id IN ({})", COMPACTABLE_SNAPSHOTS);
+    let items_removed: i64 = sqlx::query_scalar(&items_query).bind(&cutoff).fetch_one(&mut *tx).await?;
+    let snapshots_query = format!("SELECT COUNT(*) FROM ({})", COMPACTABLE_SNAPSHOTS);
+    let snapshots_removed: i64 = sqlx::query_scalar(&snapshots_query).bind(&cutoff).fetch_one(&mut *tx).await?;
+
+    if !dry_run {
+        sqlx::query(&format!("DELETE FROM snapshot_items WHERE snapshot_id IN ({})", COMPACTABLE_SNAPSHOTS))
+            .bind(&cutoff)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!("DELETE FROM screening_snapshots WHERE id IN ({})", COMPACTABLE_SNAPSHOTS))
+            .bind(&cutoff)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(ScreeningHistoryPruneReport { dry_run, snapshots_removed, items_removed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::tests::fixtures::migrated_pool;
+
+    fn item(stock_id: i64, symbol: &str, rank: i32, score: f64) -> SnapshotItem {
+        SnapshotItem { stock_id, symbol: symbol.to_string(), rank, score: Some(score) }
+    }
+
+    #[tokio::test]
+    async fn test_diff_reports_entered_left_and_rank_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        let criteria = json!({ "min_f_score": 7 });
+
+        let q1 = save_screen_snapshot_internal(
+            &pool,
+            ScreenType::Piotroski,
+            &criteria,
+            &[item(1, "AAA", 1, 9.0), item(2, "BBB", 2, 8.0), item(3, "CCC", 3, 7.0)],
+        )
+        .await
+        .unwrap();
+        let q2 = save_screen_snapshot_internal(
+            &pool,
+            ScreenType::Piotroski,
+            &criteria,
+            &[item(2, "BBB", 1, 9.0), item(1, "AAA", 2, 8.0), item(4, "DDD", 3, 7.0)],
+        )
+        .await
+        .unwrap();
+
+        let snapshots = get_screen_snapshots_internal(&pool, ScreenType::Piotroski).await.unwrap();
+        assert_eq!(snapshots.iter().map(|s| s.id).collect::<Vec<_>>(), vec![q2, q1]);
+        assert_eq!((snapshots[0].result_count, &snapshots[0].criteria), (3, &criteria));
+        assert!(get_screen_snapshots_internal(&pool, ScreenType::OShaughnessy).await.unwrap().is_empty());
+
+        let diff = diff_screen_snapshots_internal(&pool, q1, q2).await.unwrap();
+        assert_eq!(diff.entered, vec![item(4, "DDD", 3, 7.0)]);
+        assert_eq!(diff.left, vec![item(3, "CCC", 3, 7.0)]);
+        assert_eq!(
+            diff.rank_changes.iter().map(|c| (c.symbol.as_str(), c.previous_rank, c.current_rank)).collect::<Vec<_>>(),
+            vec![("BBB", 2, 1), ("AAA", 1, 2)]
+        );
+
+        let other = save_screen_snapshot_internal(&pool, ScreenType::Custom, &json!({}), &[]).await.unwrap();
+        assert!(matches!(diff_screen_snapshots_internal(&pool, q1, other).await, Err(CommandError::Validation { .. })));
+        assert!(matches!(diff_screen_snapshots_internal(&pool, q1, 999).await, Err(CommandError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_prune_removes_snapshots_past_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        let old = save_screen_snapshot_internal(&pool, ScreenType::OShaughnessy, &json!({}), &[item(1, "AAA", 1, 5.0)])
+            .await
+            .unwrap();
+        let recent = save_screen_snapshot_internal(&pool, ScreenType::OShaughnessy, &json!({}), &[item(1, "AAA", 1, 5.0)])
+            .await
+            .unwrap();
+        sqlx::query("UPDATE screening_snapshots SET run_at = datetime('now', '-100 days') WHERE id = ?")
+            .bind(old)
+            .execute(&pool).await.unwrap();
+
+        assert_eq!(prune_screen_snapshots_internal(&pool, 90).await.unwrap(), 1);
+
+        let remaining = get_screen_snapshots_internal(&pool, ScreenType::OShaughnessy).await.unwrap();
+        assert_eq!(remaining.iter().map(|s| s.id).collect::<Vec<_>>(), vec![recent]);
+        let orphaned: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM snapshot_items WHERE snapshot_id = ?")
+            .bind(old)
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(orphaned, 0);
+    }
+
+    #[tokio::test]
+    async fn test_compaction_keeps_recent_runs_and_one_per_week() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        let mut ids = Vec::new();
+        // Mon, Wed and Fri of one week, the next Tuesday, then today
+        for run_at in ["2024-01-01 10:00:00", "2024-01-03 10:00:00", "2024-01-05 10:00:00", "2024-01-09 10:00:00", ""] {
+            let id = save_screen_snapshot_internal(&pool, ScreenType::Piotroski, &json!({}), &[item(1, "AAA", 1, 8.0), item(2, "BBB", 2, 7.0)])
+                .await
+                .unwrap();
+            if !run_at.is_empty() {
+                sqlx::query("UPDATE screening_snapshots SET run_at = ? WHERE id = ?")
+                    .bind(run_at).bind(id)
+                    .execute(&pool).await.unwrap();
+            }
+            ids.push(id);
+        }
+        // Another screen's only run that week is its weekly run
+        let other = save_screen_snapshot_internal(&pool, ScreenType::Custom, &json!({}), &[]).await.unwrap();
+        sqlx::query("UPDATE screening_snapshots SET run_at = '2024-01-02 10:00:00' WHERE id = ?")
+            .bind(other)
+            .execute(&pool).await.unwrap();
+
+        let expected = ScreeningHistoryPruneReport { dry_run: true, snapshots_removed: 2, items_removed: 4 };
+        assert_eq!(prune_screening_history_internal(&pool, 30, true).await.unwrap(), expected);
+        assert_eq!(get_screen_snapshots_internal(&pool, ScreenType::Piotroski).await.unwrap().len(), 5);
+
+        let report = prune_screening_history_internal(&pool, 30, false).await.unwrap();
+        assert_eq!(report, ScreeningHistoryPruneReport { dry_run: false, ..expected });
+        let remaining = get_screen_snapshots_internal(&pool, ScreenType::Piotroski).await.unwrap();
+        assert_eq!(remaining.iter().map(|s| s.id).collect::<Vec<_>>(), vec![ids[4], ids[3], ids[2]]);
+        assert_eq!(get_screen_snapshots_internal(&pool, ScreenType::Custom).await.unwrap().len(), 1);
+
+        // Diffs between what is left still work across the gap
+        let diff = diff_screen_snapshots_internal(&pool, ids[2], ids[4]).await.unwrap();
+        assert!(diff.entered.is_empty() && diff.left.is_empty());
+
+        // With compaction on in settings, saving a run compacts too: the Monday before the
+        // kept Tuesday goes
+        sqlx::query("INSERT INTO screening_snapshots (screen_type, criteria, result_count, run_at) VALUES ('piotroski', '{}', 0, '2024-01-08 10:00:00')")
+            .execute(&pool).await.unwrap();
+        save_screening_history_retention(&pool, &ScreeningHistoryRetention { prune_after_screening: true, keep_all_days: 30 })
+            .await
+            .unwrap();
+        let latest = save_screen_snapshot_internal(&pool, ScreenType::Piotroski, &json!({}), &[]).await.unwrap();
+        let remaining = get_screen_snapshots_internal(&pool, ScreenType::Piotroski).await.unwrap();
+        assert_eq!(remaining.iter().map(|s| s.id).collect::<Vec<_>>(), vec![latest, ids[4], ids[3], ids[2]]);
+        assert!(matches!(prune_screening_history_internal(&pool, -1, true).await, Err(CommandError::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_compaction_keeps_one_run_for_a_week_spanning_new_year() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // Monday 2024-12-30 and Thursday 2025-01-02 fall in the same week
+        let mut ids = Vec::new();
+        for run_at in ["2024-12-30 10:00:00", "2025-01-02 10:00:00"] {
+            let id = save_screen_snapshot_internal(&pool, ScreenType::Piotroski, &json!({}), &[]).await.unwrap();
+            sqlx::query("UPDATE screening_snapshots SET run_at = ? WHERE id = ?")
+                .bind(run_at).bind(id)
+                .execute(&pool).await.unwrap();
+            ids.push(id);
+        }
+
+        let report = prune_screening_history_internal(&pool, 30, false).await.unwrap();
+        assert_eq!(report.snapshots_removed, 1);
+        let remaining = get_screen_snapshots_internal(&pool, ScreenType::Piotroski).await.unwrap();
+        assert_eq!(remaining.iter().map(|s| s.id).collect::<Vec<_>>(), vec![ids[1]]);
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use crate::commands::screening_snapshots::{
+    load_screening_history_retention, save_screening_history_retention, ScreeningHistoryRetention,
+};
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+use crate::tools::freshness_policy::{load_freshness_policy, save_freshness_policy, FreshnessPolicy};
+use crate::tools::http_api::{self, load_http_api_settings, save_http_api_settings, HttpApiSettings, HttpApiStatus};
+use crate::tools::notifications::{load_notification_preferences, save_notification_preferences, NotificationPreferences};
+use crate::tools::refresh_scheduler::{load_refresh_schedule, save_refresh_schedule, RefreshSchedule};
+use ts_rs::TS;
+
+/// User-adjustable settings persisted in the metadata table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AppSettings {
+    pub freshness_policy: FreshnessPolicy,
+    /// Which events raise desktop notifications; all on unless saved otherwise
+    #[serde(default)]
+    pub notification_preferences: NotificationPreferences,
+    /// Port and bearer token of the local HTTP API
+    #[serde(default)]
+    pub http_api: HttpApiSettings,
+    /// How much screen run history is kept, and whether it is compacted after each run
+    #[serde(default)]
+    pub screening_history: ScreeningHistoryRetention,
+}
+
+#[tauri::command]
+pub async fn get_settings() -> Result<AppSettings, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_settings_internal(&pool).await
+}
+
+#[tauri::command]
+pub async fn set_settings(settings: AppSettings) -> Result<AppSettings, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    set_settings_internal(&pool, settings).await
+}
+
+pub async fn get_settings_internal(pool: &SqlitePool) -> Result<AppSettings, CommandError> {
+    Ok(AppSettings {
+        freshness_policy: load_freshness_policy(pool).await?,
+        notification_preferences: load_notification_preferences(pool).await?,
+        http_api: load_http_api_settings(pool).await?,
+        screening_history: load_screening_history_retention(pool).await?,
+    })
+}
+
+pub async fn set_settings_internal(pool: &SqlitePool, settings: AppSettings) -> Result<AppSettings, CommandError> {
+    if let Err(e) = settings.freshness_policy.validate() {
+        return Err(CommandError::validation("freshness_policy", e.to_string()));
+    }
+    if let Err(e) = settings.http_api.validate() {
+        return Err(CommandError::validation("http_api", e.to_string()));
+    }
+
+    save_freshness_policy(pool, &settings.freshness_policy).await?;
+    save_notification_preferences(pool, &settings.notification_preferences).await?;
+    save_http_api_settings(pool, &settings.http_api).await?;
+    save_screening_history_retention(pool, &settings.screening_history).await?;
+    // A running API picks up a new port or token now rather than at the next launch
+    if let Err(e) = http_api::restart_http_server_if_changed(pool.clone(), &settings.http_api).await {
+        return Err(CommandError::internal(format!("HTTP API failed to restart on port {}: {}", settings.http_api.port, e)));
+    }
+    get_settings_internal(pool).await
+}
+
+#[tauri::command]
+pub async fn get_refresh_schedule() -> Result<RefreshSchedule, CommandError> {
+    let pool = get_database_connection().await?;
+
+    Ok(load_refresh_schedule(&pool).await?)
+}
+
+#[tauri::command]
+pub async fn set_refresh_schedule(schedule: RefreshSchedule) -> Result<RefreshSchedule, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    set_refresh_schedule_internal(&pool, schedule).await
+}
+
+/// Stop (or resume) automatic refreshes without changing the schedule
+#[tauri::command]
+pub async fn pause_scheduled_refresh(paused: bool) -> Result<RefreshSchedule, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    let schedule = load_refresh_schedule(&pool).await?;
+    set_refresh_schedule_internal(&pool, RefreshSchedule { paused, ..schedule }).await
+}
+
+pub async fn set_refresh_schedule_internal(pool: &SqlitePool, schedule: RefreshSchedule) -> Result<RefreshSchedule, CommandError> {
+    if let Err(e) = schedule.validate() {
+        return Err(CommandError::validation("schedule", e.to_string()));
+    }
+
+    save_refresh_schedule(pool, &schedule).await?;
+    Ok(load_refresh_schedule(pool).await?)
+}
+
+/// Serve the local HTTP API with the saved port and token, and start it with
+/// the app from now on. Restarts the server if it is already running.
+#[tauri::command]
+pub async fn start_http_api() -> Result<HttpApiStatus, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    let settings = HttpApiSettings { enabled: true, ..load_http_api_settings(&pool).await? };
+    save_http_api_settings(&pool, &settings).await?;
+    if let Err(e) = http_api::start_http_server(pool, &settings).await {
+        return Err(CommandError::internal(format!("HTTP API failed to start on port {}: {}", settings.port, e)));
+    }
+    Ok(http_api::http_server_status().await)
+}
+
+/// Stop the HTTP API and keep it off at the next launch
+#[tauri::command]
+pub async fn stop_http_api() -> Result<HttpApiStatus, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    let settings = HttpApiSettings { enabled: false, ..load_http_api_settings(&pool).await? };
+    save_http_api_settings(&pool, &settings).await?;
+    http_api::stop_http_server().await;
+    Ok(http_api::http_server_status().await)
+}
+
+#[tauri::command]
+pub async fn get_http_api_status() -> Result<HttpApiStatus, CommandError> {
+    Ok(http_api::http_server_status().await)
+}
@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, Row};
-use crate::database::helpers::get_database_connection;
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+use crate::tools::company_profiles::{self, CompanyProfile, ProfileField};
+use crate::tools::query_cache::global_query_cache;
+use crate::tools::stock_archive::{self, StockArchiveResult};
+use crate::tools::stock_merge::{self, DuplicateStockGroup, StockMergeResult};
+use tracing::warn;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StockInfo {
@@ -21,12 +27,16 @@ pub struct StockWithData {
 
 
 #[tauri::command]
-pub async fn get_all_stocks() -> Result<Vec<StockInfo>, String> {
+pub async fn get_all_stocks() -> Result<Vec<StockInfo>, CommandError> {
     let pool = get_database_connection().await?;
-    
+
+    get_all_stocks_internal(&pool).await
+}
+
+pub async fn get_all_stocks_internal(pool: &SqlitePool) -> Result<Vec<StockInfo>, CommandError> {
     let query = "SELECT id, symbol, company_name, sector FROM stocks";
     
-    match sqlx::query(query).fetch_all(&pool).await {
+    match sqlx::query(query).fetch_all(pool).await {
         Ok(rows) => {
             let stocks: Vec<StockInfo> = rows.into_iter().map(|row| {
                 StockInfo {
@@ -40,56 +50,110 @@ pub async fn get_all_stocks() -> Result<Vec<StockInfo>, String> {
         }
         Err(e) => {
             eprintln!("Database query error: {}", e);
-            Err(format!("Failed to fetch all stocks: {}", e))
+            Err(e.into())
         }
     }
 }
 
+/// Rows returned by search_stocks
+const SEARCH_LIMIT: i64 = 100;
+
+/// Name tier over the FTS index: bm25 weights symbol, company_name, description, sector, industry
+const FTS_NAME_TIER: &str =
+    "SELECT rowid AS id, 2 AS tier, bm25(stocks_fts, 10.0, 5.0, 1.0, 2.0, 2.0) AS score FROM stocks_fts WHERE stocks_fts MATCH ?3";
+
+/// Name tier for an empty query: every stock, unranked
+const LIKE_NAME_TIER: &str =
+    "SELECT id, 2 AS tier, 0.0 AS score FROM stocks WHERE company_name LIKE ?3 OR symbol LIKE ?3";
+
 #[tauri::command]
-pub async fn search_stocks(query: String) -> Result<Vec<StockWithData>, String> {
+pub async fn search_stocks(query: String) -> Result<Vec<StockWithData>, CommandError> {
     let pool = get_database_connection().await?;
-    
-    let search_query = format!("%{}%", query);
-    let sql_query = "
-        SELECT 
+
+    search_stocks_internal(&pool, &query).await
+}
+
+/// Exact symbol matches first, then symbol prefixes and former symbols of merged stocks,
+/// then name matches ranked by bm25.
+pub async fn search_stocks_internal(pool: &SqlitePool, query: &str) -> Result<Vec<StockWithData>, CommandError> {
+    let query = query.trim();
+    let symbol_prefix = format!("{}%", query);
+
+    let rows = match fts_prefix_query(query) {
+        Some(fts_query) => run_search(pool, FTS_NAME_TIER, query, &symbol_prefix, &fts_query).await?,
+        // Nothing to match on: list stocks by symbol
+        None => run_search(pool, LIKE_NAME_TIER, query, &symbol_prefix, "%").await?,
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let has_data = row.get::<i64, _>("has_data") > 0;
+            StockWithData {
+                id: row.get::<i64, _>("id"),
+                symbol: row.get::<String, _>("symbol"),
+                company_name: row.get::<String, _>("company_name"),
+                has_data,
+                data_count: if has_data { 1 } else { 0 }, // Simplified for performance
+            }
+        })
+        .collect())
+}
+
+async fn run_search(
+    pool: &SqlitePool,
+    name_tier: &str,
+    symbol: &str,
+    symbol_prefix: &str,
+    name_pattern: &str,
+) -> Result<Vec<sqlx::sqlite::SqliteRow>, sqlx::Error> {
+    let sql_query = format!(
+        "WITH matches AS (
+            SELECT id, 0 AS tier, 0.0 AS score FROM stocks WHERE symbol = ?1 COLLATE NOCASE
+            UNION ALL
+            SELECT id, 1 AS tier, 0.0 AS score FROM stocks WHERE symbol LIKE ?2
+            UNION ALL
+            SELECT stock_id, 1 AS tier, 0.0 AS score FROM symbol_aliases WHERE symbol = ?1 COLLATE NOCASE
+            UNION ALL
+            {}
+        ),
+        best AS (
+            SELECT id, MIN(tier) AS tier, MIN(score) AS score FROM matches GROUP BY id
+        )
+        SELECT
             s.id,
-            s.symbol, 
+            s.symbol,
             s.company_name,
             CASE WHEN EXISTS(SELECT 1 FROM daily_prices dp WHERE dp.stock_id = s.id) THEN 1 ELSE 0 END as has_data
-        FROM stocks s
-        WHERE s.symbol LIKE ? OR s.company_name LIKE ?
-        ORDER BY s.symbol
-        LIMIT 100
-    ";
-    
-    match sqlx::query(sql_query)
-        .bind(&search_query)
-        .bind(&search_query)
-        .fetch_all(&pool)
-        .await 
-    {
-        Ok(rows) => {
-            let stocks: Vec<StockWithData> = rows.into_iter().map(|row| {
-                let has_data = row.get::<i64, _>("has_data") > 0;
-                StockWithData {
-                    id: row.get::<i64, _>("id"),
-                    symbol: row.get::<String, _>("symbol"),
-                    company_name: row.get::<String, _>("company_name"),
-                    has_data,
-                    data_count: if has_data { 1 } else { 0 }, // Simplified for performance
-                }
-            }).collect();
-            Ok(stocks)
-        }
-        Err(e) => {
-            eprintln!("Database query error: {}", e);
-            Err(format!("Failed to search stocks: {}", e))
-        }
-    }
+        FROM best b
+        JOIN stocks s ON s.id = b.id
+        ORDER BY b.tier, CASE WHEN b.tier = 2 THEN b.score END, s.symbol
+        LIMIT ?4",
+        name_tier
+    );
+
+    sqlx::query(&sql_query)
+        .bind(symbol)
+        .bind(symbol_prefix)
+        .bind(name_pattern)
+        .bind(SEARCH_LIMIT)
+        .fetch_all(pool)
+        .await
+}
+
+/// FTS5 query matching every word of `query` as a prefix. Words are quoted so punctuation
+/// in names like "O'Reilly" or "Coca-Cola" can't form query syntax; None when there are no words.
+fn fts_prefix_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
 }
 
 #[tauri::command]
-pub async fn get_stocks_with_data_status() -> Result<Vec<StockWithData>, String> {
+pub async fn get_stocks_with_data_status() -> Result<Vec<StockWithData>, CommandError> {
     let pool = get_database_connection().await?;
     
     let query = "
@@ -118,13 +182,13 @@ pub async fn get_stocks_with_data_status() -> Result<Vec<StockWithData>, String>
         }
         Err(e) => {
             eprintln!("Database query error: {}", e);
-            Err(format!("Failed to fetch stocks with data status: {}", e))
+            Err(e.into())
         }
     }
 }
 
 #[tauri::command]
-pub async fn get_stocks_paginated(limit: i64, offset: i64) -> Result<Vec<StockWithData>, String> {
+pub async fn get_stocks_paginated(limit: i64, offset: i64) -> Result<Vec<StockWithData>, CommandError> {
     let pool = get_database_connection().await?;
     
     let query = "
@@ -159,13 +223,13 @@ pub async fn get_stocks_paginated(limit: i64, offset: i64) -> Result<Vec<StockWi
         }
         Err(e) => {
             eprintln!("Database query error: {}", e);
-            Err(format!("Failed to fetch paginated stocks: {}", e))
+            Err(e.into())
         }
     }
 }
 
 #[tauri::command]
-pub async fn get_sp500_symbols() -> Result<Vec<String>, String> {
+pub async fn get_sp500_symbols() -> Result<Vec<String>, CommandError> {
     let pool = get_database_connection().await?;
 
     // Read-only: Just fetch from database (sp500_symbols is a view on stocks where is_sp500 = 1)
@@ -173,11 +237,10 @@ pub async fn get_sp500_symbols() -> Result<Vec<String>, String> {
     get_sp500_from_database(&pool).await
 }
 
-async fn get_sp500_from_database(pool: &SqlitePool) -> Result<Vec<String>, String> {
+async fn get_sp500_from_database(pool: &SqlitePool) -> Result<Vec<String>, CommandError> {
     let rows = sqlx::query("SELECT symbol FROM sp500_symbols ORDER BY symbol")
         .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch S&P 500 symbols from database: {}", e))?;
+        .await?;
 
     let symbols: Vec<String> = rows.into_iter()
         .map(|row| row.get::<String, _>("symbol"))
@@ -187,11 +250,105 @@ async fn get_sp500_from_database(pool: &SqlitePool) -> Result<Vec<String>, Strin
     Ok(symbols)
 }
 
+/// SEC company profile with manual overrides applied, plus sector and latest market cap
+#[tauri::command]
+pub async fn get_company_profile(stock_id: i64) -> Result<CompanyProfile, CommandError> {
+    let pool = get_database_connection().await?;
+
+    company_profiles::load_company_profile(&pool, stock_id)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("No stock with id {}", stock_id)))
+}
+
+/// Override one profile field by hand; a None value clears the override. SEC refreshes
+/// never overwrite overridden fields.
+#[tauri::command]
+pub async fn set_company_profile_override(
+    stock_id: i64,
+    field: ProfileField,
+    value: Option<String>,
+) -> Result<CompanyProfile, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    let value = value.as_deref().map(str::trim);
+    if value == Some("") {
+        return Err(CommandError::validation("value", "Override value can't be empty; pass null to clear it"));
+    }
+    if field == ProfileField::Employees && value.is_some_and(|v| v.parse::<i32>().map_or(true, |n| n < 0)) {
+        return Err(CommandError::validation("value", "Employees must be a non-negative whole number"));
+    }
+
+    if company_profiles::load_company_profile(&pool, stock_id).await?.is_none() {
+        return Err(CommandError::not_found("stock", format!("No stock with id {}", stock_id)));
+    }
+
+    company_profiles::set_company_profile_override(&pool, stock_id, field, value).await?;
+    company_profiles::load_company_profile(&pool, stock_id)
+        .await?
+        .ok_or_else(|| CommandError::not_found("stock", format!("No stock with id {}", stock_id)))
+}
+
+/// Mark a stock delisted instead of deleting it. `move_data` moves its prices and statements
+/// to the archive tables; `purge` then deletes the stock and all its rows for good.
+#[tauri::command]
+pub async fn archive_stock(
+    symbol: String,
+    move_data: Option<bool>,
+    purge: Option<bool>,
+) -> Result<StockArchiveResult, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    let archived = stock_archive::archive_stock(&pool, &symbol, move_data.unwrap_or(false)).await?;
+    global_query_cache().invalidate_all();
+    if purge.unwrap_or(false) {
+        warn!(%symbol, "Purging {} and all its data", symbol);
+        let purged = stock_archive::purge_stock(&pool, &symbol).await?;
+        global_query_cache().invalidate_all();
+        return Ok(purged);
+    }
+    Ok(archived)
+}
+
+/// Reactivate an archived stock, moving its archived rows back
+#[tauri::command]
+pub async fn restore_stock(symbol: String) -> Result<StockArchiveResult, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    let restored = stock_archive::restore_stock(&pool, &symbol).await?;
+    global_query_cache().invalidate_all();
+    Ok(restored)
+}
+
+/// Stocks sharing a CIK or company name, for review before merging them
+#[tauri::command]
+pub async fn find_duplicate_stocks() -> Result<Vec<DuplicateStockGroup>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    Ok(stock_merge::find_duplicate_stocks(&pool).await?)
+}
+
+/// Merge a duplicate stock into the survivor, moving its history and keeping its symbol as an
+/// alias. Refused when overlapping price days disagree.
+#[tauri::command]
+pub async fn merge_stocks(survivor_id: i64, duplicate_id: i64) -> Result<StockMergeResult, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    warn!(survivor_id, duplicate_id, "Merging stock {} into {}", duplicate_id, survivor_id);
+    let merged = stock_merge::merge_stocks(&pool, survivor_id, duplicate_id).await?;
+    global_query_cache().invalidate_all();
+    Ok(merged)
+}
+
 #[cfg(test)]
 mod tests {
     use sqlx::{SqlitePool, pool::PoolOptions};
     use std::time::Duration;
     use anyhow::Result;
+    use crate::tests::fixtures::migrated_pool;
 
     /// Simple test database setup for stocks module tests
     struct TestDatabase {
@@ -253,6 +410,54 @@ mod tests {
         println!("✅ search_stocks test passed");
     }
 
+    async fn search_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name) VALUES
+                (1, 'MCD', 'McDonald''s Corp'),
+                (2, 'ORLY', 'O''Reilly Automotive'),
+                (3, 'KO', 'Coca-Cola Co'),
+                (4, 'COKE', 'Coca-Cola Consolidated'),
+                (5, 'MSFT', 'Microsoft Corp'),
+                (6, 'AAPL', 'Apple Inc.'),
+                (7, 'APLE', 'Apple Hospitality REIT Inc.')"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    async fn symbols(pool: &SqlitePool, query: &str) -> Vec<String> {
+        super::search_stocks_internal(pool, query).await.unwrap().into_iter().map(|s| s.symbol).collect()
+    }
+
+    #[test]
+    fn test_fts_prefix_query_quotes_words() {
+        assert_eq!(super::fts_prefix_query("O'Reilly").as_deref(), Some("\"O\"* \"Reilly\"*"));
+        assert_eq!(super::fts_prefix_query("coca-cola").as_deref(), Some("\"coca\"* \"cola\"*"));
+        assert_eq!(super::fts_prefix_query(" - ' "), None);
+    }
+
+    #[tokio::test]
+    async fn test_search_stocks_ranks_names_with_punctuation() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = search_pool(&dir).await;
+
+        assert_eq!(symbols(&pool, "o'reilly").await, ["ORLY"]);
+        assert_eq!(symbols(&pool, "McDonald's").await, ["MCD"]);
+        assert_eq!(symbols(&pool, "mcdon").await, ["MCD"]);
+        assert_eq!(symbols(&pool, "Coca-Cola").await, ["COKE", "KO"]);
+        // Exact symbol ahead of name matches; shorter name ranks ahead on bm25
+        assert_eq!(symbols(&pool, "ko").await, ["KO"]);
+        assert_eq!(symbols(&pool, "apple").await, ["AAPL", "APLE"]);
+        assert_eq!(symbols(&pool, "APLE").await, ["APLE"]);
+
+        // Triggers keep the index in step with stocks
+        sqlx::query("UPDATE stocks SET company_name = 'Apple Computer' WHERE id = 6").execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM stocks WHERE id = 2").execute(&pool).await.unwrap();
+        assert_eq!(symbols(&pool, "computer").await, ["AAPL"]);
+        assert!(symbols(&pool, "reilly").await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_sp500_symbols() {
         let _test_db = TestDatabase::new().await.unwrap();
@@ -0,0 +1,197 @@
+use sqlx::{SqlitePool, Row};
+use crate::commands::stocks::StockInfo;
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+
+/// Tags are compared case-insensitively; store and match them trimmed and lowercase
+fn normalize_tag(tag: &str) -> Result<String, CommandError> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err(CommandError::validation("tag", "Tag must not be empty"));
+    }
+    Ok(tag)
+}
+
+/// Restrict a screening query to stocks carrying any of the given tags
+pub fn push_tag_filter(query: &mut String, params: &mut Vec<String>, stock_id_column: &str, tags: &[String]) {
+    let tags: Vec<String> = tags
+        .iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if tags.is_empty() {
+        return;
+    }
+
+    let placeholders = tags.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    query.push_str(&format!(
+        " AND {} IN (SELECT stock_id FROM stock_tags WHERE tag IN ({}))",
+        stock_id_column, placeholders
+    ));
+    params.extend(tags);
+}
+
+#[tauri::command]
+pub async fn add_stock_tag(stock_id: i64, tag: String) -> Result<Vec<String>, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    add_stock_tag_internal(&pool, stock_id, &tag).await
+}
+
+#[tauri::command]
+pub async fn remove_stock_tag(stock_id: i64, tag: String) -> Result<Vec<String>, CommandError> {
+    let pool = get_database_connection().await?;
+    ensure_writable(&pool).await?;
+
+    remove_stock_tag_internal(&pool, stock_id, &tag).await
+}
+
+#[tauri::command]
+pub async fn get_tags_for_stock(stock_id: i64) -> Result<Vec<String>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_tags_for_stock_internal(&pool, stock_id).await
+}
+
+#[tauri::command]
+pub async fn get_stocks_by_tag(tag: String) -> Result<Vec<StockInfo>, CommandError> {
+    let pool = get_database_connection().await?;
+
+    get_stocks_by_tag_internal(&pool, &tag).await
+}
+
+/// Adding a tag the stock already has is a no-op; returns the stock's tags
+pub async fn add_stock_tag_internal(pool: &SqlitePool, stock_id: i64, tag: &str) -> Result<Vec<String>, CommandError> {
+    let tag = normalize_tag(tag)?;
+
+    let exists = sqlx::query("SELECT 1 FROM stocks WHERE id = ?")
+        .bind(stock_id)
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Err(CommandError::not_found("stock", format!("No stock with id {}", stock_id)));
+    }
+
+    sqlx::query("INSERT OR IGNORE INTO stock_tags (stock_id, tag) VALUES (?, ?)")
+        .bind(stock_id)
+        .bind(&tag)
+        .execute(pool)
+        .await?;
+
+    get_tags_for_stock_internal(pool, stock_id).await
+}
+
+/// Removing a tag the stock doesn't have is a no-op; returns the stock's tags
+pub async fn remove_stock_tag_internal(pool: &SqlitePool, stock_id: i64, tag: &str) -> Result<Vec<String>, CommandError> {
+    let tag = normalize_tag(tag)?;
+
+    sqlx::query("DELETE FROM stock_tags WHERE stock_id = ? AND tag = ?")
+        .bind(stock_id)
+        .bind(&tag)
+        .execute(pool)
+        .await?;
+
+    get_tags_for_stock_internal(pool, stock_id).await
+}
+
+pub async fn get_tags_for_stock_internal(pool: &SqlitePool, stock_id: i64) -> Result<Vec<String>, CommandError> {
+    let rows = sqlx::query("SELECT tag FROM stock_tags WHERE stock_id = ? ORDER BY tag")
+        .bind(stock_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(|row| row.get::<String, _>("tag")).collect())
+}
+
+pub async fn get_stocks_by_tag_internal(pool: &SqlitePool, tag: &str) -> Result<Vec<StockInfo>, CommandError> {
+    let tag = normalize_tag(tag)?;
+
+    let rows = sqlx::query(
+        "SELECT s.id, s.symbol, s.company_name, s.sector
+         FROM stocks s
+         INNER JOIN stock_tags t ON t.stock_id = s.id
+         WHERE t.tag = ?
+         ORDER BY s.symbol"
+    )
+    .bind(&tag)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| StockInfo {
+            id: row.get("id"),
+            symbol: row.get("symbol"),
+            company_name: row.get("company_name"),
+            sector: row.try_get::<Option<String>, _>("sector").unwrap_or(None),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn tagged_pool() -> SqlitePool {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE stocks (id INTEGER PRIMARY KEY, symbol TEXT NOT NULL, company_name TEXT NOT NULL, sector TEXT)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE stock_tags (id INTEGER PRIMARY KEY AUTOINCREMENT, stock_id INTEGER NOT NULL,
+             tag TEXT NOT NULL COLLATE NOCASE, created_at DATETIME DEFAULT CURRENT_TIMESTAMP, UNIQUE(stock_id, tag))"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, sector) VALUES
+                (1, 'AAPL', 'Apple Inc.', 'Technology'),
+                (2, 'XOM', 'Exxon Mobil Corp', 'Energy'),
+                (3, 'KO', 'Coca-Cola Co', 'Consumer Staples')"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_are_idempotent_and_case_insensitive() {
+        let pool = tagged_pool().await;
+
+        add_stock_tag_internal(&pool, 1, "Watchlist").await.unwrap();
+        let tags = add_stock_tag_internal(&pool, 1, "  WATCHLIST ").await.unwrap();
+        assert_eq!(tags, vec!["watchlist".to_string()]);
+
+        remove_stock_tag_internal(&pool, 1, "watchList").await.unwrap();
+        let tags = remove_stock_tag_internal(&pool, 1, "watchlist").await.unwrap();
+        assert!(tags.is_empty());
+
+        assert!(matches!(add_stock_tag_internal(&pool, 99, "avoid").await, Err(CommandError::NotFound { .. })));
+        assert!(matches!(add_stock_tag_internal(&pool, 1, "   ").await, Err(CommandError::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_filtering_by_tag() {
+        let pool = tagged_pool().await;
+        add_stock_tag_internal(&pool, 1, "watchlist").await.unwrap();
+        add_stock_tag_internal(&pool, 2, "avoid").await.unwrap();
+        add_stock_tag_internal(&pool, 3, "earnings-play").await.unwrap();
+        add_stock_tag_internal(&pool, 3, "watchlist").await.unwrap();
+
+        let symbols: Vec<String> = get_stocks_by_tag_internal(&pool, "Watchlist").await.unwrap()
+            .into_iter().map(|s| s.symbol).collect();
+        assert_eq!(symbols, vec!["AAPL".to_string(), "KO".to_string()]);
+
+        // Screener filter: any of the tags matches
+        let mut query = String::from("SELECT symbol FROM stocks WHERE 1=1");
+        let mut params = Vec::new();
+        push_tag_filter(&mut query, &mut params, "id", &["AVOID".to_string(), "earnings-play".to_string()]);
+        query.push_str(" ORDER BY symbol");
+
+        let mut sqlx_query = sqlx::query(&query);
+        for param in params {
+            sqlx_query = sqlx_query.bind(param);
+        }
+        let rows = sqlx_query.fetch_all(&pool).await.unwrap();
+        let symbols: Vec<String> = rows.iter().map(|r| r.get("symbol")).collect();
+        assert_eq!(symbols, vec!["KO".to_string(), "XOM".to_string()]);
+    }
+}
@@ -35,9 +35,9 @@ fn get_database_url() -> Result<String, String> {
         return Ok(url);
     }
 
-    // Fallback to DATABASE_PATH for backwards compatibility
+    // Fallback to DATABASE_PATH for backwards compatibility; relative paths are under the data dir
     if let Ok(path) = env::var("DATABASE_PATH") {
-        return Ok(format!("sqlite:{}", path));
+        return Ok(format!("sqlite:{}", crate::paths::data_paths().resolve(path).display()));
     }
 
     // Final fallback to PROJECT_ROOT based path
@@ -46,9 +46,31 @@ fn get_database_url() -> Result<String, String> {
         return Ok(format!("sqlite:{}", db_path));
     }
 
-    // Ultimate fallback - relative path (not recommended for production)
-    eprintln!("⚠️  WARNING: Using relative database path. Set DATABASE_URL or PROJECT_ROOT environment variables.");
-    Ok("sqlite:src-tauri/db/stocks.db".to_string())
+    // Ultimate fallback - the database in the app's data directory, or where earlier builds kept it
+    let paths = crate::paths::data_paths();
+    let db_path = paths.database_path_or_legacy(crate::paths::LEGACY_DATABASE_PATH);
+    if db_path != paths.database_path() {
+        eprintln!("⚠️  Using the database at its old location {}; move it to {} to use the data directory", db_path.display(), paths.database_path().display());
+    }
+    Ok(format!("sqlite:{}", db_path.display()))
+}
+
+/// Filesystem path of the configured database (DATABASE_URL without the scheme and options)
+pub fn get_database_path() -> Result<String, String> {
+    init_env_vars();
+    let database_url = get_database_url()?;
+    let path = database_url
+        .trim_start_matches("sqlite:")
+        .trim_start_matches("//")
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    if path.is_empty() || path == ":memory:" {
+        return Err(format!("Database URL does not point to a file: {}", database_url));
+    }
+    Ok(path)
 }
 
 /// Initialize environment variables from .env file
@@ -62,6 +84,48 @@ fn init_env_vars() {
     }
 }
 
+/// Environment variable that opens the database read-only ("1", "true" or "yes")
+pub const READ_ONLY_ENV: &str = "DATABASE_READ_ONLY";
+
+/// True when `DATABASE_READ_ONLY` asks for an analysis-only, read-only database
+pub fn read_only_requested() -> bool {
+    env::var(READ_ONLY_ENV)
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Connection options for `database_url`. Read-only connections are opened with
+/// SQLITE_OPEN_READONLY and `query_only`, which `ensure_writable` checks.
+pub fn database_connect_options(database_url: &str, read_only: bool) -> Result<sqlx::sqlite::SqliteConnectOptions, String> {
+    let options = sqlx::sqlite::SqliteConnectOptions::from_str(database_url)
+        .map_err(|e| format!("Database URL parsing failed: {}", e))?
+        .busy_timeout(std::time::Duration::from_secs(30))
+        .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
+        .create_if_missing(false); // Don't create database automatically - it should exist
+
+    Ok(if read_only {
+        options.read_only(true).pragma("query_only", "ON")
+    } else {
+        options.journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+    })
+}
+
+/// True when the pool was opened read-only
+pub async fn is_read_only(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let query_only: i64 = sqlx::query_scalar("PRAGMA query_only").fetch_one(pool).await?;
+    Ok(query_only != 0)
+}
+
+/// Refuse a write up front when the pool is read-only, instead of failing partway through
+pub async fn ensure_writable(pool: &SqlitePool) -> Result<(), crate::error::CommandError> {
+    if is_read_only(pool).await? {
+        return Err(crate::error::CommandError::read_only(
+            "The database is open read-only; unset DATABASE_READ_ONLY to make changes",
+        ));
+    }
+    Ok(())
+}
+
 /// Get database connection (test-aware and environment-configured)
 pub async fn get_database_connection() -> Result<SqlitePool, String> {
     // Check if we have a test database pool
@@ -77,7 +141,12 @@ pub async fn get_database_connection() -> Result<SqlitePool, String> {
     // Get database URL from environment
     let database_url = get_database_url()?;
 
-    println!("🔗 Connecting to database: {}", database_url.replace("sqlite:", "sqlite://"));
+    let read_only = read_only_requested();
+    println!(
+        "🔗 Connecting to database: {}{}",
+        database_url.replace("sqlite:", "sqlite://"),
+        if read_only { " (read-only)" } else { "" }
+    );
 
     // Use production database with WAL mode and connection pooling for better concurrency
     sqlx::sqlite::SqlitePoolOptions::new()
@@ -85,14 +154,8 @@ pub async fn get_database_connection() -> Result<SqlitePool, String> {
         .min_connections(5)
         .acquire_timeout(std::time::Duration::from_secs(10))
         .idle_timeout(Some(std::time::Duration::from_secs(600)))
-        .connect_with(
-            sqlx::sqlite::SqliteConnectOptions::from_str(&database_url)
-                .map_err(|e| format!("Database URL parsing failed: {}", e))?
-                .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
-                .busy_timeout(std::time::Duration::from_secs(30))
-                .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
-                .create_if_missing(false) // Don't create database automatically - it should exist
-        ).await
+        .connect_with(database_connect_options(&database_url, read_only)?)
+        .await
         .map_err(|e| format!("Database connection failed ({}): {}", database_url, e))
 }
 
@@ -164,14 +227,34 @@ pub async fn batch_update_pe_ratios(
     Ok(updated_count)
 }
 
-/// Get stock ID by symbol
+/// Get stock ID by symbol, falling back to former symbols in symbol_aliases
 pub async fn get_stock_id_by_symbol(pool: &SqlitePool, symbol: &str) -> Result<Option<i64>, String> {
-    let result = sqlx::query("SELECT id FROM stocks WHERE symbol = ?1")
-        .bind(symbol)
-        .fetch_optional(pool).await
-        .map_err(|e| format!("Failed to get stock ID: {}", e))?;
-    
-    Ok(result.map(|row| row.get::<i64, _>("id")))
+    Ok(resolve_symbol(pool, symbol).await?.map(|(id, _)| id))
+}
+
+/// Stock ID and canonical symbol for a symbol: the stock listed under it, else the stock it
+/// is a former symbol of
+pub async fn resolve_symbol(pool: &SqlitePool, symbol: &str) -> Result<Option<(i64, String)>, String> {
+    sqlx::query_as(
+        "SELECT id, symbol FROM (
+             SELECT id, symbol, 0 AS priority FROM stocks WHERE symbol = ?1
+             UNION ALL
+             SELECT s.id, s.symbol, 1 FROM symbol_aliases a JOIN stocks s ON s.id = a.stock_id WHERE a.symbol = ?1
+         )
+         ORDER BY priority
+         LIMIT 1"
+    )
+    .bind(symbol)
+    .fetch_optional(pool).await
+    .map_err(|e| format!("Failed to get stock ID: {}", e))
+}
+
+/// `resolve_symbol` for commands: the stock ID and current symbol, or NotFound
+pub async fn resolve_stock(pool: &SqlitePool, symbol: &str) -> Result<(i64, String), crate::error::CommandError> {
+    resolve_symbol(pool, symbol)
+        .await
+        .map_err(crate::error::CommandError::internal)?
+        .ok_or_else(|| crate::error::CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))
 }
 
 /// Check if stock has any price data
@@ -205,6 +288,35 @@ pub async fn get_latest_price_date(pool: &SqlitePool, stock_id: i64) -> Result<O
     Ok(result.and_then(|row| row.get::<Option<NaiveDate>, _>("latest_date")))
 }
 
+/// Latest close on or before `date`, with the date it is from, so weekends and holidays resolve
+/// to the previous trading day
+pub async fn get_price_on_or_before(pool: &SqlitePool, stock_id: i64, date: NaiveDate) -> Result<Option<(NaiveDate, f64)>, String> {
+    sqlx::query_as(
+        "SELECT date, close_price FROM daily_prices
+         WHERE stock_id = ?1 AND date <= ?2 AND close_price IS NOT NULL
+         ORDER BY date DESC LIMIT 1"
+    )
+    .bind(stock_id)
+    .bind(date)
+    .fetch_optional(pool).await
+    .map_err(|e| format!("Failed to get price on or before {}: {}", date, e))
+}
+
+/// Set each stock's missing first_trading_date to its earliest stored price date, in one pass.
+/// Stocks without prices are left unset. Returns the number of stocks updated.
+pub async fn backfill_first_trading_dates(pool: &SqlitePool) -> Result<u64, String> {
+    let result = sqlx::query(
+        "UPDATE stocks
+         SET first_trading_date = (SELECT MIN(date) FROM daily_prices WHERE stock_id = stocks.id)
+         WHERE first_trading_date IS NULL
+           AND EXISTS (SELECT 1 FROM daily_prices WHERE stock_id = stocks.id)"
+    )
+    .execute(pool).await
+    .map_err(|e| format!("Failed to backfill first trading dates: {}", e))?;
+
+    Ok(result.rows_affected())
+}
+
 /// Clear all price data for a stock
 pub async fn clear_price_data(pool: &SqlitePool, stock_id: i64) -> Result<u64, String> {
     let result = sqlx::query("DELETE FROM daily_prices WHERE stock_id = ?1")
@@ -213,4 +325,76 @@ pub async fn clear_price_data(pool: &SqlitePool, stock_id: i64) -> Result<u64, S
         .map_err(|e| format!("Failed to clear price data: {}", e))?;
     
     Ok(result.rows_affected())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_backfill_first_trading_dates_uses_earliest_price() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, first_trading_date) VALUES
+                (1, 'NEW', 'Recent IPO', NULL),
+                (2, 'SET', 'Already Set', '1990-01-02'),
+                (3, 'NOPX', 'No Prices', NULL)"
+        )
+        .execute(&pool).await.unwrap();
+        for (id, date) in [(1, "2021-03-15"), (1, "2020-12-10"), (1, "2021-01-04"), (2, "2000-01-03")] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?1, ?2, 10.0, 10.0, 10.0, 10.0)"
+            )
+            .bind(id).bind(date)
+            .execute(&pool).await.unwrap();
+        }
+
+        assert_eq!(backfill_first_trading_dates(&pool).await.unwrap(), 1);
+
+        let dates: Vec<(String, Option<NaiveDate>)> =
+            sqlx::query_as("SELECT symbol, first_trading_date FROM stocks ORDER BY id")
+                .fetch_all(&pool).await.unwrap();
+        assert_eq!(dates, vec![
+            ("NEW".to_string(), NaiveDate::from_ymd_opt(2020, 12, 10)),
+            ("SET".to_string(), NaiveDate::from_ymd_opt(1990, 1, 2)),
+            ("NOPX".to_string(), None),
+        ]);
+
+        // Nothing left to fill
+        assert_eq!(backfill_first_trading_dates(&pool).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_database_rejects_writes_and_serves_reads() {
+        use crate::commands::analysis::get_quality_metrics;
+        use crate::commands::tags::{add_stock_tag, get_tags_for_stock};
+        use crate::error::CommandError;
+        use crate::tests::fixtures::FixtureBuilder;
+
+        let fixture = FixtureBuilder::new().with_stock("AAPL").build().await;
+        sqlx::query("INSERT INTO stock_tags (stock_id, tag) VALUES (1, 'watchlist')")
+            .execute(&fixture.pool).await.unwrap();
+
+        let url = format!("sqlite:{}", fixture.path().display());
+        let options = database_connect_options(&url, true).unwrap();
+        let pool = SqlitePool::connect_with(options).await.unwrap();
+        set_test_database_pool(pool.clone()).await;
+
+        let rejected = add_stock_tag(1, "avoid".to_string()).await;
+        let tags = get_tags_for_stock(1).await;
+        // Computed from the statements without being stored
+        let quality = get_quality_metrics(1).await;
+        clear_test_database_pool().await;
+
+        assert!(matches!(rejected, Err(CommandError::ReadOnly { .. })));
+        assert_eq!(tags.unwrap(), vec!["watchlist".to_string()]);
+        assert!(quality.is_ok());
+
+        // SQLite refuses writes that skip the check
+        assert!(sqlx::query("DELETE FROM stock_tags").execute(&pool).await.is_err());
+    }
+}
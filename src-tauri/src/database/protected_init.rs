@@ -1,7 +1,132 @@
-use sqlx::SqlitePool;
+use sqlx::{SqlitePool, Row};
+use sqlx::migrate::Migrator;
+use std::fmt;
 use std::path::Path;
 use crate::database::migrations::DatabaseManager;
 
+static MIGRATOR: Migrator = sqlx::migrate!("./db/migrations");
+
+/// Comparison of the migrations applied to a database against the ones compiled into this build
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaCompatibilityReport {
+    pub applied_version: Option<i64>,
+    pub build_version: Option<i64>,
+    pub pending: Vec<PendingMigration>,
+    /// Applied versions this build doesn't know about (database is newer than the build)
+    pub unknown_applied: Vec<i64>,
+    /// Applied versions whose migration file has changed since they ran
+    pub modified: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+    pub destructive: bool,
+}
+
+impl SchemaCompatibilityReport {
+    pub fn is_downgrade(&self) -> bool {
+        !self.unknown_applied.is_empty()
+    }
+
+    /// Destructive migrations only matter once the database has an existing schema
+    pub fn has_destructive_pending(&self) -> bool {
+        self.applied_version.is_some() && self.pending.iter().any(|m| m.destructive)
+    }
+
+    pub fn is_safe(&self) -> bool {
+        !self.is_downgrade() && self.modified.is_empty() && !self.has_destructive_pending()
+    }
+}
+
+impl fmt::Display for SchemaCompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = |v: Option<i64>| v.map(|v| v.to_string()).unwrap_or_else(|| "none".to_string());
+        writeln!(f, "Schema version: database={}, build={}", version(self.applied_version), version(self.build_version))?;
+
+        if self.is_downgrade() {
+            writeln!(f, "🚨 Database schema is NEWER than this build (schema downgrade); unknown versions: {:?}", self.unknown_applied)?;
+        }
+        if !self.modified.is_empty() {
+            writeln!(f, "🚨 Applied migrations were modified after they ran: {:?}", self.modified)?;
+        }
+        for migration in &self.pending {
+            let marker = if migration.destructive { "🚨 DESTRUCTIVE" } else { "pending" };
+            writeln!(f, "   {} {} {}", marker, migration.version, migration.description)?;
+        }
+        if !self.is_safe() {
+            writeln!(f, "💡 Back up the database and run: cargo run --bin db_admin -- migrate --confirm")?;
+            write!(f, "💡 Or start over with: cargo run --bin db_admin -- reset --confirm <db filename>")?;
+        }
+        Ok(())
+    }
+}
+
+/// Statements that drop or rewrite existing data
+fn is_destructive_sql(sql: &str) -> bool {
+    let normalized = sql.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase();
+    ["DROP TABLE", "DELETE FROM", "DROP COLUMN"].iter().any(|pattern| normalized.contains(pattern))
+}
+
+/// Compare applied migrations with the migrator without changing anything
+pub async fn check_schema_compatibility(pool: &SqlitePool, migrator: &Migrator) -> Result<SchemaCompatibilityReport, sqlx::Error> {
+    let has_migrations_table = sqlx::query("SELECT name FROM sqlite_master WHERE type='table' AND name='_sqlx_migrations'")
+        .fetch_optional(pool).await?
+        .is_some();
+
+    let applied: Vec<(i64, Vec<u8>)> = if has_migrations_table {
+        sqlx::query("SELECT version, checksum FROM _sqlx_migrations WHERE success = 1 ORDER BY version")
+            .fetch_all(pool).await?
+            .iter()
+            .map(|row| (row.get::<i64, _>("version"), row.get::<Vec<u8>, _>("checksum")))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let known: Vec<_> = migrator.iter().filter(|m| !m.migration_type.is_down_migration()).collect();
+
+    let mut unknown_applied = Vec::new();
+    let mut modified = Vec::new();
+    for (version, checksum) in &applied {
+        match known.iter().find(|m| m.version == *version) {
+            None => unknown_applied.push(*version),
+            Some(m) if m.checksum.as_ref() != checksum.as_slice() => modified.push(*version),
+            Some(_) => {}
+        }
+    }
+
+    let pending = known
+        .iter()
+        .filter(|m| !applied.iter().any(|(version, _)| *version == m.version))
+        .map(|m| PendingMigration {
+            version: m.version,
+            description: m.description.to_string(),
+            destructive: is_destructive_sql(&m.sql),
+        })
+        .collect();
+
+    Ok(SchemaCompatibilityReport {
+        applied_version: applied.iter().map(|(version, _)| *version).max(),
+        build_version: known.iter().map(|m| m.version).max(),
+        pending,
+        unknown_applied,
+        modified,
+    })
+}
+
+/// Halt initialization when this build can't safely migrate the database
+async fn ensure_schema_compatible(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    let report = check_schema_compatibility(pool, &MIGRATOR).await?;
+    if report.is_safe() {
+        return Ok(());
+    }
+
+    println!("🛑 Refusing to initialize database:\n{}", report);
+    Err(format!("Database initialization halted - schema is not compatible with this build\n{}", report).into())
+}
+
 /// Protected database initialization with safeguards
 pub async fn initialize_database_safely(db_path: &str) -> Result<SqlitePool, Box<dyn std::error::Error>> {
     println!("🔒 Initializing database with safety checks: {}", db_path);
@@ -25,7 +150,13 @@ pub async fn initialize_database_safely(db_path: &str) -> Result<SqlitePool, Box
                 println!("🔒 Automatic migrations DISABLED for safety");
                 println!("💡 Use manual backup and migration commands only");
                 
+                // Never hand an older build a database with a newer schema
+                let report = check_schema_compatibility(&pool, &MIGRATOR).await?;
                 pool.close().await;
+                if report.is_downgrade() || !report.modified.is_empty() {
+                    println!("🛑 Refusing to open database:\n{}", report);
+                    return Err(format!("Database schema is not compatible with this build\n{}", report).into());
+                }
                 return Ok(SqlitePool::connect(&format!("sqlite:{}?mode=rwc", db_path)).await?);
             }
             pool.close().await;
@@ -35,6 +166,9 @@ pub async fn initialize_database_safely(db_path: &str) -> Result<SqlitePool, Box
     // Safe to proceed with initialization for small/empty databases
     let db_manager = DatabaseManager::new(db_path).await?;
     
+    // Refuse downgrades and destructive migrations before touching anything
+    ensure_schema_compatible(db_manager.pool()).await?;
+    
     // Only run migrations on small databases or new databases
     let stats = db_manager.verify_data_safety().await?;
     if stats.total_stocks < 100 && stats.database_size_mb < 50.0 {
@@ -62,4 +196,176 @@ pub async fn run_manual_migration(db_path: &str, confirm: bool) -> Result<(), Bo
     db_manager.run_migrations_safely().await?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// The confirmation string `reset_database` expects: the database file name
+pub fn reset_confirmation_token(db_path: &str) -> String {
+    Path::new(db_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| db_path.to_string())
+}
+
+/// Wipe all data and recreate the schema. Requires the database file name as confirmation.
+/// Returns the backup path when `auto_backup` is set.
+pub async fn reset_database(db_path: &str, confirmation: &str, auto_backup: bool) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let expected = reset_confirmation_token(db_path);
+    if confirmation != expected {
+        return Err(format!("Reset refused: confirmation must be the database file name '{}'", expected).into());
+    }
+
+    let backup_path = if auto_backup {
+        Some(DatabaseManager::create_backup(db_path).await?)
+    } else {
+        None
+    };
+
+    println!("🧨 Resetting database {} (backup: {})", db_path, backup_path.as_deref().unwrap_or("none"));
+    let pool = SqlitePool::connect(&format!("sqlite:{}?mode=rwc", db_path)).await?;
+    let dropped = wipe_database(&pool).await?;
+    MIGRATOR.run(&pool).await?;
+    pool.close().await;
+
+    println!("✅ Database reset: dropped {} objects and recreated schema", dropped);
+    Ok(backup_path)
+}
+
+/// Drop every table and view, including the migrations ledger
+async fn wipe_database(pool: &SqlitePool) -> Result<usize, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let objects = sqlx::query("SELECT type, name FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'")
+        .fetch_all(&mut *conn).await?;
+
+    sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await?;
+    // Views first so dropping their tables doesn't leave them dangling
+    for kind in ["view", "table"] {
+        for row in objects.iter().filter(|row| row.get::<String, _>("type") == kind) {
+            let name: String = row.get("name");
+            sqlx::query(&format!("DROP {} IF EXISTS \"{}\"", kind.to_uppercase(), name))
+                .execute(&mut *conn).await?;
+        }
+    }
+    sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await?;
+
+    Ok(objects.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    async fn temp_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let path = dir.path().join("stocks.db");
+        SqlitePool::connect(&format!("sqlite:{}?mode=rwc", path.display())).await.unwrap()
+    }
+
+    async fn migrator_from(dir: &tempfile::TempDir, name: &str, files: &[(&str, &str)]) -> Migrator {
+        let migrations_dir: PathBuf = dir.path().join(name);
+        std::fs::create_dir_all(&migrations_dir).unwrap();
+        for (file, sql) in files {
+            std::fs::write(migrations_dir.join(file), sql).unwrap();
+        }
+        Migrator::new(migrations_dir.as_path()).await.unwrap()
+    }
+
+    const CREATE_STOCKS: (&str, &str) = ("1_create_stocks.sql", "CREATE TABLE stocks (id INTEGER PRIMARY KEY, symbol TEXT NOT NULL);");
+    const ADD_PRICES: (&str, &str) = ("2_add_prices.sql", "CREATE TABLE daily_prices (id INTEGER PRIMARY KEY, stock_id INTEGER, close_price REAL);");
+    const RECREATE_STOCKS: (&str, &str) = (
+        "2_recreate_stocks.sql",
+        "CREATE TABLE stocks_new (id INTEGER PRIMARY KEY, symbol TEXT NOT NULL, cik TEXT);\nDROP   TABLE stocks;\nALTER TABLE stocks_new RENAME TO stocks;",
+    );
+
+    #[tokio::test]
+    async fn test_schema_downgrade_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = temp_pool(&dir).await;
+
+        // A newer build migrated this database to version 2
+        let newer = migrator_from(&dir, "newer", &[CREATE_STOCKS, ADD_PRICES]).await;
+        newer.run(&pool).await.unwrap();
+
+        // An older build only knows version 1
+        let older = migrator_from(&dir, "older", &[CREATE_STOCKS]).await;
+        let report = check_schema_compatibility(&pool, &older).await.unwrap();
+
+        assert_eq!(report.applied_version, Some(2));
+        assert_eq!(report.build_version, Some(1));
+        assert_eq!(report.unknown_applied, vec![2]);
+        assert!(report.is_downgrade());
+        assert!(!report.is_safe());
+        assert!(report.to_string().contains("schema downgrade"));
+    }
+
+    #[tokio::test]
+    async fn test_destructive_pending_migration_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = temp_pool(&dir).await;
+
+        let old = migrator_from(&dir, "old", &[CREATE_STOCKS]).await;
+        old.run(&pool).await.unwrap();
+
+        let new = migrator_from(&dir, "new", &[CREATE_STOCKS, RECREATE_STOCKS]).await;
+        let report = check_schema_compatibility(&pool, &new).await.unwrap();
+
+        assert_eq!(report.pending.len(), 1);
+        assert!(report.pending[0].destructive);
+        assert!(report.has_destructive_pending());
+        assert!(!report.is_safe());
+    }
+
+    #[tokio::test]
+    async fn test_additive_migration_and_fresh_database_are_safe() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = temp_pool(&dir).await;
+
+        // Fresh database: destructive steps can't lose anything yet
+        let destructive = migrator_from(&dir, "destructive", &[CREATE_STOCKS, RECREATE_STOCKS]).await;
+        let report = check_schema_compatibility(&pool, &destructive).await.unwrap();
+        assert_eq!(report.applied_version, None);
+        assert!(report.is_safe());
+
+        let old = migrator_from(&dir, "old", &[CREATE_STOCKS]).await;
+        old.run(&pool).await.unwrap();
+        let additive = migrator_from(&dir, "additive", &[CREATE_STOCKS, ADD_PRICES]).await;
+        let report = check_schema_compatibility(&pool, &additive).await.unwrap();
+        assert_eq!(report.pending.len(), 1);
+        assert!(!report.pending[0].destructive);
+        assert!(report.is_safe());
+    }
+
+    #[tokio::test]
+    async fn test_reset_requires_database_filename() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = temp_pool(&dir).await;
+        sqlx::query("CREATE TABLE stocks (id INTEGER PRIMARY KEY)").execute(&pool).await.unwrap();
+        pool.close().await;
+
+        let db_path = dir.path().join("stocks.db").to_string_lossy().to_string();
+        assert_eq!(reset_confirmation_token(&db_path), "stocks.db");
+
+        let err = reset_database(&db_path, "yes", false).await.unwrap_err();
+        assert!(err.to_string().contains("stocks.db"));
+
+        // Nothing was touched
+        let pool = temp_pool(&dir).await;
+        let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name = 'stocks'")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(tables, 1);
+    }
+
+    #[tokio::test]
+    async fn test_wipe_database_drops_tables_and_views() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = temp_pool(&dir).await;
+        sqlx::query("CREATE TABLE stocks (id INTEGER PRIMARY KEY, is_sp500 BOOLEAN)").execute(&pool).await.unwrap();
+        sqlx::query("CREATE VIEW sp500_symbols AS SELECT id FROM stocks WHERE is_sp500 = 1").execute(&pool).await.unwrap();
+
+        let dropped = wipe_database(&pool).await.unwrap();
+        assert_eq!(dropped, 2);
+
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name NOT LIKE 'sqlite_%'")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
@@ -85,6 +85,18 @@ impl DatabaseManagerSqlx {
             "#
         ).execute(&pool).await?;
         
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS symbol_aliases (
+                symbol TEXT PRIMARY KEY,
+                stock_id INTEGER NOT NULL,
+                merged_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                effective_until DATE,
+                FOREIGN KEY (stock_id) REFERENCES stocks(id)
+            )
+            "#
+        ).execute(&pool).await?;
+        
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS metadata (
@@ -127,13 +139,21 @@ impl DatabaseManagerSqlx {
         Ok(result.get::<i64, _>("id"))
     }
 
-    /// Get stock by symbol - using raw SQL
+    /// Get stock by symbol - using raw SQL. A former symbol finds the stock it now trades
+    /// under, with `matched_alias` set.
     pub async fn get_stock_by_symbol(&self, symbol: &str) -> Result<Option<Stock>> {
         let row = sqlx::query(
             r#"
-            SELECT id, symbol, company_name, cik, sector, last_updated, created_at, is_sp500
-            FROM stocks
-            WHERE symbol = ?
+            SELECT s.id, s.symbol, s.company_name, s.cik, s.sector, s.last_updated, s.created_at, s.is_sp500,
+                   s.first_trading_date, m.matched_alias
+            FROM (
+                SELECT id, NULL AS matched_alias, 0 AS priority FROM stocks WHERE symbol = ?1
+                UNION ALL
+                SELECT stock_id, symbol, 1 FROM symbol_aliases WHERE symbol = ?1
+            ) m
+            JOIN stocks s ON s.id = m.id
+            ORDER BY m.priority
+            LIMIT 1
             "#
         )
         .bind(symbol)
@@ -150,6 +170,8 @@ impl DatabaseManagerSqlx {
                 last_updated: r.get::<Option<DateTime<Utc>>, _>("last_updated"),
                 created_at: r.get::<Option<DateTime<Utc>>, _>("created_at"),
                 is_sp500: r.get::<bool, _>("is_sp500"),
+                first_trading_date: r.get::<Option<NaiveDate>, _>("first_trading_date"),
+                matched_alias: r.get::<Option<String>, _>("matched_alias"),
             }
         }))
     }
@@ -158,7 +180,7 @@ impl DatabaseManagerSqlx {
     pub async fn get_active_stocks(&self) -> Result<Vec<Stock>> {
         let rows =         sqlx::query(
             r#"
-            SELECT id, symbol, company_name, cik, sector, last_updated, created_at, is_sp500
+            SELECT id, symbol, company_name, cik, sector, last_updated, created_at, is_sp500, first_trading_date
             FROM stocks
             ORDER BY symbol
             "#
@@ -176,12 +198,47 @@ impl DatabaseManagerSqlx {
                 last_updated: r.get::<Option<DateTime<Utc>>, _>("last_updated"),
                 created_at: r.get::<Option<DateTime<Utc>>, _>("created_at"),
                 is_sp500: r.get::<bool, _>("is_sp500"),
+                first_trading_date: r.get::<Option<NaiveDate>, _>("first_trading_date"),
+                matched_alias: None,
             }
         }).collect())
     }
 
-    /// Insert daily price - using raw SQL
+    /// Insert daily price - using raw SQL. Impossible bars (see `DailyPrice::validate`) are rejected.
     pub async fn insert_daily_price(&self, price: &DailyPrice) -> Result<i64> {
+        price.validate()?;
+        let id = Self::upsert_daily_price(&self.pool, price).await?;
+        crate::tools::query_cache::global_query_cache().invalidate_all();
+
+        Ok(id)
+    }
+
+    /// Insert daily prices in one transaction. With `strict` an impossible bar fails the whole
+    /// batch before anything is written; otherwise it is logged and skipped. Returns rows written.
+    pub async fn insert_daily_prices(&self, prices: &[DailyPrice], strict: bool) -> Result<usize> {
+        let mut valid = Vec::with_capacity(prices.len());
+        for price in prices {
+            match price.validate() {
+                Ok(()) => valid.push(price),
+                Err(e) if strict => return Err(e.into()),
+                Err(e) => tracing::warn!("Skipping invalid price bar: {}", e),
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for price in &valid {
+            Self::upsert_daily_price(&mut *tx, price).await?;
+        }
+        tx.commit().await?;
+        crate::tools::query_cache::global_query_cache().invalidate_all();
+
+        Ok(valid.len())
+    }
+
+    async fn upsert_daily_price<'e, E>(executor: E, price: &DailyPrice) -> Result<i64>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
         let result = sqlx::query(
             r#"
             INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price, volume, pe_ratio, market_cap, dividend_yield)
@@ -208,7 +265,7 @@ impl DatabaseManagerSqlx {
         .bind(price.pe_ratio)
         .bind(price.market_cap)
         .bind(price.dividend_yield)
-        .fetch_one(&self.pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(result.get::<i64, _>("id"))
@@ -450,3 +507,69 @@ impl DatabaseManagerSqlx {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    fn price(day: u32, open: f64, high: f64, low: f64, close: f64) -> DailyPrice {
+        DailyPrice {
+            id: None,
+            stock_id: 1,
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            open_price: open,
+            high_price: high,
+            low_price: low,
+            close_price: close,
+            volume: None,
+            pe_ratio: None,
+            market_cap: None,
+            dividend_yield: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_bars_fail_strict_batches_and_are_skipped_otherwise() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prices.db");
+        let db = DatabaseManagerSqlx::new(path.to_str().unwrap()).await.unwrap();
+
+        let batch = vec![
+            price(2, 10.0, 11.0, 9.0, 10.5),
+            // High below low
+            price(3, 10.0, 9.0, 11.0, 10.0),
+            price(4, 10.5, 12.0, 10.0, 11.5),
+        ];
+
+        let err = db.insert_daily_prices(&batch, true).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<crate::error::CommandError>(),
+            Some(crate::error::CommandError::Validation { field, .. }) if field == "low_price"
+        ));
+        assert_eq!(db.count_existing_records(1, batch[0].date, batch[2].date).await.unwrap(), 0);
+
+        assert_eq!(db.insert_daily_prices(&batch, false).await.unwrap(), 2);
+        assert_eq!(db.count_existing_records(1, batch[0].date, batch[2].date).await.unwrap(), 2);
+
+        assert!(db.insert_daily_price(&price(5, 13.0, 12.0, 10.0, 11.0)).await.is_err());
+        assert!(db.insert_daily_price(&price(5, 11.0, 12.0, 10.0, 11.0)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_stock_lookup_falls_back_to_former_symbols() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'META', 'Meta Platforms, Inc.')")
+            .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO symbol_aliases (symbol, stock_id, effective_until) VALUES ('FB', 1, '2022-06-08')")
+            .execute(&pool).await.unwrap();
+        let db = DatabaseManagerSqlx { pool };
+
+        let current = db.get_stock_by_symbol("META").await.unwrap().unwrap();
+        assert_eq!((current.symbol.as_str(), current.matched_alias), ("META", None));
+        let former = db.get_stock_by_symbol("FB").await.unwrap().unwrap();
+        assert_eq!((former.id, former.symbol.as_str(), former.matched_alias.as_deref()), (Some(1), "META", Some("FB")));
+        assert!(db.get_stock_by_symbol("TWTR").await.unwrap().is_none());
+    }
+}
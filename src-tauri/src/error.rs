@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use ts_rs::TS;
+
+/// Error returned by every Tauri command.
+///
+/// Serialized with a `code` tag so the frontend can branch on the failure kind
+/// and show `message` directly to the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(tag = "code")]
+pub enum CommandError {
+    #[serde(rename = "NOT_FOUND")]
+    NotFound { resource: String, message: String },
+    #[serde(rename = "STALE_DATA")]
+    StaleData { message: String },
+    #[serde(rename = "EXTERNAL_API")]
+    ExternalApi { provider: String, status: Option<u16>, message: String },
+    #[serde(rename = "TOKEN_EXPIRED")]
+    TokenExpired { provider: String, message: String },
+    #[serde(rename = "DATABASE_BUSY")]
+    DatabaseBusy { message: String },
+    #[serde(rename = "DATABASE")]
+    Database { message: String },
+    #[serde(rename = "VALIDATION")]
+    Validation { field: String, message: String },
+    #[serde(rename = "READ_ONLY")]
+    ReadOnly { message: String },
+    #[serde(rename = "INTERNAL")]
+    Internal { message: String },
+}
+
+impl CommandError {
+    pub fn not_found(resource: impl Into<String>, message: impl Into<String>) -> Self {
+        CommandError::NotFound { resource: resource.into(), message: message.into() }
+    }
+
+    pub fn stale_data(message: impl Into<String>) -> Self {
+        CommandError::StaleData { message: message.into() }
+    }
+
+    pub fn external_api(provider: impl Into<String>, status: Option<u16>, message: impl Into<String>) -> Self {
+        CommandError::ExternalApi { provider: provider.into(), status, message: message.into() }
+    }
+
+    /// OAuth credentials were rejected and could not be refreshed; the user must re-authenticate
+    pub fn token_expired(provider: impl Into<String>, message: impl Into<String>) -> Self {
+        CommandError::TokenExpired { provider: provider.into(), message: message.into() }
+    }
+
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        CommandError::Validation { field: field.into(), message: message.into() }
+    }
+
+    /// The database was opened read-only, so the command's writes were refused up front
+    pub fn read_only(message: impl Into<String>) -> Self {
+        CommandError::ReadOnly { message: message.into() }
+    }
+
+    pub fn database(message: impl Into<String>) -> Self {
+        CommandError::Database { message: message.into() }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        CommandError::Internal { message: message.into() }
+    }
+
+    /// Stable machine-readable code (matches the serialized `code` tag)
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::NotFound { .. } => "NOT_FOUND",
+            CommandError::StaleData { .. } => "STALE_DATA",
+            CommandError::ExternalApi { .. } => "EXTERNAL_API",
+            CommandError::TokenExpired { .. } => "TOKEN_EXPIRED",
+            CommandError::DatabaseBusy { .. } => "DATABASE_BUSY",
+            CommandError::Database { .. } => "DATABASE",
+            CommandError::Validation { .. } => "VALIDATION",
+            CommandError::ReadOnly { .. } => "READ_ONLY",
+            CommandError::Internal { .. } => "INTERNAL",
+        }
+    }
+
+    /// User-presentable message
+    pub fn message(&self) -> &str {
+        match self {
+            CommandError::NotFound { message, .. }
+            | CommandError::StaleData { message }
+            | CommandError::ExternalApi { message, .. }
+            | CommandError::TokenExpired { message, .. }
+            | CommandError::DatabaseBusy { message }
+            | CommandError::Database { message }
+            | CommandError::Validation { message, .. }
+            | CommandError::ReadOnly { message }
+            | CommandError::Internal { message } => message,
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.code(), self.message())
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<sqlx::Error> for CommandError {
+    fn from(e: sqlx::Error) -> Self {
+        match &e {
+            sqlx::Error::RowNotFound => CommandError::not_found("row", "No matching data found"),
+            sqlx::Error::PoolTimedOut => CommandError::DatabaseBusy {
+                message: "Database is busy, please retry in a moment".to_string(),
+            },
+            sqlx::Error::Database(db_err) => {
+                // SQLITE_BUSY (5) and SQLITE_LOCKED (6)
+                let busy = matches!(db_err.code().as_deref(), Some("5") | Some("6"))
+                    || db_err.message().contains("database is locked");
+                if busy {
+                    CommandError::DatabaseBusy {
+                        message: "Database is locked by another operation, please retry in a moment".to_string(),
+                    }
+                } else {
+                    CommandError::database(format!("Database query failed: {}", e))
+                }
+            }
+            _ => CommandError::database(format!("Database query failed: {}", e)),
+        }
+    }
+}
+
+impl From<reqwest::Error> for CommandError {
+    fn from(e: reqwest::Error) -> Self {
+        let provider = match e.url().and_then(|u| u.host_str()) {
+            Some(host) if host.contains("sec.gov") => "sec_edgar".to_string(),
+            Some(host) if host.contains("schwab") => "schwab".to_string(),
+            Some(host) => host.to_string(),
+            None => "unknown".to_string(),
+        };
+        let status = e.status().map(|s| s.as_u16());
+        let message = match status {
+            Some(429) => format!("{} rate-limited the request, please wait before retrying", provider),
+            Some(code) => format!("{} returned HTTP {}", provider, code),
+            None => format!("Request to {} failed: {}", provider, e),
+        };
+        CommandError::ExternalApi { provider, status, message }
+    }
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<CommandError>() {
+            Ok(command_error) => return command_error,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<sqlx::Error>() {
+            Ok(sqlx_error) => return sqlx_error.into(),
+            Err(e) => e,
+        };
+        match e.downcast::<reqwest::Error>() {
+            Ok(http_error) => http_error.into(),
+            Err(e) => CommandError::internal(e.to_string()),
+        }
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::internal(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_not_found_maps_to_not_found() {
+        let err: CommandError = sqlx::Error::RowNotFound.into();
+        assert_eq!(err.code(), "NOT_FOUND");
+    }
+
+    #[test]
+    fn test_pool_timeout_maps_to_database_busy() {
+        let err: CommandError = sqlx::Error::PoolTimedOut.into();
+        assert_eq!(err.code(), "DATABASE_BUSY");
+    }
+
+    #[test]
+    fn test_other_sqlx_errors_map_to_database() {
+        let err: CommandError = sqlx::Error::ColumnNotFound("pe_ratio".to_string()).into();
+        assert_eq!(err.code(), "DATABASE");
+        assert!(err.message().contains("pe_ratio"));
+    }
+
+    #[tokio::test]
+    async fn test_locked_database_maps_to_database_busy() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}?mode=rwc", dir.path().join("locked.db").display());
+        let writer = sqlx::SqlitePool::connect(&url).await.unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER)").execute(&writer).await.unwrap();
+
+        let mut holder = writer.acquire().await.unwrap();
+        sqlx::query("BEGIN EXCLUSIVE").execute(&mut *holder).await.unwrap();
+
+        let options = url.parse::<sqlx::sqlite::SqliteConnectOptions>().unwrap()
+            .busy_timeout(std::time::Duration::from_millis(10));
+        let other = sqlx::SqlitePool::connect_with(options).await.unwrap();
+        let err = sqlx::query("INSERT INTO t (id) VALUES (1)").execute(&other).await.unwrap_err();
+
+        let err: CommandError = err.into();
+        assert_eq!(err.code(), "DATABASE_BUSY");
+    }
+
+    #[test]
+    fn test_anyhow_preserves_underlying_error_kind() {
+        let err: CommandError = anyhow::Error::from(sqlx::Error::RowNotFound).into();
+        assert_eq!(err.code(), "NOT_FOUND");
+
+        let err: CommandError = anyhow::Error::from(CommandError::stale_data("Prices are 12 days old")).into();
+        assert_eq!(err, CommandError::stale_data("Prices are 12 days old"));
+
+        let err: CommandError = anyhow::anyhow!("something unexpected").into();
+        assert_eq!(err.code(), "INTERNAL");
+        assert_eq!(err.message(), "something unexpected");
+    }
+
+    #[test]
+    fn test_string_maps_to_internal() {
+        let err: CommandError = "Database connection failed".to_string().into();
+        assert_eq!(err.code(), "INTERNAL");
+    }
+
+    #[test]
+    fn test_serialized_shape_has_code_and_message() {
+        let err = CommandError::validation("start_date", "Invalid start date format");
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["code"], "VALIDATION");
+        assert_eq!(json["field"], "start_date");
+        assert_eq!(json["message"], "Invalid start date format");
+
+        let err = CommandError::external_api("sec_edgar", Some(429), "rate limited");
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["code"], "EXTERNAL_API");
+        assert_eq!(json["status"], 429);
+    }
+}
@@ -7,6 +7,10 @@ pub mod database_sqlx;
 pub mod tools;
 pub mod analysis;
 pub mod types;
+pub mod error;
+pub mod logging;
+pub mod paths;
+pub mod safe_f64;
 
 #[cfg(test)]
 pub mod tests;
@@ -18,7 +22,7 @@ use tauri::WindowEvent;
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        // .plugin(tauri_plugin_log::Builder::default().build())  // Temporarily disabled due to initialization error
+        .plugin(tauri_plugin_notification::init())
         .on_window_event(|_window, event| match event {
             WindowEvent::CloseRequested { .. } => {
                 println!("🔄 Window close requested - cleaning up orphaned processes...");
@@ -85,31 +89,215 @@ pub fn run() {
             stocks::get_stocks_with_data_status,
             stocks::get_stocks_paginated,
             stocks::get_sp500_symbols,
+            stocks::get_company_profile,
+            stocks::set_company_profile_override,
+            stocks::archive_stock,
+            stocks::restore_stock,
+            stocks::find_duplicate_stocks,
+            stocks::merge_stocks,
             
             // Data collection commands
             data::get_database_stats,
+            data::get_cache_stats,
+            data::get_refresh_duration_estimates,
+            data::get_refresh_history,
+            data::get_refresh_run_details,
+            data::backfill_first_trading_dates,
+            data::recompute_all_ratios,
+            data::get_ratio_recompute_progress,
+            data::cancel_ratio_recompute,
+            data::update_fx_rates,
+            data::get_coverage_heatmap,
+            data::refresh_fundamentals_for,
+            data::check_screening_readiness,
+            data::get_stale_stocks,
+            data::find_conflicting_prices,
+            data::get_data_freshness_status,
+            data::run_database_maintenance,
             
             // Analysis commands
             commands::analysis::get_price_history,
             commands::analysis::get_stock_date_range,
             commands::analysis::get_valuation_ratios,
             commands::analysis::get_ps_evs_history,
+            commands::analysis::get_daily_valuation_ratios,
+            commands::analysis::backfill_daily_ratios,
             commands::analysis::get_valuation_extremes,
+            commands::analysis::get_undervalued_stocks_by_ps,
+            commands::analysis::get_ps_screening_with_revenue_growth,
+            commands::analysis::get_risk_metrics,
+            commands::analysis::get_benchmark_history,
+            commands::analysis::get_relative_performance,
+            commands::analysis::get_52_week_stats,
+            commands::analysis::get_stocks_near_52_week_low,
+            commands::analysis::get_stock_summary,
+            commands::analysis::get_sector_overview,
+            commands::analysis::get_industry_growth_ranking,
+            commands::analysis::get_growth_consistency,
+            commands::analysis::get_sector_relative_valuation,
+            commands::analysis::get_metric_distribution,
+            commands::analysis::get_leverage_metrics,
+            commands::analysis::get_liquidity_ratios,
+            commands::analysis::get_low_leverage_stocks,
+            commands::analysis::get_top_fcf_yield,
+            commands::analysis::get_quality_metrics,
+            commands::analysis::get_quality_compounders,
+            commands::analysis::get_roic,
+            commands::analysis::get_valuation_vs_history,
+            commands::analysis::get_gross_profitability,
+            commands::analysis::get_stocks_by_gross_profitability,
+            commands::analysis::get_accruals,
+            commands::analysis::get_watchlist_snapshot,
+            commands::analysis::get_dividend_history,
+            commands::analysis::get_dividend_growers,
+            commands::analysis::get_shareholder_yield,
+            commands::analysis::get_max_drawdown,
+            commands::analysis::get_drawdown_analysis,
+            commands::analysis::get_portfolio_drawdown_analysis,
+            commands::analysis::compute_total_return,
+            commands::analysis::get_momentum_rankings,
+            commands::analysis::get_ratio_provenance,
             
             // Initialization commands
             initialization::get_initialization_status,
             initialization::check_database_schema,
             initialization::initialize_sp500_stocks,
+            initialization::get_initialization_report,
+            initialization::reset_database,
+
+            // SEC filing diagnostics
+            filings::diff_filings_vs_sec,
+            filings::get_recently_updated_fundamentals,
+            filings::check_balance_sheet_identity,
+            filings::get_share_count_discontinuities,
+            filings::record_stock_split,
+            filings::get_filing_reactions,
+            filings::backfill_filing_reactions,
+            filings::fetch_raw_company_facts,
+
+            // Financial statement viewer
+            financials::get_financial_statements,
+
+            // Activity log
+            logs::get_recent_logs,
+
+            // Settings
+            settings::get_settings,
+            settings::set_settings,
+            settings::get_refresh_schedule,
+            settings::set_refresh_schedule,
+            settings::pause_scheduled_refresh,
+            settings::start_http_api,
+            settings::stop_http_api,
+            settings::get_http_api_status,
+
+            // Stock tags
+            tags::add_stock_tag,
+            tags::remove_stock_tag,
+            tags::get_tags_for_stock,
+            tags::get_stocks_by_tag,
+
+            // Live quotes
+            quotes::get_live_quotes,
+            quotes::get_provider_fundamentals,
+            quotes::import_benchmark_index,
+            quotes::collect_intraday_prices,
+            quotes::get_intraday_prices,
+            quotes::downsample_intraday_prices,
+            quotes::start_quote_polling,
+            quotes::stop_quote_polling,
+            quotes::get_polled_quotes,
+            quotes::fetch_option_chain,
+            quotes::get_covered_call_candidates,
 
             // Piotroski F-Score screening commands
             piotroski_screening::get_piotroski_screening_results,
             piotroski_screening::get_piotroski_statistics,
+            piotroski_screening::get_piotroski_history,
 
             // O'Shaughnessy Value Composite screening commands
             oshaughnessy_screening::get_oshaughnessy_screening_results,
-            oshaughnessy_screening::get_oshaughnessy_statistics
+            oshaughnessy_screening::get_oshaughnessy_statistics,
+            oshaughnessy_screening::get_value_composite_ranking,
+
+            // Graham value screening commands
+            graham_screening::get_graham_criteria_defaults,
+            graham_screening::run_graham_screening,
+
+            // Custom screen builder
+            custom_screening::run_custom_screen,
+            custom_screening::list_custom_screens,
+            custom_screening::save_custom_screen,
+            custom_screening::delete_custom_screen,
+
+            // Saved screener presets
+            screening_presets::save_screening_preset,
+            screening_presets::get_screening_presets,
+            screening_presets::delete_screening_preset,
+
+            // Per-stock screen badges
+            screen_membership::get_screen_membership,
+
+            // Screen run history
+            screening_snapshots::get_screen_snapshots,
+            screening_snapshots::diff_screen_snapshots,
+            screening_snapshots::prune_screen_snapshots,
+            screening_snapshots::prune_screening_history
         ])
-        .setup(|_app| {
+        .setup(|app| {
+            use tauri::Manager;
+
+            // STOCKS_DATA_DIR wins over Tauri's app data dir
+            let data_paths = match app.path().app_data_dir() {
+                Ok(dir) if std::env::var_os(paths::DATA_DIR_ENV).is_none() => paths::DataPaths::new(dir),
+                _ => paths::DataPaths::new(paths::default_data_dir()),
+            };
+            let data_paths = paths::init_data_paths(data_paths);
+            if let Err(e) = data_paths.create_dirs() {
+                eprintln!("Failed to create data directory {}: {}", data_paths.base_dir().display(), e);
+            }
+
+            // Drop stale logs before the log file is opened. Without API keys Config doesn't load,
+            // but its log settings come from the same environment variables.
+            dotenvy::dotenv().ok();
+            let (log_retention_days, log_max_files) = match models::Config::from_env() {
+                Ok(config) => (config.debug_log_retention_days, config.debug_log_max_files),
+                Err(_) => (logging::debug_log_retention_days(), logging::debug_log_max_files()),
+            };
+            if let Err(e) = logging::prune_log_files(
+                &data_paths.log_dir(),
+                log_retention_days,
+                log_max_files,
+                std::time::SystemTime::now(),
+            ) {
+                eprintln!("Failed to prune log directory {}: {}", data_paths.log_dir().display(), e);
+            }
+
+            // Replaces tauri-plugin-log, which failed to initialize
+            logging::init_logging(&data_paths.log_dir(), log_max_files);
+
+            // Refuse incompatible schemas and apply safe migrations before anything opens the pool.
+            // A read-only database is opened as-is.
+            if !database::helpers::read_only_requested() {
+                let db_path = database::helpers::get_database_path()?;
+                let pool = tauri::async_runtime::block_on(database::protected_init::initialize_database_safely(&db_path))?;
+                tauri::async_runtime::block_on(pool.close());
+            }
+
+            // Refreshes push progress to the frontend as "refresh-progress" events
+            tools::data_refresh_orchestrator::set_default_progress_sink(std::sync::Arc::new(app.handle().clone()));
+
+            // Refresh outcomes and alerts as OS notifications, or in-app toasts where unavailable
+            tools::notifications::set_default_notifier(std::sync::Arc::new(app.handle().clone()));
+
+            // Watchlist quote polls reach the frontend as "quote-update" events
+            tools::quote_polling::set_default_quote_sink(std::sync::Arc::new(app.handle().clone()));
+
+            // Weekday market refreshes and the weekly financials refresh, per the saved schedule
+            tauri::async_runtime::spawn(tools::refresh_scheduler::run_refresh_scheduler());
+
+            // JSON API on localhost for scripts, when enabled in settings
+            tauri::async_runtime::spawn(tools::http_api::start_http_server_if_enabled());
             Ok(())
         })
         .run(tauri::generate_context!())
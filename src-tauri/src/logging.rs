@@ -0,0 +1,390 @@
+// Structured logging: tracing events go to a rotating file under the app data dir
+// and to an in-memory ring buffer the frontend reads through get_recent_logs.
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+use ts_rs::TS;
+
+/// Entries kept in memory for the activity console
+const RING_BUFFER_CAPACITY: usize = 2000;
+
+/// Log file size at which it is rotated
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+const LOG_FILE_NAME: &str = "rust-stocks.log";
+
+/// Environment variable setting `Config.debug_log_retention_days`
+pub const LOG_RETENTION_DAYS_ENV: &str = "DEBUG_LOG_RETENTION_DAYS";
+
+/// Environment variable setting `Config.debug_log_max_files`
+pub const LOG_MAX_FILES_ENV: &str = "DEBUG_LOG_MAX_FILES";
+
+pub const DEFAULT_LOG_RETENTION_DAYS: u64 = 14;
+
+pub const DEFAULT_LOG_MAX_FILES: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Event fields plus those of the enclosing spans (symbol, cik, phase, records, ...)
+    pub fields: HashMap<String, String>,
+}
+
+/// Bounded buffer of the most recent log entries
+#[derive(Debug, Clone)]
+pub struct LogRingBuffer {
+    entries: Arc<Mutex<VecDeque<LogEntry>>>,
+    capacity: usize,
+}
+
+impl LogRingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    fn push(&self, entry: LogEntry) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Newest-first entries at `min_level` or more severe
+    pub fn recent(&self, min_level: Level, limit: usize) -> Vec<LogEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.level.parse::<Level>().map(|l| l <= min_level).unwrap_or(true))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Buffer shared by the installed subscriber and the get_recent_logs command
+pub fn global_log_buffer() -> &'static LogRingBuffer {
+    static BUFFER: OnceLock<LogRingBuffer> = OnceLock::new();
+    BUFFER.get_or_init(|| LogRingBuffer::new(RING_BUFFER_CAPACITY))
+}
+
+#[derive(Default)]
+struct FieldCollector {
+    message: Option<String>,
+    fields: HashMap<String, String>,
+}
+
+impl Visit for FieldCollector {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+        } else {
+            self.fields.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.fields.insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+/// Span fields stored in the span's extensions so events can inherit them
+struct SpanFields(HashMap<String, String>);
+
+/// Layer that copies each event into a LogRingBuffer
+pub struct RingBufferLayer {
+    buffer: LogRingBuffer,
+}
+
+impl RingBufferLayer {
+    pub fn new(buffer: LogRingBuffer) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<S> Layer<S> for RingBufferLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &tracing::span::Attributes<'_>, id: &tracing::span::Id, ctx: Context<'_, S>) {
+        let mut collector = FieldCollector::default();
+        attrs.record(&mut collector);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(collector.fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = HashMap::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            // Outermost span first so inner spans and the event itself take precedence
+            for span in scope.from_root() {
+                if let Some(span_fields) = span.extensions().get::<SpanFields>() {
+                    fields.extend(span_fields.0.clone());
+                }
+            }
+        }
+
+        let mut collector = FieldCollector::default();
+        event.record(&mut collector);
+        fields.extend(collector.fields);
+
+        let metadata = event.metadata();
+        self.buffer.push(LogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: collector.message.unwrap_or_default(),
+            fields,
+        });
+    }
+}
+
+/// Size-rotated log file: rust-stocks.log, then .1 (newest) to .N (oldest), N being the
+/// number of rotated files kept
+#[derive(Clone)]
+pub struct RotatingFileWriter {
+    inner: Arc<Mutex<RotatingFile>>,
+}
+
+struct RotatingFile {
+    dir: PathBuf,
+    file: File,
+    written: u64,
+    rotated_files_kept: usize,
+}
+
+impl RotatingFileWriter {
+    pub fn new(dir: &Path, rotated_files_kept: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file = OpenOptions::new().create(true).append(true).open(dir.join(LOG_FILE_NAME))?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(RotatingFile { dir: dir.to_path_buf(), file, written, rotated_files_kept })),
+        })
+    }
+}
+
+impl RotatingFile {
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for n in (1..self.rotated_files_kept).rev() {
+            let from = self.dir.join(format!("{}.{}", LOG_FILE_NAME, n));
+            if from.exists() {
+                fs::rename(&from, self.dir.join(format!("{}.{}", LOG_FILE_NAME, n + 1)))?;
+            }
+        }
+        if self.rotated_files_kept > 0 {
+            fs::rename(self.dir.join(LOG_FILE_NAME), self.dir.join(format!("{}.1", LOG_FILE_NAME)))?;
+        } else {
+            fs::remove_file(self.dir.join(LOG_FILE_NAME))?;
+        }
+
+        self.file = OpenOptions::new().create(true).append(true).open(self.dir.join(LOG_FILE_NAME))?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.written + buf.len() as u64 > MAX_LOG_FILE_BYTES {
+            inner.rotate()?;
+        }
+        let written = inner.file.write(buf)?;
+        inner.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).file.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFileWriter {
+    type Writer = RotatingFileWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Days log files are kept, from `DEBUG_LOG_RETENTION_DAYS`
+pub fn debug_log_retention_days() -> u64 {
+    std::env::var(LOG_RETENTION_DAYS_ENV)
+        .ok()
+        .and_then(|days| days.trim().parse().ok())
+        .unwrap_or(DEFAULT_LOG_RETENTION_DAYS)
+}
+
+/// Old log files kept in the log directory, from `DEBUG_LOG_MAX_FILES`
+pub fn debug_log_max_files() -> usize {
+    std::env::var(LOG_MAX_FILES_ENV)
+        .ok()
+        .and_then(|count| count.trim().parse().ok())
+        .unwrap_or(DEFAULT_LOG_MAX_FILES)
+}
+
+/// Delete files in `log_dir` last modified more than `retention_days` before `now`, then all but
+/// the newest `max_files` of the rest. The active log file is never deleted. Returns the files
+/// removed; a missing directory has none.
+pub fn prune_log_files(log_dir: &Path, retention_days: u64, max_files: usize, now: SystemTime) -> io::Result<usize> {
+    let entries = match fs::read_dir(log_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() && entry.file_name() != LOG_FILE_NAME {
+            files.push((metadata.modified()?, entry.path()));
+        }
+    }
+    // Newest first
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let cutoff = now.checked_sub(Duration::from_secs(retention_days * 24 * 60 * 60)).unwrap_or(SystemTime::UNIX_EPOCH);
+    let mut removed = 0;
+    for (i, (modified, path)) in files.iter().enumerate() {
+        if *modified < cutoff || i >= max_files {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Install the global subscriber: stdout, the rotating file under `log_dir` keeping up to
+/// `max_files` rotated files, and the ring buffer. Logging to the file is skipped (with a stderr
+/// note) if the directory can't be opened.
+pub fn init_logging(log_dir: &Path, max_files: usize) {
+    let file_layer = match RotatingFileWriter::new(log_dir, max_files) {
+        Ok(writer) => Some(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(writer)),
+        Err(e) => {
+            eprintln!("Failed to open log directory {}: {}", log_dir.display(), e);
+            None
+        }
+    };
+
+    let result = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_layer)
+        .with(RingBufferLayer::new(global_log_buffer().clone()))
+        .try_init();
+
+    if let Err(e) = result {
+        eprintln!("Logging already initialized: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::{info, info_span, warn};
+
+    #[test]
+    fn test_ring_buffer_captures_events_with_span_fields() {
+        let buffer = LogRingBuffer::new(3);
+        let subscriber = tracing_subscriber::registry().with(RingBufferLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = info_span!("refresh_step", phase = "financial_statements");
+            let _guard = span.enter();
+            info!(symbol = "AAPL", records = 4, "Stored filings");
+            warn!(cik = "0000789019", "Company Facts API failed");
+            info!("one");
+            info!("two");
+        });
+
+        // Capacity 3: the first event was evicted
+        let all = buffer.recent(Level::TRACE, 10);
+        assert_eq!(all.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), vec!["two", "one", "Company Facts API failed"]);
+
+        let warnings = buffer.recent(Level::WARN, 10);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].fields.get("cik").map(String::as_str), Some("0000789019"));
+        assert_eq!(warnings[0].fields.get("phase").map(String::as_str), Some("financial_statements"));
+    }
+
+    #[test]
+    fn test_file_writer_rotates_past_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingFileWriter::new(dir.path(), 2).unwrap();
+        for line in ["first\n", "second\n", "third\n"] {
+            writer.inner.lock().unwrap().written = MAX_LOG_FILE_BYTES - 2;
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+
+        // Two rotations kept, the oldest dropped
+        let rotated = |n: usize| fs::read_to_string(dir.path().join(format!("{}.{}", LOG_FILE_NAME, n))).ok();
+        assert_eq!(rotated(1).as_deref(), Some("second\n"));
+        assert_eq!(rotated(2).as_deref(), Some("first\n"));
+        assert_eq!(rotated(3), None);
+        let current = fs::read_to_string(dir.path().join(LOG_FILE_NAME)).unwrap();
+        assert_eq!(current, "third\n");
+    }
+
+    #[test]
+    fn test_prune_removes_logs_past_retention_and_beyond_max_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let days_ago = |days: u64| now - Duration::from_secs(days * 24 * 60 * 60);
+        let write_log = |name: &str, modified: SystemTime| {
+            let path = dir.path().join(name);
+            fs::write(&path, "collected\n").unwrap();
+            File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        };
+
+        // The active file is kept however old it is
+        write_log(LOG_FILE_NAME, days_ago(40));
+        write_log("debug_AAPL_20240101.log", days_ago(30));
+        write_log("debug_MSFT_20240110.log", days_ago(15));
+        write_log("rust-stocks.log.1", days_ago(3));
+        write_log("debug_NVDA_20240301.log", days_ago(1));
+        let remaining = || {
+            let mut names: Vec<String> =
+                fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().to_string()).collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(prune_log_files(dir.path(), 14, 10, now).unwrap(), 2);
+        assert_eq!(remaining(), vec!["debug_NVDA_20240301.log", "rust-stocks.log", "rust-stocks.log.1"]);
+
+        // Over the count the oldest go first
+        assert_eq!(prune_log_files(dir.path(), 14, 1, now).unwrap(), 1);
+        assert_eq!(remaining(), vec!["debug_NVDA_20240301.log", "rust-stocks.log"]);
+
+        assert_eq!(prune_log_files(&dir.path().join("missing"), 14, 10, now).unwrap(), 0);
+    }
+}
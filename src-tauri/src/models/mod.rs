@@ -1,5 +1,8 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::error::CommandError;
 
 /// Core stock information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +15,11 @@ pub struct Stock {
     pub last_updated: Option<DateTime<Utc>>,
     pub created_at: Option<DateTime<Utc>>,
     pub is_sp500: bool,
+    /// Earliest date the stock traded; backfilled from stored prices when unknown at import
+    pub first_trading_date: Option<NaiveDate>,
+    /// The former symbol a lookup matched through symbol_aliases; `symbol` is the current one
+    #[serde(default)]
+    pub matched_alias: Option<String>,
 }
 
 /// Daily price and fundamental data
@@ -30,6 +38,38 @@ pub struct DailyPrice {
     pub dividend_yield: Option<f64>,
 }
 
+impl DailyPrice {
+    /// Reject a bar that can't have traded: a non-positive price, a low above the high, or an
+    /// open or close outside the day's range
+    pub fn validate(&self) -> Result<(), CommandError> {
+        validate_ohlc(self.open_price, self.high_price, self.low_price, self.close_price)
+            .map_err(|e| match e {
+                CommandError::Validation { field, message } => {
+                    CommandError::validation(field, format!("{} (stock {} on {})", message, self.stock_id, self.date))
+                }
+                other => other,
+            })
+    }
+}
+
+/// Check `low <= open, close <= high` with every price positive
+pub fn validate_ohlc(open: f64, high: f64, low: f64, close: f64) -> Result<(), CommandError> {
+    for (field, price) in [("open_price", open), ("high_price", high), ("low_price", low), ("close_price", close)] {
+        if !(price.is_finite() && price > 0.0) {
+            return Err(CommandError::validation(field, format!("{} must be positive, got {}", field, price)));
+        }
+    }
+    if low > high {
+        return Err(CommandError::validation("low_price", format!("Low {} is above high {}", low, high)));
+    }
+    for (field, price) in [("open_price", open), ("close_price", close)] {
+        if price < low || price > high {
+            return Err(CommandError::validation(field, format!("{} {} is outside the day's range [{}, {}]", field, price, low, high)));
+        }
+    }
+    Ok(())
+}
+
 /// Schwab API quote response structure
 #[derive(Debug, Deserialize)]
 pub struct SchwabQuote {
@@ -53,6 +93,71 @@ pub struct SchwabQuote {
     pub dividend_yield: Option<f64>,
 }
 
+/// Interval each price history bar covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceFrequency {
+    #[default]
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl PriceFrequency {
+    /// Schwab's frequencyType value, also the tag stored with weekly and monthly bars
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PriceFrequency::Daily => "daily",
+            PriceFrequency::Weekly => "weekly",
+            PriceFrequency::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "daily" => Some(PriceFrequency::Daily),
+            "weekly" => Some(PriceFrequency::Weekly),
+            "monthly" => Some(PriceFrequency::Monthly),
+            _ => None,
+        }
+    }
+}
+
+/// Bar size of intraday_prices rows, stored as interval_type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum IntradayInterval {
+    #[serde(rename = "1min")]
+    OneMinute,
+    #[serde(rename = "5min")]
+    FiveMinutes,
+    #[serde(rename = "15min")]
+    FifteenMinutes,
+    #[serde(rename = "30min")]
+    ThirtyMinutes,
+}
+
+impl IntradayInterval {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IntradayInterval::OneMinute => "1min",
+            IntradayInterval::FiveMinutes => "5min",
+            IntradayInterval::FifteenMinutes => "15min",
+            IntradayInterval::ThirtyMinutes => "30min",
+        }
+    }
+
+    /// Schwab's minute frequency value
+    pub fn minutes(self) -> u32 {
+        match self {
+            IntradayInterval::OneMinute => 1,
+            IntradayInterval::FiveMinutes => 5,
+            IntradayInterval::FifteenMinutes => 15,
+            IntradayInterval::ThirtyMinutes => 30,
+        }
+    }
+}
+
 /// Schwab API price history bar
 #[derive(Debug, Deserialize)]
 pub struct SchwabPriceBar {
@@ -63,6 +168,32 @@ pub struct SchwabPriceBar {
     pub low: f64,
     pub close: f64,
     pub volume: i64,
+    /// Interval the bar covers; weekly and monthly bars are dated at the start of the period
+    #[serde(default)]
+    pub frequency: PriceFrequency,
+}
+
+/// One contract from a Schwab option chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchwabOptionContract {
+    /// OCC contract symbol, e.g. "AAPL  240119C00185000"
+    pub symbol: String,
+    /// 'CALL' or 'PUT'
+    pub put_call: String,
+    pub expiration_date: NaiveDate,
+    pub strike_price: f64,
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+    pub last: Option<f64>,
+    pub total_volume: Option<i64>,
+    pub open_interest: Option<i64>,
+    /// Implied volatility in percent
+    pub volatility: Option<f64>,
+    pub delta: Option<f64>,
+    pub gamma: Option<f64>,
+    pub theta: Option<f64>,
+    pub vega: Option<f64>,
+    pub rho: Option<f64>,
 }
 
 /// System metadata for tracking state
@@ -77,6 +208,11 @@ pub struct Config {
     pub database_path: String,
     pub rate_limit_per_minute: u32,
     pub batch_size: usize,
+    /// Log files older than this are deleted at startup (`DEBUG_LOG_RETENTION_DAYS`, default 14)
+    pub debug_log_retention_days: u64,
+    /// Log files kept besides the active one, counting its rotations (`DEBUG_LOG_MAX_FILES`,
+    /// default 50)
+    pub debug_log_max_files: usize,
 }
 
 impl Config {
@@ -98,6 +234,8 @@ impl Config {
         #[cfg(feature = "debug-logging")]
         println!("DEBUG: Token file exists: {}", std::path::Path::new(&schwab_token_path).exists());
         
+        let data_paths = crate::paths::data_paths();
+
         Ok(Config {
             schwab_api_key: std::env::var("SCHWAB_API_KEY")
                 .map_err(|_| anyhow::anyhow!("SCHWAB_API_KEY environment variable required"))?,
@@ -107,7 +245,10 @@ impl Config {
                 .unwrap_or_else(|_| "https://localhost:8080".to_string()),
             schwab_token_path,
             database_path: std::env::var("DATABASE_PATH")
-                .unwrap_or_else(|_| "stocks.db".to_string()),
+                .map(|path| data_paths.resolve(path))
+                .unwrap_or_else(|_| data_paths.database_path_or_legacy(crate::paths::LEGACY_DATABASE_PATH))
+                .to_string_lossy()
+                .to_string(),
             rate_limit_per_minute: std::env::var("RATE_LIMIT_PER_MINUTE")
                 .unwrap_or_else(|_| "120".to_string())
                 .parse()
@@ -116,6 +257,8 @@ impl Config {
                 .unwrap_or_else(|_| "50".to_string())
                 .parse()
                 .unwrap_or(50),
+            debug_log_retention_days: crate::logging::debug_log_retention_days(),
+            debug_log_max_files: crate::logging::debug_log_max_files(),
         })
     }
 }
@@ -218,8 +361,9 @@ pub struct EnhancedPriceData {
     pub created_at: DateTime<Utc>,
 }
 
-// Fundamental data structure for Schwab API responses
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// Fundamental data structure for provider (Schwab, Alpha Vantage) responses; yields and margins in percent
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct FundamentalData {
     pub symbol: String,
     pub pe_ratio: Option<f64>,
@@ -245,11 +389,14 @@ pub struct FundamentalData {
 }
 
 // Real-time quote data
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct RealTimeQuote {
     pub id: Option<i64>,
     pub stock_id: i64,
     pub symbol: String,
+    /// RFC 3339 time the quote was fetched
+    #[ts(type = "string")]
     pub timestamp: DateTime<Utc>,
     pub bid_price: Option<f64>,
     pub bid_size: Option<i32>,
@@ -265,12 +412,18 @@ pub struct RealTimeQuote {
 }
 
 // Intraday price data
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct IntradayPrice {
     pub id: Option<i64>,
     pub stock_id: i64,
+    /// Start of the bar, RFC 3339 in UTC
+    #[ts(type = "string")]
     pub datetime: DateTime<Utc>,
-    pub interval_type: String, // '1min', '5min', '15min', '30min', '1hour'
+    /// New York trading session the bar belongs to
+    #[ts(type = "string")]
+    pub trading_date: NaiveDate,
+    pub interval_type: IntradayInterval,
     pub open_price: f64,
     pub high_price: f64,
     pub low_price: f64,
@@ -279,11 +432,14 @@ pub struct IntradayPrice {
 }
 
 // Option chain data with Greeks
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct OptionData {
     pub id: Option<i64>,
     pub stock_id: i64,
+    /// OCC contract symbol
     pub symbol: String,
+    #[ts(type = "string")]
     pub expiration_date: NaiveDate,
     pub strike_price: f64,
     pub option_type: String, // 'CALL' or 'PUT'
@@ -298,6 +454,9 @@ pub struct OptionData {
     pub theta: Option<f64>,
     pub vega: Option<f64>,
     pub rho: Option<f64>,
+    /// RFC 3339 time of the chain snapshot the contract came from
+    #[ts(type = "string")]
+    pub snapshot_time: DateTime<Utc>,
 }
 
 // Comprehensive stock data combining all data types
@@ -343,4 +502,50 @@ pub struct ValuationExtremes {
     pub max_ps_ratio: Option<f64>,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(open: f64, high: f64, low: f64, close: f64) -> DailyPrice {
+        DailyPrice {
+            id: None,
+            stock_id: 7,
+            date: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            open_price: open,
+            high_price: high,
+            low_price: low,
+            close_price: close,
+            volume: Some(1_000),
+            pe_ratio: None,
+            market_cap: None,
+            dividend_yield: None,
+        }
+    }
+
+    fn rejected_field(price: DailyPrice) -> String {
+        match price.validate() {
+            Err(CommandError::Validation { field, message }) => {
+                assert!(message.contains("stock 7 on 2024-03-01"), "{}", message);
+                field
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
 
+    #[test]
+    fn test_validate_rejects_impossible_bars() {
+        assert!(bar(10.0, 11.0, 9.0, 10.5).validate().is_ok());
+        // Open and close may sit on the range boundaries; a flat bar is fine
+        assert!(bar(9.0, 11.0, 9.0, 11.0).validate().is_ok());
+        assert!(bar(10.0, 10.0, 10.0, 10.0).validate().is_ok());
+
+        assert_eq!(rejected_field(bar(10.0, 9.0, 11.0, 10.0)), "low_price");
+        assert_eq!(rejected_field(bar(12.0, 11.0, 9.0, 10.0)), "open_price");
+        assert_eq!(rejected_field(bar(8.0, 11.0, 9.0, 10.0)), "open_price");
+        assert_eq!(rejected_field(bar(10.0, 11.0, 9.0, 11.5)), "close_price");
+        assert_eq!(rejected_field(bar(10.0, 11.0, 9.0, 8.5)), "close_price");
+        assert_eq!(rejected_field(bar(0.0, 11.0, 9.0, 10.0)), "open_price");
+        assert_eq!(rejected_field(bar(10.0, 11.0, -1.0, 10.0)), "low_price");
+        assert_eq!(rejected_field(bar(10.0, 11.0, 9.0, f64::NAN)), "close_price");
+    }
+}
@@ -0,0 +1,158 @@
+// Filesystem locations: the database and logs live under one data directory, so the app
+// behaves the same whatever directory it was launched from and when packaged.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable overriding the data directory
+pub const DATA_DIR_ENV: &str = "STOCKS_DATA_DIR";
+
+/// Directory under the OS app-data dir; the bundle identifier, as Tauri uses for app_data_dir
+const APP_DIR_NAME: &str = "com.tauri.dev";
+
+const DATABASE_FILE_NAME: &str = "stocks.db";
+
+/// Where the database lived, relative to the working directory, before it moved under the data dir
+pub const LEGACY_DATABASE_PATH: &str = "src-tauri/db/stocks.db";
+
+/// Where the app keeps its files, all under `base_dir`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataPaths {
+    base_dir: PathBuf,
+}
+
+impl DataPaths {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self { base_dir: base_dir.into() }
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// `path` itself when absolute, otherwise `path` under the base dir rather than the working directory
+    pub fn resolve(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.base_dir.join(path)
+        }
+    }
+
+    pub fn database_path(&self) -> PathBuf {
+        self.base_dir.join("db").join(DATABASE_FILE_NAME)
+    }
+
+    /// `database_path`, unless it doesn't exist yet and a database is still at `legacy_path`:
+    /// that one is kept in use rather than starting over with an empty database
+    pub fn database_path_or_legacy(&self, legacy_path: impl AsRef<Path>) -> PathBuf {
+        let database_path = self.database_path();
+        let legacy_path = legacy_path.as_ref();
+        if !database_path.exists() && legacy_path.is_file() {
+            return std::env::current_dir().map(|cwd| cwd.join(legacy_path)).unwrap_or_else(|_| legacy_path.to_path_buf());
+        }
+        database_path
+    }
+
+    /// Rotating log files written during data collection
+    pub fn log_dir(&self) -> PathBuf {
+        self.base_dir.join("logs")
+    }
+
+    /// Create the base, database and log directories if they don't exist
+    pub fn create_dirs(&self) -> io::Result<()> {
+        fs::create_dir_all(self.database_path().parent().unwrap_or(&self.base_dir))?;
+        fs::create_dir_all(self.log_dir())
+    }
+}
+
+/// `STOCKS_DATA_DIR` when set, otherwise the OS app-data dir, otherwise the temp dir.
+/// A relative `STOCKS_DATA_DIR` is taken against the working directory once, here.
+pub fn default_data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        let dir = PathBuf::from(dir);
+        return std::env::current_dir().map(|cwd| cwd.join(&dir)).unwrap_or(dir);
+    }
+    os_app_data_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .unwrap_or_else(|| std::env::temp_dir().join("rust-stocks"))
+}
+
+/// Per-user application data directory of the platform
+fn os_app_data_dir() -> Option<PathBuf> {
+    let env_dir = |name: &str| std::env::var_os(name).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local").join("share")))
+    }
+}
+
+static DATA_PATHS: OnceLock<DataPaths> = OnceLock::new();
+
+/// Fix the app's data directory for the rest of the run. The first paths set (or defaulted to
+/// by an earlier `data_paths` call) win; returns the paths in effect.
+pub fn init_data_paths(paths: DataPaths) -> &'static DataPaths {
+    DATA_PATHS.get_or_init(|| paths)
+}
+
+/// The app's data directory; `default_data_dir` unless `init_data_paths` ran first
+pub fn data_paths() -> &'static DataPaths {
+    DATA_PATHS.get_or_init(|| DataPaths::new(default_data_dir()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::protected_init::initialize_database_safely;
+    use crate::logging::RotatingFileWriter;
+    use std::io::Write;
+
+    #[test]
+    fn test_relative_paths_resolve_under_base_dir() {
+        let paths = DataPaths::new("/srv/stocks");
+        assert_eq!(paths.resolve("db/other.db"), PathBuf::from("/srv/stocks/db/other.db"));
+        assert_eq!(paths.resolve("/tmp/elsewhere.db"), PathBuf::from("/tmp/elsewhere.db"));
+    }
+
+    #[tokio::test]
+    async fn test_database_and_logs_are_created_under_custom_base_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = DataPaths::new(dir.path().join("custom"));
+        paths.create_dirs().unwrap();
+
+        let db_path = paths.database_path();
+        let pool = initialize_database_safely(&db_path.to_string_lossy()).await.unwrap();
+        pool.close().await;
+        assert!(db_path.starts_with(dir.path().join("custom")) && db_path.exists());
+
+        let mut writer = RotatingFileWriter::new(&paths.log_dir(), 1).unwrap();
+        writer.write_all(b"collected AAPL\n").unwrap();
+        writer.flush().unwrap();
+        let logs: Vec<PathBuf> = fs::read_dir(paths.log_dir()).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].starts_with(dir.path().join("custom").join("logs")));
+    }
+
+    #[test]
+    fn test_legacy_database_is_used_until_the_data_dir_has_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = DataPaths::new(dir.path().join("data"));
+        let legacy = dir.path().join("src-tauri").join("db").join("stocks.db");
+
+        // Nothing anywhere: the data dir's database
+        assert_eq!(paths.database_path_or_legacy(&legacy), paths.database_path());
+
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, b"").unwrap();
+        assert_eq!(paths.database_path_or_legacy(&legacy), legacy);
+
+        paths.create_dirs().unwrap();
+        fs::write(paths.database_path(), b"").unwrap();
+        assert_eq!(paths.database_path_or_legacy(&legacy), paths.database_path());
+    }
+}
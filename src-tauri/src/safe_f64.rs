@@ -0,0 +1,400 @@
+//! Floats bound for the frontend. serde_json writes NaN and ±infinity as null, the same as a
+//! missing value, so a ratio that blew up would sort and display as if the data were absent.
+//! Computations go through `SafeF64`, which keeps non-finite results out and says why, and
+//! response fields serialize through it with `serialize_finite` and `serialize_finite_f64`.
+
+use std::fmt;
+
+use serde::ser::{self, Serialize, Serializer};
+
+/// Why a computed value was dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFiniteReason {
+    /// An input was already NaN or infinite
+    NonFiniteInput,
+    /// Divided by zero
+    ZeroDenominator,
+    /// The denominator is so close to zero that the quotient overflows
+    NearZeroDenominator,
+    /// The result is too large for an f64
+    Overflow,
+    /// The result is undefined, as for infinity minus infinity
+    NotANumber,
+}
+
+/// A finite float, or the reason there isn't one. Serializes like `Option<f64>`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SafeF64(Result<f64, NonFiniteReason>);
+
+impl SafeF64 {
+    pub fn new(value: f64) -> Self {
+        if value.is_nan() {
+            Self(Err(NonFiniteReason::NotANumber))
+        } else if value.is_infinite() {
+            Self(Err(NonFiniteReason::Overflow))
+        } else {
+            Self(Ok(value))
+        }
+    }
+
+    /// `numerator / denominator`
+    pub fn ratio(numerator: f64, denominator: f64) -> Self {
+        if !(numerator.is_finite() && denominator.is_finite()) {
+            return Self(Err(NonFiniteReason::NonFiniteInput));
+        }
+        if denominator == 0.0 {
+            return Self(Err(NonFiniteReason::ZeroDenominator));
+        }
+        match Self::new(numerator / denominator) {
+            Self(Err(_)) => Self(Err(NonFiniteReason::NearZeroDenominator)),
+            quotient => quotient,
+        }
+    }
+
+    /// Apply `f` to a finite value; the result is checked again
+    pub fn map(self, f: impl FnOnce(f64) -> f64) -> Self {
+        match self.0 {
+            Ok(value) => Self::new(f(value)),
+            Err(reason) => Self(Err(reason)),
+        }
+    }
+
+    pub fn value(self) -> Option<f64> {
+        self.0.ok()
+    }
+
+    pub fn reason(self) -> Option<NonFiniteReason> {
+        self.0.err()
+    }
+}
+
+impl From<f64> for SafeF64 {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<SafeF64> for Option<f64> {
+    fn from(value: SafeF64) -> Self {
+        value.value()
+    }
+}
+
+impl Serialize for SafeF64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value().serialize(serializer)
+    }
+}
+
+/// `value` if finite
+pub fn finite(value: f64) -> Option<f64> {
+    SafeF64::new(value).value()
+}
+
+/// `numerator / denominator` if the quotient is finite
+pub fn safe_ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    SafeF64::ratio(numerator, denominator).value()
+}
+
+/// `serialize_with` for `Option<f64>` response fields: a non-finite value goes out as null
+pub fn serialize_finite<S: Serializer>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+    value.map(SafeF64::new).and_then(SafeF64::value).serialize(serializer)
+}
+
+/// `serialize_with` for `f64` response fields: a non-finite value goes out as null
+pub fn serialize_finite_f64<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    SafeF64::new(*value).serialize(serializer)
+}
+
+/// Mean and population standard deviation of `values`; None when empty or when a value isn't
+/// finite. Sums that would overflow, as with P/Es from earnings a hair above zero, are taken
+/// relative to the largest magnitude instead.
+pub fn mean_and_std_dev(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() || values.iter().any(|value| !value.is_finite()) {
+        return None;
+    }
+
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / n;
+    if mean.is_finite() && variance.is_finite() {
+        return Some((mean, variance.sqrt()));
+    }
+
+    let largest = values.iter().fold(0.0_f64, |largest, value| largest.max(value.abs()));
+    let relative: Vec<f64> = values.iter().map(|value| value / largest).collect();
+    let (mean, std_dev) = mean_and_std_dev(&relative)?;
+    Some((mean * largest, std_dev * largest))
+}
+
+/// Paths of the fields holding NaN or infinity in `value` as it would be serialized, e.g.
+/// `years.roic`; empty when every float is finite
+pub fn non_finite_fields<T: Serialize + ?Sized>(value: &T) -> Vec<String> {
+    let mut check = FloatCheck::default();
+    // The check itself never fails
+    value.serialize(&mut check).ok();
+    check.found
+}
+
+#[derive(Default)]
+struct FloatCheck {
+    path: Vec<&'static str>,
+    found: Vec<String>,
+}
+
+impl FloatCheck {
+    fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), CheckError> {
+        self.path.push(key);
+        let result = value.serialize(&mut *self);
+        self.path.pop();
+        result
+    }
+}
+
+#[derive(Debug)]
+struct CheckError(String);
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+impl ser::Error for CheckError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        CheckError(message.to_string())
+    }
+}
+
+impl Serializer for &mut FloatCheck {
+    type Ok = ();
+    type Error = CheckError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_f64(self, v: f64) -> Result<(), CheckError> {
+        if !v.is_finite() {
+            self.found.push(self.path.join("."));
+        }
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), CheckError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_i8(self, _: i8) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_i16(self, _: i16) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_i32(self, _: i32) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_i64(self, _: i64) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_u8(self, _: u8) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_u16(self, _: u16) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_u32(self, _: u32) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_u64(self, _: u64) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_char(self, _: char) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_str(self, _: &str) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_none(self) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_unit(self) -> Result<(), CheckError> { Ok(()) }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), CheckError> { Ok(()) }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), CheckError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CheckError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<(), CheckError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), CheckError> {
+        self.field(variant, value)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self, CheckError> { Ok(self) }
+    fn serialize_tuple(self, _: usize) -> Result<Self, CheckError> { Ok(self) }
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, CheckError> { Ok(self) }
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, CheckError> { Ok(self) }
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, CheckError> { Ok(self) }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, variant: &'static str, _: usize) -> Result<Self, CheckError> {
+        self.path.push(variant);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, variant: &'static str, _: usize) -> Result<Self, CheckError> {
+        self.path.push(variant);
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut FloatCheck {
+    type Ok = ();
+    type Error = CheckError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CheckError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CheckError> { Ok(()) }
+}
+
+impl ser::SerializeTuple for &mut FloatCheck {
+    type Ok = ();
+    type Error = CheckError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CheckError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CheckError> { Ok(()) }
+}
+
+impl ser::SerializeTupleStruct for &mut FloatCheck {
+    type Ok = ();
+    type Error = CheckError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CheckError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CheckError> { Ok(()) }
+}
+
+impl ser::SerializeTupleVariant for &mut FloatCheck {
+    type Ok = ();
+    type Error = CheckError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CheckError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CheckError> {
+        self.path.pop();
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut FloatCheck {
+    type Ok = ();
+    type Error = CheckError;
+
+    // Keys are strings or numbers in JSON; only values can carry a float
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, _: &T) -> Result<(), CheckError> { Ok(()) }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CheckError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CheckError> { Ok(()) }
+}
+
+impl ser::SerializeStruct for &mut FloatCheck {
+    type Ok = ();
+    type Error = CheckError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), CheckError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), CheckError> { Ok(()) }
+}
+
+impl ser::SerializeStructVariant for &mut FloatCheck {
+    type Ok = ();
+    type Error = CheckError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), CheckError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), CheckError> {
+        self.path.pop();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[derive(Serialize)]
+    struct Year {
+        fiscal_year: i32,
+        roic: Option<f64>,
+    }
+
+    #[derive(Serialize)]
+    struct Metrics {
+        latest: Year,
+        years: Vec<Year>,
+        score: f64,
+    }
+
+    #[test]
+    fn test_non_finite_fields_names_each_bad_float() {
+        let metrics = Metrics {
+            latest: Year { fiscal_year: 2024, roic: Some(f64::NAN) },
+            years: vec![Year { fiscal_year: 2023, roic: None }, Year { fiscal_year: 2024, roic: Some(f64::INFINITY) }],
+            score: 1.5,
+        };
+        assert_eq!(non_finite_fields(&metrics), vec!["latest.roic".to_string(), "years.roic".to_string()]);
+        assert!(non_finite_fields(&SafeF64::ratio(1.0, 0.0)).is_empty());
+    }
+
+    #[test]
+    fn test_ratio_reasons() {
+        assert_eq!(SafeF64::ratio(6.0, 3.0).value(), Some(2.0));
+        assert_eq!(SafeF64::ratio(1.0, 0.0).reason(), Some(NonFiniteReason::ZeroDenominator));
+        assert_eq!(SafeF64::ratio(1e300, 1e-300).reason(), Some(NonFiniteReason::NearZeroDenominator));
+        assert_eq!(SafeF64::ratio(f64::NAN, 2.0).reason(), Some(NonFiniteReason::NonFiniteInput));
+        assert_eq!(SafeF64::new(f64::MAX).map(|v| v * 2.0).reason(), Some(NonFiniteReason::Overflow));
+        assert_eq!(serde_json::to_string(&SafeF64::ratio(1.0, 0.0)).unwrap(), "null");
+    }
+
+    #[test]
+    fn test_guarded_fields_serialize_without_non_finite_floats() {
+        #[derive(Serialize)]
+        struct Guarded {
+            #[serde(serialize_with = "serialize_finite")]
+            ratio: Option<f64>,
+            #[serde(serialize_with = "serialize_finite_f64")]
+            score: f64,
+        }
+
+        let guarded = Guarded { ratio: Some(f64::INFINITY), score: f64::NAN };
+        assert!(non_finite_fields(&guarded).is_empty());
+        assert_eq!(serde_json::to_string(&guarded).unwrap(), r#"{"ratio":null,"score":null}"#);
+        let finite = Guarded { ratio: Some(1.5), score: 2.0 };
+        assert_eq!(serde_json::to_string(&finite).unwrap(), r#"{"ratio":1.5,"score":2.0}"#);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_ratio_is_finite_or_has_a_reason(numerator in any::<f64>(), denominator in any::<f64>()) {
+            let ratio = SafeF64::ratio(numerator, denominator);
+            match ratio.value() {
+                Some(value) => prop_assert!(value.is_finite() && ratio.reason().is_none()),
+                None => prop_assert!(ratio.reason().is_some()),
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Offline stand-ins for SEC EDGAR and Schwab. Responses under `api_responses/` are recorded
+//! payloads trimmed to the concepts the extractors read, with one current-period fact per filing.
+
+use chrono::Utc;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::api::schwab_client::SchwabClient;
+use crate::models::Config;
+
+pub const APPLE_CIK: &str = "0000320193";
+pub const MICROSOFT_CIK: &str = "0000789019";
+
+/// Bearer token the Schwab mock accepts
+pub const SCHWAB_ACCESS_TOKEN: &str = "mock_access";
+
+fn recorded_edgar_company(cik: &str) -> (&'static str, &'static str) {
+    match cik {
+        APPLE_CIK => (
+            include_str!("api_responses/edgar_submissions_CIK0000320193.json"),
+            include_str!("api_responses/edgar_companyfacts_CIK0000320193.json"),
+        ),
+        MICROSOFT_CIK => (
+            include_str!("api_responses/edgar_submissions_CIK0000789019.json"),
+            include_str!("api_responses/edgar_companyfacts_CIK0000789019.json"),
+        ),
+        _ => panic!("no recorded EDGAR responses for CIK {}", cik),
+    }
+}
+
+/// Serve the recorded Submissions and Company Facts responses for `cik`; other CIKs get a 404
+pub async fn mount_edgar_company(server: &MockServer, cik: &str) {
+    let (submissions, company_facts) = recorded_edgar_company(cik);
+    Mock::given(method("GET"))
+        .and(path(format!("/submissions/CIK{}.json", cik)))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(submissions, "application/json"))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/xbrl/companyfacts/CIK{}.json", cik)))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(company_facts, "application/json"))
+        .mount(server)
+        .await;
+}
+
+/// Serve AAPL's recorded daily bars, 2024-01-02 to 2024-01-05 with a malformed one on the
+/// 4th, to requests starting at `start_millis`
+pub async fn mount_schwab_price_history(server: &MockServer, start_millis: i64) {
+    Mock::given(method("GET"))
+        .and(path("/marketdata/v1/pricehistory"))
+        .and(query_param("symbol", "AAPL"))
+        .and(query_param("startDate", start_millis.to_string()))
+        .and(header("Authorization", format!("Bearer {}", SCHWAB_ACCESS_TOKEN).as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            include_str!("api_responses/schwab_pricehistory_AAPL.json"),
+            "application/json",
+        ))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+/// A Schwab client for `server_uri` with an unexpired token file, in the format the
+/// authentication script writes, under `dir`
+pub fn schwab_client(dir: &tempfile::TempDir, server_uri: &str) -> SchwabClient {
+    let token_path = dir.path().join("schwab_tokens.json");
+    let token_file = serde_json::json!({
+        "creation_timestamp": Utc::now().timestamp() as f64,
+        "token": {
+            "access_token": SCHWAB_ACCESS_TOKEN,
+            "refresh_token": "mock_refresh",
+            "expires_at": (Utc::now() + chrono::Duration::hours(1)).timestamp(),
+            "expires_in": 1800,
+            "token_type": "Bearer",
+            "scope": "api",
+            "id_token": "mock_id"
+        }
+    });
+    std::fs::write(&token_path, token_file.to_string()).unwrap();
+
+    let config = Config {
+        schwab_api_key: "key".to_string(),
+        schwab_app_secret: "secret".to_string(),
+        schwab_callback_url: "https://127.0.0.1:8182".to_string(),
+        schwab_token_path: token_path.to_string_lossy().to_string(),
+        database_path: "unused.db".to_string(),
+        rate_limit_per_minute: 60_000,
+        batch_size: 1,
+        debug_log_retention_days: 14,
+        debug_log_max_files: 50,
+    };
+    SchwabClient::new(&config).unwrap().with_base_url(server_uri)
+}
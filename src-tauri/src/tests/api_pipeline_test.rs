@@ -0,0 +1,151 @@
+use wiremock::MockServer;
+
+use crate::commands::data::refresh_fundamentals_for_internal;
+use crate::error::CommandError;
+use crate::tests::api_mock::{
+    mount_edgar_company, mount_schwab_price_history, schwab_client, APPLE_CIK, MICROSOFT_CIK,
+};
+use crate::tests::fixtures::{day, FixtureBuilder, PriceBar};
+use crate::tools::data_refresh_orchestrator::import_price_history;
+use crate::tools::freshness_checker::DataStatusReader;
+use crate::tools::freshness_types::StockFilter;
+
+#[tokio::test]
+async fn test_edgar_extraction_stores_recorded_filings() {
+    let server = MockServer::start().await;
+    mount_edgar_company(&server, APPLE_CIK).await;
+    mount_edgar_company(&server, MICROSOFT_CIK).await;
+
+    let fixture = FixtureBuilder::new()
+        .with_stock("AAPL").with_cik(APPLE_CIK).with_sp500_flag()
+        .with_stock("MSFT").with_cik(MICROSOFT_CIK).with_sp500_flag()
+        // Not served by the mock, so SEC answers 404
+        .with_stock("GONE").with_cik("0000000001").with_sp500_flag()
+        .build()
+        .await;
+    let pool = &fixture.pool;
+
+    let reader = DataStatusReader::new(pool.clone()).with_sec_base_url(server.uri());
+    let stocks = reader.get_sp500_stocks_with_ciks(None).await.unwrap();
+    let (stored, errors) = reader.run_unified_financials_for_stocks(&stocks).await.unwrap();
+    assert_eq!(stored, 3);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].symbol.as_deref(), Some("GONE"));
+
+    // Only the 10-Ks; the 10-Qs in the submissions are skipped
+    let filings: Vec<(String, String, String, i32)> = sqlx::query_as(
+        "SELECT s.symbol, f.accession_number, f.form_type, f.fiscal_year
+         FROM sec_filings f JOIN stocks s ON s.id = f.stock_id
+         ORDER BY s.symbol, f.fiscal_year"
+    )
+    .fetch_all(pool).await.unwrap();
+    assert_eq!(filings, vec![
+        ("AAPL".to_string(), "0000320193-22-000108".to_string(), "10-K".to_string(), 2022),
+        ("AAPL".to_string(), "0000320193-23-000106".to_string(), "10-K".to_string(), 2023),
+        ("MSFT".to_string(), "0000950170-23-035122".to_string(), "10-K".to_string(), 2023),
+    ]);
+
+    let income: (String, f64, f64, f64, f64) = sqlx::query_as(
+        "SELECT report_date, revenue, gross_profit, operating_income, net_income
+         FROM income_statements WHERE stock_id = 1 AND fiscal_year = 2023 AND period_type = 'FY'"
+    )
+    .fetch_one(pool).await.unwrap();
+    assert_eq!(income, ("2023-09-30".to_string(), 383_285e6, 169_148e6, 114_301e6, 96_995e6));
+
+    let balance: (f64, f64, f64, f64, f64, f64, f64) = sqlx::query_as(
+        "SELECT total_assets, total_liabilities, total_equity, cash_and_equivalents, current_assets, current_liabilities,
+                shares_outstanding
+         FROM balance_sheets WHERE stock_id = 1 AND fiscal_year = 2023 AND period_type = 'Annual'"
+    )
+    .fetch_one(pool).await.unwrap();
+    assert_eq!(balance, (352_583e6, 290_437e6, 62_146e6, 29_965e6, 143_566e6, 145_308e6, 15_552_752_000.0));
+
+    let cash_flow: (f64, f64, f64, f64) = sqlx::query_as(
+        "SELECT operating_cash_flow, capital_expenditures, dividends_paid, share_repurchases
+         FROM cash_flow_statements WHERE stock_id = 1 AND fiscal_year = 2022 AND period_type = 'Annual'"
+    )
+    .fetch_one(pool).await.unwrap();
+    assert_eq!(cash_flow, (122_151e6, 10_708e6, 14_841e6, 89_402e6));
+
+    // Microsoft's fiscal year ends in June
+    let microsoft: (String, f64, f64, f64) = sqlx::query_as(
+        "SELECT i.report_date, i.revenue, b.total_equity, c.operating_cash_flow
+         FROM income_statements i
+         JOIN balance_sheets b ON b.sec_filing_id = i.sec_filing_id
+         JOIN cash_flow_statements c ON c.sec_filing_id = i.sec_filing_id
+         WHERE i.stock_id = 2"
+    )
+    .fetch_one(pool).await.unwrap();
+    assert_eq!(microsoft, ("2023-06-30".to_string(), 211_915e6, 206_223e6, 87_582e6));
+
+    // Filings already stored are not written again
+    let (stored, errors) = reader.run_unified_financials_for_stocks(&stocks).await.unwrap();
+    assert_eq!((stored, errors.len()), (0, 1));
+}
+
+#[tokio::test]
+async fn test_fundamentals_refresh_for_symbols_outside_the_sp500() {
+    let server = MockServer::start().await;
+    mount_edgar_company(&server, APPLE_CIK).await;
+    mount_edgar_company(&server, MICROSOFT_CIK).await;
+
+    let fixture = FixtureBuilder::new()
+        .with_stock("AAPL").with_cik(APPLE_CIK)
+        .with_stock("MSFT").with_cik(MICROSOFT_CIK)
+        // In the index but not asked for; SEC would answer 404 if it were fetched
+        .with_stock("GONE").with_cik("0000000001").with_sp500_flag()
+        .build()
+        .await;
+    let pool = &fixture.pool;
+    let reader = || DataStatusReader::new(pool.clone()).with_sec_base_url(server.uri());
+
+    let sp500 = reader().get_stocks_with_ciks(&StockFilter::Sp500).await.unwrap();
+    assert_eq!(sp500, vec![(3, "0000000001".to_string(), "GONE".to_string())]);
+
+    let filter = StockFilter::Symbols(vec!["aapl".to_string(), "MSFT".to_string()]);
+    let result = refresh_fundamentals_for_internal(reader(), &filter).await.unwrap();
+    assert_eq!((result.stocks_processed, result.records_stored), (2, 3));
+    assert!(result.errors.is_empty());
+
+    let filings: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT s.symbol, COUNT(f.id) FROM stocks s LEFT JOIN sec_filings f ON f.stock_id = s.id GROUP BY s.symbol ORDER BY s.symbol"
+    )
+    .fetch_all(pool).await.unwrap();
+    assert_eq!(filings, vec![("AAPL".to_string(), 2), ("GONE".to_string(), 0), ("MSFT".to_string(), 1)]);
+
+    // Nothing matching is a validation error, not an empty run
+    let none = refresh_fundamentals_for_internal(reader(), &StockFilter::Tag("watchlist".to_string())).await;
+    assert!(matches!(none, Err(CommandError::Validation { .. })));
+}
+
+#[tokio::test]
+async fn test_schwab_import_stores_recorded_bars_after_latest_price() {
+    let fixture = FixtureBuilder::new()
+        .with_stock("AAPL")
+        .with_prices(day(2023, 12, 29)..=day(2023, 12, 29), |_, _| PriceBar::flat(192.53))
+        .build()
+        .await;
+    let pool = &fixture.pool;
+
+    // The import resumes the day after the stored bar
+    let server = MockServer::start().await;
+    let resume_millis = day(2023, 12, 30).and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis();
+    mount_schwab_price_history(&server, resume_millis).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let client = schwab_client(&dir, &server.uri());
+    let inserted = import_price_history(&client, pool, 1, "AAPL", day(2024, 1, 5)).await.unwrap();
+    assert_eq!(inserted, 3);
+
+    // The 4th, with its high below its low, is dropped
+    let bars: Vec<(String, f64, f64, f64, f64, i64)> = sqlx::query_as(
+        "SELECT date, open_price, high_price, low_price, close_price, volume FROM daily_prices WHERE stock_id = 1 ORDER BY date"
+    )
+    .fetch_all(pool).await.unwrap();
+    assert_eq!(bars, vec![
+        ("2023-12-29".to_string(), 192.53, 192.53, 192.53, 192.53, 1_000_000),
+        ("2024-01-02".to_string(), 187.15, 188.44, 183.89, 185.64, 82_488_700),
+        ("2024-01-03".to_string(), 184.22, 185.88, 183.43, 184.25, 58_414_500),
+        ("2024-01-05".to_string(), 181.99, 182.76, 180.17, 181.18, 62_303_300),
+    ]);
+}
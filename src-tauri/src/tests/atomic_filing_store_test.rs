@@ -0,0 +1,102 @@
+use chrono::NaiveDate;
+use sqlx::SqlitePool;
+
+use crate::tests::fixtures::migrated_pool;
+use crate::tools::sec_edgar_client::{BalanceSheetData, CashFlowData, FilingMetadata, IncomeStatementData, SecEdgarClient};
+
+async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+    let pool = migrated_pool(dir).await;
+    sqlx::query("INSERT INTO stocks (id, symbol, company_name, cik) VALUES (1, 'AAPL', 'Apple Inc.', '320193')")
+        .execute(&pool).await.unwrap();
+    pool
+}
+
+fn filing(accession_number: &str, form_type: &str) -> FilingMetadata {
+    FilingMetadata {
+        accession_number: accession_number.to_string(),
+        form_type: form_type.to_string(),
+        filing_date: "2023-11-03".to_string(),
+        fiscal_period: "FY".to_string(),
+        report_date: "2023-09-30".to_string(),
+    }
+}
+
+fn statements() -> (BalanceSheetData, IncomeStatementData, CashFlowData) {
+    let report_date = NaiveDate::from_ymd_opt(2023, 9, 30).unwrap();
+    let balance = BalanceSheetData {
+        stock_id: 1, symbol: "AAPL".to_string(), report_date, fiscal_year: 2023,
+        total_assets: Some(352.6e9), total_liabilities: Some(290.4e9), total_equity: Some(62.1e9),
+        cash_and_equivalents: None, short_term_debt: None, long_term_debt: None, total_debt: None,
+        current_assets: None, current_liabilities: None, inventory: None, share_repurchases: None, shares_outstanding: None,
+        shares_outstanding_estimated: false,
+    };
+    let income = IncomeStatementData {
+        stock_id: 1, symbol: "AAPL".to_string(), report_date, fiscal_year: 2023, period_type: "FY".to_string(),
+        revenue: Some(383.3e9), net_income: Some(97.0e9), operating_income: None, gross_profit: None,
+        cost_of_revenue: None, interest_expense: None, tax_expense: None, pretax_income: None, shares_basic: None, shares_diluted: None,
+    };
+    let cash_flow = CashFlowData {
+        stock_id: 1, symbol: "AAPL".to_string(), report_date, fiscal_year: 2023,
+        depreciation_expense: None, amortization_expense: None, dividends_paid: None, share_repurchases: None,
+        operating_cash_flow: Some(110.5e9), investing_cash_flow: None, financing_cash_flow: None,
+        capital_expenditures: None,
+    };
+    (balance, income, cash_flow)
+}
+
+async fn row_counts(pool: &SqlitePool) -> (i64, i64, i64, i64) {
+    let count = |table: &'static str| async move {
+        sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    };
+    (
+        count("sec_filings").await,
+        count("balance_sheets").await,
+        count("income_statements").await,
+        count("cash_flow_statements").await,
+    )
+}
+
+#[tokio::test]
+async fn test_failed_cash_flow_insert_leaves_no_partial_filing() {
+    let dir = tempfile::tempdir().unwrap();
+    let pool = seeded_pool(&dir).await;
+    let (balance, income, cash_flow) = statements();
+
+    let client = SecEdgarClient::new(pool.clone()).with_failing_cash_flow_insert();
+    let result = client
+        .store_filing_atomic(1, "AAPL", &filing("0000320193-23-000106", "10-K"), 2023, "2023-09-30", &balance, &income, &cash_flow)
+        .await;
+
+    assert!(result.unwrap_err().to_string().contains("Failed to store cash flow"));
+    assert_eq!(row_counts(&pool).await, (0, 0, 0, 0));
+}
+
+#[tokio::test]
+async fn test_failed_amendment_keeps_original_filing() {
+    let dir = tempfile::tempdir().unwrap();
+    let pool = seeded_pool(&dir).await;
+    let (balance, income, cash_flow) = statements();
+
+    SecEdgarClient::new(pool.clone())
+        .store_filing_atomic(1, "AAPL", &filing("0000320193-23-000106", "10-K"), 2023, "2023-09-30", &balance, &income, &cash_flow)
+        .await
+        .unwrap();
+    assert_eq!(row_counts(&pool).await, (1, 1, 1, 1));
+
+    // The 10-K/A deletes the original 10-K inside the same transaction, so the failure must undo that too
+    let client = SecEdgarClient::new(pool.clone()).with_failing_cash_flow_insert();
+    let result = client
+        .store_filing_atomic(1, "AAPL", &filing("0000320193-24-000012", "10-K/A"), 2023, "2023-09-30", &balance, &income, &cash_flow)
+        .await;
+    assert!(result.is_err());
+
+    assert_eq!(row_counts(&pool).await, (1, 1, 1, 1));
+    let form_type: String = sqlx::query_scalar("SELECT form_type FROM sec_filings")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(form_type, "10-K");
+}
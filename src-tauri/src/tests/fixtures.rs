@@ -0,0 +1,331 @@
+//! Seeded test databases. `FixtureBuilder` migrates a fresh SQLite file and fills it with
+//! stocks, prices and statements from deterministic generators, so tests state only the
+//! numbers they assert on:
+//!
+//! ```ignore
+//! let fixture = FixtureBuilder::new()
+//!     .with_stock("AAPL")
+//!     .with_sp500_flag()
+//!     .with_prices(day(2024, 1, 1)..=day(2024, 3, 31), |i, _| PriceBar::flat(100.0 + i as f64))
+//!     .with_annual_financials(2021..=2023, |year| AnnualFinancials { revenue: Some(year as f64), ..Default::default() })
+//!     .build()
+//!     .await;
+//! ```
+
+use chrono::NaiveDate;
+use sqlx::SqlitePool;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use crate::database_sqlx::DatabaseManagerSqlx;
+
+/// File name of the database `migrated_pool` creates in its directory
+pub const TEST_DB_FILE: &str = "test.db";
+
+/// A freshly migrated, empty database in `dir`, for tests that insert their own rows
+pub async fn migrated_pool(dir: &tempfile::TempDir) -> SqlitePool {
+    let path = dir.path().join(TEST_DB_FILE);
+    let pool = SqlitePool::connect(&format!("sqlite:{}?mode=rwc", path.display())).await.unwrap();
+    sqlx::migrate!("./db/migrations").run(&pool).await.unwrap();
+    pool
+}
+
+pub fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// One daily_prices row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceBar {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+}
+
+impl PriceBar {
+    /// Open, high, low and close all at `close`, on a million shares
+    pub fn flat(close: f64) -> Self {
+        Self { open: close, high: close, low: close, close, volume: 1_000_000 }
+    }
+}
+
+/// One daily_valuation_ratios row; unset values stay NULL
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ValuationDay {
+    pub price: Option<f64>,
+    pub market_cap: Option<f64>,
+    pub ps_ratio_ttm: Option<f64>,
+    pub pe_ratio_ttm: Option<f64>,
+    pub evs_ratio_ttm: Option<f64>,
+    pub data_completeness_score: i64,
+}
+
+/// One fiscal year's statements, filed as of December 31st: an 'FY' income statement and
+/// 'Annual' balance sheet and cash flow statement, each only if one of its values is set
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AnnualFinancials {
+    // Income statement
+    pub revenue: Option<f64>,
+    pub gross_profit: Option<f64>,
+    pub operating_income: Option<f64>,
+    pub net_income: Option<f64>,
+
+    // Balance sheet
+    pub total_assets: Option<f64>,
+    pub total_equity: Option<f64>,
+    pub total_debt: Option<f64>,
+    pub cash_and_equivalents: Option<f64>,
+    pub current_assets: Option<f64>,
+    pub current_liabilities: Option<f64>,
+    pub inventory: Option<f64>,
+    pub shares_outstanding: Option<f64>,
+
+    // Cash flow statement
+    pub operating_cash_flow: Option<f64>,
+    pub capital_expenditures: Option<f64>,
+    pub dividends_paid: Option<f64>,
+    /// Cash paid for repurchases, gross of any stock issued
+    pub share_repurchases: Option<f64>,
+}
+
+impl AnnualFinancials {
+    fn has_income_statement(&self) -> bool {
+        [self.revenue, self.gross_profit, self.operating_income, self.net_income].iter().any(Option::is_some)
+    }
+
+    fn has_balance_sheet(&self) -> bool {
+        [
+            self.total_assets,
+            self.total_equity,
+            self.total_debt,
+            self.cash_and_equivalents,
+            self.current_assets,
+            self.current_liabilities,
+            self.inventory,
+            self.shares_outstanding,
+        ]
+        .iter()
+        .any(Option::is_some)
+    }
+
+    fn has_cash_flow_statement(&self) -> bool {
+        [self.operating_cash_flow, self.capital_expenditures, self.dividends_paid, self.share_repurchases]
+            .iter()
+            .any(Option::is_some)
+    }
+}
+
+struct StockFixture {
+    symbol: String,
+    company_name: String,
+    sector: Option<String>,
+    cik: Option<String>,
+    is_sp500: bool,
+    prices: Vec<(NaiveDate, PriceBar)>,
+    valuation_ratios: Vec<(NaiveDate, ValuationDay)>,
+    financials: Vec<(i32, AnnualFinancials)>,
+}
+
+/// Every calendar day in `days`, numbered from 0
+fn each_day(days: RangeInclusive<NaiveDate>) -> impl Iterator<Item = (usize, NaiveDate)> {
+    let end = *days.end();
+    days.start().iter_days().take_while(move |date| *date <= end).enumerate()
+}
+
+/// Stocks get ids 1, 2, ... in the order added. Every `with_*` call after `with_stock` applies
+/// to that stock.
+#[derive(Default)]
+pub struct FixtureBuilder {
+    stocks: Vec<StockFixture>,
+}
+
+impl FixtureBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a stock named "<symbol> Corp"
+    pub fn with_stock(mut self, symbol: &str) -> Self {
+        self.stocks.push(StockFixture {
+            symbol: symbol.to_string(),
+            company_name: format!("{} Corp", symbol),
+            sector: None,
+            cik: None,
+            is_sp500: false,
+            prices: Vec::new(),
+            valuation_ratios: Vec::new(),
+            financials: Vec::new(),
+        });
+        self
+    }
+
+    fn current(&mut self) -> &mut StockFixture {
+        self.stocks.last_mut().expect("call with_stock first")
+    }
+
+    pub fn with_company_name(mut self, company_name: &str) -> Self {
+        self.current().company_name = company_name.to_string();
+        self
+    }
+
+    pub fn with_sector(mut self, sector: &str) -> Self {
+        self.current().sector = Some(sector.to_string());
+        self
+    }
+
+    pub fn with_cik(mut self, cik: &str) -> Self {
+        self.current().cik = Some(cik.to_string());
+        self
+    }
+
+    pub fn with_sp500_flag(mut self) -> Self {
+        self.current().is_sp500 = true;
+        self
+    }
+
+    /// A price bar for every calendar day in `days`, from `generator(day_index, date)`
+    pub fn with_prices(mut self, days: RangeInclusive<NaiveDate>, generator: impl Fn(usize, NaiveDate) -> PriceBar) -> Self {
+        let prices = each_day(days).map(|(index, date)| (date, generator(index, date))).collect::<Vec<_>>();
+        self.current().prices.extend(prices);
+        self
+    }
+
+    /// A valuation ratio row for every calendar day in `days`, from `generator(day_index, date)`
+    pub fn with_valuation_ratios(mut self, days: RangeInclusive<NaiveDate>, generator: impl Fn(usize, NaiveDate) -> ValuationDay) -> Self {
+        let ratios = each_day(days).map(|(index, date)| (date, generator(index, date))).collect::<Vec<_>>();
+        self.current().valuation_ratios.extend(ratios);
+        self
+    }
+
+    /// Statements for each fiscal year in `years`, from `template(fiscal_year)`
+    pub fn with_annual_financials(mut self, years: RangeInclusive<i32>, template: impl Fn(i32) -> AnnualFinancials) -> Self {
+        let financials = years.map(|year| (year, template(year))).collect::<Vec<_>>();
+        self.current().financials.extend(financials);
+        self
+    }
+
+    /// Migrate a new database in a temporary directory and insert everything added
+    pub async fn build(self) -> Fixture {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        for (index, stock) in self.stocks.iter().enumerate() {
+            let stock_id = index as i64 + 1;
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, sector, cik, is_sp500) VALUES (?, ?, ?, ?, ?, ?)")
+                .bind(stock_id).bind(&stock.symbol).bind(&stock.company_name).bind(&stock.sector).bind(&stock.cik).bind(stock.is_sp500)
+                .execute(&mut *tx).await.unwrap();
+
+            for (date, bar) in &stock.prices {
+                sqlx::query(
+                    "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price, volume)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)"
+                )
+                .bind(stock_id).bind(date.to_string()).bind(bar.open).bind(bar.high).bind(bar.low).bind(bar.close).bind(bar.volume)
+                .execute(&mut *tx).await.unwrap();
+            }
+
+            for (date, ratios) in &stock.valuation_ratios {
+                sqlx::query(
+                    "INSERT INTO daily_valuation_ratios (stock_id, date, price, market_cap, ps_ratio_ttm, pe_ratio_ttm, evs_ratio_ttm, data_completeness_score)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+                )
+                .bind(stock_id).bind(date.to_string()).bind(ratios.price).bind(ratios.market_cap)
+                .bind(ratios.ps_ratio_ttm).bind(ratios.pe_ratio_ttm).bind(ratios.evs_ratio_ttm).bind(ratios.data_completeness_score)
+                .execute(&mut *tx).await.unwrap();
+            }
+
+            for (year, financials) in &stock.financials {
+                let report_date = format!("{}-12-31", year);
+                if financials.has_income_statement() {
+                    sqlx::query(
+                        "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, gross_profit, operating_income, net_income)
+                         VALUES (?, 'FY', ?, ?, ?, ?, ?, ?)"
+                    )
+                    .bind(stock_id).bind(&report_date).bind(year)
+                    .bind(financials.revenue).bind(financials.gross_profit).bind(financials.operating_income).bind(financials.net_income)
+                    .execute(&mut *tx).await.unwrap();
+                }
+                if financials.has_balance_sheet() {
+                    sqlx::query(
+                        "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_assets, total_equity, total_debt,
+                            cash_and_equivalents, current_assets, current_liabilities, inventory, shares_outstanding)
+                         VALUES (?, 'Annual', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+                    )
+                    .bind(stock_id).bind(&report_date).bind(year)
+                    .bind(financials.total_assets).bind(financials.total_equity).bind(financials.total_debt)
+                    .bind(financials.cash_and_equivalents).bind(financials.current_assets).bind(financials.current_liabilities)
+                    .bind(financials.inventory).bind(financials.shares_outstanding)
+                    .execute(&mut *tx).await.unwrap();
+                }
+                if financials.has_cash_flow_statement() {
+                    sqlx::query(
+                        "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, operating_cash_flow, capital_expenditures,
+                            dividends_paid, share_repurchases)
+                         VALUES (?, 'Annual', ?, ?, ?, ?, ?, ?)"
+                    )
+                    .bind(stock_id).bind(&report_date).bind(year)
+                    .bind(financials.operating_cash_flow).bind(financials.capital_expenditures)
+                    .bind(financials.dividends_paid).bind(financials.share_repurchases)
+                    .execute(&mut *tx).await.unwrap();
+                }
+            }
+        }
+        tx.commit().await.unwrap();
+
+        Fixture { pool, path: dir.path().join(TEST_DB_FILE), _dir: dir }
+    }
+}
+
+/// A built fixture database; deleted when dropped
+pub struct Fixture {
+    pub pool: SqlitePool,
+    path: PathBuf,
+    _dir: tempfile::TempDir,
+}
+
+impl Fixture {
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// A second connection to the same file through the older manager
+    pub async fn database_manager(&self) -> DatabaseManagerSqlx {
+        DatabaseManagerSqlx::new(&self.path.display().to_string()).await.unwrap()
+    }
+}
+
+#[tokio::test]
+async fn test_builder_inserts_generated_rows() {
+    let fixture = FixtureBuilder::new()
+        .with_stock("AAPL")
+        .with_sector("Technology")
+        .with_sp500_flag()
+        .with_prices(day(2024, 1, 1)..=day(2024, 1, 10), |i, _| PriceBar::flat(100.0 + i as f64))
+        .with_annual_financials(2021..=2023, |year| AnnualFinancials {
+            revenue: Some(1000.0 * (year - 2020) as f64),
+            total_assets: (year == 2023).then_some(5000.0),
+            ..Default::default()
+        })
+        .with_stock("MSFT")
+        .build()
+        .await;
+
+    let sp500: Vec<String> = sqlx::query_scalar("SELECT symbol FROM sp500_symbols").fetch_all(&fixture.pool).await.unwrap();
+    assert_eq!(sp500, vec!["AAPL"]);
+
+    let (days, last_close): (i64, f64) = sqlx::query_as("SELECT COUNT(*), MAX(close_price) FROM daily_prices WHERE stock_id = 1")
+        .fetch_one(&fixture.pool).await.unwrap();
+    assert_eq!((days, last_close), (10, 109.0));
+
+    let statements: (i64, i64, i64) = sqlx::query_as(
+        "SELECT (SELECT COUNT(*) FROM income_statements), (SELECT COUNT(*) FROM balance_sheets), (SELECT COUNT(*) FROM cash_flow_statements)"
+    )
+    .fetch_one(&fixture.pool).await.unwrap();
+    assert_eq!(statements, (3, 1, 0));
+
+    let stats = fixture.database_manager().await.get_stats().await.unwrap();
+    assert_eq!((stats["total_stocks"], stats["total_price_records"]), (2, 10));
+}
@@ -0,0 +1,136 @@
+use sqlx::SqlitePool;
+
+use crate::commands::graham_screening::{run_graham_screening_internal, GrahamScreeningCriteria};
+use crate::tests::fixtures::{day, AnnualFinancials, Fixture, FixtureBuilder, PriceBar};
+use crate::tools::screen_exclusions::{ExclusionCount, ExclusionReason, RatioNote, ScreenExclusion};
+
+/// Close of 50 on 20M shares: a $1B market cap, clear of the default size floor
+const MARKET_CAP: f64 = 1e9;
+
+/// Graham's inputs on the migrated schema: a month of prices and the statements the ratios
+/// are derived from. Every stock has P/E ≤ 15; CHEAP, VALUE and ASSETY differ in P/B, SHAKY
+/// lost money three years ago and TIGHT has a current ratio of 1.5.
+async fn graham_fixture() -> Fixture {
+    // (symbol, P/E, P/B, current ratio, years back with a loss)
+    let stocks = [
+        ("CHEAP", 8.0, 1.0, 2.5, None),
+        ("VALUE", 14.0, 1.4, 2.5, None),
+        ("ASSETY", 10.0, 2.0, 2.5, None),
+        ("SHAKY", 9.0, 1.0, 2.5, Some(3)),
+        ("TIGHT", 9.0, 1.0, 1.5, None),
+    ];
+    let mut builder = FixtureBuilder::new();
+    for (symbol, pe, pb, current_ratio, loss_years_back) in stocks {
+        builder = builder
+            .with_stock(symbol)
+            .with_prices(day(2024, 6, 1)..=day(2024, 6, 28), |_, _| PriceBar::flat(50.0))
+            .with_annual_financials(2016..=2023, |year| {
+                let latest = year == 2023;
+                let net_income = if loss_years_back == Some(2023 - year) {
+                    -1e7
+                } else if latest {
+                    MARKET_CAP / pe
+                } else {
+                    5e7
+                };
+                AnnualFinancials {
+                    net_income: Some(net_income),
+                    total_equity: latest.then_some(MARKET_CAP / pb),
+                    current_assets: latest.then_some(current_ratio * 1e8),
+                    current_liabilities: latest.then_some(1e8),
+                    shares_outstanding: latest.then_some(2e7),
+                    ..Default::default()
+                }
+            });
+    }
+    builder.build().await
+}
+
+async fn passing(pool: &SqlitePool, criteria: GrahamScreeningCriteria) -> Vec<String> {
+    run_graham_screening_internal(pool, Some(criteria), None)
+        .await
+        .unwrap()
+        .results
+        .into_iter()
+        .map(|result| result.symbol)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_combined_pe_pb_cap_is_tunable() {
+    let fixture = graham_fixture().await;
+    let pool = &fixture.pool;
+
+    // P/E × P/B of 8, 19.6 and 20 all clear 22.5; SHAKY fails stability and TIGHT the current ratio
+    let default = passing(pool, GrahamScreeningCriteria::default()).await;
+    assert_eq!(default, vec!["CHEAP", "VALUE", "ASSETY"]);
+
+    let stricter = passing(pool, GrahamScreeningCriteria { max_pe_times_pb: Some(15.0), ..GrahamScreeningCriteria::default() }).await;
+    assert_eq!(stricter, vec!["CHEAP"]);
+    assert!(stricter.len() < default.len());
+
+    // Without the combined rule the separate P/B cap of 1.5 rules out ASSETY
+    let separate = passing(pool, GrahamScreeningCriteria { use_combined_pe_pb: Some(false), ..GrahamScreeningCriteria::default() }).await;
+    assert_eq!(separate, vec!["CHEAP", "VALUE"]);
+
+    // Two years of stable earnings are enough for SHAKY
+    let lenient = passing(pool, GrahamScreeningCriteria { min_earnings_stability_years: Some(2), ..GrahamScreeningCriteria::default() }).await;
+    assert_eq!(lenient, vec!["CHEAP", "SHAKY", "VALUE", "ASSETY"]);
+}
+
+
+#[tokio::test]
+async fn test_negative_equity_and_earnings_are_excluded_with_reasons() {
+    // (symbol, latest net income, latest total equity); P/E is only computed on positive earnings
+    let stocks = [
+        ("CHEAP", MARKET_CAP / 8.0, MARKET_CAP),
+        ("LOSS", -1e7, MARKET_CAP),
+        ("UNDER", MARKET_CAP / 9.0, -2e8),
+        ("BOTH", -1e7, -2e8),
+    ];
+    let mut builder = FixtureBuilder::new();
+    for (symbol, net_income, total_equity) in stocks {
+        builder = builder
+            .with_stock(symbol)
+            .with_prices(day(2024, 6, 1)..=day(2024, 6, 28), |_, _| PriceBar::flat(50.0))
+            .with_annual_financials(2023..=2023, |_| AnnualFinancials {
+                net_income: Some(net_income),
+                total_equity: Some(total_equity),
+                current_assets: Some(2.5e8),
+                current_liabilities: Some(1e8),
+                shares_outstanding: Some(2e7),
+                ..Default::default()
+            });
+    }
+    let fixture = builder.build().await;
+    let pool = &fixture.pool;
+    let exclusion = |stock_id: i64, symbol: &str, reason| ScreenExclusion { stock_id, symbol: symbol.to_string(), reason };
+
+    // One year of history, so only the sign policy separates the four
+    let no_stability = GrahamScreeningCriteria { min_earnings_stability_years: None, ..GrahamScreeningCriteria::default() };
+    let screen = run_graham_screening_internal(pool, Some(no_stability.clone()), None).await.unwrap();
+    assert_eq!(screen.results.iter().map(|r| r.symbol.as_str()).collect::<Vec<_>>(), vec!["CHEAP"]);
+    assert_eq!(screen.excluded, vec![
+        exclusion(4, "BOTH", ExclusionReason::NegativeEquity),
+        exclusion(2, "LOSS", ExclusionReason::NegativeEarnings),
+        exclusion(3, "UNDER", ExclusionReason::NegativeEquity),
+    ]);
+    assert_eq!(screen.exclusions, vec![
+        ExclusionCount { reason: ExclusionReason::NegativeEarnings, count: 1 },
+        ExclusionCount { reason: ExclusionReason::NegativeEquity, count: 2 },
+    ]);
+
+    // Included loss-makers are listed without a P/E, after every stock that has one
+    let included = GrahamScreeningCriteria { include_negative_earnings: Some(true), ..no_stability };
+    let screen = run_graham_screening_internal(pool, Some(included), None).await.unwrap();
+    assert_eq!(screen.results.iter().map(|r| r.symbol.as_str()).collect::<Vec<_>>(), vec!["CHEAP", "LOSS"]);
+    let loss = &screen.results[1];
+    assert_eq!((loss.pe_ratio, loss.pe_times_pb, loss.pe_ratio_note), (None, None, Some(RatioNote::NegativeEarnings)));
+    assert_eq!(loss.pb_ratio, Some(1.0));
+    assert_eq!(screen.results[0].pe_ratio_note, None);
+    assert_eq!(screen.excluded, vec![
+        exclusion(4, "BOTH", ExclusionReason::NegativeEquity),
+        exclusion(3, "UNDER", ExclusionReason::NegativeEquity),
+    ]);
+    assert_eq!(screen.exclusions, vec![ExclusionCount { reason: ExclusionReason::NegativeEquity, count: 2 }]);
+}
\ No newline at end of file
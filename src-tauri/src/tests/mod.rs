@@ -1 +1,11 @@
-pub mod oshaughnessy_test;
\ No newline at end of file
+pub mod fixtures;
+pub mod api_mock;
+pub mod oshaughnessy_test;
+pub mod atomic_filing_store_test;
+pub mod ps_screening_test;
+pub mod piotroski_screening_test;
+pub mod universe_filter_test;
+pub mod screen_membership_test;
+pub mod graham_screening_test;
+pub mod api_pipeline_test;
+pub mod non_finite_test;
\ No newline at end of file
@@ -0,0 +1,135 @@
+//! Ratio and screening math over statement amounts of any size, including the near-zero
+//! denominators and extreme values that turn a quotient into NaN or infinity. None of those
+//! may reach the serialized output.
+
+use proptest::prelude::*;
+
+use crate::analysis::leverage::{compute_leverage_year, LeverageInputs};
+use crate::analysis::liquidity::liquidity_year;
+use crate::analysis::pe_statistics::{calculate_pe_statistics, calculate_risk_score, calculate_value_score};
+use crate::analysis::quality::{compute_quality_year, QualityInputs};
+use crate::analysis::risk_metrics::{beta, sharpe_ratio, sortino_ratio};
+use crate::safe_f64::{mean_and_std_dev, non_finite_fields};
+
+/// Any finite f64, subnormals and f64::MAX included
+fn finite_f64() -> impl Strategy<Value = f64> {
+    prop::num::f64::POSITIVE | prop::num::f64::NEGATIVE | prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO
+}
+
+/// A stored statement amount: usually company-sized, sometimes a hair from zero or absurdly large
+fn amount() -> impl Strategy<Value = Option<f64>> {
+    prop::option::of(prop_oneof![
+        3 => -1e12..1e12f64,
+        1 => -1e-9..1e-9f64,
+        1 => finite_f64(),
+    ])
+}
+
+/// A P/E, including those from earnings a hair above zero
+fn pe_ratio() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        3 => 0.1..200.0f64,
+        1 => 1e6..f64::MAX,
+    ]
+}
+
+fn leverage_inputs() -> impl Strategy<Value = LeverageInputs> {
+    (amount(), amount(), amount(), amount(), amount(), amount()).prop_map(
+        |(total_debt, total_equity, cash, operating_income, interest_expense, ebitda)| LeverageInputs {
+            fiscal_year: 2024,
+            report_date: "2024-12-31".to_string(),
+            currency: "USD".to_string(),
+            total_debt,
+            total_equity,
+            cash,
+            operating_income,
+            interest_expense,
+            ebitda,
+        },
+    )
+}
+
+fn quality_inputs() -> impl Strategy<Value = QualityInputs> {
+    (
+        (amount(), amount(), amount(), amount()),
+        (amount(), amount(), amount(), amount(), amount(), amount()),
+    )
+        .prop_map(
+            |(
+                (revenue, gross_profit, cost_of_revenue, operating_income),
+                (tax_expense, pretax_income, total_assets, total_debt, total_equity, cash),
+            )| QualityInputs {
+                fiscal_year: 2024,
+                report_date: "2024-12-31".to_string(),
+                revenue,
+                gross_profit,
+                cost_of_revenue,
+                operating_income,
+                tax_expense,
+                pretax_income,
+                total_assets,
+                total_debt,
+                total_equity,
+                cash,
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn prop_leverage_ratios_are_finite_or_explained(inputs in leverage_inputs()) {
+        let year = compute_leverage_year(&inputs);
+        prop_assert!(non_finite_fields(&year).is_empty(), "{:?} from {:?}", non_finite_fields(&year), inputs);
+
+        // Every missing ratio says why, including interest coverage on negligible interest
+        prop_assert_eq!(year.debt_to_equity.is_none(), year.debt_to_equity_note.is_some());
+        prop_assert_eq!(year.net_debt_to_ebitda.is_none(), year.net_debt_to_ebitda_note.is_some());
+        prop_assert_eq!(year.interest_coverage.is_none(), year.interest_coverage_note.is_some());
+    }
+
+    #[test]
+    fn prop_quality_metrics_are_finite(inputs in quality_inputs()) {
+        let year = compute_quality_year(&inputs);
+        prop_assert!(non_finite_fields(&year).is_empty(), "{:?} from {:?}", non_finite_fields(&year), inputs);
+    }
+
+    #[test]
+    fn prop_liquidity_ratios_are_finite(assets in amount(), liabilities in amount(), inventory in amount()) {
+        let year = liquidity_year(2024, "2024-12-31".to_string(), assets, liabilities, inventory);
+        prop_assert!(non_finite_fields(&year).is_empty(), "{:?}", non_finite_fields(&year));
+    }
+
+    #[test]
+    fn prop_pe_statistics_and_scores_are_finite(
+        history in prop::collection::vec(pe_ratio(), 1..300),
+        current in prop::option::of(pe_ratio()),
+    ) {
+        let stats = calculate_pe_statistics(&history);
+        prop_assert!(non_finite_fields(&stats).is_empty(), "{:?}", non_finite_fields(&stats));
+
+        prop_assert!(calculate_value_score(current, &stats).is_finite());
+        prop_assert!(calculate_risk_score(current, &stats).is_finite());
+    }
+
+    #[test]
+    fn prop_sector_spread_gives_finite_z_scores(values in prop::collection::vec(pe_ratio(), 1..50)) {
+        let (mean, std_dev) = mean_and_std_dev(&values).unwrap();
+        prop_assert!(mean.is_finite() && std_dev.is_finite());
+        if std_dev > 0.0 {
+            for value in &values {
+                prop_assert!(((value - mean) / std_dev).is_finite());
+            }
+        }
+    }
+
+    #[test]
+    fn prop_risk_ratios_are_finite(
+        returns in prop::collection::vec(finite_f64(), 0..60),
+        benchmark in prop::collection::vec(finite_f64(), 0..60),
+        risk_free in 0.0..0.1f64,
+    ) {
+        let paired: Vec<(f64, f64)> = returns.iter().copied().zip(benchmark).collect();
+        let ratios = [sharpe_ratio(&returns, risk_free), sortino_ratio(&returns, risk_free), beta(&paired)];
+        prop_assert!(non_finite_fields(&ratios).is_empty(), "{:?}", ratios);
+    }
+}
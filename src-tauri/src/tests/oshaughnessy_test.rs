@@ -6,11 +6,12 @@ async fn test_oshaughnessy_api_basic() {
 
     // Test with empty stock list (should return from database)
     println!("🔍 Calling get_oshaughnessy_screening_results...");
-    let result = get_oshaughnessy_screening_results(vec![], None, Some(5)).await;
+    let result = get_oshaughnessy_screening_results(vec![], None, Some(5), None, None).await;
     println!("🔍 Function call completed, processing result...");
 
     match result {
-        Ok(stocks) => {
+        Ok(screen) => {
+            let stocks = screen.results;
             println!("✅ Success! Got {} stocks", stocks.len());
             if !stocks.is_empty() {
                 println!("📊 First stock: {:?}", stocks[0].symbol);
@@ -35,14 +36,19 @@ async fn test_oshaughnessy_with_criteria() {
         max_ps_ratio: Some(5.0),
         max_evs_ratio: Some(5.0),
         min_market_cap: Some(100_000_000.0),
+        min_avg_daily_volume: None,
         sectors: None,
+        tags: None,
+        min_consecutive_growth_years: None,
+        max_accrual_ratio: None,
         passes_screening_only: Some(false),
     };
 
-    let result = get_oshaughnessy_screening_results(vec![], Some(criteria), Some(10)).await;
+    let result = get_oshaughnessy_screening_results(vec![], Some(criteria), Some(10), None, None).await;
 
     match result {
-        Ok(stocks) => {
+        Ok(screen) => {
+            let stocks = screen.results;
             println!("✅ Success with criteria! Got {} stocks", stocks.len());
             for stock in stocks.iter().take(3) {
                 println!("📊 {}: P/S={:?}, Composite={}, Percentile={}",
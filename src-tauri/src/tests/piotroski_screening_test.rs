@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use crate::commands::piotroski_screening::{get_piotroski_screening_results_internal, piotroski_statistics};
+use crate::tests::fixtures::{day, AnnualFinancials, FixtureBuilder, PriceBar};
+
+/// 2022 and 2023 statements, 2023 improving on 2022 in every F-Score criterion: an F-Score of 9
+fn improving_financials(year: i32) -> AnnualFinancials {
+    // (net income, revenue, debt, current assets, operating cash flow)
+    let (net_income, revenue, debt, current_assets, operating_cash_flow) =
+        if year == 2022 { (50.0, 1000.0, 400.0, 300.0, 40.0) } else { (100.0, 1200.0, 300.0, 400.0, 150.0) };
+    AnnualFinancials {
+        revenue: Some(revenue),
+        net_income: Some(net_income),
+        total_assets: Some(1000.0),
+        total_equity: Some(600.0),
+        total_debt: Some(debt),
+        current_assets: Some(current_assets),
+        current_liabilities: Some(200.0),
+        shares_outstanding: Some(1e8),
+        operating_cash_flow: Some(operating_cash_flow),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_statistics_and_screen_cover_only_the_sp500() {
+    let fixture = FixtureBuilder::new()
+        .with_stock("MEMBER")
+        .with_sp500_flag()
+        .with_prices(day(2024, 1, 1)..=day(2024, 1, 31), |_, _| PriceBar { volume: 2_000_000, ..PriceBar::flat(100.0) })
+        .with_annual_financials(2022..=2023, improving_financials)
+        .with_stock("OUTSIDE")
+        .with_prices(day(2024, 1, 1)..=day(2024, 1, 31), |_, _| PriceBar { volume: 2_000_000, ..PriceBar::flat(100.0) })
+        .with_annual_financials(2022..=2023, improving_financials)
+        .build()
+        .await;
+    let pool = &fixture.pool;
+
+    let results = get_piotroski_screening_results_internal(pool, Vec::new(), None, None).await.unwrap();
+    let symbols: Vec<&str> = results.iter().map(|s| s.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["MEMBER"]);
+    // $10B market cap over $600 of equity
+    let pb_ratio = results[0].pb_ratio.unwrap();
+    assert!((pb_ratio - 1e10 / 600.0).abs() < 1.0);
+
+    let statistics = piotroski_statistics(pool).await.unwrap();
+    assert_eq!(statistics["total_stocks"], 1);
+}
+
+/// 500 S&P 500 stocks with two fiscal years each. Debug-built SQLite is too slow for the
+/// target, so run with `cargo test --release -- --ignored`.
+#[tokio::test]
+#[ignore]
+async fn test_piotroski_screen_of_the_sp500_under_300_milliseconds() {
+    let fixture = FixtureBuilder::new().build().await;
+    let pool = &fixture.pool;
+
+    sqlx::query(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 500)
+         INSERT INTO stocks (id, symbol, company_name, is_sp500) SELECT i, 'S' || i, 'Stock ' || i, 1 FROM n"
+    )
+    .execute(pool).await.unwrap();
+    sqlx::query(
+        "WITH RECURSIVE d(day) AS (SELECT 0 UNION ALL SELECT day + 1 FROM d WHERE day < 249)
+         INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price, volume)
+         SELECT s.id, date('2024-01-01', '+' || d.day || ' days'), 100.0, 100.0, 100.0, 100.0, 2000000
+         FROM stocks s CROSS JOIN d"
+    )
+    .execute(pool).await.unwrap();
+    for year in [2022, 2023] {
+        let financials = improving_financials(year);
+        let report_date = format!("{}-12-31", year);
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income)
+             SELECT id, 'FY', ?1, ?2, ?3, ?4 FROM stocks"
+        )
+        .bind(&report_date).bind(year).bind(financials.revenue).bind(financials.net_income)
+        .execute(pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_assets, total_equity, total_debt,
+                current_assets, current_liabilities, shares_outstanding)
+             SELECT id, 'Annual', ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8 FROM stocks"
+        )
+        .bind(&report_date).bind(year)
+        .bind(financials.total_assets).bind(financials.total_equity).bind(financials.total_debt)
+        .bind(financials.current_assets).bind(financials.current_liabilities).bind(financials.shares_outstanding)
+        .execute(pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, operating_cash_flow)
+             SELECT id, 'Annual', ?1, ?2, ?3 FROM stocks"
+        )
+        .bind(&report_date).bind(year).bind(financials.operating_cash_flow)
+        .execute(pool).await.unwrap();
+    }
+    sqlx::query("ANALYZE").execute(pool).await.unwrap();
+
+    let started = Instant::now();
+    let results = get_piotroski_screening_results_internal(pool, Vec::new(), None, Some(500)).await.unwrap();
+    let elapsed = started.elapsed();
+
+    assert_eq!(results.len(), 500);
+    assert!(elapsed < Duration::from_millis(300), "Piotroski screen took {:?}", elapsed);
+}
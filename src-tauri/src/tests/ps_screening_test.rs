@@ -0,0 +1,123 @@
+use std::time::{Duration, Instant};
+
+use crate::analysis::ps_history::load_revenue_growth;
+use crate::commands::analysis::{
+    get_ps_screening_with_revenue_growth_internal, get_undervalued_stocks_by_ps_internal, PsSortKey,
+};
+use crate::tests::fixtures::{day, FixtureBuilder, ValuationDay};
+use crate::tools::universe_filter::UniverseFilter;
+
+/// $500M market cap floor; these fixtures have no volume data
+const UNIVERSE: UniverseFilter = UniverseFilter { min_market_cap: 5e8, min_avg_daily_volume: 0.0 };
+
+/// `history` daily P/S points followed by one current point, from 2024-01-01
+fn with_ps_history(builder: FixtureBuilder, symbol: &str, history: &[f64], current: f64, market_cap: f64) -> FixtureBuilder {
+    let points: Vec<f64> = history.iter().copied().chain(std::iter::once(current)).collect();
+    let last = day(2024, 1, 1) + chrono::Duration::days(points.len() as i64 - 1);
+    builder.with_stock(symbol).with_valuation_ratios(day(2024, 1, 1)..=last, |i, _| ValuationDay {
+        price: Some(100.0),
+        market_cap: Some(market_cap),
+        ps_ratio_ttm: Some(points[i]),
+        data_completeness_score: 80,
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn test_screeners_flag_stocks_below_their_history() {
+    // Mean 5, median 5, population variance 1
+    let history: Vec<f64> = (0..30).map(|i| if i % 2 == 0 { 4.0 } else { 6.0 }).collect();
+    let mut builder = FixtureBuilder::new();
+    builder = with_ps_history(builder, "CHEAP", &history, 3.0, 2e9);
+    builder = with_ps_history(builder, "FAIR", &history, 5.5, 2e9);
+    builder = with_ps_history(builder, "SMALL", &history, 3.0, 1e8);
+    builder = with_ps_history(builder, "THIN", &history[..5], 1.0, 2e9);
+    let fixture = builder.build().await;
+    let pool = &fixture.pool;
+
+    sqlx::query(
+        "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue) VALUES
+            (1, 'TTM', '2024-06-30', 2024, 110.0),
+            (1, 'TTM', '2023-06-30', 2023, 100.0)"
+    )
+    .execute(pool).await.unwrap();
+
+    let tickers: Vec<String> = ["CHEAP", "FAIR", "SMALL", "THIN"].iter().map(|s| s.to_string()).collect();
+
+    let smart = get_undervalued_stocks_by_ps_internal(pool, &tickers, 50, &UNIVERSE, PsSortKey::PsAscending)
+        .await
+        .unwrap();
+    assert_eq!(smart.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), vec!["CHEAP"]);
+    let cheap = &smart[0];
+    assert_eq!((cheap.historical_mean, cheap.historical_median, cheap.historical_variance), (5.0, 5.0, 1.0));
+    assert_eq!((cheap.historical_min, cheap.historical_max), (4.0, 6.0));
+    assert_eq!(cheap.z_score, -2.0);
+    assert!((cheap.ttm_growth_rate.unwrap() - 10.0).abs() < 1e-9);
+
+    let growth = get_ps_screening_with_revenue_growth_internal(pool, &tickers, 50, &UNIVERSE).await.unwrap();
+    assert_eq!(growth.len(), 1);
+    assert_eq!(growth[0].symbol, "CHEAP");
+    assert_eq!(growth[0].data_points, 30);
+    assert_eq!(growth[0].annual_growth_rate, None);
+
+    // Without revenue growth the second screen drops it
+    sqlx::query("DELETE FROM income_statements").execute(pool).await.unwrap();
+    let growth = get_ps_screening_with_revenue_growth_internal(pool, &tickers, 50, &UNIVERSE).await.unwrap();
+    assert!(growth.is_empty());
+}
+
+#[tokio::test]
+async fn test_annual_revenue_growth_skips_stub_years() {
+    let fixture = FixtureBuilder::new().with_stock("STEADY").with_stock("SHIFT").build().await;
+    let pool = &fixture.pool;
+    // SHIFT moved its fiscal year end from December to June: FY2023 is a six-month stub
+    sqlx::query(
+        "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue) VALUES
+            (1, 'FY', '2021-12-31', 2021, 90.0), (1, 'FY', '2022-12-31', 2022, 100.0), (1, 'FY', '2023-12-31', 2023, 110.0),
+            (2, 'FY', '2021-12-31', 2021, 90.0), (2, 'FY', '2022-12-31', 2022, 100.0), (2, 'FY', '2023-06-30', 2023, 55.0)"
+    )
+    .execute(pool).await.unwrap();
+
+    let growth = load_revenue_growth(pool, &[1, 2]).await.unwrap();
+    assert!((growth[&1].annual_growth_rate.unwrap() - 10.0).abs() < 1e-9);
+    assert_eq!(growth[&1].current_annual_revenue, Some(110.0));
+    assert_eq!(growth[&2].annual_growth_rate, None);
+    assert_eq!(growth[&2].current_annual_revenue, Some(55.0));
+}
+
+/// 500 stocks × 4000 trading days = 2M valuation rows. Debug-built SQLite is too slow for
+/// the target, so run with `cargo test --release -- --ignored`.
+#[tokio::test]
+#[ignore]
+async fn test_screeners_on_two_million_rows_under_one_second() {
+    let fixture = FixtureBuilder::new().build().await;
+    let pool = &fixture.pool;
+
+    sqlx::query(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 500)
+         INSERT INTO stocks (id, symbol, company_name) SELECT i, 'S' || i, 'Stock ' || i FROM n"
+    )
+    .execute(pool).await.unwrap();
+    sqlx::query(
+        "WITH RECURSIVE d(day) AS (SELECT 0 UNION ALL SELECT day + 1 FROM d WHERE day < 3999)
+         INSERT INTO daily_valuation_ratios (stock_id, date, price, market_cap, ps_ratio_ttm, data_completeness_score)
+         SELECT s.id, date('2014-01-01', '+' || d.day || ' days'), 50.0, 1e10,
+                0.5 + ((s.id * 7919 + d.day * 104729) % 1000) / 100.0, 80
+         FROM stocks s CROSS JOIN d"
+    )
+    .execute(pool).await.unwrap();
+    sqlx::query("ANALYZE").execute(pool).await.unwrap();
+
+    let tickers: Vec<String> = (1..=500).map(|i| format!("S{}", i)).collect();
+
+    let started = Instant::now();
+    get_undervalued_stocks_by_ps_internal(pool, &tickers, 50, &UNIVERSE, PsSortKey::PsAscending).await.unwrap();
+    let smart_elapsed = started.elapsed();
+
+    let started = Instant::now();
+    get_ps_screening_with_revenue_growth_internal(pool, &tickers, 50, &UNIVERSE).await.unwrap();
+    let growth_elapsed = started.elapsed();
+
+    assert!(smart_elapsed < Duration::from_secs(1), "smart P/S screen took {:?}", smart_elapsed);
+    assert!(growth_elapsed < Duration::from_secs(1), "revenue growth screen took {:?}", growth_elapsed);
+}
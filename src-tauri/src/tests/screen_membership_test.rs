@@ -0,0 +1,76 @@
+use crate::commands::screen_membership::{get_screen_membership_internal, ScreenMembership, ScreenStatus};
+use crate::error::CommandError;
+use crate::tests::fixtures::{day, AnnualFinancials, Fixture, FixtureBuilder, PriceBar, ValuationDay};
+
+/// Complete data, but an F-Score of 4 is below the default minimum of 7: profitable, cash
+/// flow above earnings and no new shares, worse on everything else
+fn f_score_4_financials(year: i32) -> AnnualFinancials {
+    // (net income, revenue, debt, current assets, operating cash flow)
+    let (net_income, revenue, debt, current_assets, operating_cash_flow) =
+        if year == 2022 { (100.0, 1000.0, 300.0, 400.0, 40.0) } else { (50.0, 900.0, 400.0, 300.0, 150.0) };
+    AnnualFinancials {
+        revenue: Some(revenue),
+        net_income: Some(net_income),
+        total_assets: Some(1000.0),
+        total_equity: Some(1000.0 - debt),
+        total_debt: Some(debt),
+        current_assets: Some(current_assets),
+        current_liabilities: Some(200.0),
+        shares_outstanding: Some(1e8),
+        operating_cash_flow: Some(operating_cash_flow),
+        ..Default::default()
+    }
+}
+
+/// Mean 5, variance 1 over 30 days, then a current P/S of 3
+fn ps_day(index: usize) -> ValuationDay {
+    let ps = if index == 30 { 3.0 } else if index % 2 == 0 { 4.0 } else { 6.0 };
+    ValuationDay {
+        price: Some(100.0),
+        market_cap: Some(1e10),
+        ps_ratio_ttm: Some(ps),
+        data_completeness_score: 90,
+        ..Default::default()
+    }
+}
+
+/// CHEAP, an S&P 500 stock closing at 100 on 1e8 shares, and BLANK with no data at all
+async fn membership_fixture() -> Fixture {
+    FixtureBuilder::new()
+        .with_stock("CHEAP")
+        .with_sp500_flag()
+        .with_prices(day(2024, 1, 1)..=day(2024, 1, 31), |_, _| PriceBar { volume: 2_000_000, ..PriceBar::flat(100.0) })
+        .with_valuation_ratios(day(2024, 1, 1)..=day(2024, 1, 31), |index, _| ps_day(index))
+        .with_annual_financials(2022..=2023, f_score_4_financials)
+        .with_stock("BLANK")
+        .build()
+        .await
+}
+
+#[tokio::test]
+async fn test_membership_passes_ps_fails_the_rest() {
+    let fixture = membership_fixture().await;
+    let pool = &fixture.pool;
+
+    let membership = get_screen_membership_internal(pool, "CHEAP").await.unwrap();
+    assert_eq!(
+        membership.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec!["graham", "oshaughnessy", "piotroski", "ps_undervalued"]
+    );
+    assert_eq!(membership["ps_undervalued"], ScreenMembership { status: ScreenStatus::Pass, score: Some(3.0) });
+    assert_eq!(membership["piotroski"], ScreenMembership { status: ScreenStatus::Fail, score: Some(4.0) });
+    // A $10B company on $900 of revenue and $50 of earnings is far too expensive for either
+    assert_eq!(membership["oshaughnessy"].status, ScreenStatus::Fail);
+    assert!(membership["oshaughnessy"].score.is_some());
+    assert_eq!(membership["graham"].status, ScreenStatus::Fail);
+    assert!(membership["graham"].score.is_some());
+
+    let blank = get_screen_membership_internal(pool, "BLANK").await.unwrap();
+    assert_eq!(blank.len(), 4);
+    assert!(blank.values().all(|m| *m == ScreenMembership { status: ScreenStatus::Unknown, score: None }));
+
+    assert!(matches!(
+        get_screen_membership_internal(pool, "NOPE").await,
+        Err(CommandError::NotFound { .. })
+    ));
+}
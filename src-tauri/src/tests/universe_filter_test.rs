@@ -0,0 +1,173 @@
+use crate::commands::analysis::{
+    get_ps_screening_with_revenue_growth_internal, get_undervalued_stocks_by_ps_internal, PsSortKey,
+};
+use crate::commands::oshaughnessy_screening::{get_oshaughnessy_screening_results_internal, OShaughnessyScreeningCriteria};
+use crate::commands::piotroski_screening::{get_piotroski_screening_results_internal, PiotroskilScreeningCriteria};
+use crate::tests::fixtures::{day, AnnualFinancials, Fixture, FixtureBuilder, PriceBar, ValuationDay};
+use crate::tools::universe_filter::UniverseFilter;
+
+/// Two fiscal years of statements, the latest improving on the prior in every F-Score criterion
+/// but share count: an F-Score of 8. `shares` is the latest year's count, if any.
+fn f_score_8_financials(shares: Option<f64>) -> impl Fn(i32) -> AnnualFinancials {
+    move |year| {
+        // (net income, revenue, assets, debt, current assets, current liabilities, shares, operating cash flow)
+        let (net_income, revenue, assets, debt, current_assets, current_liabilities, shares, operating_cash_flow) = if year == 2022 {
+            (50.0, 1000.0, 1000.0, 400.0, 300.0, 200.0, shares.map(|shares| shares / 1.1), 40.0)
+        } else {
+            (100.0, 1200.0, 1000.0, 300.0, 400.0, 200.0, shares, 150.0)
+        };
+        AnnualFinancials {
+            revenue: Some(revenue),
+            net_income: Some(net_income),
+            total_assets: Some(assets),
+            total_equity: Some(assets - debt),
+            total_debt: Some(debt),
+            current_assets: Some(current_assets),
+            current_liabilities: Some(current_liabilities),
+            shares_outstanding: shares,
+            operating_cash_flow: Some(operating_cash_flow),
+            ..Default::default()
+        }
+    }
+}
+
+/// Mean 5, variance 1 over 30 days, then a current P/S of 3
+fn ps_day(index: usize, market_cap: f64) -> ValuationDay {
+    let ps = if index == 30 { 3.0 } else if index % 2 == 0 { 4.0 } else { 6.0 };
+    ValuationDay {
+        price: Some(100.0),
+        market_cap: Some(market_cap),
+        ps_ratio_ttm: Some(ps),
+        data_completeness_score: 90,
+        ..Default::default()
+    }
+}
+
+/// S&P 500 stocks on the migrated schema, each closing at 100 for 31 days: a large liquid
+/// stock, a tiny-cap stock, a large but illiquid stock, and a liquid stock with no share
+/// count, whose market cap is unknown. Market caps come from close × shares only.
+async fn screening_fixture() -> Fixture {
+    let mut builder = FixtureBuilder::new();
+    // (symbol, latest shares outstanding, daily volume)
+    for (symbol, shares, volume) in [("BIG", Some(1e8), 2_000_000), ("TINY", Some(2e5), 2_000_000), ("ILLIQ", Some(1e8), 5_000), ("NOCAP", None, 2_000_000)] {
+        let market_cap = shares.map_or(1e10, |shares| shares * 100.0);
+        builder = builder
+            .with_stock(symbol)
+            .with_sp500_flag()
+            .with_prices(day(2024, 1, 1)..=day(2024, 1, 31), |_, _| PriceBar { volume, ..PriceBar::flat(100.0) })
+            .with_valuation_ratios(day(2024, 1, 1)..=day(2024, 1, 31), |index, _| ps_day(index, market_cap))
+            .with_annual_financials(2022..=2023, f_score_8_financials(shares));
+    }
+    let fixture = builder.build().await;
+
+    // Growing TTM revenue for the P/S growth screen
+    for stock_id in 1..=4 {
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue) VALUES
+                (?1, 'TTM', '2024-06-30', 2024, 110.0), (?1, 'TTM', '2023-06-30', 2023, 100.0)"
+        )
+        .bind(stock_id)
+        .execute(&fixture.pool).await.unwrap();
+    }
+    fixture
+}
+
+fn sorted_symbols<'a>(rows: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut symbols: Vec<&str> = rows.into_iter().collect();
+    symbols.sort();
+    symbols
+}
+
+#[tokio::test]
+async fn test_tiny_and_illiquid_stocks_are_filtered_from_every_screener() {
+    let fixture = screening_fixture().await;
+    let pool = &fixture.pool;
+    let tickers: Vec<String> = ["BIG", "TINY", "ILLIQ", "NOCAP"].iter().map(|s| s.to_string()).collect();
+    let universe = UniverseFilter::default();
+
+    // A stock without a share count isn't held to the market cap floor; its cap is reported unknown
+    let piotroski = get_piotroski_screening_results_internal(pool, tickers.clone(), None, None).await.unwrap();
+    assert_eq!(sorted_symbols(piotroski.iter().map(|s| s.symbol.as_str())), vec!["BIG", "NOCAP"]);
+    assert_eq!(piotroski.iter().find(|s| s.symbol == "BIG").unwrap().market_cap, Some(1e10));
+    assert_eq!(piotroski.iter().find(|s| s.symbol == "NOCAP").unwrap().market_cap, None);
+
+    // Only the shared floors; NOCAP has no ratios to rank without a share count
+    let floors_only = OShaughnessyScreeningCriteria {
+        max_composite_percentile: None,
+        max_ps_ratio: None,
+        max_evs_ratio: None,
+        min_market_cap: None,
+        passes_screening_only: Some(false),
+        ..OShaughnessyScreeningCriteria::default()
+    };
+    let oshaughnessy = get_oshaughnessy_screening_results_internal(pool, tickers.clone(), Some(floors_only), None).await.unwrap();
+    assert_eq!(sorted_symbols(oshaughnessy.iter().map(|s| s.symbol.as_str())), vec!["BIG"]);
+
+    let smart = get_undervalued_stocks_by_ps_internal(pool, &tickers, 50, &universe, PsSortKey::PsAscending)
+        .await
+        .unwrap();
+    assert_eq!(sorted_symbols(smart.iter().map(|s| s.symbol.as_str())), vec!["BIG", "NOCAP"]);
+
+    let growth = get_ps_screening_with_revenue_growth_internal(pool, &tickers, 50, &universe).await.unwrap();
+    assert_eq!(sorted_symbols(growth.iter().map(|s| s.symbol.as_str())), vec!["BIG", "NOCAP"]);
+
+    // Dropping the floors lets the other two through
+    let open = UniverseFilter { min_market_cap: 0.0, min_avg_daily_volume: 0.0 };
+    let smart = get_undervalued_stocks_by_ps_internal(pool, &tickers, 50, &open, PsSortKey::PsAscending)
+        .await
+        .unwrap();
+    assert_eq!(smart.len(), 4);
+}
+
+#[tokio::test]
+async fn test_market_cap_floor_is_compared_in_usd() {
+    // A EUR-listed company worth EUR 45M: under the $50M floor as a raw number, above it in USD
+    let fixture = FixtureBuilder::new()
+        .with_stock("BIG")
+        .with_sp500_flag()
+        .with_prices(day(2024, 1, 1)..=day(2024, 1, 31), |_, _| PriceBar { volume: 2_000_000, ..PriceBar::flat(100.0) })
+        .with_valuation_ratios(day(2024, 1, 1)..=day(2024, 1, 31), |index, _| ps_day(index, 1e10))
+        .with_annual_financials(2022..=2023, f_score_8_financials(Some(1e8)))
+        .with_stock("EURO")
+        .with_company_name("Euro SE")
+        .with_sp500_flag()
+        .with_prices(day(2024, 1, 1)..=day(2024, 1, 31), |_, _| PriceBar { volume: 2_000_000, ..PriceBar::flat(100.0) })
+        .with_valuation_ratios(day(2024, 1, 1)..=day(2024, 1, 31), |index, _| ps_day(index, 45e6))
+        .with_annual_financials(2022..=2023, f_score_8_financials(Some(4.5e5)))
+        .build()
+        .await;
+    let pool = &fixture.pool;
+    for table in ["daily_prices", "daily_valuation_ratios"] {
+        sqlx::query(&format!("UPDATE {} SET currency = 'EUR' WHERE stock_id = 2", table))
+            .execute(pool).await.unwrap();
+    }
+
+    let tickers: Vec<String> = ["BIG", "EURO"].iter().map(|s| s.to_string()).collect();
+    let universe = UniverseFilter::default();
+    // Without a rate the EUR market cap is unknown: the P/S screen, which compares its own
+    // stored market caps, leaves the stock out while the floor itself doesn't apply
+    let smart = get_undervalued_stocks_by_ps_internal(pool, &tickers, 50, &universe, PsSortKey::PsAscending)
+        .await
+        .unwrap();
+    assert_eq!(sorted_symbols(smart.iter().map(|s| s.symbol.as_str())), vec!["BIG"]);
+    let piotroski = get_piotroski_screening_results_internal(pool, tickers.clone(), None, None).await.unwrap();
+    assert_eq!(piotroski.iter().find(|s| s.symbol == "EURO").unwrap().market_cap, None);
+
+    // 0.8 EUR per USD makes it $56.25M
+    sqlx::query("INSERT INTO fx_rates (currency, date, rate) VALUES ('EUR', '2023-12-29', 0.8)")
+        .execute(pool).await.unwrap();
+
+    let smart = get_undervalued_stocks_by_ps_internal(pool, &tickers, 50, &universe, PsSortKey::PsAscending)
+        .await
+        .unwrap();
+    assert_eq!(sorted_symbols(smart.iter().map(|s| s.symbol.as_str())), vec!["BIG", "EURO"]);
+
+    let piotroski = get_piotroski_screening_results_internal(pool, tickers.clone(), None, None).await.unwrap();
+    let euro_cap = piotroski.iter().find(|s| s.symbol == "EURO").unwrap().market_cap.unwrap();
+    assert!((euro_cap - 56.25e6).abs() < 1.0);
+
+    // Now that its market cap is known, a floor above it excludes it
+    let strict = PiotroskilScreeningCriteria { min_market_cap: Some(60e6), ..PiotroskilScreeningCriteria::default() };
+    let piotroski = get_piotroski_screening_results_internal(pool, tickers, Some(strict), None).await.unwrap();
+    assert_eq!(sorted_symbols(piotroski.iter().map(|s| s.symbol.as_str())), vec!["BIG"]);
+}
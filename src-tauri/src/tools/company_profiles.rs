@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+/// Profile fields a user may override by hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileField {
+    Description,
+    Website,
+    Phone,
+    Headquarters,
+    StateOfIncorporation,
+    SicDescription,
+    Employees,
+}
+
+impl ProfileField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProfileField::Description => "description",
+            ProfileField::Website => "website",
+            ProfileField::Phone => "phone",
+            ProfileField::Headquarters => "headquarters",
+            ProfileField::StateOfIncorporation => "state_of_incorporation",
+            ProfileField::SicDescription => "sic_description",
+            ProfileField::Employees => "employees",
+        }
+    }
+}
+
+/// Company profile with manual overrides applied over the SEC values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CompanyProfile {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub company_name: String,
+    pub sector: Option<String>,
+    pub industry: Option<String>,
+    /// Latest stored market cap
+    pub market_cap: Option<f64>,
+    pub description: Option<String>,
+    pub website: Option<String>,
+    pub phone: Option<String>,
+    /// SEC business address
+    pub headquarters: Option<String>,
+    pub state_of_incorporation: Option<String>,
+    pub sic_code: Option<String>,
+    pub sic_description: Option<String>,
+    /// Not published by SEC; only set by override
+    pub employees: Option<i32>,
+    /// Month (1-12) the latest annual period ended in, by nearest month end
+    pub fiscal_year_end_month: Option<i32>,
+    /// Annual filings on record end in more than one month
+    pub fiscal_year_end_changed: bool,
+    /// Fields whose value comes from a manual override
+    pub overridden_fields: Vec<ProfileField>,
+    pub sec_updated_at: Option<String>,
+}
+
+/// Profile fields read from one SEC Submissions API response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SecCompanyProfile {
+    pub sic_code: Option<String>,
+    pub sic_description: Option<String>,
+    pub state_of_incorporation: Option<String>,
+    pub headquarters: Option<String>,
+    pub phone: Option<String>,
+    pub website: Option<String>,
+    pub description: Option<String>,
+}
+
+/// SEC leaves unknown fields as empty strings
+fn non_empty(json: &serde_json::Value, key: &str) -> Option<String> {
+    json.get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+pub fn parse_sec_company_profile(submissions: &serde_json::Value) -> SecCompanyProfile {
+    // "street1, street2, city, ST zip"
+    let headquarters = submissions.get("addresses").and_then(|a| a.get("business")).and_then(|address| {
+        let region = [non_empty(address, "stateOrCountry"), non_empty(address, "zipCode")]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let parts: Vec<String> = [non_empty(address, "street1"), non_empty(address, "street2"), non_empty(address, "city")]
+            .into_iter()
+            .flatten()
+            .chain((!region.is_empty()).then_some(region))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    });
+
+    SecCompanyProfile {
+        sic_code: non_empty(submissions, "sic"),
+        sic_description: non_empty(submissions, "sicDescription"),
+        state_of_incorporation: non_empty(submissions, "stateOfIncorporation"),
+        headquarters,
+        phone: non_empty(submissions, "phone"),
+        website: non_empty(submissions, "website").or_else(|| non_empty(submissions, "investorWebsite")),
+        description: non_empty(submissions, "description"),
+    }
+}
+
+/// Upsert the SEC profile fields from a submissions response. A field SEC now leaves blank
+/// keeps its stored value; overrides are untouched.
+pub async fn store_sec_company_profile(pool: &SqlitePool, stock_id: i64, submissions: &serde_json::Value) -> Result<()> {
+    let profile = parse_sec_company_profile(submissions);
+
+    sqlx::query(
+        "INSERT INTO company_profiles (stock_id, sic_code, sic_description, state_of_incorporation, headquarters, phone, website, description)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(stock_id) DO UPDATE SET
+            sic_code = COALESCE(excluded.sic_code, sic_code),
+            sic_description = COALESCE(excluded.sic_description, sic_description),
+            state_of_incorporation = COALESCE(excluded.state_of_incorporation, state_of_incorporation),
+            headquarters = COALESCE(excluded.headquarters, headquarters),
+            phone = COALESCE(excluded.phone, phone),
+            website = COALESCE(excluded.website, website),
+            description = COALESCE(excluded.description, description),
+            sec_updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(stock_id)
+    .bind(&profile.sic_code)
+    .bind(&profile.sic_description)
+    .bind(&profile.state_of_incorporation)
+    .bind(&profile.headquarters)
+    .bind(&profile.phone)
+    .bind(&profile.website)
+    .bind(&profile.description)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Set a manual value for a field, or clear it with None so the SEC value shows again
+pub async fn set_company_profile_override(
+    pool: &SqlitePool,
+    stock_id: i64,
+    field: ProfileField,
+    value: Option<&str>,
+) -> Result<()> {
+    match value.map(str::trim) {
+        Some(value) => {
+            sqlx::query(
+                "INSERT INTO company_profile_overrides (stock_id, field, value) VALUES (?, ?, ?)
+                 ON CONFLICT(stock_id, field) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP"
+            )
+            .bind(stock_id)
+            .bind(field.as_str())
+            .bind(value)
+            .execute(pool)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM company_profile_overrides WHERE stock_id = ? AND field = ?")
+                .bind(stock_id)
+                .bind(field.as_str())
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// None when the stock doesn't exist. Description falls back to the stocks table's own.
+pub async fn load_company_profile(pool: &SqlitePool, stock_id: i64) -> Result<Option<CompanyProfile>> {
+    let Some(row) = sqlx::query(
+        "SELECT s.id, s.symbol, s.company_name, s.sector, s.industry, s.description AS stock_description,
+                p.sic_code, p.sic_description, p.state_of_incorporation, p.headquarters, p.phone, p.website,
+                p.description, p.sec_updated_at, f.fiscal_year_end_month,
+                COALESCE(f.fiscal_year_end_changed, 0) AS fiscal_year_end_changed,
+                COALESCE(
+                    (SELECT market_cap FROM daily_valuation_ratios
+                     WHERE stock_id = s.id AND market_cap IS NOT NULL ORDER BY date DESC LIMIT 1),
+                    (SELECT market_cap FROM daily_prices
+                     WHERE stock_id = s.id AND market_cap IS NOT NULL ORDER BY date DESC LIMIT 1)
+                ) AS market_cap
+         FROM stocks s
+         LEFT JOIN company_profiles p ON p.stock_id = s.id
+         LEFT JOIN stock_fiscal_year_ends f ON f.stock_id = s.id
+         WHERE s.id = ?"
+    )
+    .bind(stock_id)
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let overrides: HashMap<String, String> = sqlx::query("SELECT field, value FROM company_profile_overrides WHERE stock_id = ?")
+        .bind(stock_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|r| (r.get("field"), r.get("value")))
+        .collect();
+
+    let mut overridden_fields = Vec::new();
+    let mut resolve = |field: ProfileField, stored: Option<String>| match overrides.get(field.as_str()) {
+        Some(value) => {
+            overridden_fields.push(field);
+            Some(value.clone())
+        }
+        None => stored,
+    };
+
+    let description = resolve(
+        ProfileField::Description,
+        row.get::<Option<String>, _>("description").or(row.get("stock_description")),
+    );
+    let website = resolve(ProfileField::Website, row.get("website"));
+    let phone = resolve(ProfileField::Phone, row.get("phone"));
+    let headquarters = resolve(ProfileField::Headquarters, row.get("headquarters"));
+    let state_of_incorporation = resolve(ProfileField::StateOfIncorporation, row.get("state_of_incorporation"));
+    let sic_description = resolve(ProfileField::SicDescription, row.get("sic_description"));
+    let employees = resolve(ProfileField::Employees, None).and_then(|v| v.parse().ok());
+
+    Ok(Some(CompanyProfile {
+        stock_id: row.get("id"),
+        symbol: row.get("symbol"),
+        company_name: row.get("company_name"),
+        sector: row.get("sector"),
+        industry: row.get("industry"),
+        market_cap: row.get("market_cap"),
+        description,
+        website,
+        phone,
+        headquarters,
+        state_of_incorporation,
+        sic_code: row.get("sic_code"),
+        sic_description,
+        employees,
+        fiscal_year_end_month: row.get("fiscal_year_end_month"),
+        fiscal_year_end_changed: row.get("fiscal_year_end_changed"),
+        overridden_fields,
+        sec_updated_at: row.get("sec_updated_at"),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name, sector) VALUES (1, 'AAPL', 'Apple Inc.', 'Technology')")
+            .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO daily_valuation_ratios (stock_id, date, market_cap) VALUES (1, '2024-06-01', 3.0e12)")
+            .execute(&pool).await.unwrap();
+        pool
+    }
+
+    fn submissions(phone: &str, website: &str) -> serde_json::Value {
+        json!({
+            "cik": "320193",
+            "name": "Apple Inc.",
+            "sic": "3571",
+            "sicDescription": "Electronic Computers",
+            "stateOfIncorporation": "CA",
+            "phone": phone,
+            "website": website,
+            "description": "",
+            "addresses": {
+                "business": { "street1": "ONE APPLE PARK WAY", "street2": null, "city": "CUPERTINO", "stateOrCountry": "CA", "zipCode": "95014" }
+            },
+            "filings": { "recent": {} }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_refresh_updates_sec_fields_and_keeps_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+
+        store_sec_company_profile(&pool, 1, &submissions("(408) 996-1010", "")).await.unwrap();
+        let profile = load_company_profile(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(profile.headquarters.as_deref(), Some("ONE APPLE PARK WAY, CUPERTINO, CA 95014"));
+        assert_eq!((profile.sic_code.as_deref(), profile.website.as_deref()), (Some("3571"), None));
+        assert_eq!((profile.sector.as_deref(), profile.market_cap), (Some("Technology"), Some(3.0e12)));
+
+        set_company_profile_override(&pool, 1, ProfileField::Phone, Some("(408) 555-0100")).await.unwrap();
+        set_company_profile_override(&pool, 1, ProfileField::Employees, Some("161000")).await.unwrap();
+
+        // A later refresh with a new phone and a website
+        store_sec_company_profile(&pool, 1, &submissions("(408) 996-2020", "https://www.apple.com")).await.unwrap();
+        let profile = load_company_profile(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(profile.website.as_deref(), Some("https://www.apple.com"));
+        assert_eq!(profile.phone.as_deref(), Some("(408) 555-0100"));
+        assert_eq!(profile.employees, Some(161000));
+        assert_eq!(profile.overridden_fields, vec![ProfileField::Phone, ProfileField::Employees]);
+
+        // Clearing the override shows the refreshed SEC value; a blank SEC website keeps the stored one
+        set_company_profile_override(&pool, 1, ProfileField::Phone, None).await.unwrap();
+        store_sec_company_profile(&pool, 1, &submissions("(408) 996-2020", "")).await.unwrap();
+        let profile = load_company_profile(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(profile.phone.as_deref(), Some("(408) 996-2020"));
+        assert_eq!(profile.website.as_deref(), Some("https://www.apple.com"));
+
+        assert!(load_company_profile(&pool, 99).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fiscal_year_end_and_change_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (2, 'SHIFT', 'Shift Corp')")
+            .execute(&pool).await.unwrap();
+
+        // AAPL's 52/53-week years end late September or early October; SHIFT moved from June to December
+        for (stock_id, report_date, fiscal_year) in [
+            (1, "2022-09-24", 2022), (1, "2023-09-30", 2023), (1, "2021-09-25", 2021), (1, "2016-10-01", 2016),
+            (2, "2022-06-30", 2022), (2, "2023-12-31", 2023),
+        ] {
+            sqlx::query("INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year) VALUES (?, 'FY', ?, ?)")
+                .bind(stock_id).bind(report_date).bind(fiscal_year)
+                .execute(&pool).await.unwrap();
+        }
+
+        let apple = load_company_profile(&pool, 1).await.unwrap().unwrap();
+        assert_eq!((apple.fiscal_year_end_month, apple.fiscal_year_end_changed), (Some(9), false));
+        let shift = load_company_profile(&pool, 2).await.unwrap().unwrap();
+        assert_eq!((shift.fiscal_year_end_month, shift.fiscal_year_end_changed), (Some(12), true));
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::tools::date_range_calculator::{DateRange, DateRangeCalculator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageGranularity {
+    #[default]
+    Year,
+    Quarter,
+}
+
+impl CoverageGranularity {
+    fn label(self, date: NaiveDate) -> String {
+        match self {
+            CoverageGranularity::Year => date.year().to_string(),
+            CoverageGranularity::Quarter => format!("{}-Q{}", date.year(), (date.month() - 1) / 3 + 1),
+        }
+    }
+}
+
+/// Price coverage of one stock in one period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageFlag {
+    /// Every expected trading day has a price
+    Full,
+    Partial,
+    /// Trading days expected, none stored
+    Missing,
+    /// The period ends before the stock's first trading date
+    NotListed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StockCoverageRow {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// One flag per entry of `CoverageHeatmap::periods`
+    pub cells: Vec<CoverageFlag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PeriodCoverageTotals {
+    pub period: String,
+    pub full: i32,
+    pub partial: i32,
+    pub missing: i32,
+    pub not_listed: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CoverageHeatmap {
+    pub target_date: String,
+    pub granularity: CoverageGranularity,
+    /// Column labels, oldest first: "2023" or "2023-Q2"
+    pub periods: Vec<String>,
+    pub rows: Vec<StockCoverageRow>,
+    /// Per-period counts across all rows, aligned with `periods`
+    pub totals: Vec<PeriodCoverageTotals>,
+}
+
+/// Daily price coverage of each S&P 500 stock per period, from the earliest stored price up to
+/// `target_date`. A stock is expected to trade from its first trading date (its earliest stored
+/// price when that is unknown); expected days are the missing-ranges calculator's trading days.
+pub async fn compute_coverage_heatmap(
+    pool: &SqlitePool,
+    target_date: NaiveDate,
+    granularity: CoverageGranularity,
+) -> Result<CoverageHeatmap> {
+    let stocks = sqlx::query(
+        "SELECT s.id, s.symbol,
+                COALESCE(s.first_trading_date, (SELECT MIN(dp.date) FROM daily_prices dp WHERE dp.stock_id = s.id)) AS listed_from
+         FROM stocks s
+         INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol
+         ORDER BY s.symbol"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut listed_from: HashMap<i64, NaiveDate> = HashMap::new();
+    for row in &stocks {
+        if let Some(date) = row.get::<Option<String>, _>("listed_from") {
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| anyhow!("Invalid date {}: {}", date, e))?;
+            listed_from.insert(row.get("id"), date);
+        }
+    }
+
+    let Some(start) = listed_from.values().min().copied().filter(|start| *start <= target_date) else {
+        return Ok(CoverageHeatmap {
+            target_date: target_date.to_string(),
+            granularity,
+            periods: Vec::new(),
+            rows: Vec::new(),
+            totals: Vec::new(),
+        });
+    };
+
+    let calculator = DateRangeCalculator::new();
+    let range = DateRange { start_date: start, end_date: target_date };
+
+    // Expected trading days per period over the whole window; each stock's are a subset
+    let mut expected: BTreeMap<String, Vec<NaiveDate>> = BTreeMap::new();
+    for day in calculator.generate_trading_days(start, target_date) {
+        expected.entry(granularity.label(day)).or_default().push(day);
+    }
+    let periods: Vec<String> = expected.keys().cloned().collect();
+
+    let mut stored: HashMap<i64, HashSet<NaiveDate>> = HashMap::new();
+    let prices = sqlx::query(
+        "SELECT dp.stock_id, dp.date FROM daily_prices dp
+         INNER JOIN stocks s ON s.id = dp.stock_id
+         INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol
+         WHERE dp.date BETWEEN ? AND ?"
+    )
+    .bind(start.to_string())
+    .bind(target_date.to_string())
+    .fetch_all(pool)
+    .await?;
+    for row in prices {
+        if let Ok(date) = NaiveDate::parse_from_str(&row.get::<String, _>("date"), "%Y-%m-%d") {
+            stored.entry(row.get("stock_id")).or_default().insert(date);
+        }
+    }
+
+    let empty = HashSet::new();
+    let rows: Vec<StockCoverageRow> = stocks
+        .iter()
+        .map(|row| {
+            let stock_id: i64 = row.get("id");
+            let listed = listed_from.get(&stock_id).copied().unwrap_or(start);
+            let existing = stored.get(&stock_id).unwrap_or(&empty);
+
+            let stock_range = DateRange { start_date: listed.max(range.start_date), end_date: range.end_date };
+            let mut missing_by_period: HashMap<String, usize> = HashMap::new();
+            for day in calculator.find_missing_dates(existing, &stock_range) {
+                *missing_by_period.entry(granularity.label(day)).or_default() += 1;
+            }
+
+            let cells = expected
+                .iter()
+                .map(|(period, days)| {
+                    let expected_days = days.iter().filter(|day| **day >= listed).count();
+                    let missing = missing_by_period.get(period).copied().unwrap_or(0);
+                    if expected_days == 0 {
+                        CoverageFlag::NotListed
+                    } else if missing == 0 {
+                        CoverageFlag::Full
+                    } else if missing == expected_days {
+                        CoverageFlag::Missing
+                    } else {
+                        CoverageFlag::Partial
+                    }
+                })
+                .collect();
+
+            StockCoverageRow { stock_id, symbol: row.get("symbol"), cells }
+        })
+        .collect();
+
+    let totals = periods
+        .iter()
+        .enumerate()
+        .map(|(i, period)| {
+            let count = |flag: CoverageFlag| rows.iter().filter(|row| row.cells[i] == flag).count() as i32;
+            PeriodCoverageTotals {
+                period: period.clone(),
+                full: count(CoverageFlag::Full),
+                partial: count(CoverageFlag::Partial),
+                missing: count(CoverageFlag::Missing),
+                not_listed: count(CoverageFlag::NotListed),
+            }
+        })
+        .collect();
+
+    Ok(CoverageHeatmap {
+        target_date: target_date.to_string(),
+        granularity,
+        periods,
+        rows,
+        totals,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn insert_prices(pool: &SqlitePool, stock_id: i64, start: &str, end: &str) {
+        let calculator = DateRangeCalculator::new();
+        let parse = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        for day in calculator.generate_trading_days(parse(start), parse(end)) {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?, ?, 1.0, 1.0, 1.0, 1.0)"
+            )
+            .bind(stock_id)
+            .bind(day.to_string())
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_per_period_flags_and_totals() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        // FULL has every day since 2022; GAPPY lost 2023 H2; NEWCO listed in mid-2023
+        for (id, symbol, first_trading_date) in [(1, "FULL", None), (2, "GAPPY", None), (3, "NEWCO", Some("2023-07-03"))] {
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500, first_trading_date) VALUES (?, ?, ?, 1, ?)")
+                .bind(id).bind(symbol).bind(symbol).bind(first_trading_date)
+                .execute(&pool).await.unwrap();
+        }
+        insert_prices(&pool, 1, "2022-01-03", "2024-06-28").await;
+        insert_prices(&pool, 2, "2022-01-03", "2023-06-30").await;
+        insert_prices(&pool, 2, "2024-01-02", "2024-06-28").await;
+        insert_prices(&pool, 3, "2023-07-03", "2024-03-28").await;
+
+        let target = NaiveDate::from_ymd_opt(2024, 6, 28).unwrap();
+        let yearly = compute_coverage_heatmap(&pool, target, CoverageGranularity::Year).await.unwrap();
+        assert_eq!(yearly.periods, vec!["2022", "2023", "2024"]);
+
+        use CoverageFlag::*;
+        let cells: HashMap<&str, Vec<CoverageFlag>> = yearly.rows.iter().map(|r| (r.symbol.as_str(), r.cells.clone())).collect();
+        assert_eq!(cells["FULL"], vec![Full, Full, Full]);
+        assert_eq!(cells["GAPPY"], vec![Full, Partial, Full]);
+        // Not expected before listing, so 2023 counts as full; prices stop at the end of March
+        assert_eq!(cells["NEWCO"], vec![NotListed, Full, Partial]);
+
+        let totals: Vec<(i32, i32, i32, i32)> = yearly.totals.iter().map(|t| (t.full, t.partial, t.missing, t.not_listed)).collect();
+        assert_eq!(totals, vec![(2, 0, 0, 1), (2, 1, 0, 0), (2, 1, 0, 0)]);
+
+        let quarterly = compute_coverage_heatmap(&pool, target, CoverageGranularity::Quarter).await.unwrap();
+        assert_eq!(quarterly.periods.len(), 10);
+        let q3_2023 = quarterly.periods.iter().position(|p| p == "2023-Q3").unwrap();
+        assert_eq!(quarterly.totals[q3_2023], PeriodCoverageTotals {
+            period: "2023-Q3".to_string(),
+            full: 2,
+            partial: 0,
+            missing: 1,
+            not_listed: 0,
+        });
+        let q2_2024 = quarterly.periods.iter().position(|p| p == "2024-Q2").unwrap();
+        assert_eq!((quarterly.totals[q2_2024].full, quarterly.totals[q2_2024].missing), (2, 1));
+    }
+}
@@ -3,21 +3,32 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, Row};
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 use tokio::process::Command;
 use tokio::time::sleep;
 use std::time::Duration as StdDuration;
+use tracing::{error, info, info_span, warn, Instrument};
+use ts_rs::TS;
 use uuid::Uuid;
 
 use crate::tools::freshness_checker::DataStatusReader;
-use crate::tools::freshness_types::{
-    SystemFreshnessReport, DataFreshnessStatus, FreshnessStatus,
-    RefreshPriority, DataSummary, ScreeningReadiness
-};
+use crate::tools::freshness_types::SystemFreshnessReport;
+use crate::tools::fx_rates::{update_fx_rates, FRANKFURTER_BASE_URL};
+use crate::tools::notifications::{default_notifier, dispatch_notification, Notification, NotificationKind, Notifier};
 use crate::tools::date_range_calculator::DateRangeCalculator;
+use crate::tools::query_cache::global_query_cache;
+use crate::tools::refresh_estimates::record_refresh_throughput;
+use crate::tools::ratio_recompute::recompute_for_stocks;
+use crate::tools::filing_reactions::backfill_filing_reactions;
+use crate::tools::refresh_runs::{finish_refresh_run, start_refresh_run, RefreshRunError, RefreshRunStats};
 // use crate::tools::sec_edgar_client::SecEdgarClient; // removed; unified path uses DataStatusReader
+use crate::analysis::benchmark::update_composite_benchmarks;
+use crate::analysis::price_range::refresh_52_week_range;
 use crate::api::schwab_client::SchwabClient;
 use crate::api::StockDataProvider;
-use crate::models::Config;
+use crate::database::helpers::ensure_writable;
+use crate::error::CommandError;
+use crate::models::{validate_ohlc, Config, PriceFrequency};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, clap::ValueEnum)]
 pub enum RefreshMode {
@@ -48,6 +59,8 @@ pub struct RefreshResult {
     pub sources_refreshed: Vec<String>,
     pub sources_failed: Vec<String>,
     pub total_records_processed: i64,
+    /// Stocks that received new or updated statements during this run, whose ratios were recomputed
+    pub updated_stock_ids: Vec<i64>,
     pub error_message: Option<String>,
     pub recommendations: Vec<String>,
 }
@@ -77,12 +90,182 @@ pub struct RefreshProgress {
     pub error_details: Option<String>,
 }
 
+/// Tauri event carrying RefreshProgressEvent payloads
+pub const REFRESH_PROGRESS_EVENT: &str = "refresh-progress";
+
+/// Pushed to subscribers whenever a refresh's progress changes, so the UI need not poll
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RefreshProgressEvent {
+    pub session_id: String,
+    /// Step or phase being worked on
+    pub stage: String,
+    /// Whole run, 0-100; never decreases within a run
+    pub percent: f64,
+    /// Stock being processed, during per-stock phases
+    pub current_symbol: Option<String>,
+    /// Projected from the time taken so far; None until there is progress to project from
+    pub eta_seconds: Option<i64>,
+}
+
+/// Receives progress as a refresh runs
+pub trait RefreshProgressSink: Send + Sync {
+    fn send_progress(&self, event: &RefreshProgressEvent);
+}
+
+impl RefreshProgressSink for tauri::AppHandle {
+    fn send_progress(&self, event: &RefreshProgressEvent) {
+        use tauri::Emitter;
+        if let Err(e) = self.emit(REFRESH_PROGRESS_EVENT, event) {
+            warn!("Failed to emit refresh progress: {}", e);
+        }
+    }
+}
+
+/// Sink given to every manager created after it is set; the app sets its handle at startup
+static DEFAULT_PROGRESS_SINK: OnceLock<Arc<dyn RefreshProgressSink>> = OnceLock::new();
+
+pub fn set_default_progress_sink(sink: Arc<dyn RefreshProgressSink>) {
+    if DEFAULT_PROGRESS_SINK.set(sink).is_err() {
+        warn!("Refresh progress sink already set");
+    }
+}
+
+/// The sink set at startup, for sinks that pass events on to it
+pub fn default_progress_sink() -> Option<Arc<dyn RefreshProgressSink>> {
+    DEFAULT_PROGRESS_SINK.get().cloned()
+}
+
+/// Where the run in progress stands, for computing overall percent and ETA
+#[derive(Debug, Clone)]
+struct ProgressState {
+    started_at: DateTime<Utc>,
+    total_steps: i32,
+    step_number: i32,
+    percent: f64,
+}
+
+/// A refresh lock claim not renewed for this long was abandoned by a process that died, and
+/// may be taken over
+const REFRESH_LOCK_LEASE_SECONDS: i64 = 300;
+
+/// How often a held refresh lock renews its lease
+const REFRESH_LOCK_RENEW_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// Whether a refresh or database maintenance holds the refresh lock, in this or another process
+pub async fn is_refresh_running(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM refresh_lock WHERE expires_at > datetime('now'))")
+        .fetch_one(pool)
+        .await
+}
+
+/// The database's refresh lock, held for the length of a refresh or of database maintenance.
+/// Its lease is renewed in the background while held; `release` gives it up, and dropping it
+/// releases it in the background.
+pub(crate) struct RefreshRunningGuard {
+    pool: SqlitePool,
+    /// Empty once released
+    holder: String,
+    renewal: tokio::task::JoinHandle<()>,
+}
+
+impl RefreshRunningGuard {
+    /// Claim the lock for `purpose`, taking over an expired claim. Fails with DatabaseBusy while
+    /// another holder's lease is live.
+    pub(crate) async fn acquire(pool: &SqlitePool, purpose: &str) -> Result<Self, CommandError> {
+        let holder = format!("{} (pid {}, {})", purpose, std::process::id(), Uuid::new_v4());
+        let lease = format!("+{} seconds", REFRESH_LOCK_LEASE_SECONDS);
+        let claimed = sqlx::query(
+            "INSERT INTO refresh_lock (id, holder, acquired_at, expires_at)
+             VALUES (1, ?1, datetime('now'), datetime('now', ?2))
+             ON CONFLICT(id) DO UPDATE SET
+                holder = excluded.holder,
+                acquired_at = excluded.acquired_at,
+                expires_at = excluded.expires_at
+             WHERE refresh_lock.expires_at <= datetime('now')"
+        )
+        .bind(&holder)
+        .bind(&lease)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if claimed == 0 {
+            return Err(CommandError::DatabaseBusy { message: "A data refresh is already running".to_string() });
+        }
+
+        let renewal = tokio::spawn({
+            let pool = pool.clone();
+            let holder = holder.clone();
+            async move {
+                let mut interval = tokio::time::interval(REFRESH_LOCK_RENEW_INTERVAL);
+                // The first tick completes immediately; the claim was just made
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    let renewed = sqlx::query("UPDATE refresh_lock SET expires_at = datetime('now', ?) WHERE id = 1 AND holder = ?")
+                        .bind(&lease)
+                        .bind(&holder)
+                        .execute(&pool)
+                        .await;
+                    if let Err(e) = renewed {
+                        warn!("Failed to renew the refresh lock: {}", e);
+                    }
+                }
+            }
+        });
+
+        Ok(Self { pool: pool.clone(), holder, renewal })
+    }
+
+    /// Give up the lock now
+    pub(crate) async fn release(mut self) {
+        self.renewal.abort();
+        let holder = std::mem::take(&mut self.holder);
+        if let Err(e) = release_refresh_lock(&self.pool, &holder).await {
+            warn!("Failed to release the refresh lock; it expires on its own: {}", e);
+        }
+    }
+}
+
+impl Drop for RefreshRunningGuard {
+    fn drop(&mut self) {
+        self.renewal.abort();
+        if self.holder.is_empty() {
+            return;
+        }
+        // Without a runtime to release it on, the lease expires instead
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let pool = self.pool.clone();
+            let holder = std::mem::take(&mut self.holder);
+            runtime.spawn(async move {
+                if let Err(e) = release_refresh_lock(&pool, &holder).await {
+                    warn!("Failed to release the refresh lock; it expires on its own: {}", e);
+                }
+            });
+        }
+    }
+}
+
+async fn release_refresh_lock(pool: &SqlitePool, holder: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM refresh_lock WHERE id = 1 AND holder = ?")
+        .bind(holder)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
 pub struct DataRefreshManager {
     pool: SqlitePool,
     status_reader: DataStatusReader,
     #[allow(dead_code)]
     date_calculator: DateRangeCalculator,
     refresh_steps: HashMap<RefreshMode, Vec<RefreshStep>>,
+    /// Totals and per-stock errors of the run in progress, saved to refresh_runs when it ends
+    run_stats: std::sync::Mutex<RefreshRunStats>,
+    progress_sink: Option<Arc<dyn RefreshProgressSink>>,
+    /// Told when a run completes or fails, subject to the notification preferences
+    notifier: Option<Arc<dyn Notifier>>,
+    progress_state: std::sync::Mutex<ProgressState>,
 }
 
 impl DataRefreshManager {
@@ -99,27 +282,82 @@ impl DataRefreshManager {
             status_reader,
             date_calculator,
             refresh_steps,
+            run_stats: std::sync::Mutex::new(RefreshRunStats::default()),
+            progress_sink: DEFAULT_PROGRESS_SINK.get().cloned(),
+            notifier: default_notifier(),
+            progress_state: std::sync::Mutex::new(ProgressState {
+                started_at: Utc::now(),
+                total_steps: 0,
+                step_number: 0,
+                percent: 0.0,
+            }),
         })
     }
 
-    /// Execute a data refresh operation based on the request
+    /// Send progress events to `sink` instead of the app's default
+    pub fn with_progress_sink(mut self, sink: Arc<dyn RefreshProgressSink>) -> Self {
+        self.progress_sink = Some(sink);
+        self
+    }
+
+    /// Report run outcomes to `notifier` instead of the app's default
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Execute a data refresh operation based on the request. Fails with DatabaseBusy while
+    /// another refresh or database maintenance holds the refresh lock, in any process.
     pub async fn execute_refresh(&self, request: RefreshRequest) -> Result<RefreshResult> {
+        ensure_writable(&self.pool).await?;
+        let running = RefreshRunningGuard::acquire(&self.pool, "data refresh").await?;
+        let result = self.execute_refresh_locked(request).await;
+        running.release().await;
+        result
+    }
+
+    async fn execute_refresh_locked(&self, request: RefreshRequest) -> Result<RefreshResult> {
         let session_id = request.session_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
         let _start_time = Utc::now();
 
-        println!("🚀 Starting data refresh session: {}", session_id);
-        println!("🎯 Mode: {:?} | Initiated by: {}", request.mode, request.initiated_by);
+        info!("Starting data refresh session: {}", session_id);
+        info!("Mode: {:?} | Initiated by: {}", request.mode, request.initiated_by);
 
         // Create progress tracking record
         self.create_progress_record(&session_id, &request).await?;
 
-        let result = match self.execute_refresh_internal(session_id.clone(), request.clone()).await {
+        // Start the run history record
+        let requested_sources: Vec<String> = self.refresh_steps.get(&request.mode)
+            .map(|steps| steps.iter().map(|step| step.data_source.clone()).collect())
+            .unwrap_or_default();
+        *self.run_stats.lock().unwrap_or_else(|e| e.into_inner()) = RefreshRunStats::default();
+        *self.progress_state.lock().unwrap_or_else(|e| e.into_inner()) = ProgressState {
+            started_at: Utc::now(),
+            total_steps: requested_sources.len() as i32 + 2, // +2 for start/finish
+            step_number: 0,
+            percent: 0.0,
+        };
+        start_refresh_run(
+            &self.pool,
+            &session_id,
+            &format!("{:?}", request.mode),
+            &requested_sources,
+            &request.initiated_by,
+            Utc::now(),
+        ).await?;
+
+        let session_span = info_span!("refresh_session", session_id = %session_id, mode = ?request.mode);
+        let result = match self.execute_refresh_internal(session_id.clone(), request.clone()).instrument(session_span).await {
             Ok(result) => {
                 self.mark_progress_complete(&session_id, true, None).await?;
+                self.finish_run_record(&session_id, None).await?;
+                self.notify_run_outcome(&request.mode, None).await;
                 result
             }
             Err(e) => {
                 self.mark_progress_complete(&session_id, false, Some(e.to_string())).await?;
+                self.finish_run_record(&session_id, Some(&e.to_string())).await?;
+                self.notify_run_outcome(&request.mode, Some(&e.to_string())).await;
                 return Err(e);
             }
         };
@@ -127,16 +365,92 @@ impl DataRefreshManager {
         Ok(result)
     }
 
+    /// Notify that the run completed, with its totals, or failed, with the first line of the error.
+    /// A notification that can't be sent doesn't fail the run.
+    async fn notify_run_outcome(&self, mode: &RefreshMode, error_message: Option<&str>) {
+        let Some(notifier) = &self.notifier else { return };
+
+        let notification = match error_message {
+            None => {
+                let stats = self.run_stats.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                Notification {
+                    kind: NotificationKind::RefreshCompleted,
+                    title: format!("{:?} refresh completed", mode),
+                    body: format!(
+                        "{} stocks, {} prices, {} filings, {} errors",
+                        stats.stocks_processed, stats.prices_inserted, stats.filings_stored, stats.errors.len()
+                    ),
+                }
+            }
+            Some(message) => Notification {
+                kind: NotificationKind::RefreshFailed,
+                title: format!("{:?} refresh failed", mode),
+                body: message.lines().next().unwrap_or_default().chars().take(200).collect(),
+            },
+        };
+        if let Err(e) = dispatch_notification(&self.pool, notifier.as_ref(), notification).await {
+            warn!("Failed to send refresh notification: {}", e);
+        }
+    }
+
+    /// Save the run's end time, totals and per-stock errors to the run history
+    async fn finish_run_record(&self, session_id: &str, error_message: Option<&str>) -> Result<()> {
+        let stats = self.run_stats.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let result = finish_refresh_run(&self.pool, session_id, Utc::now(), &stats, error_message).await;
+        // Even a failed run may have written some data
+        global_query_cache().invalidate_all();
+        result
+    }
+
+    fn record_run_stats(&self, update: impl FnOnce(&mut RefreshRunStats)) {
+        update(&mut self.run_stats.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+
+    /// Send the run's overall progress to the sink. `step_number` None stays on the current step;
+    /// `step_progress` is 0-100 within the step.
+    fn publish_progress(&self, session_id: &str, step_number: Option<i32>, stage: &str, step_progress: f64, current_symbol: Option<&str>) {
+        let Some(sink) = &self.progress_sink else { return };
+
+        let event = {
+            let mut state = self.progress_state.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(step_number) = step_number {
+                state.step_number = step_number;
+            }
+            let total_steps = state.total_steps.max(state.step_number).max(1);
+            let completed = (state.step_number - 1).max(0) as f64 + step_progress.clamp(0.0, 100.0) / 100.0;
+            state.percent = state.percent.max((completed / total_steps as f64 * 100.0).min(100.0));
+
+            let elapsed_seconds = Utc::now().signed_duration_since(state.started_at).num_milliseconds() as f64 / 1000.0;
+            let eta_seconds = if state.percent >= 100.0 {
+                Some(0)
+            } else if state.percent > 0.0 {
+                Some((elapsed_seconds * (100.0 - state.percent) / state.percent).round() as i64)
+            } else {
+                None
+            };
+
+            RefreshProgressEvent {
+                session_id: session_id.to_string(),
+                stage: stage.to_string(),
+                percent: state.percent,
+                current_symbol: current_symbol.map(str::to_string),
+                eta_seconds,
+            }
+        };
+        sink.send_progress(&event);
+    }
+
     async fn execute_refresh_internal(&self, session_id: String, request: RefreshRequest) -> Result<RefreshResult> {
         let start_time = Utc::now();
         let mut sources_refreshed = Vec::new();
         let mut sources_failed = Vec::new();
         let mut total_records_processed = 0i64;
+        let mut updated_stock_ids: Vec<i64> = Vec::new();
 
         // 1. Check current freshness status (skip if filtering by ticker)
         let refresh_plan = if request.only_cik.is_some() {
             // Skip freshness check when filtering - just create plan based on request mode
-            println!("🎯 Skipping freshness check (filtered by ticker)");
+            info!("Skipping freshness check (filtered by ticker)");
             match request.mode {
                 RefreshMode::Financials => vec![RefreshStep {
                     name: "Refresh financial statements".to_string(),
@@ -174,9 +488,8 @@ impl DataRefreshManager {
                 ],
             }
         } else {
-            // Skip freshness check entirely - just execute the requested mode
-            // The freshness checker actually downloads data, which we don't want for mode filtering
-            println!("🔍 Preparing refresh for {:?} mode...", request.mode);
+            // Execute the requested mode as-is rather than planning from freshness
+            info!("Preparing refresh for {:?} mode...", request.mode);
             self.update_progress(&session_id, 1, "Preparing refresh", 100.0).await?;
 
             // Get the steps for the requested mode without checking freshness
@@ -184,10 +497,10 @@ impl DataRefreshManager {
                 .ok_or_else(|| anyhow!("Unknown refresh mode: {:?}", request.mode))?
                 .clone();
 
-            println!("📋 Refresh plan: {} steps for {:?} mode", plan.len(), request.mode);
+            info!("Refresh plan: {} steps for {:?} mode", plan.len(), request.mode);
 
             if plan.is_empty() {
-                println!("✅ All data is current, no refresh needed");
+                info!("All data is current, no refresh needed");
                 return Ok(RefreshResult {
                     session_id,
                     success: true,
@@ -197,6 +510,7 @@ impl DataRefreshManager {
                     sources_refreshed: vec!["none (all current)".to_string()],
                     sources_failed: Vec::new(),
                     total_records_processed: 0,
+                    updated_stock_ids: Vec::new(),
                     error_message: None,
                     recommendations: vec!["All data sources are current".to_string()],
                 });
@@ -211,109 +525,61 @@ impl DataRefreshManager {
 
         for (step_index, step) in refresh_plan.iter().enumerate() {
             let step_number = step_index as i32 + 2; // +1 for zero-index, +1 for initial check
-            println!("🔄 Step {}/{}: {}", step_number, total_steps, step.name);
+            info!("Step {}/{}: {}", step_number, total_steps, step.name);
 
             self.update_progress(&session_id, step_number, &step.name, 0.0).await?;
+            let step_start = Utc::now();
 
-            match self.execute_refresh_step(step, &session_id, request.only_cik.as_ref()).await {
+            let step_span = info_span!("refresh_step", phase = %step.data_source);
+            match self.execute_refresh_step(step, &session_id, request.only_cik.as_ref()).instrument(step_span).await {
                 Ok(records) => {
                     sources_refreshed.push(step.data_source.clone());
                     total_records_processed += records;
                     self.update_refresh_status(&step.data_source, true, Some(records), None).await?;
                     self.update_progress(&session_id, step_number, &step.name, 100.0).await?;
-                    println!("✅ {} completed successfully ({} records)", step.name, records);
+                    info!(records, "{} completed successfully ({} records)", step.name, records);
+
+                    // Screening ratios are derived from statements, so only these stocks need recomputing
+                    if step.data_source == "financial_statements" {
+                        updated_stock_ids = stocks_with_new_statements(&self.pool, step_start).await?;
+                        let rows = recompute_for_stocks(&self.pool, &updated_stock_ids).await?;
+                        let phase = format!("Ratios recomputed for {} stocks ({} rows)", updated_stock_ids.len(), rows);
+                        self.update_progress(&session_id, step_number, &phase, 100.0).await?;
+                        info!("{}", phase);
+                    }
                 }
                 Err(e) => {
                     sources_failed.push(step.data_source.clone());
                     self.update_refresh_status(&step.data_source, false, None, Some(e.to_string())).await?;
-                    println!("❌ {} failed: {}", step.name, e);
+                    error!("{} failed: {}", step.name, e);
+
+                    // Expired Schwab credentials need re-authentication; keep the typed error for the UI
+                    if is_token_expired(&e) {
+                        return Err(e);
+                    }
 
                     // For critical steps, abort the entire refresh
                     if step.priority <= 2 {
-                        println!("🔥 Critical step failed - aborting entire refresh");
+                        error!("Critical step failed - aborting entire refresh");
                         return Err(anyhow!("Critical refresh step '{}' failed: {}", step.name, e));
                     }
                     // For non-critical steps, continue but log the failure
-                    println!("⚠️ Non-critical step failed - continuing with remaining steps");
+                    warn!("Non-critical step failed - continuing with remaining steps");
                 }
             }
         }
 
-        // 4. Final verification and cleanup
-        // IMPORTANT: Skip the final freshness check entirely when running in specific modes
-        // check_system_freshness() actually downloads financial data, which we DON'T want
-        // when the user explicitly requested only market data refresh
+        // 4. Final verification: the freshness check only reads stored data, so it is cheap to run here
         self.update_progress(&session_id, total_steps, "Finalizing refresh", 100.0).await?;
-        println!("✅ Skipping final freshness check (targeted refresh mode)");
-
-        let final_report = SystemFreshnessReport {
-            overall_status: FreshnessStatus::Current,
-            market_data: DataFreshnessStatus {
-                data_source: "daily_prices".to_string(),
-                status: FreshnessStatus::Current,
-                latest_data_date: None,
-                last_refresh: None,
-                staleness_days: None,
-                records_count: if request.mode == RefreshMode::Market { total_records_processed } else { 0 },
-                message: format!("{:?} refresh completed: {} records", request.mode, total_records_processed),
-                refresh_priority: RefreshPriority::Low,
-                data_summary: DataSummary {
-                    date_range: None,
-                    stock_count: None,
-                    data_types: vec![],
-                    key_metrics: vec![],
-                    completeness_score: None,
-                },
-            },
-            financial_data: DataFreshnessStatus {
-                data_source: "financial_statements".to_string(),
-                status: FreshnessStatus::Current,
-                latest_data_date: None,
-                last_refresh: None,
-                staleness_days: None,
-                records_count: if request.mode == RefreshMode::Financials { total_records_processed } else { 0 },
-                message: format!("{:?} refresh completed: {} records", request.mode, total_records_processed),
-                refresh_priority: RefreshPriority::Low,
-                data_summary: DataSummary {
-                    date_range: None,
-                    stock_count: None,
-                    data_types: vec![],
-                    key_metrics: vec![],
-                    completeness_score: None,
-                },
-            },
-            calculated_ratios: DataFreshnessStatus {
-                data_source: "calculated_ratios".to_string(),
-                status: FreshnessStatus::Current,
-                latest_data_date: None,
-                last_refresh: None,
-                staleness_days: None,
-                records_count: 0,
-                message: "Skipped (targeted refresh)".to_string(),
-                refresh_priority: RefreshPriority::Low,
-                data_summary: DataSummary {
-                    date_range: None,
-                    stock_count: None,
-                    data_types: vec![],
-                    key_metrics: vec![],
-                    completeness_score: None,
-                },
-            },
-            recommendations: vec![],
-            screening_readiness: ScreeningReadiness {
-                valuation_analysis: true,
-                blocking_issues: vec![],
-            },
-            last_check: chrono::Utc::now().to_rfc3339(),
-        };
+        let final_report = self.status_reader.check_system_freshness().await?;
 
         let end_time = Utc::now();
         let duration_seconds = end_time.signed_duration_since(start_time).num_seconds();
 
-        println!("🎉 Refresh session completed in {} seconds", duration_seconds);
-        println!("✅ Refreshed: {}", sources_refreshed.join(", "));
+        info!("Refresh session completed in {} seconds", duration_seconds);
+        info!("Refreshed: {}", sources_refreshed.join(", "));
         if !sources_failed.is_empty() {
-            println!("❌ Failed: {}", sources_failed.join(", "));
+            error!("Failed: {}", sources_failed.join(", "));
         }
 
         Ok(RefreshResult {
@@ -325,6 +591,7 @@ impl DataRefreshManager {
             sources_refreshed,
             sources_failed,
             total_records_processed,
+            updated_stock_ids,
             error_message: None,
             recommendations: self.generate_post_refresh_recommendations(&final_report),
         })
@@ -338,16 +605,25 @@ impl DataRefreshManager {
         self.record_refresh_start(&step.data_source).await?;
 
         let records_processed = match step.data_source.as_str() {
-            "daily_prices" => self.refresh_market_internal(session_id).await?,
+            "daily_prices" => self.refresh_market_internal(session_id, only_cik).await?,
             "financial_statements" => self.refresh_financials_unified(session_id, only_cik).await?,
             _ => return Err(anyhow!("Unknown data source: {}", step.data_source)),
         };
 
         let end_time = Utc::now();
-        let duration_seconds = end_time.signed_duration_since(start_time).num_seconds();
+        let duration_seconds = end_time.signed_duration_since(start_time).num_milliseconds() as f64 / 1000.0;
+
+        // Throughput units: bars for market data, CIKs for financials; duration estimates scale per stock
+        let (units_processed, stocks_processed) = match step.data_source.as_str() {
+            "financial_statements" => {
+                let ciks = self.status_reader.get_sp500_stocks_with_ciks(only_cik).await?.len() as i64;
+                (ciks, ciks)
+            }
+            _ => (records_processed, self.count_market_stocks().await?),
+        };
 
         // Record the completion
-        self.record_refresh_complete(&step.data_source, records_processed, duration_seconds as i32).await?;
+        self.record_refresh_complete(&step.data_source, records_processed, units_processed, stocks_processed, duration_seconds).await?;
 
         Ok(records_processed)
     }
@@ -356,9 +632,10 @@ impl DataRefreshManager {
     // CLEAN INTERNAL FUNCTIONS (No external cargo calls)
     // ========================================
 
-    /// Refresh market data from Schwab (prices, shares, market cap)
-    async fn refresh_market_internal(&self, _session_id: &str) -> Result<i64> {
-        println!("💰 Refreshing market data from Schwab...");
+    /// Refresh market data from Schwab (prices, shares, market cap), for one stock when
+    /// `only_cik` is set
+    async fn refresh_market_internal(&self, session_id: &str, only_cik: Option<&String>) -> Result<i64> {
+        info!("Refreshing market data from Schwab...");
 
         // Load configuration and create Schwab client
         let config = Config::from_env()?;
@@ -367,20 +644,22 @@ impl DataRefreshManager {
         // Get today's date for end date
         let end_date = chrono::Local::now().naive_local().date();
 
-        println!("📅 Importing market data up to {}", end_date);
+        info!("Importing market data up to {}", end_date);
 
         // Get only S&P 500 stocks that need price updates
         let stocks_query = r#"
             SELECT s.id, s.symbol
             FROM stocks s
             INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol
+            WHERE ?1 IS NULL OR s.cik = ?1
             ORDER BY s.symbol
         "#;
         let stocks = sqlx::query_as::<_, (i64, String)>(stocks_query)
+            .bind(only_cik)
             .fetch_all(&self.pool)
             .await?;
 
-        println!("📊 Found {} S&P 500 stocks to update", stocks.len());
+        info!("Found {} S&P 500 stocks to update", stocks.len());
 
         let total_stocks = stocks.len();
 
@@ -389,6 +668,7 @@ impl DataRefreshManager {
         let mut tasks = Vec::new();
 
         for (stock_id, symbol) in stocks {
+            let task_symbol = symbol.clone();
             let permit = semaphore.clone();
             let pool = self.pool.clone();
             let config = config.clone();
@@ -397,7 +677,7 @@ impl DataRefreshManager {
                 let _permit = match permit.acquire().await {
                     Ok(permit) => permit,
                     Err(e) => {
-                        println!("❌ Failed to acquire permit for {}: {}", symbol, e);
+                        error!(%symbol, "Failed to acquire permit for {}: {}", symbol, e);
                         return Err(anyhow!("Failed to acquire permit: {}", e));
                     }
                 };
@@ -408,143 +688,133 @@ impl DataRefreshManager {
                     Err(e) => return Err(anyhow!("Failed to create client for {}: {}", symbol, e)),
                 };
 
-                // Get the latest date for this symbol to determine where to start
-                let latest_date_query = "SELECT MAX(date) as latest FROM daily_prices WHERE stock_id = ?";
-                let latest_result = sqlx::query(latest_date_query)
-                    .bind(stock_id)
-                    .fetch_optional(&pool)
-                    .await;
-
-                let start_update_date = if let Ok(Some(row)) = latest_result {
-                    if let Ok(latest_str) = row.try_get::<String, _>("latest") {
-                        if let Ok(latest_date) = chrono::NaiveDate::parse_from_str(&latest_str, "%Y-%m-%d") {
-                            latest_date.succ_opt().unwrap_or(end_date)
-                        } else {
-                            chrono::NaiveDate::from_ymd_opt(2015, 1, 1).unwrap_or_else(|| chrono::NaiveDate::from_ymd_opt(2015, 1, 1).expect("Valid date"))
-                        }
-                    } else {
-                        chrono::NaiveDate::from_ymd_opt(2015, 1, 1).unwrap()
-                    }
-                } else {
-                    chrono::NaiveDate::from_ymd_opt(2015, 1, 1).unwrap()
-                };
-
-                // Skip if already up to date
-                if start_update_date > end_date {
-                    return Ok((symbol, 0));
-                }
-
-                // Fetch price data
-                match client.get_price_history(&symbol, start_update_date, end_date).await {
-                    Ok(candles) => {
-                        if !candles.is_empty() {
-                            let mut records_inserted = 0;
-                            // Insert the candles into database
-                            for candle in &candles {
-                                let insert_query = r#"
-                                    INSERT OR REPLACE INTO daily_prices
-                                    (stock_id, date, open_price, high_price, low_price, close_price, volume, created_at)
-                                    VALUES (?, ?, ?, ?, ?, ?, ?, datetime('now'))
-                                "#;
-
-                                // Convert Unix timestamp to date string
-                                let datetime = DateTime::from_timestamp(candle.datetime / 1000, 0)
-                                    .unwrap_or_else(|| Utc::now());
-                                let date_str = datetime.format("%Y-%m-%d").to_string();
-
-                                if let Ok(_) = sqlx::query(insert_query)
-                                    .bind(stock_id)
-                                    .bind(date_str)
-                                    .bind(candle.open)
-                                    .bind(candle.high)
-                                    .bind(candle.low)
-                                    .bind(candle.close)
-                                    .bind(candle.volume)
-                                    .execute(&pool)
-                                    .await {
-                                    records_inserted += 1;
-                                }
-                            }
-                            Ok((symbol, records_inserted))
-                        } else {
-                            Ok((symbol, 0))
-                        }
-                    }
+                match import_price_history(&client, &pool, stock_id, &symbol, end_date).await {
+                    Ok(records_inserted) => Ok((symbol, records_inserted)),
+                    Err(e) if is_token_expired(&e) => Err(e),
                     Err(e) => {
                         Err(anyhow!("Failed to fetch {}: {}", symbol, e))
                     }
                 }
             });
 
-            tasks.push(task);
+            tasks.push((task_symbol, task));
         }
 
         // Wait for all tasks to complete
-        println!("🚀 Processing {} stocks concurrently (max 10 parallel)...", tasks.len());
+        info!("Processing {} stocks concurrently (max 10 parallel)...", tasks.len());
 
         let mut total_records = 0;
         let mut updated_symbols = 0;
+        let mut finished_symbols = 0;
+        let mut failures = Vec::new();
 
-        for (i, task) in tasks.into_iter().enumerate() {
+        let mut pending = tasks.into_iter();
+        while let Some((task_symbol, task)) = pending.next() {
             match task.await {
                 Ok(Ok((symbol, records))) => {
                     total_records += records;
                     updated_symbols += 1;
                     if records > 0 {
-                        println!("✅ {} - {} new price records", symbol, records);
+                        info!(%symbol, records, "{} - {} new price records", symbol, records);
+                    }
+                }
+                Ok(Err(e)) if is_token_expired(&e) => {
+                    // Every remaining request would fail the same way
+                    warn!("Schwab token expired - stopping market data refresh");
+                    for (_, remaining) in pending {
+                        remaining.abort();
                     }
+                    self.record_run_stats(|stats| {
+                        stats.prices_inserted += total_records as i64;
+                        stats.errors.extend(failures);
+                    });
+                    return Err(e);
                 }
                 Ok(Err(e)) => {
-                    println!("⚠️ Task failed: {}", e);
+                    warn!(symbol = %task_symbol, "Task failed: {}", e);
+                    failures.push(market_error(&task_symbol, e.to_string()));
                 }
                 Err(e) => {
-                    println!("⚠️ Task {} panicked: {}", i, e);
+                    warn!(symbol = %task_symbol, "Task for {} panicked: {}", task_symbol, e);
+                    failures.push(market_error(&task_symbol, format!("Task panicked: {}", e)));
                 }
             }
 
+            finished_symbols += 1;
+            let step_progress = finished_symbols as f64 / total_stocks as f64 * 100.0;
+            self.publish_progress(session_id, None, "Update market data", step_progress, Some(&task_symbol));
+
             // Progress update every 25 stocks or at the end
             if updated_symbols % 25 == 0 || updated_symbols == total_stocks {
                 let progress_percent = (updated_symbols as f64 / total_stocks as f64) * 100.0;
-                println!("📊 Progress: {}/{} stocks ({:.1}%) - {} total records",
-                         updated_symbols, total_stocks, progress_percent, total_records);
+                info!(total_records, "Progress: {}/{} stocks ({:.1}%) - {} total records", updated_symbols, total_stocks, progress_percent, total_records);
+            }
+        }
+
+        // Exchange rates let screens compare non-USD market caps; stale rates only affect those
+        if let Err(e) = update_fx_rates(&self.pool, FRANKFURTER_BASE_URL).await {
+            warn!("Failed to update exchange rates: {}", e);
+        }
+
+        if total_records > 0 {
+            if let Err(e) = update_composite_benchmarks(&self.pool).await {
+                warn!("Failed to extend S&P 500 benchmark composites: {}", e);
+            }
+            // New closes can complete the returns of recent filings
+            if let Err(e) = backfill_filing_reactions(&self.pool, None).await {
+                warn!("Failed to backfill filing price reactions: {}", e);
             }
         }
 
-        println!("✅ S&P 500 market data refresh completed - {} symbols, {} records", updated_symbols, total_records);
+        info!(total_records, "S&P 500 market data refresh completed - {} symbols, {} records", updated_symbols, total_records);
+
+        // Market and financial steps cover the same stocks, so keep the larger count rather than summing
+        self.record_run_stats(|stats| {
+            stats.stocks_processed = stats.stocks_processed.max(total_stocks as i64);
+            stats.prices_inserted += total_records as i64;
+            stats.errors.extend(failures);
+        });
+
         Ok(total_records as i64)
     }
 
     /// Refresh all EDGAR financial data using unified single-stage approach
     async fn refresh_financials_unified(&self, _session_id: &str, only_cik: Option<&String>) -> Result<i64> {
-        println!("📈 Refreshing EDGAR financial data using unified single-stage approach...");
+        info!("Refreshing EDGAR financial data using unified single-stage approach...");
 
         // Get filtered or all stocks using early filtering
         let stocks_with_ciks = self.status_reader.get_sp500_stocks_with_ciks(only_cik).await?;
 
         if stocks_with_ciks.is_empty() {
             if let Some(cik) = only_cik {
-                println!("❌ CIK {} not found in S&P 500 stocks", cik);
+                error!(%cik, "CIK {} not found in S&P 500 stocks", cik);
             } else {
-                println!("❌ No S&P 500 stocks found");
+                error!("No S&P 500 stocks found");
             }
             return Ok(0);
         }
 
         if let Some(cik) = only_cik {
-            println!("🎯 Processing single stock: CIK {}", cik);
+            info!(%cik, "Processing single stock: CIK {}", cik);
         } else {
-            println!("📊 Processing {} S&P 500 stocks for financial data extraction", stocks_with_ciks.len());
+            info!("Processing {} S&P 500 stocks for financial data extraction", stocks_with_ciks.len());
         }
 
         // Call the unified method with filtered stocks
-        let total_records_stored = self.status_reader
+        let (total_records_stored, errors) = self.status_reader
             .run_unified_financials_for_stocks(&stocks_with_ciks)
             .await?;
 
+        self.record_run_stats(|stats| {
+            stats.stocks_processed = stats.stocks_processed.max(stocks_with_ciks.len() as i64);
+            stats.filings_stored += total_records_stored;
+            stats.errors.extend(errors);
+        });
+
         if let Some(_cik) = only_cik {
-            println!("✅ Single-stock refresh completed: {} records stored", total_records_stored);
+            info!(total_records_stored, "Single-stock refresh completed: {} records stored", total_records_stored);
         } else {
-            println!("✅ Full refresh completed: {} records stored", total_records_stored);
+            info!(total_records_stored, "Full refresh completed: {} records stored", total_records_stored);
         }
 
         Ok(total_records_stored)
@@ -572,7 +842,7 @@ impl DataRefreshManager {
         while let Ok(None) = child.try_wait() {
             sleep(StdDuration::from_secs(30)).await;
             elapsed += 30;
-            println!("⏱️  Price refresh running... {} seconds elapsed", elapsed);
+            info!("Price refresh running... {} seconds elapsed", elapsed);
         }
 
         // Wait for final completion
@@ -588,7 +858,7 @@ impl DataRefreshManager {
             .await?;
         let recent_records: i64 = result.get("count");
 
-        println!("✅ Price refresh completed - {} recent records", recent_records);
+        info!("Price refresh completed - {} recent records", recent_records);
         Ok(recent_records)
     }
 
@@ -679,6 +949,8 @@ impl DataRefreshManager {
 
     /// Update progress for a specific step
     async fn update_progress(&self, session_id: &str, step_number: i32, step_name: &str, step_progress: f64) -> Result<()> {
+        self.publish_progress(session_id, Some(step_number), step_name, step_progress, None);
+
         let query = r#"
             UPDATE refresh_progress
             SET completed_steps = ?, current_step_name = ?, current_step_progress = ?
@@ -698,6 +970,8 @@ impl DataRefreshManager {
 
     /// Update total steps (used when plan is finalized)
     async fn update_progress_total_steps(&self, session_id: &str, total_steps: i32) -> Result<()> {
+        self.progress_state.lock().unwrap_or_else(|e| e.into_inner()).total_steps = total_steps;
+
         let query = "UPDATE refresh_progress SET total_steps = ? WHERE session_id = ?";
 
         sqlx::query(query)
@@ -737,17 +1011,25 @@ impl DataRefreshManager {
     }
 
     /// Record the completion of a data source refresh
-    async fn record_refresh_complete(&self, data_source: &str, records_updated: i64, _duration_seconds: i32) -> Result<()> {
-        println!("📝 Recording completion for {}: {} records", data_source, records_updated);
-        // data_refresh_status table was removed during cleanup
-        // This function is now a no-op
-        Ok(())
+    async fn record_refresh_complete(&self, data_source: &str, records_updated: i64, units_processed: i64, stocks_processed: i64, duration_seconds: f64) -> Result<()> {
+        info!("Recording completion for {}: {} records in {:.1}s", data_source, records_updated, duration_seconds);
+        record_refresh_throughput(&self.pool, data_source, units_processed, records_updated, stocks_processed, duration_seconds).await
+    }
+
+    /// Stocks covered by the market refresh (same selection as refresh_market_internal)
+    async fn count_market_stocks(&self) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM stocks s INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count)
     }
 
     /// Update refresh status for a data source
     async fn update_refresh_status(&self, data_source: &str, success: bool, records: Option<i64>, _error: Option<String>) -> Result<()> {
         let status = if success { "current" } else { "error" };
-        println!("🔄 Updating refresh status for {}: success={}, records={:?}, status={}", data_source, success, records, status);
+        info!(records, "Updating refresh status for {}: success={}, records={:?}, status={}", data_source, success, records, status);
         // data_refresh_status table was removed during cleanup
         // This function is now a no-op
         Ok(())
@@ -758,7 +1040,7 @@ impl DataRefreshManager {
         let mut recommendations = Vec::new();
 
 
-        if freshness_report.screening_readiness.valuation_analysis {
+        if freshness_report.screening_readiness.valuation_analysis.ready {
             recommendations.push("Valuation analysis is now ready with current ratios".to_string());
         }
 
@@ -819,4 +1101,245 @@ impl DataRefreshManager {
     pub async fn get_system_status(&self) -> Result<SystemFreshnessReport> {
         self.status_reader.check_system_freshness().await
     }
-}
\ No newline at end of file
+}
+
+/// True when the error is a rejected Schwab session that needs re-authentication
+fn is_token_expired(e: &anyhow::Error) -> bool {
+    matches!(e.downcast_ref::<CommandError>(), Some(CommandError::TokenExpired { .. }))
+}
+
+fn market_error(symbol: &str, message: String) -> RefreshRunError {
+    RefreshRunError {
+        data_source: "daily_prices".to_string(),
+        symbol: Some(symbol.to_string()),
+        cik: None,
+        message,
+    }
+}
+
+/// Fetch `symbol`'s daily bars from the day after its latest stored price (2015-01-01 when it
+/// has none) through `end_date` and store the valid ones; returns the number of rows written
+pub async fn import_price_history(
+    client: &SchwabClient,
+    pool: &SqlitePool,
+    stock_id: i64,
+    symbol: &str,
+    end_date: chrono::NaiveDate,
+) -> Result<usize> {
+    let first_history_date = chrono::NaiveDate::from_ymd_opt(2015, 1, 1).expect("Valid date");
+
+    // Get the latest date for this symbol to determine where to start
+    let latest: Option<String> = sqlx::query_scalar("SELECT MAX(date) FROM daily_prices WHERE stock_id = ?")
+        .bind(stock_id)
+        .fetch_one(pool)
+        .await
+        .unwrap_or(None);
+    let start_update_date = match latest.and_then(|latest| chrono::NaiveDate::parse_from_str(&latest, "%Y-%m-%d").ok()) {
+        Some(latest_date) => latest_date.succ_opt().unwrap_or(end_date),
+        None => first_history_date,
+    };
+
+    // Skip if already up to date
+    if start_update_date > end_date {
+        return Ok(0);
+    }
+
+    let candles = client.get_price_history(symbol, start_update_date, end_date, PriceFrequency::Daily).await?;
+    let mut records_inserted = 0;
+    for candle in &candles {
+        // Convert Unix timestamp to date string
+        let datetime = DateTime::from_timestamp(candle.datetime / 1000, 0).unwrap_or_else(Utc::now);
+        let date_str = datetime.format("%Y-%m-%d").to_string();
+
+        if let Err(e) = validate_ohlc(candle.open, candle.high, candle.low, candle.close) {
+            warn!(%symbol, date = %date_str, "Skipping invalid price bar for {}: {}", symbol, e);
+            continue;
+        }
+
+        let inserted = sqlx::query(
+            "INSERT OR REPLACE INTO daily_prices
+             (stock_id, date, open_price, high_price, low_price, close_price, volume, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, datetime('now'))"
+        )
+        .bind(stock_id)
+        .bind(date_str)
+        .bind(candle.open)
+        .bind(candle.high)
+        .bind(candle.low)
+        .bind(candle.close)
+        .bind(candle.volume)
+        .execute(pool)
+        .await;
+        if inserted.is_ok() {
+            records_inserted += 1;
+        }
+    }
+
+    if records_inserted > 0 {
+        global_query_cache().invalidate_all();
+        if let Err(e) = refresh_52_week_range(pool, stock_id).await {
+            warn!(%symbol, "Failed to update 52-week range for {}: {}", symbol, e);
+        }
+    }
+    Ok(records_inserted)
+}
+
+/// Stocks whose statements were inserted after `since` (inserted_at is stamped by triggers in UTC)
+async fn stocks_with_new_statements(pool: &SqlitePool, since: DateTime<Utc>) -> Result<Vec<i64>> {
+    let since_timestamp = since.format("%Y-%m-%d %H:%M:%S%.3f").to_string();
+
+    let rows = sqlx::query(
+        "SELECT stock_id FROM income_statements WHERE inserted_at > ?1
+         UNION
+         SELECT stock_id FROM balance_sheets WHERE inserted_at > ?1
+         UNION
+         SELECT stock_id FROM cash_flow_statements WHERE inserted_at > ?1
+         ORDER BY stock_id"
+    )
+    .bind(&since_timestamp)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|row| row.get::<i64, _>("stock_id")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{migrated_pool, TEST_DB_FILE};
+
+    #[tokio::test]
+    async fn test_stocks_with_new_statements_skips_untouched_stocks() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        for table in ["income_statements", "balance_sheets", "cash_flow_statements"] {
+            sqlx::query(&format!("CREATE TABLE {} (stock_id INTEGER, inserted_at DATETIME)", table))
+                .execute(&pool).await.unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO income_statements VALUES (1, '2025-01-01 00:00:00.000'), (2, '2025-03-01 12:00:00.500')"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO balance_sheets VALUES (2, '2025-03-01 12:00:01.000'), (3, '2025-03-02 08:00:00.000')")
+            .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO cash_flow_statements VALUES (4, '2024-12-31 23:59:59.999')")
+            .execute(&pool).await.unwrap();
+
+        let since = DateTime::parse_from_rfc3339("2025-03-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(stocks_with_new_statements(&pool, since).await.unwrap(), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_lock_is_exclusive_until_released_or_expired() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        // A separate pool on the same file stands in for another process
+        let other_process = SqlitePool::connect(&format!("sqlite:{}", dir.path().join(TEST_DB_FILE).display())).await.unwrap();
+
+        let held = RefreshRunningGuard::acquire(&pool, "test").await.unwrap();
+        assert!(is_refresh_running(&other_process).await.unwrap());
+        let busy = RefreshRunningGuard::acquire(&other_process, "test").await;
+        assert!(matches!(busy, Err(CommandError::DatabaseBusy { .. })));
+        let manager = DataRefreshManager::new(other_process.clone()).await.unwrap();
+        let request = RefreshRequest {
+            mode: RefreshMode::Financials,
+            force_sources: Vec::new(),
+            initiated_by: "test".to_string(),
+            session_id: None,
+            only_cik: Some("0000000000".to_string()),
+        };
+        assert!(manager.execute_refresh(request).await.is_err());
+
+        held.release().await;
+        assert!(!is_refresh_running(&other_process).await.unwrap());
+
+        // A holder that died without releasing is taken over once its lease runs out
+        let abandoned = RefreshRunningGuard::acquire(&pool, "test").await.unwrap();
+        abandoned.renewal.abort();
+        std::mem::forget(abandoned);
+        sqlx::query("UPDATE refresh_lock SET expires_at = datetime('now', '-1 seconds')")
+            .execute(&pool).await.unwrap();
+        assert!(!is_refresh_running(&other_process).await.unwrap());
+        RefreshRunningGuard::acquire(&other_process, "test").await.unwrap().release().await;
+    }
+
+    #[derive(Default)]
+    struct RecordingSink(std::sync::Mutex<Vec<RefreshProgressEvent>>);
+
+    impl RefreshProgressSink for RecordingSink {
+        fn send_progress(&self, event: &RefreshProgressEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_emits_progress_in_increasing_percent_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        let sink = Arc::new(RecordingSink::default());
+        let manager = DataRefreshManager::new(pool.clone()).await.unwrap().with_progress_sink(sink.clone());
+        // An unknown CIK runs every step without contacting SEC
+        let request = RefreshRequest {
+            mode: RefreshMode::Financials,
+            force_sources: Vec::new(),
+            initiated_by: "test".to_string(),
+            session_id: Some("progress-session".to_string()),
+            only_cik: Some("0000000000".to_string()),
+        };
+        manager.execute_refresh(request).await.unwrap();
+        assert!(!is_refresh_running(&pool).await.unwrap());
+
+        let events = sink.0.lock().unwrap().clone();
+        assert!(events.len() >= 3);
+        assert!(events.iter().all(|event| event.session_id == "progress-session"));
+        assert!(events.windows(2).all(|pair| pair[0].percent <= pair[1].percent));
+        assert!(events[0].percent > 0.0 && events[0].percent < 100.0);
+        assert!(events.iter().any(|event| event.stage == "Refresh financial statements"));
+
+        let last = events.last().unwrap();
+        assert_eq!((last.stage.as_str(), last.percent, last.eta_seconds), ("Finalizing refresh", 100.0, Some(0)));
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier(std::sync::Mutex<Vec<Notification>>);
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, notification: &Notification) {
+            self.0.lock().unwrap().push(notification.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_outcomes_are_dispatched_as_notifications() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        let notifier = Arc::new(RecordingNotifier::default());
+        let manager = DataRefreshManager::new(pool.clone()).await.unwrap().with_notifier(notifier.clone());
+        let request = RefreshRequest {
+            mode: RefreshMode::Financials,
+            force_sources: Vec::new(),
+            initiated_by: "test".to_string(),
+            session_id: None,
+            only_cik: Some("0000000000".to_string()),
+        };
+        manager.execute_refresh(request).await.unwrap();
+        manager.notify_run_outcome(&RefreshMode::Market, Some("Schwab token expired\nre-authenticate")).await;
+
+        assert_eq!(
+            *notifier.0.lock().unwrap(),
+            vec![
+                Notification {
+                    kind: NotificationKind::RefreshCompleted,
+                    title: "Financials refresh completed".to_string(),
+                    body: "0 stocks, 0 prices, 0 filings, 0 errors".to_string(),
+                },
+                Notification {
+                    kind: NotificationKind::RefreshFailed,
+                    title: "Market refresh failed".to_string(),
+                    body: "Schwab token expired".to_string(),
+                },
+            ]
+        );
+    }
+}
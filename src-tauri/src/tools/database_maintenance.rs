@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+
+/// Rows and on-disk size of one table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TableSize {
+    pub name: String,
+    pub row_count: i64,
+    /// Pages of the table and its indexes; None when SQLite was built without dbstat
+    pub size_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DatabaseMaintenanceReport {
+    pub integrity_ok: bool,
+    /// What PRAGMA integrity_check found; just "ok" when the database is sound
+    pub integrity_messages: Vec<String>,
+    pub vacuumed: bool,
+    pub size_bytes_before: i64,
+    pub size_bytes_after: i64,
+    /// Space held by free pages after maintenance, which only VACUUM returns to the disk
+    pub free_bytes: i64,
+    /// Largest first
+    pub tables: Vec<TableSize>,
+}
+
+async fn file_size(pool: &SqlitePool) -> Result<(i64, i64)> {
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(pool).await?;
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(pool).await?;
+    let freelist_count: i64 = sqlx::query_scalar("PRAGMA freelist_count").fetch_one(pool).await?;
+    Ok((page_size * page_count, page_size * freelist_count))
+}
+
+/// Bytes per table, its indexes included, or None without the dbstat virtual table
+async fn table_sizes(pool: &SqlitePool) -> Option<HashMap<String, i64>> {
+    let sizes: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT COALESCE(m.tbl_name, d.name), SUM(d.pgsize)
+         FROM dbstat d
+         LEFT JOIN sqlite_master m ON m.name = d.name
+         GROUP BY COALESCE(m.tbl_name, d.name)"
+    )
+    .fetch_all(pool)
+    .await
+    .ok()?;
+    Some(sizes.into_iter().collect())
+}
+
+/// Check integrity, refresh the query planner's statistics, optionally VACUUM, and report
+/// each table's rows and size. VACUUM rewrites the whole file: it can take minutes on a large
+/// database and needs free disk space about the size of the database while it runs.
+pub async fn run_database_maintenance(pool: &SqlitePool, vacuum: bool) -> Result<DatabaseMaintenanceReport> {
+    let (size_bytes_before, _) = file_size(pool).await?;
+
+    let integrity_messages: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check").fetch_all(pool).await?;
+    let integrity_ok = integrity_messages == ["ok"];
+
+    sqlx::query("ANALYZE").execute(pool).await?;
+    // Never rewrite a damaged file; integrity problems are reported instead
+    let vacuumed = vacuum && integrity_ok;
+    if vacuumed {
+        sqlx::query("VACUUM").execute(pool).await?;
+    }
+
+    let (size_bytes_after, free_bytes) = file_size(pool).await?;
+
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+    )
+    .fetch_all(pool)
+    .await?;
+    let sizes = table_sizes(pool).await;
+    let mut tables = Vec::new();
+    for name in names {
+        let row_count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")))
+            .fetch_one(pool)
+            .await?;
+        let size_bytes = sizes.as_ref().map(|sizes| sizes.get(&name).copied().unwrap_or(0));
+        tables.push(TableSize { name, row_count, size_bytes });
+    }
+    tables.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then(b.row_count.cmp(&a.row_count)));
+
+    Ok(DatabaseMaintenanceReport {
+        integrity_ok,
+        integrity_messages,
+        vacuumed,
+        size_bytes_before,
+        size_bytes_after,
+        free_bytes,
+        tables,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_vacuum_returns_free_pages_and_tables_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'KEEP', 'Kept Co'), (2, 'GONE', 'Removed Co')")
+            .execute(&pool).await.unwrap();
+        for day in 0..2000 {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (2, date('2000-01-01', ?), 1, 1, 1, 1)"
+            )
+            .bind(format!("+{} days", day))
+            .execute(&pool).await.unwrap();
+        }
+        sqlx::query("DELETE FROM daily_prices").execute(&pool).await.unwrap();
+
+        let checked = run_database_maintenance(&pool, false).await.unwrap();
+        assert!(checked.integrity_ok && !checked.vacuumed);
+        assert!(checked.free_bytes > 0);
+
+        let report = run_database_maintenance(&pool, true).await.unwrap();
+        assert_eq!(report.integrity_messages, vec!["ok"]);
+        assert!(report.vacuumed);
+        assert_eq!(report.free_bytes, 0);
+        assert!(report.size_bytes_after < report.size_bytes_before);
+
+        let stocks = report.tables.iter().find(|table| table.name == "stocks").unwrap();
+        assert_eq!(stocks.row_count, 2);
+        let prices = report.tables.iter().find(|table| table.name == "daily_prices").unwrap();
+        assert_eq!(prices.row_count, 0);
+        assert!(!report.tables.iter().any(|table| table.name.starts_with("sqlite_")));
+    }
+}
@@ -127,15 +127,9 @@ impl DateRangeCalculator {
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
-        // Generate expected trading days
-        let expected_dates = self.generate_trading_days(desired_range.start_date, desired_range.end_date);
-
         // Find missing dates
         let existing_set: HashSet<NaiveDate> = existing_dates.into_iter().collect();
-        let missing_dates: Vec<NaiveDate> = expected_dates
-            .into_iter()
-            .filter(|date| !existing_set.contains(date))
-            .collect();
+        let missing_dates = self.find_missing_dates(&existing_set, desired_range);
 
         // Group consecutive missing dates into ranges
         let missing_ranges = self.group_consecutive_dates(missing_dates);
@@ -143,6 +137,14 @@ impl DateRangeCalculator {
         Ok(missing_ranges)
     }
 
+    /// Expected trading days in the range that have no stored price
+    pub fn find_missing_dates(&self, existing: &HashSet<NaiveDate>, range: &DateRange) -> Vec<NaiveDate> {
+        self.generate_trading_days(range.start_date, range.end_date)
+            .into_iter()
+            .filter(|date| !existing.contains(date))
+            .collect()
+    }
+
     /// Generate list of expected trading days (excludes weekends and holidays)
     pub fn generate_trading_days(&self, start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
         let mut trading_days = Vec::new();
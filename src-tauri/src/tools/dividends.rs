@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+/// Per-share dividend concepts in order of preference when both report the same period,
+/// with the source their rows are stored under
+const DIVIDEND_CONCEPTS: [(&str, &str); 2] = [
+    ("CommonStockDividendsPerShareDeclared", "sec_declared"),
+    ("CommonStockDividendsPerShareCashPaid", "sec_paid"),
+];
+
+/// A quarter paying more than this multiple of its usual dividend includes a special dividend
+pub const SPECIAL_DIVIDEND_MULTIPLE: f64 = 2.0;
+
+/// Prior quarters whose median is taken as the usual dividend; at least two are needed
+const SPECIAL_BASELINE_QUARTERS: usize = 4;
+
+/// Fact durations, in days, read as a quarter and as a fiscal year. Year-to-date facts from
+/// 10-Qs fall between the two and are skipped.
+const QUARTER_DAYS: std::ops::RangeInclusive<i64> = 80..=100;
+const YEAR_DAYS: std::ops::RangeInclusive<i64> = 350..=380;
+
+/// Dividends per share over one quarter or fiscal year
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DividendRecord {
+    /// Quarterly or Annual
+    pub period_type: String,
+    pub period_start: Option<String>,
+    pub period_end: String,
+    /// Year of the fiscal year end the period falls in, when known
+    pub fiscal_year: Option<i32>,
+    pub amount_per_share: f64,
+    /// Part of `amount_per_share` judged a one-off special dividend
+    pub special_amount: f64,
+    pub is_special: bool,
+    /// sec_declared, sec_paid or cash_flow_derived
+    pub source: String,
+    pub accession_number: Option<String>,
+}
+
+impl DividendRecord {
+    /// Amount per share without the special dividend
+    pub fn regular_amount(&self) -> f64 {
+        self.amount_per_share - self.special_amount
+    }
+
+    fn contains(&self, date: &str) -> bool {
+        self.period_start.as_deref().is_some_and(|start| date > start) && date <= self.period_end.as_str()
+    }
+}
+
+/// Every quarterly and annual per-share dividend in a Company Facts response, oldest first.
+/// The preferred concept wins a period; within a concept the latest filing wins. Fourth
+/// quarters, which 10-Ks don't report on their own, are implied from the year less the
+/// other three, and special dividends are flagged.
+pub fn parse_dividend_facts(company_facts: &serde_json::Value) -> Vec<DividendRecord> {
+    let mut by_period: HashMap<(String, String), (usize, Option<String>, DividendRecord)> = HashMap::new();
+
+    for (priority, &(concept, source)) in DIVIDEND_CONCEPTS.iter().enumerate() {
+        let Some(values) = company_facts
+            .get("facts")
+            .and_then(|f| f.get("us-gaap"))
+            .and_then(|t| t.get(concept))
+            .and_then(|c| c.get("units"))
+            .and_then(|u| u.get("USD/shares"))
+            .and_then(|s| s.as_array())
+        else {
+            continue;
+        };
+
+        for value in values {
+            let (Some(amount), Some(start), Some(end)) = (
+                value.get("val").and_then(|v| v.as_f64()),
+                value.get("start").and_then(|s| s.as_str()),
+                value.get("end").and_then(|e| e.as_str()),
+            ) else {
+                continue;
+            };
+            let (Ok(start_date), Ok(end_date)) = (
+                NaiveDate::parse_from_str(start, "%Y-%m-%d"),
+                NaiveDate::parse_from_str(end, "%Y-%m-%d"),
+            ) else {
+                continue;
+            };
+            let days = (end_date - start_date).num_days();
+            let period_type = if QUARTER_DAYS.contains(&days) {
+                "Quarterly"
+            } else if YEAR_DAYS.contains(&days) {
+                "Annual"
+            } else {
+                continue;
+            };
+            if amount < 0.0 {
+                continue;
+            }
+
+            let filed = value.get("filed").and_then(|f| f.as_str()).map(str::to_string);
+            let record = DividendRecord {
+                period_type: period_type.to_string(),
+                period_start: Some(start.to_string()),
+                period_end: end.to_string(),
+                fiscal_year: None,
+                amount_per_share: amount,
+                special_amount: 0.0,
+                is_special: false,
+                source: source.to_string(),
+                accession_number: value.get("accn").and_then(|a| a.as_str()).map(str::to_string),
+            };
+            let key = (period_type.to_string(), end.to_string());
+            let replace = match by_period.get(&key) {
+                None => true,
+                Some((existing_priority, existing_filed, _)) => {
+                    priority < *existing_priority || (priority == *existing_priority && filed > *existing_filed)
+                }
+            };
+            if replace {
+                by_period.insert(key, (priority, filed, record));
+            }
+        }
+    }
+
+    let (mut years, mut quarters): (Vec<DividendRecord>, Vec<DividendRecord>) = by_period
+        .into_values()
+        .map(|(_, _, record)| record)
+        .partition(|record| record.period_type == "Annual");
+    years.sort_by(|a, b| a.period_end.cmp(&b.period_end));
+
+    for year in &mut years {
+        year.fiscal_year = year.period_end.get(..4).and_then(|y| y.parse().ok());
+    }
+    for quarter in &mut quarters {
+        quarter.fiscal_year = years.iter().find(|year| year.contains(&quarter.period_end)).and_then(|year| year.fiscal_year);
+    }
+
+    // A 10-K reports the year; with the first three quarters from 10-Qs the fourth is the rest
+    for year in &years {
+        let mut within: Vec<&DividendRecord> = quarters.iter().filter(|q| year.contains(&q.period_end)).collect();
+        within.sort_by(|a, b| a.period_end.cmp(&b.period_end));
+        if within.len() != 3 || within[2].period_end >= year.period_end {
+            continue;
+        }
+        let fourth = year.amount_per_share - within.iter().map(|q| q.amount_per_share).sum::<f64>();
+        let day_after = NaiveDate::parse_from_str(&within[2].period_end, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.succ_opt());
+        if fourth < 0.0 {
+            continue;
+        }
+        let implied = DividendRecord {
+            period_type: "Quarterly".to_string(),
+            period_start: day_after.map(|date| date.format("%Y-%m-%d").to_string()),
+            period_end: year.period_end.clone(),
+            amount_per_share: fourth,
+            ..year.clone()
+        };
+        quarters.push(implied);
+    }
+
+    let mut records: Vec<DividendRecord> = years.into_iter().chain(quarters).collect();
+    flag_special_dividends(&mut records);
+    records.sort_by(|a, b| a.period_end.cmp(&b.period_end).then_with(|| b.period_type.cmp(&a.period_type)));
+    records
+}
+
+/// Mark the part of each quarter above its usual dividend as special when the quarter pays more
+/// than `SPECIAL_DIVIDEND_MULTIPLE` times it. The usual dividend is the median regular amount of
+/// the prior `SPECIAL_BASELINE_QUARTERS` quarters. Fiscal years carry the special amounts of
+/// their quarters, so a one-off payout doesn't read as growth followed by a cut.
+pub fn flag_special_dividends(records: &mut [DividendRecord]) {
+    let mut quarter_indices: Vec<usize> = (0..records.len()).filter(|&i| records[i].period_type == "Quarterly").collect();
+    quarter_indices.sort_by(|&a, &b| records[a].period_end.cmp(&records[b].period_end));
+
+    for (n, &i) in quarter_indices.iter().enumerate() {
+        let mut prior: Vec<f64> = quarter_indices[n.saturating_sub(SPECIAL_BASELINE_QUARTERS)..n]
+            .iter()
+            .map(|&j| records[j].regular_amount())
+            .collect();
+        records[i].special_amount = 0.0;
+        if prior.len() < 2 {
+            records[i].is_special = false;
+            continue;
+        }
+        prior.sort_by(f64::total_cmp);
+        let mid = prior.len() / 2;
+        let usual = if prior.len() % 2 == 0 { (prior[mid - 1] + prior[mid]) / 2.0 } else { prior[mid] };
+        if usual > 0.0 && records[i].amount_per_share > usual * SPECIAL_DIVIDEND_MULTIPLE {
+            records[i].special_amount = records[i].amount_per_share - usual;
+        }
+        records[i].is_special = records[i].special_amount > 0.0;
+    }
+
+    for i in 0..records.len() {
+        if records[i].period_type != "Annual" {
+            continue;
+        }
+        let special: f64 = quarter_indices
+            .iter()
+            .filter(|&&j| records[i].contains(&records[j].period_end))
+            .map(|&j| records[j].special_amount)
+            .sum();
+        records[i].special_amount = special.min(records[i].amount_per_share);
+        records[i].is_special = records[i].special_amount > 0.0;
+    }
+}
+
+/// Replace a stock's dividend history from a Company Facts response, then fill fiscal years the
+/// facts don't cover from the stored cash flow statements. Returns the rows stored.
+pub async fn store_dividend_history(pool: &SqlitePool, stock_id: i64, company_facts: &serde_json::Value) -> Result<u64> {
+    let records = parse_dividend_facts(company_facts);
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM dividends WHERE stock_id = ?")
+        .bind(stock_id)
+        .execute(&mut *tx)
+        .await?;
+    for record in &records {
+        sqlx::query(
+            "INSERT INTO dividends (stock_id, period_type, period_start, period_end, fiscal_year, amount_per_share,
+                                    special_amount, is_special, source, accession_number)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id)
+        .bind(&record.period_type)
+        .bind(&record.period_start)
+        .bind(&record.period_end)
+        .bind(record.fiscal_year)
+        .bind(record.amount_per_share)
+        .bind(record.special_amount)
+        .bind(record.is_special)
+        .bind(&record.source)
+        .bind(&record.accession_number)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    let derived = derive_dividends_from_cash_flow(pool, stock_id).await?;
+    Ok(records.len() as u64 + derived)
+}
+
+/// Approximate dividends per share as dividends paid over shares outstanding for each fiscal
+/// year with dividends paid but no annual dividend row. Shares are the balance sheet count,
+/// else the basic weighted average. Returns the rows added.
+pub async fn derive_dividends_from_cash_flow(pool: &SqlitePool, stock_id: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT OR IGNORE INTO dividends (stock_id, period_type, period_end, fiscal_year, amount_per_share, source)
+         SELECT c.stock_id, 'Annual', c.report_date, c.fiscal_year,
+                ABS(c.dividends_paid) / COALESCE(b.shares_outstanding, i.shares_basic), 'cash_flow_derived'
+         FROM (
+             SELECT stock_id, fiscal_year, report_date, dividends_paid,
+                    ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+             FROM cash_flow_statements
+             WHERE stock_id = ?1 AND period_type = 'Annual' AND fiscal_year IS NOT NULL
+         ) c
+         LEFT JOIN (
+             SELECT stock_id, fiscal_year, shares_outstanding,
+                    ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+             FROM balance_sheets
+             WHERE stock_id = ?1 AND period_type = 'Annual' AND shares_outstanding > 0
+         ) b ON b.fiscal_year = c.fiscal_year AND b.rn = 1
+         LEFT JOIN (
+             SELECT stock_id, fiscal_year, shares_basic,
+                    ROW_NUMBER() OVER (PARTITION BY stock_id, fiscal_year ORDER BY report_date DESC) AS rn
+             FROM income_statements
+             WHERE stock_id = ?1 AND period_type = 'FY' AND shares_basic > 0
+         ) i ON i.fiscal_year = c.fiscal_year AND i.rn = 1
+         WHERE c.rn = 1
+           AND c.dividends_paid IS NOT NULL AND c.dividends_paid != 0
+           AND COALESCE(b.shares_outstanding, i.shares_basic) IS NOT NULL
+           AND NOT EXISTS (
+               SELECT 1 FROM dividends d
+               WHERE d.stock_id = c.stock_id AND d.period_type = 'Annual' AND d.fiscal_year = c.fiscal_year
+           )"
+    )
+    .bind(stock_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// A stock's stored dividend rows, oldest first, fiscal years after their last quarter
+pub async fn load_dividend_records(pool: &SqlitePool, stock_id: i64) -> Result<Vec<DividendRecord>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT period_type, period_start, period_end, fiscal_year, amount_per_share, special_amount,
+                is_special, source, accession_number
+         FROM dividends WHERE stock_id = ? ORDER BY period_end, period_type DESC"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| DividendRecord {
+            period_type: row.get("period_type"),
+            period_start: row.get("period_start"),
+            period_end: row.get("period_end"),
+            fiscal_year: row.get("fiscal_year"),
+            amount_per_share: row.get("amount_per_share"),
+            special_amount: row.get("special_amount"),
+            is_special: row.get("is_special"),
+            source: row.get("source"),
+            accession_number: row.get("accession_number"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'COST', 'Costco Wholesale')")
+            .execute(&pool).await.unwrap();
+        pool
+    }
+
+    fn fact(start: &str, end: &str, val: f64, accn: &str, filed: &str) -> serde_json::Value {
+        json!({ "start": start, "end": end, "val": val, "accn": accn, "filed": filed })
+    }
+
+    /// 0.50 a quarter through 2022, 0.55 in 2023 with a 15.00 special in the third quarter
+    fn company_facts() -> serde_json::Value {
+        json!({
+            "facts": { "us-gaap": {
+                "CommonStockDividendsPerShareDeclared": { "units": { "USD/shares": [
+                    fact("2022-01-01", "2022-03-31", 0.50, "q1-22", "2022-05-01"),
+                    fact("2022-04-01", "2022-06-30", 0.50, "q2-22", "2022-08-01"),
+                    fact("2022-01-01", "2022-06-30", 1.00, "q2-22", "2022-08-01"),
+                    fact("2022-07-01", "2022-09-30", 0.50, "q3-22", "2022-11-01"),
+                    fact("2022-01-01", "2022-12-31", 2.00, "k-22", "2023-02-20"),
+                    fact("2023-01-01", "2023-03-31", 0.55, "q1-23", "2023-05-01"),
+                    fact("2023-04-01", "2023-06-30", 0.55, "q2-23", "2023-08-01"),
+                    fact("2023-07-01", "2023-09-30", 15.55, "q3-23", "2023-11-01"),
+                    fact("2023-01-01", "2023-12-31", 17.20, "k-23", "2024-02-20")
+                ] } },
+                "CommonStockDividendsPerShareCashPaid": { "units": { "USD/shares": [
+                    fact("2022-01-01", "2022-12-31", 1.95, "k-22", "2023-02-20")
+                ] } }
+            } }
+        })
+    }
+
+    #[test]
+    fn test_parse_implies_fourth_quarter_and_flags_special() {
+        let records = parse_dividend_facts(&company_facts());
+        let annual: Vec<&DividendRecord> = records.iter().filter(|r| r.period_type == "Annual").collect();
+        let quarters: Vec<&DividendRecord> = records.iter().filter(|r| r.period_type == "Quarterly").collect();
+
+        // Declared beats paid; the six-month year-to-date fact is skipped
+        assert_eq!(annual.iter().map(|r| (r.fiscal_year, r.amount_per_share)).collect::<Vec<_>>(), vec![(Some(2022), 2.0), (Some(2023), 17.2)]);
+        assert_eq!(quarters.len(), 8);
+        let q4_23 = quarters.last().unwrap();
+        assert_eq!((q4_23.period_start.as_deref(), q4_23.period_end.as_str()), (Some("2023-10-01"), "2023-12-31"));
+        assert!((q4_23.amount_per_share - 0.55).abs() < 1e-9);
+
+        // The usual dividend before Q3 2023 is the median of 0.50, 0.50, 0.55 and 0.55
+        let special = quarters.iter().find(|q| q.is_special).unwrap();
+        assert_eq!(special.period_end, "2023-09-30");
+        assert!((special.special_amount - 15.025).abs() < 1e-9);
+        assert!(!q4_23.is_special);
+        assert!(annual[1].is_special && (annual[1].regular_amount() - 2.175).abs() < 1e-9);
+        assert!(!annual[0].is_special);
+    }
+
+    #[tokio::test]
+    async fn test_store_falls_back_to_cash_flow_for_uncovered_years() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+
+        // 2021 has no per-share facts: 800 paid over 400 shares
+        sqlx::query(
+            "INSERT INTO cash_flow_statements (stock_id, period_type, report_date, fiscal_year, dividends_paid)
+             VALUES (1, 'Annual', '2021-12-31', 2021, -800.0), (1, 'Annual', '2022-12-31', 2022, -900.0)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, shares_outstanding)
+             VALUES (1, 'Annual', '2021-12-31', 2021, 400.0), (1, 'Annual', '2022-12-31', 2022, 450.0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        assert_eq!(store_dividend_history(&pool, 1, &company_facts()).await.unwrap(), 11);
+        let records = load_dividend_records(&pool, 1).await.unwrap();
+        let derived = &records[0];
+        assert_eq!((derived.source.as_str(), derived.fiscal_year, derived.amount_per_share), ("cash_flow_derived", Some(2021), 2.0));
+        // 2022 keeps the reported figure
+        assert!(records.iter().all(|r| r.fiscal_year != Some(2022) || r.source == "sec_declared"));
+
+        // Rerunning replaces rather than duplicates
+        assert_eq!(store_dividend_history(&pool, 1, &company_facts()).await.unwrap(), 11);
+    }
+}
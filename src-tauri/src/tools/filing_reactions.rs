@@ -0,0 +1,163 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+/// A filing with the close on the first trading day on or after it was filed and the returns
+/// that followed. Price fields are None until prices reach the filed date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FilingReaction {
+    pub sec_filing_id: i64,
+    pub accession_number: String,
+    pub form_type: String,
+    pub filed_date: String,
+    pub fiscal_year: i32,
+    pub fiscal_period: Option<String>,
+    pub report_date: String,
+    pub reaction_date: Option<String>,
+    pub reaction_close: Option<f64>,
+    /// Percent change from the reaction close to the close 5 trading days later
+    pub return_5d_pct: Option<f64>,
+    /// Percent change from the reaction close to the close 30 trading days later
+    pub return_30d_pct: Option<f64>,
+}
+
+/// Price every filing that has no reaction yet and fill in returns still missing, for one
+/// stock or all of them. Filings made after the latest stored price wait for a later run.
+/// Returns the reactions written.
+pub async fn backfill_filing_reactions(pool: &SqlitePool, stock_id: Option<i64>) -> Result<i64> {
+    let written = sqlx::query(
+        "WITH pending AS (
+            SELECT f.id, f.stock_id,
+                   (SELECT MIN(d.date) FROM daily_prices d
+                    WHERE d.stock_id = f.stock_id AND d.date >= f.filed_date) AS reaction_date
+            FROM sec_filings f
+            LEFT JOIN filing_price_reactions r ON r.sec_filing_id = f.id
+            WHERE (?1 IS NULL OR f.stock_id = ?1)
+              AND (r.sec_filing_id IS NULL OR r.return_5d_pct IS NULL OR r.return_30d_pct IS NULL)
+        ),
+        trading_days AS (
+            SELECT stock_id, date, close_price,
+                   ROW_NUMBER() OVER (PARTITION BY stock_id ORDER BY date) AS day_number
+            FROM daily_prices
+            WHERE stock_id IN (SELECT stock_id FROM pending)
+        )
+        INSERT INTO filing_price_reactions
+            (sec_filing_id, stock_id, reaction_date, reaction_close, return_5d_pct, return_30d_pct, updated_at)
+        SELECT p.id, p.stock_id, base.date, base.close_price,
+               (after_5.close_price / base.close_price - 1) * 100,
+               (after_30.close_price / base.close_price - 1) * 100,
+               CURRENT_TIMESTAMP
+        FROM pending p
+        JOIN trading_days base ON base.stock_id = p.stock_id AND base.date = p.reaction_date
+        LEFT JOIN trading_days after_5 ON after_5.stock_id = p.stock_id AND after_5.day_number = base.day_number + 5
+        LEFT JOIN trading_days after_30 ON after_30.stock_id = p.stock_id AND after_30.day_number = base.day_number + 30
+        WHERE base.close_price > 0
+        ON CONFLICT(sec_filing_id) DO UPDATE SET
+            reaction_date = excluded.reaction_date,
+            reaction_close = excluded.reaction_close,
+            return_5d_pct = excluded.return_5d_pct,
+            return_30d_pct = excluded.return_30d_pct,
+            updated_at = excluded.updated_at"
+    )
+    .bind(stock_id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(written as i64)
+}
+
+/// A stock's filings with their price reactions, most recently filed first
+pub async fn load_filing_reactions(pool: &SqlitePool, stock_id: i64) -> Result<Vec<FilingReaction>> {
+    let rows = sqlx::query(
+        "SELECT f.id, f.accession_number, f.form_type, f.filed_date, f.fiscal_year, f.fiscal_period, f.report_date,
+                r.reaction_date, r.reaction_close, r.return_5d_pct, r.return_30d_pct
+         FROM sec_filings f
+         LEFT JOIN filing_price_reactions r ON r.sec_filing_id = f.id
+         WHERE f.stock_id = ?
+         ORDER BY f.filed_date DESC, f.id DESC"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| FilingReaction {
+            sec_filing_id: row.get("id"),
+            accession_number: row.get("accession_number"),
+            form_type: row.get("form_type"),
+            filed_date: row.get("filed_date"),
+            fiscal_year: row.get("fiscal_year"),
+            fiscal_period: row.get("fiscal_period"),
+            report_date: row.get("report_date"),
+            reaction_date: row.get("reaction_date"),
+            reaction_close: row.get("reaction_close"),
+            return_5d_pct: row.get("return_5d_pct"),
+            return_30d_pct: row.get("return_30d_pct"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Duration, NaiveDate, Weekday};
+    use crate::tests::fixtures::migrated_pool;
+
+    /// Insert weekday closes from `start` for `days` trading days, rising a dollar a day from 100
+    async fn insert_trading_days(pool: &SqlitePool, start: NaiveDate, days: i64) {
+        let mut date = start;
+        let mut close = 100.0;
+        for _ in 0..days {
+            while matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                date += Duration::days(1);
+            }
+            sqlx::query(
+                "INSERT OR IGNORE INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (1, ?, ?, ?, ?, ?)"
+            )
+            .bind(date.to_string()).bind(close).bind(close).bind(close).bind(close)
+            .execute(pool).await.unwrap();
+            date += Duration::days(1);
+            close += 1.0;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reactions_start_on_next_trading_day_and_fill_in_as_prices_arrive() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'FILE', 'Filer Inc');
+             INSERT INTO sec_filings (id, stock_id, accession_number, form_type, filed_date, fiscal_year, report_date) VALUES
+                (1, 1, '0001-24-000001', '10-K', '2024-01-06', 2023, '2023-12-31'),
+                (2, 1, '0001-24-000002', '10-Q', '2024-05-01', 2024, '2024-03-31')"
+        )
+        .execute(&pool).await.unwrap();
+
+        // Monday Jan 8 through Feb 12: the 10-K, filed on a Saturday, reacts on Monday at 100
+        insert_trading_days(&pool, NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(), 26).await;
+        assert_eq!(backfill_filing_reactions(&pool, None).await.unwrap(), 1);
+
+        let reactions = load_filing_reactions(&pool, 1).await.unwrap();
+        assert_eq!(reactions.iter().map(|r| r.form_type.as_str()).collect::<Vec<_>>(), vec!["10-Q", "10-K"]);
+        // The 10-Q was filed after the last price
+        assert_eq!((reactions[0].reaction_date.as_deref(), reactions[0].reaction_close), (None, None));
+        let annual = &reactions[1];
+        assert_eq!((annual.reaction_date.as_deref(), annual.reaction_close), (Some("2024-01-08"), Some(100.0)));
+        assert!((annual.return_5d_pct.unwrap() - 5.0).abs() < 1e-9);
+        assert_eq!(annual.return_30d_pct, None);
+
+        // Thirty days on the 30-day return is filled in; running again changes nothing
+        insert_trading_days(&pool, NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(), 31).await;
+        assert_eq!(backfill_filing_reactions(&pool, Some(1)).await.unwrap(), 1);
+        let annual = load_filing_reactions(&pool, 1).await.unwrap().remove(1);
+        assert!((annual.return_30d_pct.unwrap() - 30.0).abs() < 1e-9);
+        assert_eq!(backfill_filing_reactions(&pool, Some(1)).await.unwrap(), 0);
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM filing_price_reactions").fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 1);
+    }
+}
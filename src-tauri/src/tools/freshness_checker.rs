@@ -8,207 +8,296 @@ use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{Semaphore, Mutex};
+use tracing::{error, info, warn};
 
+use crate::commands::tags::push_tag_filter;
+use crate::tools::company_profiles::store_sec_company_profile;
 use crate::tools::freshness_types::*;
+use crate::tools::freshness_policy::{load_freshness_policy, FreshnessPolicy};
+use crate::tools::refresh_estimates::estimate_refresh_durations;
+use crate::tools::refresh_runs::RefreshRunError;
+use crate::tools::screening_readiness::evaluate_screening_readiness;
+use crate::tools::dividends::store_dividend_history;
+use crate::tools::shares_history::{estimate_shares_from_market_cap, store_shares_outstanding_history};
 use crate::tools::sec_edgar_client::{SecEdgarClient, BalanceSheetData, IncomeStatementData, CashFlowData};
 
+/// Relative gap between `Revenues` and `RevenueFromContractWithCustomerExcludingAssessedTax` in
+/// one filing above which they are taken to cover different scopes, and the choice is logged
+const REVENUE_CONCEPT_DISAGREEMENT: f64 = 0.05;
+
+/// First fiscal year under ASC 606, from which the contract revenue concept is the full top line
+const ASC_606_FIRST_FISCAL_YEAR: i32 = 2018;
+
 pub struct DataStatusReader {
     pool: SqlitePool,
+    sec_base_url: String,
 }
 
 impl DataStatusReader {
     pub fn new(pool: SqlitePool) -> Self {
         Self {
             pool,
+            sec_base_url: "https://data.sec.gov".to_string(),
         }
     }
 
-    /// Check freshness of all data sources and generate comprehensive report using SEC filing-based freshness
-    pub async fn check_system_freshness(&self) -> Result<SystemFreshnessReport> {
-        // Use our new SEC filing-based freshness checker for financial data
-        self.check_financial_filing_freshness().await
+    /// Fetch Submissions and Company Facts from a different host (used by tests with a mock server)
+    pub fn with_sec_base_url(mut self, sec_base_url: impl Into<String>) -> Self {
+        self.sec_base_url = sec_base_url.into();
+        self
     }
 
-    /// Check financial data freshness using SEC Company Facts API (SIMPLE APPROACH)
-    pub async fn check_financial_filing_freshness(&self) -> Result<SystemFreshnessReport> {
-        println!("🔍 Checking financial data freshness and extracting missing data...");
-        println!("📅 Started at: {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
-        
-        let market_data = self.check_daily_prices_direct().await?;
+    /// Read-only freshness assessment of all data sources from what is stored locally.
+    /// Never contacts SEC or writes; extraction runs only as part of a data refresh.
+    pub async fn check_system_freshness(&self) -> Result<SystemFreshnessReport> {
+        let policy = load_freshness_policy(&self.pool).await?;
+        let today = Utc::now().date_naive();
+
+        let market_data = self.check_daily_prices_direct(&policy).await?;
+        let financial_data = self.check_stored_filings(&policy, today).await?;
+        let calculated_ratios = self.check_stored_ratios(&policy, today).await?;
+
+        let estimates = estimate_refresh_durations(&self.pool, &policy, today).await?;
+        let mut recommendations = Vec::new();
+        if market_data.status.needs_refresh() {
+            recommendations.push(RefreshRecommendation {
+                action: "Refresh market data".to_string(),
+                reason: market_data.message.clone(),
+                estimated_duration: format!("~{:.0} min ({})", estimates.market.expected_minutes, estimates.market.basis),
+                priority: market_data.refresh_priority.clone(),
+            });
+        }
+        if financial_data.status.needs_refresh() {
+            recommendations.push(RefreshRecommendation {
+                action: "Refresh financial statements".to_string(),
+                reason: financial_data.message.clone(),
+                estimated_duration: format!("~{:.0} min ({})", estimates.financials.expected_minutes, estimates.financials.basis),
+                priority: financial_data.refresh_priority.clone(),
+            });
+        }
+        if calculated_ratios.status.needs_refresh() {
+            recommendations.push(RefreshRecommendation {
+                action: "Backfill daily valuation ratios".to_string(),
+                reason: calculated_ratios.message.clone(),
+                estimated_duration: "~10 min".to_string(),
+                priority: calculated_ratios.refresh_priority.clone(),
+            });
+        }
 
-        // Step 1: Get S&P 500 stocks with CIKs (all stocks we should check)
-        let stocks_with_ciks = self.get_sp500_stocks_with_ciks(None).await?;
-        println!("📊 Processing {} S&P 500 stocks for financial data extraction", stocks_with_ciks.len());
-        println!("🔧 Using 10 concurrent threads with 10 requests/second rate limiting");
-        
-        // Step 2: Get ALL our filing dates from database (since 2016)
-        let our_all_dates = self.get_our_all_filing_dates().await?;
-        println!("✅ Found {} S&P 500 stocks with existing filing metadata", our_all_dates.len());
-        
-        // Step 3: Create rate-limited HTTP client
-        let (client, limiter) = self.create_rate_limited_client().await?;
-        
-        // Step 4: Process ALL stocks - get dates AND extract missing data
-        let (_sec_all_dates, total_records_stored) = self.get_sec_all_filing_dates_and_extract_data(&client, &limiter, &stocks_with_ciks).await?;
-
-        // Step 5: Generate final report
-        let processed_count = stocks_with_ciks.len();
-
-        println!("\n🎉 FINANCIAL DATA EXTRACTION COMPLETE!");
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!("📊 Total stocks processed: {}", processed_count);
-        println!("📈 Total 10-K filings stored: {}", total_records_stored);
-        println!("📅 Completion time: {}", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"));
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-
-        // Determine actual status based on results
-        let financial_status = if total_records_stored > 0 {
-            FreshnessStatus::Current  // Successfully stored new data
+        let statuses = [&market_data.status, &financial_data.status, &calculated_ratios.status];
+        let overall_status = if statuses.iter().any(|s| **s == FreshnessStatus::Missing) {
+            FreshnessStatus::Missing
+        } else if statuses.iter().any(|s| s.needs_refresh()) {
+            FreshnessStatus::Stale
         } else {
-            FreshnessStatus::Current  // All data already current
+            FreshnessStatus::Current
         };
 
-        let overall_status = FreshnessStatus::Current;
-
         Ok(SystemFreshnessReport {
             overall_status,
             market_data,
-            financial_data: DataFreshnessStatus {
-                data_source: "sec_edgar".to_string(),
-                status: financial_status,
-                latest_data_date: Some(chrono::Utc::now().date_naive().format("%Y-%m-%d").to_string()),
-                last_refresh: Some(chrono::Utc::now().to_rfc3339()),
-                staleness_days: Some(0),
-                records_count: stocks_with_ciks.len() as i64,
-                message: if total_records_stored > 0 {
-                    format!("✅ SUCCESS: Stored {} new 10-K filings from {} stocks", total_records_stored, processed_count)
-                } else {
-                    format!("✅ SUCCESS: All {} stocks already have current 10-K data", processed_count)
-                },
-                refresh_priority: RefreshPriority::Low,
-                data_summary: DataSummary {
-                    date_range: Some("2016-present (10-K annual filings only)".to_string()),
-                    stock_count: Some(stocks_with_ciks.len() as i64),
-                    data_types: vec!["10-K Annual Reports".to_string(), "Balance Sheets".to_string(), "Income Statements".to_string(), "Cash Flow Statements".to_string()],
-                    key_metrics: vec!["Annual financial statements".to_string()],
-                    completeness_score: Some(100.0),
-                },
-            },
-            calculated_ratios: DataFreshnessStatus {
-                data_source: "screening_readiness".to_string(),
-                status: FreshnessStatus::Current,
-                latest_data_date: None,
-                last_refresh: None,
-                staleness_days: None,
-                records_count: 0,
-                message: "All stocks have current 10-K data, ready for screening".to_string(),
-                refresh_priority: RefreshPriority::Low,
-                data_summary: DataSummary {
-                    date_range: None,
-                    stock_count: None,
-                    data_types: vec!["Piotroski F-Score".to_string(), "O'Shaughnessy Value".to_string()],
-                    key_metrics: vec!["Financial data freshness required".to_string()],
-                    completeness_score: None,
-                },
-            },
-            recommendations: vec![],  // All data current after refresh
-            screening_readiness: ScreeningReadiness {
-                valuation_analysis: true,  // All data current
-                blocking_issues: vec![],  // No blocking issues
-            },
+            financial_data,
+            calculated_ratios,
+            recommendations,
+            screening_readiness: evaluate_screening_readiness(&self.pool, &policy, today).await?,
             last_check: Utc::now().to_rfc3339(),
+            freshness_policy: policy,
         })
     }
 
-    /// Get ALL filing dates for each S&P 500 stock from our database
-    async fn get_our_all_filing_dates(&self) -> Result<HashMap<String, Vec<String>>> {
-        let query = r#"
-            SELECT 
-                s.cik,
-                sf.filed_date
+    /// Compare each S&P 500 stock's latest stored filing date against the policy
+    async fn check_stored_filings(&self, policy: &FreshnessPolicy, today: NaiveDate) -> Result<DataFreshnessStatus> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                (SELECT MAX(sf.filed_date) FROM sec_filings sf WHERE sf.stock_id = s.id) as latest_filed
             FROM stocks s
-            INNER JOIN sec_filings sf ON s.id = sf.stock_id
-            WHERE s.is_sp500 = 1 
-                AND s.cik IS NOT NULL 
-                AND sf.filed_date IS NOT NULL
-                AND sf.filed_date >= '2016-01-01'
-            ORDER BY s.cik, sf.filed_date
-        "#;
-        
-        let rows = sqlx::query(query).fetch_all(&self.pool).await?;
-        let mut results: HashMap<String, Vec<String>> = HashMap::new();
+            WHERE s.is_sp500 = 1
+                AND s.cik IS NOT NULL
+                AND s.cik != ''
+                AND s.cik != 'Unknown'
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total_stocks = rows.len() as i64;
+        let latest_per_stock: Vec<Option<NaiveDate>> = rows
+            .iter()
+            .map(|row| {
+                row.try_get::<Option<String>, _>("latest_filed")
+                    .ok()
+                    .flatten()
+                    .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+            })
+            .collect();
 
-        for row in rows {
-            let cik: String = row.get("cik");
-            let filed_date: String = row.get("filed_date");
-            
-            results.entry(cik).or_insert_with(Vec::new).push(filed_date);
-        }
-        
-        Ok(results)
+        let with_filings = latest_per_stock.iter().flatten().count() as i64;
+        let current_stocks = latest_per_stock
+            .iter()
+            .flatten()
+            .filter(|d| (today - **d).num_days() <= policy.financial_data.current_days)
+            .count() as i64;
+        let newest = latest_per_stock.iter().flatten().max().copied();
+        // Staleness is that of the most out-of-date stock that has filings at all
+        let staleness_days = latest_per_stock.iter().flatten().min().map(|d| (today - *d).num_days());
+
+        let (status, priority) = policy.financial_data.classify(staleness_days);
+        let message = if with_filings == 0 {
+            "No 10-K filings stored".to_string()
+        } else {
+            format!(
+                "{} of {} S&P 500 stocks have a 10-K filed in the last {} days",
+                current_stocks, total_stocks, policy.financial_data.current_days
+            )
+        };
+        let newest_str = newest.map(|d| d.format("%Y-%m-%d").to_string());
+
+        Ok(DataFreshnessStatus {
+            data_source: "sec_edgar".to_string(),
+            status,
+            latest_data_date: newest_str.clone(),
+            last_refresh: None,
+            staleness_days,
+            records_count: with_filings,
+            message,
+            refresh_priority: priority,
+            data_summary: DataSummary {
+                date_range: newest_str,
+                stock_count: Some(total_stocks),
+                data_types: vec!["10-K Annual Reports".to_string(), "Balance Sheets".to_string(), "Income Statements".to_string(), "Cash Flow Statements".to_string()],
+                key_metrics: vec![format!("{} stocks with filings", with_filings)],
+                completeness_score: if total_stocks > 0 { Some(current_stocks as f32 / total_stocks as f32 * 100.0) } else { None },
+            },
+        })
+    }
+
+    /// Check the latest stored daily valuation ratios
+    async fn check_stored_ratios(&self, policy: &FreshnessPolicy, today: NaiveDate) -> Result<DataFreshnessStatus> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) as total_records, MAX(date) as latest_date, COUNT(DISTINCT stock_id) as unique_stocks
+             FROM daily_valuation_ratios"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let total_records: i64 = row.get("total_records");
+        let unique_stocks: i64 = row.get("unique_stocks");
+        let latest_date = row
+            .try_get::<Option<String>, _>("latest_date")
+            .ok()
+            .flatten()
+            .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+        let staleness_days = latest_date.map(|d| (today - d).num_days());
+        let latest_date_str = latest_date.map(|d| d.format("%Y-%m-%d").to_string());
+
+        let (status, priority) = policy.calculated_ratios.classify(staleness_days);
+        let message = match latest_date_str.as_deref() {
+            Some(date) => format!("Latest ratios: {} ({} stocks)", date, unique_stocks),
+            None => "No computed valuation ratios".to_string(),
+        };
+
+        Ok(DataFreshnessStatus {
+            data_source: "daily_valuation_ratios".to_string(),
+            status,
+            latest_data_date: latest_date_str.clone(),
+            last_refresh: None,
+            staleness_days,
+            records_count: total_records,
+            message,
+            refresh_priority: priority,
+            data_summary: DataSummary {
+                date_range: latest_date_str,
+                stock_count: Some(unique_stocks),
+                data_types: vec!["P/E, P/S, EV/S".to_string()],
+                key_metrics: vec![format!("{} records", total_records)],
+                completeness_score: None,
+            },
+        })
     }
 
     /// Get S&P 500 stocks with CIKs (optionally filtered by CIK)
     pub async fn get_sp500_stocks_with_ciks(&self, only_cik: Option<&String>) -> Result<Vec<(i64, String, String)>> {
-        let (query, bind_cik) = if let Some(cik) = only_cik {
-            // Filtered query for single CIK
-            (r#"
-                SELECT s.id, s.cik, s.symbol
-                FROM stocks s
-                WHERE s.is_sp500 = 1
-                    AND s.cik = ?
-                    AND s.cik IS NOT NULL
-                    AND s.cik != ''
-                    AND s.cik != 'Unknown'
-                ORDER BY s.symbol
-            "#, Some(cik))
-        } else {
-            // All stocks query
-            (r#"
-                SELECT s.id, s.cik, s.symbol
-                FROM stocks s
-                WHERE s.is_sp500 = 1
-                    AND s.cik IS NOT NULL
-                    AND s.cik != ''
-                    AND s.cik != 'Unknown'
-                ORDER BY s.symbol
-            "#, None)
-        };
+        let mut stocks = self.get_stocks_with_ciks(&StockFilter::Sp500).await?;
+        if let Some(cik) = only_cik {
+            stocks.retain(|(_, stock_cik, _)| stock_cik == cik);
+        }
+        Ok(stocks)
+    }
 
-        let mut query_builder = sqlx::query(query);
-        if let Some(cik) = bind_cik {
-            query_builder = query_builder.bind(cik);
+    /// (id, CIK, symbol) of the stocks matching `filter` that have a CIK, by symbol
+    pub async fn get_stocks_with_ciks(&self, filter: &StockFilter) -> Result<Vec<(i64, String, String)>> {
+        let mut query = String::from(
+            "SELECT s.id, s.cik, s.symbol
+             FROM stocks s
+             WHERE s.cik IS NOT NULL
+                 AND s.cik != ''
+                 AND s.cik != 'Unknown'"
+        );
+        let mut params: Vec<String> = Vec::new();
+        match filter {
+            StockFilter::All => {}
+            StockFilter::Sp500 => query.push_str(" AND s.is_sp500 = 1"),
+            StockFilter::Symbols(symbols) => {
+                let symbols: Vec<String> = symbols
+                    .iter()
+                    .map(|symbol| symbol.trim().to_uppercase())
+                    .filter(|symbol| !symbol.is_empty())
+                    .collect();
+                if symbols.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let placeholders = symbols.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+                query.push_str(&format!(" AND s.symbol IN ({})", placeholders));
+                params.extend(symbols);
+            }
+            StockFilter::Tag(tag) => {
+                if tag.trim().is_empty() {
+                    return Ok(Vec::new());
+                }
+                push_tag_filter(&mut query, &mut params, "s.id", std::slice::from_ref(tag));
+            }
         }
+        query.push_str(" ORDER BY s.symbol");
 
+        let mut query_builder = sqlx::query(&query);
+        for param in &params {
+            query_builder = query_builder.bind(param);
+        }
         let rows = query_builder.fetch_all(&self.pool).await?;
-        let mut stocks = Vec::new();
 
-        for row in rows {
-            let stock_id: i64 = row.get("id");
-            let cik: String = row.get("cik");
-            let symbol: String = row.get("symbol");
-            stocks.push((stock_id, cik, symbol));
-        }
-        
-        Ok(stocks)
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("id"), row.get("cik"), row.get("symbol")))
+            .collect())
     }
 
     /// Public unified entry point: process a provided list of stocks using the unified pipeline
     pub async fn run_unified_financials_for_stocks(
         &self,
         stocks: &[(i64, String, String)]
-    ) -> Result<i64> {
+    ) -> Result<(i64, Vec<RefreshRunError>)> {
         // Create rate-limited client
         let (client, limiter) = self.create_rate_limited_client().await?;
         // Run unified extraction/store
-        let (_sec_all_dates, total_records_stored) = self
+        let (_sec_all_dates, total_records_stored, error_list) = self
             .get_sec_all_filing_dates_and_extract_data(&client, &limiter, stocks)
             .await?;
-        Ok(total_records_stored)
+
+        let errors = error_list
+            .into_iter()
+            .map(|(symbol, cik, message)| RefreshRunError {
+                data_source: "financial_statements".to_string(),
+                symbol: Some(symbol),
+                cik: Some(cik),
+                message,
+            })
+            .collect();
+        Ok((total_records_stored, errors))
     }
 
     /// Check daily_prices table directly
-    async fn check_daily_prices_direct(&self) -> Result<DataFreshnessStatus> {
+    async fn check_daily_prices_direct(&self, policy: &FreshnessPolicy) -> Result<DataFreshnessStatus> {
         let query = r#"
             SELECT
                 COUNT(*) as total_records,
@@ -232,27 +321,15 @@ impl DataStatusReader {
             None => None,
         };
         
-        let status = match (latest_date, staleness_days) {
-            (None, _) => FreshnessStatus::Missing,
-            (_, Some(days)) if days <= 7 => FreshnessStatus::Current,
-            (_, Some(days)) if days <= 30 => FreshnessStatus::Stale,
-            (_, Some(_)) => FreshnessStatus::Stale, // Consider anything > 30 days as stale
-            _ => FreshnessStatus::Current,
-        };
+        let (status, priority) = policy.market_data.classify(staleness_days);
         
         let message = match status {
             FreshnessStatus::Current => format!("Latest data: {} ({} records)", latest_date_str.as_deref().unwrap_or("N/A"), total_records),
-            FreshnessStatus::Stale => format!("Latest data: {} days old ({} records)", staleness_days.unwrap_or(0), total_records),
+            FreshnessStatus::Stale => format!("Latest data: {} days old, stale after {} days ({} records)", staleness_days.unwrap_or(0), policy.market_data.current_days, total_records),
             FreshnessStatus::Missing => "No market data available".to_string(),
             FreshnessStatus::Error => "Error accessing market data".to_string(),
         };
 
-        let priority = match status {
-            FreshnessStatus::Current => RefreshPriority::Low,
-            FreshnessStatus::Stale => RefreshPriority::Medium,
-            FreshnessStatus::Missing | FreshnessStatus::Error => RefreshPriority::Critical,
-        };
-
         Ok(DataFreshnessStatus {
             data_source: "daily_prices".to_string(),
             status,
@@ -292,7 +369,7 @@ impl DataStatusReader {
         client: &Client,
         limiter: &Arc<RateLimiter<governor::state::direct::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock>>,
         stocks: &[(i64, String, String)]  // (stock_id, cik, symbol)
-    ) -> Result<(HashMap<String, Vec<String>>, i64)> {
+    ) -> Result<(HashMap<String, Vec<String>>, i64, Vec<(String, String, String)>)> {
         let semaphore = Arc::new(Semaphore::new(10)); // 10 concurrent workers
         let results = Arc::new(Mutex::new(HashMap::new()));
         let total_records = Arc::new(Mutex::new(0i64));
@@ -311,6 +388,7 @@ impl DataStatusReader {
             let total_records = total_records.clone();
             let error_reports = error_reports.clone();
             let pool = self.pool.clone();  // Clone pool for database access
+            let sec_base_url = self.sec_base_url.clone();
             let cik = cik.clone();
             let symbol = symbol.clone();
             let stock_id = *stock_id;
@@ -318,7 +396,7 @@ impl DataStatusReader {
             let handle = tokio::spawn(async move {
                 let _permit = permit; // Move permit into task
                 
-                match Self::get_all_sec_filings_for_cik_and_extract_data(&client, &limiter, &cik, stock_id, &symbol, &pool).await {
+                match Self::get_all_sec_filings_for_cik_and_extract_data(&client, &limiter, &sec_base_url, &cik, stock_id, &symbol, &pool).await {
                     Ok((sec_dates, records_stored)) => {
                         if !sec_dates.is_empty() {
                             let mut res = results.lock().await;
@@ -329,7 +407,7 @@ impl DataStatusReader {
                         }
                     }
                     Err(e) => {
-                        println!("❌ Failed {} (CIK: {}): {}", symbol, cik, e);
+                        error!(%symbol, %cik, "Failed {} (CIK: {}): {}", symbol, cik, e);
                         let mut errors = error_reports.lock().await;
                         errors.push((symbol, cik, e.to_string()));
                     }
@@ -348,10 +426,10 @@ impl DataStatusReader {
         let total_records_count = Arc::try_unwrap(total_records).map_err(|_| anyhow!("Failed to unwrap total_records Arc"))?.into_inner();
         let error_list = Arc::try_unwrap(error_reports).map_err(|_| anyhow!("Failed to unwrap error_reports Arc"))?.into_inner();
         
-        // Store error reports for final summary
-        Self::store_error_reports(error_list).await?;
+        // Log error reports; the caller records them with the refresh run
+        Self::store_error_reports(&error_list).await?;
         
-        Ok((results_map, total_records_count))
+        Ok((results_map, total_records_count, error_list))
     }
 
     /// Get ALL SEC filing dates for a single CIK AND extract missing financial data - HYBRID API APPROACH
@@ -359,6 +437,7 @@ impl DataStatusReader {
     async fn get_all_sec_filings_for_cik_and_extract_data(
         client: &Client,
         limiter: &Arc<RateLimiter<governor::state::direct::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock>>,
+        sec_base_url: &str,
         cik: &str,
         stock_id: i64,
         symbol: &str,
@@ -369,7 +448,7 @@ impl DataStatusReader {
         limiter.until_ready().await;
 
         let cik_padded = format!("{:0>10}", cik);
-        let submissions_url = format!("https://data.sec.gov/submissions/CIK{}.json", cik_padded);
+        let submissions_url = format!("{}/submissions/CIK{}.json", sec_base_url, cik_padded);
 
         let submissions_response = client
             .get(&submissions_url)
@@ -384,6 +463,11 @@ impl DataStatusReader {
 
         let submissions_json: serde_json::Value = submissions_response.json().await?;
 
+        // Company profile fields ride along in the same response; a failed write shouldn't stop the refresh
+        if let Err(e) = store_sec_company_profile(pool, stock_id, &submissions_json).await {
+            warn!("Failed to store company profile for {}: {}", symbol, e);
+        }
+
         // Extract 10-K metadata from Submissions API
         let mut metadata_vec = Vec::new();
         if let Some(recent) = submissions_json.get("filings").and_then(|f| f.get("recent")) {
@@ -415,7 +499,7 @@ impl DataStatusReader {
             }
         }
 
-        println!("  📋 {} (CIK {}): Found {} 10-K/10-K/A filings from Submissions API", symbol, cik, metadata_vec.len());
+        info!(%symbol, %cik, "{} (CIK {}): Found {} 10-K/10-K/A filings from Submissions API", symbol, cik, metadata_vec.len());
 
         // Deduplicate: if multiple filings exist for same report_date, prefer amendments (10-K/A)
         // and use latest filing_date as tiebreaker
@@ -445,7 +529,7 @@ impl DataStatusReader {
         }
 
         let metadata_vec: Vec<(String, String, String, String)> = deduped_map.into_values().collect();
-        println!("  📊 {} (CIK {}): After deduplication: {} unique filings", symbol, cik, metadata_vec.len());
+        info!(%symbol, %cik, "{} (CIK {}): After deduplication: {} unique filings", symbol, cik, metadata_vec.len());
 
         // Collect all filing dates for return value
         let filing_dates: Vec<String> = metadata_vec.iter().map(|(_, filed, _, _)| filed.clone()).collect();
@@ -453,7 +537,7 @@ impl DataStatusReader {
         // STEP 2: Fetch Company Facts API for financial data (rate limited)
         limiter.until_ready().await;
 
-        let facts_url = format!("https://data.sec.gov/api/xbrl/companyfacts/CIK{}.json", cik_padded);
+        let facts_url = format!("{}/api/xbrl/companyfacts/CIK{}.json", sec_base_url, cik_padded);
 
         let facts_response = client
             .get(&facts_url)
@@ -468,6 +552,11 @@ impl DataStatusReader {
 
         let company_facts: serde_json::Value = facts_response.json().await?;
 
+        // Share counts as of every fact date, for point-in-time market caps
+        if let Err(e) = store_shares_outstanding_history(pool, stock_id, &company_facts).await {
+            warn!("Failed to store share count history for {}: {}", symbol, e);
+        }
+
         // STEP 3: Extract and store data for each 10-K filing
         let mut records_stored = 0;
 
@@ -485,23 +574,24 @@ impl DataStatusReader {
             let fiscal_year = match NaiveDate::parse_from_str(&report_date, "%Y-%m-%d") {
                 Ok(date) => date.year(),
                 Err(_) => {
-                    println!("    ⚠️ Skipping filing {}: invalid report_date {}", accession_number, report_date);
+                    warn!("Skipping filing {}: invalid report_date {}", accession_number, report_date);
                     continue;
                 }
             };
 
             // Extract data for this specific accession number
-            let balance_data = match Self::extract_balance_sheet_for_filing(
+            let balance_data = match Self::balance_sheet_with_share_estimate(
+                pool,
                 &company_facts,
                 &accession_number,
                 stock_id,
                 symbol,
                 &report_date,
                 fiscal_year
-            ) {
+            ).await {
                 Ok(data) => data,
                 Err(e) => {
-                    println!("    ⚠️  Skipping filing {}: {}", accession_number, e);
+                    warn!("Skipping filing {}: {}", accession_number, e);
                     continue;
                 }
             };
@@ -516,7 +606,7 @@ impl DataStatusReader {
             ) {
                 Ok(data) => data,
                 Err(e) => {
-                    println!("    ⚠️  Skipping filing {}: {}", accession_number, e);
+                    warn!("Skipping filing {}: {}", accession_number, e);
                     continue;
                 }
             };
@@ -531,7 +621,7 @@ impl DataStatusReader {
             ) {
                 Ok(data) => data,
                 Err(e) => {
-                    println!("    ⚠️  Skipping filing {}: {}", accession_number, e);
+                    warn!("Skipping filing {}: {}", accession_number, e);
                     continue;
                 }
             };
@@ -559,18 +649,23 @@ impl DataStatusReader {
             ).await {
                 Ok(_) => {
                     records_stored += 1;
-                    println!("    ✅ Stored {} filing: {} ({})", form_type, metadata.report_date, metadata.accession_number);
+                    info!("Stored {} filing: {} ({})", form_type, metadata.report_date, metadata.accession_number);
                 }
                 Err(e) => {
-                    println!("    ⚠️  Failed to store {}: {}", metadata.accession_number, e);
+                    warn!("Failed to store {}: {}", metadata.accession_number, e);
                 }
             }
         }
 
+        // After the statements, so fiscal years without per-share facts can fall back to dividends paid
+        if let Err(e) = store_dividend_history(pool, stock_id, &company_facts).await {
+            warn!("Failed to store dividend history for {}: {}", symbol, e);
+        }
+
         if records_stored > 0 {
-            println!("✅ {} (CIK {}): Stored {} complete 10-K filings", symbol, cik, records_stored);
+            info!(%symbol, %cik, records_stored, "{} (CIK {}): Stored {} complete 10-K filings", symbol, cik, records_stored);
         } else {
-            println!("✅ {} (CIK {}): Already has all 10-K financial data (current)", symbol, cik);
+            info!(%symbol, %cik, "{} (CIK {}): Already has all 10-K financial data (current)", symbol, cik);
         }
 
         Ok((filing_dates, records_stored))
@@ -587,10 +682,10 @@ impl DataStatusReader {
     }
 
     /// Store error reports for final summary
-    async fn store_error_reports(errors: Vec<(String, String, String)>) -> Result<()> {
+    async fn store_error_reports(errors: &[(String, String, String)]) -> Result<()> {
         // Store errors for final summary
         for (symbol, cik, error) in errors {
-            println!("❌ Error processing {} ({}): {}", symbol, cik, error);
+            error!(%symbol, %cik, "Error processing {} ({}): {}", symbol, cik, error);
         }
         Ok(())
     }
@@ -651,6 +746,42 @@ impl DataStatusReader {
         None
     }
 
+    /// Helper: Revenue for one filing. `Revenues` is taken first, except that from fiscal 2018 on
+    /// (ASC 606) a materially different `RevenueFromContractWithCustomerExcludingAssessedTax` wins:
+    /// filers reporting both sometimes tag a narrower scope as `Revenues`.
+    fn find_revenue_for_accession(
+        facts: &serde_json::Value,
+        accession_number: &str,
+        symbol: &str,
+        fiscal_year: i32
+    ) -> Option<f64> {
+        let revenues = Self::find_value_for_accession(facts, "Revenues", accession_number);
+        let contract_revenue = Self::find_value_for_accession(facts, "RevenueFromContractWithCustomerExcludingAssessedTax", accession_number);
+
+        let revenue = match (revenues, contract_revenue) {
+            (Some(revenues), Some(contract_revenue)) => {
+                let gap = (revenues - contract_revenue).abs() / revenues.abs().max(contract_revenue.abs());
+                if gap > REVENUE_CONCEPT_DISAGREEMENT {
+                    let prefer_contract = fiscal_year >= ASC_606_FIRST_FISCAL_YEAR;
+                    warn!(
+                        %symbol, fiscal_year, revenues, contract_revenue,
+                        "Revenue concepts disagree by {:.1}% for {} FY{}; using {}",
+                        gap * 100.0,
+                        symbol,
+                        fiscal_year,
+                        if prefer_contract { "RevenueFromContractWithCustomerExcludingAssessedTax" } else { "Revenues" }
+                    );
+                    Some(if prefer_contract { contract_revenue } else { revenues })
+                } else {
+                    Some(revenues)
+                }
+            }
+            (revenues, contract_revenue) => revenues.or(contract_revenue),
+        };
+
+        revenue.or_else(|| Self::find_value_for_accession(facts, "SalesRevenueNet", accession_number))
+    }
+
     /// Helper function to extract a value for a specific fiscal year from a field
     fn try_extract_field_for_fiscal_year(
         taxonomy: &serde_json::Value,
@@ -748,12 +879,39 @@ impl DataStatusReader {
                 .or_else(|| Self::find_value_for_accession(facts, "LongTermDebt", accession_number)),
             current_assets: Self::find_value_for_accession(facts, "AssetsCurrent", accession_number),
             current_liabilities: Self::find_value_for_accession(facts, "LiabilitiesCurrent", accession_number),
+            inventory: Self::find_value_for_accession(facts, "InventoryNet", accession_number),
             share_repurchases: Self::find_value_for_accession(facts, "StockRepurchasedDuringPeriodValue", accession_number)
                 .or_else(|| Self::find_value_for_accession(facts, "TreasuryStockValueAcquiredCostMethod", accession_number)),
             shares_outstanding,
         })
     }
 
+    /// Balance sheet for a 10-K filing. When none of the XBRL share count fields are reported,
+    /// shares_outstanding falls back to the provider market cap over the close nearest the
+    /// report date and is flagged as estimated.
+    async fn balance_sheet_with_share_estimate(
+        pool: &SqlitePool,
+        company_facts: &serde_json::Value,
+        accession_number: &str,
+        stock_id: i64,
+        symbol: &str,
+        report_date: &str,
+        fiscal_year: i32
+    ) -> Result<BalanceSheetData> {
+        let mut balance_data = Self::extract_balance_sheet_for_filing(
+            company_facts, accession_number, stock_id, symbol, report_date, fiscal_year
+        )?;
+        if balance_data.shares_outstanding.is_none() {
+            let estimate = estimate_shares_from_market_cap(pool, stock_id, report_date).await?;
+            if let Some(shares) = estimate {
+                warn!(%symbol, fiscal_year, shares, "No share count filed for {} FY{}; estimated {:.0} from market cap", symbol, fiscal_year, shares);
+            }
+            balance_data.shares_outstanding = estimate;
+            balance_data.shares_outstanding_estimated = estimate.is_some();
+        }
+        Ok(balance_data)
+    }
+
     /// Extract income statement data for a specific 10-K filing (by accession number)
     fn extract_income_statement_for_filing(
         company_facts: &serde_json::Value,
@@ -774,9 +932,7 @@ impl DataStatusReader {
             report_date: NaiveDate::parse_from_str(report_date, "%Y-%m-%d")?,
             fiscal_year,
             period_type: "FY".to_string(),  // 10-K = annual
-            revenue: Self::find_value_for_accession(facts, "Revenues", accession_number)
-                .or_else(|| Self::find_value_for_accession(facts, "RevenueFromContractWithCustomerExcludingAssessedTax", accession_number))
-                .or_else(|| Self::find_value_for_accession(facts, "SalesRevenueNet", accession_number)),
+            revenue: Self::find_revenue_for_accession(facts, accession_number, symbol, fiscal_year),
             net_income: Self::find_value_for_accession(facts, "NetIncomeLoss", accession_number)
                 .or_else(|| Self::find_value_for_accession(facts, "ProfitLoss", accession_number)),
             operating_income: Self::find_value_for_accession(facts, "OperatingIncomeLoss", accession_number),
@@ -785,6 +941,8 @@ impl DataStatusReader {
                 .or_else(|| Self::find_value_for_accession(facts, "CostOfGoodsAndServicesSold", accession_number)),
             interest_expense: Self::find_value_for_accession(facts, "InterestExpense", accession_number),
             tax_expense: Self::find_value_for_accession(facts, "IncomeTaxExpenseBenefit", accession_number),
+            pretax_income: Self::find_value_for_accession(facts, "IncomeLossFromContinuingOperationsBeforeIncomeTaxesExtraordinaryItemsNoncontrollingInterest", accession_number)
+                .or_else(|| Self::find_value_for_accession(facts, "IncomeLossFromContinuingOperationsBeforeIncomeTaxesMinorityInterestAndIncomeLossFromEquityMethodInvestments", accession_number)),
             shares_basic: Self::find_value_for_accession(facts, "WeightedAverageNumberOfSharesOutstandingBasic", accession_number),
             shares_diluted: Self::find_value_for_accession(facts, "WeightedAverageNumberOfDilutedSharesOutstanding", accession_number),
         })
@@ -819,6 +977,9 @@ impl DataStatusReader {
             operating_cash_flow: Self::find_value_for_accession(facts, "NetCashProvidedByUsedInOperatingActivities", accession_number),
             investing_cash_flow: Self::find_value_for_accession(facts, "NetCashProvidedByUsedInInvestingActivities", accession_number),
             financing_cash_flow: Self::find_value_for_accession(facts, "NetCashProvidedByUsedInFinancingActivities", accession_number),
+            capital_expenditures: Self::find_value_for_accession(facts, "PaymentsToAcquirePropertyPlantAndEquipment", accession_number)
+                .or_else(|| Self::find_value_for_accession(facts, "PaymentsToAcquireProductiveAssets", accession_number))
+                .or_else(|| Self::find_value_for_accession(facts, "PaymentsForCapitalImprovements", accession_number)),
         })
     }
 }
@@ -827,6 +988,7 @@ impl DataStatusReader {
 mod tests {
     use super::*;
     use sqlx::SqlitePool;
+    use crate::tests::fixtures::migrated_pool;
 
     /// Test helper to create a test database pool
     async fn create_test_pool() -> SqlitePool {
@@ -857,7 +1019,7 @@ mod tests {
             ("MSFT".to_string(), "0000000002".to_string(), "Another error".to_string()),
         ];
         
-        let result = DataStatusReader::store_error_reports(errors).await;
+        let result = DataStatusReader::store_error_reports(&errors).await;
         assert!(result.is_ok());
     }
 
@@ -896,6 +1058,119 @@ mod tests {
         assert!(FreshnessStatus::Error.needs_refresh());
     }
 
+    #[test]
+    fn test_cash_flow_capex_falls_back_to_alternative_concept() {
+        let facts = serde_json::json!({
+            "facts": { "us-gaap": {
+                "NetCashProvidedByUsedInOperatingActivities": { "units": { "USD": [
+                    { "accn": "0000000001-24-000001", "val": 500.0 }
+                ]}},
+                // No PaymentsToAcquirePropertyPlantAndEquipment for this filer
+                "PaymentsToAcquireProductiveAssets": { "units": { "USD": [
+                    { "accn": "0000000001-24-000001", "val": 120.0 }
+                ]}}
+            }}
+        });
+
+        let cash_flow = DataStatusReader::extract_cash_flow_for_filing(&facts, "0000000001-24-000001", 1, "TEST", "2023-12-31", 2023).unwrap();
+        assert_eq!(cash_flow.capital_expenditures, Some(120.0));
+        assert_eq!(cash_flow.free_cash_flow(), Some(380.0));
+    }
+
+    #[test]
+    fn test_revenue_prefers_contract_revenue_when_concepts_disagree_after_asc_606() {
+        let facts = serde_json::json!({
+            "facts": { "us-gaap": {
+                "Revenues": { "units": { "USD": [
+                    { "accn": "0000000001-18-000001", "val": 1000.0 },
+                    { "accn": "0000000001-23-000001", "val": 900.0 },
+                    { "accn": "0000000001-24-000001", "val": 1000.0 }
+                ]}},
+                "RevenueFromContractWithCustomerExcludingAssessedTax": { "units": { "USD": [
+                    { "accn": "0000000001-18-000001", "val": 800.0 },
+                    { "accn": "0000000001-23-000001", "val": 1200.0 },
+                    { "accn": "0000000001-24-000001", "val": 1010.0 }
+                ]}}
+            }}
+        });
+        let revenue = |accession_number: &str, fiscal_year: i32| {
+            let report_date = format!("{}-12-31", fiscal_year);
+            DataStatusReader::extract_income_statement_for_filing(&facts, accession_number, 1, "TEST", &report_date, fiscal_year)
+                .unwrap()
+                .revenue
+        };
+
+        // Pre-ASC 606 keeps Revenues; afterwards a material disagreement takes contract revenue
+        assert_eq!(revenue("0000000001-18-000001", 2017), Some(1000.0));
+        assert_eq!(revenue("0000000001-23-000001", 2022), Some(1200.0));
+        // 1% apart is the same figure; Revenues as before
+        assert_eq!(revenue("0000000001-24-000001", 2023), Some(1000.0));
+    }
+
+    #[tokio::test]
+    async fn test_missing_share_counts_fall_back_to_market_cap_estimate() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'NOSH', 'No Shares Inc')")
+            .execute(&pool).await.unwrap();
+        // Provider market caps imply 2M shares at the year end; the later day's is ignored
+        for (date, close, market_cap) in [("2023-12-29", 50.0, 1e8), ("2024-01-05", 55.0, 1.21e8), ("2024-03-28", 60.0, 1.5e8)] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price, market_cap)
+                 VALUES (1, ?, ?, ?, ?, ?, ?)"
+            )
+            .bind(date).bind(close).bind(close).bind(close).bind(close).bind(market_cap)
+            .execute(&pool).await.unwrap();
+        }
+
+        // None of CommonStockSharesOutstanding, EntityCommonStockSharesOutstanding or the weighted average
+        let accession = "0000000001-24-000001";
+        let facts = serde_json::json!({
+            "facts": { "us-gaap": {
+                "Revenues": { "units": { "USD": [{ "accn": accession, "val": 4e7 }] }},
+                "NetIncomeLoss": { "units": { "USD": [{ "accn": accession, "val": 5e6 }] }},
+                "StockholdersEquity": { "units": { "USD": [{ "accn": accession, "val": 2e7 }] }}
+            }}
+        });
+        assert_eq!(DataStatusReader::extract_shares_outstanding_for_fiscal_year(&facts, 2023, "NOSH"), None);
+
+        let balance = DataStatusReader::balance_sheet_with_share_estimate(&pool, &facts, accession, 1, "NOSH", "2023-12-31", 2023)
+            .await
+            .unwrap();
+        assert_eq!((balance.shares_outstanding, balance.shares_outstanding_estimated), (Some(2e6), true));
+
+        // The flag follows the share count into the ratios built on it
+        let income = DataStatusReader::extract_income_statement_for_filing(&facts, accession, 1, "NOSH", "2023-12-31", 2023).unwrap();
+        let cash_flow = DataStatusReader::extract_cash_flow_for_filing(&facts, accession, 1, "NOSH", "2023-12-31", 2023).unwrap();
+        let metadata = crate::tools::sec_edgar_client::FilingMetadata {
+            accession_number: accession.to_string(),
+            form_type: "10-K".to_string(),
+            filing_date: "2024-02-15".to_string(),
+            fiscal_period: "FY".to_string(),
+            report_date: "2023-12-31".to_string(),
+        };
+        SecEdgarClient::new(pool.clone())
+            .store_filing_atomic(1, "NOSH", &metadata, 2023, "2023-12-31", &balance, &income, &cash_flow)
+            .await
+            .unwrap();
+        crate::analysis::valuation_ratios::backfill_daily_ratios(&pool, 1).await.unwrap();
+
+        let ratios = crate::analysis::valuation_ratios::load_daily_valuation_ratios(&pool, "NOSH", "2024-01-01", "2024-12-31")
+            .await
+            .unwrap();
+        assert_eq!(ratios.len(), 2);
+        assert!(ratios.iter().all(|r| r.is_estimated));
+        assert_eq!((ratios[1].market_cap, ratios[1].ps_ratio_ttm), (Some(1.2e8), Some(3.0)));
+
+        let provenance = crate::analysis::ratio_provenance::calculate_ratio_provenance(
+            &pool, 1, crate::analysis::ratio_provenance::ProvenanceRatio::Pe, "2024-03-28"
+        )
+        .await
+        .unwrap();
+        assert!(provenance.is_estimated);
+        assert_eq!(provenance.value, Some(24.0));
+    }
+
     #[test]
     fn test_refresh_priority_ordering() {
         assert!(RefreshPriority::Low < RefreshPriority::Medium);
@@ -958,6 +1233,7 @@ mod tests {
             },
         };
 
+        let ready = ScreenReadiness { ready: true, blocking_issues: vec![] };
         let report = SystemFreshnessReport {
             overall_status: FreshnessStatus::Current,
             market_data: market_data.clone(),
@@ -965,10 +1241,15 @@ mod tests {
             calculated_ratios: market_data,
             recommendations: vec![],
             screening_readiness: ScreeningReadiness {
-                valuation_analysis: true,
+                valuation_analysis: ready.clone(),
+                piotroski_screening: ready.clone(),
+                oshaughnessy_screening: ready.clone(),
+                graham_screening: ready.clone(),
+                ps_screening: ready,
                 blocking_issues: vec![],
             },
             last_check: "2024-01-01T00:00:00Z".to_string(),
+            freshness_policy: FreshnessPolicy::default(),
         };
 
         assert_eq!(report.overall_status, FreshnessStatus::Current);
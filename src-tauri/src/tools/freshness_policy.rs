@@ -0,0 +1,140 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::tools::freshness_types::{FreshnessStatus, RefreshPriority};
+
+/// metadata table key holding the JSON-encoded policy
+const FRESHNESS_POLICY_KEY: &str = "freshness_policy";
+
+/// Age limits (in days) for one data source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FreshnessThreshold {
+    /// Data this old or newer is Current
+    pub current_days: i64,
+    /// Older than current_days is Stale. Screening readiness wants recent data within this window.
+    pub stale_days: i64,
+}
+
+impl FreshnessThreshold {
+    pub fn classify(&self, staleness_days: Option<i64>) -> (FreshnessStatus, RefreshPriority) {
+        match staleness_days {
+            None => (FreshnessStatus::Missing, RefreshPriority::Critical),
+            Some(days) if days <= self.current_days => (FreshnessStatus::Current, RefreshPriority::Low),
+            Some(_) => (FreshnessStatus::Stale, RefreshPriority::Medium),
+        }
+    }
+}
+
+/// Per-source thresholds used when classifying data freshness
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FreshnessPolicy {
+    pub market_data: FreshnessThreshold,
+    pub financial_data: FreshnessThreshold,
+    pub calculated_ratios: FreshnessThreshold,
+}
+
+impl Default for FreshnessPolicy {
+    fn default() -> Self {
+        Self {
+            market_data: FreshnessThreshold { current_days: 7, stale_days: 30 },
+            // 10-K filings arrive yearly; allow for the filing window after fiscal year end
+            financial_data: FreshnessThreshold { current_days: 400, stale_days: 730 },
+            calculated_ratios: FreshnessThreshold { current_days: 7, stale_days: 30 },
+        }
+    }
+}
+
+impl FreshnessPolicy {
+    pub fn validate(&self) -> Result<()> {
+        for (name, threshold) in [
+            ("market_data", &self.market_data),
+            ("financial_data", &self.financial_data),
+            ("calculated_ratios", &self.calculated_ratios),
+        ] {
+            if threshold.current_days < 0 {
+                return Err(anyhow!("{}: current_days must not be negative", name));
+            }
+            if threshold.stale_days < threshold.current_days {
+                return Err(anyhow!("{}: stale_days must be at least current_days", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stored policy, or the defaults when none has been saved (or it can't be parsed)
+pub async fn load_freshness_policy(pool: &SqlitePool) -> Result<FreshnessPolicy> {
+    let row = sqlx::query("SELECT value FROM metadata WHERE key = ?")
+        .bind(FRESHNESS_POLICY_KEY)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row
+        .and_then(|row| serde_json::from_str(&row.get::<String, _>("value")).ok())
+        .unwrap_or_default())
+}
+
+pub async fn save_freshness_policy(pool: &SqlitePool, policy: &FreshnessPolicy) -> Result<()> {
+    policy.validate()?;
+
+    sqlx::query(
+        "INSERT INTO metadata (key, value, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(FRESHNESS_POLICY_KEY)
+    .bind(serde_json::to_string(policy)?)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn metadata_pool() -> SqlitePool {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE metadata (key TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at DATETIME DEFAULT CURRENT_TIMESTAMP)")
+            .execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_default_market_thresholds_match_previous_behavior() {
+        let market = FreshnessPolicy::default().market_data;
+        assert_eq!(market.classify(Some(7)), (FreshnessStatus::Current, RefreshPriority::Low));
+        assert_eq!(market.classify(Some(8)), (FreshnessStatus::Stale, RefreshPriority::Medium));
+        assert_eq!(market.classify(Some(31)), (FreshnessStatus::Stale, RefreshPriority::Medium));
+        assert_eq!(market.classify(None), (FreshnessStatus::Missing, RefreshPriority::Critical));
+    }
+
+    #[tokio::test]
+    async fn test_saved_policy_round_trips_and_changes_classification() {
+        let pool = metadata_pool().await;
+        assert_eq!(load_freshness_policy(&pool).await.unwrap(), FreshnessPolicy::default());
+
+        // Weekly refreshers: 10 days old is still current
+        let mut policy = FreshnessPolicy::default();
+        policy.market_data = FreshnessThreshold { current_days: 10, stale_days: 45 };
+        save_freshness_policy(&pool, &policy).await.unwrap();
+
+        let loaded = load_freshness_policy(&pool).await.unwrap();
+        assert_eq!(loaded, policy);
+        assert_eq!(loaded.market_data.classify(Some(10)).0, FreshnessStatus::Current);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_policy_is_rejected() {
+        let pool = metadata_pool().await;
+        let mut policy = FreshnessPolicy::default();
+        policy.financial_data = FreshnessThreshold { current_days: 30, stale_days: 10 };
+
+        assert!(save_freshness_policy(&pool, &policy).await.is_err());
+        assert_eq!(load_freshness_policy(&pool).await.unwrap(), FreshnessPolicy::default());
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use crate::tools::freshness_policy::FreshnessPolicy;
+use crate::tools::refresh_runs::RefreshRunError;
+
 #[derive(Debug, Clone)]
 pub struct FilingFreshnessResult {
     pub cik: String,
@@ -62,6 +65,8 @@ pub struct SystemFreshnessReport {
     pub recommendations: Vec<RefreshRecommendation>,
     pub screening_readiness: ScreeningReadiness,
     pub last_check: String, // Changed to String for TS compatibility
+    /// Thresholds used to classify each source
+    pub freshness_policy: FreshnessPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -73,13 +78,49 @@ pub struct RefreshRecommendation {
     pub priority: RefreshPriority,
 }
 
+/// Whether one screen has the data it reads, and what it lacks if not
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreenReadiness {
+    pub ready: bool,
+    pub blocking_issues: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct ScreeningReadiness {
-    pub valuation_analysis: bool,
+    /// Also gates the custom screen, which reads ratios, prices and statements alike
+    pub valuation_analysis: ScreenReadiness,
+    pub piotroski_screening: ScreenReadiness,
+    pub oshaughnessy_screening: ScreenReadiness,
+    pub graham_screening: ScreenReadiness,
+    /// Both P/S screens
+    pub ps_screening: ScreenReadiness,
+    /// Every screen's issues, each listed once
     pub blocking_issues: Vec<String>,
 }
 
+/// Which stocks a fundamentals refresh covers; only those with a CIK are ever refreshed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum StockFilter {
+    All,
+    Sp500,
+    /// Symbols as listed, compared case-insensitively
+    Symbols(Vec<String>),
+    /// Stocks carrying this tag
+    Tag(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FundamentalsRefreshResult {
+    pub stocks_processed: i64,
+    pub records_stored: i64,
+    pub errors: Vec<RefreshRunError>,
+}
+
 impl FreshnessStatus {
     pub fn is_current(&self) -> bool {
         matches!(self, FreshnessStatus::Current)
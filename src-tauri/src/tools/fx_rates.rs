@@ -0,0 +1,238 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+
+/// Free ECB reference rates; https://www.frankfurter.app
+pub const FRANKFURTER_BASE_URL: &str = "https://api.frankfurter.app";
+
+/// Currency all screening thresholds are expressed in
+pub const BASE_CURRENCY: &str = "USD";
+
+/// Time series response: rates by date, then by currency
+#[derive(Debug, Deserialize)]
+struct FrankfurterSeries {
+    rates: BTreeMap<String, HashMap<String, f64>>,
+}
+
+/// Non-USD currencies in stored prices and statements, with the first and last day a rate is needed
+async fn needed_rate_ranges(pool: &SqlitePool) -> Result<Vec<(String, String, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT currency, MIN(day), MAX(day) FROM (
+             SELECT currency, date(date) AS day FROM daily_prices WHERE currency != 'USD'
+             UNION ALL SELECT currency, report_date FROM income_statements WHERE currency != 'USD'
+             UNION ALL SELECT currency, report_date FROM balance_sheets WHERE currency != 'USD'
+             UNION ALL SELECT currency, report_date FROM cash_flow_statements WHERE currency != 'USD'
+         )
+         GROUP BY currency
+         ORDER BY currency"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Day ranges within `first..=last` not yet covered by stored `currency` rates: before the
+/// earliest stored rate and after the latest. Rates are looked up on or before a day, so the
+/// days in between are covered.
+async fn missing_rate_ranges(pool: &SqlitePool, currency: &str, first: &str, last: &str) -> Result<Vec<(String, String)>, sqlx::Error> {
+    let (earliest, latest): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT MIN(date), MAX(date) FROM fx_rates WHERE currency = ?")
+            .bind(currency)
+            .fetch_one(pool)
+            .await?;
+    let (Some(earliest), Some(latest)) = (earliest, latest) else {
+        return Ok(vec![(first.to_string(), last.to_string())]);
+    };
+
+    let mut missing = Vec::new();
+    if first < earliest.as_str() {
+        missing.push((first.to_string(), earliest));
+    }
+    if last > latest.as_str() {
+        missing.push((latest, last.to_string()));
+    }
+    Ok(missing)
+}
+
+/// Fetch `currency` per USD for every ECB publication day in `start..=end`
+async fn fetch_rate_series(client: &reqwest::Client, base_url: &str, currency: &str, start: &str, end: &str) -> Result<BTreeMap<String, f64>> {
+    let url = format!("{}/{}..{}?from={}&to={}", base_url.trim_end_matches('/'), start, end, BASE_CURRENCY, currency);
+    let response = client.get(&url).timeout(Duration::from_secs(30)).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("FX rates API error {}: {}", response.status(), url));
+    }
+    let series: FrankfurterSeries = response.json().await?;
+    Ok(series
+        .rates
+        .into_iter()
+        .filter_map(|(date, rates)| rates.get(currency).copied().filter(|rate| *rate > 0.0).map(|rate| (date, rate)))
+        .collect())
+}
+
+/// Store the USD crosses for the days stored prices and statements are dated, so each is
+/// converted at its own day's rate. Rates already stored are reused: the API is only called
+/// for days before the earliest or after the latest stored rate of a currency, and not at all
+/// when every day is covered. Returns the number of rates stored.
+pub async fn update_fx_rates(pool: &SqlitePool, base_url: &str) -> Result<u64> {
+    let client = reqwest::Client::new();
+    let mut stored = 0;
+    for (currency, first, last) in needed_rate_ranges(pool).await? {
+        for (start, end) in missing_rate_ranges(pool, &currency, &first, &last).await? {
+            let rates = match fetch_rate_series(&client, base_url, &currency, &start, &end).await {
+                Ok(rates) => rates,
+                Err(e) => {
+                    warn!(%currency, "Failed to fetch exchange rates for {}..{}: {}", start, end, e);
+                    continue;
+                }
+            };
+
+            let mut tx = pool.begin().await?;
+            for (date, rate) in &rates {
+                sqlx::query(
+                    "INSERT INTO fx_rates (currency, date, rate) VALUES (?, ?, ?)
+                     ON CONFLICT(currency, date) DO UPDATE SET rate = excluded.rate, fetched_at = CURRENT_TIMESTAMP"
+                )
+                .bind(&currency)
+                .bind(date)
+                .bind(rate)
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            stored += rates.len() as u64;
+        }
+    }
+
+    info!(rates = stored, "Stored USD exchange rates");
+    Ok(stored)
+}
+
+/// SQL for the units of `currency` per USD on `date`: 1 for USD (or NULL, which is how
+/// pre-currency statements read), otherwise the latest stored rate on or before that day,
+/// NULL when none is known. Both arguments are SQL expressions.
+pub fn usd_rate_sql(currency: &str, date: &str) -> String {
+    format!(
+        "(CASE WHEN COALESCE({currency}, 'USD') = 'USD' THEN 1.0 ELSE
+            (SELECT fx.rate FROM fx_rates fx WHERE fx.currency = {currency} AND fx.date <= {date}
+             ORDER BY fx.date DESC LIMIT 1) END)",
+        currency = currency,
+        date = date
+    )
+}
+
+/// SQL converting `value` in `currency` to USD as of `date`; NULL when no rate is known
+pub fn usd_value_sql(value: &str, currency: &str, date: &str) -> String {
+    format!("({} / {})", value, usd_rate_sql(currency, date))
+}
+
+/// Units of `currency` per USD on `date`, or None when no rate on or before that day is stored
+pub async fn usd_rate(pool: &SqlitePool, currency: &str, date: &str) -> Result<Option<f64>, sqlx::Error> {
+    if currency == BASE_CURRENCY {
+        return Ok(Some(1.0));
+    }
+    sqlx::query_scalar("SELECT rate FROM fx_rates WHERE currency = ? AND date <= ? ORDER BY date DESC LIMIT 1")
+        .bind(currency)
+        .bind(date)
+        .fetch_optional(pool)
+        .await
+}
+
+/// `amount` in `currency` converted to USD as of `date`
+pub async fn to_usd(pool: &SqlitePool, amount: f64, currency: &str, date: &str) -> Result<Option<f64>, sqlx::Error> {
+    Ok(usd_rate(pool, currency, date).await?.map(|rate| amount / rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_updater_stores_rates_for_stored_days_and_reuses_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'EURO', 'Euro SE'), (2, 'USA', 'USA Inc')")
+            .execute(&pool).await.unwrap();
+        for (stock_id, day, currency) in [(1, "2024-06-03", "EUR"), (1, "2024-06-05", "EUR"), (2, "2024-06-05", "USD")] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price, currency)
+                 VALUES (?, ?, 80.0, 80.0, 80.0, 80.0, ?)"
+            )
+            .bind(stock_id).bind(day).bind(currency)
+            .execute(&pool).await.unwrap();
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/2024-06-03..2024-06-05"))
+            .and(query_param("from", "USD"))
+            .and(query_param("to", "EUR"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "amount": 1.0,
+                "base": "USD",
+                "start_date": "2024-06-03",
+                "end_date": "2024-06-05",
+                "rates": {
+                    "2024-06-03": { "EUR": 0.8 },
+                    "2024-06-04": { "EUR": 0.5 },
+                    "2024-06-05": { "EUR": 0.64 }
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert_eq!(update_fx_rates(&pool, &server.uri()).await.unwrap(), 3);
+        // Every price day is covered now: no second request
+        assert_eq!(update_fx_rates(&pool, &server.uri()).await.unwrap(), 0);
+
+        // Each day converts at its own rate; days before the first rate have none
+        assert_eq!(to_usd(&pool, 80.0, "EUR", "2024-06-03").await.unwrap(), Some(100.0));
+        assert_eq!(to_usd(&pool, 80.0, "EUR", "2024-06-05").await.unwrap(), Some(125.0));
+        assert_eq!(to_usd(&pool, 80.0, "EUR", "2024-06-01").await.unwrap(), None);
+        assert_eq!(to_usd(&pool, 80.0, "USD", "2024-06-01").await.unwrap(), Some(80.0));
+
+        let in_sql: Option<f64> = sqlx::query_scalar(&format!("SELECT {}", usd_value_sql("80.0", "'EUR'", "'2024-06-04'")))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(in_sql, Some(160.0));
+    }
+
+    #[tokio::test]
+    async fn test_updater_fetches_only_days_after_the_latest_stored_rate() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'EURO', 'Euro SE')")
+            .execute(&pool).await.unwrap();
+        for day in ["2024-06-03", "2024-06-10"] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price, currency)
+                 VALUES (1, ?, 80.0, 80.0, 80.0, 80.0, 'EUR')"
+            )
+            .bind(day)
+            .execute(&pool).await.unwrap();
+        }
+        sqlx::query("INSERT INTO fx_rates (currency, date, rate) VALUES ('EUR', '2024-06-03', 0.8), ('EUR', '2024-06-07', 0.8)")
+            .execute(&pool).await.unwrap();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/2024-06-07..2024-06-10"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "base": "USD",
+                "rates": { "2024-06-07": { "EUR": 0.8 }, "2024-06-10": { "EUR": 0.64 } }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert_eq!(update_fx_rates(&pool, &server.uri()).await.unwrap(), 2);
+        assert_eq!(to_usd(&pool, 80.0, "EUR", "2024-06-10").await.unwrap(), Some(125.0));
+    }
+}
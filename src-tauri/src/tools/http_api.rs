@@ -0,0 +1,690 @@
+use anyhow::{Result, anyhow};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use std::convert::Infallible;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use ts_rs::TS;
+
+use crate::commands::analysis::{get_price_history_internal, get_valuation_ratios_internal, validate_price_history_dates, PriceFieldSet};
+use crate::commands::data::get_cached_data_freshness_status;
+use crate::commands::graham_screening::run_graham_screening_internal;
+use crate::commands::stocks::get_all_stocks_internal;
+use crate::database::helpers::{ensure_writable, get_database_connection};
+use crate::error::CommandError;
+use crate::tools::data_refresh_orchestrator::{
+    default_progress_sink, is_refresh_running, DataRefreshManager, RefreshMode, RefreshProgressEvent, RefreshProgressSink,
+    RefreshRequest, REFRESH_PROGRESS_EVENT,
+};
+use crate::tools::freshness_policy::load_freshness_policy;
+use crate::tools::refresh_estimates::{estimate_refresh_durations, RefreshDurationEstimates};
+
+/// metadata table key holding the JSON-encoded settings
+const HTTP_API_SETTINGS_KEY: &str = "http_api";
+
+/// Price window served by /stocks/{symbol}/prices when no dates are given
+const DEFAULT_PRICE_HISTORY_DAYS: i64 = 365;
+
+/// initiated_by recorded in the run history for refreshes started over HTTP
+pub const HTTP_API_TRIGGER: &str = "http_api";
+
+/// Server-sent event closing a refresh stream with its RefreshResult
+pub const REFRESH_COMPLETED_EVENT: &str = "refresh-completed";
+
+/// Server-sent event closing a refresh stream with the CommandError it failed with
+pub const REFRESH_FAILED_EVENT: &str = "refresh-failed";
+
+/// JSON API on localhost for scripts and notebooks: read-only data routes, plus POST /refresh
+/// to run the collection engine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct HttpApiSettings {
+    /// Start the server with the app; set by start_http_api and cleared by stop_http_api
+    pub enabled: bool,
+    /// Port on 127.0.0.1
+    pub port: u16,
+    /// When set, requests need `Authorization: Bearer <token>`; /openapi.json stays open.
+    /// POST /refresh is refused until a token is set.
+    pub bearer_token: Option<String>,
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8787,
+            bearer_token: None,
+        }
+    }
+}
+
+impl HttpApiSettings {
+    pub fn validate(&self) -> Result<()> {
+        if self.port == 0 {
+            return Err(anyhow!("port must be between 1 and 65535"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct HttpApiStatus {
+    pub running: bool,
+    /// Base URL, e.g. "http://127.0.0.1:8787", while running
+    pub base_url: Option<String>,
+}
+
+/// Stored settings, or the defaults when none have been saved (or they can't be parsed)
+pub async fn load_http_api_settings(pool: &SqlitePool) -> Result<HttpApiSettings> {
+    let row = sqlx::query("SELECT value FROM metadata WHERE key = ?")
+        .bind(HTTP_API_SETTINGS_KEY)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row
+        .and_then(|row| serde_json::from_str(&row.get::<String, _>("value")).ok())
+        .unwrap_or_default())
+}
+
+pub async fn save_http_api_settings(pool: &SqlitePool, settings: &HttpApiSettings) -> Result<()> {
+    settings.validate()?;
+
+    sqlx::query(
+        "INSERT INTO metadata (key, value, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(HTTP_API_SETTINGS_KEY)
+    .bind(serde_json::to_string(settings)?)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+impl IntoResponse for CommandError {
+    /// The same JSON body the frontend gets, under a matching HTTP status
+    fn into_response(self) -> Response {
+        let status = match &self {
+            CommandError::NotFound { .. } => StatusCode::NOT_FOUND,
+            CommandError::Validation { .. } => StatusCode::BAD_REQUEST,
+            CommandError::ReadOnly { .. } => StatusCode::FORBIDDEN,
+            CommandError::StaleData { .. } => StatusCode::CONFLICT,
+            CommandError::DatabaseBusy { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            CommandError::ExternalApi { .. } | CommandError::TokenExpired { .. } => StatusCode::BAD_GATEWAY,
+            CommandError::Database { .. } | CommandError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(self)).into_response()
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    pool: SqlitePool,
+    bearer_token: Option<String>,
+    /// Port the server is bound to, which the Host header has to name
+    port: u16,
+}
+
+/// Whether two tokens are equal, comparing every byte so the time taken doesn't reveal how
+/// long a matching prefix was
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    let (provided, expected) = (provided.as_bytes(), expected.as_bytes());
+    provided.len() == expected.len()
+        && std::hint::black_box(provided.iter().zip(expected).fold(0u8, |diff, (a, b)| diff | (a ^ b))) == 0
+}
+
+/// Refuse requests addressed to any host but this server, so a web page can't reach it
+/// through a DNS name rebound to 127.0.0.1
+async fn require_local_host(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let host = request.headers().get(header::HOST).and_then(|value| value.to_str().ok());
+    let allowed = [format!("127.0.0.1:{}", state.port), format!("localhost:{}", state.port)];
+    if !host.is_some_and(|host| allowed.iter().any(|allowed| allowed.eq_ignore_ascii_case(host))) {
+        let error = CommandError::validation("host", format!("Requests must be addressed to 127.0.0.1:{} or localhost:{}", state.port, state.port));
+        return (StatusCode::FORBIDDEN, Json(error)).into_response();
+    }
+    next.run(request).await
+}
+
+async fn require_bearer_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if let Some(token) = &state.bearer_token {
+        let provided = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if !provided.is_some_and(|provided| tokens_match(provided, token)) {
+            let error = CommandError::validation("authorization", "Missing or invalid bearer token");
+            return (StatusCode::UNAUTHORIZED, Json(error)).into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Refuse routes that write unless a token guards them
+async fn require_token_for_writes(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if state.bearer_token.is_none() {
+        let error = CommandError::validation("bearer_token", "Set an HTTP API bearer token in settings to enable refreshes over HTTP");
+        return (StatusCode::FORBIDDEN, Json(error)).into_response();
+    }
+    next.run(request).await
+}
+
+#[derive(Debug, Deserialize)]
+struct PriceHistoryParams {
+    start_date: Option<String>,
+    end_date: Option<String>,
+    fields: Option<PriceFieldSet>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrahamParams {
+    limit: Option<i32>,
+}
+
+async fn list_stocks(State(state): State<ApiState>) -> Result<impl IntoResponse, CommandError> {
+    Ok(Json(get_all_stocks_internal(&state.pool).await?))
+}
+
+/// Same as get_price_history; the last year up to today unless dates are given
+async fn price_history(
+    State(state): State<ApiState>,
+    Path(symbol): Path<String>,
+    Query(params): Query<PriceHistoryParams>,
+) -> Result<impl IntoResponse, CommandError> {
+    let end_date = params.end_date.unwrap_or_else(|| chrono::Local::now().date_naive().format("%Y-%m-%d").to_string());
+    let start_date = match params.start_date {
+        Some(start_date) => start_date,
+        None => {
+            let end = chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+                .map_err(|e| CommandError::validation("end_date", format!("Invalid end date format: {}", e)))?;
+            (end - chrono::Duration::days(DEFAULT_PRICE_HISTORY_DAYS)).format("%Y-%m-%d").to_string()
+        }
+    };
+    validate_price_history_dates(&start_date, &end_date)?;
+
+    let prices = get_price_history_internal(&state.pool, &symbol, &start_date, &end_date, params.fields.unwrap_or_default()).await?;
+    Ok(Json(prices))
+}
+
+/// Latest valuation ratios; 404 rather than null when the symbol has none
+async fn valuation_ratios(State(state): State<ApiState>, Path(symbol): Path<String>) -> Result<impl IntoResponse, CommandError> {
+    match get_valuation_ratios_internal(&state.pool, &symbol).await? {
+        Some(ratios) => Ok(Json(ratios)),
+        None => Err(CommandError::not_found("ratios", format!("No valuation ratios for {}", symbol))),
+    }
+}
+
+/// Graham screen with the default criteria
+async fn graham_screen(State(state): State<ApiState>, Query(params): Query<GrahamParams>) -> Result<impl IntoResponse, CommandError> {
+    Ok(Json(run_graham_screening_internal(&state.pool, None, params.limit).await?))
+}
+
+async fn freshness(State(state): State<ApiState>) -> Result<impl IntoResponse, CommandError> {
+    Ok(Json(get_cached_data_freshness_status(&state.pool).await?))
+}
+
+/// Body of POST /refresh
+#[derive(Debug, Deserialize)]
+struct HttpRefreshRequest {
+    mode: RefreshMode,
+    /// Limit the run to this S&P 500 stock's filings and prices
+    symbol: Option<String>,
+    /// Check the request and return the duration estimates without refreshing anything
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct RefreshDryRun {
+    mode: RefreshMode,
+    only_cik: Option<String>,
+    estimates: RefreshDurationEstimates,
+}
+
+/// Forwards progress to a refresh stream, and on to the app's sink so the desktop UI follows
+/// runs started over HTTP too
+struct StreamProgressSink {
+    events: mpsc::UnboundedSender<Event>,
+    forward: Option<Arc<dyn RefreshProgressSink>>,
+}
+
+impl RefreshProgressSink for StreamProgressSink {
+    fn send_progress(&self, event: &RefreshProgressEvent) {
+        if let Ok(sse_event) = Event::default().event(REFRESH_PROGRESS_EVENT).json_data(event) {
+            // The client may have disconnected; the refresh carries on
+            self.events.send(sse_event).ok();
+        }
+        if let Some(forward) = &self.forward {
+            forward.send_progress(event);
+        }
+    }
+}
+
+/// Start a refresh and stream its progress as server-sent events: `refresh-progress` with the
+/// payloads the desktop app receives, then one `refresh-completed` or `refresh-failed`.
+/// 404 for a symbol with no CIK, 422 for one outside the S&P 500 that refreshes cover, and 409
+/// while another refresh is running.
+async fn start_refresh(State(state): State<ApiState>, Json(body): Json<HttpRefreshRequest>) -> Result<Response, CommandError> {
+    let only_cik = match &body.symbol {
+        Some(symbol) => {
+            let stock: Option<(String, bool)> = sqlx::query_as(
+                "SELECT cik, COALESCE(is_sp500, 0) FROM stocks WHERE UPPER(symbol) = UPPER(?) AND cik IS NOT NULL AND cik != ''"
+            )
+            .bind(symbol)
+            .fetch_optional(&state.pool)
+            .await?;
+            let (cik, is_sp500) = stock.ok_or_else(|| CommandError::not_found("stock", format!("No stock with a CIK for symbol {}", symbol)))?;
+            if !is_sp500 {
+                let error = CommandError::validation("symbol", format!("{} is not in the S&P 500; refreshes only cover S&P 500 stocks", symbol));
+                return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(error)).into_response());
+            }
+            Some(cik)
+        }
+        None => None,
+    };
+
+    if is_refresh_running(&state.pool).await? {
+        let error = CommandError::DatabaseBusy { message: "A data refresh is already running".to_string() };
+        return Ok((StatusCode::CONFLICT, Json(error)).into_response());
+    }
+
+    if body.dry_run {
+        let policy = load_freshness_policy(&state.pool).await?;
+        let estimates = estimate_refresh_durations(&state.pool, &policy, chrono::Local::now().date_naive()).await?;
+        return Ok(Json(RefreshDryRun { mode: body.mode, only_cik, estimates }).into_response());
+    }
+    ensure_writable(&state.pool).await?;
+
+    let (events, receiver) = mpsc::unbounded_channel();
+    let sink = StreamProgressSink { events: events.clone(), forward: default_progress_sink() };
+    let manager = DataRefreshManager::new(state.pool.clone()).await?.with_progress_sink(Arc::new(sink));
+    let request = RefreshRequest {
+        mode: body.mode,
+        force_sources: Vec::new(),
+        initiated_by: HTTP_API_TRIGGER.to_string(),
+        session_id: None,
+        only_cik,
+    };
+    tokio::spawn(async move {
+        let outcome = match manager.execute_refresh(request).await {
+            Ok(result) => Event::default().event(REFRESH_COMPLETED_EVENT).json_data(&result),
+            Err(e) => Event::default().event(REFRESH_FAILED_EVENT).json_data(CommandError::from(e)),
+        };
+        if let Ok(outcome) = outcome {
+            events.send(outcome).ok();
+        }
+    });
+
+    // Ends when the refresh task drops the last sender
+    let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|event| (Ok::<_, Infallible>(event), receiver))
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()).into_response())
+}
+
+async fn openapi() -> impl IntoResponse {
+    Json(openapi_document())
+}
+
+/// OpenAPI 3 description of the routes, served at /openapi.json
+pub fn openapi_document() -> serde_json::Value {
+    let symbol = serde_json::json!({ "name": "symbol", "in": "path", "required": true, "schema": { "type": "string" } });
+    let date = |name: &str, description: &str| serde_json::json!({
+        "name": name, "in": "query", "required": false, "description": description,
+        "schema": { "type": "string", "format": "date" }
+    });
+    let json_response = |description: &str, schema_type: &str| serde_json::json!({
+        "200": { "description": description, "content": { "application/json": { "schema": { "type": schema_type } } } },
+        "default": { "description": "CommandError with a `code` tag", "content": { "application/json": { "schema": { "type": "object" } } } }
+    });
+
+    serde_json::json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "rust-stocks local API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Local analysis data, identical to the desktop app's commands, and refreshes"
+        },
+        "servers": [{ "url": "http://127.0.0.1" }],
+        "components": {
+            "securitySchemes": { "bearer": { "type": "http", "scheme": "bearer" } }
+        },
+        "security": [{ "bearer": [] }],
+        "paths": {
+            "/stocks": {
+                "get": { "summary": "All stocks", "responses": json_response("id, symbol, company_name and sector per stock", "array") }
+            },
+            "/stocks/{symbol}/prices": {
+                "get": {
+                    "summary": "Daily prices, oldest first, at most 1000 rows",
+                    "parameters": [
+                        symbol,
+                        date("start_date", "Defaults to a year before end_date"),
+                        date("end_date", "Defaults to today"),
+                        {
+                            "name": "fields", "in": "query", "required": false,
+                            "schema": { "type": "string", "enum": ["close_only", "ohlc", "ohlc_v", "all"] }
+                        }
+                    ],
+                    "responses": json_response("Price rows", "array")
+                }
+            },
+            "/stocks/{symbol}/ratios": {
+                "get": {
+                    "summary": "Latest P/S and EV/S ratios",
+                    "parameters": [symbol],
+                    "responses": json_response("Valuation ratios", "object")
+                }
+            },
+            "/screens/graham": {
+                "get": {
+                    "summary": "Graham value screen with the default criteria",
+                    "parameters": [{ "name": "limit", "in": "query", "required": false, "schema": { "type": "integer" } }],
+                    "responses": json_response("Passing stocks, and those excluded for negative equity or earnings", "object")
+                }
+            },
+            "/freshness": {
+                "get": { "summary": "Market and financial data freshness", "responses": json_response("Freshness report", "object") }
+            },
+            "/refresh": {
+                "post": {
+                    "summary": "Run a data refresh and stream its progress; needs a bearer token to be set",
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": {
+                            "type": "object",
+                            "required": ["mode"],
+                            "properties": {
+                                "mode": { "type": "string", "enum": ["Market", "Financials", "All"] },
+                                "symbol": { "type": "string", "description": "Refresh only this S&P 500 stock" },
+                                "dry_run": { "type": "boolean", "default": false, "description": "Validate and return duration estimates only" }
+                            }
+                        } } }
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "text/event-stream of refresh-progress events, then refresh-completed or refresh-failed; JSON estimates for a dry run",
+                            "content": {
+                                "text/event-stream": { "schema": { "type": "string" } },
+                                "application/json": { "schema": { "type": "object" } }
+                            }
+                        },
+                        "403": { "description": "No bearer token is configured, or the database is read-only" },
+                        "404": { "description": "No stock with a CIK has this symbol" },
+                        "409": { "description": "Another refresh is running" },
+                        "422": { "description": "The stock is outside the S&P 500 that refreshes cover" }
+                    }
+                }
+            },
+            "/openapi.json": {
+                "get": { "summary": "This document", "security": [], "responses": json_response("OpenAPI document", "object") }
+            }
+        }
+    })
+}
+
+/// Routes over `pool` for a server bound to `port`; every route but /openapi.json checks
+/// `bearer_token` when set, POST /refresh requires one, and all of them check the Host header
+pub fn router(pool: SqlitePool, bearer_token: Option<String>, port: u16) -> Router {
+    let state = ApiState {
+        pool,
+        bearer_token: bearer_token.filter(|token| !token.is_empty()),
+        port,
+    };
+    let writes = Router::new()
+        .route("/refresh", post(start_refresh))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token_for_writes));
+
+    Router::new()
+        .merge(writes)
+        .route("/stocks", get(list_stocks))
+        .route("/stocks/{symbol}/prices", get(price_history))
+        .route("/stocks/{symbol}/ratios", get(valuation_ratios))
+        .route("/screens/graham", get(graham_screen))
+        .route("/freshness", get(freshness))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_bearer_token))
+        .route("/openapi.json", get(openapi))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_local_host))
+        .with_state(state)
+}
+
+struct RunningServer {
+    address: SocketAddr,
+    /// What it was started with, to tell whether saved settings need a restart
+    settings: HttpApiSettings,
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+/// The one server the app runs, if started
+static HTTP_SERVER: Mutex<Option<RunningServer>> = Mutex::const_new(None);
+
+/// Serve the API on 127.0.0.1 with `settings`, replacing a server already running.
+/// Returns the bound address (useful with port 0).
+pub async fn start_http_server(pool: SqlitePool, settings: &HttpApiSettings) -> Result<SocketAddr> {
+    let mut server = HTTP_SERVER.lock().await;
+    if let Some(running) = server.take() {
+        shutdown(running).await;
+    }
+
+    let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port)).await?;
+    let address = listener.local_addr()?;
+    let app = router(pool, settings.bearer_token.clone(), address.port());
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let task = tokio::spawn(async move {
+        let serve = axum::serve(listener, app).with_graceful_shutdown(async {
+            shutdown_rx.await.ok();
+        });
+        if let Err(e) = serve.await {
+            warn!("HTTP API server stopped with an error: {}", e);
+        }
+    });
+
+    info!("HTTP API listening on http://{}", address);
+    *server = Some(RunningServer { address, settings: settings.clone(), shutdown: shutdown_tx, task });
+    Ok(address)
+}
+
+/// Restart a running server whose port or token differs from `settings`, so saved changes
+/// take effect. A stopped server stays stopped. Returns whether it was restarted.
+pub async fn restart_http_server_if_changed(pool: SqlitePool, settings: &HttpApiSettings) -> Result<bool> {
+    let changed = HTTP_SERVER.lock().await.as_ref().is_some_and(|running| {
+        running.settings.port != settings.port || running.settings.bearer_token != settings.bearer_token
+    });
+    if changed {
+        start_http_server(pool, settings).await?;
+    }
+    Ok(changed)
+}
+
+/// Stop the server and wait for in-flight requests. Returns whether one was running.
+pub async fn stop_http_server() -> bool {
+    match HTTP_SERVER.lock().await.take() {
+        Some(running) => {
+            shutdown(running).await;
+            true
+        }
+        None => false,
+    }
+}
+
+async fn shutdown(running: RunningServer) {
+    running.shutdown.send(()).ok();
+    if let Err(e) = running.task.await {
+        warn!("HTTP API server task failed: {}", e);
+    }
+    info!("HTTP API on http://{} stopped", running.address);
+}
+
+pub async fn http_server_status() -> HttpApiStatus {
+    let address = HTTP_SERVER.lock().await.as_ref().map(|running| running.address);
+    HttpApiStatus {
+        running: address.is_some(),
+        base_url: address.map(|address| format!("http://{}", address)),
+    }
+}
+
+/// Started from the Tauri setup hook; serves the API when the saved settings enable it
+pub async fn start_http_server_if_enabled() {
+    let pool = match get_database_connection().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            warn!("HTTP API could not open the database: {}", e);
+            return;
+        }
+    };
+    match load_http_api_settings(&pool).await {
+        Ok(settings) if settings.enabled => {
+            if let Err(e) = start_http_server(pool, &settings).await {
+                warn!("HTTP API failed to start on port {}: {}", settings.port, e);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Failed to load HTTP API settings: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_serves_command_results_behind_bearer_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name, sector) VALUES (1, 'AAPL', 'Apple Inc.', 'Technology')")
+            .execute(&pool).await.unwrap();
+
+        let settings = HttpApiSettings { enabled: true, port: 0, bearer_token: Some("secret".to_string()) };
+        assert!(settings.validate().is_err(), "port 0 is only for binding in tests");
+        let address = start_http_server(pool.clone(), &settings).await.unwrap();
+        assert!(address.ip().is_loopback());
+        assert!(http_server_status().await.running);
+
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let base = format!("http://{}", address);
+        let unauthorized = client.get(format!("{}/stocks", base)).send().await.unwrap();
+        assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let stocks: serde_json::Value = client.get(format!("{}/stocks", base)).bearer_auth("secret")
+            .send().await.unwrap().json().await.unwrap();
+        let expected = serde_json::to_value(get_all_stocks_internal(&pool).await.unwrap()).unwrap();
+        assert_eq!(stocks, expected);
+
+        let missing = client.get(format!("{}/stocks/AAPL/ratios", base)).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+        let error: serde_json::Value = missing.json().await.unwrap();
+        assert_eq!(error["code"], "NOT_FOUND");
+
+        let invalid = client.get(format!("{}/stocks/AAPL/prices?start_date=2024-13-01", base)).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(invalid.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        // A rebound DNS name reaches the socket but names another host
+        let rebound = client.get(format!("{}/openapi.json", base))
+            .header(reqwest::header::HOST, format!("attacker.example:{}", address.port()))
+            .send().await.unwrap();
+        assert_eq!(rebound.status(), reqwest::StatusCode::FORBIDDEN);
+        let localhost = client.get(format!("{}/openapi.json", base))
+            .header(reqwest::header::HOST, format!("localhost:{}", address.port()))
+            .send().await.unwrap();
+        assert_eq!(localhost.status(), reqwest::StatusCode::OK);
+        assert!(!tokens_match("secreT", "secret") && !tokens_match("secret2", "secret") && tokens_match("secret", "secret"));
+
+        // A new token applies without stopping the API by hand
+        let rotated = HttpApiSettings { bearer_token: Some("rotated".to_string()), ..settings.clone() };
+        assert!(!restart_http_server_if_changed(pool.clone(), &settings).await.unwrap());
+        assert!(restart_http_server_if_changed(pool.clone(), &rotated).await.unwrap());
+        let base = http_server_status().await.base_url.unwrap();
+        let old_token = client.get(format!("{}/stocks", base)).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(old_token.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let new_token = client.get(format!("{}/stocks", base)).bearer_auth("rotated").send().await.unwrap();
+        assert_eq!(new_token.status(), reqwest::StatusCode::OK);
+
+        let spec: serde_json::Value = client.get(format!("{}/openapi.json", base)).send().await.unwrap().json().await.unwrap();
+        for path in ["/stocks", "/stocks/{symbol}/prices", "/stocks/{symbol}/ratios", "/screens/graham", "/freshness"] {
+            assert!(spec["paths"].get(path).is_some(), "{} missing from the OpenAPI document", path);
+        }
+
+        assert!(spec["paths"].get("/refresh").is_some());
+
+        assert!(stop_http_server().await);
+        assert!(!http_server_status().await.running);
+        assert!(client.get(format!("{}/openapi.json", base)).send().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_streams_progress_and_requires_a_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, cik, is_sp500) VALUES
+                (1, 'TINY', 'Tiny Co', '0000000000', 1), (2, 'OTC', 'Off Index Co', '0000000001', 0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        let serve = |bearer_token: Option<&str>| {
+            let (pool, bearer_token) = (pool.clone(), bearer_token.map(str::to_string));
+            async move {
+                let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+                let address = listener.local_addr().unwrap();
+                let app = router(pool, bearer_token, address.port());
+                let base = format!("http://{}", address);
+                tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+                base
+            }
+        };
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let refresh = serde_json::json!({ "mode": "Financials", "symbol": "TINY" });
+
+        let open = serve(None).await;
+        let refused = client.post(format!("{}/refresh", open)).json(&refresh).send().await.unwrap();
+        assert_eq!(refused.status(), reqwest::StatusCode::FORBIDDEN);
+
+        let base = serve(Some("secret")).await;
+        let unauthorized = client.post(format!("{}/refresh", base)).json(&refresh).send().await.unwrap();
+        assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let unknown = client.post(format!("{}/refresh", base)).bearer_auth("secret")
+            .json(&serde_json::json!({ "mode": "Financials", "symbol": "NOPE" })).send().await.unwrap();
+        assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+
+        // Refreshes only cover the S&P 500, so a stock outside it is refused, in either mode
+        for mode in ["Financials", "Market"] {
+            let out_of_scope = client.post(format!("{}/refresh", base)).bearer_auth("secret")
+                .json(&serde_json::json!({ "mode": mode, "symbol": "OTC" })).send().await.unwrap();
+            assert_eq!(out_of_scope.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        }
+
+        let dry_run: serde_json::Value = client.post(format!("{}/refresh", base)).bearer_auth("secret")
+            .json(&serde_json::json!({ "mode": "Financials", "symbol": "TINY", "dry_run": true }))
+            .send().await.unwrap().json().await.unwrap();
+        assert_eq!(dry_run["only_cik"], "0000000000");
+        let runs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM refresh_runs").fetch_one(&pool).await.unwrap();
+        assert_eq!(runs, 0, "a dry run must not start a refresh");
+
+        // With no S&P 500 stock left, the refresh has nothing to fetch
+        sqlx::query("UPDATE stocks SET is_sp500 = 0").execute(&pool).await.unwrap();
+        let stream = client.post(format!("{}/refresh", base)).bearer_auth("secret")
+            .json(&serde_json::json!({ "mode": "Financials" })).send().await.unwrap();
+        assert_eq!(stream.status(), reqwest::StatusCode::OK);
+        let body = stream.text().await.unwrap();
+        let events: Vec<&str> = body.lines().filter_map(|line| line.strip_prefix("event: ")).collect();
+        assert!(events.contains(&REFRESH_PROGRESS_EVENT), "{}", body);
+        assert_eq!(events.last(), Some(&REFRESH_COMPLETED_EVENT), "{}", body);
+
+        let initiated_by: String = sqlx::query_scalar("SELECT initiated_by FROM refresh_runs").fetch_one(&pool).await.unwrap();
+        assert_eq!(initiated_by, HTTP_API_TRIGGER);
+    }
+}
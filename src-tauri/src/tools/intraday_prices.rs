@@ -0,0 +1,389 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, SecondsFormat, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use tracing::warn;
+use ts_rs::TS;
+
+use crate::api::StockDataProvider;
+use crate::models::{IntradayInterval, IntradayPrice, SchwabPriceBar};
+
+/// Only stocks with this tag have intraday bars collected, to keep the volume manageable
+pub const WATCHLIST_TAG: &str = "watchlist";
+
+/// Sessions whose 1-minute bars are kept before they are folded into 30-minute bars
+pub const DEFAULT_INTRADAY_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct IntradayCollectionResult {
+    #[ts(type = "string")]
+    pub trading_date: NaiveDate,
+    pub interval: IntradayInterval,
+    pub stocks_collected: i32,
+    pub bars_stored: i64,
+    /// Watchlist symbols whose bars couldn't be fetched
+    pub failed_symbols: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct IntradayDownsampleResult {
+    pub minute_bars_removed: i64,
+    pub bars_written: i64,
+}
+
+/// The `n`th Sunday of a month, from 1
+fn nth_sunday(year: i32, month: u32, n: u32) -> NaiveDate {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).expect("Valid date");
+    let to_sunday = (7 - first.weekday().num_days_from_sunday()) % 7;
+    first + Duration::days((to_sunday + 7 * (n - 1)) as i64)
+}
+
+/// New York's offset from UTC at `utc`: daylight time from 2am on the second Sunday of March
+/// to 2am on the first Sunday of November, standard time otherwise
+pub fn new_york_offset(utc: DateTime<Utc>) -> Duration {
+    let year = utc.year();
+    // 2am EST is 07:00 UTC; 2am EDT is 06:00 UTC
+    let dst_start = nth_sunday(year, 3, 2).and_hms_opt(7, 0, 0).unwrap().and_utc();
+    let dst_end = nth_sunday(year, 11, 1).and_hms_opt(6, 0, 0).unwrap().and_utc();
+    if utc >= dst_start && utc < dst_end {
+        Duration::hours(-4)
+    } else {
+        Duration::hours(-5)
+    }
+}
+
+/// The New York calendar day of an instant, which is the trading session it belongs to
+pub fn trading_date(utc: DateTime<Utc>) -> NaiveDate {
+    (utc + new_york_offset(utc)).date_naive()
+}
+
+/// Whether `utc` falls within a weekday's 9:30-16:00 New York session. Exchange holidays
+/// aren't known here and count as open.
+pub fn is_regular_session(utc: DateTime<Utc>) -> bool {
+    let local = (utc + new_york_offset(utc)).naive_utc();
+    let open = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+    let close = NaiveTime::from_hms_opt(16, 0, 0).unwrap();
+    !matches!(local.weekday(), Weekday::Sat | Weekday::Sun) && local.time() >= open && local.time() < close
+}
+
+/// The stored form of a bar time: RFC 3339 in UTC with a 'Z' suffix, so text order is time order
+fn format_datetime(utc: DateTime<Utc>) -> String {
+    utc.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Store bars for one stock, replacing any already stored at the same time and interval.
+/// Bars outside `session` are skipped. Returns the number written.
+pub async fn store_intraday_bars(
+    pool: &SqlitePool,
+    stock_id: i64,
+    session: NaiveDate,
+    interval: IntradayInterval,
+    bars: &[SchwabPriceBar],
+) -> Result<i64> {
+    let mut tx = pool.begin().await?;
+    let mut stored = 0;
+    for bar in bars {
+        let Some(datetime) = Utc.timestamp_millis_opt(bar.datetime).single() else {
+            continue;
+        };
+        if trading_date(datetime) != session {
+            continue;
+        }
+        sqlx::query(
+            "INSERT OR REPLACE INTO intraday_prices
+                (stock_id, datetime, trading_date, interval_type, open_price, high_price, low_price, close_price, volume)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id)
+        .bind(format_datetime(datetime))
+        .bind(session.to_string())
+        .bind(interval.as_str())
+        .bind(bar.open)
+        .bind(bar.high)
+        .bind(bar.low)
+        .bind(bar.close)
+        .bind(bar.volume)
+        .execute(&mut *tx)
+        .await?;
+        stored += 1;
+    }
+    tx.commit().await?;
+    Ok(stored)
+}
+
+/// Fetch and store one session's bars for every watchlist stock. A symbol that fails is
+/// logged and listed; the rest carry on.
+pub async fn collect_watchlist_intraday(
+    pool: &SqlitePool,
+    provider: &(impl StockDataProvider + Sync),
+    session: NaiveDate,
+    interval: IntradayInterval,
+) -> Result<IntradayCollectionResult> {
+    let watchlist: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT s.id, s.symbol FROM stocks s
+         JOIN stock_tags t ON t.stock_id = s.id
+         WHERE t.tag = ?
+         ORDER BY s.symbol"
+    )
+    .bind(WATCHLIST_TAG)
+    .fetch_all(pool)
+    .await?;
+
+    let mut result = IntradayCollectionResult {
+        trading_date: session,
+        interval,
+        stocks_collected: 0,
+        bars_stored: 0,
+        failed_symbols: Vec::new(),
+    };
+    for (stock_id, symbol) in watchlist {
+        match provider.get_intraday_history(&symbol, session, interval).await {
+            Ok(bars) => {
+                result.bars_stored += store_intraday_bars(pool, stock_id, session, interval, &bars).await?;
+                result.stocks_collected += 1;
+            }
+            Err(e) => {
+                warn!(%symbol, "Intraday fetch failed for {}: {}", symbol, e);
+                result.failed_symbols.push(symbol);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// A stock's bars of one interval for one session, earliest first
+pub async fn load_intraday_prices(
+    pool: &SqlitePool,
+    stock_id: i64,
+    session: NaiveDate,
+    interval: IntradayInterval,
+) -> Result<Vec<IntradayPrice>> {
+    let rows = sqlx::query(
+        "SELECT id, datetime, open_price, high_price, low_price, close_price, volume
+         FROM intraday_prices
+         WHERE stock_id = ? AND trading_date = ? AND interval_type = ?
+         ORDER BY datetime"
+    )
+    .bind(stock_id)
+    .bind(session.to_string())
+    .bind(interval.as_str())
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| -> Result<IntradayPrice> {
+            let datetime: String = row.get("datetime");
+            Ok(IntradayPrice {
+                id: row.get("id"),
+                stock_id,
+                datetime: DateTime::parse_from_rfc3339(&datetime)?.with_timezone(&Utc),
+                trading_date: session,
+                interval_type: interval,
+                open_price: row.get("open_price"),
+                high_price: row.get("high_price"),
+                low_price: row.get("low_price"),
+                close_price: row.get("close_price"),
+                volume: row.get("volume"),
+            })
+        })
+        .collect()
+}
+
+/// 30-minute bar being built from 1-minute ones, which arrive in time order
+struct Bucket {
+    trading_date: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: Option<i64>,
+}
+
+/// Fold 1-minute bars from sessions more than `retention_days` before `today` into 30-minute
+/// bars and delete them. A 30-minute bar that was collected directly is kept as it is.
+pub async fn downsample_intraday_prices(pool: &SqlitePool, retention_days: i64, today: NaiveDate) -> Result<IntradayDownsampleResult> {
+    let cutoff = (today - Duration::days(retention_days)).to_string();
+    let mut tx = pool.begin().await?;
+
+    let rows = sqlx::query(
+        "SELECT stock_id, datetime, trading_date, open_price, high_price, low_price, close_price, volume
+         FROM intraday_prices
+         WHERE interval_type = '1min' AND trading_date < ?
+         ORDER BY stock_id, datetime"
+    )
+    .bind(&cutoff)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut buckets: BTreeMap<(i64, i64), Bucket> = BTreeMap::new();
+    for row in &rows {
+        let datetime: String = row.get("datetime");
+        let timestamp = DateTime::parse_from_rfc3339(&datetime)?.timestamp();
+        // Sessions open on the half hour in UTC both in and out of daylight time
+        let start = timestamp - timestamp.rem_euclid(30 * 60);
+        let (high, low, close, volume): (f64, f64, f64, Option<i64>) =
+            (row.get("high_price"), row.get("low_price"), row.get("close_price"), row.get("volume"));
+        buckets
+            .entry((row.get("stock_id"), start))
+            .and_modify(|bucket| {
+                bucket.high = bucket.high.max(high);
+                bucket.low = bucket.low.min(low);
+                bucket.close = close;
+                bucket.volume = match (bucket.volume, volume) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+            })
+            .or_insert_with(|| Bucket {
+                trading_date: row.get("trading_date"),
+                open: row.get("open_price"),
+                high,
+                low,
+                close,
+                volume,
+            });
+    }
+
+    let mut bars_written = 0;
+    for ((stock_id, start), bucket) in &buckets {
+        let datetime = Utc.timestamp_opt(*start, 0).single().expect("Valid timestamp");
+        bars_written += sqlx::query(
+            "INSERT OR IGNORE INTO intraday_prices
+                (stock_id, datetime, trading_date, interval_type, open_price, high_price, low_price, close_price, volume)
+             VALUES (?, ?, ?, '30min', ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id)
+        .bind(format_datetime(datetime))
+        .bind(&bucket.trading_date)
+        .bind(bucket.open)
+        .bind(bucket.high)
+        .bind(bucket.low)
+        .bind(bucket.close)
+        .bind(bucket.volume)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() as i64;
+    }
+
+    let minute_bars_removed = sqlx::query("DELETE FROM intraday_prices WHERE interval_type = '1min' AND trading_date < ?")
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() as i64;
+    tx.commit().await?;
+
+    Ok(IntradayDownsampleResult { minute_bars_removed, bars_written })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PriceFrequency;
+    use crate::tests::fixtures::migrated_pool;
+
+    /// Returns the same (time, price) minute bars for every symbol except FAIL
+    struct FixedBars(Vec<(DateTime<Utc>, f64)>);
+
+    #[async_trait::async_trait]
+    impl StockDataProvider for FixedBars {
+        async fn get_quotes(&self, _symbols: &[String]) -> Result<Vec<crate::models::SchwabQuote>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_price_history(&self, _: &str, _: NaiveDate, _: NaiveDate, _: PriceFrequency) -> Result<Vec<SchwabPriceBar>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_intraday_history(&self, symbol: &str, _: NaiveDate, _: IntradayInterval) -> Result<Vec<SchwabPriceBar>> {
+            if symbol == "FAIL" {
+                return Err(anyhow::anyhow!("no data"));
+            }
+            Ok(self.0.iter().map(|(utc, price)| minute_bar(*utc, *price)).collect())
+        }
+    }
+
+    fn minute_bar(utc: DateTime<Utc>, price: f64) -> SchwabPriceBar {
+        SchwabPriceBar {
+            datetime: utc.timestamp_millis(),
+            open: price,
+            high: price + 1.0,
+            low: price - 1.0,
+            close: price + 0.5,
+            volume: 100,
+            frequency: PriceFrequency::default(),
+        }
+    }
+
+    #[test]
+    fn test_trading_date_follows_new_york_daylight_time() {
+        // 2024 daylight time ran from March 10 to November 3
+        assert_eq!(new_york_offset(Utc.with_ymd_and_hms(2024, 3, 10, 6, 59, 0).unwrap()), Duration::hours(-5));
+        assert_eq!(new_york_offset(Utc.with_ymd_and_hms(2024, 3, 10, 7, 0, 0).unwrap()), Duration::hours(-4));
+        assert_eq!(new_york_offset(Utc.with_ymd_and_hms(2024, 11, 3, 6, 0, 0).unwrap()), Duration::hours(-5));
+        // 8pm in New York on June 3 is already June 4 in UTC
+        let evening = Utc.with_ymd_and_hms(2024, 6, 4, 0, 0, 0).unwrap();
+        assert_eq!(trading_date(evening), NaiveDate::from_ymd_opt(2024, 6, 3).unwrap());
+        assert!(!is_regular_session(evening));
+        assert!(is_regular_session(Utc.with_ymd_and_hms(2024, 6, 3, 13, 30, 0).unwrap()));
+        // 9:30 in January is 14:30 UTC; Saturdays are closed
+        assert!(!is_regular_session(Utc.with_ymd_and_hms(2024, 1, 8, 14, 29, 0).unwrap()));
+        assert!(!is_regular_session(Utc.with_ymd_and_hms(2024, 6, 8, 15, 0, 0).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_collect_watchlist_then_downsample_old_minutes() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name) VALUES
+                (1, 'AAPL', 'Apple Inc.'), (2, 'FAIL', 'Failing Co'), (3, 'MSFT', 'Microsoft Corp');
+             INSERT INTO stock_tags (stock_id, tag) VALUES (1, 'watchlist'), (2, 'watchlist')"
+        )
+        .execute(&pool).await.unwrap();
+
+        // June 3, 2024 opened at 9:30 EDT, 13:30 UTC: three minutes in the first half hour and one
+        // in the second, plus a bar from the previous session that is dropped
+        let session = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let open = Utc.with_ymd_and_hms(2024, 6, 3, 13, 30, 0).unwrap();
+        let provider = FixedBars(vec![
+            (open, 190.0),
+            (open + Duration::minutes(1), 192.0),
+            (open + Duration::minutes(29), 189.0),
+            (open + Duration::minutes(30), 195.0),
+            (Utc.with_ymd_and_hms(2024, 5, 31, 19, 59, 0).unwrap(), 180.0),
+        ]);
+
+        let collected = collect_watchlist_intraday(&pool, &provider, session, IntradayInterval::OneMinute).await.unwrap();
+        assert_eq!((collected.stocks_collected, collected.bars_stored), (1, 4));
+        assert_eq!(collected.failed_symbols, vec!["FAIL".to_string()]);
+
+        let bars = load_intraday_prices(&pool, 1, session, IntradayInterval::OneMinute).await.unwrap();
+        assert_eq!(bars.len(), 4);
+        assert_eq!(bars[0].datetime, open);
+        let stored: String = sqlx::query_scalar("SELECT datetime FROM intraday_prices ORDER BY datetime LIMIT 1")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, "2024-06-03T13:30:00Z");
+
+        // Still inside the retention window: nothing changes
+        let recent = downsample_intraday_prices(&pool, 30, NaiveDate::from_ymd_opt(2024, 6, 20).unwrap()).await.unwrap();
+        assert_eq!(recent, IntradayDownsampleResult { minute_bars_removed: 0, bars_written: 0 });
+
+        let old = downsample_intraday_prices(&pool, 30, NaiveDate::from_ymd_opt(2024, 7, 10).unwrap()).await.unwrap();
+        assert_eq!(old, IntradayDownsampleResult { minute_bars_removed: 4, bars_written: 2 });
+        assert!(load_intraday_prices(&pool, 1, session, IntradayInterval::OneMinute).await.unwrap().is_empty());
+
+        let half_hours = load_intraday_prices(&pool, 1, session, IntradayInterval::ThirtyMinutes).await.unwrap();
+        assert_eq!(half_hours.len(), 2);
+        let first = &half_hours[0];
+        assert_eq!(first.datetime, open);
+        assert_eq!(
+            (first.open_price, first.high_price, first.low_price, first.close_price, first.volume),
+            (190.0, 193.0, 188.0, 189.5, Some(300))
+        );
+        assert_eq!(half_hours[1].datetime, open + Duration::minutes(30));
+    }
+}
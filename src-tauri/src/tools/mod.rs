@@ -2,4 +2,31 @@ pub mod date_range_calculator;
 pub mod data_refresh_orchestrator;
 pub mod sec_edgar_client;
 pub mod freshness_types;
-pub mod freshness_checker;
\ No newline at end of file
+pub mod freshness_policy;
+pub mod freshness_checker;
+pub mod refresh_estimates;
+pub mod screening_readiness;
+pub mod universe_filter;
+pub mod screen_exclusions;
+pub mod refresh_runs;
+pub mod query_cache;
+pub mod ratio_recompute;
+pub mod company_profiles;
+pub mod fx_rates;
+pub mod shares_history;
+pub mod dividends;
+pub mod data_coverage;
+pub mod stock_archive;
+pub mod stock_merge;
+pub mod symbol_aliases;
+pub mod sp500_constituents;
+pub mod intraday_prices;
+pub mod quote_polling;
+pub mod option_chains;
+pub mod filing_reactions;
+pub mod stale_stocks;
+pub mod price_conflicts;
+pub mod refresh_scheduler;
+pub mod notifications;
+pub mod http_api;
+pub mod database_maintenance;
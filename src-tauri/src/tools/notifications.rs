@@ -0,0 +1,168 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use std::sync::{Arc, OnceLock};
+use tracing::warn;
+use ts_rs::TS;
+
+/// metadata table key holding the JSON-encoded preferences
+const NOTIFICATION_PREFERENCES_KEY: &str = "notification_preferences";
+
+/// Tauri event carrying a Notification to show in-app when the OS can't display it
+pub const NOTIFICATION_TOAST_EVENT: &str = "notification-toast";
+
+/// Which events raise a desktop notification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct NotificationPreferences {
+    pub refresh_completed: bool,
+    pub refresh_failed: bool,
+    pub alert_triggered: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            refresh_completed: true,
+            refresh_failed: true,
+            alert_triggered: true,
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn allows(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::RefreshCompleted => self.refresh_completed,
+            NotificationKind::RefreshFailed => self.refresh_failed,
+            NotificationKind::AlertTriggered => self.alert_triggered,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum NotificationKind {
+    RefreshCompleted,
+    RefreshFailed,
+    /// A price or valuation alert's condition was met
+    AlertTriggered,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+}
+
+/// Shows notifications to the user; the app's implementation wraps the Tauri notification plugin
+pub trait Notifier: Send + Sync {
+    fn notify(&self, notification: &Notification);
+}
+
+impl Notifier for tauri::AppHandle {
+    /// OS notification when permitted, otherwise an in-app toast event
+    fn notify(&self, notification: &Notification) {
+        use tauri::Emitter;
+        use tauri_plugin_notification::{NotificationExt, PermissionState};
+
+        let notifications = self.notification();
+        let shown = matches!(notifications.permission_state(), Ok(PermissionState::Granted))
+            && notifications
+                .builder()
+                .title(&notification.title)
+                .body(&notification.body)
+                .show()
+                .is_ok();
+        if !shown {
+            if let Err(e) = self.emit(NOTIFICATION_TOAST_EVENT, notification) {
+                warn!("Failed to emit notification toast: {}", e);
+            }
+        }
+    }
+}
+
+/// Notifier used by the orchestrator when none is given; the app sets its handle at startup
+static DEFAULT_NOTIFIER: OnceLock<Arc<dyn Notifier>> = OnceLock::new();
+
+pub fn set_default_notifier(notifier: Arc<dyn Notifier>) {
+    if DEFAULT_NOTIFIER.set(notifier).is_err() {
+        warn!("Default notifier already set");
+    }
+}
+
+pub fn default_notifier() -> Option<Arc<dyn Notifier>> {
+    DEFAULT_NOTIFIER.get().cloned()
+}
+
+/// Stored preferences, or the defaults when none have been saved (or they can't be parsed)
+pub async fn load_notification_preferences(pool: &SqlitePool) -> Result<NotificationPreferences> {
+    let row = sqlx::query("SELECT value FROM metadata WHERE key = ?")
+        .bind(NOTIFICATION_PREFERENCES_KEY)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row
+        .and_then(|row| serde_json::from_str(&row.get::<String, _>("value")).ok())
+        .unwrap_or_default())
+}
+
+pub async fn save_notification_preferences(pool: &SqlitePool, preferences: &NotificationPreferences) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO metadata (key, value, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(NOTIFICATION_PREFERENCES_KEY)
+    .bind(serde_json::to_string(preferences)?)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Show `notification` unless its kind is turned off. Returns whether it was sent.
+pub async fn dispatch_notification(pool: &SqlitePool, notifier: &dyn Notifier, notification: Notification) -> Result<bool> {
+    if !load_notification_preferences(pool).await?.allows(notification.kind) {
+        return Ok(false);
+    }
+    notifier.notify(&notification);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingNotifier(std::sync::Mutex<Vec<Notification>>);
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, notification: &Notification) {
+            self.0.lock().unwrap().push(notification.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_respects_per_event_preferences() {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query("CREATE TABLE metadata (key TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at DATETIME DEFAULT CURRENT_TIMESTAMP)")
+            .execute(&pool).await.unwrap();
+        let notifier = RecordingNotifier::default();
+        let alert = Notification {
+            kind: NotificationKind::AlertTriggered,
+            title: "AAPL alert".to_string(),
+            body: "P/E fell below 20".to_string(),
+        };
+
+        assert!(dispatch_notification(&pool, &notifier, alert.clone()).await.unwrap());
+
+        let preferences = NotificationPreferences { alert_triggered: false, ..NotificationPreferences::default() };
+        save_notification_preferences(&pool, &preferences).await.unwrap();
+        assert_eq!(load_notification_preferences(&pool).await.unwrap(), preferences);
+        assert!(!dispatch_notification(&pool, &notifier, alert.clone()).await.unwrap());
+
+        assert_eq!(*notifier.0.lock().unwrap(), vec![alert]);
+    }
+}
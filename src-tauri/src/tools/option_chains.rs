@@ -0,0 +1,338 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, SecondsFormat, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::api::StockDataProvider;
+use crate::models::{OptionData, SchwabOptionContract};
+use crate::tools::intraday_prices::{trading_date, WATCHLIST_TAG};
+
+/// A chain snapshot older than this is flagged as stale in the covered-call screen
+pub const STALE_SNAPSHOT_HOURS: i64 = 24;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct OptionChainSnapshot {
+    pub stock_id: i64,
+    pub symbol: String,
+    #[ts(type = "string")]
+    pub snapshot_time: DateTime<Utc>,
+    /// Monthly expirations stored, earliest first
+    #[ts(type = "Array<string>")]
+    pub expirations: Vec<NaiveDate>,
+    pub contracts_stored: i64,
+}
+
+/// An out-of-the-money call on a watchlist stock, priced from its latest chain snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CoveredCallCandidate {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// Latest stored close
+    pub current_price: f64,
+    pub price_date: String,
+    pub contract: OptionData,
+    pub days_to_expiry: i64,
+    /// Bid over the current price, in percent
+    pub premium_yield_pct: f64,
+    /// `premium_yield_pct` scaled to a 365-day year
+    pub annualized_premium_yield_pct: f64,
+    /// How far the stock can rise before the call is in the money, in percent
+    pub upside_to_strike_pct: f64,
+    /// The snapshot is more than `STALE_SNAPSHOT_HOURS` old
+    pub is_stale: bool,
+}
+
+/// Whether `date` is a standard monthly expiration: the third Friday, or the Thursday before it
+/// when that Friday is an exchange holiday
+pub fn is_monthly_expiration(date: NaiveDate) -> bool {
+    match date.weekday() {
+        Weekday::Fri => (15..=21).contains(&date.day()),
+        Weekday::Thu => (14..=20).contains(&date.day()),
+        _ => false,
+    }
+}
+
+/// The stored form of a snapshot time: RFC 3339 in UTC with a 'Z' suffix, so text order is time order
+fn format_snapshot_time(utc: DateTime<Utc>) -> String {
+    utc.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Store contracts under one snapshot, replacing any already stored for the same contract and
+/// snapshot time. Returns the number written.
+pub async fn store_option_chain(
+    pool: &SqlitePool,
+    stock_id: i64,
+    snapshot_time: DateTime<Utc>,
+    contracts: &[SchwabOptionContract],
+) -> Result<i64> {
+    let snapshot_time = format_snapshot_time(snapshot_time);
+    let mut tx = pool.begin().await?;
+    for contract in contracts {
+        sqlx::query(
+            "INSERT OR REPLACE INTO options_chains
+                (stock_id, snapshot_time, contract_symbol, expiration_date, strike_price, option_type, bid, ask,
+                 last_price, volume, open_interest, implied_volatility, delta, gamma, theta, vega, rho)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id)
+        .bind(&snapshot_time)
+        .bind(&contract.symbol)
+        .bind(contract.expiration_date.to_string())
+        .bind(contract.strike_price)
+        .bind(&contract.put_call)
+        .bind(contract.bid)
+        .bind(contract.ask)
+        .bind(contract.last)
+        .bind(contract.total_volume)
+        .bind(contract.open_interest)
+        .bind(contract.volatility)
+        .bind(contract.delta)
+        .bind(contract.gamma)
+        .bind(contract.theta)
+        .bind(contract.vega)
+        .bind(contract.rho)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(contracts.len() as i64)
+}
+
+/// Fetch a stock's chain for expirations between the dates and store its monthly expirations
+/// as one snapshot taken at `snapshot_time`. Weekly and quarterly expirations are dropped.
+pub async fn fetch_option_chain(
+    pool: &SqlitePool,
+    provider: &(impl StockDataProvider + Sync),
+    stock_id: i64,
+    symbol: &str,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    snapshot_time: DateTime<Utc>,
+) -> Result<OptionChainSnapshot> {
+    let contracts: Vec<SchwabOptionContract> = provider
+        .get_option_chain(symbol, from_date, to_date)
+        .await?
+        .into_iter()
+        .filter(|contract| is_monthly_expiration(contract.expiration_date))
+        .collect();
+
+    let mut expirations: Vec<NaiveDate> = contracts.iter().map(|contract| contract.expiration_date).collect();
+    expirations.sort();
+    expirations.dedup();
+    let contracts_stored = store_option_chain(pool, stock_id, snapshot_time, &contracts).await?;
+
+    Ok(OptionChainSnapshot { stock_id, symbol: symbol.to_string(), snapshot_time, expirations, contracts_stored })
+}
+
+/// Out-of-the-money calls on watchlist stocks from each stock's latest snapshot, expiring within
+/// `max_days_to_expiry` of `now`'s New York date, with at least `min_open_interest` contracts open
+/// and a bid worth `min_annualized_premium_yield` percent a year of the latest close. Highest
+/// annualized yield first.
+pub async fn covered_call_candidates(
+    pool: &SqlitePool,
+    min_annualized_premium_yield: f64,
+    max_days_to_expiry: i64,
+    min_open_interest: i64,
+    now: DateTime<Utc>,
+) -> Result<Vec<CoveredCallCandidate>> {
+    let today = trading_date(now);
+    let rows = sqlx::query(
+        "SELECT s.id AS stock_id, s.symbol, p.close_price, p.date AS price_date,
+                o.id, o.snapshot_time, o.contract_symbol, o.expiration_date, o.strike_price, o.option_type,
+                o.bid, o.ask, o.last_price, o.volume, o.open_interest, o.implied_volatility,
+                o.delta, o.gamma, o.theta, o.vega, o.rho
+         FROM stocks s
+         JOIN stock_tags t ON t.stock_id = s.id AND t.tag = ?
+         JOIN daily_prices p ON p.id = (
+             SELECT id FROM daily_prices WHERE stock_id = s.id ORDER BY date DESC LIMIT 1
+         )
+         JOIN options_chains o ON o.stock_id = s.id
+             AND o.snapshot_time = (SELECT MAX(snapshot_time) FROM options_chains WHERE stock_id = s.id)
+         WHERE o.option_type = 'CALL'
+           AND o.strike_price >= p.close_price
+           AND o.bid > 0
+           AND o.expiration_date > ? AND o.expiration_date <= ?
+           AND COALESCE(o.open_interest, 0) >= ?"
+    )
+    .bind(WATCHLIST_TAG)
+    .bind(today.to_string())
+    .bind((today + Duration::days(max_days_to_expiry)).to_string())
+    .bind(min_open_interest)
+    .fetch_all(pool)
+    .await?;
+
+    let mut candidates = Vec::new();
+    for row in &rows {
+        let current_price: f64 = row.get("close_price");
+        if current_price <= 0.0 {
+            continue;
+        }
+        let snapshot_time: String = row.get("snapshot_time");
+        let snapshot_time = DateTime::parse_from_rfc3339(&snapshot_time)?.with_timezone(&Utc);
+        let expiration_date: String = row.get("expiration_date");
+        let contract = OptionData {
+            id: row.get("id"),
+            stock_id: row.get("stock_id"),
+            symbol: row.get("contract_symbol"),
+            expiration_date: NaiveDate::parse_from_str(&expiration_date, "%Y-%m-%d")?,
+            strike_price: row.get("strike_price"),
+            option_type: row.get("option_type"),
+            bid: row.get("bid"),
+            ask: row.get("ask"),
+            last_price: row.get("last_price"),
+            volume: row.get("volume"),
+            open_interest: row.get("open_interest"),
+            implied_volatility: row.get("implied_volatility"),
+            delta: row.get("delta"),
+            gamma: row.get("gamma"),
+            theta: row.get("theta"),
+            vega: row.get("vega"),
+            rho: row.get("rho"),
+            snapshot_time,
+        };
+
+        let days_to_expiry = (contract.expiration_date - today).num_days();
+        let premium_yield_pct = contract.bid.unwrap_or(0.0) / current_price * 100.0;
+        let annualized_premium_yield_pct = premium_yield_pct * 365.0 / days_to_expiry as f64;
+        if annualized_premium_yield_pct < min_annualized_premium_yield {
+            continue;
+        }
+        candidates.push(CoveredCallCandidate {
+            stock_id: contract.stock_id,
+            symbol: row.get("symbol"),
+            current_price,
+            price_date: row.get("price_date"),
+            days_to_expiry,
+            premium_yield_pct,
+            annualized_premium_yield_pct,
+            upside_to_strike_pct: (contract.strike_price / current_price - 1.0) * 100.0,
+            is_stale: now - snapshot_time > Duration::hours(STALE_SNAPSHOT_HOURS),
+            contract,
+        });
+    }
+
+    candidates.sort_by(|a, b| b.annualized_premium_yield_pct.total_cmp(&a.annualized_premium_yield_pct));
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PriceFrequency, SchwabPriceBar};
+    use chrono::TimeZone;
+    use crate::tests::fixtures::migrated_pool;
+
+    /// Returns the same contracts for every symbol
+    struct FixedChain(Vec<SchwabOptionContract>);
+
+    #[async_trait::async_trait]
+    impl StockDataProvider for FixedChain {
+        async fn get_quotes(&self, _symbols: &[String]) -> Result<Vec<crate::models::SchwabQuote>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_price_history(&self, _: &str, _: NaiveDate, _: NaiveDate, _: PriceFrequency) -> Result<Vec<SchwabPriceBar>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_option_chain(&self, _: &str, _: NaiveDate, _: NaiveDate) -> Result<Vec<SchwabOptionContract>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn contract(put_call: &str, expiration: (i32, u32, u32), strike_price: f64, bid: f64, open_interest: i64) -> SchwabOptionContract {
+        let expiration_date = NaiveDate::from_ymd_opt(expiration.0, expiration.1, expiration.2).unwrap();
+        SchwabOptionContract {
+            symbol: format!("TEST {}{}{}", expiration_date.format("%y%m%d"), &put_call[..1], strike_price),
+            put_call: put_call.to_string(),
+            expiration_date,
+            strike_price,
+            bid: Some(bid),
+            ask: Some(bid + 0.05),
+            last: None,
+            total_volume: None,
+            open_interest: Some(open_interest),
+            volatility: Some(25.0),
+            delta: Some(0.3),
+            gamma: None,
+            theta: None,
+            vega: None,
+            rho: None,
+        }
+    }
+
+    #[test]
+    fn test_monthly_expirations_are_third_fridays() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert!(is_monthly_expiration(date(1, 19)));
+        assert!(!is_monthly_expiration(date(1, 26)));
+        assert!(!is_monthly_expiration(date(1, 12)));
+        // Juneteenth 2026 falls on the third Friday, moving the expiration to Thursday
+        assert!(is_monthly_expiration(NaiveDate::from_ymd_opt(2026, 6, 18).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_covered_calls_from_latest_snapshot_of_watchlist_stocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name) VALUES
+                (1, 'AAPL', 'Apple Inc.'), (2, 'MSFT', 'Microsoft Corp'), (3, 'IBM', 'IBM');
+             INSERT INTO stock_tags (stock_id, tag) VALUES (1, 'watchlist'), (2, 'watchlist');
+             INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price) VALUES
+                (1, '2024-01-08', 99.0, 99.0, 99.0, 99.0), (1, '2024-01-09', 100.0, 100.0, 100.0, 100.0),
+                (2, '2024-01-09', 200.0, 200.0, 200.0, 200.0), (3, '2024-01-09', 150.0, 150.0, 150.0, 150.0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        let now = Utc.with_ymd_and_hms(2024, 1, 10, 15, 0, 0).unwrap();
+        let (from, to) = (NaiveDate::from_ymd_opt(2024, 1, 10).unwrap(), NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+
+        // An older AAPL snapshot with a far richer bid, which the latest one replaces
+        let older = FixedChain(vec![contract("CALL", (2024, 1, 19), 105.0, 9.0, 500)]);
+        fetch_option_chain(&pool, &older, 1, "AAPL", from, to, now - Duration::hours(72)).await.unwrap();
+
+        let latest = FixedChain(vec![
+            contract("CALL", (2024, 1, 19), 105.0, 1.0, 500),
+            // In the money, a put, too little open interest, and too small a premium
+            contract("CALL", (2024, 1, 19), 95.0, 6.0, 500),
+            contract("PUT", (2024, 1, 19), 95.0, 1.0, 500),
+            contract("CALL", (2024, 2, 16), 110.0, 1.5, 50),
+            contract("CALL", (2024, 2, 16), 120.0, 0.2, 1000),
+            // A weekly, which isn't stored
+            contract("CALL", (2024, 1, 26), 105.0, 1.5, 500),
+        ]);
+        let snapshot = fetch_option_chain(&pool, &latest, 1, "AAPL", from, to, now).await.unwrap();
+        assert_eq!(snapshot.contracts_stored, 5);
+        assert_eq!(snapshot.expirations, vec![NaiveDate::from_ymd_opt(2024, 1, 19).unwrap(), NaiveDate::from_ymd_opt(2024, 2, 16).unwrap()]);
+
+        // Fetching the same snapshot again replaces rather than duplicates
+        fetch_option_chain(&pool, &latest, 1, "AAPL", from, to, now).await.unwrap();
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM options_chains WHERE stock_id = 1")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 6);
+
+        let msft = FixedChain(vec![contract("CALL", (2024, 1, 19), 210.0, 3.0, 1000)]);
+        fetch_option_chain(&pool, &msft, 2, "MSFT", from, to, now - Duration::hours(30)).await.unwrap();
+        fetch_option_chain(&pool, &msft, 3, "IBM", from, to, now).await.unwrap();
+
+        let candidates = covered_call_candidates(&pool, 5.0, 45, 100, now).await.unwrap();
+        assert_eq!(
+            candidates.iter().map(|c| (c.symbol.as_str(), c.contract.strike_price, c.is_stale)).collect::<Vec<_>>(),
+            vec![("MSFT", 210.0, true), ("AAPL", 105.0, false)]
+        );
+        // A $1 bid on a $100 stock nine days out: 1% now, about 40.6% a year
+        let aapl = &candidates[1];
+        assert_eq!((aapl.current_price, aapl.price_date.as_str(), aapl.days_to_expiry), (100.0, "2024-01-09", 9));
+        assert!((aapl.premium_yield_pct - 1.0).abs() < 1e-9);
+        assert!((aapl.annualized_premium_yield_pct - 365.0 / 9.0).abs() < 1e-9);
+        assert!((aapl.upside_to_strike_pct - 5.0).abs() < 1e-9);
+        assert_eq!(aapl.contract.snapshot_time, now);
+
+        // January's calls are out of reach of a week-long horizon
+        assert!(covered_call_candidates(&pool, 5.0, 7, 100, now).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+
+/// A stored market cap further than this fraction from close × shares outstanding
+pub const MARKET_CAP_TOLERANCE: f64 = 0.5;
+
+/// A computed valuation row whose price is further than this fraction from the day's close
+pub const VALUATION_PRICE_TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceConflictKind {
+    /// More than one row for the day, stored under differently formatted dates so the
+    /// UNIQUE(stock_id, date) constraint let both in
+    DuplicateDay,
+    /// High below low, or the open or close outside the day's range
+    InconsistentRange,
+    /// Stored market cap far from the close times the shares outstanding that day
+    MarketCapMismatch,
+    /// The daily_valuation_ratios price for the day differs from the close it was computed from
+    ValuationPriceMismatch,
+}
+
+/// A daily_prices row whose values disagree with themselves or with related tables
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PriceConflict {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub date: String,
+    pub kind: PriceConflictKind,
+    /// The value as stored in daily_prices, and what the related data says it should be, for
+    /// market cap and valuation price mismatches
+    pub stored_value: Option<f64>,
+    pub expected_value: Option<f64>,
+    pub detail: String,
+}
+
+/// Stock/date pairs in daily_prices with conflicting values, for review, by symbol, date and kind
+pub async fn find_price_conflicts(pool: &SqlitePool) -> Result<Vec<PriceConflict>> {
+    let mut conflicts = Vec::new();
+
+    let duplicates: Vec<(i64, String, String, i64, f64, f64)> = sqlx::query_as(
+        "SELECT p.stock_id, s.symbol, date(p.date) AS day, COUNT(*), MIN(p.close_price), MAX(p.close_price)
+         FROM daily_prices p JOIN stocks s ON s.id = p.stock_id
+         GROUP BY p.stock_id, day
+         HAVING COUNT(*) > 1"
+    )
+    .fetch_all(pool)
+    .await?;
+    conflicts.extend(duplicates.into_iter().map(|(stock_id, symbol, date, copies, min_close, max_close)| PriceConflict {
+        stock_id,
+        symbol,
+        date,
+        kind: PriceConflictKind::DuplicateDay,
+        stored_value: None,
+        expected_value: None,
+        detail: format!("{} rows for the day with closes from {} to {}", copies, min_close, max_close),
+    }));
+
+    let ranges: Vec<(i64, String, String, f64, f64, f64, f64)> = sqlx::query_as(
+        "SELECT p.stock_id, s.symbol, p.date, p.open_price, p.high_price, p.low_price, p.close_price
+         FROM daily_prices p JOIN stocks s ON s.id = p.stock_id
+         WHERE p.high_price < p.low_price
+            OR p.open_price NOT BETWEEN p.low_price AND p.high_price
+            OR p.close_price NOT BETWEEN p.low_price AND p.high_price"
+    )
+    .fetch_all(pool)
+    .await?;
+    conflicts.extend(ranges.into_iter().map(|(stock_id, symbol, date, open, high, low, close)| PriceConflict {
+        stock_id,
+        symbol,
+        date,
+        kind: PriceConflictKind::InconsistentRange,
+        stored_value: None,
+        expected_value: None,
+        detail: format!("Open {}, high {}, low {}, close {}", open, high, low, close),
+    }));
+
+    // Shares from the row itself, else the latest reported count on or before the day
+    let market_caps: Vec<(i64, String, String, f64, f64)> = sqlx::query_as(
+        "SELECT stock_id, symbol, date, market_cap, computed FROM (
+            SELECT p.stock_id, s.symbol, p.date, p.market_cap,
+                   p.close_price * COALESCE(p.shares_outstanding,
+                       (SELECT h.shares FROM shares_outstanding_history h
+                        WHERE h.stock_id = p.stock_id AND h.as_of_date <= p.date
+                        ORDER BY h.as_of_date DESC LIMIT 1)) AS computed
+            FROM daily_prices p JOIN stocks s ON s.id = p.stock_id
+            WHERE p.market_cap IS NOT NULL
+         )
+         WHERE computed > 0 AND ABS(market_cap - computed) > ? * computed"
+    )
+    .bind(MARKET_CAP_TOLERANCE)
+    .fetch_all(pool)
+    .await?;
+    conflicts.extend(market_caps.into_iter().map(|(stock_id, symbol, date, market_cap, computed)| PriceConflict {
+        stock_id,
+        symbol,
+        date,
+        kind: PriceConflictKind::MarketCapMismatch,
+        stored_value: Some(market_cap),
+        expected_value: Some(computed),
+        detail: format!("Market cap {} is {:.1}x close × shares outstanding", market_cap, market_cap / computed),
+    }));
+
+    let valuation_prices: Vec<(i64, String, String, f64, f64)> = sqlx::query_as(
+        "SELECT p.stock_id, s.symbol, p.date, p.close_price, v.price
+         FROM daily_prices p
+         JOIN daily_valuation_ratios v ON v.stock_id = p.stock_id AND v.date = p.date
+         JOIN stocks s ON s.id = p.stock_id
+         WHERE v.price IS NOT NULL AND ABS(v.price - p.close_price) > ? * p.close_price"
+    )
+    .bind(VALUATION_PRICE_TOLERANCE)
+    .fetch_all(pool)
+    .await?;
+    conflicts.extend(valuation_prices.into_iter().map(|(stock_id, symbol, date, close, valuation_price)| PriceConflict {
+        stock_id,
+        symbol,
+        date,
+        kind: PriceConflictKind::ValuationPriceMismatch,
+        stored_value: Some(close),
+        expected_value: Some(valuation_price),
+        detail: format!("Close {} but valuation ratios computed from {}", close, valuation_price),
+    }));
+
+    conflicts.sort_by(|a, b| (&a.symbol, &a.date, a.kind).cmp(&(&b.symbol, &b.date, b.kind)));
+    Ok(conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{day, FixtureBuilder, PriceBar, ValuationDay};
+
+    #[tokio::test]
+    async fn test_inconsistent_rows_are_reported() {
+        let fixture = FixtureBuilder::new()
+            .with_stock("GOOD")
+            .with_prices(day(2024, 1, 2)..=day(2024, 1, 5), |_, _| PriceBar::flat(100.0))
+            .with_stock("BAD")
+            .with_prices(day(2024, 1, 2)..=day(2024, 1, 5), |_, _| PriceBar::flat(50.0))
+            .with_valuation_ratios(day(2024, 1, 2)..=day(2024, 1, 3), |i, _| ValuationDay {
+                price: Some(if i == 0 { 50.0 } else { 5.0 }),
+                ..Default::default()
+            })
+            .build()
+            .await;
+        let pool = &fixture.pool;
+
+        // Both have a million shares; GOOD's caps agree with that, BAD's cap on the 4th is ten times over
+        for stock_id in [1, 2] {
+            sqlx::query("INSERT INTO shares_outstanding_history (stock_id, as_of_date, shares, concept) VALUES (?, '2023-12-31', 1e6, 'dei')")
+                .bind(stock_id)
+                .execute(pool).await.unwrap();
+        }
+        sqlx::query("UPDATE daily_prices SET market_cap = close_price * 1e6 * (CASE WHEN stock_id = 2 AND date = '2024-01-04' THEN 10 ELSE 1.2 END)")
+            .execute(pool).await.unwrap();
+        // A second source's row for the 2nd under a timestamped date, and a high below the low on the 5th
+        sqlx::query(
+            "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+             VALUES (2, '2024-01-02 00:00:00', 52.0, 52.0, 52.0, 52.0)"
+        )
+        .execute(pool).await.unwrap();
+        sqlx::query("UPDATE daily_prices SET high_price = 45.0 WHERE stock_id = 2 AND date = '2024-01-05'")
+            .execute(pool).await.unwrap();
+
+        let conflicts = find_price_conflicts(pool).await.unwrap();
+        let found: Vec<(&str, &str, PriceConflictKind)> =
+            conflicts.iter().map(|c| (c.symbol.as_str(), c.date.as_str(), c.kind)).collect();
+        assert_eq!(found, vec![
+            ("BAD", "2024-01-02", PriceConflictKind::DuplicateDay),
+            ("BAD", "2024-01-03", PriceConflictKind::ValuationPriceMismatch),
+            ("BAD", "2024-01-04", PriceConflictKind::MarketCapMismatch),
+            ("BAD", "2024-01-05", PriceConflictKind::InconsistentRange),
+        ]);
+
+        assert_eq!(conflicts[0].detail, "2 rows for the day with closes from 50 to 52");
+        assert_eq!((conflicts[1].stored_value, conflicts[1].expected_value), (Some(50.0), Some(5.0)));
+        assert_eq!((conflicts[2].stored_value, conflicts[2].expected_value), (Some(5e8), Some(5e7)));
+    }
+}
@@ -0,0 +1,175 @@
+// TTL cache for read-mostly aggregate queries the frontend repeats on every navigation.
+// Writers call invalidate_all() before reporting success, so a cached result never outlives
+// the data it was computed from.
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// Lifetime of a cached result unless QUERY_CACHE_TTL_SECONDS overrides it
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Results that may be served from the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CachedQuery {
+    DatabaseStats,
+    DataFreshnessStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CacheStats {
+    pub hits: i64,
+    pub misses: i64,
+    pub invalidations: i64,
+    /// Entries currently stored, including expired ones not yet replaced
+    pub entries: i64,
+    pub ttl_seconds: i64,
+}
+
+struct CacheEntry {
+    stored_at: Instant,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+pub struct QueryCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<CachedQuery, CacheEntry>>,
+    /// Bumped by every invalidation; a result computed across one is returned but not stored
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl QueryCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    fn lookup<T: Clone + 'static>(&self, key: CachedQuery) -> Option<T> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&key)
+            .filter(|entry| entry.stored_at.elapsed() < self.ttl)
+            .and_then(|entry| entry.value.downcast_ref::<T>().cloned())
+    }
+
+    /// Cached value for `key`, or the result of `compute`, stored if it succeeds
+    pub async fn get_or_compute<T, E, F, Fut>(&self, key: CachedQuery, compute: F) -> Result<T, E>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(value) = self.lookup::<T>(key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let value = compute().await?;
+
+        // Checked under the write lock, which invalidate_all also holds while bumping the generation
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        if self.generation.load(Ordering::SeqCst) == generation {
+            entries.insert(key, CacheEntry { stored_at: Instant::now(), value: Arc::new(value.clone()) });
+        }
+
+        Ok(value)
+    }
+
+    /// Drop every cached result; call after any write that could change them
+    pub fn invalidate_all(&self) {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        entries.clear();
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed) as i64,
+            misses: self.misses.load(Ordering::Relaxed) as i64,
+            invalidations: self.invalidations.load(Ordering::Relaxed) as i64,
+            entries: self.entries.read().unwrap_or_else(|e| e.into_inner()).len() as i64,
+            ttl_seconds: self.ttl.as_secs() as i64,
+        }
+    }
+}
+
+/// Cache shared by the commands and every writer that must invalidate it
+pub fn global_query_cache() -> &'static QueryCache {
+    static CACHE: OnceLock<QueryCache> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        let ttl = std::env::var("QUERY_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_CACHE_TTL);
+        QueryCache::new(ttl)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hits_misses_expiry_and_invalidation() {
+        let cache = QueryCache::new(Duration::from_secs(60));
+        let compute = |v: i64| move || async move { Ok::<_, String>(v) };
+
+        assert_eq!(cache.get_or_compute(CachedQuery::DatabaseStats, compute(1)).await, Ok(1));
+        assert_eq!(cache.get_or_compute(CachedQuery::DatabaseStats, compute(2)).await, Ok(1));
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        // Errors are returned and never cached
+        let failed = cache
+            .get_or_compute(CachedQuery::DataFreshnessStatus, || async { Err::<i64, _>("db locked".to_string()) })
+            .await;
+        assert!(failed.is_err());
+        assert_eq!(cache.stats().entries, 1);
+
+        cache.invalidate_all();
+        assert_eq!(cache.get_or_compute(CachedQuery::DatabaseStats, compute(3)).await, Ok(3));
+        assert_eq!(cache.stats().invalidations, 1);
+
+        // A zero TTL never serves a stored value
+        let expired = QueryCache::new(Duration::ZERO);
+        expired.get_or_compute(CachedQuery::DatabaseStats, compute(1)).await.unwrap();
+        assert_eq!(expired.get_or_compute(CachedQuery::DatabaseStats, compute(2)).await, Ok(2));
+    }
+
+    #[tokio::test]
+    async fn test_result_computed_across_an_invalidation_is_not_stored() {
+        let cache = QueryCache::new(Duration::from_secs(60));
+
+        // A write lands while the read is still computing from the old data
+        let stale = cache
+            .get_or_compute(CachedQuery::DatabaseStats, || async {
+                cache.invalidate_all();
+                Ok::<_, String>("before write")
+            })
+            .await;
+        assert_eq!(stale, Ok("before write"));
+
+        let fresh = cache
+            .get_or_compute(CachedQuery::DatabaseStats, || async { Ok::<_, String>("after write") })
+            .await;
+        assert_eq!(fresh, Ok("after write"));
+    }
+}
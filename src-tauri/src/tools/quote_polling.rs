@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use ts_rs::TS;
+
+use crate::api::StockDataProvider;
+use crate::commands::quotes::{to_real_time_quote, QuoteFailure};
+use crate::commands::tags::get_stocks_by_tag_internal;
+use crate::models::{RealTimeQuote, SchwabQuote};
+use crate::tools::intraday_prices::{is_regular_session, trading_date};
+
+/// Tauri event carrying QuoteUpdate payloads
+pub const QUOTE_UPDATE_EVENT: &str = "quote-update";
+
+/// Symbols sent in one quotes request
+pub const QUOTE_SYMBOLS_PER_REQUEST: usize = 100;
+
+/// Longest wait between polls while the market is closed
+pub const MAX_CLOSED_MARKET_DELAY: Duration = Duration::from_secs(30 * 60);
+
+/// Pushed after every poll with that poll's quotes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QuoteUpdate {
+    pub watchlist_id: String,
+    pub quotes: Vec<RealTimeQuote>,
+    pub failures: Vec<QuoteFailure>,
+    pub market_open: bool,
+    /// Seconds until the next poll, longer while the market is closed
+    pub next_poll_secs: u64,
+}
+
+/// Receives quotes as they are polled
+pub trait QuoteUpdateSink: Send + Sync {
+    fn send_quotes(&self, update: &QuoteUpdate);
+}
+
+impl QuoteUpdateSink for tauri::AppHandle {
+    fn send_quotes(&self, update: &QuoteUpdate) {
+        use tauri::Emitter;
+        if let Err(e) = self.emit(QUOTE_UPDATE_EVENT, update) {
+            warn!("Failed to emit quote update: {}", e);
+        }
+    }
+}
+
+/// Sink the poller sends to; the app sets its handle at startup
+static DEFAULT_QUOTE_SINK: OnceLock<Arc<dyn QuoteUpdateSink>> = OnceLock::new();
+
+pub fn set_default_quote_sink(sink: Arc<dyn QuoteUpdateSink>) {
+    if DEFAULT_QUOTE_SINK.set(sink).is_err() {
+        warn!("Quote update sink already set");
+    }
+}
+
+/// Latest polled quote per symbol; kept in memory only, never written to real_time_quotes
+fn latest_quotes() -> &'static Mutex<HashMap<String, RealTimeQuote>> {
+    static LATEST_QUOTES: OnceLock<Mutex<HashMap<String, RealTimeQuote>>> = OnceLock::new();
+    LATEST_QUOTES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The latest quote polled for each symbol, by symbol
+pub fn polled_quotes() -> Vec<RealTimeQuote> {
+    let mut quotes: Vec<RealTimeQuote> = latest_quotes().lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect();
+    quotes.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+    quotes
+}
+
+struct Poller {
+    watchlist_id: String,
+    task: JoinHandle<()>,
+}
+
+/// The one poller running in this process, if any
+fn poller() -> &'static Mutex<Option<Poller>> {
+    static POLLER: OnceLock<Mutex<Option<Poller>>> = OnceLock::new();
+    POLLER.get_or_init(|| Mutex::new(None))
+}
+
+/// Shortest poll interval in seconds that keeps `symbol_count` symbols within the provider's
+/// requests per minute
+pub fn minimum_poll_interval_secs(symbol_count: usize, rate_limit_per_minute: u32) -> u64 {
+    let requests = symbol_count.div_ceil(QUOTE_SYMBOLS_PER_REQUEST).max(1) as u64;
+    (requests * 60).div_ceil(rate_limit_per_minute.max(1) as u64)
+}
+
+/// Wait before the next poll: the requested interval while the market is open, otherwise double
+/// the previous wait up to MAX_CLOSED_MARKET_DELAY
+pub fn next_poll_delay(interval: Duration, market_open: bool, previous: Duration) -> Duration {
+    if market_open {
+        interval
+    } else {
+        (previous * 2).clamp(interval, MAX_CLOSED_MARKET_DELAY.max(interval))
+    }
+}
+
+/// Each stock's close on the last trading day before `session`
+async fn load_previous_closes(pool: &SqlitePool, stock_ids: &[i64], session: NaiveDate) -> Result<HashMap<i64, f64>> {
+    if stock_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let placeholders = stock_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+    let query = format!(
+        "SELECT p.stock_id, p.close_price
+         FROM daily_prices p
+         WHERE p.date = (SELECT MAX(d.date) FROM daily_prices d WHERE d.stock_id = p.stock_id AND d.date < ?)
+           AND p.stock_id IN ({})",
+        placeholders
+    );
+    let mut query_builder = sqlx::query(&query).bind(session.to_string());
+    for stock_id in stock_ids {
+        query_builder = query_builder.bind(stock_id);
+    }
+    let rows = query_builder.fetch_all(pool).await?;
+
+    Ok(rows.iter().map(|row| (row.get("stock_id"), row.get("close_price"))).collect())
+}
+
+/// Fetch quotes for the stocks tagged `watchlist_id`, measure each against the stock's previous
+/// close in daily_prices (the provider's close when there is none), and keep them as the latest
+pub async fn poll_watchlist_quotes(
+    pool: &SqlitePool,
+    provider: &(impl StockDataProvider + Sync),
+    watchlist_id: &str,
+    now: DateTime<Utc>,
+) -> Result<(Vec<RealTimeQuote>, Vec<QuoteFailure>)> {
+    let stocks = get_stocks_by_tag_internal(pool, watchlist_id).await?;
+    let symbols: Vec<String> = stocks.iter().map(|stock| stock.symbol.clone()).collect();
+
+    let mut returned: HashMap<String, SchwabQuote> = HashMap::new();
+    for chunk in symbols.chunks(QUOTE_SYMBOLS_PER_REQUEST) {
+        for quote in provider.get_quotes(chunk).await? {
+            returned.insert(quote.symbol.to_uppercase(), quote);
+        }
+    }
+
+    let stock_ids: Vec<i64> = stocks.iter().map(|stock| stock.id).collect();
+    let previous_closes = load_previous_closes(pool, &stock_ids, trading_date(now)).await?;
+
+    let mut quotes = Vec::new();
+    let mut failures = Vec::new();
+    for stock in stocks {
+        match returned.remove(&stock.symbol.to_uppercase()) {
+            Some(quote) if quote.last_price > 0.0 => {
+                let previous_close = previous_closes.get(&stock.id).copied().or(quote.close_price);
+                quotes.push(to_real_time_quote(stock.id, &quote, previous_close, now));
+            }
+            Some(_) => failures.push(QuoteFailure { symbol: stock.symbol, reason: "Quote has no last price".to_string() }),
+            None => failures.push(QuoteFailure { symbol: stock.symbol, reason: "No quote returned".to_string() }),
+        }
+    }
+
+    let mut latest = latest_quotes().lock().unwrap_or_else(|e| e.into_inner());
+    for quote in &quotes {
+        latest.insert(quote.symbol.clone(), quote.clone());
+    }
+    Ok((quotes, failures))
+}
+
+async fn run_quote_polling<P: StockDataProvider + Send + Sync>(pool: SqlitePool, provider: P, watchlist_id: String, interval: Duration) {
+    let mut delay = interval;
+    loop {
+        let now = Utc::now();
+        let market_open = is_regular_session(now);
+        delay = next_poll_delay(interval, market_open, delay);
+
+        match poll_watchlist_quotes(&pool, &provider, &watchlist_id, now).await {
+            Ok((quotes, failures)) => {
+                if let Some(sink) = DEFAULT_QUOTE_SINK.get() {
+                    sink.send_quotes(&QuoteUpdate {
+                        watchlist_id: watchlist_id.clone(),
+                        quotes,
+                        failures,
+                        market_open,
+                        next_poll_secs: delay.as_secs(),
+                    });
+                }
+            }
+            Err(e) => warn!("Quote poll for watchlist '{}' failed: {}", watchlist_id, e),
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Poll the watchlist every `interval` in the background, replacing any poller already running
+pub fn start_quote_polling<P: StockDataProvider + Send + Sync + 'static>(
+    pool: SqlitePool,
+    provider: P,
+    watchlist_id: String,
+    interval: Duration,
+) {
+    let task = tokio::spawn(run_quote_polling(pool, provider, watchlist_id.clone(), interval));
+    info!("Polling quotes for watchlist '{}' every {}s", watchlist_id, interval.as_secs());
+    if let Some(previous) = poller().lock().unwrap_or_else(|e| e.into_inner()).replace(Poller { watchlist_id, task }) {
+        previous.task.abort();
+        info!("Stopped polling quotes for watchlist '{}'", previous.watchlist_id);
+    }
+}
+
+/// Stop the running poller; false if none was running. Polled quotes stay available.
+pub fn stop_quote_polling() -> bool {
+    match poller().lock().unwrap_or_else(|e| e.into_inner()).take() {
+        Some(previous) => {
+            previous.task.abort();
+            info!("Stopped polling quotes for watchlist '{}'", previous.watchlist_id);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PriceFrequency, SchwabPriceBar};
+    use chrono::TimeZone;
+    use crate::tests::fixtures::migrated_pool;
+
+    /// Fixed last prices; symbols without one are left out of the response
+    struct FixedQuotes(Vec<(&'static str, f64)>);
+
+    #[async_trait::async_trait]
+    impl StockDataProvider for FixedQuotes {
+        async fn get_quotes(&self, symbols: &[String]) -> Result<Vec<SchwabQuote>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|(symbol, _)| symbols.iter().any(|s| s == symbol))
+                .map(|(symbol, last_price)| SchwabQuote {
+                    symbol: symbol.to_string(),
+                    last_price: *last_price,
+                    open_price: None,
+                    high_price: None,
+                    low_price: None,
+                    // Schwab's own idea of the previous close, used only without a stored one
+                    close_price: Some(50.0),
+                    volume: None,
+                    pe_ratio: None,
+                    market_cap: None,
+                    dividend_yield: None,
+                })
+                .collect())
+        }
+
+        async fn get_price_history(&self, _: &str, _: NaiveDate, _: NaiveDate, _: PriceFrequency) -> Result<Vec<SchwabPriceBar>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_interval_floor_and_closed_market_backoff() {
+        // 250 symbols take 3 requests; at 120 a minute that is one poll every 2 seconds
+        assert_eq!(minimum_poll_interval_secs(250, 120), 2);
+        assert_eq!(minimum_poll_interval_secs(5, 120), 1);
+        assert_eq!(minimum_poll_interval_secs(5, 4), 15);
+
+        let interval = Duration::from_secs(10);
+        assert_eq!(next_poll_delay(interval, true, Duration::from_secs(640)), interval);
+        assert_eq!(next_poll_delay(interval, false, interval), Duration::from_secs(20));
+        assert_eq!(next_poll_delay(interval, false, Duration::from_secs(1500)), MAX_CLOSED_MARKET_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_poll_measures_change_from_previous_stored_close() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name) VALUES
+                (1, 'QPA', 'Poll A'), (2, 'QPB', 'Poll B'), (3, 'QPC', 'Poll C'), (4, 'QPD', 'Poll D');
+             INSERT INTO stock_tags (stock_id, tag) VALUES (1, 'tech'), (2, 'tech'), (3, 'tech');
+             INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price, volume) VALUES
+                (1, '2024-06-03', 100, 100, 100, 100, 1000),
+                (1, '2024-06-04', 104, 104, 104, 104, 1000)"
+        )
+        .execute(&pool).await.unwrap();
+
+        // Mid-session on June 4: today's stored bar is ignored, so the change is against June 3
+        let now = Utc.with_ymd_and_hms(2024, 6, 4, 15, 0, 0).unwrap();
+        let provider = FixedQuotes(vec![("QPA", 110.0), ("QPB", 55.0), ("QPD", 1.0)]);
+        let (quotes, failures) = poll_watchlist_quotes(&pool, &provider, "Tech", now).await.unwrap();
+
+        assert_eq!(quotes.iter().map(|q| q.symbol.as_str()).collect::<Vec<_>>(), vec!["QPA", "QPB"]);
+        assert_eq!((quotes[0].change_amount, quotes[0].change_percent), (Some(10.0), Some(10.0)));
+        // No stored close: the provider's is used
+        assert_eq!((quotes[1].change_amount, quotes[1].change_percent), (Some(5.0), Some(10.0)));
+        assert_eq!(failures, vec![QuoteFailure { symbol: "QPC".to_string(), reason: "No quote returned".to_string() }]);
+
+        let polled: Vec<(String, f64)> = polled_quotes()
+            .into_iter()
+            .filter(|q| q.symbol.starts_with("QP"))
+            .map(|q| (q.symbol, q.last_price))
+            .collect();
+        assert_eq!(polled, vec![("QPA".to_string(), 110.0), ("QPB".to_string(), 55.0)]);
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM real_time_quotes").fetch_one(&pool).await.unwrap();
+        assert_eq!(stored, 0);
+    }
+}
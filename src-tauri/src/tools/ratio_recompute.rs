@@ -0,0 +1,290 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use tracing::{info, warn};
+use ts_rs::TS;
+
+use crate::analysis::quality::refresh_quality_metrics;
+use crate::analysis::valuation_ratios::backfill_daily_ratios;
+use crate::tools::query_cache::global_query_cache;
+use crate::tools::refresh_estimates::{record_refresh_throughput, RATIOS_DATA_SOURCE};
+
+/// Stocks recomputed between progress updates and cancellation checks
+pub const RECOMPUTE_CHUNK_SIZE: usize = 25;
+
+/// operation_type of recompute sessions in refresh_progress
+const OPERATION_TYPE: &str = "ratios";
+
+/// A ratio recompute session as recorded in refresh_progress
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RatioRecomputeProgress {
+    pub session_id: String,
+    pub total_stocks: i32,
+    pub processed_stocks: i32,
+    pub percent_complete: f64,
+    /// running, completed, cancelled or error
+    pub status: String,
+    pub rows_written: i64,
+    pub error_details: Option<String>,
+}
+
+/// Rebuild the stored daily ratio series of every S&P 500 stock, in chunks. Progress goes to
+/// refresh_progress under `session_id`; setting that row's status to cancelled stops the run
+/// at the next chunk boundary.
+pub async fn recompute_all_ratios(pool: &SqlitePool, session_id: &str) -> Result<RatioRecomputeProgress> {
+    let started = std::time::Instant::now();
+    let stock_ids: Vec<i64> = sqlx::query_scalar(
+        "SELECT s.id FROM stocks s INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol ORDER BY s.symbol"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO refresh_progress (session_id, operation_type, total_steps, current_step_name, initiated_by, data_sources_refreshed)
+         VALUES (?, ?, ?, 'Recomputing ratios', 'user', '[\"daily_valuation_ratios\"]')"
+    )
+    .bind(session_id)
+    .bind(OPERATION_TYPE)
+    .bind(stock_ids.len() as i32)
+    .execute(pool)
+    .await?;
+    info!(session_id, stocks = stock_ids.len(), "Starting ratio recompute");
+
+    let mut processed = 0;
+    let mut rows_written = 0;
+    let mut cancelled = false;
+    let mut failure = None;
+
+    for chunk in stock_ids.chunks(RECOMPUTE_CHUNK_SIZE) {
+        if is_cancelled(pool, session_id).await? {
+            cancelled = true;
+            break;
+        }
+
+        for stock_id in chunk {
+            // Quality metrics come from the same statements, so they are rebuilt alongside
+            let recomputed = match backfill_daily_ratios(pool, *stock_id).await {
+                Ok(rows) => refresh_quality_metrics(pool, *stock_id).await.map(|_| rows),
+                Err(e) => Err(e),
+            };
+            match recomputed {
+                Ok(rows) => rows_written += rows,
+                Err(e) => {
+                    failure = Some(format!("stock {}: {}", stock_id, e));
+                    break;
+                }
+            }
+            processed += 1;
+        }
+        if failure.is_some() {
+            break;
+        }
+
+        let percent = processed as f64 / stock_ids.len() as f64 * 100.0;
+        sqlx::query(
+            "UPDATE refresh_progress
+             SET completed_steps = ?, current_step_progress = ?, total_records_processed = ?
+             WHERE session_id = ?"
+        )
+        .bind(processed)
+        .bind(percent)
+        .bind(rows_written)
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+    }
+
+    let status = match (&failure, cancelled) {
+        (Some(e), _) => {
+            warn!(session_id, "Ratio recompute failed: {}", e);
+            "error"
+        }
+        (None, true) => "cancelled",
+        (None, false) => "completed",
+    };
+    sqlx::query(
+        "UPDATE refresh_progress
+         SET status = ?, end_time = CURRENT_TIMESTAMP, error_details = ?,
+             current_step_progress = CASE WHEN ? = 'completed' THEN 100.0 ELSE current_step_progress END
+         WHERE session_id = ?"
+    )
+    .bind(status)
+    .bind(&failure)
+    .bind(status)
+    .bind(session_id)
+    .execute(pool)
+    .await?;
+
+    if rows_written > 0 {
+        global_query_cache().invalidate_all();
+    }
+    // Completed runs feed the next duration estimate
+    if status == "completed" {
+        record_refresh_throughput(pool, RATIOS_DATA_SOURCE, processed, rows_written, processed, started.elapsed().as_secs_f64()).await?;
+    }
+    info!(session_id, processed, rows_written, status, "Ratio recompute finished");
+
+    load_ratio_recompute_progress(pool, session_id)
+        .await?
+        .ok_or_else(|| anyhow!("Progress row for session {} disappeared", session_id))
+}
+
+/// Rebuild the stored daily ratios and quality metrics of just `stock_ids`, e.g. the stocks a
+/// financial refresh gave new statements; other stocks' rows are left untouched. Returns the
+/// number of ratio rows written.
+pub async fn recompute_for_stocks(pool: &SqlitePool, stock_ids: &[i64]) -> Result<i64> {
+    let mut rows_written = 0;
+    for stock_id in stock_ids {
+        rows_written += backfill_daily_ratios(pool, *stock_id).await?;
+        refresh_quality_metrics(pool, *stock_id).await?;
+    }
+    if rows_written > 0 {
+        global_query_cache().invalidate_all();
+    }
+    Ok(rows_written)
+}
+
+async fn is_cancelled(pool: &SqlitePool, session_id: &str) -> Result<bool> {
+    let status: Option<String> = sqlx::query_scalar("SELECT status FROM refresh_progress WHERE session_id = ?")
+        .bind(session_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(status.as_deref() == Some("cancelled"))
+}
+
+/// Ask a running recompute to stop; returns false if no such session is running
+pub async fn cancel_ratio_recompute(pool: &SqlitePool, session_id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE refresh_progress SET status = 'cancelled'
+         WHERE session_id = ? AND operation_type = ? AND status = 'running'"
+    )
+    .bind(session_id)
+    .bind(OPERATION_TYPE)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn load_ratio_recompute_progress(pool: &SqlitePool, session_id: &str) -> Result<Option<RatioRecomputeProgress>> {
+    let row = sqlx::query(
+        "SELECT session_id, total_steps, completed_steps, current_step_progress, status,
+                total_records_processed, error_details
+         FROM refresh_progress
+         WHERE session_id = ? AND operation_type = ?"
+    )
+    .bind(session_id)
+    .bind(OPERATION_TYPE)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| RatioRecomputeProgress {
+        session_id: row.get("session_id"),
+        total_stocks: row.get("total_steps"),
+        processed_stocks: row.get("completed_steps"),
+        percent_complete: row.get("current_step_progress"),
+        status: row.get("status"),
+        rows_written: row.get("total_records_processed"),
+        error_details: row.get("error_details"),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+
+        // Three S&P 500 stocks with a year of financials and two prices each; OTHER is outside the universe
+        for (id, symbol) in [(1, "AAA"), (2, "BBB"), (3, "CCC"), (4, "OTHER")] {
+            sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (?, ?, ?, ?)")
+                .bind(id).bind(symbol).bind(symbol).bind(symbol != "OTHER")
+                .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income)
+                 VALUES (?, 'FY', '2023-12-31', 2023, 1000.0, 100.0)"
+            )
+            .bind(id)
+            .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, shares_outstanding)
+                 VALUES (?, 'Annual', '2023-12-31', 2023, 10.0)"
+            )
+            .bind(id)
+            .execute(&pool).await.unwrap();
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price) VALUES
+                    (?1, '2024-01-02', 50.0, 50.0, 50.0, 50.0),
+                    (?1, '2024-01-03', 60.0, 60.0, 60.0, 60.0)"
+            )
+            .bind(id)
+            .execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_recompute_refreshes_universe_and_reaches_full_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+        // A ratio row left over from an old formula
+        sqlx::query("INSERT INTO daily_valuation_ratios (stock_id, date, ps_ratio_ttm) VALUES (1, '2024-01-02', 99.0)")
+            .execute(&pool).await.unwrap();
+
+        let progress = recompute_all_ratios(&pool, "recompute-1").await.unwrap();
+        assert_eq!(progress.status, "completed");
+        assert_eq!((progress.total_stocks, progress.processed_stocks, progress.rows_written), (3, 3, 6));
+        assert_eq!(progress.percent_complete, 100.0);
+
+        // Market cap 50 x 10 shares over 1000 revenue
+        let ps: f64 = sqlx::query_scalar("SELECT ps_ratio_ttm FROM daily_valuation_ratios WHERE stock_id = 1 AND date = '2024-01-02'")
+            .fetch_one(&pool).await.unwrap();
+        assert!((ps - 0.5).abs() < 1e-9);
+        let outside: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM daily_valuation_ratios WHERE stock_id = 4")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(outside, 0);
+
+        // The run's throughput feeds the ratios duration estimate
+        let tracked: (i64, i64) = sqlx::query_as("SELECT stocks_processed, records_processed FROM refresh_tracking WHERE data_source = ?")
+            .bind(RATIOS_DATA_SOURCE)
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(tracked, (3, 6));
+
+        // Finished sessions can't be cancelled
+        assert!(!cancel_ratio_recompute(&pool, "recompute-1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_recompute_for_stocks_leaves_other_stocks_rows_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+        recompute_all_ratios(&pool, "recompute-3").await.unwrap();
+        sqlx::query("UPDATE daily_valuation_ratios SET created_at = '2000-01-01 00:00:00'")
+            .execute(&pool).await.unwrap();
+
+        assert_eq!(recompute_for_stocks(&pool, &[2]).await.unwrap(), 2);
+
+        let untouched: Vec<(i64, i64)> = sqlx::query_as(
+            "SELECT stock_id, SUM(created_at = '2000-01-01 00:00:00') FROM daily_valuation_ratios GROUP BY stock_id ORDER BY stock_id"
+        )
+        .fetch_all(&pool).await.unwrap();
+        assert_eq!(untouched, vec![(1, 2), (2, 0), (3, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_session_stops_before_next_chunk() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+        // The session was cancelled before its first chunk ran
+        sqlx::query(
+            "CREATE TRIGGER cancel_on_start AFTER INSERT ON refresh_progress
+             BEGIN UPDATE refresh_progress SET status = 'cancelled' WHERE session_id = new.session_id; END"
+        )
+        .execute(&pool).await.unwrap();
+
+        let progress = recompute_all_ratios(&pool, "recompute-2").await.unwrap();
+        assert_eq!((progress.status.as_str(), progress.processed_stocks), ("cancelled", 0));
+    }
+}
@@ -0,0 +1,296 @@
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::tools::freshness_policy::FreshnessPolicy;
+
+/// Static estimates from the refresh step definitions, used until a run has been recorded
+const STATIC_MARKET_MINUTES: f64 = 15.0;
+const STATIC_FINANCIALS_MINUTES: f64 = 90.0;
+const STATIC_RATIOS_MINUTES: f64 = 10.0;
+
+/// refresh_tracking data_source of ratio recomputes
+pub const RATIOS_DATA_SOURCE: &str = "daily_valuation_ratios";
+
+/// Number of most recent runs the median is taken over
+const TRAILING_RUNS: i64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DurationRange {
+    pub min_minutes: f64,
+    pub expected_minutes: f64,
+    pub max_minutes: f64,
+    /// Stocks the next refresh of this source has to update
+    pub stale_stocks: i64,
+    pub total_stocks: i64,
+    /// 0 means the static estimate was used
+    pub based_on_runs: i32,
+    /// Where the estimate comes from, e.g. "based on 5 previous runs, 42 stale stocks"
+    pub basis: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RefreshDurationEstimates {
+    pub market: DurationRange,
+    pub financials: DurationRange,
+    pub ratios: DurationRange,
+}
+
+/// Store one completed step's throughput
+pub async fn record_refresh_throughput(
+    pool: &SqlitePool,
+    data_source: &str,
+    units_processed: i64,
+    records_processed: i64,
+    stocks_processed: i64,
+    duration_seconds: f64,
+) -> Result<()> {
+    let units_per_second = if duration_seconds > 0.0 { Some(units_processed as f64 / duration_seconds) } else { None };
+
+    sqlx::query(
+        "INSERT INTO refresh_tracking (data_source, duration_seconds, units_processed, records_processed, units_per_second, stocks_processed)
+         VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(data_source)
+    .bind(duration_seconds)
+    .bind(units_processed)
+    .bind(records_processed)
+    .bind(units_per_second)
+    .bind(stocks_processed)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Median seconds per stock of recent runs, scaled to the stale stocks; min/max use the fastest and slowest run
+pub fn estimate_duration(stale_stocks: i64, total_stocks: i64, seconds_per_stock: &[f64], static_minutes: f64) -> DurationRange {
+    if seconds_per_stock.is_empty() {
+        return DurationRange {
+            min_minutes: static_minutes,
+            expected_minutes: static_minutes,
+            max_minutes: static_minutes,
+            stale_stocks,
+            total_stocks,
+            based_on_runs: 0,
+            basis: format!("default estimate (no previous runs), {} stale stocks", stale_stocks),
+        };
+    }
+
+    let minutes_at = |seconds: f64| stale_stocks as f64 * seconds / 60.0;
+    let fastest = seconds_per_stock.iter().copied().fold(f64::INFINITY, f64::min);
+    let slowest = seconds_per_stock.iter().copied().fold(0.0, f64::max);
+    let runs = seconds_per_stock.len();
+
+    DurationRange {
+        min_minutes: minutes_at(fastest),
+        expected_minutes: minutes_at(median(seconds_per_stock)),
+        max_minutes: minutes_at(slowest),
+        stale_stocks,
+        total_stocks,
+        based_on_runs: runs as i32,
+        basis: format!(
+            "based on {} previous run{}, {} stale stocks",
+            runs,
+            if runs == 1 { "" } else { "s" },
+            stale_stocks
+        ),
+    }
+}
+
+/// Seconds per stock of the most recent runs that recorded a stock count
+async fn trailing_seconds_per_stock(pool: &SqlitePool, data_source: &str) -> Result<Vec<f64>> {
+    let rows = sqlx::query(
+        "SELECT duration_seconds / stocks_processed AS seconds_per_stock FROM refresh_tracking
+         WHERE data_source = ? AND stocks_processed > 0 AND duration_seconds > 0
+         ORDER BY completed_at DESC, id DESC
+         LIMIT ?"
+    )
+    .bind(data_source)
+    .bind(TRAILING_RUNS)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|row| row.get::<f64, _>("seconds_per_stock")).collect())
+}
+
+/// Most recent weekday before `today`; its bar is the newest one a refresh can download
+fn last_complete_trading_day(today: NaiveDate) -> NaiveDate {
+    let mut day = today - Duration::days(1);
+    while matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+        day -= Duration::days(1);
+    }
+    day
+}
+
+/// (stale, total) S&P 500 stocks missing the last complete trading day's price
+async fn stale_market_stocks(pool: &SqlitePool, today: NaiveDate) -> Result<(i64, i64)> {
+    let row = sqlx::query(
+        "SELECT
+            COUNT(*) AS total,
+            COALESCE(SUM(NOT EXISTS (
+                SELECT 1 FROM daily_prices dp WHERE dp.stock_id = s.id AND dp.date >= ?
+            )), 0) AS stale
+         FROM stocks s
+         INNER JOIN sp500_symbols sp ON s.symbol = sp.symbol"
+    )
+    .bind(last_complete_trading_day(today).format("%Y-%m-%d").to_string())
+    .fetch_one(pool)
+    .await?;
+
+    Ok((row.get("stale"), row.get("total")))
+}
+
+/// (stale, total) S&P 500 stocks with a CIK whose latest filing is older than the policy's current window
+async fn stale_financial_stocks(pool: &SqlitePool, policy: &FreshnessPolicy, today: NaiveDate) -> Result<(i64, i64)> {
+    let cutoff = today - Duration::days(policy.financial_data.current_days);
+
+    let row = sqlx::query(
+        "SELECT
+            COUNT(*) AS total,
+            COALESCE(SUM(NOT EXISTS (
+                SELECT 1 FROM sec_filings f WHERE f.stock_id = s.id AND f.filed_date >= ?
+            )), 0) AS stale
+         FROM stocks s
+         WHERE s.is_sp500 = 1 AND s.cik IS NOT NULL AND s.cik != '' AND s.cik != 'Unknown'"
+    )
+    .bind(cutoff.format("%Y-%m-%d").to_string())
+    .fetch_one(pool)
+    .await?;
+
+    Ok((row.get("stale"), row.get("total")))
+}
+
+pub async fn estimate_refresh_durations(pool: &SqlitePool, policy: &FreshnessPolicy, today: NaiveDate) -> Result<RefreshDurationEstimates> {
+    let (stale, total) = stale_market_stocks(pool, today).await?;
+    let market = estimate_duration(
+        stale,
+        total,
+        &trailing_seconds_per_stock(pool, "daily_prices").await?,
+        STATIC_MARKET_MINUTES,
+    );
+
+    let (stale, total) = stale_financial_stocks(pool, policy, today).await?;
+    let financials = estimate_duration(
+        stale,
+        total,
+        &trailing_seconds_per_stock(pool, "financial_statements").await?,
+        STATIC_FINANCIALS_MINUTES,
+    );
+
+    // A recompute rebuilds every S&P 500 stock, so all of them count as stale
+    let (_, total) = stale_market_stocks(pool, today).await?;
+    let ratios = estimate_duration(
+        total,
+        total,
+        &trailing_seconds_per_stock(pool, RATIOS_DATA_SOURCE).await?,
+        STATIC_RATIOS_MINUTES,
+    );
+
+    Ok(RefreshDurationEstimates { market, financials, ratios })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[test]
+    fn test_estimate_scales_median_run_to_stale_stocks() {
+        // Five runs at 2, 3, 4, 10 and 3 seconds per stock: median 3s
+        let runs = [2.0, 3.0, 4.0, 10.0, 3.0];
+        let estimate = estimate_duration(40, 503, &runs, STATIC_FINANCIALS_MINUTES);
+
+        assert!((estimate.expected_minutes - 40.0 * 3.0 / 60.0).abs() < 1e-9);
+        assert!((estimate.min_minutes - 40.0 * 2.0 / 60.0).abs() < 1e-9);
+        assert!((estimate.max_minutes - 40.0 * 10.0 / 60.0).abs() < 1e-9);
+        assert_eq!(estimate.based_on_runs, 5);
+        assert_eq!(estimate.basis, "based on 5 previous runs, 40 stale stocks");
+
+        // Even count: mean of the middle two
+        let estimate = estimate_duration(60, 503, &[1.0, 4.0, 2.0, 3.0], STATIC_FINANCIALS_MINUTES);
+        assert!((estimate.expected_minutes - 60.0 * 2.5 / 60.0).abs() < 1e-9);
+
+        // Nothing stale: nothing to wait for
+        assert_eq!(estimate_duration(0, 503, &runs, STATIC_FINANCIALS_MINUTES).expected_minutes, 0.0);
+
+        let fallback = estimate_duration(40, 503, &[], STATIC_FINANCIALS_MINUTES);
+        assert_eq!(fallback.expected_minutes, STATIC_FINANCIALS_MINUTES);
+        assert_eq!(fallback.based_on_runs, 0);
+        assert!(fallback.basis.starts_with("default estimate"));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_counts_stale_stocks_and_ignores_runs_without_stock_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, cik, is_sp500) VALUES
+                (1, 'AAPL', 'Apple Inc.', '0000320193', 1),
+                (2, 'MSFT', 'Microsoft Corp', '0000789019', 1),
+                (3, 'NVDA', 'NVIDIA Corp', '0001045810', 1),
+                (4, 'OLD', 'Delisted Co', '0000000001', 0)"
+        )
+        .execute(&pool).await.unwrap();
+        // AAPL filed recently; MSFT's latest filing is outside the 400-day current window
+        sqlx::query(
+            "INSERT INTO sec_filings (stock_id, accession_number, form_type, filed_date, fiscal_year, report_date) VALUES
+                (1, '0000320193-24-000123', '10-K', '2024-11-01', 2024, '2024-09-28'),
+                (2, '0000789019-23-000014', '10-K', '2023-07-27', 2023, '2023-06-30')"
+        )
+        .execute(&pool).await.unwrap();
+
+        // 500 stocks in 1000s and 1500s; the older row predates stock counts and is skipped
+        sqlx::query(
+            "INSERT INTO refresh_tracking (data_source, duration_seconds, units_processed, records_processed, units_per_second)
+             VALUES ('financial_statements', 50.0, 500, 900, 10.0)"
+        )
+        .execute(&pool).await.unwrap();
+        record_refresh_throughput(&pool, "financial_statements", 500, 1200, 500, 1000.0).await.unwrap();
+        record_refresh_throughput(&pool, "financial_statements", 500, 900, 500, 1500.0).await.unwrap();
+
+        let today = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let estimates = estimate_refresh_durations(&pool, &FreshnessPolicy::default(), today).await.unwrap();
+
+        let financials = &estimates.financials;
+        assert_eq!(financials.based_on_runs, 2);
+        assert_eq!((financials.stale_stocks, financials.total_stocks), (2, 3));
+        assert!((financials.expected_minutes - 2.0 * 2.5 / 60.0).abs() < 1e-9);
+        assert_eq!(financials.basis, "based on 2 previous runs, 2 stale stocks");
+
+        // No market history yet: static estimate
+        assert_eq!(estimates.market.based_on_runs, 0);
+        assert_eq!(estimates.market.expected_minutes, STATIC_MARKET_MINUTES);
+
+        // Ratios: a recompute covers all three S&P 500 stocks, at 0.6s each
+        assert_eq!(estimates.ratios.expected_minutes, STATIC_RATIOS_MINUTES);
+        record_refresh_throughput(&pool, RATIOS_DATA_SOURCE, 500, 25_000, 500, 300.0).await.unwrap();
+        let ratios = estimate_refresh_durations(&pool, &FreshnessPolicy::default(), today).await.unwrap().ratios;
+        assert_eq!((ratios.stale_stocks, ratios.total_stocks, ratios.based_on_runs), (3, 3, 1));
+        assert!((ratios.expected_minutes - 3.0 * 0.6 / 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_last_complete_trading_day_skips_weekends() {
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let tuesday = NaiveDate::from_ymd_opt(2025, 3, 4).unwrap();
+        assert_eq!(last_complete_trading_day(monday), NaiveDate::from_ymd_opt(2025, 2, 28).unwrap());
+        assert_eq!(last_complete_trading_day(tuesday), monday);
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+use crate::error::CommandError;
+
+/// One stock that failed during a refresh run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RefreshRunError {
+    pub data_source: String,
+    pub symbol: Option<String>,
+    pub cik: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RefreshRunSummary {
+    pub run_id: String,
+    pub mode: String,
+    pub requested_sources: Vec<String>,
+    pub initiated_by: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    /// None while the run is still in progress
+    pub duration_seconds: Option<i64>,
+    /// running, completed or failed
+    pub status: String,
+    pub stocks_processed: i64,
+    pub filings_stored: i64,
+    pub prices_inserted: i64,
+    pub errors_count: i64,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RefreshRunDetails {
+    pub run: RefreshRunSummary,
+    pub errors: Vec<RefreshRunError>,
+}
+
+/// Totals accumulated by the orchestrator while a run executes
+#[derive(Debug, Clone, Default)]
+pub struct RefreshRunStats {
+    pub stocks_processed: i64,
+    pub filings_stored: i64,
+    pub prices_inserted: i64,
+    pub errors: Vec<RefreshRunError>,
+}
+
+/// Insert the row for a run that is starting
+pub async fn start_refresh_run(
+    pool: &SqlitePool,
+    run_id: &str,
+    mode: &str,
+    requested_sources: &[String],
+    initiated_by: &str,
+    started_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO refresh_runs (run_id, mode, requested_sources, initiated_by, started_at, status)
+         VALUES (?, ?, ?, ?, ?, 'running')"
+    )
+    .bind(run_id)
+    .bind(mode)
+    .bind(requested_sources.join(","))
+    .bind(initiated_by)
+    .bind(started_at.to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record a run's outcome, totals and per-stock errors
+pub async fn finish_refresh_run(
+    pool: &SqlitePool,
+    run_id: &str,
+    ended_at: DateTime<Utc>,
+    stats: &RefreshRunStats,
+    error_message: Option<&str>,
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE refresh_runs
+         SET ended_at = ?, status = ?, stocks_processed = ?, filings_stored = ?,
+             prices_inserted = ?, errors_count = ?, error_message = ?
+         WHERE run_id = ?"
+    )
+    .bind(ended_at.to_rfc3339())
+    .bind(if error_message.is_some() { "failed" } else { "completed" })
+    .bind(stats.stocks_processed)
+    .bind(stats.filings_stored)
+    .bind(stats.prices_inserted)
+    .bind(stats.errors.len() as i64)
+    .bind(error_message)
+    .bind(run_id)
+    .execute(&mut *tx)
+    .await?;
+
+    for error in &stats.errors {
+        sqlx::query(
+            "INSERT INTO refresh_run_errors (run_id, data_source, symbol, cik, message) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(run_id)
+        .bind(&error.data_source)
+        .bind(&error.symbol)
+        .bind(&error.cik)
+        .bind(&error.message)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+fn summary_from_row(row: &sqlx::sqlite::SqliteRow) -> RefreshRunSummary {
+    let started_at: String = row.get("started_at");
+    let ended_at: Option<String> = row.get("ended_at");
+
+    let duration_seconds = ended_at.as_deref().and_then(|end| {
+        let start = DateTime::parse_from_rfc3339(&started_at).ok()?;
+        let end = DateTime::parse_from_rfc3339(end).ok()?;
+        Some(end.signed_duration_since(start).num_seconds())
+    });
+    let requested_sources: String = row.get("requested_sources");
+
+    RefreshRunSummary {
+        run_id: row.get("run_id"),
+        mode: row.get("mode"),
+        requested_sources: requested_sources
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect(),
+        initiated_by: row.get("initiated_by"),
+        started_at,
+        ended_at,
+        duration_seconds,
+        status: row.get("status"),
+        stocks_processed: row.get("stocks_processed"),
+        filings_stored: row.get("filings_stored"),
+        prices_inserted: row.get("prices_inserted"),
+        errors_count: row.get("errors_count"),
+        error_message: row.get("error_message"),
+    }
+}
+
+/// Most recent runs first
+pub async fn load_refresh_history(pool: &SqlitePool, limit: i64) -> Result<Vec<RefreshRunSummary>> {
+    let rows = sqlx::query("SELECT * FROM refresh_runs ORDER BY started_at DESC LIMIT ?")
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(summary_from_row).collect())
+}
+
+pub async fn load_refresh_run_details(pool: &SqlitePool, run_id: &str) -> Result<RefreshRunDetails> {
+    let row = sqlx::query("SELECT * FROM refresh_runs WHERE run_id = ?")
+        .bind(run_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| CommandError::not_found("refresh_run", format!("No refresh run with id {}", run_id)))?;
+
+    let errors = sqlx::query(
+        "SELECT data_source, symbol, cik, message FROM refresh_run_errors WHERE run_id = ? ORDER BY id"
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| RefreshRunError {
+        data_source: row.get("data_source"),
+        symbol: row.get("symbol"),
+        cik: row.get("cik"),
+        message: row.get("message"),
+    })
+    .collect();
+
+    Ok(RefreshRunDetails { run: summary_from_row(&row), errors })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_run_history_records_totals_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        let sources = vec!["daily_prices".to_string(), "financial_statements".to_string()];
+
+        let first_start = Utc.with_ymd_and_hms(2025, 3, 1, 6, 0, 0).unwrap();
+        start_refresh_run(&pool, "run-1", "All", &sources, "cli", first_start).await.unwrap();
+        let stats = RefreshRunStats {
+            stocks_processed: 503,
+            filings_stored: 1200,
+            prices_inserted: 2515,
+            errors: vec![RefreshRunError {
+                data_source: "financial_statements".to_string(),
+                symbol: Some("BRK.B".to_string()),
+                cik: Some("0001067983".to_string()),
+                message: "Company Facts API returned 404".to_string(),
+            }],
+        };
+        finish_refresh_run(&pool, "run-1", first_start + chrono::Duration::minutes(95), &stats, None).await.unwrap();
+
+        let second_start = Utc.with_ymd_and_hms(2025, 3, 2, 6, 0, 0).unwrap();
+        start_refresh_run(&pool, "run-2", "Market", &sources[..1], "ui", second_start).await.unwrap();
+
+        let history = load_refresh_history(&pool, 10).await.unwrap();
+        assert_eq!(history.iter().map(|r| r.run_id.as_str()).collect::<Vec<_>>(), vec!["run-2", "run-1"]);
+        assert_eq!(history[0].status, "running");
+        assert_eq!(history[0].duration_seconds, None);
+        assert_eq!(history[1].status, "completed");
+        assert_eq!(history[1].duration_seconds, Some(95 * 60));
+        assert_eq!(history[1].requested_sources, sources);
+        assert_eq!(history[1].errors_count, 1);
+
+        let details = load_refresh_run_details(&pool, "run-1").await.unwrap();
+        assert_eq!(details.run.prices_inserted, 2515);
+        assert_eq!(details.errors, stats.errors);
+
+        let missing = load_refresh_run_details(&pool, "nope").await;
+        assert!(matches!(
+            missing.unwrap_err().downcast_ref::<CommandError>(),
+            Some(CommandError::NotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use std::time::Duration as StdDuration;
+use tracing::{error, info, warn};
+use ts_rs::TS;
+
+use crate::database::helpers::{get_database_connection, read_only_requested};
+use crate::tools::data_refresh_orchestrator::{is_refresh_running, DataRefreshManager, RefreshMode, RefreshRequest};
+
+/// metadata table key holding the JSON-encoded schedule
+const REFRESH_SCHEDULE_KEY: &str = "refresh_schedule";
+
+/// initiated_by recorded in the run history for runs the scheduler starts
+pub const SCHEDULED_TRIGGER: &str = "scheduled";
+
+/// How often the scheduler wakes to check whether a run is due
+const SCHEDULER_TICK: StdDuration = StdDuration::from_secs(60);
+
+/// When the desktop app refreshes data on its own. Times are local.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RefreshSchedule {
+    /// No automatic runs while set
+    pub paused: bool,
+    /// Weekday evenings at or after this time ("HH:MM"), the incremental market data update runs
+    pub market_refresh_time: String,
+    /// Day of the week ("Sat", "Sunday", ...) the financial statements refresh also runs, at
+    /// the same time
+    pub financials_refresh_day: String,
+}
+
+impl Default for RefreshSchedule {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            // After the close, once Schwab has published the day's candles
+            market_refresh_time: "18:30".to_string(),
+            financials_refresh_day: "Sat".to_string(),
+        }
+    }
+}
+
+impl RefreshSchedule {
+    pub fn validate(&self) -> Result<()> {
+        self.refresh_time()?;
+        self.financials_weekday()?;
+        Ok(())
+    }
+
+    fn refresh_time(&self) -> Result<NaiveTime> {
+        NaiveTime::parse_from_str(&self.market_refresh_time, "%H:%M")
+            .map_err(|_| anyhow!("market_refresh_time must be HH:MM, got '{}'", self.market_refresh_time))
+    }
+
+    fn financials_weekday(&self) -> Result<Weekday> {
+        self.financials_refresh_day
+            .parse::<Weekday>()
+            .map_err(|_| anyhow!("financials_refresh_day must be a day of the week, got '{}'", self.financials_refresh_day))
+    }
+}
+
+/// Stored schedule, or the defaults when none has been saved (or it can't be parsed)
+pub async fn load_refresh_schedule(pool: &SqlitePool) -> Result<RefreshSchedule> {
+    let row = sqlx::query("SELECT value FROM metadata WHERE key = ?")
+        .bind(REFRESH_SCHEDULE_KEY)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row
+        .and_then(|row| serde_json::from_str(&row.get::<String, _>("value")).ok())
+        .unwrap_or_default())
+}
+
+pub async fn save_refresh_schedule(pool: &SqlitePool, schedule: &RefreshSchedule) -> Result<()> {
+    schedule.validate()?;
+
+    sqlx::query(
+        "INSERT INTO metadata (key, value, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(REFRESH_SCHEDULE_KEY)
+    .bind(serde_json::to_string(schedule)?)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The refresh due at `now`, if any, given the local dates the scheduler last started each mode.
+/// Market data is due weekday evenings from the scheduled time; financials once on the chosen
+/// day. A run that was started today counts even if it failed, so a failing source is not
+/// retried every minute.
+pub fn due_refresh(
+    schedule: &RefreshSchedule,
+    now: NaiveDateTime,
+    last_market_run: Option<NaiveDate>,
+    last_financials_run: Option<NaiveDate>,
+) -> Result<Option<RefreshMode>> {
+    if schedule.paused || now.time() < schedule.refresh_time()? {
+        return Ok(None);
+    }
+
+    let today = now.date();
+    let is_weekday = !matches!(today.weekday(), Weekday::Sat | Weekday::Sun);
+    if is_weekday && last_market_run != Some(today) {
+        return Ok(Some(RefreshMode::Market));
+    }
+    if today.weekday() == schedule.financials_weekday()? && last_financials_run != Some(today) {
+        return Ok(Some(RefreshMode::Financials));
+    }
+    Ok(None)
+}
+
+/// Local date of the last run the scheduler started in `mode`
+async fn last_scheduled_run(pool: &SqlitePool, mode: &RefreshMode) -> Result<Option<NaiveDate>> {
+    let started_at: Option<String> = sqlx::query_scalar(
+        "SELECT MAX(started_at) FROM refresh_runs WHERE initiated_by = ? AND mode = ?"
+    )
+    .bind(SCHEDULED_TRIGGER)
+    .bind(format!("{:?}", mode))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(started_at
+        .and_then(|started_at| DateTime::parse_from_rfc3339(&started_at).ok())
+        .map(|started_at| started_at.with_timezone(&Local).date_naive()))
+}
+
+/// Start the refresh due now, if any. Skipped while another refresh is running; it is picked up
+/// on a later tick. Returns the mode that ran.
+pub async fn run_due_refresh(pool: &SqlitePool, now: NaiveDateTime) -> Result<Option<RefreshMode>> {
+    let schedule = load_refresh_schedule(pool).await?;
+    let last_market_run = last_scheduled_run(pool, &RefreshMode::Market).await?;
+    let last_financials_run = last_scheduled_run(pool, &RefreshMode::Financials).await?;
+
+    let Some(mode) = due_refresh(&schedule, now, last_market_run, last_financials_run)? else {
+        return Ok(None);
+    };
+    if is_refresh_running(pool).await? {
+        info!("Scheduled {:?} refresh is due but another refresh is running; will retry", mode);
+        return Ok(None);
+    }
+
+    info!("Starting scheduled {:?} refresh", mode);
+    let manager = DataRefreshManager::new(pool.clone()).await?;
+    let request = RefreshRequest {
+        mode: mode.clone(),
+        force_sources: Vec::new(),
+        initiated_by: SCHEDULED_TRIGGER.to_string(),
+        session_id: None,
+        only_cik: None,
+    };
+    // The outcome is in the run history either way
+    if let Err(e) = manager.execute_refresh(request).await {
+        error!("Scheduled {:?} refresh failed: {}", mode, e);
+    }
+    Ok(Some(mode))
+}
+
+/// Background loop started from the Tauri setup hook; checks once a minute for a due refresh
+pub async fn run_refresh_scheduler() {
+    if read_only_requested() {
+        info!("Database is read-only; scheduled refreshes are off");
+        return;
+    }
+    let mut interval = tokio::time::interval(SCHEDULER_TICK);
+    // Opened once and reused on every tick; retried each tick until it opens
+    let pool = loop {
+        interval.tick().await;
+        match get_database_connection().await {
+            Ok(pool) => break pool,
+            Err(e) => warn!("Refresh scheduler could not open the database: {}", e),
+        }
+    };
+    loop {
+        if let Err(e) = run_due_refresh(&pool, Local::now().naive_local()).await {
+            warn!("Refresh scheduler check failed: {}", e);
+        }
+        interval.tick().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn date(date: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+    }
+
+    #[test]
+    fn test_market_runs_weekday_evenings_and_financials_weekly() {
+        let schedule = RefreshSchedule::default();
+        // 2025-03-07 is a Friday, 2025-03-08 a Saturday
+        assert_eq!(due_refresh(&schedule, at("2025-03-07", "18:29"), None, None).unwrap(), None);
+        assert_eq!(due_refresh(&schedule, at("2025-03-07", "18:30"), None, None).unwrap(), Some(RefreshMode::Market));
+        assert_eq!(due_refresh(&schedule, at("2025-03-07", "23:00"), date("2025-03-07"), None).unwrap(), None);
+
+        assert_eq!(due_refresh(&schedule, at("2025-03-08", "19:00"), date("2025-03-07"), None).unwrap(), Some(RefreshMode::Financials));
+        assert_eq!(due_refresh(&schedule, at("2025-03-08", "19:00"), date("2025-03-07"), date("2025-03-08")).unwrap(), None);
+        assert_eq!(due_refresh(&schedule, at("2025-03-09", "19:00"), None, None).unwrap(), None);
+
+        let paused = RefreshSchedule { paused: true, ..RefreshSchedule::default() };
+        assert_eq!(due_refresh(&paused, at("2025-03-07", "18:30"), None, None).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_runs_are_recorded_and_not_repeated_the_same_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        assert_eq!(load_refresh_schedule(&pool).await.unwrap(), RefreshSchedule::default());
+        let invalid = RefreshSchedule { market_refresh_time: "6pm".to_string(), ..RefreshSchedule::default() };
+        assert!(save_refresh_schedule(&pool, &invalid).await.is_err());
+        let schedule = RefreshSchedule { financials_refresh_day: "Wednesday".to_string(), ..RefreshSchedule::default() };
+        save_refresh_schedule(&pool, &schedule).await.unwrap();
+        assert_eq!(load_refresh_schedule(&pool).await.unwrap(), schedule);
+
+        // Only the scheduler's own runs count, failed or not
+        let now = Local::now().date_naive().and_hms_opt(20, 0, 0).unwrap();
+        let started_at = now.and_local_timezone(Local).unwrap().with_timezone(&chrono::Utc).to_rfc3339();
+        sqlx::query(
+            "INSERT INTO refresh_runs (run_id, mode, requested_sources, initiated_by, started_at, status)
+             VALUES ('manual', 'Market', 'daily_prices', 'ui', ?1, 'completed'),
+                    ('nightly', 'Market', 'daily_prices', 'scheduled', ?1, 'failed')"
+        )
+        .bind(&started_at)
+        .execute(&pool).await.unwrap();
+
+        assert_eq!(last_scheduled_run(&pool, &RefreshMode::Market).await.unwrap(), Some(now.date()));
+        assert_eq!(last_scheduled_run(&pool, &RefreshMode::Financials).await.unwrap(), None);
+        let last_market_run = last_scheduled_run(&pool, &RefreshMode::Market).await.unwrap();
+        assert_ne!(due_refresh(&schedule, now, last_market_run, None).unwrap(), Some(RefreshMode::Market));
+    }
+}
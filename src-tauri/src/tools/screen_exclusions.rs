@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+
+/// Why a screen left out a stock whose figures make its ratios meaningless
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    /// Latest fiscal-year net income at or below zero, so there is no meaningful P/E
+    NegativeEarnings,
+    /// Latest annual total equity at or below zero, so there is no meaningful P/B
+    NegativeEquity,
+}
+
+impl ExclusionReason {
+    const ALL: [ExclusionReason; 2] = [ExclusionReason::NegativeEarnings, ExclusionReason::NegativeEquity];
+
+    /// The reason a screen records for a stock with these latest figures; negative equity
+    /// takes precedence when both apply
+    pub fn for_figures(net_income: Option<f64>, total_equity: Option<f64>) -> Option<Self> {
+        if total_equity.is_some_and(|equity| equity <= 0.0) {
+            Some(ExclusionReason::NegativeEquity)
+        } else if net_income.is_some_and(|income| income <= 0.0) {
+            Some(ExclusionReason::NegativeEarnings)
+        } else {
+            None
+        }
+    }
+}
+
+/// Set in place of a ratio the screen reports without a value because of the sign of its denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum RatioNote {
+    /// Net income at or below zero; P/E is left empty
+    NegativeEarnings,
+    /// Total equity at or below zero; P/B is left empty rather than negative
+    NegativeBookValue,
+}
+
+impl RatioNote {
+    pub fn for_pe(net_income: Option<f64>) -> Option<Self> {
+        net_income.filter(|income| *income <= 0.0).map(|_| RatioNote::NegativeEarnings)
+    }
+
+    pub fn for_pb(total_equity: Option<f64>) -> Option<Self> {
+        total_equity.filter(|equity| *equity <= 0.0).map(|_| RatioNote::NegativeBookValue)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreenExclusion {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub reason: ExclusionReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ExclusionCount {
+    pub reason: ExclusionReason,
+    pub count: i32,
+}
+
+/// Stocks excluded per reason, omitting reasons with none
+pub fn count_exclusions(excluded: &[ScreenExclusion]) -> Vec<ExclusionCount> {
+    ExclusionReason::ALL
+        .into_iter()
+        .map(|reason| ExclusionCount {
+            reason,
+            count: excluded.iter().filter(|exclusion| exclusion.reason == reason).count() as i32,
+        })
+        .filter(|count| count.count > 0)
+        .collect()
+}
+
+/// A screen's matches, with the stocks it considered but didn't list whose latest figures
+/// make its ratios meaningless
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreenResults<T> {
+    pub results: Vec<T>,
+    pub excluded: Vec<ScreenExclusion>,
+    pub exclusions: Vec<ExclusionCount>,
+}
+
+impl<T> ScreenResults<T> {
+    /// `results` with the stocks of `candidates`, or of the S&P 500 when it is empty, that
+    /// aren't among them and whose latest fiscal year lost money or ended with equity at or
+    /// below zero
+    pub async fn with_exclusions(
+        pool: &SqlitePool,
+        results: Vec<T>,
+        stock_id: impl Fn(&T) -> i64,
+        candidates: &[String],
+    ) -> Result<Self, sqlx::Error> {
+        let listed: std::collections::HashSet<i64> = results.iter().map(stock_id).collect();
+        let excluded: Vec<ScreenExclusion> = load_latest_figures(pool, candidates)
+            .await?
+            .into_iter()
+            .filter(|(stock_id, ..)| !listed.contains(stock_id))
+            .filter_map(|(stock_id, symbol, net_income, total_equity)| {
+                ExclusionReason::for_figures(net_income, total_equity).map(|reason| ScreenExclusion { stock_id, symbol, reason })
+            })
+            .collect();
+
+        Ok(Self { exclusions: count_exclusions(&excluded), results, excluded })
+    }
+}
+
+/// Latest fiscal-year net income and latest annual total equity of `candidates`, or of the
+/// S&P 500 when it is empty
+async fn load_latest_figures(
+    pool: &SqlitePool,
+    candidates: &[String],
+) -> Result<Vec<(i64, String, Option<f64>, Option<f64>)>, sqlx::Error> {
+    let scope = if candidates.is_empty() {
+        "s.is_sp500 = 1".to_string()
+    } else {
+        format!("s.symbol IN ({})", candidates.iter().map(|_| "?").collect::<Vec<_>>().join(","))
+    };
+    let query = format!(
+        "SELECT s.id, s.symbol,
+                (SELECT i.net_income FROM income_statements i
+                 WHERE i.stock_id = s.id AND i.period_type = 'FY' AND i.net_income IS NOT NULL
+                 ORDER BY i.report_date DESC LIMIT 1) AS net_income,
+                (SELECT b.total_equity FROM balance_sheets b
+                 WHERE b.stock_id = s.id AND b.period_type = 'Annual' AND b.total_equity IS NOT NULL
+                 ORDER BY b.report_date DESC LIMIT 1) AS total_equity
+         FROM stocks s
+         WHERE {}
+         ORDER BY s.symbol",
+        scope
+    );
+
+    let mut sqlx_query = sqlx::query_as(&query);
+    for symbol in candidates {
+        sqlx_query = sqlx_query.bind(symbol);
+    }
+    sqlx_query.fetch_all(pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{AnnualFinancials, FixtureBuilder};
+
+    #[tokio::test]
+    async fn test_unlisted_candidates_are_reported_by_the_sign_of_their_figures() {
+        let mut builder = FixtureBuilder::new();
+        // (symbol, net income, total equity)
+        for (symbol, net_income, total_equity) in [("LISTED", -1.0, 10.0), ("LOSS", -1.0, 10.0), ("UNDER", 1.0, -10.0), ("FINE", 1.0, 10.0)] {
+            builder = builder
+                .with_stock(symbol)
+                .with_sp500_flag()
+                .with_annual_financials(2023..=2023, move |_| AnnualFinancials {
+                    net_income: Some(net_income),
+                    total_equity: Some(total_equity),
+                    ..Default::default()
+                });
+        }
+        let fixture = builder.build().await;
+
+        // A screen that listed LISTED and left the other three out
+        let screen = ScreenResults::with_exclusions(&fixture.pool, vec![1_i64], |stock_id| *stock_id, &[]).await.unwrap();
+        assert_eq!(screen.results, vec![1]);
+        assert_eq!(screen.excluded, vec![
+            ScreenExclusion { stock_id: 2, symbol: "LOSS".to_string(), reason: ExclusionReason::NegativeEarnings },
+            ScreenExclusion { stock_id: 3, symbol: "UNDER".to_string(), reason: ExclusionReason::NegativeEquity },
+        ]);
+        assert_eq!(screen.exclusions.len(), 2);
+
+        // Only the requested tickers are candidates
+        let tickers = vec!["UNDER".to_string(), "FINE".to_string()];
+        let screen = ScreenResults::with_exclusions(&fixture.pool, Vec::<i64>::new(), |stock_id| *stock_id, &tickers).await.unwrap();
+        assert_eq!(screen.excluded.iter().map(|e| e.symbol.as_str()).collect::<Vec<_>>(), vec!["UNDER"]);
+    }
+}
@@ -0,0 +1,300 @@
+use anyhow::Result;
+use chrono::{Months, NaiveDate};
+use sqlx::{SqlitePool, Row};
+
+use crate::error::CommandError;
+use crate::tools::freshness_policy::{load_freshness_policy, FreshnessPolicy};
+use crate::tools::freshness_types::{ScreenReadiness, ScreeningReadiness};
+
+/// Share of S&P 500 stocks that must have each kind of data before screens are trusted
+const MIN_COVERAGE: f64 = 0.8;
+
+/// A 10-K filed within this window counts as a current filing
+pub const FILING_LOOKBACK_MONTHS: u32 = 15;
+
+/// Screens that can be gated on readiness
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Screen {
+    Piotroski,
+    OShaughnessy,
+    Graham,
+    /// The smart P/S screen and the P/S screen with revenue growth
+    Ps,
+    Custom,
+}
+
+/// Per-stock data availability across the S&P 500
+#[derive(Debug, Default)]
+struct CoverageCounts {
+    total: i64,
+    recent_filing: i64,
+    recent_prices: i64,
+    computed_ratios: i64,
+    consecutive_fiscal_years: i64,
+    balance_sheet_items: i64,
+    fy_revenue: i64,
+    fy_net_income: i64,
+    current_ratio_items: i64,
+}
+
+async fn load_coverage_counts(pool: &SqlitePool, filing_cutoff: NaiveDate, price_cutoff: NaiveDate) -> Result<CoverageCounts> {
+    let row = sqlx::query(
+        "SELECT
+            COUNT(*) AS total,
+            COALESCE(SUM(EXISTS (
+                SELECT 1 FROM sec_filings f WHERE f.stock_id = s.id AND f.filed_date >= ?1
+            )), 0) AS recent_filing,
+            COALESCE(SUM(EXISTS (
+                SELECT 1 FROM daily_prices p WHERE p.stock_id = s.id AND p.date >= ?2
+            )), 0) AS recent_prices,
+            COALESCE(SUM(EXISTS (
+                SELECT 1 FROM daily_valuation_ratios r WHERE r.stock_id = s.id
+            )), 0) AS computed_ratios,
+            COALESCE(SUM(EXISTS (
+                SELECT 1 FROM income_statements cur
+                INNER JOIN income_statements prev
+                    ON prev.stock_id = cur.stock_id
+                    AND prev.period_type = 'FY'
+                    AND prev.fiscal_year = cur.fiscal_year - 1
+                WHERE cur.stock_id = s.id AND cur.period_type = 'FY'
+            )), 0) AS consecutive_fiscal_years,
+            COALESCE(SUM(EXISTS (
+                SELECT 1 FROM balance_sheets b
+                WHERE b.stock_id = s.id AND b.period_type = 'Annual'
+                    AND b.total_equity IS NOT NULL AND b.shares_outstanding > 0
+            )), 0) AS balance_sheet_items,
+            COALESCE(SUM(EXISTS (
+                SELECT 1 FROM income_statements i
+                WHERE i.stock_id = s.id AND i.period_type = 'FY' AND i.revenue IS NOT NULL
+            )), 0) AS fy_revenue,
+            COALESCE(SUM(EXISTS (
+                SELECT 1 FROM income_statements i
+                WHERE i.stock_id = s.id AND i.period_type = 'FY' AND i.net_income IS NOT NULL
+            )), 0) AS fy_net_income,
+            COALESCE(SUM(EXISTS (
+                SELECT 1 FROM balance_sheets b
+                WHERE b.stock_id = s.id AND b.period_type = 'Annual'
+                    AND b.current_assets IS NOT NULL AND b.current_liabilities > 0
+            )), 0) AS current_ratio_items
+         FROM stocks s
+         WHERE s.is_sp500 = 1"
+    )
+    .bind(filing_cutoff.format("%Y-%m-%d").to_string())
+    .bind(price_cutoff.format("%Y-%m-%d").to_string())
+    .fetch_one(pool)
+    .await?;
+
+    Ok(CoverageCounts {
+        total: row.get("total"),
+        recent_filing: row.get("recent_filing"),
+        recent_prices: row.get("recent_prices"),
+        computed_ratios: row.get("computed_ratios"),
+        consecutive_fiscal_years: row.get("consecutive_fiscal_years"),
+        balance_sheet_items: row.get("balance_sheet_items"),
+        fy_revenue: row.get("fy_revenue"),
+        fy_net_income: row.get("fy_net_income"),
+        current_ratio_items: row.get("current_ratio_items"),
+    })
+}
+
+/// The blocking issue when fewer than MIN_COVERAGE of the stocks have the data
+fn coverage_issue(prefix: &str, description: &str, have: i64, total: i64) -> Option<String> {
+    if have as f64 >= total as f64 * MIN_COVERAGE {
+        return None;
+    }
+    Some(format!(
+        "{}only {} of {} S&P 500 stocks have {} (need {:.0}%)",
+        prefix, have, total, description, MIN_COVERAGE * 100.0
+    ))
+}
+
+/// Ready when none of the screen's inputs has an issue
+fn screen_readiness(inputs: &[&Option<String>]) -> ScreenReadiness {
+    let blocking_issues: Vec<String> = inputs.iter().filter_map(|issue| (*issue).clone()).collect();
+    ScreenReadiness { ready: blocking_issues.is_empty(), blocking_issues }
+}
+
+/// Check whether the database holds enough data for valuation analysis and each screen
+pub async fn evaluate_screening_readiness(pool: &SqlitePool, policy: &FreshnessPolicy, today: NaiveDate) -> Result<ScreeningReadiness> {
+    let filing_cutoff = today.checked_sub_months(Months::new(FILING_LOOKBACK_MONTHS)).unwrap_or(today);
+    let price_window_days = policy.market_data.stale_days;
+    let price_cutoff = today - chrono::Duration::days(price_window_days);
+
+    let counts = load_coverage_counts(pool, filing_cutoff, price_cutoff).await?;
+
+    if counts.total == 0 {
+        let no_stocks = Some("No S&P 500 stocks loaded; initialize the stock list first".to_string());
+        let not_ready = screen_readiness(&[&no_stocks]);
+        return Ok(ScreeningReadiness {
+            valuation_analysis: not_ready.clone(),
+            piotroski_screening: not_ready.clone(),
+            oshaughnessy_screening: not_ready.clone(),
+            graham_screening: not_ready.clone(),
+            ps_screening: not_ready,
+            blocking_issues: no_stocks.into_iter().collect(),
+        });
+    }
+
+    let total = counts.total;
+    let filings = coverage_issue("", &format!("a 10-K filed in the last {} months", FILING_LOOKBACK_MONTHS), counts.recent_filing, total);
+    let prices = coverage_issue("", &format!("prices in the last {} days", price_window_days), counts.recent_prices, total);
+    let ratios = (counts.computed_ratios == 0).then(|| "No computed valuation ratios; run the daily ratio backfill".to_string());
+
+    let consecutive_years = coverage_issue(
+        "Piotroski: ", "two consecutive fiscal years of income statements", counts.consecutive_fiscal_years, total,
+    );
+    // The O'Shaughnessy ranking prices stocks itself from the latest close, the annual balance
+    // sheet's equity and share count, and fiscal-year revenue; it doesn't read computed ratios.
+    // Graham prices stocks the same way and adds earnings and the current ratio.
+    let balance_sheet = coverage_issue(
+        "O'Shaughnessy and Graham: ", "annual balance sheets with equity and shares outstanding", counts.balance_sheet_items, total,
+    );
+    let fy_revenue = coverage_issue(
+        "O'Shaughnessy: ", "fiscal-year income statements with revenue", counts.fy_revenue, total,
+    );
+    let fy_net_income = coverage_issue(
+        "Graham: ", "fiscal-year income statements with net income", counts.fy_net_income, total,
+    );
+    let current_ratio = coverage_issue(
+        "Graham: ", "annual balance sheets with current assets and liabilities", counts.current_ratio_items, total,
+    );
+
+    let all_issues = [&filings, &prices, &ratios, &consecutive_years, &balance_sheet, &fy_revenue, &fy_net_income, &current_ratio];
+    Ok(ScreeningReadiness {
+        valuation_analysis: screen_readiness(&[&filings, &prices, &ratios]),
+        piotroski_screening: screen_readiness(&[&filings, &consecutive_years]),
+        oshaughnessy_screening: screen_readiness(&[&prices, &balance_sheet, &fy_revenue]),
+        graham_screening: screen_readiness(&[&prices, &balance_sheet, &fy_net_income, &current_ratio]),
+        ps_screening: screen_readiness(&[&prices, &ratios]),
+        blocking_issues: screen_readiness(&all_issues).blocking_issues,
+    })
+}
+
+/// Refuse to run a screen on incomplete data unless the caller overrides the check
+pub async fn require_screen_ready(pool: &SqlitePool, screen: Screen, override_readiness: Option<bool>) -> Result<(), CommandError> {
+    if override_readiness.unwrap_or(false) {
+        return Ok(());
+    }
+
+    let policy = load_freshness_policy(pool).await?;
+    let readiness = evaluate_screening_readiness(pool, &policy, chrono::Local::now().date_naive()).await?;
+
+    let screen_readiness = match screen {
+        Screen::Piotroski => readiness.piotroski_screening,
+        Screen::OShaughnessy => readiness.oshaughnessy_screening,
+        Screen::Graham => readiness.graham_screening,
+        Screen::Ps => readiness.ps_screening,
+        Screen::Custom => readiness.valuation_analysis,
+    };
+    if screen_readiness.ready {
+        return Ok(());
+    }
+
+    Err(CommandError::stale_data(format!(
+        "{:?} screening is blocked: {}",
+        screen,
+        screen_readiness.blocking_issues.join("; ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_empty_database_is_not_ready() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        let today = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+
+        let readiness = evaluate_screening_readiness(&pool, &FreshnessPolicy::default(), today).await.unwrap();
+
+        assert!(!readiness.valuation_analysis.ready);
+        assert!(!readiness.piotroski_screening.ready);
+        assert!(!readiness.oshaughnessy_screening.ready);
+        assert!(!readiness.graham_screening.ready);
+        assert!(!readiness.ps_screening.ready);
+        assert_eq!(readiness.blocking_issues.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_issues_name_missing_data_and_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        let today = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, cik, is_sp500) VALUES
+                (1, 'AAPL', 'Apple Inc.', '0000320193', 1),
+                (2, 'MSFT', 'Microsoft Corp', '0000789019', 1)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO sec_filings (stock_id, accession_number, form_type, filed_date, fiscal_year, report_date) VALUES
+                (1, '0000320193-24-000123', '10-K', '2024-11-01', 2024, '2024-09-28'),
+                (2, '0000789019-24-000456', '10-K', '2024-07-30', 2024, '2024-06-30')"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, net_income) VALUES
+                (1, 'FY', '2024-09-28', 2024, 93.7e9),
+                (1, 'FY', '2023-09-30', 2023, 97.0e9),
+                (2, 'FY', '2024-06-30', 2024, 88.1e9),
+                (2, 'FY', '2023-06-30', 2023, 72.4e9)"
+        )
+        .execute(&pool).await.unwrap();
+
+        let readiness = evaluate_screening_readiness(&pool, &FreshnessPolicy::default(), today).await.unwrap();
+
+        // Filings and two fiscal years are present; prices, ratios and balance sheets are not
+        assert_eq!(readiness.piotroski_screening, ScreenReadiness { ready: true, blocking_issues: Vec::new() });
+        assert!(!readiness.valuation_analysis.ready);
+        assert!(!readiness.oshaughnessy_screening.ready);
+        assert!(!readiness.graham_screening.ready);
+        assert!(readiness.blocking_issues.iter().any(|i| i.contains("only 0 of 2 S&P 500 stocks have prices")));
+        assert!(readiness.blocking_issues.iter().any(|i| i.contains("No computed valuation ratios")));
+        assert!(readiness.blocking_issues.iter().any(|i| i.starts_with("O'Shaughnessy: only 0 of 2")));
+
+        // Each screen lists only what it lacks itself
+        let ps_issues = &readiness.ps_screening.blocking_issues;
+        assert_eq!(ps_issues.len(), 2);
+        assert!(ps_issues[0].contains("have prices") && ps_issues[1].contains("No computed valuation ratios"));
+        assert!(!readiness.oshaughnessy_screening.blocking_issues.iter().any(|i| i.contains("ratios") || i.starts_with("Graham")));
+        // Graham has net income; it lacks prices and balance sheets
+        assert!(!readiness.graham_screening.blocking_issues.iter().any(|i| i.contains("net income")));
+        assert_eq!(readiness.graham_screening.blocking_issues.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_oshaughnessy_is_ready_without_computed_ratios() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        let today = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (1, 'AAPL', 'Apple Inc.', 1)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+             VALUES (1, '2025-02-28', 240, 242, 238, 241)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue)
+             VALUES (1, 'FY', '2024-09-28', 2024, 391.0e9)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_equity, shares_outstanding)
+             VALUES (1, 'Annual', '2024-09-28', 2024, 57.0e9, 15.1e9)"
+        )
+        .execute(&pool).await.unwrap();
+
+        let readiness = evaluate_screening_readiness(&pool, &FreshnessPolicy::default(), today).await.unwrap();
+
+        // The ranking prices stocks from closes and statements, so no ratio backfill is needed
+        assert!(readiness.oshaughnessy_screening.ready);
+        assert!(!readiness.valuation_analysis.ready);
+        assert!(!readiness.ps_screening.ready);
+    }
+}
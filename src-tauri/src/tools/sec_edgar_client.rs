@@ -7,12 +7,17 @@ use tokio::time::sleep;
 use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::Client;
 use std::collections::HashMap;
+use tracing::{debug, error, info, warn};
 
 /// SEC EDGAR API client for downloading 10-K filings and extracting balance sheet data
 pub struct SecEdgarClient {
     pool: SqlitePool,
     http_client: Client,
     rate_limiter: RateLimiter,
+    base_url: String,
+    /// Fault injection: make the cash flow insert in store_filing_atomic fail
+    #[cfg(any(test, feature = "test-utils"))]
+    fail_cash_flow_insert: bool,
 }
 
 /// Rate limiter to respect SEC's 10 requests per second limit
@@ -73,8 +78,12 @@ pub struct BalanceSheetData {
     pub total_debt: Option<f64>,
     pub current_assets: Option<f64>,
     pub current_liabilities: Option<f64>,
+    /// InventoryNet; None for companies that carry no inventory or don't report it
+    pub inventory: Option<f64>,
     pub share_repurchases: Option<f64>,
     pub shares_outstanding: Option<f64>,
+    /// No filed share count: shares_outstanding is a provider market cap over the close
+    pub shares_outstanding_estimated: bool,
 }
 
 /// Income statement data extracted from SEC filing
@@ -92,6 +101,8 @@ pub struct IncomeStatementData {
     pub cost_of_revenue: Option<f64>,
     pub interest_expense: Option<f64>,
     pub tax_expense: Option<f64>,
+    /// Income from continuing operations before income taxes
+    pub pretax_income: Option<f64>,
     pub shares_basic: Option<f64>,
     pub shares_diluted: Option<f64>,
 }
@@ -110,6 +121,14 @@ pub struct CashFlowData {
     pub operating_cash_flow: Option<f64>,
     pub investing_cash_flow: Option<f64>,
     pub financing_cash_flow: Option<f64>,
+    pub capital_expenditures: Option<f64>,
+}
+
+impl CashFlowData {
+    /// Operating cash flow minus capital expenditures; capex is an outflow whatever its reported sign
+    pub fn free_cash_flow(&self) -> Option<f64> {
+        Some(self.operating_cash_flow? - self.capital_expenditures?.abs())
+    }
 }
 
 /// Company Facts API response structure for filing metadata
@@ -186,9 +205,25 @@ impl SecEdgarClient {
             pool,
             http_client,
             rate_limiter: RateLimiter::new(),
+            base_url: "https://data.sec.gov".to_string(),
+            #[cfg(any(test, feature = "test-utils"))]
+            fail_cash_flow_insert: false,
         }
     }
 
+    /// Point the Submissions and Company Facts APIs at a different host (used by tests with a mock server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Force the cash flow insert of store_filing_atomic to fail (used by rollback tests)
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn with_failing_cash_flow_insert(mut self) -> Self {
+        self.fail_cash_flow_insert = true;
+        self
+    }
+
     /// Check if financial data needs update based on latest SEC filings
     /// Check if stock needs update based on data coverage (not just latest filing date)
     pub async fn check_if_update_needed(&mut self, cik: &str, stock_id: i64) -> Result<bool> {
@@ -196,7 +231,7 @@ impl SecEdgarClient {
         let years_covered = self.get_years_of_data_coverage(stock_id).await?;
         
         if years_covered < 5 {
-            println!("    📊 {} has only {} years of data, needs historical download", cik, years_covered);
+            info!(%cik, "{} has only {} years of data, needs historical download", cik, years_covered);
             return Ok(true);
         }
         
@@ -207,7 +242,7 @@ impl SecEdgarClient {
         match (latest_filing, our_latest) {
             (Some(sec_date), Some(our_date)) => {
                 if sec_date > our_date {
-                    println!("    📋 {} has newer SEC filings available", cik);
+                    info!(%cik, "{} has newer SEC filings available", cik);
                     Ok(true)
                 } else {
                     Ok(false)
@@ -246,8 +281,8 @@ impl SecEdgarClient {
         self.rate_limiter.wait_if_needed().await;
 
         let url = format!(
-            "https://data.sec.gov/api/xbrl/companyfacts/CIK{:0>10}.json",
-            cik
+            "{}/api/xbrl/companyfacts/CIK{:0>10}.json",
+            self.base_url, cik
         );
 
         let response = self.http_client
@@ -372,7 +407,7 @@ impl SecEdgarClient {
     pub async fn fetch_company_submissions(&mut self, cik: &str) -> Result<SubmissionsResponse> {
         // Pad CIK to 10 digits with leading zeros
         let cik_padded = format!("{:0>10}", cik);
-        let url = format!("https://data.sec.gov/submissions/CIK{}.json", cik_padded);
+        let url = format!("{}/submissions/CIK{}.json", self.base_url, cik_padded);
 
         // Rate limiting (10 req/sec)
         self.rate_limiter.wait_if_needed().await;
@@ -394,6 +429,29 @@ impl SecEdgarClient {
         Ok(submissions)
     }
 
+    /// Fetch the Company Facts JSON for a CIK as returned by SEC, without parsing it
+    pub async fn fetch_company_facts_raw(&mut self, cik: &str) -> Result<String> {
+        let cik_padded = format!("{:0>10}", cik);
+        let url = format!("{}/api/xbrl/companyfacts/CIK{}.json", self.base_url, cik_padded);
+
+        // Rate limiting (10 req/sec)
+        self.rate_limiter.wait_if_needed().await;
+
+        let response = self.http_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to fetch company facts for CIK {}: {}", cik, e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("SEC Company Facts API returned status {}: {}",
+                response.status(), url));
+        }
+
+        response.text().await
+            .map_err(|e| anyhow!("Failed to read company facts for CIK {}: {}", cik, e))
+    }
+
     /// Extract 10-K filing metadata from Submissions API response
     /// Only returns annual 10-K filings, not 10-Q or 8-K
     pub fn extract_10k_metadata(&self, submissions: &SubmissionsResponse) -> Vec<FilingMetadata> {
@@ -441,7 +499,7 @@ impl SecEdgarClient {
             mappings.push(mapping);
         }
 
-        println!("📊 Found {} S&P 500 CIK mappings", mappings.len());
+        info!("Found {} S&P 500 CIK mappings", mappings.len());
         Ok(mappings)
     }
 
@@ -454,8 +512,8 @@ impl SecEdgarClient {
         
         // SEC EDGAR Submissions API endpoint for company filings
         let url = format!(
-            "https://data.sec.gov/submissions/CIK{:0>10}.json",
-            cik
+            "{}/submissions/CIK{:0>10}.json",
+            self.base_url, cik
         );
 
         let response = self.http_client
@@ -523,7 +581,7 @@ impl SecEdgarClient {
         // Sort by filing date (most recent first)
         filings.sort_by(|a, b| b.filing_date.cmp(&a.filing_date));
         
-        println!("  📋 Found {} 10-K filings for {} (last 5 years)", filings.len(), symbol);
+        info!(%symbol, "Found {} 10-K filings for {} (last 5 years)", filings.len(), symbol);
         Ok(filings)
     }
 
@@ -531,12 +589,12 @@ impl SecEdgarClient {
     pub async fn extract_balance_sheet_data(&mut self, cik: &str, stock_id: i64, symbol: &str) -> Result<Option<BalanceSheetData>> {
         self.rate_limiter.wait_if_needed().await;
 
-        println!("  📊 Extracting historical balance sheet data for {} using Company Facts API", symbol);
+        info!(%symbol, "Extracting historical balance sheet data for {} using Company Facts API", symbol);
         
         // Use SEC EDGAR Company Facts API
         let url = format!(
-            "https://data.sec.gov/api/xbrl/companyfacts/CIK{:0>10}.json",
-            cik
+            "{}/api/xbrl/companyfacts/CIK{:0>10}.json",
+            self.base_url, cik
         );
 
         let response = self.http_client
@@ -546,7 +604,7 @@ impl SecEdgarClient {
             .await?;
 
         if !response.status().is_success() {
-            println!("    ⚠️ Company Facts API failed for {}: {}", symbol, response.status());
+            warn!(%symbol, "Company Facts API failed for {}: {}", symbol, response.status());
             return Ok(None);
         }
 
@@ -559,7 +617,7 @@ impl SecEdgarClient {
         let historical_cash_flow_data = self.parse_cash_flow_json(&json, symbol)?;
         
         if historical_balance_data.is_empty() && historical_cash_flow_data.is_empty() {
-            println!("    ⚠️ No historical data found for {}", symbol);
+            warn!(%symbol, "No historical data found for {}", symbol);
             return Ok(None);
         }
 
@@ -629,8 +687,10 @@ impl SecEdgarClient {
                     total_debt,
                     current_assets: balance_data.get("AssetsCurrent").copied(),
                     current_liabilities: balance_data.get("LiabilitiesCurrent").copied(),
+                    inventory: balance_data.get("InventoryNet").copied(),
                     share_repurchases: balance_data.get("ShareRepurchases").copied(),
                     shares_outstanding: balance_data.get("SharesOutstanding").copied(),
+                    shares_outstanding_estimated: false,
                 }, matching_metadata).await;
 
                 // Store cash flow data for the same report date
@@ -647,22 +707,23 @@ impl SecEdgarClient {
                         operating_cash_flow: cash_flow_data.get("operating_cash_flow").copied(),
                         investing_cash_flow: cash_flow_data.get("investing_cash_flow").copied(),
                         financing_cash_flow: cash_flow_data.get("financing_cash_flow").copied(),
+                        capital_expenditures: cash_flow_data.get("capital_expenditures").copied(),
                     }, matching_metadata).await;
 
                     if cash_flow_result.is_err() {
-                        println!("    ⚠️ Failed to store cash flow data for {} on {}", symbol, report_date_str);
+                        warn!(%symbol, "Failed to store cash flow data for {} on {}", symbol, report_date_str);
                     }
                 }
 
                 if balance_sheet_result.is_ok() {
                     stored_records += 1;
                 } else {
-                    println!("    ⚠️ Failed to store balance sheet data for {} on {}", symbol, report_date_str);
+                    warn!(%symbol, "Failed to store balance sheet data for {} on {}", symbol, report_date_str);
                 }
             }
         }
 
-        println!("    ✅ Successfully stored {} historical balance sheet records for {}", stored_records, symbol);
+        info!(stored_records, %symbol, "Successfully stored {} historical balance sheet records for {}", stored_records, symbol);
         
         // Return the most recent record for compatibility
         if stored_records > 0 {
@@ -680,8 +741,10 @@ impl SecEdgarClient {
                 total_debt: None,
                 current_assets: None,
                 current_liabilities: None,
+                inventory: None,
                 share_repurchases: None,
                 shares_outstanding: None,
+                shares_outstanding_estimated: false,
             }))
         } else {
             Ok(None)
@@ -698,6 +761,7 @@ impl SecEdgarClient {
             ("AssetsCurrent", "AssetsCurrent"),
             ("Liabilities", "Liabilities"),
             ("LiabilitiesCurrent", "LiabilitiesCurrent"),
+            ("InventoryNet", "InventoryNet"),
             ("StockholdersEquity", "StockholdersEquity"),
             ("CashAndCashEquivalentsAtCarryingValue", "CashAndCashEquivalentsAtCarryingValue"),
             // Debt fields - try multiple XBRL concepts
@@ -800,7 +864,7 @@ impl SecEdgarClient {
             }
         });
 
-        println!("    📊 Extracted {} historical balance sheet data points since 2016 for {}", historical_data.len(), symbol);
+        info!(%symbol, "Extracted {} historical balance sheet data points since 2016 for {}", historical_data.len(), symbol);
         Ok(historical_data)
     }
 
@@ -822,6 +886,10 @@ impl SecEdgarClient {
             ("NetCashProvidedByUsedInOperatingActivities", "operating_cash_flow"),
             ("NetCashProvidedByUsedInInvestingActivities", "investing_cash_flow"),
             ("NetCashProvidedByUsedInFinancingActivities", "financing_cash_flow"),
+            // Later concepts win when several report the same date, so the usual capex concept comes last
+            ("PaymentsForCapitalImprovements", "capital_expenditures"),
+            ("PaymentsToAcquireProductiveAssets", "capital_expenditures"),
+            ("PaymentsToAcquirePropertyPlantAndEquipment", "capital_expenditures"),
         ];
 
         // Navigate to the facts section
@@ -872,7 +940,7 @@ impl SecEdgarClient {
             }
         });
 
-        println!("    💰 Extracted {} historical cash flow data points since 2016 for {}", historical_data.len(), symbol);
+        info!(%symbol, "Extracted {} historical cash flow data points since 2016 for {}", historical_data.len(), symbol);
         Ok(historical_data)
     }
 
@@ -893,6 +961,8 @@ impl SecEdgarClient {
             ("CostOfGoodsAndServicesSold", "cost_of_revenue"),
             ("InterestExpense", "interest_expense"),
             ("IncomeTaxExpenseBenefit", "tax_expense"),
+            ("IncomeLossFromContinuingOperationsBeforeIncomeTaxesMinorityInterestAndIncomeLossFromEquityMethodInvestments", "pretax_income"),
+            ("IncomeLossFromContinuingOperationsBeforeIncomeTaxesExtraordinaryItemsNoncontrollingInterest", "pretax_income"),
         ];
 
         // Shares outstanding field mappings
@@ -989,7 +1059,7 @@ impl SecEdgarClient {
             }
         });
 
-        println!("    📈 Extracted {} historical income statement data points since 2016 for {}", historical_data.len(), symbol);
+        info!(%symbol, "Extracted {} historical income statement data points since 2016 for {}", historical_data.len(), symbol);
         Ok(historical_data)
     }
 
@@ -997,12 +1067,12 @@ impl SecEdgarClient {
     pub async fn extract_income_statement_data(&mut self, cik: &str, stock_id: i64, symbol: &str) -> Result<Option<IncomeStatementData>> {
         self.rate_limiter.wait_if_needed().await;
 
-        println!("  📈 Extracting income statement data for {} using Company Facts API", symbol);
+        info!(%symbol, "Extracting income statement data for {} using Company Facts API", symbol);
 
         // Use SEC EDGAR Company Facts API
         let url = format!(
-            "https://data.sec.gov/api/xbrl/companyfacts/CIK{:0>10}.json",
-            cik
+            "{}/api/xbrl/companyfacts/CIK{:0>10}.json",
+            self.base_url, cik
         );
 
         let response = self.http_client
@@ -1012,7 +1082,7 @@ impl SecEdgarClient {
             .await?;
 
         if !response.status().is_success() {
-            println!("    ⚠️ Company Facts API failed for {}: {}", symbol, response.status());
+            warn!(%symbol, "Company Facts API failed for {}: {}", symbol, response.status());
             return Ok(None);
         }
 
@@ -1022,7 +1092,7 @@ impl SecEdgarClient {
         let historical_income_data = self.parse_income_statement_json(&json, symbol)?;
 
         if historical_income_data.is_empty() {
-            println!("    ⚠️ No historical income statement data found for {}", symbol);
+            warn!(%symbol, "No historical income statement data found for {}", symbol);
             return Ok(None);
         }
 
@@ -1066,6 +1136,7 @@ impl SecEdgarClient {
                     cost_of_revenue: income_data.get("cost_of_revenue").copied(),
                     interest_expense: income_data.get("interest_expense").copied(),
                     tax_expense: income_data.get("tax_expense").copied(),
+                    pretax_income: income_data.get("pretax_income").copied(),
                     shares_basic: income_data.get("shares_basic").copied(),
                     shares_diluted: income_data.get("shares_diluted").copied(),
                 }, matching_metadata).await;
@@ -1073,12 +1144,12 @@ impl SecEdgarClient {
                 if income_result.is_ok() {
                     stored_records += 1;
                 } else {
-                    println!("    ⚠️ Failed to store income statement data for {} on {}", symbol, report_date_str);
+                    warn!(%symbol, "Failed to store income statement data for {} on {}", symbol, report_date_str);
                 }
             }
         }
 
-        println!("    ✅ Successfully stored {} historical income statement records for {}", stored_records, symbol);
+        info!(stored_records, %symbol, "Successfully stored {} historical income statement records for {}", stored_records, symbol);
         
         // Return the most recent record for compatibility
         if stored_records > 0 {
@@ -1095,6 +1166,7 @@ impl SecEdgarClient {
                 cost_of_revenue: None,
                 interest_expense: None,
                 tax_expense: None,
+                pretax_income: None,
                 shares_basic: None,
                 shares_diluted: None,
             }))
@@ -1136,7 +1208,7 @@ impl SecEdgarClient {
                 let old_filing_id: i64 = row.get("id");
                 let old_accession: String = row.get("accession_number");
 
-                println!("    🔄 [UPSERT] Replacing 10-K (accession: {}) with 10-K/A (accession: {})", old_accession, metadata.accession_number);
+                info!("[UPSERT] Replacing 10-K (accession: {}) with 10-K/A (accession: {})", old_accession, metadata.accession_number);
 
                 // Delete old financial data (cascading delete via foreign keys)
                 sqlx::query("DELETE FROM balance_sheets WHERE sec_filing_id = ?")
@@ -1160,7 +1232,7 @@ impl SecEdgarClient {
                     .execute(&mut *tx)
                     .await?;
 
-                println!("    ✅ [UPSERT] Deleted old 10-K filing (id={})", old_filing_id);
+                info!("[UPSERT] Deleted old 10-K filing (id={})", old_filing_id);
             }
         }
 
@@ -1182,8 +1254,9 @@ impl SecEdgarClient {
         // 5. Commit transaction (ACID guarantee: all-or-nothing)
         tx.commit().await
             .map_err(|e| anyhow!("Failed to commit transaction for {} ({}): {}", symbol, metadata.filing_date, e))?;
+        crate::tools::query_cache::global_query_cache().invalidate_all();
 
-        println!("    ✅ [ATOMIC] Stored complete filing for {} on {} (sec_filing_id={})", symbol, report_date, sec_filing_id);
+        info!(%symbol, "[ATOMIC] Stored complete filing for {} on {} (sec_filing_id={})", symbol, report_date, sec_filing_id);
         Ok(sec_filing_id)
     }
 
@@ -1203,7 +1276,7 @@ impl SecEdgarClient {
             .fetch_optional(&self.pool)
             .await?
         {
-            println!("    📋 Found existing sec_filing record ID={} for filed_date={}", existing_id, metadata.filing_date);
+            debug!("Found existing sec_filing record ID={} for filed_date={}", existing_id, metadata.filing_date);
             return Ok(existing_id);
         }
 
@@ -1213,8 +1286,7 @@ impl SecEdgarClient {
             VALUES (?, ?, ?, ?, ?, ?, ?)
         "#;
 
-        println!("    📋 Creating new sec_filing record: stock_id={}, filed_date={}, report_date={}, fiscal_year={}",
-                 stock_id, metadata.filing_date, report_date, fiscal_year);
+        debug!("Creating new sec_filing record: stock_id={}, filed_date={}, report_date={}, fiscal_year={}", stock_id, metadata.filing_date, report_date, fiscal_year);
 
         let result = sqlx::query(insert_query)
             .bind(stock_id)
@@ -1228,7 +1300,7 @@ impl SecEdgarClient {
             .await?;
 
         let new_id = result.last_insert_rowid();
-        println!("    ✅ Created sec_filing record ID={} for filed_date={}", new_id, metadata.filing_date);
+        debug!("Created sec_filing record ID={} for filed_date={}", new_id, metadata.filing_date);
         Ok(new_id)
     }
 
@@ -1248,7 +1320,7 @@ impl SecEdgarClient {
             .fetch_optional(&mut **tx)
             .await?
         {
-            println!("    📋 [TX] Found existing sec_filing record ID={} for filed_date={}", existing_id, metadata.filing_date);
+            debug!("[TX] Found existing sec_filing record ID={} for filed_date={}", existing_id, metadata.filing_date);
             return Ok(existing_id);
         }
 
@@ -1258,8 +1330,7 @@ impl SecEdgarClient {
             VALUES (?, ?, ?, ?, ?, ?, ?)
         "#;
 
-        println!("    📋 [TX] Creating new sec_filing record: stock_id={}, filed_date={}, report_date={}, fiscal_year={}",
-                 stock_id, metadata.filing_date, report_date, fiscal_year);
+        debug!("[TX] Creating new sec_filing record: stock_id={}, filed_date={}, report_date={}, fiscal_year={}", stock_id, metadata.filing_date, report_date, fiscal_year);
 
         let result = sqlx::query(insert_query)
             .bind(stock_id)
@@ -1273,7 +1344,7 @@ impl SecEdgarClient {
             .await?;
 
         let new_id = result.last_insert_rowid();
-        println!("    ✅ [TX] Created sec_filing record ID={} for filed_date={}", new_id, metadata.filing_date);
+        debug!("[TX] Created sec_filing record ID={} for filed_date={}", new_id, metadata.filing_date);
         Ok(new_id)
     }
 
@@ -1291,10 +1362,10 @@ impl SecEdgarClient {
                 stock_id, period_type, report_date, fiscal_year,
                 total_assets, total_liabilities, total_equity,
                 cash_and_equivalents, short_term_debt, long_term_debt, total_debt,
-                current_assets, current_liabilities,
+                current_assets, current_liabilities, inventory,
                 share_repurchases, sec_filing_id
             ) VALUES (
-                ?1, 'Annual', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14
+                ?1, 'Annual', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15
             )
         "#;
 
@@ -1311,6 +1382,7 @@ impl SecEdgarClient {
             .bind(data.total_debt)
             .bind(data.current_assets)
             .bind(data.current_liabilities)
+            .bind(data.inventory)
             .bind(data.share_repurchases)
             .bind(sec_filing_id)
             .execute(&self.pool)
@@ -1326,10 +1398,10 @@ impl SecEdgarClient {
                 stock_id, period_type, report_date, fiscal_year,
                 total_assets, total_liabilities, total_equity,
                 cash_and_equivalents, short_term_debt, long_term_debt, total_debt,
-                current_assets, current_liabilities,
-                share_repurchases, shares_outstanding, sec_filing_id
+                current_assets, current_liabilities, inventory,
+                share_repurchases, shares_outstanding, shares_outstanding_estimated, sec_filing_id
             ) VALUES (
-                ?1, 'Annual', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15
+                ?1, 'Annual', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17
             )
         "#;
 
@@ -1346,8 +1418,10 @@ impl SecEdgarClient {
             .bind(data.total_debt)
             .bind(data.current_assets)
             .bind(data.current_liabilities)
+            .bind(data.inventory)
             .bind(data.share_repurchases)
             .bind(data.shares_outstanding)
+            .bind(data.shares_outstanding_estimated)
             .bind(sec_filing_id)
             .execute(&mut **tx)
             .await?;
@@ -1369,9 +1443,9 @@ impl SecEdgarClient {
                 stock_id, period_type, report_date, fiscal_year,
                 depreciation_expense, amortization_expense, dividends_paid,
                 share_repurchases, operating_cash_flow, investing_cash_flow, financing_cash_flow,
-                sec_filing_id
+                capital_expenditures, free_cash_flow, sec_filing_id
             ) VALUES (
-                ?1, 'Annual', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11
+                ?1, 'Annual', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13
             )
         "#;
 
@@ -1386,6 +1460,8 @@ impl SecEdgarClient {
             .bind(data.operating_cash_flow)
             .bind(data.investing_cash_flow)
             .bind(data.financing_cash_flow)
+            .bind(data.capital_expenditures)
+            .bind(data.free_cash_flow())
             .bind(sec_filing_id)
             .execute(&self.pool)
             .await?;
@@ -1395,14 +1471,19 @@ impl SecEdgarClient {
 
     /// Store cash flow data in the database with filing metadata (transaction variant)
     async fn store_cash_flow_data_tx(&self, tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, data: &CashFlowData, sec_filing_id: i64) -> Result<()> {
+        #[cfg(any(test, feature = "test-utils"))]
+        if self.fail_cash_flow_insert {
+            return Err(anyhow!("Injected cash flow insert failure"));
+        }
+
         let query = r#"
             INSERT OR REPLACE INTO cash_flow_statements (
                 stock_id, period_type, report_date, fiscal_year,
                 depreciation_expense, amortization_expense, dividends_paid,
                 share_repurchases, operating_cash_flow, investing_cash_flow, financing_cash_flow,
-                sec_filing_id
+                capital_expenditures, free_cash_flow, sec_filing_id
             ) VALUES (
-                ?1, 'Annual', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11
+                ?1, 'Annual', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13
             )
         "#;
 
@@ -1417,6 +1498,8 @@ impl SecEdgarClient {
             .bind(data.operating_cash_flow)
             .bind(data.investing_cash_flow)
             .bind(data.financing_cash_flow)
+            .bind(data.capital_expenditures)
+            .bind(data.free_cash_flow())
             .bind(sec_filing_id)
             .execute(&mut **tx)
             .await?;
@@ -1437,10 +1520,10 @@ impl SecEdgarClient {
             INSERT OR REPLACE INTO income_statements (
                 stock_id, period_type, report_date, fiscal_year,
                 revenue, gross_profit, operating_income, net_income,
-                shares_basic, shares_diluted, currency,
+                shares_basic, shares_diluted, tax_expense, pretax_income, currency,
                 sec_filing_id
             ) VALUES (
-                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'USD', ?11
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, 'USD', ?13
             )
         "#;
 
@@ -1455,6 +1538,8 @@ impl SecEdgarClient {
             .bind(data.net_income)
             .bind(data.shares_basic)
             .bind(data.shares_diluted)
+            .bind(data.tax_expense)
+            .bind(data.pretax_income)
             .bind(sec_filing_id)
             .execute(&self.pool)
             .await?;
@@ -1468,10 +1553,10 @@ impl SecEdgarClient {
             INSERT OR REPLACE INTO income_statements (
                 stock_id, period_type, report_date, fiscal_year,
                 revenue, gross_profit, operating_income, net_income,
-                shares_basic, shares_diluted, currency,
+                shares_basic, shares_diluted, tax_expense, pretax_income, currency,
                 sec_filing_id
             ) VALUES (
-                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, 'USD', ?11
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, 'USD', ?13
             )
         "#;
 
@@ -1486,6 +1571,8 @@ impl SecEdgarClient {
             .bind(data.net_income)
             .bind(data.shares_basic)
             .bind(data.shares_diluted)
+            .bind(data.tax_expense)
+            .bind(data.pretax_income)
             .bind(sec_filing_id)
             .execute(&mut **tx)
             .await?;
@@ -1495,7 +1582,7 @@ impl SecEdgarClient {
 
     /// Download balance sheet data for all S&P 500 companies
     pub async fn download_all_sp500_balance_sheets(&mut self) -> Result<()> {
-        println!("🚀 Starting SEC EDGAR balance sheet data download for S&P 500 companies...");
+        info!("Starting SEC EDGAR balance sheet data download for S&P 500 companies...");
         
         let mappings = self.get_sp500_cik_mappings().await?;
         let total_companies = mappings.len();
@@ -1519,12 +1606,12 @@ impl SecEdgarClient {
                 Ok(filings_processed) => {
                     success_count += 1;
                     if filings_processed > 0 {
-                        println!("  ✅ {}: {} filings processed", mapping.symbol, filings_processed);
+                        info!(symbol = %mapping.symbol, "{}: {} filings processed", mapping.symbol, filings_processed);
                     }
                 }
                 Err(e) => {
                     error_count += 1;
-                    eprintln!("  ❌ {}: {}", mapping.symbol, e);
+                    error!(symbol = %mapping.symbol, "{}: {}", mapping.symbol, e);
                 }
             }
 
@@ -1538,18 +1625,14 @@ impl SecEdgarClient {
 
         pb.finish_with_message("✅ Balance sheet download completed");
         
-        println!("\n📊 SEC EDGAR Download Summary:");
-        println!("  Total Companies: {}", total_companies);
-        println!("  Successful: {}", success_count);
-        println!("  Errors: {}", error_count);
-        println!("  Success Rate: {:.1}%", (success_count as f64 / total_companies as f64) * 100.0);
+        info!(total_companies, success_count, error_count, "SEC EDGAR download summary: {:.1}% success rate", (success_count as f64 / total_companies as f64) * 100.0);
 
         Ok(())
     }
 
     /// Download income statement data for all S&P 500 companies
     pub async fn download_all_sp500_income_statements(&mut self) -> Result<()> {
-        println!("🚀 Starting SEC EDGAR income statement data download for S&P 500 companies...");
+        info!("Starting SEC EDGAR income statement data download for S&P 500 companies...");
         
         let mappings = self.get_sp500_cik_mappings().await?;
         let total_companies = mappings.len();
@@ -1573,12 +1656,12 @@ impl SecEdgarClient {
                 Ok(filings_processed) => {
                     success_count += 1;
                     if filings_processed > 0 {
-                        println!("  ✅ {}: {} filings processed", mapping.symbol, filings_processed);
+                        info!(symbol = %mapping.symbol, "{}: {} filings processed", mapping.symbol, filings_processed);
                     }
                 }
                 Err(e) => {
                     error_count += 1;
-                    eprintln!("  ❌ {}: {}", mapping.symbol, e);
+                    error!(symbol = %mapping.symbol, "{}: {}", mapping.symbol, e);
                 }
             }
 
@@ -1592,11 +1675,7 @@ impl SecEdgarClient {
 
         pb.finish_with_message("✅ Income statement download completed");
         
-        println!("\n📊 SEC EDGAR Income Statement Download Summary:");
-        println!("  Total Companies: {}", total_companies);
-        println!("  Successful: {}", success_count);
-        println!("  Errors: {}", error_count);
-        println!("  Success Rate: {:.1}%", (success_count as f64 / total_companies as f64) * 100.0);
+        info!(total_companies, success_count, error_count, "SEC EDGAR income statement download summary: {:.1}% success rate", (success_count as f64 / total_companies as f64) * 100.0);
 
         Ok(())
     }
@@ -1613,7 +1692,7 @@ impl SecEdgarClient {
                 Ok(0)
             }
             Err(e) => {
-                eprintln!("    ⚠️ Failed to extract balance sheet data for {}: {}", mapping.symbol, e);
+                warn!(symbol = %mapping.symbol, "Failed to extract balance sheet data for {}: {}", mapping.symbol, e);
                 Ok(0)
             }
         }
@@ -1631,7 +1710,7 @@ impl SecEdgarClient {
                 Ok(0)
             }
             Err(e) => {
-                eprintln!("    ⚠️ Failed to extract income statement data for {}: {}", mapping.symbol, e);
+                warn!(symbol = %mapping.symbol, "Failed to extract income statement data for {}: {}", mapping.symbol, e);
                 Ok(0)
             }
         }
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqlitePool, Row};
+use ts_rs::TS;
+
+/// Share-count concepts in order of preference when several report the same date: the two
+/// point-in-time counts, then the period-average basic count
+const SHARE_COUNT_CONCEPTS: [(&str, &str); 3] = [
+    ("us-gaap", "CommonStockSharesOutstanding"),
+    ("dei", "EntityCommonStockSharesOutstanding"),
+    ("us-gaap", "WeightedAverageNumberOfSharesOutstandingBasic"),
+];
+
+/// Changes larger than this fraction between consecutive values need a split to explain them
+pub const DISCONTINUITY_THRESHOLD: f64 = 0.10;
+
+/// One share count as of a fact's end date
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareCountFact {
+    pub as_of_date: String,
+    pub shares: f64,
+    pub concept: &'static str,
+    pub accession_number: Option<String>,
+    pub filed_date: Option<String>,
+}
+
+/// A jump in share count with no split recorded between the two dates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ShareCountDiscontinuity {
+    pub stock_id: i64,
+    pub from_date: String,
+    pub to_date: String,
+    pub from_shares: f64,
+    pub to_shares: f64,
+    /// Percent change from `from_shares`
+    pub change_pct: f64,
+}
+
+/// Every share-count fact in a Company Facts response, one per end date. The preferred concept
+/// wins a date; within a concept the latest filing wins, so restatements replace originals.
+pub fn parse_share_count_facts(company_facts: &serde_json::Value) -> Vec<ShareCountFact> {
+    let mut by_date: HashMap<String, (usize, ShareCountFact)> = HashMap::new();
+
+    for (priority, &(taxonomy, concept)) in SHARE_COUNT_CONCEPTS.iter().enumerate() {
+        let Some(values) = company_facts
+            .get("facts")
+            .and_then(|f| f.get(taxonomy))
+            .and_then(|t| t.get(concept))
+            .and_then(|c| c.get("units"))
+            .and_then(|u| u.get("shares"))
+            .and_then(|s| s.as_array())
+        else {
+            continue;
+        };
+
+        for value in values {
+            let (Some(shares), Some(end)) = (
+                value.get("val").and_then(|v| v.as_f64()),
+                value.get("end").and_then(|e| e.as_str()),
+            ) else {
+                continue;
+            };
+            if shares <= 0.0 || chrono::NaiveDate::parse_from_str(end, "%Y-%m-%d").is_err() {
+                continue;
+            }
+
+            let fact = ShareCountFact {
+                as_of_date: end.to_string(),
+                shares,
+                concept,
+                accession_number: value.get("accn").and_then(|a| a.as_str()).map(str::to_string),
+                filed_date: value.get("filed").and_then(|f| f.as_str()).map(str::to_string),
+            };
+            let replace = match by_date.get(end) {
+                None => true,
+                Some((existing_priority, existing)) => {
+                    priority < *existing_priority
+                        || (priority == *existing_priority && fact.filed_date > existing.filed_date)
+                }
+            };
+            if replace {
+                by_date.insert(end.to_string(), (priority, fact));
+            }
+        }
+    }
+
+    let mut facts: Vec<ShareCountFact> = by_date.into_values().map(|(_, fact)| fact).collect();
+    facts.sort_by(|a, b| a.as_of_date.cmp(&b.as_of_date));
+    facts
+}
+
+/// Replace a stock's share count history from a Company Facts response, then re-flag
+/// discontinuities. Returns the number of dates stored.
+pub async fn store_shares_outstanding_history(pool: &SqlitePool, stock_id: i64, company_facts: &serde_json::Value) -> Result<u64> {
+    let facts = parse_share_count_facts(company_facts);
+    if facts.is_empty() {
+        return Ok(0);
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM shares_outstanding_history WHERE stock_id = ?")
+        .bind(stock_id)
+        .execute(&mut *tx)
+        .await?;
+    for fact in &facts {
+        sqlx::query(
+            "INSERT INTO shares_outstanding_history (stock_id, as_of_date, shares, concept, accession_number, filed_date)
+             VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(stock_id)
+        .bind(&fact.as_of_date)
+        .bind(fact.shares)
+        .bind(fact.concept)
+        .bind(&fact.accession_number)
+        .bind(&fact.filed_date)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    flag_share_count_discontinuities(pool, stock_id).await?;
+    Ok(facts.len() as u64)
+}
+
+/// Latest share count on or before `date`
+pub async fn get_shares_outstanding_on(pool: &SqlitePool, stock_id: i64, date: &str) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT shares FROM shares_outstanding_history
+         WHERE stock_id = ? AND as_of_date <= ? ORDER BY as_of_date DESC LIMIT 1"
+    )
+    .bind(stock_id)
+    .bind(date)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Share count implied by the provider market cap over the close on the price day nearest
+/// `date`, preferring days on or before it. The last resort for a period whose filing
+/// reported no share count.
+pub async fn estimate_shares_from_market_cap(pool: &SqlitePool, stock_id: i64, date: &str) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT market_cap / close_price FROM daily_prices
+         WHERE stock_id = ?1 AND market_cap > 0 AND close_price > 0
+         ORDER BY date > ?2, ABS(julianday(date) - julianday(?2))
+         LIMIT 1"
+    )
+    .bind(stock_id)
+    .bind(date)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Mark each value that moved more than `DISCONTINUITY_THRESHOLD` from the one before it,
+/// unless the splits with ex-dates between the two account for the move to within the
+/// same threshold. Returns the flagged moves.
+pub async fn flag_share_count_discontinuities(pool: &SqlitePool, stock_id: i64) -> Result<Vec<ShareCountDiscontinuity>, sqlx::Error> {
+    let history: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT as_of_date, shares FROM shares_outstanding_history WHERE stock_id = ? ORDER BY as_of_date"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+    let splits: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT ex_date, ratio FROM corporate_actions WHERE stock_id = ? AND action_type = 'split' ORDER BY ex_date"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut discontinuities = Vec::new();
+    for pair in history.windows(2) {
+        let ((from_date, from_shares), (to_date, to_shares)) = (&pair[0], &pair[1]);
+        if (to_shares / from_shares - 1.0).abs() <= DISCONTINUITY_THRESHOLD {
+            continue;
+        }
+
+        let split_factor: f64 = splits
+            .iter()
+            .filter(|(ex_date, _)| ex_date > from_date && ex_date <= to_date)
+            .map(|(_, ratio)| ratio)
+            .product();
+        if (to_shares / (from_shares * split_factor) - 1.0).abs() <= DISCONTINUITY_THRESHOLD {
+            continue;
+        }
+
+        discontinuities.push(ShareCountDiscontinuity {
+            stock_id,
+            from_date: from_date.clone(),
+            to_date: to_date.clone(),
+            from_shares: *from_shares,
+            to_shares: *to_shares,
+            change_pct: (to_shares / from_shares - 1.0) * 100.0,
+        });
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE shares_outstanding_history SET unexplained_change = 0 WHERE stock_id = ?")
+        .bind(stock_id)
+        .execute(&mut *tx)
+        .await?;
+    for discontinuity in &discontinuities {
+        sqlx::query("UPDATE shares_outstanding_history SET unexplained_change = 1 WHERE stock_id = ? AND as_of_date = ?")
+            .bind(stock_id)
+            .bind(&discontinuity.to_date)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(discontinuities)
+}
+
+/// Flagged moves as last computed, oldest first
+pub async fn load_share_count_discontinuities(pool: &SqlitePool, stock_id: i64) -> Result<Vec<ShareCountDiscontinuity>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT prev.as_of_date AS from_date, prev.shares AS from_shares, h.as_of_date AS to_date, h.shares AS to_shares
+         FROM shares_outstanding_history h
+         JOIN shares_outstanding_history prev ON prev.stock_id = h.stock_id
+          AND prev.as_of_date = (SELECT MAX(p.as_of_date) FROM shares_outstanding_history p
+                                 WHERE p.stock_id = h.stock_id AND p.as_of_date < h.as_of_date)
+         WHERE h.stock_id = ? AND h.unexplained_change = 1
+         ORDER BY h.as_of_date"
+    )
+    .bind(stock_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let from_shares: f64 = row.get("from_shares");
+            let to_shares: f64 = row.get("to_shares");
+            ShareCountDiscontinuity {
+                stock_id,
+                from_date: row.get("from_date"),
+                to_date: row.get("to_date"),
+                from_shares,
+                to_shares,
+                change_pct: (to_shares / from_shares - 1.0) * 100.0,
+            }
+        })
+        .collect())
+}
+
+/// Record a split and re-check the stock's history against it
+pub async fn record_stock_split(pool: &SqlitePool, stock_id: i64, ex_date: &str, ratio: f64) -> Result<Vec<ShareCountDiscontinuity>, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO corporate_actions (stock_id, action_type, ex_date, ratio) VALUES (?, 'split', ?, ?)
+         ON CONFLICT(stock_id, action_type, ex_date) DO UPDATE SET ratio = excluded.ratio"
+    )
+    .bind(stock_id)
+    .bind(ex_date)
+    .bind(ratio)
+    .execute(pool)
+    .await?;
+
+    flag_share_count_discontinuities(pool, stock_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn seeded_pool(dir: &tempfile::TempDir) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'NVDA', 'NVIDIA Corp')")
+            .execute(&pool).await.unwrap();
+        pool
+    }
+
+    fn company_facts() -> serde_json::Value {
+        json!({
+            "facts": {
+                "us-gaap": {
+                    "CommonStockSharesOutstanding": { "units": { "shares": [
+                        { "end": "2022-01-30", "val": 2.5e9, "accn": "a1", "filed": "2022-03-18" },
+                        { "end": "2023-01-29", "val": 2.47e9, "accn": "a2", "filed": "2023-02-24" },
+                        // 10-for-1 split in June 2024
+                        { "end": "2024-01-28", "val": 2.46e9, "accn": "a3", "filed": "2024-02-21" },
+                        { "end": "2025-01-26", "val": 24.5e9, "accn": "a4", "filed": "2025-02-26" }
+                    ] } },
+                    "WeightedAverageNumberOfSharesOutstandingBasic": { "units": { "shares": [
+                        { "end": "2023-01-29", "val": 2.49e9, "accn": "a2", "filed": "2023-02-24" },
+                        { "end": "2023-04-30", "val": 2.47e9, "accn": "q1", "filed": "2023-05-26" }
+                    ] } }
+                },
+                "dei": {
+                    "EntityCommonStockSharesOutstanding": { "units": { "shares": [
+                        { "end": "2023-04-30", "val": 2.47e9, "accn": "q1", "filed": "2023-05-26" },
+                        { "end": "2023-07-30", "val": 2.47e9, "accn": "q2", "filed": "2023-08-25" },
+                        { "end": "2023-07-30", "val": 2.48e9, "accn": "q2a", "filed": "2023-09-01" }
+                    ] } }
+                }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_history_lookup_and_split_cross_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = seeded_pool(&dir).await;
+
+        let facts = parse_share_count_facts(&company_facts());
+        // One value per end date: the point-in-time count beats the weighted average, the amendment beats the original
+        assert_eq!(facts.len(), 6);
+        let apr = facts.iter().find(|f| f.as_of_date == "2023-04-30").unwrap();
+        assert_eq!(apr.concept, "EntityCommonStockSharesOutstanding");
+        assert_eq!(facts.iter().find(|f| f.as_of_date == "2023-07-30").unwrap().shares, 2.48e9);
+        assert_eq!(facts.iter().find(|f| f.as_of_date == "2023-01-29").unwrap().shares, 2.47e9);
+
+        assert_eq!(store_shares_outstanding_history(&pool, 1, &company_facts()).await.unwrap(), 6);
+        assert_eq!(get_shares_outstanding_on(&pool, 1, "2023-06-15").await.unwrap(), Some(2.47e9));
+        assert_eq!(get_shares_outstanding_on(&pool, 1, "2025-01-26").await.unwrap(), Some(24.5e9));
+        assert_eq!(get_shares_outstanding_on(&pool, 1, "2021-12-31").await.unwrap(), None);
+
+        // The tenfold jump is unexplained until the split is recorded
+        let flagged = load_share_count_discontinuities(&pool, 1).await.unwrap();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!((flagged[0].from_date.as_str(), flagged[0].to_date.as_str()), ("2024-01-28", "2025-01-26"));
+        assert!((flagged[0].change_pct - 895.93).abs() < 0.01);
+
+        assert!(record_stock_split(&pool, 1, "2024-06-10", 10.0).await.unwrap().is_empty());
+        assert!(load_share_count_discontinuities(&pool, 1).await.unwrap().is_empty());
+
+        // A split of the wrong size doesn't explain it
+        assert_eq!(record_stock_split(&pool, 1, "2024-06-10", 4.0).await.unwrap().len(), 1);
+    }
+}
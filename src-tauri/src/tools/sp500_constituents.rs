@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::tools::symbol_aliases::{find_renamed_stock, record_ticker_change};
+
+/// S&P 500 constituents list published by the datasets project
+pub const SP500_CONSTITUENTS_URL: &str =
+    "https://raw.githubusercontent.com/datasets/s-and-p-500-companies/main/data/constituents.csv";
+
+/// One row of the constituents list
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sp500Constituent {
+    #[serde(rename = "Symbol")]
+    pub symbol: String,
+    #[serde(rename = "Security")]
+    pub company_name: String,
+    #[serde(rename = "GICS Sector", default)]
+    pub sector: Option<String>,
+    #[serde(rename = "CIK", default)]
+    pub cik: Option<String>,
+}
+
+/// What `sync_sp500_constituents` changed
+#[derive(Debug, Default)]
+pub struct Sp500SyncSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    /// (old symbol, new symbol)
+    pub renamed: Vec<(String, String)>,
+    /// Stocks no longer on the list, kept with their history but no longer flagged
+    pub removed: usize,
+    /// (symbol, error) for rows that couldn't be stored
+    pub failed: Vec<(String, String)>,
+}
+
+/// Parse the constituents CSV, trimming fields and dropping rows without a symbol
+pub fn parse_sp500_constituents(csv_text: &str) -> Result<Vec<Sp500Constituent>> {
+    let mut reader = csv::Reader::from_reader(csv_text.as_bytes());
+    let mut companies = Vec::new();
+    for record in reader.deserialize() {
+        let mut company: Sp500Constituent = record?;
+        company.symbol = company.symbol.trim().to_string();
+        company.company_name = company.company_name.trim().to_string();
+        company.sector = company.sector.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        company.cik = company.cik.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        if !company.symbol.is_empty() {
+            companies.push(company);
+        }
+    }
+    Ok(companies)
+}
+
+/// Download and parse the current constituents list
+pub async fn fetch_sp500_constituents() -> Result<Vec<Sp500Constituent>> {
+    let csv_text = reqwest::get(SP500_CONSTITUENTS_URL).await?.error_for_status()?.text().await?;
+    parse_sp500_constituents(&csv_text)
+}
+
+/// Bring the stocks table in line with the constituents list without dropping any stock's
+/// history. A new symbol for a company already stored is recorded as a ticker change, listed
+/// stocks are upserted and flagged, and stocks that left the index lose the flag.
+pub async fn sync_sp500_constituents(
+    pool: &SqlitePool,
+    companies: &[Sp500Constituent],
+    as_of: &str,
+) -> Result<Sp500SyncSummary> {
+    let mut summary = Sp500SyncSummary::default();
+    let listed_symbols: HashSet<String> = companies.iter().map(|c| c.symbol.clone()).collect();
+
+    for company in companies {
+        // A new symbol for a company we already have is a ticker change: keep its history
+        // under the new symbol and the old one as an alias
+        match find_renamed_stock(pool, &company.symbol, company.cik.as_deref(), &company.company_name, &listed_symbols).await {
+            Ok(Some((stock_id, old_symbol))) => {
+                match record_ticker_change(pool, stock_id, &old_symbol, &company.symbol, as_of).await {
+                    Ok(()) => summary.renamed.push((old_symbol, company.symbol.clone())),
+                    Err(e) => summary.failed.push((company.symbol.clone(), format!("rename from {}: {}", old_symbol, e))),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => summary.failed.push((company.symbol.clone(), format!("ticker change check: {}", e))),
+        }
+
+        let known: Option<i64> = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+            .bind(&company.symbol)
+            .fetch_optional(pool)
+            .await?;
+        let result = sqlx::query(
+            "INSERT INTO stocks (symbol, company_name, sector, is_sp500)
+             VALUES (?1, ?2, ?3, 1)
+             ON CONFLICT(symbol) DO UPDATE SET
+                company_name = ?2,
+                sector = COALESCE(?3, stocks.sector),
+                is_sp500 = 1"
+        )
+        .bind(&company.symbol)
+        .bind(&company.company_name)
+        .bind(&company.sector)
+        .execute(pool)
+        .await;
+
+        match result {
+            Ok(_) if known.is_some() => summary.updated += 1,
+            Ok(_) => summary.inserted += 1,
+            Err(e) => summary.failed.push((company.symbol.clone(), e.to_string())),
+        }
+    }
+
+    let stored: Vec<(i64, String)> = sqlx::query_as("SELECT id, symbol FROM stocks WHERE is_sp500 = 1")
+        .fetch_all(pool)
+        .await?;
+    for (stock_id, symbol) in stored {
+        if !listed_symbols.contains(&symbol) {
+            sqlx::query("UPDATE stocks SET is_sp500 = 0 WHERE id = ?")
+                .bind(stock_id)
+                .execute(pool)
+                .await?;
+            summary.removed += 1;
+        }
+    }
+
+    sqlx::query("INSERT OR REPLACE INTO metadata (key, value) VALUES ('sp500_last_updated', ?1)")
+        .bind(as_of)
+        .execute(pool)
+        .await?;
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_sync_keeps_history_and_records_ticker_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, cik, is_sp500) VALUES
+                (1, 'FB', 'Meta Platforms, Inc.', '0001326801', 1),
+                (2, 'AAPL', 'Apple Inc.', '320193', 1),
+                (3, 'GONE', 'Delisted Corp', NULL, 1)"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price) VALUES (1, '2024-01-02', 1, 1, 1, 1)")
+            .execute(&pool).await.unwrap();
+
+        let companies = parse_sp500_constituents(
+            "Symbol,Security,GICS Sector,GICS Sub-Industry,CIK\n\
+             META,Meta Platforms,Communication Services,Interactive Media,0001326801\n\
+             AAPL,Apple Inc.,Information Technology,Hardware,0000320193\n\
+             NEW,New Co,,Other,\n"
+        )
+        .unwrap();
+        assert_eq!(companies[2].sector, None);
+
+        let summary = sync_sp500_constituents(&pool, &companies, "2024-06-01").await.unwrap();
+        assert_eq!(summary.renamed, vec![("FB".to_string(), "META".to_string())]);
+        assert_eq!((summary.inserted, summary.updated, summary.removed), (1, 2, 1));
+        assert!(summary.failed.is_empty());
+
+        // META kept FB's id and prices; GONE is kept but no longer flagged
+        let meta: (i64, i64) = sqlx::query_as("SELECT s.id, COUNT(p.date) FROM stocks s JOIN daily_prices p ON p.stock_id = s.id WHERE s.symbol = 'META'")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(meta, (1, 1));
+        let flags: Vec<(String, bool)> = sqlx::query_as("SELECT symbol, is_sp500 FROM stocks ORDER BY id")
+            .fetch_all(&pool).await.unwrap();
+        assert_eq!(
+            flags,
+            vec![("META".to_string(), true), ("AAPL".to_string(), true), ("GONE".to_string(), false), ("NEW".to_string(), true)]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use ts_rs::TS;
+
+use crate::tools::screening_readiness::FILING_LOOKBACK_MONTHS;
+
+/// An S&P 500 stock whose filings and prices are both behind, with why
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StaleStock {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub latest_filing_date: Option<String>,
+    pub latest_price_date: Option<String>,
+    /// Days since the latest filing / price, as of the check
+    pub filing_age_days: Option<i64>,
+    pub price_age_days: Option<i64>,
+    pub reasons: Vec<String>,
+}
+
+/// Why a stock's filings are behind as of `today`, None when they're current. Filings are
+/// behind when none was filed in the last `FILING_LOOKBACK_MONTHS` months (a yearly 10-K plus
+/// the filing window).
+pub fn filing_staleness(latest_filing: Option<NaiveDate>, today: NaiveDate) -> Option<String> {
+    let filing_cutoff = today.checked_sub_months(Months::new(FILING_LOOKBACK_MONTHS)).unwrap_or(today);
+    match latest_filing {
+        None => Some("No SEC filings stored".to_string()),
+        Some(filed) if filed < filing_cutoff => Some(format!(
+            "Latest SEC filing {} is more than {} months old; a newer 10-K is expected",
+            filed, FILING_LOOKBACK_MONTHS
+        )),
+        Some(_) => None,
+    }
+}
+
+/// Why a stock's prices are behind as of `today`: the latest close is over `staleness_days` old
+pub fn price_staleness(latest_price: Option<NaiveDate>, staleness_days: i64, today: NaiveDate) -> Option<String> {
+    match latest_price {
+        None => Some("No daily prices stored".to_string()),
+        Some(date) if (today - date).num_days() > staleness_days => Some(format!(
+            "Latest price {} is {} days old (limit {})",
+            date,
+            (today - date).num_days(),
+            staleness_days
+        )),
+        Some(_) => None,
+    }
+}
+
+/// S&P 500 stocks behind on both SEC filings and daily prices, by symbol
+pub async fn find_stale_stocks(pool: &SqlitePool, staleness_days: i64, today: NaiveDate) -> Result<Vec<StaleStock>> {
+    let rows: Vec<(i64, String, Option<NaiveDate>, Option<NaiveDate>)> = sqlx::query_as(
+        "SELECT s.id, s.symbol,
+                (SELECT MAX(f.filed_date) FROM sec_filings f WHERE f.stock_id = s.id) AS latest_filing,
+                (SELECT MAX(p.date) FROM daily_prices p WHERE p.stock_id = s.id) AS latest_price
+         FROM sp500_symbols sp
+         JOIN stocks s ON s.id = sp.id
+         ORDER BY s.symbol"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let to_string = |date: Option<NaiveDate>| date.map(|date| date.format("%Y-%m-%d").to_string());
+    Ok(rows
+        .into_iter()
+        .filter_map(|(stock_id, symbol, latest_filing, latest_price)| {
+            let filing_reason = filing_staleness(latest_filing, today)?;
+            let price_reason = price_staleness(latest_price, staleness_days, today)?;
+            Some(StaleStock {
+                stock_id,
+                symbol,
+                latest_filing_date: to_string(latest_filing),
+                latest_price_date: to_string(latest_price),
+                filing_age_days: latest_filing.map(|date| (today - date).num_days()),
+                price_age_days: latest_price.map(|date| (today - date).num_days()),
+                reasons: vec![filing_reason, price_reason],
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_only_stale_stock_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES
+                (1, 'FRESH', 'Fresh Co', 1), (2, 'OLD', 'Old Co', 1), (3, 'OTHER', 'Not In Index', 0),
+                (4, 'LATE', 'Late Filer', 1)"
+        )
+        .execute(&pool).await.unwrap();
+        // FRESH filed its 10-K in February and traded this week; OLD last filed two years ago
+        // and stopped trading in March. OTHER isn't in the S&P 500. LATE is behind on filings
+        // only: it still traded this week.
+        for (stock_id, filed) in [(1, "2024-02-20"), (2, "2022-05-01"), (3, "2020-01-01"), (4, "2022-05-01")] {
+            sqlx::query(
+                "INSERT INTO sec_filings (stock_id, accession_number, form_type, filed_date, fiscal_year, report_date)
+                 VALUES (?, ?, '10-K', ?, 2023, '2023-12-31')"
+            )
+            .bind(stock_id).bind(format!("0000-{}", stock_id)).bind(filed)
+            .execute(&pool).await.unwrap();
+        }
+        for (stock_id, day) in [(1, "2024-06-03"), (2, "2024-03-15"), (4, "2024-06-03")] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?, ?, 10.0, 10.0, 10.0, 10.0)"
+            )
+            .bind(stock_id).bind(day)
+            .execute(&pool).await.unwrap();
+        }
+
+        let today = NaiveDate::from_ymd_opt(2024, 6, 5).unwrap();
+        let stale = find_stale_stocks(&pool, 7, today).await.unwrap();
+        assert_eq!(stale.len(), 1);
+        let old = &stale[0];
+        assert_eq!(old.symbol, "OLD");
+        assert_eq!((old.filing_age_days, old.price_age_days), (Some(766), Some(82)));
+        assert_eq!(old.reasons.len(), 2);
+        assert!(old.reasons[1].contains("82 days old"));
+
+        // Under a looser price limit OLD's prices are current, so it's no longer stale
+        let loose = find_stale_stocks(&pool, 100, today).await.unwrap();
+        assert!(loose.is_empty());
+    }
+}
@@ -0,0 +1,339 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use ts_rs::TS;
+
+use crate::error::CommandError;
+
+/// Tables whose rows move to `<table>_archive` when a stock is archived with its data
+pub const ARCHIVED_TABLES: [&str; 4] = ["daily_prices", "income_statements", "balance_sheets", "cash_flow_statements"];
+
+/// Outcome of archiving, restoring or purging one stock
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StockArchiveResult {
+    pub stock_id: i64,
+    pub symbol: String,
+    /// 'active' or 'delisted'; None once purged
+    pub status: Option<String>,
+    pub archived_at: Option<String>,
+    /// Price and statement rows moved into (or back out of) the archive tables
+    pub rows_moved: i64,
+    /// Rows physically deleted; only nonzero for a purge
+    pub rows_deleted: i64,
+}
+
+async fn find_stock(tx: &mut Transaction<'_, Sqlite>, symbol: &str) -> Result<(i64, String)> {
+    let stock: Option<(i64, String)> = sqlx::query_as("SELECT id, status FROM stocks WHERE symbol = ?")
+        .bind(symbol)
+        .fetch_optional(&mut **tx)
+        .await?;
+    Ok(stock.ok_or_else(|| CommandError::not_found("stock", format!("Unknown symbol {}", symbol)))?)
+}
+
+/// Columns the archive table shares with its live table, so a move copies them by name
+/// even after later migrations add columns to the live table. The row id is left out: ids
+/// freed by archiving are reused by other stocks' rows, so a restored row takes a new one.
+async fn shared_columns(tx: &mut Transaction<'_, Sqlite>, table: &str) -> Result<String> {
+    let columns: Vec<String> = sqlx::query_scalar(
+        "SELECT a.name FROM pragma_table_info(?1) a
+         JOIN pragma_table_info(?2) t ON t.name = a.name
+         WHERE a.name != 'id'
+         ORDER BY a.cid"
+    )
+    .bind(format!("{}_archive", table))
+    .bind(table)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(columns.join(", "))
+}
+
+/// Move one stock's rows from `from` to `to`, which share `columns`. Rows `to` already holds
+/// under its unique key (stock and date or period) win: a restore keeps prices and statements
+/// refreshed since the archive and drops the archived copies.
+async fn move_rows(tx: &mut Transaction<'_, Sqlite>, from: &str, to: &str, columns: &str, stock_id: i64) -> Result<i64> {
+    let moved = sqlx::query(&format!(
+        "INSERT OR IGNORE INTO {to} ({columns}) SELECT {columns} FROM {from} WHERE stock_id = ?"
+    ))
+    .bind(stock_id)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+    sqlx::query(&format!("DELETE FROM {from} WHERE stock_id = ?"))
+        .bind(stock_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(moved as i64)
+}
+
+async fn load_result(pool: &SqlitePool, stock_id: i64, rows_moved: i64) -> Result<StockArchiveResult> {
+    let (symbol, status, archived_at): (String, String, Option<String>) =
+        sqlx::query_as("SELECT symbol, status, archived_at FROM stocks WHERE id = ?")
+            .bind(stock_id)
+            .fetch_one(pool)
+            .await?;
+    Ok(StockArchiveResult {
+        stock_id,
+        symbol,
+        status: Some(status),
+        archived_at,
+        rows_moved,
+        rows_deleted: 0,
+    })
+}
+
+/// Mark a stock delisted, keeping all its data. With `move_data` its prices and statements
+/// move to the archive tables so screens and refreshes no longer see them. Archiving an
+/// archived stock again only moves any rows still in the live tables.
+pub async fn archive_stock(pool: &SqlitePool, symbol: &str, move_data: bool) -> Result<StockArchiveResult> {
+    let mut tx = pool.begin().await?;
+    let (stock_id, _) = find_stock(&mut tx, symbol).await?;
+
+    sqlx::query(
+        "UPDATE stocks SET status = 'delisted', archived_at = COALESCE(archived_at, CURRENT_TIMESTAMP)
+         WHERE id = ?"
+    )
+    .bind(stock_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let mut rows_moved = 0;
+    if move_data {
+        for table in ARCHIVED_TABLES {
+            let columns = shared_columns(&mut tx, table).await?;
+            rows_moved += move_rows(&mut tx, table, &format!("{}_archive", table), &columns, stock_id).await?;
+        }
+    }
+    tx.commit().await?;
+
+    load_result(pool, stock_id, rows_moved).await
+}
+
+/// Reactivate an archived stock and move any archived rows back to the live tables
+pub async fn restore_stock(pool: &SqlitePool, symbol: &str) -> Result<StockArchiveResult> {
+    let mut tx = pool.begin().await?;
+    let (stock_id, status) = find_stock(&mut tx, symbol).await?;
+    if status != "delisted" {
+        return Err(CommandError::validation("symbol", format!("{} is not archived", symbol)).into());
+    }
+
+    let mut rows_moved = 0;
+    for table in ARCHIVED_TABLES {
+        let columns = shared_columns(&mut tx, table).await?;
+        rows_moved += move_rows(&mut tx, &format!("{}_archive", table), table, &columns, stock_id).await?;
+    }
+    sqlx::query("UPDATE stocks SET status = 'active', archived_at = NULL WHERE id = ?")
+        .bind(stock_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    load_result(pool, stock_id, rows_moved).await
+}
+
+/// Tables holding per-stock rows and the column naming the stock: every `stock_id` column,
+/// with or without a foreign key (the archives, snapshot items and option chains have none),
+/// and any other column with a foreign key to stocks. Tables other tables point at
+/// (sec_filings, under the statements) go last.
+pub(crate) async fn referencing_tables(tx: &mut Transaction<'_, Sqlite>) -> Result<Vec<(String, String)>> {
+    Ok(sqlx::query_as(
+        "SELECT m.name, c.name FROM sqlite_master m, pragma_table_info(m.name) c
+         WHERE m.type = 'table' AND m.name != 'stocks'
+           AND (c.name = 'stock_id' OR EXISTS (
+               SELECT 1 FROM pragma_foreign_key_list(m.name) f WHERE f.\"table\" = 'stocks' AND f.\"from\" = c.name
+           ))
+         ORDER BY EXISTS (
+             SELECT 1 FROM sqlite_master d, pragma_foreign_key_list(d.name) df
+             WHERE d.type = 'table' AND df.\"table\" = m.name
+         )"
+    )
+    .fetch_all(&mut **tx)
+    .await?)
+}
+
+/// Physically delete an archived stock: its row, every row referencing it, and its archived
+/// rows. Only archived stocks can be purged, so a purge always follows an explicit archive.
+pub async fn purge_stock(pool: &SqlitePool, symbol: &str) -> Result<StockArchiveResult> {
+    let mut tx = pool.begin().await?;
+    let (stock_id, status) = find_stock(&mut tx, symbol).await?;
+    if status != "delisted" {
+        return Err(CommandError::validation("symbol", format!("Archive {} before purging it", symbol)).into());
+    }
+
+    let mut rows_deleted = 0;
+    for (table, column) in referencing_tables(&mut tx).await? {
+        rows_deleted += sqlx::query(&format!("DELETE FROM \"{}\" WHERE \"{}\" = ?", table, column))
+            .bind(stock_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected() as i64;
+    }
+    rows_deleted += sqlx::query("DELETE FROM stocks WHERE id = ?")
+        .bind(stock_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() as i64;
+    tx.commit().await?;
+
+    Ok(StockArchiveResult {
+        stock_id,
+        symbol: symbol.to_string(),
+        status: None,
+        archived_at: None,
+        rows_moved: 0,
+        rows_deleted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn count(pool: &SqlitePool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} WHERE stock_id = 1", table))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_restore_round_trip_keeps_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name, is_sp500) VALUES (1, 'GONE', 'Gone Corp', 1)")
+            .execute(&pool).await.unwrap();
+        for (day, close) in [("2024-01-02", 10.0), ("2024-01-03", 11.5)] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (1, ?, ?, ?, ?, ?)"
+            )
+            .bind(day).bind(close).bind(close).bind(close).bind(close)
+            .execute(&pool).await.unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, net_income)
+             VALUES (1, 'FY', '2023-12-31', 2023, 1000.0, 100.0)"
+        )
+        .execute(&pool).await.unwrap();
+
+        let archived = archive_stock(&pool, "GONE", true).await.unwrap();
+        assert_eq!(archived.status.as_deref(), Some("delisted"));
+        assert!(archived.archived_at.is_some());
+        assert_eq!(archived.rows_moved, 3);
+        assert_eq!((count(&pool, "daily_prices").await, count(&pool, "daily_prices_archive").await), (0, 2));
+        let listed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sp500_symbols").fetch_one(&pool).await.unwrap();
+        assert_eq!(listed, 0);
+
+        let restored = restore_stock(&pool, "GONE").await.unwrap();
+        assert_eq!((restored.status.as_deref(), restored.archived_at), (Some("active"), None));
+        assert_eq!(restored.rows_moved, 3);
+        assert_eq!((count(&pool, "daily_prices").await, count(&pool, "daily_prices_archive").await), (2, 0));
+
+        let close: f64 = sqlx::query_scalar("SELECT close_price FROM daily_prices WHERE stock_id = 1 AND date = '2024-01-03'")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(close, 11.5);
+        let revenue: f64 = sqlx::query_scalar("SELECT revenue FROM income_statements WHERE stock_id = 1")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(revenue, 1000.0);
+
+        // Purging needs the stock archived first
+        assert!(purge_stock(&pool, "GONE").await.is_err());
+        archive_stock(&pool, "GONE", false).await.unwrap();
+        let purged = purge_stock(&pool, "GONE").await.unwrap();
+        assert_eq!(purged.rows_deleted, 4);
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM stocks").fetch_one(&pool).await.unwrap();
+        assert_eq!(remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_restore_keeps_refreshed_rows_and_purge_leaves_no_orphans() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'GONE', 'Gone Corp')")
+            .execute(&pool).await.unwrap();
+        let insert_price = |close: f64| {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (1, '2024-01-02', ?1, ?1, ?1, ?1)"
+            )
+            .bind(close)
+        };
+        insert_price(10.0).execute(&pool).await.unwrap();
+        archive_stock(&pool, "GONE", true).await.unwrap();
+
+        // The same day was refreshed while archived: the live row wins over the archived one
+        insert_price(12.0).execute(&pool).await.unwrap();
+        let restored = restore_stock(&pool, "GONE").await.unwrap();
+        assert_eq!(restored.rows_moved, 0);
+        assert_eq!((count(&pool, "daily_prices").await, count(&pool, "daily_prices_archive").await), (1, 0));
+        let close: f64 = sqlx::query_scalar("SELECT close_price FROM daily_prices WHERE stock_id = 1")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(close, 12.0);
+
+        // Tables whose stock_id has no foreign key are purged too
+        sqlx::query("INSERT INTO screening_snapshots (id, screen_type, criteria, result_count) VALUES (1, 'piotroski', '{}', 1)")
+            .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO snapshot_items (snapshot_id, stock_id, symbol, rank) VALUES (1, 1, 'GONE', 1)")
+            .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO options_chains (stock_id, snapshot_time, contract_symbol, expiration_date, strike_price, option_type)
+             VALUES (1, '2024-01-02T15:00:00Z', 'GONE  240119C00010000', '2024-01-19', 10.0, 'CALL')"
+        )
+        .execute(&pool).await.unwrap();
+        archive_stock(&pool, "GONE", false).await.unwrap();
+        purge_stock(&pool, "GONE").await.unwrap();
+        for table in ["daily_prices", "snapshot_items", "options_chains"] {
+            assert_eq!(count(&pool, table).await, 0, "{} kept rows of the purged stock", table);
+        }
+    }
+
+    /// Two prices, an income statement and a balance sheet
+    async fn insert_price_and_statement_rows(pool: &SqlitePool, stock_id: i64) {
+        for day in ["2024-01-02", "2024-01-03"] {
+            sqlx::query(
+                "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price)
+                 VALUES (?, ?, 10.0, 10.0, 10.0, 10.0)"
+            )
+            .bind(stock_id).bind(day)
+            .execute(pool).await.unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue)
+             VALUES (?, 'FY', '2023-12-31', 2023, 1000.0)"
+        )
+        .bind(stock_id)
+        .execute(pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO balance_sheets (stock_id, period_type, report_date, fiscal_year, total_assets)
+             VALUES (?, 'Annual', '2023-12-31', 2023, 500.0)"
+        )
+        .bind(stock_id)
+        .execute(pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restore_brings_back_rows_whose_ids_were_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (1, 'GONE', 'Gone Corp'), (2, 'LIVE', 'Live Corp')")
+            .execute(&pool).await.unwrap();
+        insert_price_and_statement_rows(&pool, 1).await;
+        archive_stock(&pool, "GONE", true).await.unwrap();
+
+        // With the live tables empty, LIVE's rows take the ids GONE's rows had
+        insert_price_and_statement_rows(&pool, 2).await;
+
+        let restored = restore_stock(&pool, "GONE").await.unwrap();
+        assert_eq!(restored.rows_moved, 4);
+        for table in ["daily_prices", "income_statements", "balance_sheets"] {
+            let per_stock: Vec<(i64, i64)> = sqlx::query_as(&format!(
+                "SELECT stock_id, COUNT(*) FROM {} GROUP BY stock_id ORDER BY stock_id", table
+            ))
+            .fetch_all(&pool).await.unwrap();
+            let expected = if table == "daily_prices" { vec![(1, 2), (2, 2)] } else { vec![(1, 1), (2, 1)] };
+            assert_eq!(per_stock, expected, "{} lost rows on restore", table);
+            assert_eq!(count(&pool, &format!("{}_archive", table)).await, 0);
+        }
+    }
+}
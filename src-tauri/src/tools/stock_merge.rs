@@ -0,0 +1,457 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use ts_rs::TS;
+
+use crate::error::CommandError;
+use crate::tools::stock_archive::referencing_tables;
+
+/// Trailing words dropped when comparing company names, so "Meta Platforms, Inc." and
+/// "Meta Platforms Inc" match
+const COMPANY_NAME_SUFFIXES: [&str; 10] =
+    ["inc", "incorporated", "corp", "corporation", "co", "company", "ltd", "limited", "plc", "the"];
+
+/// Statement tables without a uniqueness constraint; a duplicate's statements for a period the
+/// survivor already reports are dropped rather than stored twice
+const PERIOD_KEYED_TABLES: [&str; 3] = ["income_statements", "balance_sheets", "cash_flow_statements"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateMatch {
+    /// Same SEC CIK once leading zeros are ignored
+    Cik,
+    /// Same company name once case, punctuation and suffixes like "Inc." are ignored, with
+    /// at most one CIK among them
+    CompanyName,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DuplicateStock {
+    pub stock_id: i64,
+    pub symbol: String,
+    pub company_name: String,
+    pub cik: Option<String>,
+    pub status: String,
+    pub price_rows: i64,
+    pub latest_price_date: Option<String>,
+}
+
+/// Stocks that look like one company stored under more than one row, usually after a
+/// ticker change; candidates for review, not merged automatically
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DuplicateStockGroup {
+    pub match_kind: DuplicateMatch,
+    /// The normalized CIK or company name the stocks share
+    pub key: String,
+    pub stocks: Vec<DuplicateStock>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StockMergeResult {
+    pub survivor_id: i64,
+    pub survivor_symbol: String,
+    /// The merged stock's symbol, now an alias of the survivor
+    pub merged_symbol: String,
+    /// Rows re-pointed from the merged stock to the survivor
+    pub rows_moved: i64,
+    /// The merged stock's rows dropped because the survivor already had them
+    pub rows_dropped: i64,
+}
+
+pub(crate) fn normalize_cik(cik: &str) -> Option<String> {
+    let digits = cik.trim().trim_start_matches('0');
+    (!digits.is_empty()).then(|| digits.to_string())
+}
+
+pub(crate) fn normalize_company_name(name: &str) -> String {
+    let lowered = name.to_lowercase();
+    let mut words: Vec<&str> = lowered
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    while words.len() > 1 && words.last().is_some_and(|word| COMPANY_NAME_SUFFIXES.contains(word)) {
+        words.pop();
+    }
+    if words.len() > 1 && words[0] == "the" {
+        words.remove(0);
+    }
+    words.join(" ")
+}
+
+/// Groups of stocks sharing a CIK or a company name, CIK matches first. Stocks already grouped
+/// by CIK aren't listed again under their name, and same-named stocks with different CIKs are
+/// different registrants, so they aren't listed at all.
+pub async fn find_duplicate_stocks(pool: &SqlitePool) -> Result<Vec<DuplicateStockGroup>> {
+    let stocks: Vec<(i64, String, String, Option<String>, String, i64, Option<String>)> = sqlx::query_as(
+        "SELECT s.id, s.symbol, s.company_name, s.cik, s.status, COUNT(p.date), MAX(p.date)
+         FROM stocks s LEFT JOIN daily_prices p ON p.stock_id = s.id
+         GROUP BY s.id
+         ORDER BY s.symbol"
+    )
+    .fetch_all(pool)
+    .await?;
+    let stocks: Vec<DuplicateStock> = stocks
+        .into_iter()
+        .map(|(stock_id, symbol, company_name, cik, status, price_rows, latest_price_date)| DuplicateStock {
+            stock_id,
+            symbol,
+            company_name,
+            cik,
+            status,
+            price_rows,
+            latest_price_date,
+        })
+        .collect();
+
+    let mut by_cik: BTreeMap<String, Vec<DuplicateStock>> = BTreeMap::new();
+    let mut by_name: BTreeMap<String, Vec<DuplicateStock>> = BTreeMap::new();
+    for stock in stocks {
+        if let Some(cik) = stock.cik.as_deref().and_then(normalize_cik) {
+            by_cik.entry(cik).or_default().push(stock.clone());
+        }
+        by_name.entry(normalize_company_name(&stock.company_name)).or_default().push(stock);
+    }
+
+    let mut groups: Vec<DuplicateStockGroup> = by_cik
+        .into_iter()
+        .filter(|(_, stocks)| stocks.len() > 1)
+        .map(|(key, stocks)| DuplicateStockGroup { match_kind: DuplicateMatch::Cik, key, stocks })
+        .collect();
+    groups.extend(by_name.into_iter().filter_map(|(key, stocks)| {
+        let ciks: BTreeSet<Option<String>> =
+            stocks.iter().map(|stock| stock.cik.as_deref().and_then(normalize_cik)).collect();
+        let known_ciks = ciks.iter().flatten().count();
+        // One shared CIK means the CIK group above already lists them
+        let already_listed = ciks.len() == 1 && known_ciks == 1;
+        (stocks.len() > 1 && known_ciks <= 1 && !already_listed)
+            .then(|| DuplicateStockGroup { match_kind: DuplicateMatch::CompanyName, key, stocks })
+    }));
+    Ok(groups)
+}
+
+/// (symbol, cik, sector, industry, description, first_trading_date, is_sp500)
+type StockDetails = (String, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, bool);
+
+async fn load_stock(tx: &mut Transaction<'_, Sqlite>, stock_id: i64) -> Result<StockDetails> {
+    let stock: Option<StockDetails> = sqlx::query_as(
+        "SELECT symbol, cik, sector, industry, description, first_trading_date, is_sp500 FROM stocks WHERE id = ?"
+    )
+    .bind(stock_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    Ok(stock.ok_or_else(|| CommandError::not_found("stock", format!("No stock with id {}", stock_id)))?)
+}
+
+/// Re-point the duplicate's rows in `table` to the survivor. Rows the survivor already has
+/// (by the table's unique key, or by period for the statement tables) are dropped instead.
+/// Returns (moved, dropped).
+async fn move_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+    column: &str,
+    survivor_id: i64,
+    duplicate_id: i64,
+) -> Result<(i64, i64)> {
+    let mut dropped = 0;
+    if PERIOD_KEYED_TABLES.contains(&table) {
+        dropped += sqlx::query(&format!(
+            "DELETE FROM {table} WHERE stock_id = ?2 AND EXISTS (
+                 SELECT 1 FROM {table} s
+                 WHERE s.stock_id = ?1 AND s.period_type = {table}.period_type AND s.report_date = {table}.report_date
+             )"
+        ))
+        .bind(survivor_id)
+        .bind(duplicate_id)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected() as i64;
+    }
+
+    let moved = sqlx::query(&format!("UPDATE OR IGNORE \"{}\" SET \"{}\" = ? WHERE \"{}\" = ?", table, column, column))
+        .bind(survivor_id)
+        .bind(duplicate_id)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected() as i64;
+
+    if table == "sec_filings" {
+        repoint_filing_references(tx, survivor_id, duplicate_id).await?;
+    }
+    dropped += sqlx::query(&format!("DELETE FROM \"{}\" WHERE \"{}\" = ?", table, column))
+        .bind(duplicate_id)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected() as i64;
+    Ok((moved, dropped))
+}
+
+/// Filings both stocks stored stay with the duplicate until dropped; point the statements
+/// parsed from them at the survivor's copy first
+async fn repoint_filing_references(tx: &mut Transaction<'_, Sqlite>, survivor_id: i64, duplicate_id: i64) -> Result<()> {
+    let referencing: Vec<(String, String)> = sqlx::query_as(
+        "SELECT m.name, f.\"from\" FROM sqlite_master m, pragma_foreign_key_list(m.name) f
+         WHERE m.type = 'table' AND f.\"table\" = 'sec_filings'"
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+    for (table, column) in referencing {
+        sqlx::query(&format!(
+            "UPDATE \"{table}\" SET \"{column}\" = (
+                 SELECT s.id FROM sec_filings s, sec_filings d
+                 WHERE d.id = \"{table}\".\"{column}\" AND s.stock_id = ?1
+                   AND (s.accession_number = d.accession_number
+                        OR (s.form_type, s.report_date, s.fiscal_year) = (d.form_type, d.report_date, d.fiscal_year))
+                 LIMIT 1
+             )
+             WHERE \"{column}\" IN (SELECT id FROM sec_filings WHERE stock_id = ?2)"
+        ))
+        .bind(survivor_id)
+        .bind(duplicate_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Merge `duplicate_id` into `survivor_id` in one transaction: every row referencing the
+/// duplicate moves to the survivor, the duplicate's symbol becomes an alias of the survivor,
+/// and the duplicate's row is deleted. Identical overlapping price days keep the survivor's
+/// row; if any overlapping day has different prices nothing is merged.
+pub async fn merge_stocks(pool: &SqlitePool, survivor_id: i64, duplicate_id: i64) -> Result<StockMergeResult> {
+    if survivor_id == duplicate_id {
+        return Err(CommandError::validation("duplicate_id", "Can't merge a stock into itself").into());
+    }
+
+    let mut tx = pool.begin().await?;
+    let survivor = load_stock(&mut tx, survivor_id).await?;
+    let (duplicate_symbol, cik, sector, industry, description, first_trading_date, is_sp500) =
+        load_stock(&mut tx, duplicate_id).await?;
+
+    let conflicts: Vec<(String, f64, f64)> = sqlx::query_as(
+        "SELECT date(d.date), s.close_price, d.close_price
+         FROM daily_prices d
+         JOIN daily_prices s ON s.stock_id = ?1 AND date(s.date) = date(d.date)
+         WHERE d.stock_id = ?2
+           AND (s.open_price IS NOT d.open_price OR s.high_price IS NOT d.high_price
+                OR s.low_price IS NOT d.low_price OR s.close_price IS NOT d.close_price
+                OR s.volume IS NOT d.volume)
+         ORDER BY 1"
+    )
+    .bind(survivor_id)
+    .bind(duplicate_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    if let Some((date, survivor_close, duplicate_close)) = conflicts.first() {
+        return Err(CommandError::validation(
+            "duplicate_id",
+            format!(
+                "{} and {} have different prices on {} overlapping days, first {} (close {} vs {}); resolve them before merging",
+                survivor.0, duplicate_symbol, conflicts.len(), date, survivor_close, duplicate_close
+            ),
+        )
+        .into());
+    }
+
+    // The old symbol stopped trading with the duplicate's last bar
+    let last_traded: Option<String> = sqlx::query_scalar("SELECT MAX(date(date)) FROM daily_prices WHERE stock_id = ?")
+        .bind(duplicate_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    // Filings before the statements that point at them
+    let referencing = referencing_tables(&mut tx).await?;
+    let (mut rows_moved, mut rows_dropped) = (0, 0);
+    for (table, column) in referencing.into_iter().rev() {
+        let (moved, dropped) = move_rows(&mut tx, &table, &column, survivor_id, duplicate_id).await?;
+        rows_moved += moved;
+        rows_dropped += dropped;
+    }
+
+    sqlx::query("DELETE FROM stocks WHERE id = ?")
+        .bind(duplicate_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT OR REPLACE INTO symbol_aliases (symbol, stock_id, effective_until) VALUES (?, ?, ?)")
+        .bind(&duplicate_symbol)
+        .bind(survivor_id)
+        .bind(last_traded)
+        .execute(&mut *tx)
+        .await?;
+    // The survivor keeps its own details, filling gaps from the duplicate's
+    sqlx::query(
+        "UPDATE stocks SET
+             cik = COALESCE(cik, ?2),
+             sector = COALESCE(sector, ?3),
+             industry = COALESCE(industry, ?4),
+             description = COALESCE(description, ?5),
+             first_trading_date = CASE WHEN first_trading_date IS NULL OR ?6 < first_trading_date THEN ?6 ELSE first_trading_date END,
+             is_sp500 = MAX(is_sp500, ?7)
+         WHERE id = ?1"
+    )
+    .bind(survivor_id)
+    .bind(cik)
+    .bind(sector)
+    .bind(industry)
+    .bind(description)
+    .bind(first_trading_date)
+    .bind(is_sp500)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(StockMergeResult {
+        survivor_id,
+        survivor_symbol: survivor.0,
+        merged_symbol: duplicate_symbol,
+        rows_moved,
+        rows_dropped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::stocks::search_stocks_internal;
+    use crate::tests::fixtures::migrated_pool;
+
+    async fn insert_price(pool: &SqlitePool, stock_id: i64, date: &str, close: f64) {
+        sqlx::query(
+            "INSERT INTO daily_prices (stock_id, date, open_price, high_price, low_price, close_price, volume)
+             VALUES (?, ?, ?, ?, ?, ?, 1000)"
+        )
+        .bind(stock_id).bind(date).bind(close).bind(close).bind(close).bind(close)
+        .execute(pool).await.unwrap();
+    }
+
+    /// FB (id 1, with a filing and its statement) renamed to META (id 2), whose history starts
+    /// the day FB's last bar was stored
+    async fn renamed_stock_pool(dir: &tempfile::TempDir, meta_close_on_overlap: f64) -> SqlitePool {
+        let pool = migrated_pool(dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, cik, is_sp500) VALUES
+                (1, 'FB', 'Facebook, Inc.', '0001326801', 1),
+                (2, 'META', 'Meta Platforms, Inc.', NULL, 0)"
+        )
+        .execute(&pool).await.unwrap();
+        insert_price(&pool, 1, "2022-06-08", 190.0).await;
+        insert_price(&pool, 1, "2022-06-09", 191.0).await;
+        insert_price(&pool, 2, "2022-06-09", meta_close_on_overlap).await;
+        insert_price(&pool, 2, "2022-06-10", 192.0).await;
+        sqlx::query(
+            "INSERT INTO sec_filings (id, stock_id, accession_number, form_type, filed_date, fiscal_year, report_date)
+             VALUES (10, 1, '0001326801-22-000018', '10-K', '2022-02-02', 2021, '2021-12-31')"
+        )
+        .execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO income_statements (stock_id, period_type, report_date, fiscal_year, revenue, sec_filing_id)
+             VALUES (1, 'FY', '2021-12-31', 2021, 117.9e9, 10)"
+        )
+        .execute(&pool).await.unwrap();
+        pool
+    }
+
+    async fn count(pool: &SqlitePool, table: &str, stock_id: i64) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} WHERE stock_id = ?", table))
+            .bind(stock_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_company_names_normalize_past_punctuation_and_suffixes() {
+        assert_eq!(normalize_company_name("Meta Platforms, Inc."), "meta platforms");
+        assert_eq!(normalize_company_name("The Coca-Cola Co"), "coca cola");
+        assert_eq!(normalize_company_name("Company"), "company");
+        assert_eq!(normalize_cik(" 0000320193 ").as_deref(), Some("320193"));
+        assert_eq!(normalize_cik("000"), None);
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_group_by_cik_then_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, cik) VALUES
+                (1, 'FB', 'Facebook, Inc.', '1326801'),
+                (2, 'META', 'Meta Platforms, Inc.', '0001326801'),
+                (3, 'TWTR', 'Twitter, Inc.', '1418091'),
+                (4, 'TWTR.OLD', 'Twitter Inc', NULL),
+                (5, 'KO', 'Coca-Cola Co', '21344'),
+                (6, 'COKE', 'Coca-Cola Consolidated', '317540'),
+                (7, 'ACME', 'Acme Corp', '111'),
+                (8, 'ACMR', 'ACME Corporation', '222')"
+        )
+        .execute(&pool).await.unwrap();
+        insert_price(&pool, 1, "2022-06-08", 190.0).await;
+
+        let groups = find_duplicate_stocks(&pool).await.unwrap();
+        let found: Vec<(DuplicateMatch, &str, Vec<&str>)> = groups
+            .iter()
+            .map(|g| (g.match_kind, g.key.as_str(), g.stocks.iter().map(|s| s.symbol.as_str()).collect()))
+            .collect();
+        // Same-named ACME stocks have different CIKs, so they're different companies
+        assert_eq!(found, vec![
+            (DuplicateMatch::Cik, "1326801", vec!["FB", "META"]),
+            (DuplicateMatch::CompanyName, "twitter", vec!["TWTR", "TWTR.OLD"]),
+        ]);
+        assert_eq!((groups[0].stocks[0].price_rows, groups[0].stocks[0].latest_price_date.as_deref()), (1, Some("2022-06-08")));
+    }
+
+    #[tokio::test]
+    async fn test_merge_moves_history_and_keeps_old_symbol_searchable() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = renamed_stock_pool(&dir, 191.0).await;
+
+        let merged = merge_stocks(&pool, 2, 1).await.unwrap();
+        assert_eq!((merged.survivor_symbol.as_str(), merged.merged_symbol.as_str()), ("META", "FB"));
+        // FB's 2022-06-09 bar matches META's, so it's dropped rather than moved
+        assert_eq!((merged.rows_moved, merged.rows_dropped), (3, 1));
+
+        let dates: Vec<String> = sqlx::query_scalar("SELECT date FROM daily_prices WHERE stock_id = 2 ORDER BY date")
+            .fetch_all(&pool).await.unwrap();
+        assert_eq!(dates, ["2022-06-08", "2022-06-09", "2022-06-10"]);
+        let statement: (i64, Option<i64>) = sqlx::query_as("SELECT stock_id, sec_filing_id FROM income_statements")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(statement, (2, Some(10)));
+        assert_eq!(count(&pool, "sec_filings", 2).await, 1);
+
+        let survivor: (Option<String>, bool) = sqlx::query_as("SELECT cik, is_sp500 FROM stocks WHERE id = 2")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(survivor, (Some("0001326801".to_string()), true));
+        let stocks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM stocks").fetch_one(&pool).await.unwrap();
+        assert_eq!(stocks, 1);
+        let alias: (i64, Option<String>) = sqlx::query_as("SELECT stock_id, effective_until FROM symbol_aliases WHERE symbol = 'FB'")
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(alias, (2, Some("2022-06-09".to_string())));
+
+        let found: Vec<String> = search_stocks_internal(&pool, "fb").await.unwrap().into_iter().map(|s| s.symbol).collect();
+        assert_eq!(found, ["META"]);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_overlap_rolls_back_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = renamed_stock_pool(&dir, 195.5).await;
+
+        let err = merge_stocks(&pool, 2, 1).await.unwrap_err();
+        assert!(err.to_string().contains("different prices on 1 overlapping days, first 2022-06-09 (close 195.5 vs 191)"), "{}", err);
+
+        // Nothing moved: both stocks keep their rows and FB gets no alias
+        assert_eq!((count(&pool, "daily_prices", 1).await, count(&pool, "daily_prices", 2).await), (2, 2));
+        assert_eq!((count(&pool, "sec_filings", 1).await, count(&pool, "income_statements", 1).await), (1, 1));
+        let aliases: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM symbol_aliases").fetch_one(&pool).await.unwrap();
+        assert_eq!(aliases, 0);
+        let symbols: Vec<String> = sqlx::query_scalar("SELECT symbol FROM stocks ORDER BY id").fetch_all(&pool).await.unwrap();
+        assert_eq!(symbols, ["FB", "META"]);
+
+        assert!(merge_stocks(&pool, 2, 2).await.is_err());
+        assert!(merge_stocks(&pool, 2, 99).await.is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::tools::stock_merge::{normalize_cik, normalize_company_name};
+
+/// The stock a newly listed symbol most likely renamed: an active stock with the same CIK, or
+/// without a CIK to go on the same company name, whose own symbol is no longer listed. None when
+/// the symbol is already stored, nothing matches, or more than one stock does.
+pub async fn find_renamed_stock(
+    pool: &SqlitePool,
+    symbol: &str,
+    cik: Option<&str>,
+    company_name: &str,
+    listed_symbols: &HashSet<String>,
+) -> Result<Option<(i64, String)>> {
+    let known: Option<i64> = sqlx::query_scalar("SELECT id FROM stocks WHERE symbol = ?")
+        .bind(symbol)
+        .fetch_optional(pool)
+        .await?;
+    if known.is_some() {
+        return Ok(None);
+    }
+
+    let stocks: Vec<(i64, String, String, Option<String>)> =
+        sqlx::query_as("SELECT id, symbol, company_name, cik FROM stocks WHERE status = 'active'")
+            .fetch_all(pool)
+            .await?;
+    let cik = cik.and_then(normalize_cik);
+    let name = normalize_company_name(company_name);
+    let candidates: Vec<(i64, String)> = stocks
+        .into_iter()
+        .filter(|(_, stored_symbol, _, _)| !listed_symbols.contains(stored_symbol))
+        .filter(|(_, _, stored_name, stored_cik)| match (&cik, stored_cik.as_deref().and_then(normalize_cik)) {
+            (Some(cik), Some(stored_cik)) => *cik == stored_cik,
+            _ => normalize_company_name(stored_name) == name,
+        })
+        .map(|(id, stored_symbol, _, _)| (id, stored_symbol))
+        .collect();
+
+    if candidates.len() > 1 {
+        let symbols: Vec<&str> = candidates.iter().map(|(_, s)| s.as_str()).collect();
+        warn!(%symbol, "{} could be a new ticker for any of {}; not renaming", symbol, symbols.join(", "));
+        return Ok(None);
+    }
+    Ok(candidates.into_iter().next())
+}
+
+/// Move a stock to its new ticker, keeping the old one as an alias that stopped trading on
+/// `effective_until`
+pub async fn record_ticker_change(
+    pool: &SqlitePool,
+    stock_id: i64,
+    old_symbol: &str,
+    new_symbol: &str,
+    effective_until: &str,
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE stocks SET symbol = ? WHERE id = ?")
+        .bind(new_symbol)
+        .bind(stock_id)
+        .execute(&mut *tx)
+        .await?;
+    // A ticker changed back is no longer an alias
+    sqlx::query("DELETE FROM symbol_aliases WHERE symbol = ?")
+        .bind(new_symbol)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT OR REPLACE INTO symbol_aliases (symbol, stock_id, effective_until) VALUES (?, ?, ?)")
+        .bind(old_symbol)
+        .bind(stock_id)
+        .bind(effective_until)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::helpers::resolve_symbol;
+    use crate::tests::fixtures::migrated_pool;
+
+    #[tokio::test]
+    async fn test_ticker_change_is_detected_and_old_symbol_resolves() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            "INSERT INTO stocks (id, symbol, company_name, cik) VALUES
+                (1, 'FB', 'Meta Platforms, Inc.', '0001326801'),
+                (2, 'ANTM', 'Anthem Inc.', NULL),
+                (3, 'AAPL', 'Apple Inc.', '320193')"
+        )
+        .execute(&pool).await.unwrap();
+        let listed: HashSet<String> = ["META", "ELV", "AAPL"].into_iter().map(String::from).collect();
+
+        // Same CIK despite the new name; without a CIK the name has to match
+        let renamed = find_renamed_stock(&pool, "META", Some("1326801"), "Meta Platforms", &listed).await.unwrap();
+        assert_eq!(renamed, Some((1, "FB".to_string())));
+        assert_eq!(find_renamed_stock(&pool, "ELV", None, "Elevance Health", &listed).await.unwrap(), None);
+        assert_eq!(find_renamed_stock(&pool, "AAPL", Some("320193"), "Apple Inc.", &listed).await.unwrap(), None);
+
+        record_ticker_change(&pool, 1, "FB", "META", "2022-06-08").await.unwrap();
+        assert_eq!(resolve_symbol(&pool, "META").await.unwrap(), Some((1, "META".to_string())));
+        assert_eq!(resolve_symbol(&pool, "FB").await.unwrap(), Some((1, "META".to_string())));
+        assert_eq!(resolve_symbol(&pool, "TWTR").await.unwrap(), None);
+
+        // A new listing that takes over the old ticker wins over the alias
+        sqlx::query("INSERT INTO stocks (id, symbol, company_name) VALUES (4, 'FB', 'Some Other Co')")
+            .execute(&pool).await.unwrap();
+        assert_eq!(resolve_symbol(&pool, "FB").await.unwrap(), Some((4, "FB".to_string())));
+    }
+}
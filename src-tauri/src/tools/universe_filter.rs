@@ -0,0 +1,95 @@
+use crate::tools::fx_rates::usd_value_sql;
+
+/// Market cap floor applied by every screener unless overridden
+pub const DEFAULT_MIN_MARKET_CAP: f64 = 50_000_000.0;
+
+/// Average shares traded per day, below which a stock is too illiquid to screen
+pub const DEFAULT_MIN_AVG_DAILY_VOLUME: f64 = 100_000.0;
+
+/// Trading days (daily price rows) averaged for liquidity
+const VOLUME_LOOKBACK_TRADING_DAYS: i64 = 30;
+
+/// Size and liquidity floor shared by all screeners
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniverseFilter {
+    pub min_market_cap: f64,
+    pub min_avg_daily_volume: f64,
+}
+
+impl Default for UniverseFilter {
+    fn default() -> Self {
+        Self {
+            min_market_cap: DEFAULT_MIN_MARKET_CAP,
+            min_avg_daily_volume: DEFAULT_MIN_AVG_DAILY_VOLUME,
+        }
+    }
+}
+
+impl UniverseFilter {
+    /// Screener parameters, falling back to the defaults when not given
+    pub fn new(min_market_cap: Option<f64>, min_avg_daily_volume: Option<f64>) -> Self {
+        Self {
+            min_market_cap: min_market_cap.unwrap_or(DEFAULT_MIN_MARKET_CAP),
+            min_avg_daily_volume: min_avg_daily_volume.unwrap_or(DEFAULT_MIN_AVG_DAILY_VOLUME),
+        }
+    }
+}
+
+/// SQL for a stock's market cap in USD: its latest close × the latest share count on or
+/// before that day from shares_outstanding_history, falling back to the latest annual
+/// balance sheet's, converted at that day's rate. NULL ("unknown") when the stock has no
+/// price, no share count or no known rate. `stock_id_column` must be qualified with its
+/// table or alias so it isn't resolved against the tables inside the subquery.
+pub fn market_cap_sql(stock_id_column: &str) -> String {
+    format!(
+        "(SELECT {usd_cap} FROM daily_prices mp
+          WHERE mp.stock_id = {col} AND mp.close_price > 0
+          ORDER BY mp.date DESC LIMIT 1)",
+        col = stock_id_column,
+        usd_cap = usd_value_sql(&format!("mp.close_price * {}", shares_on_date_sql("mp.stock_id", "mp.date")), "mp.currency", "mp.date")
+    )
+}
+
+/// SQL for a stock's share count on `date_column`: the latest shares_outstanding_history entry
+/// on or before it, else the latest annual balance sheet's. Both columns must be qualified.
+pub fn shares_on_date_sql(stock_id_column: &str, date_column: &str) -> String {
+    format!(
+        "COALESCE(
+            (SELECT h.shares FROM shares_outstanding_history h
+             WHERE h.stock_id = {col} AND h.as_of_date <= {date}
+             ORDER BY h.as_of_date DESC LIMIT 1),
+            (SELECT b.shares_outstanding FROM balance_sheets b
+             WHERE b.stock_id = {col} AND b.period_type = 'Annual'
+               AND b.shares_outstanding > 0 AND b.report_date <= {date}
+             ORDER BY b.report_date DESC LIMIT 1))",
+        col = stock_id_column,
+        date = date_column
+    )
+}
+
+/// Restrict a screening query to stocks above the market cap and average daily volume floors.
+/// Market cap is `market_cap_sql`'s close × shares figure, in USD like the floor; a stock
+/// whose market cap can't be computed is kept and reported as unknown rather than excluded.
+/// Volume is averaged over the last 30 trading days; stocks without volume are excluded
+/// whenever that floor is positive. `stock_id_column` must be qualified with its table or alias.
+pub fn apply_universe_filter(query: &mut String, params: &mut Vec<String>, stock_id_column: &str, filter: &UniverseFilter) {
+    if filter.min_market_cap > 0.0 {
+        query.push_str(&format!(
+            " AND COALESCE({} >= CAST(? AS REAL), 1)",
+            market_cap_sql(stock_id_column)
+        ));
+        params.push(filter.min_market_cap.to_string());
+    }
+
+    if filter.min_avg_daily_volume > 0.0 {
+        query.push_str(&format!(
+            " AND (SELECT AVG(v.volume) FROM (
+                SELECT p.volume FROM daily_prices p
+                WHERE p.stock_id = {col} AND p.volume IS NOT NULL ORDER BY p.date DESC LIMIT {days}
+            ) v) >= CAST(? AS REAL)",
+            col = stock_id_column,
+            days = VOLUME_LOOKBACK_TRADING_DAYS
+        ));
+        params.push(filter.min_avg_daily_volume.to_string());
+    }
+}
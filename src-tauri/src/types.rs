@@ -2,9 +2,75 @@ use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 // Re-export types from other modules for ts-rs generation
-pub use crate::tools::freshness_types::{SystemFreshnessReport, DataFreshnessStatus, FreshnessStatus, RefreshPriority, RefreshRecommendation, ScreeningReadiness};
+pub use crate::tools::freshness_types::{SystemFreshnessReport, DataFreshnessStatus, FreshnessStatus, RefreshPriority, RefreshRecommendation, ScreeningReadiness, ScreenReadiness, StockFilter, FundamentalsRefreshResult};
+pub use crate::tools::stale_stocks::StaleStock;
+pub use crate::tools::price_conflicts::{PriceConflict, PriceConflictKind};
+pub use crate::tools::database_maintenance::{DatabaseMaintenanceReport, TableSize};
 pub use crate::commands::piotroski_screening::{PiotoskiFScoreResult, PiotroskilScreeningCriteria};
 pub use crate::commands::oshaughnessy_screening::{OShaughnessyValueResult, OShaughnessyScreeningCriteria};
+pub use crate::commands::graham_screening::{GrahamScreeningResult, GrahamScreeningCriteria, GrahamScreeningResponse};
+pub use crate::tools::screen_exclusions::{ExclusionCount, ExclusionReason, RatioNote, ScreenExclusion, ScreenResults};
+pub use crate::commands::screening_presets::{ScreenType, ScreeningPreset};
+pub use crate::commands::screen_membership::{ScreenStatus, ScreenMembership};
+pub use crate::commands::screening_snapshots::{
+    SnapshotItem, ScreenSnapshot, SnapshotRankChange, SnapshotDiff, ScreeningHistoryRetention, ScreeningHistoryPruneReport,
+};
+pub use crate::commands::custom_screening::{ScreenMetric, FilterOp, FilterValue, ScreenFilter, SortSpec, CustomScreen, ScreenMetricValue, CustomScreenResult, CustomScreenResponse};
+pub use crate::error::CommandError;
+pub use crate::commands::analysis::{PriceFieldSet, PsSortKey};
+pub use crate::commands::initialization::{InitializationReport, InitializationAction};
+pub use crate::commands::filings::{FilingDiff, UpdatedFundamentals, BalanceSheetIdentityCheck, RawCompanyFacts};
+pub use crate::analysis::risk_metrics::RiskMetrics;
+pub use crate::analysis::price_range::{NearLowStock, Week52Range};
+pub use crate::analysis::drawdown::{DrawdownAnalysis, DrawdownEpisode, MaxDrawdown, PortfolioHolding};
+pub use crate::analysis::stock_summary::StockSummary;
+pub use crate::analysis::sector_overview::{SectorMetric, SectorOverview};
+pub use crate::analysis::industry_growth::IndustryGrowth;
+pub use crate::analysis::benchmark::{BenchmarkHistory, BenchmarkPoint, RelativePerformance, RelativeStrengthPoint};
+pub use crate::analysis::growth_consistency::{AnnualRevenueGrowth, GrowthConsistency, SkippedGrowthPeriod};
+pub use crate::analysis::sector_relative::{ValuationMetric, SectorRelativeValuation};
+pub use crate::analysis::metric_distribution::MetricDistribution;
+pub use crate::analysis::leverage::{LeverageMetrics, LeverageNote, LeverageYear, LowLeverageCriteria, LowLeverageStock};
+pub use crate::analysis::liquidity::{LiquidityRatios, LiquidityYear};
+pub use crate::analysis::free_cash_flow::FcfYieldStock;
+pub use crate::analysis::accruals::{AccrualHistory, AccrualYear};
+pub use crate::analysis::watchlist_snapshot::WatchlistSnapshotRow;
+pub use crate::analysis::quality::{
+    GrossProfitabilityHistory, GrossProfitabilityRanking, GrossProfitabilityYear, QualityCompounder, QualityCompounderCriteria,
+    QualityMetrics, QualityYear, RoicHistory, RoicYear,
+};
+pub use crate::analysis::piotroski_history::{PiotroskiHistory, PiotroskiYear};
+pub use crate::analysis::dividend_growth::{DividendGrower, DividendHistory, DividendYear};
+pub use crate::tools::dividends::DividendRecord;
+pub use crate::analysis::shareholder_yield::ShareholderYield;
+pub use crate::analysis::total_return::TotalReturn;
+pub use crate::analysis::momentum::{MomentumCriteria, MomentumRanking, MomentumRankings};
+pub use crate::analysis::valuation_history::{ValuationMetricVsHistory, ValuationVsHistory};
+pub use crate::logging::LogEntry;
+pub use crate::analysis::ratio_provenance::{ProvenanceRatio, ProvenanceInput, RatioProvenance};
+pub use crate::analysis::valuation_ratios::DailyValuationRatio;
+pub use crate::tools::freshness_policy::{FreshnessPolicy, FreshnessThreshold};
+pub use crate::commands::settings::AppSettings;
+pub use crate::tools::refresh_scheduler::RefreshSchedule;
+pub use crate::tools::http_api::{HttpApiSettings, HttpApiStatus};
+pub use crate::tools::notifications::{Notification, NotificationKind, NotificationPreferences};
+pub use crate::tools::refresh_estimates::{DurationRange, RefreshDurationEstimates};
+pub use crate::tools::refresh_runs::{RefreshRunDetails, RefreshRunError, RefreshRunSummary};
+pub use crate::tools::data_refresh_orchestrator::RefreshProgressEvent;
+pub use crate::tools::ratio_recompute::RatioRecomputeProgress;
+pub use crate::tools::data_coverage::{CoverageFlag, CoverageGranularity, CoverageHeatmap, PeriodCoverageTotals, StockCoverageRow};
+pub use crate::tools::company_profiles::{CompanyProfile, ProfileField};
+pub use crate::tools::stock_archive::StockArchiveResult;
+pub use crate::tools::stock_merge::{DuplicateMatch, DuplicateStock, DuplicateStockGroup, StockMergeResult};
+pub use crate::tools::shares_history::ShareCountDiscontinuity;
+pub use crate::tools::filing_reactions::FilingReaction;
+pub use crate::tools::query_cache::CacheStats;
+pub use crate::commands::quotes::{LiveQuotes, QuoteFailure};
+pub use crate::models::{FundamentalData, IntradayInterval, IntradayPrice, OptionData, RealTimeQuote};
+pub use crate::tools::intraday_prices::{IntradayCollectionResult, IntradayDownsampleResult};
+pub use crate::tools::quote_polling::QuoteUpdate;
+pub use crate::tools::option_chains::{CoveredCallCandidate, OptionChainSnapshot};
+pub use crate::commands::financials::{FinancialStatementType, FinancialStatementView, FinancialLineItem, FilingProvenance};
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
@@ -78,6 +144,14 @@ mod ts_bindings_export_tests {
         RefreshPriority::export().unwrap();
         RefreshRecommendation::export().unwrap();
         ScreeningReadiness::export().unwrap();
+        ScreenReadiness::export().unwrap();
+        StockFilter::export().unwrap();
+        FundamentalsRefreshResult::export().unwrap();
+        StaleStock::export().unwrap();
+        PriceConflictKind::export().unwrap();
+        PriceConflict::export().unwrap();
+        TableSize::export().unwrap();
+        DatabaseMaintenanceReport::export().unwrap();
 
         // Piotroski F-Score types
         PiotoskiFScoreResult::export().unwrap();
@@ -86,6 +160,240 @@ mod ts_bindings_export_tests {
         // O'Shaughnessy Value Composite types
         OShaughnessyValueResult::export().unwrap();
         OShaughnessyScreeningCriteria::export().unwrap();
+
+        // Graham value screening types
+        GrahamScreeningResult::export().unwrap();
+        GrahamScreeningCriteria::export().unwrap();
+        GrahamScreeningResponse::export().unwrap();
+
+        // Sign policy exclusions shared by the screens
+        ExclusionReason::export().unwrap();
+        ExclusionCount::export().unwrap();
+        ScreenExclusion::export().unwrap();
+        RatioNote::export().unwrap();
+        ScreenResults::<()>::export().unwrap();
+
+        // Custom screen builder
+        ScreenMetric::export().unwrap();
+        FilterOp::export().unwrap();
+        FilterValue::export().unwrap();
+        ScreenFilter::export().unwrap();
+        SortSpec::export().unwrap();
+        CustomScreen::export().unwrap();
+        ScreenMetricValue::export().unwrap();
+        CustomScreenResult::export().unwrap();
+        CustomScreenResponse::export().unwrap();
+
+        // Saved screener presets
+        ScreenType::export().unwrap();
+        ScreeningPreset::export().unwrap();
+
+        // Per-stock screen badges
+        ScreenStatus::export().unwrap();
+        ScreenMembership::export().unwrap();
+
+        // Command error type
+        CommandError::export().unwrap();
+
+        // P/S screener sort options
+        PsSortKey::export().unwrap();
+
+        // Price history column selection
+        PriceFieldSet::export().unwrap();
+
+        // Onboarding checklist types
+        InitializationReport::export().unwrap();
+        InitializationAction::export().unwrap();
+
+        // SEC filing diagnostics
+        FilingDiff::export().unwrap();
+        UpdatedFundamentals::export().unwrap();
+        BalanceSheetIdentityCheck::export().unwrap();
+        RawCompanyFacts::export().unwrap();
+
+        // Risk metrics
+        RiskMetrics::export().unwrap();
+
+        // 52-week range
+        Week52Range::export().unwrap();
+        NearLowStock::export().unwrap();
+
+        // Max drawdown
+        MaxDrawdown::export().unwrap();
+        DrawdownEpisode::export().unwrap();
+        DrawdownAnalysis::export().unwrap();
+        PortfolioHolding::export().unwrap();
+
+        // Stock detail header
+        StockSummary::export().unwrap();
+
+        // Sector heatmap
+        SectorMetric::export().unwrap();
+        SectorOverview::export().unwrap();
+        IndustryGrowth::export().unwrap();
+        BenchmarkPoint::export().unwrap();
+        BenchmarkHistory::export().unwrap();
+        RelativeStrengthPoint::export().unwrap();
+        RelativePerformance::export().unwrap();
+
+        // Revenue growth consistency
+        AnnualRevenueGrowth::export().unwrap();
+        SkippedGrowthPeriod::export().unwrap();
+        GrowthConsistency::export().unwrap();
+
+        // Sector-relative valuation
+        ValuationMetric::export().unwrap();
+        SectorRelativeValuation::export().unwrap();
+        MetricDistribution::export().unwrap();
+
+        // Debt and leverage
+        LeverageMetrics::export().unwrap();
+        LiquidityYear::export().unwrap();
+        LiquidityRatios::export().unwrap();
+        LeverageYear::export().unwrap();
+        LeverageNote::export().unwrap();
+        LowLeverageCriteria::export().unwrap();
+        LowLeverageStock::export().unwrap();
+
+        // Free cash flow yield screen
+        FcfYieldStock::export().unwrap();
+
+        // Quality metrics and compounders screen
+        QualityYear::export().unwrap();
+        QualityMetrics::export().unwrap();
+        QualityCompounderCriteria::export().unwrap();
+        QualityCompounder::export().unwrap();
+        RoicYear::export().unwrap();
+        RoicHistory::export().unwrap();
+        GrossProfitabilityYear::export().unwrap();
+        GrossProfitabilityHistory::export().unwrap();
+        GrossProfitabilityRanking::export().unwrap();
+        AccrualYear::export().unwrap();
+        AccrualHistory::export().unwrap();
+
+        // Watchlist dashboard
+        WatchlistSnapshotRow::export().unwrap();
+
+        // Valuation against its own 5-year history
+        ValuationMetricVsHistory::export().unwrap();
+        ValuationVsHistory::export().unwrap();
+
+        // Piotroski F-Score history
+        PiotroskiYear::export().unwrap();
+        PiotroskiHistory::export().unwrap();
+
+        // Dividend history and growers screen
+        DividendRecord::export().unwrap();
+        DividendYear::export().unwrap();
+        DividendHistory::export().unwrap();
+        DividendGrower::export().unwrap();
+
+        // Shareholder yield
+        ShareholderYield::export().unwrap();
+
+        // Total return
+        TotalReturn::export().unwrap();
+
+        // Momentum rankings
+        MomentumCriteria::export().unwrap();
+        MomentumRanking::export().unwrap();
+        MomentumRankings::export().unwrap();
+
+        // Activity log
+        LogEntry::export().unwrap();
+
+        // Ratio audit trail
+        ProvenanceRatio::export().unwrap();
+        ProvenanceInput::export().unwrap();
+        RatioProvenance::export().unwrap();
+
+        // Stored daily valuation series
+        DailyValuationRatio::export().unwrap();
+
+        // Settings and freshness thresholds
+        AppSettings::export().unwrap();
+        FreshnessPolicy::export().unwrap();
+        RefreshSchedule::export().unwrap();
+        HttpApiSettings::export().unwrap();
+        HttpApiStatus::export().unwrap();
+        NotificationPreferences::export().unwrap();
+        NotificationKind::export().unwrap();
+        Notification::export().unwrap();
+        FreshnessThreshold::export().unwrap();
+
+        // Refresh duration estimates
+        RefreshDurationEstimates::export().unwrap();
+        DurationRange::export().unwrap();
+
+        // Refresh run history
+        RefreshRunSummary::export().unwrap();
+        RefreshProgressEvent::export().unwrap();
+        RefreshRunDetails::export().unwrap();
+        RefreshRunError::export().unwrap();
+
+        // Bulk ratio recompute
+        RatioRecomputeProgress::export().unwrap();
+
+        // Data coverage heatmap
+        CoverageHeatmap::export().unwrap();
+        CoverageGranularity::export().unwrap();
+        CoverageFlag::export().unwrap();
+        StockCoverageRow::export().unwrap();
+        PeriodCoverageTotals::export().unwrap();
+
+        // Company profiles
+        CompanyProfile::export().unwrap();
+        ProfileField::export().unwrap();
+
+        // Stock archiving
+        StockArchiveResult::export().unwrap();
+        DuplicateMatch::export().unwrap();
+        DuplicateStock::export().unwrap();
+        DuplicateStockGroup::export().unwrap();
+        StockMergeResult::export().unwrap();
+
+        // Share count history checks
+        ShareCountDiscontinuity::export().unwrap();
+
+        // Price reactions to filings
+        FilingReaction::export().unwrap();
+
+        // Query cache counters
+        CacheStats::export().unwrap();
+
+        // Live quotes
+        RealTimeQuote::export().unwrap();
+        FundamentalData::export().unwrap();
+        QuoteFailure::export().unwrap();
+        LiveQuotes::export().unwrap();
+
+        // Intraday bars
+        IntradayInterval::export().unwrap();
+        IntradayPrice::export().unwrap();
+        IntradayCollectionResult::export().unwrap();
+        IntradayDownsampleResult::export().unwrap();
+
+        // Watchlist quote polling events
+        QuoteUpdate::export().unwrap();
+
+        // Option chain snapshots and the covered-call screen
+        OptionData::export().unwrap();
+        OptionChainSnapshot::export().unwrap();
+        CoveredCallCandidate::export().unwrap();
+
+        // Financial statement viewer
+        FinancialStatementType::export().unwrap();
+        FinancialStatementView::export().unwrap();
+        FinancialLineItem::export().unwrap();
+        FilingProvenance::export().unwrap();
+
+        // Screen run history
+        SnapshotItem::export().unwrap();
+        ScreenSnapshot::export().unwrap();
+        SnapshotRankChange::export().unwrap();
+        SnapshotDiff::export().unwrap();
+        ScreeningHistoryRetention::export().unwrap();
+        ScreeningHistoryPruneReport::export().unwrap();
     }
 }
 
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AccrualYear } from "./AccrualYear";
+
+export interface AccrualHistory { stock_id: bigint, symbol: string, latest: AccrualYear | null, history: Array<AccrualYear>, }
\ No newline at end of file
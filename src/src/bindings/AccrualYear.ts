@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface AccrualYear { fiscal_year: number, report_date: string, net_income: number | null, operating_cash_flow: number | null, total_assets: number | null, accrual_ratio: number | null, }
\ No newline at end of file
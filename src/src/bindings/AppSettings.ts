@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FreshnessPolicy } from "./FreshnessPolicy";
+import type { HttpApiSettings } from "./HttpApiSettings";
+import type { NotificationPreferences } from "./NotificationPreferences";
+import type { ScreeningHistoryRetention } from "./ScreeningHistoryRetention";
+
+export interface AppSettings { freshness_policy: FreshnessPolicy, notification_preferences: NotificationPreferences, http_api: HttpApiSettings, screening_history: ScreeningHistoryRetention, }
\ No newline at end of file
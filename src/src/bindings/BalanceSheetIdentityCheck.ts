@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface BalanceSheetIdentityCheck { fiscal_year: number, report_date: string, total_assets: number | null, total_liabilities: number | null, total_equity: number | null, discrepancy_pct: number | null, imbalanced: boolean, }
\ No newline at end of file
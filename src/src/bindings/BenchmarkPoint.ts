@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface BenchmarkPoint { date: string, value: number, daily_return: number | null, constituents: number | null, }
\ No newline at end of file
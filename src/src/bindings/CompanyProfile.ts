@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProfileField } from "./ProfileField";
+
+export interface CompanyProfile { stock_id: bigint, symbol: string, company_name: string, sector: string | null, industry: string | null, market_cap: number | null, description: string | null, website: string | null, phone: string | null, headquarters: string | null, state_of_incorporation: string | null, sic_code: string | null, sic_description: string | null, employees: number | null, fiscal_year_end_month: number | null, fiscal_year_end_changed: boolean, overridden_fields: Array<ProfileField>, sec_updated_at: string | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OptionData } from "./OptionData";
+
+export interface CoveredCallCandidate { stock_id: bigint, symbol: string, current_price: number, price_date: string, contract: OptionData, days_to_expiry: bigint, premium_yield_pct: number, annualized_premium_yield_pct: number, upside_to_strike_pct: number, is_stale: boolean, }
\ No newline at end of file
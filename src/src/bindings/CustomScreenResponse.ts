@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CustomScreenResult } from "./CustomScreenResult";
+import type { ExclusionCount } from "./ExclusionCount";
+import type { ScreenExclusion } from "./ScreenExclusion";
+
+export interface CustomScreenResponse { results: Array<CustomScreenResult>, excluded: Array<ScreenExclusion>, exclusions: Array<ExclusionCount>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScreenMetricValue } from "./ScreenMetricValue";
+
+export interface CustomScreenResult { stock_id: bigint, symbol: string, company_name: string, sector: string | null, values: Array<ScreenMetricValue>, }
\ No newline at end of file
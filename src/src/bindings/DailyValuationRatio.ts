@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface DailyValuationRatio { stock_id: bigint, symbol: string, date: string, price: number | null, market_cap: number | null, enterprise_value: number | null, revenue_ttm: number | null, net_income_ttm: number | null, ps_ratio_ttm: number | null, pe_ratio_ttm: number | null, evs_ratio_ttm: number | null, free_cash_flow: number | null, fcf_yield: number | null, is_estimated: boolean, data_completeness_score: number, last_financial_update: string | null, }
\ No newline at end of file
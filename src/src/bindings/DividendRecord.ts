@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface DividendRecord { period_type: string, period_start: string | null, period_end: string, fiscal_year: number | null, amount_per_share: number, special_amount: number, is_special: boolean, source: string, accession_number: string | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface DividendYear { fiscal_year: number, period_end: string, regular_per_share: number, special_per_share: number, growth_pct: number | null, payout_ratio_earnings: number | null, payout_ratio_fcf: number | null, source: string, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DrawdownEpisode } from "./DrawdownEpisode";
+
+export interface DrawdownAnalysis { start_date: string, end_date: string, max_drawdown: DrawdownEpisode | null, current_drawdown_pct: number, current_peak_date: string, current_peak_value: number, started_in_drawdown: boolean, episodes: Array<DrawdownEpisode>, recovered_episodes: number, avg_days_to_recover: number | null, max_days_to_recover: bigint | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface DrawdownEpisode { peak_date: string, peak_value: number, trough_date: string, trough_value: number, depth_pct: number, recovery_date: string | null, days_to_trough: bigint, days_to_recover: bigint | null, }
\ No newline at end of file
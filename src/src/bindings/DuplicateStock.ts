@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface DuplicateStock { stock_id: bigint, symbol: string, company_name: string, cik: string | null, status: string, price_rows: bigint, latest_price_date: string | null, }
\ No newline at end of file
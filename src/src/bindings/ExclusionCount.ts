@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExclusionReason } from "./ExclusionReason";
+
+export interface ExclusionCount { reason: ExclusionReason, count: number, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface FilingDiff { symbol: string, cik: string, missing_locally: Array<string>, missing_at_sec: Array<string>, matched: Array<string>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface FilingProvenance { fiscal_year: number, report_date: string | null, accession_number: string | null, filed_date: string | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface FilingReaction { sec_filing_id: bigint, accession_number: string, form_type: string, filed_date: string, fiscal_year: number, fiscal_period: string | null, report_date: string, reaction_date: string | null, reaction_close: number | null, return_5d_pct: number | null, return_30d_pct: number | null, }
\ No newline at end of file
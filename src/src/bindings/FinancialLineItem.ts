@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface FinancialLineItem { name: string, values: Array<number | null>, yoy_change: Array<number | null>, yoy_percent_change: Array<number | null>, }
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FilingProvenance } from "./FilingProvenance";
+import type { FinancialLineItem } from "./FinancialLineItem";
+import type { FinancialStatementType } from "./FinancialStatementType";
+
+export interface FinancialStatementView { stock_id: bigint, statement_type: FinancialStatementType, fiscal_years: Array<number>, line_items: Array<FinancialLineItem>, provenance: Array<FilingProvenance>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FreshnessThreshold } from "./FreshnessThreshold";
+
+export interface FreshnessPolicy { market_data: FreshnessThreshold, financial_data: FreshnessThreshold, calculated_ratios: FreshnessThreshold, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GrossProfitabilityYear } from "./GrossProfitabilityYear";
+
+export interface GrossProfitabilityHistory { stock_id: bigint, symbol: string, latest: GrossProfitabilityYear | null, history: Array<GrossProfitabilityYear>, }
\ No newline at end of file
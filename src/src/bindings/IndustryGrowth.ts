@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface IndustryGrowth { industry: string, sector: string | null, rank: number, member_count: number, stocks_with_growth: number, total_market_cap: number, weighted_revenue_cagr: number, equal_weight_revenue_cagr: number, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IntradayInterval } from "./IntradayInterval";
+
+export interface IntradayPrice { id: bigint | null, stock_id: bigint, datetime: string, trading_date: string, interval_type: IntradayInterval, open_price: number, high_price: number, low_price: number, close_price: number, volume: bigint | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LeverageNote } from "./LeverageNote";
+
+export interface LeverageYear { fiscal_year: number, report_date: string, currency: string, total_debt: number | null, total_equity: number | null, cash: number | null, net_debt: number | null, ebitda: number | null, debt_to_equity: number | null, debt_to_equity_note: LeverageNote | null, net_debt_to_ebitda: number | null, net_debt_to_ebitda_note: LeverageNote | null, interest_coverage: number | null, interest_coverage_note: LeverageNote | null, }
\ No newline at end of file
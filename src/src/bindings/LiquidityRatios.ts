@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LiquidityYear } from "./LiquidityYear";
+
+export interface LiquidityRatios { stock_id: bigint, symbol: string, years: Array<LiquidityYear>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface MomentumCriteria { as_of: string | null, min_price: number | null, min_trading_days: number | null, trending_value: boolean | null, max_value_percentile: number | null, limit: number | null, }
\ No newline at end of file
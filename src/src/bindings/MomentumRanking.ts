@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface MomentumRanking { stock_id: bigint, symbol: string, sector: string | null, latest_date: string, latest_close: number, return_6m_pct: number, return_12_1m_pct: number, percentile_6m: number, percentile_12_1m: number, momentum_percentile: number, value_percentile: number | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface NearLowStock { stock_id: bigint, symbol: string, sector: string | null, as_of_date: string, latest_close: number, low_52_week: number, high_52_week: number | null, pct_from_low: number, pct_from_high: number | null, limited_history: boolean, f_score: number | null, free_cash_flow: number | null, }
\ No newline at end of file
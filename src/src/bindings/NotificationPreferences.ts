@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface NotificationPreferences { refresh_completed: boolean, refresh_failed: boolean, alert_triggered: boolean, }
\ No newline at end of file
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export interface OShaughnessyScreeningCriteria { max_composite_percentile: number | null, max_ps_ratio: number | null, max_evs_ratio: number | null, min_market_cap: number | null, sectors: Array<string> | null, passes_screening_only: boolean | null, }
\ No newline at end of file
+export interface OShaughnessyScreeningCriteria { max_composite_percentile: number | null, max_ps_ratio: number | null, max_evs_ratio: number | null, min_market_cap: number | null, min_avg_daily_volume: number | null, sectors: Array<string> | null, tags: Array<string> | null, min_consecutive_growth_years: number | null, max_accrual_ratio: number | null, passes_screening_only: boolean | null, }
\ No newline at end of file
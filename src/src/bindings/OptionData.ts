@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface OptionData { id: bigint | null, stock_id: bigint, symbol: string, expiration_date: string, strike_price: number, option_type: string, bid: number | null, ask: number | null, last_price: number | null, volume: bigint | null, open_interest: bigint | null, implied_volatility: number | null, delta: number | null, gamma: number | null, theta: number | null, vega: number | null, rho: number | null, snapshot_time: string, }
\ No newline at end of file
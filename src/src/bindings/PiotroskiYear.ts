@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface PiotroskiYear { fiscal_year: number, report_date: string, criterion_positive_net_income: number, criterion_positive_operating_cash_flow: number, criterion_improving_roa: number, criterion_cash_flow_quality: number, criterion_decreasing_debt_ratio: number, criterion_improving_current_ratio: number, criterion_no_dilution: number, criterion_improving_net_margin: number, criterion_improving_asset_turnover: number, f_score: number, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ProvenanceInput { name: string, value: number | null, source_table: string, report_date: string, accession_number: string | null, filed_date: string | null, is_estimated: boolean, }
\ No newline at end of file
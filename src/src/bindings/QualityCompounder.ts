@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QualityYear } from "./QualityYear";
+
+export interface QualityCompounder { stock_id: bigint, symbol: string, company_name: string, sector: string | null, latest: QualityYear, min_roic: number, }
\ No newline at end of file
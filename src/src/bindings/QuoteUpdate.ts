@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QuoteFailure } from "./QuoteFailure";
+import type { RealTimeQuote } from "./RealTimeQuote";
+
+export interface QuoteUpdate { watchlist_id: string, quotes: Array<RealTimeQuote>, failures: Array<QuoteFailure>, market_open: boolean, next_poll_secs: bigint, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProvenanceInput } from "./ProvenanceInput";
+import type { ProvenanceRatio } from "./ProvenanceRatio";
+
+export interface RatioProvenance { stock_id: bigint, ratio: ProvenanceRatio, as_of_date: string, value: number | null, price_date: string | null, close_price: number | null, market_cap: number | null, market_cap_source: string | null, shares_outstanding: ProvenanceInput | null, denominator: ProvenanceInput | null, is_estimated: boolean, formula: string, }
\ No newline at end of file
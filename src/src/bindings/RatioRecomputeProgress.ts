@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RatioRecomputeProgress { session_id: string, total_stocks: number, processed_stocks: number, percent_complete: number, status: string, rows_written: bigint, error_details: string | null, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RealTimeQuote { id: bigint | null, stock_id: bigint, symbol: string, timestamp: string, bid_price: number | null, bid_size: number | null, ask_price: number | null, ask_size: number | null, last_price: number, last_size: number | null, volume: bigint | null, change_amount: number | null, change_percent: number | null, day_high: number | null, day_low: number | null, }
\ No newline at end of file
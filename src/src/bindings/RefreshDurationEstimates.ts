@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DurationRange } from "./DurationRange";
+
+export interface RefreshDurationEstimates { market: DurationRange, financials: DurationRange, ratios: DurationRange, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RefreshRunError } from "./RefreshRunError";
+import type { RefreshRunSummary } from "./RefreshRunSummary";
+
+export interface RefreshRunDetails { run: RefreshRunSummary, errors: Array<RefreshRunError>, }
\ No newline at end of file
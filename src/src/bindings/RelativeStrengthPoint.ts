@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface RelativeStrengthPoint { date: string, stock_value: number, benchmark_value: number, relative_strength: number, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RoicYear } from "./RoicYear";
+
+export interface RoicHistory { stock_id: bigint, symbol: string, latest: RoicYear | null, history: Array<RoicYear>, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExclusionReason } from "./ExclusionReason";
+
+export interface ScreenExclusion { stock_id: bigint, symbol: string, reason: ExclusionReason, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ScreenMetric = "market_cap" | "pe" | "ps" | "pb" | "ev_ebitda" | "revenue_growth_3y" | "fcf_yield" | "piotroski_score" | "debt_to_equity" | "dividend_yield" | "roic" | "sector";
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RatioNote } from "./RatioNote";
+import type { ScreenMetric } from "./ScreenMetric";
+
+export interface ScreenMetricValue { metric: ScreenMetric, value: number | null, note: RatioNote | null, }
\ No newline at end of file
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExclusionCount } from "./ExclusionCount";
+import type { ScreenExclusion } from "./ScreenExclusion";
+
+export interface ScreenResults<T> { results: Array<T>, excluded: Array<ScreenExclusion>, exclusions: Array<ExclusionCount>, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface ScreeningHistoryRetention { prune_after_screening: boolean, keep_all_days: bigint, }
\ No newline at end of file
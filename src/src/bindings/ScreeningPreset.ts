@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScreenType } from "./ScreenType";
+
+export interface ScreeningPreset { screen_type: ScreenType, name: string, criteria: Record<string, unknown>, schema_version: number, unknown_fields: Array<string>, error: string | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SectorMetric } from "./SectorMetric";
+
+export interface SectorOverview { name: string, stock_count: bigint, total_market_cap: number | null, median_market_cap: SectorMetric, median_pe: SectorMetric, median_ps: SectorMetric, median_ev_ebitda: SectorMetric, median_revenue_growth: SectorMetric, avg_piotroski_score: SectorMetric, return_1m: SectorMetric, return_1y: SectorMetric, }
\ No newline at end of file
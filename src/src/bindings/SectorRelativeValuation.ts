@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface SectorRelativeValuation { stock_id: bigint, symbol: string, sector: string, value: number, sector_mean: number, sector_stddev: number, z_score: number, sector_sample_size: bigint, }
\ No newline at end of file
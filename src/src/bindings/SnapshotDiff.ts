@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SnapshotItem } from "./SnapshotItem";
+import type { SnapshotRankChange } from "./SnapshotRankChange";
+
+export interface SnapshotDiff { from_snapshot_id: bigint, to_snapshot_id: bigint, entered: Array<SnapshotItem>, left: Array<SnapshotItem>, rank_changes: Array<SnapshotRankChange>, }
\ No newline at end of file
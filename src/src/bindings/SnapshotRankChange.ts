@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface SnapshotRankChange { stock_id: bigint, symbol: string, previous_rank: number, current_rank: number, previous_score: number | null, current_score: number | null, }
\ No newline at end of file
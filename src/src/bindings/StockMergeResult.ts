@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface StockMergeResult { survivor_id: bigint, survivor_symbol: string, merged_symbol: string, rows_moved: bigint, rows_dropped: bigint, }
\ No newline at end of file
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface UpdatedFundamentals { stock_id: bigint, symbol: string, statement_types: Array<string>, latest_filed_date: string | null, last_stored_at: string | null, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ValuationMetricVsHistory } from "./ValuationMetricVsHistory";
+
+export interface ValuationVsHistory { stock_id: bigint, symbol: string, as_of_date: string | null, pe: ValuationMetricVsHistory, ps: ValuationMetricVsHistory, pb: ValuationMetricVsHistory, }
\ No newline at end of file
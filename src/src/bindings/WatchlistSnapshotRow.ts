@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export interface WatchlistSnapshotRow { symbol: string, stock_id: bigint | null, company_name: string | null, price_date: string | null, close_price: number | null, day_change: number | null, day_change_pct: number | null, market_cap: number | null, pe_ratio: number | null, ps_ratio: number | null, passes_graham: boolean | null, }
\ No newline at end of file
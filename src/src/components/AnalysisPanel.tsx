@@ -1,6 +1,7 @@
 import { createSignal, onMount, Show } from 'solid-js';
 import type { Stock, PriceData, ValuationRatios, DateRange } from '../utils/types';
 import { analysisAPI } from '../services/api';
+import type { RelativeStrengthPoint } from '../bindings/RelativeStrengthPoint';
 
 interface AnalysisPanelProps {
   stock: Stock;
@@ -12,6 +13,8 @@ export default function AnalysisPanel(props: AnalysisPanelProps) {
   const [dateRange, setDateRange] = createSignal<DateRange | null>(null);
   const [priceHistory, setPriceHistory] = createSignal<PriceData[]>([]);
   const [valuationRatios, setValuationRatios] = createSignal<ValuationRatios | null>(null);
+  const [showRelative, setShowRelative] = createSignal(false);
+  const [relativeByDate, setRelativeByDate] = createSignal<Map<string, RelativeStrengthPoint>>(new Map());
 
   onMount(async () => {
     await loadAnalysisData();
@@ -44,6 +47,24 @@ export default function AnalysisPanel(props: AnalysisPanelProps) {
     }
   };
 
+  // Relative strength against the S&P 500 composite over the same 30 days, loaded on first use
+  const toggleRelative = async (enabled: boolean) => {
+    setShowRelative(enabled);
+    const history = priceHistory();
+    if (!enabled || relativeByDate().size > 0 || history.length === 0) return;
+
+    try {
+      const performance = await analysisAPI.getRelativePerformance(
+        props.stock.symbol,
+        history[0].date,
+        history[history.length - 1].date
+      );
+      setRelativeByDate(new Map(performance.points.map((point) => [point.date, point])));
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to load relative performance');
+    }
+  };
+
   return (
     <div class="p-6 bg-gray-50">
       <div class="mb-4">
@@ -144,7 +165,18 @@ export default function AnalysisPanel(props: AnalysisPanelProps) {
 
           {/* Recent Price Data */}
           <div class="bg-white rounded-lg p-4 shadow-sm md:col-span-2">
-            <h4 class="font-medium text-gray-900 mb-3">Recent Price History (Last 30 Days)</h4>
+            <div class="flex items-center justify-between mb-3">
+              <h4 class="font-medium text-gray-900">Recent Price History (Last 30 Days)</h4>
+              <label class="flex items-center text-sm text-gray-600">
+                <input
+                  type="checkbox"
+                  class="mr-2"
+                  checked={showRelative()}
+                  onChange={(e) => toggleRelative(e.currentTarget.checked)}
+                />
+                Compare to S&P 500
+              </label>
+            </div>
             <Show 
               when={priceHistory().length > 0}
               fallback={<p class="text-sm text-gray-500">No recent price data available</p>}
@@ -159,19 +191,31 @@ export default function AnalysisPanel(props: AnalysisPanelProps) {
                       <th class="px-3 py-2 text-right text-gray-600">Low</th>
                       <th class="px-3 py-2 text-right text-gray-600">Close</th>
                       <th class="px-3 py-2 text-right text-gray-600">Volume</th>
+                      <Show when={showRelative()}>
+                        <th class="px-3 py-2 text-right text-gray-600" title="Stock return over S&P 500 return since the first date, 1.0 = in line">
+                          vs S&P 500
+                        </th>
+                      </Show>
                     </tr>
                   </thead>
                   <tbody class="divide-y divide-gray-200">
                     {priceHistory().slice(-10).map((price) => (
                       <tr class="hover:bg-gray-50">
                         <td class="px-3 py-2 text-gray-900">{price.date}</td>
-                        <td class="px-3 py-2 text-right text-gray-900">${price.open_price.toFixed(2)}</td>
-                        <td class="px-3 py-2 text-right text-gray-900">${price.high_price.toFixed(2)}</td>
-                        <td class="px-3 py-2 text-right text-gray-900">${price.low_price.toFixed(2)}</td>
+                        <td class="px-3 py-2 text-right text-gray-900">${price.open_price!.toFixed(2)}</td>
+                        <td class="px-3 py-2 text-right text-gray-900">${price.high_price!.toFixed(2)}</td>
+                        <td class="px-3 py-2 text-right text-gray-900">${price.low_price!.toFixed(2)}</td>
                         <td class="px-3 py-2 text-right font-medium text-gray-900">${price.close_price.toFixed(2)}</td>
                         <td class="px-3 py-2 text-right text-gray-600">
                           {price.volume ? price.volume.toLocaleString() : 'N/A'}
                         </td>
+                        <Show when={showRelative()}>
+                          <td
+                            class={`px-3 py-2 text-right ${(relativeByDate().get(price.date)?.relative_strength ?? 1) >= 1 ? 'text-green-700' : 'text-red-700'}`}
+                          >
+                            {relativeByDate().get(price.date)?.relative_strength.toFixed(3) ?? 'N/A'}
+                          </td>
+                        </Show>
                       </tr>
                     ))}
                   </tbody>
@@ -1,6 +1,7 @@
 import { Show, For } from 'solid-js';
 import { dataRefreshStore } from '../stores/dataRefreshStore';
 import type { DataFreshnessStatus } from '../bindings';
+import type { ScreeningReadiness } from '../bindings/ScreeningReadiness';
 
 interface DataTypeCardProps {
   title: string;
@@ -108,22 +109,17 @@ function DataTypeCard(props: DataTypeCardProps) {
 }
 
 interface ScreeningReadinessProps {
-  readiness: {
-    garp_screening: boolean;
-    graham_screening: boolean;
-    piotroski_screening: boolean;
-    oshaughnessy_screening: boolean;
-    blocking_issues: string[];
-  } | undefined;
+  readiness: ScreeningReadiness | undefined;
 }
 
 function ScreeningReadinessIndicator(props: ScreeningReadinessProps) {
   const features = [
-    { key: 'garp_screening', name: 'GARP Screening', icon: '🎯' },
-    { key: 'graham_screening', name: 'Graham Screening', icon: '💎' },
+    { key: 'valuation_analysis', name: 'Valuation Analysis', icon: '🎯' },
     { key: 'piotroski_screening', name: 'Piotroski Screening', icon: '📊' },
     { key: 'oshaughnessy_screening', name: "O'Shaughnessy Screening", icon: '📈' },
-  ];
+    { key: 'graham_screening', name: 'Graham Screening', icon: '🏛️' },
+    { key: 'ps_screening', name: 'P/S Screening', icon: '💹' },
+  ] as const;
 
   return (
     <div class="bg-gray-50 rounded-lg p-6">
@@ -135,7 +131,7 @@ function ScreeningReadinessIndicator(props: ScreeningReadinessProps) {
       <div class="grid grid-cols-1 sm:grid-cols-2 gap-3 mb-4">
         <For each={features}>
           {(feature) => {
-            const isReady = props.readiness?.[feature.key as keyof typeof props.readiness] as boolean;
+            const isReady = props.readiness?.[feature.key]?.ready ?? false;
             return (
               <div class="flex items-center justify-between p-3 bg-white rounded-lg border">
                 <div class="flex items-center">
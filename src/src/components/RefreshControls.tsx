@@ -5,6 +5,7 @@ interface RefreshButtonProps {
   mode: 'market' | 'financials' | 'ratios';
   title: string;
   duration: string;
+  basis?: string;
   icon: string;
   description: string;
 }
@@ -31,6 +32,9 @@ function RefreshButton(props: RefreshButtonProps) {
           <span class="text-gray-600">Duration:</span>
           <span class="font-medium text-gray-900">{props.duration}</span>
         </div>
+        <Show when={props.basis}>
+          <p class="text-xs text-gray-400 mt-1">{props.basis}</p>
+        </Show>
       </div>
 
       <button
@@ -109,8 +113,8 @@ export default function RefreshControls() {
 
   // Format duration estimates from the backend
   const getDuration = (type: 'market' | 'financials' | 'ratios'): string => {
-    const minutes = durationEstimates?.[type];
-    if (!minutes) return '~estimate loading';
+    const minutes = durationEstimates?.[type]?.expected_minutes;
+    if (minutes === undefined) return '~estimate loading';
     return dataRefreshStore.formatDuration(minutes);
   };
 
@@ -131,6 +135,7 @@ export default function RefreshControls() {
             mode="market"
             title="Market Data"
             duration={getDuration('market')}
+            basis={durationEstimates?.market?.basis}
             icon="📈"
             description="Daily prices, shares, market cap"
           />
@@ -138,6 +143,7 @@ export default function RefreshControls() {
             mode="financials"
             title="Financial Data"
             duration={getDuration('financials')}
+            basis={durationEstimates?.financials?.basis}
             icon="📋"
             description="Income, balance, cash flow statements"
           />
@@ -145,6 +151,7 @@ export default function RefreshControls() {
             mode="ratios"
             title="Calculated Ratios"
             duration={getDuration('ratios')}
+            basis={durationEstimates?.ratios?.basis}
             icon="🧮"
             description="P/E, P/S, screening ratios"
           />
@@ -166,7 +173,7 @@ export default function RefreshControls() {
             type="quick"
             title="Quick Refresh"
             description="Market data + calculated ratios"
-            duration={`~${((durationEstimates?.market || 15) + (durationEstimates?.ratios || 10))} min`}
+            duration={`~${((durationEstimates?.market?.expected_minutes ?? 15) + (durationEstimates?.ratios?.expected_minutes ?? 10))} min`}
             icon="⚡"
             bgColor="bg-blue-600"
             hoverColor="hover:bg-blue-700"
@@ -175,7 +182,7 @@ export default function RefreshControls() {
             type="full"
             title="Full Refresh"
             description="All data types (Market + Financials + Ratios)"
-            duration={`~${((durationEstimates?.market || 15) + (durationEstimates?.financials || 90) + (durationEstimates?.ratios || 10))} min`}
+            duration={`~${((durationEstimates?.market?.expected_minutes ?? 15) + (durationEstimates?.financials?.expected_minutes ?? 90) + (durationEstimates?.ratios?.expected_minutes ?? 10))} min`}
             icon="🔋"
             bgColor="bg-purple-600"
             hoverColor="hover:bg-purple-700"
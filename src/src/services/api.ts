@@ -1,9 +1,107 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type {
   RefreshRequestDto,
   RefreshProgressDto,
   SystemFreshnessReport
 } from '../bindings';
+import type { InitializationReport } from '../bindings/InitializationReport';
+import type { FilingDiff } from '../bindings/FilingDiff';
+import type { UpdatedFundamentals } from '../bindings/UpdatedFundamentals';
+import type { BalanceSheetIdentityCheck } from '../bindings/BalanceSheetIdentityCheck';
+import type { RiskMetrics } from '../bindings/RiskMetrics';
+import type { BenchmarkHistory } from '../bindings/BenchmarkHistory';
+import type { RelativePerformance } from '../bindings/RelativePerformance';
+import type { Week52Range } from '../bindings/Week52Range';
+import type { NearLowStock } from '../bindings/NearLowStock';
+import type { MaxDrawdown } from '../bindings/MaxDrawdown';
+import type { DrawdownAnalysis } from '../bindings/DrawdownAnalysis';
+import type { PortfolioHolding } from '../bindings/PortfolioHolding';
+import type { TotalReturn } from '../bindings/TotalReturn';
+import type { MomentumCriteria } from '../bindings/MomentumCriteria';
+import type { MomentumRankings } from '../bindings/MomentumRankings';
+import type { StockSummary } from '../bindings/StockSummary';
+import type { SectorOverview } from '../bindings/SectorOverview';
+import type { IndustryGrowth } from '../bindings/IndustryGrowth';
+import type { GrowthConsistency } from '../bindings/GrowthConsistency';
+import type { ValuationMetric } from '../bindings/ValuationMetric';
+import type { SectorRelativeValuation } from '../bindings/SectorRelativeValuation';
+import type { MetricDistribution } from '../bindings/MetricDistribution';
+import type { ScreenFilter } from '../bindings/ScreenFilter';
+import type { SortSpec } from '../bindings/SortSpec';
+import type { CompanyProfile } from '../bindings/CompanyProfile';
+import type { StockArchiveResult } from '../bindings/StockArchiveResult';
+import type { DuplicateStockGroup } from '../bindings/DuplicateStockGroup';
+import type { StockMergeResult } from '../bindings/StockMergeResult';
+import type { PriceFieldSet } from '../bindings/PriceFieldSet';
+import type { ShareCountDiscontinuity } from '../bindings/ShareCountDiscontinuity';
+import type { FilingReaction } from '../bindings/FilingReaction';
+import type { CoverageHeatmap } from '../bindings/CoverageHeatmap';
+import type { StockFilter } from '../bindings/StockFilter';
+import type { FundamentalsRefreshResult } from '../bindings/FundamentalsRefreshResult';
+import type { CoverageGranularity } from '../bindings/CoverageGranularity';
+import type { LeverageMetrics } from '../bindings/LeverageMetrics';
+import type { LowLeverageCriteria } from '../bindings/LowLeverageCriteria';
+import type { LowLeverageStock } from '../bindings/LowLeverageStock';
+import type { LiquidityRatios } from '../bindings/LiquidityRatios';
+import type { OShaughnessyValueResult } from '../bindings/OShaughnessyValueResult';
+import type { FcfYieldStock } from '../bindings/FcfYieldStock';
+import type { QualityMetrics } from '../bindings/QualityMetrics';
+import type { RoicHistory } from '../bindings/RoicHistory';
+import type { GrossProfitabilityHistory } from '../bindings/GrossProfitabilityHistory';
+import type { GrossProfitabilityRanking } from '../bindings/GrossProfitabilityRanking';
+import type { AccrualHistory } from '../bindings/AccrualHistory';
+import type { WatchlistSnapshotRow } from '../bindings/WatchlistSnapshotRow';
+import type { ValuationVsHistory } from '../bindings/ValuationVsHistory';
+import type { PiotroskiHistory } from '../bindings/PiotroskiHistory';
+import type { QualityCompounderCriteria } from '../bindings/QualityCompounderCriteria';
+import type { QualityCompounder } from '../bindings/QualityCompounder';
+import type { DividendHistory } from '../bindings/DividendHistory';
+import type { DividendGrower } from '../bindings/DividendGrower';
+import type { ShareholderYield } from '../bindings/ShareholderYield';
+import type { RawCompanyFacts } from '../bindings/RawCompanyFacts';
+import type { GrahamScreeningCriteria } from '../bindings/GrahamScreeningCriteria';
+import type { GrahamScreeningResponse } from '../bindings/GrahamScreeningResponse';
+import type { ScreenResults } from '../bindings/ScreenResults';
+import type { PsSortKey } from '../bindings/PsSortKey';
+import type { ProfileField } from '../bindings/ProfileField';
+import type { CustomScreen } from '../bindings/CustomScreen';
+import type { CustomScreenResponse } from '../bindings/CustomScreenResponse';
+import type { ScreenMembership } from '../bindings/ScreenMembership';
+import type { ScreenType } from '../bindings/ScreenType';
+import type { ScreeningPreset } from '../bindings/ScreeningPreset';
+import type { ScreenSnapshot } from '../bindings/ScreenSnapshot';
+import type { SnapshotDiff } from '../bindings/SnapshotDiff';
+import type { ScreeningHistoryPruneReport } from '../bindings/ScreeningHistoryPruneReport';
+import type { ProvenanceRatio } from '../bindings/ProvenanceRatio';
+import type { RatioProvenance } from '../bindings/RatioProvenance';
+import type { DailyValuationRatio } from '../bindings/DailyValuationRatio';
+import type { AppSettings } from '../bindings/AppSettings';
+import type { DatabaseMaintenanceReport } from '../bindings/DatabaseMaintenanceReport';
+import type { RefreshSchedule } from '../bindings/RefreshSchedule';
+import type { HttpApiStatus } from '../bindings/HttpApiStatus';
+import type { RefreshProgressEvent } from '../bindings/RefreshProgressEvent';
+import type { Notification } from '../bindings/Notification';
+import type { LogEntry } from '../bindings/LogEntry';
+import type { ScreeningReadiness } from '../bindings/ScreeningReadiness';
+import type { StaleStock } from '../bindings/StaleStock';
+import type { PriceConflict } from '../bindings/PriceConflict';
+import type { RefreshDurationEstimates } from '../bindings/RefreshDurationEstimates';
+import type { RefreshRunSummary } from '../bindings/RefreshRunSummary';
+import type { RefreshRunDetails } from '../bindings/RefreshRunDetails';
+import type { RatioRecomputeProgress } from '../bindings/RatioRecomputeProgress';
+import type { CacheStats } from '../bindings/CacheStats';
+import type { LiveQuotes } from '../bindings/LiveQuotes';
+import type { IntradayInterval } from '../bindings/IntradayInterval';
+import type { IntradayPrice } from '../bindings/IntradayPrice';
+import type { OptionChainSnapshot } from '../bindings/OptionChainSnapshot';
+import type { CoveredCallCandidate } from '../bindings/CoveredCallCandidate';
+import type { IntradayCollectionResult } from '../bindings/IntradayCollectionResult';
+import type { IntradayDownsampleResult } from '../bindings/IntradayDownsampleResult';
+import type { RealTimeQuote } from '../bindings/RealTimeQuote';
+import type { FundamentalData } from '../bindings/FundamentalData';
+import type { FinancialStatementType } from '../bindings/FinancialStatementType';
+import type { FinancialStatementView } from '../bindings/FinancialStatementView';
 import type {
   Stock,
   PriceData,
@@ -13,8 +111,7 @@ import type {
   ValueRecommendation,
   DatabaseStats,
   InitializationStatus,
-  RefreshResult,
-  RefreshDurationEstimates
+  RefreshResult
 } from '../utils/types';
 
 /**
@@ -42,6 +139,115 @@ export const stockAPI = {
   // Get S&P 500 symbols
   async getSp500Symbols(): Promise<string[]> {
     return await invoke('get_sp500_symbols');
+  },
+
+  // Get a stock's company profile with manual overrides applied
+  async getCompanyProfile(stockId: number): Promise<CompanyProfile> {
+    return await invoke('get_company_profile', { stockId });
+  },
+
+  // Override a company profile field by hand; null clears the override
+  async setCompanyProfileOverride(stockId: number, field: ProfileField, value: string | null): Promise<CompanyProfile> {
+    return await invoke('set_company_profile_override', { stockId, field, value });
+  },
+
+  // Mark a stock delisted, optionally moving its data to the archive tables or purging it for good
+  async archiveStock(symbol: string, moveData?: boolean, purge?: boolean): Promise<StockArchiveResult> {
+    return await invoke('archive_stock', { symbol, moveData, purge });
+  },
+
+  // Reactivate an archived stock and move its archived rows back
+  async restoreStock(symbol: string): Promise<StockArchiveResult> {
+    return await invoke('restore_stock', { symbol });
+  },
+
+  // List stocks sharing a CIK or company name, for review before merging
+  async findDuplicateStocks(): Promise<DuplicateStockGroup[]> {
+    return await invoke('find_duplicate_stocks');
+  },
+
+  // Merge a duplicate stock into the survivor; its symbol stays searchable as an alias
+  async mergeStocks(survivorId: number, duplicateId: number): Promise<StockMergeResult> {
+    return await invoke('merge_stocks', { survivorId, duplicateId });
+  },
+
+  // Add a tag to a stock; returns the stock's tags
+  async addStockTag(stockId: number, tag: string): Promise<string[]> {
+    return await invoke('add_stock_tag', { stockId, tag });
+  },
+
+  // Remove a tag from a stock; returns the stock's tags
+  async removeStockTag(stockId: number, tag: string): Promise<string[]> {
+    return await invoke('remove_stock_tag', { stockId, tag });
+  },
+
+  // Get the tags on a stock
+  async getTagsForStock(stockId: number): Promise<string[]> {
+    return await invoke('get_tags_for_stock', { stockId });
+  },
+
+  // Get stocks carrying a tag
+  async getStocksByTag(tag: string): Promise<Stock[]> {
+    return await invoke('get_stocks_by_tag', { tag });
+  },
+
+  // Get live Schwab quotes; persist also saves them to real_time_quotes
+  async getLiveQuotes(symbols: string[], persist = false): Promise<LiveQuotes> {
+    return await invoke('get_live_quotes', { symbols, persist });
+  },
+
+  // Get a symbol's fundamentals from Alpha Vantage to cross-check the SEC figures
+  async getProviderFundamentals(symbol: string): Promise<FundamentalData> {
+    return await invoke('get_provider_fundamentals', { symbol });
+  },
+
+  // Import an index's daily closes from Schwab (e.g. $SPX) as a benchmark series; returns days written
+  async importBenchmarkIndex(symbol: string, benchmark?: string, startDate?: string): Promise<number> {
+    return await invoke('import_benchmark_index', { symbol, benchmark, startDate });
+  },
+
+  // Collect a session's minute bars for watchlist stocks (today's, 1-minute by default)
+  async collectIntradayPrices(date?: string, interval?: IntradayInterval): Promise<IntradayCollectionResult> {
+    return await invoke('collect_intraday_prices', { date, interval });
+  },
+
+  // Get a stock's stored bars of one interval for one New York session
+  async getIntradayPrices(stockId: number, date: string, interval: IntradayInterval): Promise<IntradayPrice[]> {
+    return await invoke('get_intraday_prices', { stockId, date, interval });
+  },
+
+  // Fold 1-minute bars older than the retention window into 30-minute bars
+  async downsampleIntradayPrices(retentionDays?: number): Promise<IntradayDownsampleResult> {
+    return await invoke('downsample_intraday_prices', { retentionDays });
+  },
+
+  // Poll quotes for a tagged watchlist; updates arrive as "quote-update" events. Returns the symbol count
+  async startQuotePolling(watchlistId: string, intervalSecs: number): Promise<number> {
+    return await invoke('start_quote_polling', { watchlistId, intervalSecs });
+  },
+
+  // Stop quote polling; false if it wasn't running
+  async stopQuotePolling(): Promise<boolean> {
+    return await invoke('stop_quote_polling');
+  },
+
+  // Get the latest polled quote for each symbol
+  async getPolledQuotes(): Promise<RealTimeQuote[]> {
+    return await invoke('get_polled_quotes');
+  },
+
+  // Fetch and store a snapshot of a stock's monthly option expirations
+  async fetchOptionChain(symbol: string, fromDate?: string, toDate?: string): Promise<OptionChainSnapshot> {
+    return await invoke('fetch_option_chain', { symbol, fromDate, toDate });
+  },
+
+  // Screen watchlist stocks for covered calls from their latest option chain snapshots
+  async getCoveredCallCandidates(
+    minAnnualizedPremiumYield?: number,
+    maxDaysToExpiry?: number,
+    minOpenInterest?: number
+  ): Promise<CoveredCallCandidate[]> {
+    return await invoke('get_covered_call_candidates', { minAnnualizedPremiumYield, maxDaysToExpiry, minOpenInterest });
   }
 };
 
@@ -52,9 +258,9 @@ export const analysisAPI = {
     return await invoke('get_stock_date_range', { symbol });
   },
 
-  // Get price history
-  async getPriceHistory(symbol: string, startDate: string, endDate: string): Promise<PriceData[]> {
-    return await invoke('get_price_history', { symbol, start_date: startDate, end_date: endDate });
+  // Get price history; fields selects the columns returned (default all)
+  async getPriceHistory(symbol: string, startDate: string, endDate: string, fields?: PriceFieldSet): Promise<PriceData[]> {
+    return await invoke('get_price_history', { symbol, start_date: startDate, end_date: endDate, fields });
   },
 
   // Get valuation ratios
@@ -67,11 +273,186 @@ export const analysisAPI = {
     return await invoke('get_ps_evs_history', { symbol, start_date: startDate, end_date: endDate });
   },
 
+  // Get the stored daily P/S, P/E and EV/S series
+  async getDailyValuationRatios(symbol: string, startDate: string, endDate: string): Promise<DailyValuationRatio[]> {
+    return await invoke('get_daily_valuation_ratios', { symbol, startDate, endDate });
+  },
+
+  // Rebuild the stored daily ratio series for a stock
+  async backfillDailyRatios(stockId: number): Promise<number> {
+    return await invoke('backfill_daily_ratios', { stockId });
+  },
+
   // Get valuation extremes (all-time high/low P/E and P/S ratios)
   async getValuationExtremes(symbol: string): Promise<any> {
     return await invoke('get_valuation_extremes', { symbol });
   },
 
+  // Get Sharpe, Sortino and beta over a trailing window; beta defaults to the equal-weight S&P 500 composite
+  async getRiskMetrics(stockId: number, periodDays?: number, riskFreeAnnual?: number, benchmark?: string): Promise<RiskMetrics> {
+    return await invoke('get_risk_metrics', { stockId, periodDays, riskFreeAnnual, benchmark });
+  },
+
+  // Get a stored benchmark series (SP500_EW, SP500_CW or an imported index), optionally within a date range
+  async getBenchmarkHistory(benchmark?: string, startDate?: string, endDate?: string): Promise<BenchmarkHistory> {
+    return await invoke('get_benchmark_history', { benchmark, startDate, endDate });
+  },
+
+  // Get a stock's cumulative return over a benchmark's, both 1.0 at the first common date
+  async getRelativePerformance(symbol: string, startDate: string, endDate: string, benchmark?: string): Promise<RelativePerformance> {
+    return await invoke('get_relative_performance', { symbol, startDate, endDate, benchmark });
+  },
+
+  // Get the largest peak-to-trough decline in closing price over a date range
+  async getMaxDrawdown(stockId: number, startDate: string, endDate: string): Promise<MaxDrawdown> {
+    return await invoke('get_max_drawdown', { stockId, startDate, endDate });
+  },
+
+  // Get max and current drawdown of a stock with each episode's recovery
+  async getDrawdownAnalysis(stockId: number, startDate: string, endDate: string): Promise<DrawdownAnalysis> {
+    return await invoke('get_drawdown_analysis', { stockId, startDate, endDate });
+  },
+
+  // Get drawdown analysis of a weighted buy-and-hold portfolio
+  async getPortfolioDrawdownAnalysis(holdings: PortfolioHolding[], startDate: string, endDate: string): Promise<DrawdownAnalysis> {
+    return await invoke('get_portfolio_drawdown_analysis', { holdings, startDate, endDate });
+  },
+
+  // Get price-only and dividend-reinvested return between two dates, annualized
+  async computeTotalReturn(symbol: string, startDate: string, endDate: string): Promise<TotalReturn> {
+    return await invoke('compute_total_return', { symbol, startDate, endDate });
+  },
+
+  // Get stocks ranked by 6-month and 12-1 month momentum, optionally combined with the value composite
+  async getMomentumRankings(criteria?: MomentumCriteria): Promise<MomentumRankings> {
+    return await invoke('get_momentum_rankings', { criteria });
+  },
+
+  // Get the trailing 52-week high/low and the latest close's distance from each
+  async get52WeekStats(symbol: string): Promise<Week52Range> {
+    return await invoke('get_52_week_stats', { symbol });
+  },
+
+  // Get stocks near their 52-week low, optionally only those with a minimum F-Score or positive free cash flow
+  async getStocksNear52WeekLow(maxDistancePct: number, minFScore?: number, requirePositiveFcf?: boolean, limit?: number): Promise<NearLowStock[]> {
+    return await invoke('get_stocks_near_52_week_low', { maxDistancePct, minFScore, requirePositiveFcf, limit });
+  },
+
+  // Get the detail page header figures (latest close, day change, valuation, 52-week range) in one call
+  async getStockSummary(symbol: string): Promise<StockSummary> {
+    return await invoke('get_stock_summary', { symbol });
+  },
+
+  // Get per-sector aggregates for the heatmap, or per-industry within one sector
+  async getSectorOverview(sector?: string): Promise<SectorOverview[]> {
+    return await invoke('get_sector_overview', { sector: sector ?? null });
+  },
+
+  // Get industries ranked by market-cap-weighted revenue CAGR, fastest first
+  async getIndustryGrowthRanking(years: number): Promise<IndustryGrowth[]> {
+    return await invoke('get_industry_growth_ranking', { years });
+  },
+
+  // Get consecutive revenue growth years and growth rate spread over the last N fiscal years
+  async getGrowthConsistency(symbol: string, years: number): Promise<GrowthConsistency> {
+    return await invoke('get_growth_consistency', { symbol, years });
+  },
+
+  // Get each stock's z-score of P/E, P/S or P/B within its sector, most undervalued first
+  async getSectorRelativeValuation(metric: ValuationMetric): Promise<SectorRelativeValuation[]> {
+    return await invoke('get_sector_relative_valuation', { metric });
+  },
+
+  // Get a histogram of P/E, P/S or P/B across the S&P 500, with the symbol's bin when given
+  async getMetricDistribution(metric: ValuationMetric, buckets: number, symbol?: string): Promise<MetricDistribution> {
+    return await invoke('get_metric_distribution', { metric, buckets, symbol });
+  },
+
+  // Get debt/equity, net debt, net debt/EBITDA and interest coverage with a five-year trend
+  async getLeverageMetrics(stockId: number): Promise<LeverageMetrics> {
+    return await invoke('get_leverage_metrics', { stockId });
+  },
+
+  // Get current and quick ratio per fiscal year; quick ratio is null where inventory is missing
+  async getLiquidityRatios(symbol: string): Promise<LiquidityRatios> {
+    return await invoke('get_liquidity_ratios', { symbol });
+  },
+
+  // Get S&P 500 stocks within the leverage ceilings, lowest debt/equity first
+  async getLowLeverageStocks(criteria?: LowLeverageCriteria): Promise<ScreenResults<LowLeverageStock>> {
+    return await invoke('get_low_leverage_stocks', { criteria: criteria ?? null });
+  },
+
+  // Get the highest FCF yields among stocks with positive free cash flow N fiscal years running
+  async getTopFcfYield(minPositiveYears?: number, minMarketCap?: number, limit?: number): Promise<ScreenResults<FcfYieldStock>> {
+    return await invoke('get_top_fcf_yield', { minPositiveYears, minMarketCap, limit });
+  },
+
+  // Get ROIC, margins and asset turnover by fiscal year, recomputed from the stored statements
+  async getQualityMetrics(stockId: number): Promise<QualityMetrics> {
+    return await invoke('get_quality_metrics', { stockId });
+  },
+
+  // Get S&P 500 stocks with high ROIC and steady margins over their last N fiscal years
+  async getQualityCompounders(criteria?: QualityCompounderCriteria): Promise<QualityCompounder[]> {
+    return await invoke('get_quality_compounders', { criteria });
+  },
+
+  // Get ROIC with its NOPAT, tax rate and invested capital inputs by fiscal year
+  async getRoic(symbol: string): Promise<RoicHistory> {
+    return await invoke('get_roic', { symbol });
+  },
+
+  // Get current P/E, P/S and P/B against their 5-year medians
+  async getValuationVsHistory(symbol: string): Promise<ValuationVsHistory> {
+    return await invoke('get_valuation_vs_history', { symbol });
+  },
+
+  // Get gross profit over total assets (Novy-Marx profitability) by fiscal year
+  async getGrossProfitability(symbol: string): Promise<GrossProfitabilityHistory> {
+    return await invoke('get_gross_profitability', { symbol });
+  },
+
+  // Get S&P 500 stocks ranked by their latest gross profitability
+  async getStocksByGrossProfitability(limit?: number): Promise<GrossProfitabilityRanking[]> {
+    return await invoke('get_stocks_by_gross_profitability', { limit });
+  },
+
+  // Get the accrual ratio (earnings not backed by operating cash flow) by fiscal year
+  async getAccruals(symbol: string): Promise<AccrualHistory> {
+    return await invoke('get_accruals', { symbol });
+  },
+
+  // Get price, valuation and Graham status for a whole watchlist in one call
+  async getWatchlistSnapshot(symbols: string[]): Promise<WatchlistSnapshotRow[]> {
+    return await invoke('get_watchlist_snapshot', { symbols });
+  },
+
+  // Get stored dividends with fiscal-year growth, payout ratios and special dividends flagged
+  async getDividendHistory(stockId: number): Promise<DividendHistory> {
+    return await invoke('get_dividend_history', { stockId });
+  },
+
+  // Get S&P 500 stocks that raised their regular dividend N fiscal years running
+  async getDividendGrowers(minYearsOfGrowth: number, minYield?: number, limit?: number): Promise<DividendGrower[]> {
+    return await invoke('get_dividend_growers', { minYearsOfGrowth, minYield, limit });
+  },
+
+  // Get trailing shareholder yield with its dividend and net buyback components
+  async getShareholderYield(symbol: string): Promise<ShareholderYield> {
+    return await invoke('get_shareholder_yield', { symbol });
+  },
+
+  // Get the inputs (price, shares, statement values and source filings) behind a ratio as of a date
+  async getRatioProvenance(stockId: number, ratio: ProvenanceRatio, date?: string): Promise<RatioProvenance> {
+    return await invoke('get_ratio_provenance', { stockId, ratio, date });
+  },
+
+  // Get annual statement line items pivoted by fiscal year with YoY deltas
+  async getFinancialStatements(stockId: number, statementType: FinancialStatementType, years?: number): Promise<FinancialStatementView> {
+    return await invoke('get_financial_statements', { stockId, statementType, years });
+  },
+
   // Export data
   async exportData(symbol: string, format: string): Promise<string> {
     return await invoke('export_data', { symbol, format });
@@ -82,7 +463,7 @@ export const analysisAPI = {
 export const recommendationsAPI = {
 
   // Get Piotroski F-Score screening results
-  async getPiotroskilScreeningResults(stockTickers: string[], criteria?: any, limit?: number): Promise<any[]> {
+  async getPiotroskilScreeningResults(stockTickers: string[], criteria?: any, limit?: number, overrideReadiness?: boolean, persist?: boolean): Promise<ScreenResults<any>> {
     return await invoke('get_piotroski_screening_results', {
       stockTickers,
       criteria: criteria || {
@@ -90,7 +471,9 @@ export const recommendationsAPI = {
         minDataCompleteness: 80,
         passesScreeningOnly: true
       },
-      limit: limit || 10
+      limit: limit || 10,
+      overrideReadiness,
+      persist
     });
   },
 
@@ -99,8 +482,13 @@ export const recommendationsAPI = {
     return await invoke('get_piotroski_statistics');
   },
 
+  // Get the F-Score breakdown per fiscal year that has a prior year to compare against
+  async getPiotroskiHistory(symbol: string): Promise<PiotroskiHistory> {
+    return await invoke('get_piotroski_history', { symbol });
+  },
+
   // Get O'Shaughnessy Value Composite screening results
-  async getOShaughnessyScreeningResults(stockTickers: string[], criteria?: any, limit?: number): Promise<any[]> {
+  async getOShaughnessyScreeningResults(stockTickers: string[], criteria?: any, limit?: number, overrideReadiness?: boolean, persist?: boolean): Promise<ScreenResults<any>> {
     return await invoke('get_oshaughnessy_screening_results', {
       stockTickers,
       criteria: criteria || {
@@ -109,7 +497,9 @@ export const recommendationsAPI = {
         maxEvsRatio: 2.0,
         passesScreeningOnly: false
       },
-      limit: limit || 50
+      limit: limit || 50,
+      overrideReadiness,
+      persist
     });
   },
 
@@ -118,6 +508,92 @@ export const recommendationsAPI = {
     return await invoke('get_oshaughnessy_statistics');
   },
 
+  // Get stocks trading below their own historical P/S range, with the stocks excluded and why
+  async getUndervaluedStocksByPs(stockTickers: string[], limit?: number, minMarketCap?: number, sortBy?: PsSortKey, minAvgDailyVolume?: number, overrideReadiness?: boolean): Promise<ScreenResults<any>> {
+    return await invoke('get_undervalued_stocks_by_ps', { stockTickers, limit, minMarketCap, sortBy, minAvgDailyVolume, overrideReadiness });
+  },
+
+  // Get low P/S stocks that also grew revenue, with the stocks excluded and why
+  async getPsScreeningWithRevenueGrowth(stockTickers: string[], limit?: number, minMarketCap?: number, minAvgDailyVolume?: number, overrideReadiness?: boolean): Promise<ScreenResults<any>> {
+    return await invoke('get_ps_screening_with_revenue_growth', { stockTickers, limit, minMarketCap, minAvgDailyVolume, overrideReadiness });
+  },
+
+  // Get the S&P 500 ranked by value composite percentile, cheapest first
+  async getValueCompositeRanking(limit?: number): Promise<OShaughnessyValueResult[]> {
+    return await invoke('get_value_composite_ranking', { limit });
+  },
+
+  // Get the Graham thresholds applied when no criteria are given
+  async getGrahamCriteriaDefaults(): Promise<GrahamScreeningCriteria> {
+    return await invoke('get_graham_criteria_defaults');
+  },
+
+  // Run the Graham value screen, optionally with tuned thresholds, with the stocks it excluded and why
+  async runGrahamScreening(criteria?: GrahamScreeningCriteria, limit?: number, overrideReadiness?: boolean): Promise<GrahamScreeningResponse> {
+    return await invoke('run_graham_screening', { criteria: criteria ?? null, limit, overrideReadiness });
+  },
+
+  // Run an ad-hoc screen: filters are ANDed over whitelisted metrics
+  async runCustomScreen(filters: ScreenFilter[], sort?: SortSpec, limit?: number, persist?: boolean, includeNegativeEarnings?: boolean, overrideReadiness?: boolean): Promise<CustomScreenResponse> {
+    return await invoke('run_custom_screen', { filters, sort: sort ?? null, limit, persist, includeNegativeEarnings, overrideReadiness });
+  },
+
+  // Get saved custom screens
+  async listCustomScreens(): Promise<CustomScreen[]> {
+    return await invoke('list_custom_screens');
+  },
+
+  // Save a custom screen (replacing one with the same name); returns all saved screens
+  async saveCustomScreen(screen: CustomScreen): Promise<CustomScreen[]> {
+    return await invoke('save_custom_screen', { screen });
+  },
+
+  // Delete a saved custom screen; returns the remaining screens
+  async deleteCustomScreen(name: string): Promise<CustomScreen[]> {
+    return await invoke('delete_custom_screen', { name });
+  },
+
+  // Save screener criteria under a name (replacing one with the same name); returns that screen's presets
+  async saveScreeningPreset(screenType: ScreenType, name: string, criteria: Record<string, unknown>): Promise<ScreeningPreset[]> {
+    return await invoke('save_screening_preset', { screenType, name, criteria });
+  },
+
+  // Get a screen's saved presets, flagging any saved under older criteria fields
+  async getScreeningPresets(screenType: ScreenType): Promise<ScreeningPreset[]> {
+    return await invoke('get_screening_presets', { screenType });
+  },
+
+  // Delete a saved preset; returns that screen's remaining presets
+  async deleteScreeningPreset(screenType: ScreenType, name: string): Promise<ScreeningPreset[]> {
+    return await invoke('delete_screening_preset', { screenType, name });
+  },
+
+  // Get pass/fail/unknown per screener for one stock under default criteria
+  async getScreenMembership(symbol: string): Promise<Record<string, ScreenMembership>> {
+    return await invoke('get_screen_membership', { symbol });
+  },
+
+  // Get a screen's persisted runs, newest first
+  async getScreenSnapshots(screenType: ScreenType): Promise<ScreenSnapshot[]> {
+    return await invoke('get_screen_snapshots', { screenType });
+  },
+
+  // Compare two runs of a screen: stocks that entered, left, or changed rank from a to b
+  async diffScreenSnapshots(a: number, b: number): Promise<SnapshotDiff> {
+    return await invoke('diff_screen_snapshots', { a, b });
+  },
+
+  // Delete runs older than the retention (default 365 days); returns the number removed
+  async pruneScreenSnapshots(retentionDays?: number): Promise<number> {
+    return await invoke('prune_screen_snapshots', { retentionDays });
+  },
+
+  // Keep every run from the last keepAllDays days (default from settings), then one per screen and week;
+  // dryRun only counts what would go
+  async pruneScreeningHistory(keepAllDays?: number, dryRun?: boolean): Promise<ScreeningHistoryPruneReport> {
+    return await invoke('prune_screening_history', { keepAllDays, dryRun });
+  },
+
 };
 
 // Note: Enhanced Data API removed - these commands don't exist in the backend
@@ -137,9 +613,19 @@ export const dataRefreshAPI = {
     }
   },
 
-  // Check if specific screening features are ready
-  async checkScreeningReadiness(feature: string): Promise<boolean> {
-    return await invoke('check_screening_readiness', { feature });
+  // Check which screening features have enough data to run
+  async checkScreeningReadiness(): Promise<ScreeningReadiness> {
+    return await invoke('check_screening_readiness');
+  },
+
+  // Get S&P 500 stocks behind on filings or prices, with the reasons
+  async getStaleStocks(stalenessDays?: number): Promise<StaleStock[]> {
+    return await invoke('get_stale_stocks', { stalenessDays });
+  },
+
+  // Get daily price rows that conflict with themselves or with market cap and valuation data
+  async findConflictingPrices(): Promise<PriceConflict[]> {
+    return await invoke('find_conflicting_prices');
   },
 
   // Start data refresh operation
@@ -152,6 +638,11 @@ export const dataRefreshAPI = {
     return await invoke('get_refresh_progress', { sessionId });
   },
 
+  // Subscribe to refresh progress pushed by the backend; call the returned function to stop
+  async onRefreshProgress(handler: (progress: RefreshProgressEvent) => void): Promise<UnlistenFn> {
+    return await listen<RefreshProgressEvent>('refresh-progress', (event) => handler(event.payload));
+  },
+
   // Get last refresh result
   async getLastRefreshResult(): Promise<RefreshResult | null> {
     return await invoke('get_last_refresh_result');
@@ -165,6 +656,56 @@ export const dataRefreshAPI = {
   // Get refresh duration estimates
   async getRefreshDurationEstimates(): Promise<RefreshDurationEstimates> {
     return await invoke('get_refresh_duration_estimates');
+  },
+
+  // Get past refresh runs (newest first)
+  async getRefreshHistory(limit?: number): Promise<RefreshRunSummary[]> {
+    return await invoke('get_refresh_history', { limit });
+  },
+
+  // Get one refresh run with its per-stock errors
+  async getRefreshRunDetails(runId: string): Promise<RefreshRunDetails> {
+    return await invoke('get_refresh_run_details', { runId });
+  },
+
+  // Fill missing first trading dates from stored prices; returns stocks updated
+  async backfillFirstTradingDates(): Promise<number> {
+    return await invoke('backfill_first_trading_dates');
+  },
+
+  // Recompute stored ratios for the S&P 500; poll progress or cancel with the same session id
+  async recomputeAllRatios(sessionId?: string): Promise<RatioRecomputeProgress> {
+    return await invoke('recompute_all_ratios', { sessionId });
+  },
+
+  // Get a ratio recompute session's progress
+  async getRatioRecomputeProgress(sessionId: string): Promise<RatioRecomputeProgress | null> {
+    return await invoke('get_ratio_recompute_progress', { sessionId });
+  },
+
+  // Stop a running ratio recompute after its current chunk
+  async cancelRatioRecompute(sessionId: string): Promise<boolean> {
+    return await invoke('cancel_ratio_recompute', { sessionId });
+  },
+
+  // Fetch today's USD exchange rates; returns the number of currencies stored
+  async updateFxRates(): Promise<number> {
+    return await invoke('update_fx_rates');
+  },
+
+  // Get per-stock price coverage flags by year or quarter, with per-period totals
+  async getCoverageHeatmap(targetDate: string, granularity?: CoverageGranularity): Promise<CoverageHeatmap> {
+    return await invoke('get_coverage_heatmap', { targetDate, granularity });
+  },
+
+  // Extract SEC fundamentals for any set of stocks: all, the S&P 500, a symbol list or a tag
+  async refreshFundamentalsFor(filter: StockFilter): Promise<FundamentalsRefreshResult> {
+    return await invoke('refresh_fundamentals_for', { filter });
+  },
+
+  // Get recent backend log entries (newest first) for the activity console
+  async getRecentLogs(level?: string, limit?: number): Promise<LogEntry[]> {
+    return await invoke('get_recent_logs', { level, limit });
   }
 };
 
@@ -175,17 +716,132 @@ export const systemAPI = {
     return await invoke('get_initialization_status');
   },
 
+  // Get onboarding checklist (row counts and required next actions)
+  async getInitializationReport(): Promise<InitializationReport> {
+    return await invoke('get_initialization_report');
+  },
+
+  // Get user settings (freshness thresholds, notification preferences)
+  async getSettings(): Promise<AppSettings> {
+    return await invoke('get_settings');
+  },
+
+  // Save user settings; returns the stored settings
+  async setSettings(settings: AppSettings): Promise<AppSettings> {
+    return await invoke('set_settings', { settings });
+  },
+
+  // Get the automatic refresh schedule
+  async getRefreshSchedule(): Promise<RefreshSchedule> {
+    return await invoke('get_refresh_schedule');
+  },
+
+  // Save the automatic refresh schedule; returns the stored schedule
+  async setRefreshSchedule(schedule: RefreshSchedule): Promise<RefreshSchedule> {
+    return await invoke('set_refresh_schedule', { schedule });
+  },
+
+  // Pause or resume automatic refreshes
+  async pauseScheduledRefresh(paused: boolean): Promise<RefreshSchedule> {
+    return await invoke('pause_scheduled_refresh', { paused });
+  },
+
+  // Start the local HTTP API (and keep it on at launch)
+  async startHttpApi(): Promise<HttpApiStatus> {
+    return await invoke('start_http_api');
+  },
+
+  // Stop the local HTTP API (and keep it off at launch)
+  async stopHttpApi(): Promise<HttpApiStatus> {
+    return await invoke('stop_http_api');
+  },
+
+  // Whether the local HTTP API is running, and its base URL
+  async getHttpApiStatus(): Promise<HttpApiStatus> {
+    return await invoke('get_http_api_status');
+  },
+
+  // Integrity check, ANALYZE and optional VACUUM with per-table rows and size; refused during a refresh.
+  // VACUUM can take minutes and needs free disk space about the size of the database.
+  async runDatabaseMaintenance(vacuum?: boolean): Promise<DatabaseMaintenanceReport> {
+    return await invoke('run_database_maintenance', { vacuum });
+  },
+
+  // Subscribe to notifications the OS couldn't show, to display as in-app toasts
+  async onNotificationToast(handler: (notification: Notification) => void): Promise<UnlistenFn> {
+    return await listen<Notification>('notification-toast', (event) => handler(event.payload));
+  },
+
+  // Compare stored 10-K filing dates for a stock against SEC
+  async diffFilingsVsSec(symbol: string): Promise<FilingDiff> {
+    return await invoke('diff_filings_vs_sec', { symbol });
+  },
+
+  // Stocks that received new filings or statements since the given time (ISO 8601)
+  async getRecentlyUpdatedFundamentals(since: string): Promise<UpdatedFundamentals[]> {
+    return await invoke('get_recently_updated_fundamentals', { since });
+  },
+
+  // Check Assets = Liabilities + Equity per fiscal year; years off by more than the tolerance (default 1%) are flagged
+  async checkBalanceSheetIdentity(symbol: string, tolerancePct?: number): Promise<BalanceSheetIdentityCheck[]> {
+    return await invoke('check_balance_sheet_identity', { symbol, tolerancePct });
+  },
+
+  // Get share count moves over 10% that no recorded split explains
+  async getShareCountDiscontinuities(stockId: number): Promise<ShareCountDiscontinuity[]> {
+    return await invoke('get_share_count_discontinuities', { stockId });
+  },
+
+  // Record a stock split (new shares per old share); returns the moves still unexplained
+  async recordStockSplit(stockId: number, exDate: string, ratio: number): Promise<ShareCountDiscontinuity[]> {
+    return await invoke('record_stock_split', { stockId, exDate, ratio });
+  },
+
+  // Get a stock's filings with the price on the filing date and the 5- and 30-day returns after
+  async getFilingReactions(stockId: number): Promise<FilingReaction[]> {
+    return await invoke('get_filing_reactions', { stockId });
+  },
+
+  // Price new filings and fill in returns waiting on later prices; returns the reactions written
+  async backfillFilingReactions(stockId?: number): Promise<number> {
+    return await invoke('backfill_filing_reactions', { stockId });
+  },
+
+  // Diagnostic: fetch a stock's raw SEC companyfacts JSON, cut at maxBytes (5 MB by default)
+  async fetchRawCompanyFacts(symbol: string, pretty?: boolean, maxBytes?: number): Promise<RawCompanyFacts> {
+    return await invoke('fetch_raw_company_facts', { symbol, pretty, maxBytes });
+  },
+
+  // Wipe all data and recreate the schema; confirmation must be the database file name
+  async resetDatabase(confirmation: string, autoBackup = true): Promise<string> {
+    return await invoke('reset_database', { confirmation, autoBackup });
+  },
+
   // Get database stats
   async getDatabaseStats(): Promise<DatabaseStats> {
     return await invoke('get_database_stats');
   },
 
+  // Get query cache hit/miss/invalidation counts (debugging)
+  async getCacheStats(): Promise<CacheStats> {
+    return await invoke('get_cache_stats');
+  },
+
   // Note: get_available_stock_symbols and get_database_migration_status removed - not registered in Tauri
 };
 
 // Centralized error handling
 export const handleAPIError = (error: any, context = 'API call') => {
   console.error(`Error in ${context}:`, error);
+  // Commands reject with a CommandError object: { code, message, ... }
+  if (error && typeof error === 'object' && 'code' in error) {
+    return {
+      success: false,
+      code: error.code,
+      error: error.message,
+      message: error.message
+    };
+  }
   return {
     success: false,
     error: error.toString(),
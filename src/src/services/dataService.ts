@@ -162,7 +162,7 @@ export const recommendationsDataService = {
     );
 
     return {
-      stocks: result.success ? result.data : [],
+      stocks: result.success ? result.data.results : [],
       error: result.success ? null : result.error
     };
   },
@@ -176,7 +176,7 @@ export const recommendationsDataService = {
 
     return {
       success: result.success,
-      stocks: result.success ? result.data : [],
+      stocks: result.success ? result.data.results : [],
       error: result.success ? null : result.error
     };
   },
@@ -162,8 +162,9 @@ export function createRecommendationsStore() {
       }
       
       // Transform data for all screening types
-      if (result && result.length > 0) {
-        const transformedRecommendations = result.map((stock: any, index: number) => {
+      const rows = result ? result.results : [];
+      if (rows.length > 0) {
+        const transformedRecommendations = rows.map((stock: any, index: number) => {
           if (currentScreeningType === 'piotroski') {
             // Piotroski F-Score transformation
             return {
@@ -172,7 +173,7 @@ export function createRecommendationsStore() {
               company_name: stock.symbol,
               current_pe_ratio: null,
               current_price: null,
-              market_cap: null,
+              market_cap: stock.market_cap,
               garp_score: stock.f_score_complete, // Fixed: use correct field name
               quality_score: stock.data_completeness_score,
               passes_garp_screening: stock.passes_screening === 1,
@@ -222,7 +223,7 @@ export function createRecommendationsStore() {
               garp_score: 100 - stock.composite_percentile, // Invert percentile for scoring
               quality_score: stock.data_completeness_score,
               passes_garp_screening: stock.passes_screening === 1,
-              reasoning: `Value Rank: ${stock.overall_rank} (${stock.composite_percentile}th percentile) | Metrics: ${stock.metrics_available}/6 | P/S: ${stock.ps_ratio?.toFixed(2) || 'N/A'} | P/E: ${stock.pe_ratio?.toFixed(2) || 'N/A'} | P/B: ${stock.pb_ratio?.toFixed(2) || 'N/A'} | EV/EBITDA: ${stock.ev_ebitda_ratio?.toFixed(2) || 'N/A'} | EV/S: ${stock.evs_ratio?.toFixed(2) || 'N/A'} | Yield: ${stock.shareholder_yield?.toFixed(1) || 'N/A'}%`
+              reasoning: `Value Rank: ${stock.overall_rank} (${stock.composite_percentile}th percentile) | Metrics: ${stock.metrics_available}/5 | P/S: ${stock.ps_ratio?.toFixed(2) || 'N/A'} | P/E: ${stock.pe_ratio?.toFixed(2) || 'N/A'} | P/B: ${stock.pb_ratio?.toFixed(2) || 'N/A'} | EV/EBITDA: ${stock.ev_ebitda_ratio?.toFixed(2) || 'N/A'} | EV/S: ${stock.evs_ratio?.toFixed(2) || 'N/A'} | Yield: ${stock.shareholder_yield?.toFixed(1) || 'N/A'}%`
             };
           }
         });
@@ -12,9 +12,9 @@ export interface Stock {
 // Analysis related types
 export interface PriceData {
   date: string;
-  open_price: number;
-  high_price: number;
-  low_price: number;
+  open_price?: number;
+  high_price?: number;
+  low_price?: number;
   close_price: number;
   volume?: number;
   pe_ratio?: number;
@@ -131,10 +131,4 @@ export interface RefreshResult {
   total_records_processed: number;
   error_message?: string;
   duration_minutes: number;
-}
-
-export interface RefreshDurationEstimates {
-  market: number;      // minutes
-  financials: number;  // minutes
-  ratios: number;      // minutes
 }
\ No newline at end of file